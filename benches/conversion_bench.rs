@@ -54,7 +54,7 @@ fn bench_audio_format_parsing(c: &mut Criterion) {
         b.iter(|| {
             for input in &test_inputs {
                 let result = AudioFormat::from_user_input(input);
-                black_box(result);
+                let _ = black_box(result);
             }
         });
     });
@@ -83,7 +83,7 @@ fn bench_directory_operations(c: &mut Criterion) {
             || TempDir::new().unwrap(),
             |temp_dir| {
                 let result = processor.create_output_directory(temp_dir.path());
-                black_box(result);
+                let _ = black_box(result);
             },
             criterion::BatchSize::SmallInput,
         );
@@ -211,7 +211,6 @@ fn bench_concurrency(c: &mut Criterion) {
     let temp_dir = create_test_files(100);
     let processor = FileProcessor::new();
     let files = processor.find_video_files(temp_dir.path()).unwrap();
-    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
     
     group.bench_function("parallel_iteration", |b| {
         b.iter(|| {
@@ -8,7 +8,7 @@
 //! cargo run --example basic_usage
 //! ```
 
-use video2audio_rs::{AudioFormat, FileProcessor, UserInterface, VideoToAudioError};
+use video2audio_rs::{AudioFormat, BatchSummary, FileProcessor, UserInterface, VideoToAudioError};
 
 fn main() -> Result<(), VideoToAudioError> {
     println!("=== Video2Audio-RS 基本使用示例 ===\n");
@@ -113,7 +113,7 @@ fn user_interface_example() {
     
     // 模拟完成信息
     let temp_dir = std::env::temp_dir().join("audio_exports");
-    ui.show_completion(10, &temp_dir);
+    ui.show_completion(&BatchSummary::new(10), &temp_dir);
     
     println!();
 }
@@ -125,7 +125,7 @@ fn error_handling_example() {
     let ui = UserInterface::new();
     
     // 演示不同类型的错误
-    let errors = vec![
+    let errors = [
         VideoToAudioError::InvalidPath("/nonexistent/path".to_string()),
         VideoToAudioError::InvalidInput("invalid_choice".to_string()),
         VideoToAudioError::FfmpegError("编码失败".to_string()),
@@ -173,7 +173,7 @@ fn complete_workflow_example() -> Result<(), VideoToAudioError> {
         println!(); // 换行
         
         println!("5. 显示完成信息...");
-        ui.show_completion(total_files, &output_dir);
+        ui.show_completion(&BatchSummary::new(total_files), &output_dir);
     }
     
     // 清理临时文件
@@ -189,7 +189,7 @@ fn create_demo_environment() -> Result<std::path::PathBuf, VideoToAudioError> {
     
     // 创建目录
     std::fs::create_dir_all(&temp_dir)
-        .map_err(|e| VideoToAudioError::Io(e))?;
+        .map_err(VideoToAudioError::from)?;
     
     // 创建一些模拟的视频文件
     let demo_files = vec![
@@ -203,7 +203,7 @@ fn create_demo_environment() -> Result<std::path::PathBuf, VideoToAudioError> {
     for file_name in demo_files {
         let file_path = temp_dir.join(file_name);
         std::fs::write(&file_path, "这是一个模拟的视频文件内容")
-            .map_err(|e| VideoToAudioError::Io(e))?;
+            .map_err(VideoToAudioError::from)?;
     }
     
     // 创建一些非视频文件（应该被忽略）
@@ -216,17 +216,17 @@ fn create_demo_environment() -> Result<std::path::PathBuf, VideoToAudioError> {
     for file_name in non_video_files {
         let file_path = temp_dir.join(file_name);
         std::fs::write(&file_path, "其他类型的文件内容")
-            .map_err(|e| VideoToAudioError::Io(e))?;
+            .map_err(VideoToAudioError::from)?;
     }
     
     // 创建子目录和嵌套文件
     let sub_dir = temp_dir.join("subfolder");
     std::fs::create_dir_all(&sub_dir)
-        .map_err(|e| VideoToAudioError::Io(e))?;
+        .map_err(VideoToAudioError::from)?;
     
     let nested_file = sub_dir.join("nested_video.mp4");
     std::fs::write(&nested_file, "嵌套的视频文件")
-        .map_err(|e| VideoToAudioError::Io(e))?;
+        .map_err(VideoToAudioError::from)?;
     
     Ok(temp_dir)
 }
@@ -255,6 +255,7 @@ fn configuration_example() {
             AudioFormat::Mp3 => "高兼容性",
             AudioFormat::AacCopy => "最快速度",
             AudioFormat::Opus => "最小体积",
+            AudioFormat::M4b => "有声书",
         };
         
         println!(
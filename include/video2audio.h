@@ -0,0 +1,64 @@
+/*
+ * video2audio-rs C 接口
+ *
+ * 构建动态库:
+ *     cargo rustc --release --lib --features ffi --crate-type cdylib
+ *
+ * 所有路径均为 UTF-8 编码的 C 字符串。函数返回 V2A_OK 表示成功，
+ * 失败时可通过 v2a_last_error() 获取当前线程最近一次的错误信息。
+ */
+
+#ifndef VIDEO2AUDIO_H
+#define VIDEO2AUDIO_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* 状态码 */
+#define V2A_OK 0
+#define V2A_ERR_IO 1
+#define V2A_ERR_FFMPEG 2
+#define V2A_ERR_INVALID_ARGUMENT 3
+#define V2A_ERR_UNSUPPORTED_FORMAT 4
+#define V2A_ERR_MISSING_DEPENDENCY 5
+#define V2A_ERR_CANCELLED 6
+
+/* 音频格式 */
+#define V2A_FORMAT_MP3 0
+#define V2A_FORMAT_AAC 1
+#define V2A_FORMAT_OPUS 2
+#define V2A_FORMAT_M4B 3
+
+/* 批量转换进度回调: (当前进度, 总数, 用户数据)，在调用线程上串行执行 */
+typedef void (*v2a_progress_callback)(size_t current, size_t total, void *user_data);
+
+/* 当前线程最近一次的错误信息，没有错误时为 NULL，调用方不得释放 */
+const char *v2a_last_error(void);
+
+/* 查找目录中的视频文件，结果需用 v2a_string_array_free 释放 */
+int v2a_find_video_files(const char *source_dir, char ***out_files, size_t *out_count);
+
+/* 转换单个视频文件，out_path 可为 NULL，非 NULL 时结果需用 v2a_string_free 释放 */
+int v2a_convert_single_file(const char *source_file, const char *output_dir, int format, char **out_path);
+
+/* 批量并行转换，callback、user_data、out_success、out_failure 均可为 NULL */
+int v2a_batch_convert(const char *const *files,
+                      size_t count,
+                      const char *output_dir,
+                      int format,
+                      v2a_progress_callback callback,
+                      void *user_data,
+                      size_t *out_success,
+                      size_t *out_failure);
+
+void v2a_string_free(char *value);
+void v2a_string_array_free(char **values, size_t count);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* VIDEO2AUDIO_H */
@@ -0,0 +1,321 @@
+//! # 异步转换模块
+//!
+//! 基于 `tokio::process` 的异步转换接口，仅在启用 `async` 特性时编译。
+//! 适合嵌入异步服务：等待 FFmpeg 时不会占用运行时线程，也不依赖 Rayon 全局线程池。
+//!
+//! ## 使用示例
+//!
+//! ```rust,no_run
+//! use video2audio_rs::{AudioFormat, CancellationToken, ConversionEvent, FileProcessor};
+//! use std::path::PathBuf;
+//!
+//! # async fn example() {
+//! let processor = FileProcessor::new();
+//! let files = processor.find_video_files("videos".as_ref()).unwrap();
+//! let mut events = processor.batch_convert_stream(
+//!     files,
+//!     PathBuf::from("audio"),
+//!     AudioFormat::Mp3,
+//!     CancellationToken::new(),
+//! );
+//!
+//! while let Some(event) = events.recv().await {
+//!     if let ConversionEvent::FileFailed { source, error, .. } = event {
+//!         eprintln!("{}: {error}", source.display());
+//!     }
+//! }
+//! # }
+//! ```
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::event::ConversionEvent;
+use crate::file_processor::FileProcessor;
+use crate::i18n::text;
+use crate::options::ConversionOptions;
+use crate::tr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::{mpsc, Semaphore};
+use tokio::task::JoinSet;
+
+/// 等待 FFmpeg 进程时检查取消请求的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl FileProcessor {
+    /// 异步转换单个视频文件为音频
+    ///
+    /// 与 [`convert_single_file`](Self::convert_single_file) 行为相同，
+    /// 但通过 `tokio::process` 等待 FFmpeg，不会阻塞运行时线程。
+    ///
+    /// # 错误
+    ///
+    /// 当转换失败时返回相应的错误信息
+    pub async fn convert_single_file_async(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+    ) -> Result<PathBuf> {
+        self.convert_single_file_async_with_cancel(source_file, output_dir, options, &CancellationToken::new())
+            .await
+    }
+
+    /// 可取消的异步单文件转换
+    ///
+    /// 令牌被取消时终止 FFmpeg 进程、删除未完成的输出并返回
+    /// [`VideoToAudioError::Cancelled`]。
+    pub async fn convert_single_file_async_with_cancel(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        let options = options.into();
+        let (output_path, skip) = self.resolve_output(source_file, output_dir, &options)?;
+        if skip {
+            return Ok(output_path);
+        }
+
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        // 解析嵌入的封面和双遍响度标准化的分析在阻塞线程中进行
+        let options = {
+            let processor = self.clone();
+            let source_file = source_file.to_path_buf();
+            let cancel_token = cancel_token.clone();
+            tokio::task::spawn_blocking(move || processor.prepare_options(&source_file, &options, &cancel_token))
+                .await
+                .map_err(|e| VideoToAudioError::from(std::io::Error::other(e)))??
+        };
+
+        let partial_path = Self::partial_output_path(&output_path);
+        let args = self.ffmpeg_args(source_file, &partial_path, &options);
+        let started_at = Instant::now();
+
+        let mut child = Command::from(self.conversion_command())
+            .args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => VideoToAudioError::MissingDependency(
+                    text(
+                        "FFmpeg 未安装或不在系统 PATH 中。请安装 FFmpeg 后重试。",
+                        "FFmpeg is not installed or not in the system PATH. Install FFmpeg and try again.",
+                    ).to_string()
+                ),
+                _ => VideoToAudioError::io(text("启动 FFmpeg", "start FFmpeg"), self.ffmpeg_path(), e),
+            })?;
+
+        // 并发读取错误输出，避免管道写满导致进程阻塞
+        let stderr_pipe = child.stderr.take();
+        let stderr_reader = tokio::spawn(async move {
+            let mut stderr = Vec::new();
+            if let Some(mut pipe) = stderr_pipe {
+                let _ = pipe.read_to_end(&mut stderr).await;
+            }
+            stderr
+        });
+
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break status?,
+                _ = tokio::time::sleep(CANCEL_POLL_INTERVAL) => {
+                    let timed_out = options.timeout.is_some_and(|timeout| started_at.elapsed() >= timeout);
+                    if cancel_token.is_cancelled() || timed_out {
+                        let _ = child.kill().await;
+                        let _ = fs::remove_file(&partial_path);
+                        return Err(match options.timeout {
+                            Some(timeout) if timed_out => Self::timeout_error(timeout),
+                            _ => VideoToAudioError::Cancelled,
+                        });
+                    }
+                }
+            }
+        };
+
+        let stderr = stderr_reader.await.unwrap_or_default();
+
+        if !status.success() {
+            let _ = fs::remove_file(&partial_path);
+            let stderr = String::from_utf8_lossy(&stderr);
+            return Err(VideoToAudioError::from_ffmpeg_stderr(&stderr, tr!(
+                "转换失败: {stderr}",
+                "Conversion failed: {stderr}",
+            )));
+        }
+
+        tokio::fs::rename(&partial_path, &output_path).await.map_err(|e| {
+            let _ = fs::remove_file(&partial_path);
+            VideoToAudioError::io(text("写入输出文件", "write output file"), &output_path, e)
+        })?;
+
+        // 增益标签的分析和写入同样在阻塞线程中进行
+        let processor = self.clone();
+        let cancel_token = cancel_token.clone();
+        tokio::task::spawn_blocking(move || {
+            processor.apply_replay_gain(&output_path, &options, &cancel_token)?;
+            Ok(output_path)
+        })
+        .await
+        .map_err(|e| VideoToAudioError::from(std::io::Error::other(e)))?
+    }
+
+    /// 异步批量转换，以事件流的形式报告进度
+    ///
+    /// 在当前 tokio 运行时中启动转换任务并立即返回事件接收端，
+    /// 同时运行的 FFmpeg 进程数不超过 CPU 核心数。
+    /// 接收端被丢弃后剩余的文件仍会继续转换；如需停止请取消 `cancel_token`。
+    /// 该接口不处理重试和状态日志。
+    ///
+    /// # 参数
+    ///
+    /// * `files` - 要转换的视频文件路径列表
+    /// * `output_dir` - 输出目录路径
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`](crate::AudioFormat)
+    /// * `cancel_token` - 取消令牌
+    ///
+    /// # 注意
+    ///
+    /// 必须在 tokio 运行时中调用
+    pub fn batch_convert_stream(
+        &self,
+        files: Vec<PathBuf>,
+        output_dir: PathBuf,
+        options: impl Into<ConversionOptions>,
+        cancel_token: CancellationToken,
+    ) -> mpsc::UnboundedReceiver<ConversionEvent> {
+        let options = Arc::new(options.into());
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let processor = Arc::new(self.clone());
+        let concurrency = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+        tokio::spawn(async move {
+            let total = files.len();
+            // 接收端被丢弃时发送失败，忽略即可
+            let _ = sender.send(ConversionEvent::Started { total });
+
+            let permits = Arc::new(Semaphore::new(concurrency));
+            let mut tasks = JoinSet::new();
+            for source in files {
+                let processor = Arc::clone(&processor);
+                let permits = Arc::clone(&permits);
+                let output_dir = output_dir.clone();
+                let options = Arc::clone(&options);
+                let cancel_token = cancel_token.clone();
+                let sender = sender.clone();
+
+                tasks.spawn(async move {
+                    let _permit = permits.acquire_owned().await.ok()?;
+                    if cancel_token.is_stopped() {
+                        return None;
+                    }
+                    let _ = sender.send(ConversionEvent::FileStarted { source: source.clone() });
+                    let result = processor
+                        .convert_single_file_async_with_cancel(&source, &output_dir, options.as_ref().clone(), &cancel_token)
+                        .await;
+                    Some((source, result))
+                });
+            }
+
+            let (mut succeeded, mut failed, mut completed) = (0, 0, 0);
+            while let Some(joined) = tasks.join_next().await {
+                let event = match joined {
+                    Ok(Some((_, Err(VideoToAudioError::Cancelled)))) | Ok(None) => continue,
+                    Ok(Some((source, Ok(output)))) => {
+                        succeeded += 1;
+                        completed += 1;
+                        ConversionEvent::FileCompleted { source, output, completed, total }
+                    }
+                    Ok(Some((source, Err(e)))) => {
+                        failed += 1;
+                        completed += 1;
+                        ConversionEvent::FileFailed { source, error: e.to_string(), completed, total }
+                    }
+                    // 任务崩溃时无法得知对应的文件，只计入失败数
+                    Err(_) => {
+                        failed += 1;
+                        completed += 1;
+                        continue;
+                    }
+                };
+                let _ = sender.send(event);
+            }
+
+            let _ = sender.send(ConversionEvent::Finished {
+                succeeded,
+                failed,
+                cancelled: cancel_token.is_cancelled(),
+            });
+        });
+
+        receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_format::AudioFormat;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_convert_missing_file_async() {
+        let processor = FileProcessor::new();
+        let result = processor
+            .convert_single_file_async(Path::new("/nonexistent/video.mp4"), Path::new("/tmp"), AudioFormat::Mp3)
+            .await;
+
+        match result {
+            Err(VideoToAudioError::InvalidPath(_)) => (),
+            other => panic!("应该返回 InvalidPath 错误: {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_batch_convert_stream_reports_every_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<PathBuf> = ["a.mp4", "b.mkv"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, "fake").unwrap();
+                path
+            })
+            .collect();
+
+        let mut events = FileProcessor::new().batch_convert_stream(
+            files,
+            temp_dir.path().join("out"),
+            AudioFormat::Mp3,
+            CancellationToken::new(),
+        );
+
+        let mut received = Vec::new();
+        while let Some(event) = events.recv().await {
+            received.push(event);
+        }
+
+        assert_eq!(received.first(), Some(&ConversionEvent::Started { total: 2 }));
+        let failures = received
+            .iter()
+            .filter(|event| matches!(event, ConversionEvent::FileFailed { .. }))
+            .count();
+        assert_eq!(failures, 2);
+        assert_eq!(
+            received.last(),
+            Some(&ConversionEvent::Finished { succeeded: 0, failed: 2, cancelled: false })
+        );
+    }
+}
@@ -4,6 +4,8 @@
 //! 每种格式都针对不同的使用场景进行了优化。
 
 use crate::error::{Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::tr;
 
 /// 支持的音频格式枚举
 /// 
@@ -11,7 +13,8 @@ use crate::error::{Result, VideoToAudioError};
 /// - MP3: 最广泛兼容，适合一般用途
 /// - AAC: 高效压缩，适合移动设备
 /// - Opus: 现代化编码，适合网络传输
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// - M4B: 有声书格式，支持章节和封面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AudioFormat {
     /// MP3 格式 - 使用 VBR 最高质量设置
     /// 
@@ -36,6 +39,14 @@ pub enum AudioFormat {
     /// - 优秀的压缩效率
     /// - 低延迟特性
     Opus,
+
+    /// M4B 格式 - AAC 编码的有声书
+    /// 
+    /// 优点：
+    /// - 保留章节信息，播放器可以按章节跳转
+    /// - 可以嵌入封面图片
+    /// - 有声书播放器会记住播放位置
+    M4b,
 }
 
 impl AudioFormat {
@@ -53,12 +64,14 @@ impl AudioFormat {
     /// assert_eq!(AudioFormat::Mp3.extension(), "mp3");
     /// assert_eq!(AudioFormat::AacCopy.extension(), "aac");
     /// assert_eq!(AudioFormat::Opus.extension(), "opus");
+    /// assert_eq!(AudioFormat::M4b.extension(), "m4b");
     /// ```
     pub fn extension(&self) -> &'static str {
         match self {
             AudioFormat::Mp3 => "mp3",
             AudioFormat::AacCopy => "aac",
             AudioFormat::Opus => "opus",
+            AudioFormat::M4b => "m4b",
         }
     }
 
@@ -89,12 +102,41 @@ impl AudioFormat {
             
             // 使用 libopus 编码器，192k 码率 - 现代化高效编码
             AudioFormat::Opus => vec!["-c:a", "libopus", "-b:a", "192k"],
+
+            // 使用 FFmpeg 内置的 AAC 编码器，128k 码率对语音内容足够
+            AudioFormat::M4b => vec!["-c:a", "aac", "-b:a", "128k"],
+        }
+    }
+
+    /// 获取 FFmpeg 输出封装格式名称
+    /// 
+    /// 转换时先写入临时文件，其扩展名无法让 FFmpeg 推断封装格式，
+    /// 因此需要通过 `-f` 显式指定。
+    /// 
+    /// # 示例
+    /// 
+    /// ```rust
+    /// use video2audio_rs::AudioFormat;
+    /// 
+    /// assert_eq!(AudioFormat::AacCopy.muxer(), "adts");
+    /// ```
+    pub fn muxer(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "mp3",
+            AudioFormat::AacCopy => "adts",
+            AudioFormat::Opus => "opus",
+            AudioFormat::M4b => "mp4",
         }
     }
 
+    /// 是否支持嵌入封面图片
+    pub fn supports_cover(&self) -> bool {
+        matches!(self, AudioFormat::Mp3 | AudioFormat::M4b)
+    }
+
     /// 从用户输入字符串解析音频格式
     /// 
-    /// 支持数字选择（1-3）和格式名称（不区分大小写）
+    /// 支持数字选择（1-4）和格式名称（不区分大小写）
     /// 
     /// # 参数
     /// 
@@ -119,8 +161,10 @@ impl AudioFormat {
             "1" | "mp3" => Ok(AudioFormat::Mp3),
             "2" | "aac" | "aac-copy" => Ok(AudioFormat::AacCopy),
             "3" | "opus" => Ok(AudioFormat::Opus),
-            _ => Err(VideoToAudioError::InvalidInput(format!(
-                "不支持的音频格式选择: '{input}'. 请选择 1-3 或格式名称 (mp3/aac/opus)"
+            "4" | "m4b" => Ok(AudioFormat::M4b),
+            _ => Err(VideoToAudioError::InvalidInput(tr!(
+                "不支持的音频格式选择: '{input}'. 请选择 1-4 或格式名称 (mp3/aac/opus/m4b)",
+                "Unsupported audio format choice: '{input}'. Choose 1-4 or a format name (mp3/aac/opus/m4b)"
             ))),
         }
     }
@@ -134,9 +178,23 @@ impl AudioFormat {
     /// 格式的中文描述字符串
     pub fn description(&self) -> &'static str {
         match self {
-            AudioFormat::Mp3 => "MP3 (高质量, 最佳兼容性)",
-            AudioFormat::AacCopy => "AAC (直接复制, 速度最快, 零损耗)",
-            AudioFormat::Opus => "Opus (现代化, 高效率)",
+            AudioFormat::Mp3 => text("MP3 (高质量, 最佳兼容性)", "MP3 (high quality, best compatibility)"),
+            AudioFormat::AacCopy => text("AAC (直接复制, 速度最快, 零损耗)", "AAC (stream copy, fastest, lossless)"),
+            AudioFormat::Opus => text("Opus (现代化, 高效率)", "Opus (modern, efficient)"),
+            AudioFormat::M4b => text("M4B (有声书, 支持章节和封面)", "M4B (audiobook with chapters and cover)"),
+        }
+    }
+
+    /// 获取可用于该格式的 FFmpeg 编码器，按优先级排列
+    /// 
+    /// 第一个为默认编码器，其余为精简版 FFmpeg 缺少默认编码器时的备选。
+    /// AAC 直接复制音频流，不需要编码器，返回空列表。
+    pub fn encoder_candidates(&self) -> &'static [&'static str] {
+        match self {
+            AudioFormat::Mp3 => &["libmp3lame", "mp3_mf"],
+            AudioFormat::AacCopy => &[],
+            AudioFormat::Opus => &["libopus", "opus"],
+            AudioFormat::M4b => &["aac"],
         }
     }
 
@@ -148,7 +206,7 @@ impl AudioFormat {
     ///
     /// 包含所有 `AudioFormat` 变体的向量
     pub fn all_formats() -> Vec<Self> {
-        vec![AudioFormat::Mp3, AudioFormat::AacCopy, AudioFormat::Opus]
+        vec![AudioFormat::Mp3, AudioFormat::AacCopy, AudioFormat::Opus, AudioFormat::M4b]
     }
 }
 
@@ -161,6 +219,7 @@ mod tests {
         assert_eq!(AudioFormat::Mp3.extension(), "mp3");
         assert_eq!(AudioFormat::AacCopy.extension(), "aac");
         assert_eq!(AudioFormat::Opus.extension(), "opus");
+        assert_eq!(AudioFormat::M4b.extension(), "m4b");
     }
 
     #[test]
@@ -170,11 +229,20 @@ mod tests {
         assert_eq!(AudioFormat::Opus.ffmpeg_args(), vec!["-c:a", "libopus", "-b:a", "192k"]);
     }
 
+    #[test]
+    fn test_muxer() {
+        assert_eq!(AudioFormat::Mp3.muxer(), "mp3");
+        assert_eq!(AudioFormat::AacCopy.muxer(), "adts");
+        assert_eq!(AudioFormat::Opus.muxer(), "opus");
+        assert_eq!(AudioFormat::M4b.muxer(), "mp4");
+    }
+
     #[test]
     fn test_from_user_input_numbers() {
         assert_eq!(AudioFormat::from_user_input("1").unwrap(), AudioFormat::Mp3);
         assert_eq!(AudioFormat::from_user_input("2").unwrap(), AudioFormat::AacCopy);
         assert_eq!(AudioFormat::from_user_input("3").unwrap(), AudioFormat::Opus);
+        assert_eq!(AudioFormat::from_user_input("4").unwrap(), AudioFormat::M4b);
     }
 
     #[test]
@@ -190,7 +258,7 @@ mod tests {
 
     #[test]
     fn test_from_user_input_invalid() {
-        assert!(AudioFormat::from_user_input("4").is_err());
+        assert!(AudioFormat::from_user_input("5").is_err());
         assert!(AudioFormat::from_user_input("invalid").is_err());
         assert!(AudioFormat::from_user_input("").is_err());
         assert!(AudioFormat::from_user_input("   ").is_err());
@@ -206,10 +274,11 @@ mod tests {
     #[test]
     fn test_all_formats() {
         let formats = AudioFormat::all_formats();
-        assert_eq!(formats.len(), 3);
+        assert_eq!(formats.len(), 4);
         assert!(formats.contains(&AudioFormat::Mp3));
         assert!(formats.contains(&AudioFormat::AacCopy));
         assert!(formats.contains(&AudioFormat::Opus));
+        assert!(formats.contains(&AudioFormat::M4b));
     }
 
     #[test]
@@ -0,0 +1,107 @@
+//! # 转换后端模块
+//!
+//! 默认通过 FFmpeg 命令行完成转换。某些环境不允许附带或启动外部程序
+//! （例如部分应用商店分发的程序），此时可以通过 [`ConversionBackend`]
+//! 接入其他转换实现，由 [`FileProcessor`](crate::FileProcessor) 统一负责
+//! 输出路径、覆盖策略、临时文件和重试等逻辑。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::options::ConversionOptions;
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 转换后端
+///
+/// 实现者只需把源文件转换写入指定的输出路径。输出路径是一个临时文件，
+/// 转换成功后由文件处理器重命名为最终文件；失败时临时文件会被删除。
+pub trait ConversionBackend: fmt::Debug + Send + Sync {
+    /// 后端名称，用于日志和错误信息
+    fn name(&self) -> &'static str;
+
+    /// 检查后端在当前环境中是否可用
+    ///
+    /// # 错误
+    ///
+    /// 后端依赖的库或组件缺失时返回 [`VideoToAudioError::MissingDependency`]
+    fn check_available(&self) -> Result<()>;
+
+    /// 转换单个文件
+    ///
+    /// # 参数
+    ///
+    /// * `source_file` - 源视频文件
+    /// * `partial_path` - 输出的临时文件路径，封装格式由 `options.format` 决定
+    /// * `options` - 转换选项，后端不支持的选项应返回错误而不是静默忽略
+    /// * `cancel_token` - 取消令牌，被取消时应尽快返回 [`VideoToAudioError::Cancelled`]
+    fn convert(
+        &self,
+        source_file: &Path,
+        partial_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()>;
+}
+
+/// 可通过命令行或配置文件选择的后端
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// FFmpeg 命令行（默认）
+    #[default]
+    Ffmpeg,
+
+    /// Symphonia 纯 Rust 音频流提取，无需 FFmpeg 但不能重新编码
+    #[cfg(feature = "symphonia")]
+    Symphonia,
+
+    /// GStreamer 进程内转换，无需启动外部程序
+    #[cfg(feature = "gstreamer")]
+    Gstreamer,
+}
+
+impl BackendKind {
+    /// 从用户输入解析后端名称（不区分大小写）
+    pub fn from_user_input(input: &str) -> Result<Self> {
+        match input.trim().to_lowercase().as_str() {
+            "ffmpeg" => Ok(Self::Ffmpeg),
+            #[cfg(feature = "symphonia")]
+            "symphonia" => Ok(Self::Symphonia),
+            #[cfg(feature = "gstreamer")]
+            "gstreamer" => Ok(Self::Gstreamer),
+            _ => Err(VideoToAudioError::InvalidInput(tr!(
+                "不支持的转换后端: {input}",
+                "Unsupported conversion backend: {input}"
+            ))),
+        }
+    }
+
+    /// 创建后端实例
+    ///
+    /// 返回 `None` 表示使用文件处理器内置的 FFmpeg 命令行实现
+    pub fn create(self) -> Option<Arc<dyn ConversionBackend>> {
+        match self {
+            Self::Ffmpeg => None,
+            #[cfg(feature = "symphonia")]
+            Self::Symphonia => Some(Arc::new(crate::symphonia_backend::SymphoniaBackend::new())),
+            #[cfg(feature = "gstreamer")]
+            Self::Gstreamer => Some(Arc::new(crate::gstreamer_backend::GStreamerBackend::new())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backend_kind_from_user_input() {
+        assert_eq!(BackendKind::from_user_input(" FFmpeg ").unwrap(), BackendKind::Ffmpeg);
+        assert!(BackendKind::from_user_input("vlc").is_err());
+        #[cfg(feature = "gstreamer")]
+        assert_eq!(BackendKind::from_user_input("GStreamer").unwrap(), BackendKind::Gstreamer);
+    }
+}
@@ -0,0 +1,133 @@
+//! # 编码设置基准测试模块
+//!
+//! 把同一个源文件的一小段依次转换为每种格式的每个音质预设，测量编码速度和输出大小，
+//! 在用户自己的机器上直接比较各种设置，而不必凭经验猜测该选哪种格式。
+//!
+//! 基准测试只使用格式和音质预设，不受 `--bitrate`、`--quality` 等编码设置影响。
+
+use crate::audio_format::AudioFormat;
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::estimate::{converted_duration, encode_sample, output_kbps, SampleEncode};
+use crate::file_processor::FileProcessor;
+use crate::options::{ConversionOptions, QualityPreset};
+use crate::probe::probe_duration;
+use crate::tr;
+use std::path::Path;
+use std::time::Duration;
+
+/// 一项基准测试：目标格式和音质预设
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BenchCase {
+    /// 目标音频格式
+    pub format: AudioFormat,
+
+    /// 音质预设
+    pub preset: QualityPreset,
+}
+
+/// 一项基准测试的结果
+#[derive(Debug)]
+pub struct BenchResult {
+    /// 测试项
+    pub case: BenchCase,
+
+    /// 实际使用的编码设置，如 `V0`、`96k`
+    pub settings: String,
+
+    /// 试转换的结果，编码器不可用或转换失败时为错误
+    pub outcome: Result<SampleEncode>,
+}
+
+/// 列出要测试的格式和音质预设组合
+///
+/// AAC 直接复制音频流，音质预设不起作用，只测试一次
+pub fn bench_cases(formats: &[AudioFormat]) -> Vec<BenchCase> {
+    formats
+        .iter()
+        .flat_map(|&format| {
+            let presets = if format == AudioFormat::AacCopy {
+                vec![QualityPreset::High]
+            } else {
+                QualityPreset::all().to_vec()
+            };
+            presets.into_iter().map(move |preset| BenchCase { format, preset })
+        })
+        .collect()
+}
+
+/// 编码设置的简短说明：复制音频流为 `copy`，MP3 为 VBR 质量（如 `V0`），其余为码率（如 `96k`）
+pub fn settings_label(options: &ConversionOptions) -> String {
+    if options.is_stream_copy() {
+        return "copy".to_string();
+    }
+    match (options.format, options.encoder.as_deref(), options.bitrate) {
+        (AudioFormat::Mp3, None, None) => format!("V{}", options.vbr_quality.unwrap_or(0)),
+        _ => output_kbps(options, None).map_or_else(String::new, |kbps| format!("{kbps}k")),
+    }
+}
+
+/// 运行一项基准测试
+///
+/// 从 `start`（未设置时从头）开始转换 `length` 长的片段，源文件剩余部分更短时只转换到结尾
+///
+/// # 参数
+///
+/// * `processor` - 文件处理器
+/// * `input` - 测试用的源文件
+/// * `start` - 片段的开始时间
+/// * `length` - 片段长度
+/// * `case` - 格式和音质预设
+/// * `cancel_token` - 取消令牌
+pub fn run_case(
+    processor: &FileProcessor,
+    input: &Path,
+    start: Option<Duration>,
+    length: Duration,
+    case: BenchCase,
+    cancel_token: &CancellationToken,
+) -> BenchResult {
+    let options = processor
+        .check_encoder(case.format)
+        .map(|options| case.preset.apply(options).with_segment(start, None));
+    let settings = options.as_ref().map(settings_label).unwrap_or_default();
+    let outcome = options.and_then(|options| {
+        // 读不到时长时按指定长度转换，由 FFmpeg 在文件结尾停止
+        let length = probe_duration(processor.ffmpeg_path(), input)
+            .map_or(length, |duration| converted_duration(duration, &options).min(length));
+        if length.is_zero() {
+            return Err(VideoToAudioError::InvalidInput(tr!(
+                "测试片段的开始时间超出了源文件的时长: {}",
+                "The sample start time is past the end of the source file: {}",
+                input.display()
+            )));
+        }
+        encode_sample(processor, input, length, &options, cancel_token)
+    });
+    BenchResult { case, settings, outcome }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench_cases() {
+        let cases = bench_cases(&AudioFormat::all_formats());
+        assert_eq!(cases.len(), 10);
+        assert_eq!(cases.iter().filter(|case| case.format == AudioFormat::AacCopy).count(), 1);
+        assert_eq!(cases[0], BenchCase { format: AudioFormat::Mp3, preset: QualityPreset::High });
+    }
+
+    #[test]
+    fn test_settings_label() {
+        let label = |format, preset: QualityPreset| settings_label(&preset.apply(ConversionOptions::new(format)));
+        assert_eq!(label(AudioFormat::Mp3, QualityPreset::High), "V0");
+        assert_eq!(label(AudioFormat::Mp3, QualityPreset::Small), "V7");
+        assert_eq!(label(AudioFormat::Opus, QualityPreset::High), "192k");
+        assert_eq!(label(AudioFormat::Opus, QualityPreset::Small), "64k");
+        assert_eq!(label(AudioFormat::M4b, QualityPreset::Standard), "96k");
+        assert_eq!(settings_label(&ConversionOptions::new(AudioFormat::Mp3).with_encoder("mp3_mf")), "320k");
+        assert_eq!(settings_label(&ConversionOptions::new(AudioFormat::AacCopy)), "copy");
+    }
+}
@@ -0,0 +1,182 @@
+//! # 取消控制模块
+//!
+//! 提供跨线程共享的取消令牌，用于中止正在运行的批量转换。
+//! 适合 GUI 或服务等嵌入场景，在用户点击“停止”时安全地结束任务。
+//! 令牌也可以暂停批量转换：暂停期间不再开始新文件，正在转换的文件照常完成；
+//! 或者停止批量转换：不再开始新文件，正在转换的文件完成后结束。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// 暂停期间检查是否恢复的间隔
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 取消令牌
+///
+/// 克隆得到的令牌共享同一个取消状态：任意一个副本调用 [`cancel`](Self::cancel)
+/// 后，所有副本的 [`is_cancelled`](Self::is_cancelled) 都会返回 `true`。
+///
+/// # 示例
+///
+/// ```rust
+/// use video2audio_rs::CancellationToken;
+///
+/// let token = CancellationToken::new();
+/// let worker_token = token.clone();
+///
+/// token.cancel();
+/// assert!(worker_token.is_cancelled());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+
+    /// 是否暂停开始新文件
+    paused: Arc<AtomicBool>,
+
+    /// 是否不再开始新文件，正在转换的文件照常完成
+    stopped: Arc<AtomicBool>,
+
+    /// 父令牌，父令牌被取消时本令牌也视为已取消
+    parent: Option<Arc<CancellationToken>>,
+}
+
+impl CancellationToken {
+    /// 创建一个未取消的新令牌
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 请求取消
+    ///
+    /// 尚未开始的文件不会再被处理，正在运行的 FFmpeg 进程会被终止。
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// 检查是否已请求取消（包括父令牌被取消的情况）
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+            || self.parent.as_ref().is_some_and(|parent| parent.is_cancelled())
+    }
+
+    /// 停止批量转换：不再开始新文件，正在转换的文件照常完成后结束
+    ///
+    /// 与 [`cancel`](Self::cancel) 不同，不会终止正在运行的 FFmpeg 进程
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::SeqCst);
+    }
+
+    /// 检查是否不再开始新文件：已请求停止或已取消（包括父令牌的情况）
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::SeqCst)
+            || self.is_cancelled()
+            || self.parent.as_ref().is_some_and(|parent| parent.is_stopped())
+    }
+
+    /// 暂停批量转换，不再开始新文件，正在转换的文件照常完成
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    /// 恢复暂停的批量转换
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// 检查是否已暂停（包括父令牌被暂停的情况）
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst) || self.parent.as_ref().is_some_and(|parent| parent.is_paused())
+    }
+
+    /// 暂停期间阻塞当前线程，恢复、停止或取消后返回
+    pub fn wait_while_paused(&self) {
+        while self.is_paused() && !self.is_stopped() {
+            std::thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+    }
+
+    /// 创建子令牌
+    ///
+    /// 父令牌被取消或暂停时子令牌也随之取消或暂停，而取消子令牌不会影响父令牌。
+    /// 适合在进程级的 Ctrl+C 令牌下为单个任务创建可独立取消的令牌。
+    pub fn child_token(&self) -> Self {
+        Self {
+            cancelled: Arc::new(AtomicBool::new(false)),
+            paused: Arc::new(AtomicBool::new(false)),
+            stopped: Arc::new(AtomicBool::new(false)),
+            parent: Some(Arc::new(self.clone())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_token_is_not_cancelled() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+    }
+
+    #[test]
+    fn test_cancel_is_shared_between_clones() {
+        let token = CancellationToken::new();
+        let cloned = token.clone();
+
+        cloned.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(cloned.is_cancelled());
+    }
+
+    #[test]
+    fn test_child_token_follows_parent_only() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+
+        child.cancel();
+        assert!(child.is_cancelled());
+        assert!(!parent.is_cancelled());
+
+        let child = parent.child_token();
+        parent.cancel();
+        assert!(child.is_cancelled());
+    }
+
+    #[test]
+    fn test_pause_and_resume() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+
+        parent.pause();
+        assert!(child.is_paused());
+        parent.resume();
+        assert!(!child.is_paused());
+
+        // 取消后不再等待暂停结束
+        child.pause();
+        child.cancel();
+        child.wait_while_paused();
+        assert!(!parent.is_paused());
+    }
+
+    #[test]
+    fn test_stop_keeps_running_conversions() {
+        let parent = CancellationToken::new();
+        let child = parent.child_token();
+
+        // 停止只是不再开始新文件，不算取消
+        parent.pause();
+        parent.stop();
+        assert!(child.is_stopped());
+        assert!(!child.is_cancelled());
+        child.wait_while_paused();
+
+        let token = CancellationToken::new();
+        token.cancel();
+        assert!(token.is_stopped());
+    }
+}
@@ -0,0 +1,138 @@
+//! # FFmpeg 能力检测模块
+//!
+//! 解析 `ffmpeg -encoders` 的输出，在批处理开始前确认所选格式的编码器可用。
+//! 精简版 FFmpeg 可能缺少 `libopus`、`libmp3lame` 等编码器，
+//! 提前检测可以自动改用备选编码器，或在处理第一个文件前给出明确的解决办法。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::ffmpeg_command;
+use crate::i18n::text;
+use crate::tr;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+
+/// FFmpeg 支持的功能
+#[derive(Debug, Clone, Default)]
+pub struct FfmpegCapabilities {
+    /// 可用的音频编码器名称
+    audio_encoders: HashSet<String>,
+}
+
+impl FfmpegCapabilities {
+    /// 运行 `ffmpeg -encoders` 检测可用的编码器
+    ///
+    /// # 错误
+    ///
+    /// 当 FFmpeg 无法执行时返回 [`VideoToAudioError::MissingDependency`]
+    pub fn detect(ffmpeg_path: &Path) -> Result<Self> {
+        let output = ffmpeg_command(ffmpeg_path)
+            .args(["-hide_banner", "-encoders"])
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .output()
+            .map_err(|_| VideoToAudioError::MissingDependency(
+                text(
+                    "FFmpeg 未安装或不在系统 PATH 中。请安装 FFmpeg 后重试。",
+                    "FFmpeg is not installed or not in the system PATH. Install FFmpeg and try again.",
+                ).to_string()
+            ))?;
+
+        Ok(Self::parse_encoders(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// 解析 `ffmpeg -encoders` 的输出
+    ///
+    /// 编码器列表位于 `------` 分隔行之后，每行形如 ` A....D libopus   描述`，
+    /// 第一列首字母为 `A` 的是音频编码器。
+    pub fn parse_encoders(output: &str) -> Self {
+        let audio_encoders = output
+            .lines()
+            .skip_while(|line| !line.trim_start().starts_with("---"))
+            .skip(1)
+            .filter_map(|line| {
+                let mut columns = line.split_whitespace();
+                let flags = columns.next()?;
+                let name = columns.next()?;
+                flags.starts_with('A').then(|| name.to_string())
+            })
+            .collect();
+
+        Self { audio_encoders }
+    }
+
+    /// 检查是否支持指定的音频编码器
+    pub fn has_encoder(&self, name: &str) -> bool {
+        self.audio_encoders.contains(name)
+    }
+
+    /// 为格式选择可用的编码器
+    ///
+    /// # 返回值
+    ///
+    /// 格式不需要编码器（直接复制）时返回 `None`，否则返回第一个可用的候选编码器
+    ///
+    /// # 错误
+    ///
+    /// 所有候选编码器都不可用时返回带有解决办法的错误
+    pub fn select_encoder(&self, format: AudioFormat) -> Result<Option<&'static str>> {
+        let candidates = format.encoder_candidates();
+        if candidates.is_empty() {
+            return Ok(None);
+        }
+
+        candidates
+            .iter()
+            .find(|encoder| self.has_encoder(encoder))
+            .map(|encoder| Some(*encoder))
+            .ok_or_else(|| VideoToAudioError::MissingDependency(tr!(
+                "当前 FFmpeg 不支持 {} 编码（需要 {} 编码器之一）。请安装完整版 FFmpeg，或改用其他输出格式 (如 --format aac)",
+                "This FFmpeg cannot encode {} (needs one of the {} encoders). Install a full FFmpeg build or choose another output format (e.g. --format aac)",
+                format.extension().to_uppercase(),
+                candidates.join(" / ")
+            )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_OUTPUT: &str = "\
+Encoders:
+ V..... = Video
+ A..... = Audio
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ A....D aac                  AAC (Advanced Audio Coding)
+ A....D opus                 Opus (codec opus)
+ A....D libmp3lame           libmp3lame MP3 (MPEG audio layer 3) (codec mp3)
+";
+
+    #[test]
+    fn test_parse_encoders() {
+        let capabilities = FfmpegCapabilities::parse_encoders(SAMPLE_OUTPUT);
+        assert!(capabilities.has_encoder("libmp3lame"));
+        assert!(capabilities.has_encoder("opus"));
+        assert!(!capabilities.has_encoder("libx264"));
+        assert!(!capabilities.has_encoder("libopus"));
+    }
+
+    #[test]
+    fn test_select_encoder_with_fallback() {
+        let capabilities = FfmpegCapabilities::parse_encoders(SAMPLE_OUTPUT);
+        assert_eq!(capabilities.select_encoder(AudioFormat::Mp3).unwrap(), Some("libmp3lame"));
+        assert_eq!(capabilities.select_encoder(AudioFormat::Opus).unwrap(), Some("opus"));
+        assert_eq!(capabilities.select_encoder(AudioFormat::AacCopy).unwrap(), None);
+    }
+
+    #[test]
+    fn test_select_encoder_missing() {
+        let capabilities = FfmpegCapabilities::parse_encoders("");
+        match capabilities.select_encoder(AudioFormat::Mp3) {
+            Err(VideoToAudioError::MissingDependency(message)) => assert!(message.contains("libmp3lame")),
+            other => panic!("应该返回 MissingDependency 错误: {other:?}"),
+        }
+    }
+}
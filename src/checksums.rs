@@ -0,0 +1,164 @@
+//! # 校验和清单模块
+//!
+//! 为输出目录中生成的音频文件维护 `sha256sum` 格式的校验和清单，
+//! 之后可以用 `sha256sum -c SHA256SUMS` 检查归档是否完整。
+//!
+//! 每个文件转换完成后立即追加到清单，中断的批处理也会留下有效的部分清单。
+//! 重新转换清单中已有的文件时，整个清单以替换文件的方式重写。
+
+use crate::error::{IoResultExt, Result};
+use crate::i18n::text;
+use crate::playlist::relative_entry;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+/// 校验和算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumAlgorithm {
+    /// SHA-256
+    Sha256,
+}
+
+impl ChecksumAlgorithm {
+    /// 清单文件名，与 coreutils 的习惯一致
+    pub fn manifest_file_name(self) -> &'static str {
+        match self {
+            ChecksumAlgorithm::Sha256 => "SHA256SUMS",
+        }
+    }
+
+    /// 计算文件的校验和，返回小写十六进制字符串
+    ///
+    /// # 错误
+    ///
+    /// 无法读取文件时返回 I/O 错误
+    pub fn digest_file(self, path: &Path) -> Result<String> {
+        match self {
+            ChecksumAlgorithm::Sha256 => {
+                let mut file = File::open(path)?;
+                let mut hasher = Sha256::new();
+                let mut buffer = [0u8; 64 * 1024];
+                loop {
+                    let read = file.read(&mut buffer)?;
+                    if read == 0 {
+                        break;
+                    }
+                    hasher.update(&buffer[..read]);
+                }
+                Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+            }
+        }
+    }
+}
+
+/// 输出目录中的校验和清单
+#[derive(Debug)]
+pub struct ChecksumManifest {
+    algorithm: ChecksumAlgorithm,
+    output_dir: PathBuf,
+    path: PathBuf,
+
+    /// 清单中已有的条目：相对路径 -> 校验和
+    entries: BTreeMap<String, String>,
+}
+
+impl ChecksumManifest {
+    /// 打开输出目录中的清单，读取之前运行留下的条目
+    ///
+    /// # 错误
+    ///
+    /// 清单存在但无法读取时返回 I/O 错误
+    pub fn open(output_dir: &Path, algorithm: ChecksumAlgorithm) -> Result<Self> {
+        let path = output_dir.join(algorithm.manifest_file_name());
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => content
+                .lines()
+                .filter_map(|line| {
+                    let (hash, name) = line.split_once("  ").or_else(|| line.split_once(" *"))?;
+                    Some((name.to_string(), hash.to_string()))
+                })
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            algorithm,
+            output_dir: output_dir.to_path_buf(),
+            path,
+            entries,
+        })
+    }
+
+    /// 清单文件的路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 计算输出文件的校验和并写入清单
+    ///
+    /// # 错误
+    ///
+    /// 无法读取输出文件或写入清单时返回 I/O 错误
+    pub fn record(&mut self, output_file: &Path) -> Result<()> {
+        let hash = self.algorithm.digest_file(output_file)?;
+        let name = relative_entry(&self.output_dir, output_file);
+
+        match self.entries.insert(name.clone(), hash.clone()) {
+            Some(previous) if previous == hash => Ok(()),
+            Some(_) => self.rewrite(),
+            None => {
+                let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+                writeln!(file, "{hash}  {name}")?;
+                Ok(())
+            }
+        }
+    }
+
+    /// 以替换文件的方式重写整个清单，中途中断时保留原清单
+    fn rewrite(&self) -> Result<()> {
+        let content: String = self.entries.iter().map(|(name, hash)| format!("{hash}  {name}\n")).collect();
+        let mut temp_path = self.path.clone().into_os_string();
+        temp_path.push(".tmp");
+        fs::write(&temp_path, content).context(text("写入校验和清单", "write checksum manifest"), Path::new(&temp_path))?;
+        fs::rename(&temp_path, &self.path).context(text("写入校验和清单", "write checksum manifest"), &self.path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_appends_and_replaces_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path();
+        fs::create_dir(output_dir.join("Live")).unwrap();
+        fs::write(output_dir.join("a.mp3"), "abc").unwrap();
+        fs::write(output_dir.join("Live/b.mp3"), "").unwrap();
+
+        let mut manifest = ChecksumManifest::open(output_dir, ChecksumAlgorithm::Sha256).unwrap();
+        manifest.record(&output_dir.join("a.mp3")).unwrap();
+        manifest.record(&output_dir.join("Live/b.mp3")).unwrap();
+        assert_eq!(
+            fs::read_to_string(manifest.path()).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad  a.mp3\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  Live/b.mp3\n"
+        );
+
+        // 再次运行时重新转换的文件替换旧条目
+        fs::write(output_dir.join("a.mp3"), "").unwrap();
+        let mut manifest = ChecksumManifest::open(output_dir, ChecksumAlgorithm::Sha256).unwrap();
+        manifest.record(&output_dir.join("a.mp3")).unwrap();
+        assert_eq!(
+            fs::read_to_string(manifest.path()).unwrap(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  Live/b.mp3\n\
+             e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855  a.mp3\n"
+        );
+    }
+}
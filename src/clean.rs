@@ -0,0 +1,138 @@
+//! # 输出目录清理模块
+//!
+//! 找出输出目录中长期积累的无用文件：源文件已不存在的孤立音频、
+//! 中断的转换留下的临时文件，以及大小为零的音频文件。
+//!
+//! 临时文件可能属于正在运行的转换，清理前应确认没有其他批处理在写入同一目录。
+
+use crate::audio_format::AudioFormat;
+use crate::error::Result;
+use crate::file_processor::{FileProcessor, PARTIAL_FILE_SUFFIX};
+use crate::sync::{remove_empty_dirs, remove_file, SyncPlan};
+use crate::tagging::format_from_path;
+use std::path::{Path, PathBuf};
+
+/// 清理计划
+#[derive(Debug, Default)]
+pub struct CleanPlan {
+    /// 源文件已不存在的孤立输出文件
+    pub orphans: Vec<PathBuf>,
+
+    /// 中断的转换留下的临时文件
+    pub partials: Vec<PathBuf>,
+
+    /// 大小为零的音频文件
+    pub empty: Vec<PathBuf>,
+}
+
+impl CleanPlan {
+    /// 对比源目录和输出目录，生成清理计划
+    ///
+    /// 与 [`SyncPlan::build`] 相同，`processor` 应设置为以 `source_root` 为根保留目录结构，
+    /// 只有 `format` 格式的输出会被视为孤立文件；临时文件和空文件不限格式。
+    ///
+    /// # 错误
+    ///
+    /// 当目录无法访问时返回错误
+    pub fn build(
+        processor: &FileProcessor,
+        source_root: &Path,
+        output_root: &Path,
+        format: AudioFormat,
+    ) -> Result<Self> {
+        let mut plan = CleanPlan::default();
+
+        for entry in walkdir::WalkDir::new(output_root).into_iter().filter_map(|e| e.ok()) {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy();
+            if name.ends_with(PARTIAL_FILE_SUFFIX) || name.ends_with(".part.chapters") {
+                plan.partials.push(path.to_path_buf());
+            } else if format_from_path(path).is_some() && entry.metadata().is_ok_and(|m| m.len() == 0) {
+                plan.empty.push(path.to_path_buf());
+            }
+        }
+
+        // 空文件单独列出，不重复计入孤立文件
+        plan.orphans = SyncPlan::build(processor, source_root, output_root, format)?
+            .orphans
+            .into_iter()
+            .filter(|orphan| !plan.empty.contains(orphan))
+            .collect();
+        Ok(plan)
+    }
+
+    /// 是否没有需要清理的文件
+    pub fn is_empty(&self) -> bool {
+        self.orphans.is_empty() && self.partials.is_empty() && self.empty.is_empty()
+    }
+
+    /// 全部需要删除的文件
+    pub fn files(&self) -> impl Iterator<Item = &PathBuf> {
+        self.orphans.iter().chain(&self.partials).chain(&self.empty)
+    }
+
+    /// 删除计划中的全部文件，并清理因此变空的子目录（输出根目录本身保留）
+    ///
+    /// `to_trash` 为 `true` 时移到系统回收站，可以恢复。
+    ///
+    /// # 返回值
+    ///
+    /// 成功删除的文件数
+    pub fn remove(&self, output_root: &Path, to_trash: bool) -> Result<usize> {
+        let mut removed = 0;
+        for file in self.files() {
+            remove_file(file, to_trash)?;
+            removed += 1;
+        }
+        remove_empty_dirs(output_root);
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_build_plan_finds_junk() {
+        let source = TempDir::new().unwrap();
+        let output = TempDir::new().unwrap();
+        fs::create_dir(source.path().join("a")).unwrap();
+        for name in ["a/kept.mp4", "a/empty.mp4"] {
+            fs::write(source.path().join(name), "video").unwrap();
+        }
+        for (path, content) in [
+            ("a/kept.mp3", "audio"),
+            ("a/empty.mp3", ""),
+            ("a/kept.mp3.part", "half"),
+            ("book.m4b.part.chapters", ";FFMETADATA1"),
+            ("b/removed.mp3", "audio"),
+            ("b/removed-empty.mp3", ""),
+            ("b/cover.jpg", ""),
+        ] {
+            fs::create_dir_all(output.path().join(path).parent().unwrap()).unwrap();
+            fs::write(output.path().join(path), content).unwrap();
+        }
+
+        let processor = FileProcessor::new().with_preserved_structure(Some(source.path().to_path_buf()));
+        let mut plan = CleanPlan::build(&processor, source.path(), output.path(), AudioFormat::Mp3).unwrap();
+        plan.partials.sort();
+        plan.empty.sort();
+
+        assert_eq!(plan.orphans, [output.path().join("b/removed.mp3")]);
+        assert_eq!(
+            plan.partials,
+            [output.path().join("a/kept.mp3.part"), output.path().join("book.m4b.part.chapters")]
+        );
+        assert_eq!(plan.empty, [output.path().join("a/empty.mp3"), output.path().join("b/removed-empty.mp3")]);
+
+        assert_eq!(plan.remove(output.path(), false).unwrap(), 5);
+        assert!(output.path().join("a/kept.mp3").exists());
+        assert!(output.path().join("b/cover.jpg").exists());
+    }
+}
@@ -0,0 +1,815 @@
+//! # 子命令实现
+//!
+//! 处理 `sync`、`merge`、`tag`、`verify`、`bench`、`clean`、`history`、`stats`、`explain`、`config` 等子命令的执行流程。常规的批量转换流程仍在 `main.rs` 中。
+
+use std::fs;
+use std::path::Path;
+use std::process::ExitCode;
+use video2audio_rs::bench::{bench_cases, run_case};
+use video2audio_rs::clean::CleanPlan;
+use video2audio_rs::config::{
+    BenchArgs, CleanArgs, ConfigAction, ConfigArgs, ExplainArgs, HistoryArgs, MergeArgs, StatsArgs, SyncArgs,
+    TagArgs, VerifyArgs,
+};
+use video2audio_rs::disk_space::format_bytes;
+use video2audio_rs::error::IoResultExt;
+use video2audio_rs::error_codes::{self, ERROR_CODES};
+use video2audio_rs::history::{History, HistoryStats};
+use video2audio_rs::i18n::text;
+use video2audio_rs::lock::{containing_output_dir, locked_error, OutputLock};
+use video2audio_rs::selection::{sort_files, SortOrder};
+use video2audio_rs::style;
+use video2audio_rs::tr;
+use video2audio_rs::{
+    Args, AudioFormat, CancellationToken, Command, Config, CoverArt, Daemon, OverwritePolicy, RuntimeConfig,
+    SyncPlan, TagUpdate, UserInterface, VideoToAudioError,
+};
+
+/// 执行子命令
+pub fn run(
+    command: Command,
+    ui: &UserInterface,
+    config: &RuntimeConfig,
+    file_config: &Config,
+    cancel_token: &CancellationToken,
+) -> Result<ExitCode, VideoToAudioError> {
+    let result = match command {
+        // 部分文件失败时已逐个显示原因，只返回非零状态码
+        Command::Sync(args) => return run_sync(args, ui, config, cancel_token),
+        Command::Tag(args) => return run_tag(args, config, cancel_token),
+        Command::Verify(args) => return run_verify(args, config, cancel_token),
+        Command::Merge(args) => run_merge(args, config, cancel_token),
+        Command::Bench(args) => run_bench(args, config, cancel_token),
+        Command::Clean(args) => run_clean(args, config, cancel_token),
+        Command::History(args) => run_history(args, config),
+        Command::Stats(args) => run_stats(args),
+        Command::Explain(args) => run_explain(args),
+        // main 在加载配置文件之前直接调用 run_config
+        Command::Config(_) => unreachable!("config 子命令不经过 commands::run"),
+        Command::Daemon => run_daemon(config, file_config, cancel_token),
+        #[cfg(feature = "ffmpeg-download")]
+        Command::DownloadFfmpeg(args) => run_download_ffmpeg(args, config),
+    };
+    result.map(|()| ExitCode::SUCCESS)
+}
+
+/// 锁定输出目录，防止其他实例同时处理
+///
+/// 目录已被锁定时，设置了 `--wait-for-lock` 则等待，否则返回错误
+pub fn lock_output_dir(
+    output_dir: &std::path::Path,
+    config: &RuntimeConfig,
+    cancel_token: &CancellationToken,
+) -> Result<OutputLock, VideoToAudioError> {
+    if let Some(lock) = OutputLock::try_acquire(output_dir)? {
+        return Ok(lock);
+    }
+    if !config.wait_for_lock {
+        return Err(locked_error(output_dir));
+    }
+    if !config.quiet {
+        println!("{}", text(
+            "⏳ 输出目录正被另一个实例处理，等待其完成...",
+            "⏳ The output directory is in use by another instance, waiting for it to finish...",
+        ));
+    }
+    OutputLock::acquire_waiting(output_dir, cancel_token)
+}
+
+/// 执行 `download-ffmpeg` 子命令
+///
+/// 下载 FFmpeg 到应用数据目录，之后系统 PATH 中找不到 FFmpeg 时会自动使用
+#[cfg(feature = "ffmpeg-download")]
+fn run_download_ffmpeg(
+    args: video2audio_rs::config::DownloadFfmpegArgs,
+    config: &RuntimeConfig,
+) -> Result<(), VideoToAudioError> {
+    use std::io::Write;
+
+    if !config.quiet {
+        println!("{}", text("⬇️  正在下载 FFmpeg...", "⬇️  Downloading FFmpeg..."));
+    }
+
+    let mut last_percent = None;
+    let path = video2audio_rs::managed_ffmpeg::download_ffmpeg(args.force, |downloaded, total| {
+        if config.quiet {
+            return;
+        }
+        let Some(total) = total.filter(|total| *total > 0) else { return };
+        let percent = downloaded * 100 / total;
+        if last_percent != Some(percent) {
+            last_percent = Some(percent);
+            print!("\r   {percent:>3}% ({:.1} / {:.1} MB)", downloaded as f64 / 1e6, total as f64 / 1e6);
+            let _ = std::io::stdout().flush();
+        }
+    })?;
+
+    if !config.quiet {
+        println!();
+        println!("{}", style::success(tr!("✅ FFmpeg 已就绪: {}", "✅ FFmpeg is ready: {}", path.display())));
+    }
+    Ok(())
+}
+
+/// 执行 `daemon` 子命令
+///
+/// 按配置文件中的监视规则运行常驻转码服务，直到收到终止信号
+fn run_daemon(
+    config: &RuntimeConfig,
+    file_config: &Config,
+    cancel_token: &CancellationToken,
+) -> Result<(), VideoToAudioError> {
+    // 守护进程运行期间一直锁定全部规则的输出目录
+    // 源目录不存在的规则由 Daemon::run 报错，不为其创建输出目录
+    let mut output_dirs: Vec<_> = file_config
+        .watch_rules
+        .iter()
+        .filter(|rule| rule.source_dir.is_dir())
+        .map(|rule| rule.resolved_output_dir())
+        .collect();
+    output_dirs.sort();
+    output_dirs.dedup();
+    let mut output_locks = Vec::with_capacity(output_dirs.len());
+    for output_dir in &output_dirs {
+        fs::create_dir_all(output_dir).context(text("创建输出目录", "create output directory"), output_dir)?;
+        output_locks.push(lock_output_dir(output_dir, config, cancel_token)?);
+    }
+
+    let daemon = Daemon::new(&file_config.watch_rules, config.file_processor(), config.watch_debounce)
+        .with_log_writer(std::io::stderr());
+    daemon.run(cancel_token)
+}
+
+/// 执行 `merge` 子命令
+///
+/// 按文件名排序后把目录中的全部视频合并为一个带章节标记的音频文件
+fn run_merge(
+    args: MergeArgs,
+    config: &RuntimeConfig,
+    cancel_token: &CancellationToken,
+) -> Result<(), VideoToAudioError> {
+    let format = AudioFormat::from(args.format);
+    let processor = config.file_processor();
+
+    let mut files = processor.find_video_files(&args.source_dir)?;
+    if files.is_empty() {
+        if !config.quiet {
+            println!("{}", text("未找到任何视频文件，程序退出。", "No video files found, exiting."));
+        }
+        return Ok(());
+    }
+    sort_files(&mut files, SortOrder::Name, false, &processor);
+
+    let mut output = args.output;
+    if output.extension().is_none() {
+        output.set_extension(format.extension());
+    }
+    // 锁定输出文件所在的目录，避免与向同一目录转换的实例争抢输出文件
+    let output_dir = match output.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => Path::new(".").to_path_buf(),
+    };
+    fs::create_dir_all(&output_dir).context(text("创建输出目录", "create output directory"), &output_dir)?;
+    let _output_lock = lock_output_dir(&output_dir, config, cancel_token)?;
+
+    if !config.quiet {
+        println!("{}", tr!(
+            "🔗 正在合并 {} 个文件 (每个文件一个章节)...",
+            "🔗 Merging {} files (one chapter per file)...",
+            files.len()
+        ));
+        if config.verbose {
+            for file in &files {
+                println!("   {}", file.display());
+            }
+        }
+    }
+
+    let options = config.conversion_options(processor.check_encoder(format)?);
+    processor.merge_files(&files, &output, options, cancel_token)?;
+
+    if !config.quiet {
+        println!("{}", style::success(tr!("🎉 合并完成: {}", "🎉 Merged: {}", output.display())));
+    }
+    Ok(())
+}
+
+/// 执行 `tag` 子命令
+///
+/// 为已有的音频文件写入标签和封面，单个文件失败不影响其余文件
+fn run_tag(args: TagArgs, config: &RuntimeConfig, cancel_token: &CancellationToken) -> Result<ExitCode, VideoToAudioError> {
+    let cover = match &config.cover {
+        Some(CoverArt::File(path)) => Some(path.clone()),
+        Some(_) => {
+            return Err(VideoToAudioError::InvalidInput(
+                text("tag 子命令的 --cover 只支持图片文件", "--cover of the tag subcommand only accepts image files").to_string(),
+            ))
+        }
+        None => None,
+    };
+    if config.tags.is_empty() && config.tag_pattern.is_none() && cover.is_none() {
+        return Err(VideoToAudioError::InvalidInput(
+            text(
+                "没有要写入的内容，请指定 --tag、--tag-pattern 或 --cover",
+                "Nothing to write, specify --tag, --tag-pattern or --cover",
+            )
+            .to_string(),
+        ));
+    }
+
+    // 要修改的文件位于其他实例正在处理的输出目录中时，等待或报错，不与转换同时改写文件
+    let mut output_dirs: Vec<_> = args.files.iter().filter_map(|file| containing_output_dir(file)).collect();
+    output_dirs.sort();
+    output_dirs.dedup();
+    let _output_locks = output_dirs
+        .iter()
+        .map(|output_dir| lock_output_dir(output_dir, config, cancel_token))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let processor = config.file_processor();
+    let mut failure_count = 0;
+    for file in &args.files {
+        if cancel_token.is_cancelled() {
+            return Err(VideoToAudioError::Cancelled);
+        }
+
+        // 模板标签在前，--tag 指定的同名标签覆盖模板解析的值
+        let mut update = TagUpdate { tags: Vec::new(), cover: cover.clone() };
+        if let Some(pattern) = &config.tag_pattern {
+            update.tags.extend(pattern.captures_file(file).unwrap_or_default());
+        }
+        update.tags.extend(config.tags.iter().cloned());
+
+        match processor.write_tags(file, &update, cancel_token) {
+            Ok(()) => {
+                if config.verbose && !config.quiet {
+                    println!("🏷️  {}", file.display());
+                }
+            }
+            Err(e) => {
+                failure_count += 1;
+                eprintln!("{}", style::failure(tr!("❌ 写入标签失败 {}: {e}", "❌ Failed to write tags to {}: {e}", file.display())).for_stderr());
+            }
+        }
+    }
+
+    if !config.quiet {
+        println!("{}", style::success(tr!(
+            "🎉 已写入 {} 个文件的标签",
+            "🎉 Wrote tags to {} files",
+            args.files.len() - failure_count
+        )));
+    }
+    if failure_count > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// 执行 `clean` 子命令
+///
+/// 删除输出目录中的孤立音频、临时文件和空文件。删除前锁定输出目录，
+/// 避免把其他实例正在写入的临时文件当作残留删除
+fn run_clean(args: CleanArgs, config: &RuntimeConfig, cancel_token: &CancellationToken) -> Result<(), VideoToAudioError> {
+    let format = AudioFormat::from(args.format);
+    // 在生成清理计划之前锁定，等待锁期间其他实例写完的文件不会出现在计划中
+    let _output_lock = (!args.dry_run && args.output_dir.is_dir())
+        .then(|| lock_output_dir(&args.output_dir, config, cancel_token))
+        .transpose()?;
+    let processor = config
+        .file_processor()
+        .with_preserved_structure(Some(args.source_dir.clone()))
+        .with_excluded_dir(&args.output_dir);
+
+    let plan = CleanPlan::build(&processor, &args.source_dir, &args.output_dir, format)?;
+
+    if !config.quiet {
+        println!("{}", text("🧹 清理计划:", "🧹 Clean plan:"));
+        println!("{}", tr!("   孤立音频: {} 个文件", "   Orphaned audio: {} files", plan.orphans.len()));
+        println!("{}", tr!("   临时文件: {} 个文件", "   Partial files: {} files", plan.partials.len()));
+        println!("{}", tr!("   空文件: {} 个文件", "   Empty files: {} files", plan.empty.len()));
+        println!();
+    }
+
+    if args.dry_run {
+        for file in plan.files() {
+            println!("  - {}", file.display());
+        }
+        return Ok(());
+    }
+
+    if !plan.is_empty() {
+        let removed = plan.remove(&args.output_dir, config.trash)?;
+        if !config.quiet {
+            if config.trash {
+                println!("{}", tr!("   🗑️  已移到回收站 {removed} 个文件", "   🗑️  Moved {removed} files to the trash"));
+            } else {
+                println!("{}", tr!("   🗑️  已删除 {removed} 个文件", "   🗑️  Deleted {removed} files"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 执行 `history` 子命令
+///
+/// 按时间倒序列出转换历史中的记录
+fn run_history(args: HistoryArgs, config: &RuntimeConfig) -> Result<(), VideoToAudioError> {
+    let path = History::default_path()?;
+    let history = History::load(&path)?;
+    let search = args.search.map(|search| search.to_lowercase());
+    let matches = |path: &std::path::Path| {
+        search.as_ref().is_none_or(|search| path.to_string_lossy().to_lowercase().contains(search))
+    };
+
+    let records: Vec<_> = history
+        .records()
+        .iter()
+        .rev()
+        .filter(|record| matches(&record.source) || matches(&record.output))
+        .take(if args.limit == 0 { usize::MAX } else { args.limit })
+        .collect();
+
+    if records.is_empty() {
+        if !config.quiet {
+            println!("{}", tr!(
+                "转换历史中没有匹配的记录 ({})",
+                "No matching records in the conversion history ({})",
+                path.display(),
+            ));
+        }
+        return Ok(());
+    }
+    for record in records {
+        println!(
+            "{}  {:<4}  {} -> {}",
+            humantime::format_rfc3339_seconds(record.converted_at()),
+            record.format,
+            record.source.display(),
+            record.output.display()
+        );
+    }
+    Ok(())
+}
+
+/// 执行 `config` 子命令
+///
+/// 在加载配置文件之前执行，配置文件格式错误时仍可以用 `config edit` 或 `config reset` 修复
+pub fn run_config(args: ConfigArgs, cli: Args, ui: &UserInterface) -> Result<(), VideoToAudioError> {
+    let path = Config::resolve_path(cli.config_file.as_ref())?;
+    match args.action {
+        ConfigAction::Show => show_config(&path, cli),
+        ConfigAction::Set { key, value } => {
+            let mut config = Config::load(Some(&path))?;
+            config.set_setting(&key, &value)?;
+            config.save(Some(&path))?;
+            println!("{}", style::success(tr!(
+                "✅ {key} = {}（已写入 {}）",
+                "✅ {key} = {} (written to {})",
+                format_setting(&config.get_setting(&key)?),
+                path.display()
+            )));
+
+            let env_var = Config::setting_env_var(&key);
+            if std::env::var_os(&env_var).is_some() {
+                println!("{}", style::warning(tr!(
+                    "⚠️  环境变量 {env_var} 已设置，运行时会覆盖配置文件中的值",
+                    "⚠️  The environment variable {env_var} is set and overrides the configuration file at run time"
+                )));
+            }
+            Ok(())
+        }
+        ConfigAction::Edit => edit_config(&path),
+        ConfigAction::Reset { key: Some(key), .. } => {
+            let mut config = Config::load(Some(&path))?;
+            config.reset_setting(&key)?;
+            config.save(Some(&path))?;
+            println!("{}", style::success(tr!(
+                "✅ {key} 已恢复为默认值 {}",
+                "✅ {key} reset to the default {}",
+                format_setting(&config.get_setting(&key)?)
+            )));
+            Ok(())
+        }
+        ConfigAction::Reset { key: None, yes } => {
+            if !yes {
+                let prompt = tr!(
+                    "确定要把 {} 恢复为默认设置吗？配置方案和监视规则也会被删除",
+                    "Reset {} to the default settings? Profiles and watch rules will be removed too",
+                    path.display()
+                );
+                if !ui.confirm(&prompt, false)? {
+                    println!("{}", text("已取消", "Cancelled"));
+                    return Ok(());
+                }
+            }
+            Config::default().save(Some(&path))?;
+            println!("{}", style::success(tr!("✅ 已恢复默认设置: {}", "✅ Restored the default settings: {}", path.display())));
+            Ok(())
+        }
+    }
+}
+
+/// 显示每项设置当前生效的值及其来源
+fn show_config(path: &Path, cli: Args) -> Result<(), VideoToAudioError> {
+    let mut config = Config::load(Some(&path.to_path_buf()))?;
+    let env_settings = config.apply_env_overrides()?;
+    let runtime_config = RuntimeConfig::from_args_and_config(cli, config.clone());
+    let mut flagged = config.clone();
+    apply_runtime_settings(&mut flagged, &runtime_config);
+    let defaults = Config::default();
+
+    if path.exists() {
+        println!("{}", tr!("📄 配置文件: {}", "📄 Configuration file: {}", path.display()));
+    } else {
+        println!("{}", tr!(
+            "📄 配置文件: {}（不存在，使用默认设置）",
+            "📄 Configuration file: {} (missing, using the default settings)",
+            path.display()
+        ));
+    }
+    println!();
+    for key in Config::setting_keys() {
+        let value = flagged.get_setting(&key)?;
+        let source = if value != config.get_setting(&key)? {
+            text("命令行", "command line")
+        } else if env_settings.contains(&key) {
+            text("环境变量", "environment")
+        } else if value != defaults.get_setting(&key)? {
+            text("配置文件", "config file")
+        } else {
+            text("默认值", "default")
+        };
+        println!("  {key:<28} = {:<12} ({source})", format_setting(&value));
+    }
+
+    if !config.profiles.is_empty() {
+        let names: Vec<_> = config.profiles.keys().map(String::as_str).collect();
+        println!();
+        println!("{}", tr!("🎛️  配置方案: {}", "🎛️  Profiles: {}", names.join(", ")));
+    }
+    if !config.watch_rules.is_empty() {
+        println!("{}", tr!("👀 监视规则: {} 条", "👀 Watch rules: {}", config.watch_rules.len()));
+    }
+    Ok(())
+}
+
+/// 把命令行参数覆盖的设置写回配置，用于判断设置的来源
+fn apply_runtime_settings(config: &mut Config, runtime_config: &RuntimeConfig) {
+    if let Some(format) = runtime_config.format {
+        if config.get_default_format().ok() != Some(format) {
+            config.set_default_format(format);
+        }
+    }
+    config.default_jobs = runtime_config.jobs;
+    config.adaptive_jobs = runtime_config.adaptive_jobs;
+    config.threads_per_file = runtime_config.threads_per_file;
+    config.nice = runtime_config.nice;
+    config.hwaccel = runtime_config.hwaccel;
+    config.backend = runtime_config.backend;
+    config.skip_existing = runtime_config.skip_existing;
+    config.verbose = runtime_config.verbose;
+    config.quiet = runtime_config.quiet;
+    match &runtime_config.normalize {
+        Some(normalization) if normalization.two_pass => config.normalize_two_pass = true,
+        Some(_) => config.normalize = true,
+        None => {}
+    }
+    config.replay_gain = runtime_config.replay_gain;
+    config.strip_metadata = runtime_config.strip_metadata;
+    config.history = runtime_config.history;
+}
+
+/// 设置值的显示文本，未设置的可选设置显示为 `-`
+fn format_setting(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Null => "-".to_string(),
+        serde_json::Value::String(text) => text.clone(),
+        value => value.to_string(),
+    }
+}
+
+/// 用 $VISUAL 或 $EDITOR 指定的编辑器打开配置文件，编辑后检查格式
+fn edit_config(path: &Path) -> Result<(), VideoToAudioError> {
+    if !path.exists() {
+        Config::default().save(Some(&path.to_path_buf()))?;
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+    let editor_args = shlex::split(&editor)
+        .filter(|args| !args.is_empty())
+        .ok_or_else(|| VideoToAudioError::InvalidInput(tr!("无效的编辑器命令: {editor}", "Invalid editor command: {editor}")))?;
+    let status = std::process::Command::new(&editor_args[0])
+        .args(&editor_args[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| VideoToAudioError::MissingDependency(tr!(
+            "无法启动编辑器 {editor}: {e}，可通过 VISUAL 或 EDITOR 环境变量指定编辑器",
+            "Failed to start the editor {editor}: {e}, set the VISUAL or EDITOR environment variable to choose one"
+        )))?;
+    if !status.success() {
+        return Err(VideoToAudioError::InvalidInput(tr!("编辑器异常退出: {status}", "The editor exited abnormally: {status}")));
+    }
+
+    // 立即检查修改后的配置，而不是等到下次转换时才报错
+    Config::load(Some(&path.to_path_buf()))?;
+    println!("{}", style::success(tr!("✅ 配置文件已保存: {}", "✅ Configuration saved: {}", path.display())));
+    Ok(())
+}
+
+/// 执行 `stats` 子命令
+///
+/// 汇总转换历史，显示总量、格式、平均速度和按月份的统计
+fn run_stats(args: StatsArgs) -> Result<(), VideoToAudioError> {
+    let history = History::load(&History::default_path()?)?;
+    let records = history
+        .records()
+        .iter()
+        .filter(|record| args.since.is_none_or(|since| record.converted_at() >= since));
+    let stats = HistoryStats::from_records(records);
+
+    if stats.files == 0 {
+        println!("{}", text("转换历史中没有记录", "The conversion history is empty"));
+        return Ok(());
+    }
+
+    println!("{}", text("📊 转换统计:", "📊 Conversion statistics:"));
+    println!("{}", tr!("   转换文件: {} 个", "   Files converted: {}", stats.files));
+    println!("{}", tr!("   音频时长: {:.1} 小时", "   Audio duration: {:.1} hours", stats.audio_seconds / 3600.0));
+    println!("{}", tr!(
+        "   输入 → 输出: {} → {}",
+        "   Input → output: {} → {}",
+        format_bytes(stats.input_bytes),
+        format_bytes(stats.output_bytes)
+    ));
+    if let Some(speed) = stats.speed() {
+        println!("{}", tr!("   平均速度: {speed:.1}x 实时", "   Average speed: {speed:.1}x realtime"));
+    }
+    let formats: Vec<_> = stats
+        .formats
+        .iter()
+        .map(|(format, count)| tr!("{format} {count} 个", "{format} {count}"))
+        .collect();
+    println!("{}", tr!("   格式: {}", "   Formats: {}", formats.join(text("，", ", "))));
+
+    println!();
+    println!("{}", text("   按月份:", "   By month:"));
+    for (month, (files, audio_seconds)) in &stats.months {
+        println!("{}", tr!(
+            "   {month}  {files:>5} 个文件  {:>8.1} 小时",
+            "   {month}  {files:>5} files  {:>8.1} hours",
+            audio_seconds / 3600.0
+        ));
+    }
+    Ok(())
+}
+
+/// 执行 `explain` 子命令
+///
+/// 显示错误代码的原因和解决方法，不指定代码时列出全部错误代码
+fn run_explain(args: ExplainArgs) -> Result<(), VideoToAudioError> {
+    let Some(code) = args.code else {
+        println!("{}", text("错误代码:", "Error codes:"));
+        for info in ERROR_CODES {
+            println!("   {}  {}", info.code, info.summary());
+        }
+        println!();
+        println!("{}", text(
+            "运行 video2audio-rs explain <代码> 查看原因和解决方法",
+            "Run video2audio-rs explain <code> for causes and fixes",
+        ));
+        return Ok(());
+    };
+
+    let info = error_codes::lookup(&code).ok_or_else(|| VideoToAudioError::InvalidInput(tr!(
+        "未知的错误代码: {code}，运行 video2audio-rs explain 列出全部错误代码",
+        "Unknown error code: {code}, run video2audio-rs explain to list all error codes"
+    )))?;
+    println!("{} ({}): {}", info.code, info.name, info.summary());
+    println!();
+    println!("{}", text("常见原因:", "Common causes:"));
+    for cause in info.causes() {
+        println!("   • {cause}");
+    }
+    println!();
+    println!("{}", text("解决方法:", "How to fix:"));
+    for fix in info.fixes() {
+        println!("   • {fix}");
+    }
+    Ok(())
+}
+
+/// 执行 `verify` 子命令
+///
+/// 检查镜像输出目录中的音频文件，发现问题时以非零状态码退出
+fn run_verify(args: VerifyArgs, config: &RuntimeConfig, cancel_token: &CancellationToken) -> Result<ExitCode, VideoToAudioError> {
+    let format = AudioFormat::from(args.format);
+    let processor = config
+        .file_processor()
+        .with_preserved_structure(Some(args.source_dir.clone()))
+        .with_excluded_dir(&args.output_dir);
+
+    if !config.quiet {
+        println!("{}", tr!("🔍 正在检查 {} ...", "🔍 Checking {} ...", args.output_dir.display()));
+    }
+    let report = processor.verify_outputs(&args.source_dir, &args.output_dir, format, args.tolerance, cancel_token)?;
+
+    for (output_path, issue) in &report.issues {
+        println!("{}", style::failure(format!("  ❌ {}: {issue}", output_path.display())));
+    }
+    if config.verbose {
+        for source_file in &report.missing {
+            println!("{}", tr!("  ⏳ 尚未转换: {}", "  ⏳ Not converted yet: {}", source_file.display()));
+        }
+    }
+    if !config.quiet {
+        println!();
+        println!("{}", tr!("   已检查: {} 个文件", "   Checked: {} files", report.checked));
+        println!("{}", tr!("   有问题: {} 个文件", "   With problems: {} files", report.issues.len()));
+        if !report.missing.is_empty() {
+            println!("{}", tr!("   尚未转换: {} 个文件", "   Not converted yet: {} files", report.missing.len()));
+        }
+    }
+
+    if !report.issues.is_empty() {
+        return Ok(ExitCode::FAILURE);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// 执行 `bench` 子命令
+///
+/// 依次转换源文件的同一段，并列显示每种格式和音质预设的编码速度和输出大小；全部失败时返回错误
+fn run_bench(args: BenchArgs, config: &RuntimeConfig, cancel_token: &CancellationToken) -> Result<(), VideoToAudioError> {
+    if !args.input.is_file() {
+        return Err(VideoToAudioError::InvalidPath(tr!(
+            "测试文件不存在: {}",
+            "Test file not found: {}",
+            args.input.display()
+        )));
+    }
+    let formats: Vec<AudioFormat> = if args.formats.is_empty() {
+        AudioFormat::all_formats()
+    } else {
+        args.formats.into_iter().map(AudioFormat::from).collect()
+    };
+    let cases = bench_cases(&formats);
+    let processor = config.file_processor();
+
+    let count = cases.len();
+    let input = args.input.display();
+    let length = humantime::format_duration(args.length);
+    println!("{}", tr!(
+        "🏁 截取 {input} 的 {length} 片段，测试 {count} 种编码设置...",
+        "🏁 Testing {count} encoder settings on a {length} segment of {input}..."
+    ));
+    println!();
+    println!("{}", text(
+        "格式   预设      设置       速度   片段大小       码率   每小时约",
+        "Format Preset    Codec     Speed       Size    Bitrate   Per hour",
+    ));
+
+    let mut succeeded = 0;
+    for case in cases {
+        let result = run_case(&processor, &args.input, config.start, args.length, case, cancel_token);
+        if cancel_token.is_cancelled() {
+            return Err(VideoToAudioError::Cancelled);
+        }
+        let name = case.format.extension().to_uppercase();
+        match &result.outcome {
+            Ok(sample) => {
+                succeeded += 1;
+                println!(
+                    "{name:<6} {:<9} {:<6} {:>8} {:>10} {:>10} {:>10}",
+                    case.preset.name(),
+                    result.settings,
+                    format!("{:.1}x", sample.speed()),
+                    format_bytes(sample.output_bytes),
+                    format!("{:.0} kbps", sample.kbps()),
+                    format_bytes(sample.bytes_per_hour()),
+                );
+            }
+            Err(e) => println!(
+                "{}",
+                style::failure(format!("{name:<6} {:<9} {:<6} ❌ {e}", case.preset.name(), result.settings))
+            ),
+        }
+    }
+
+    println!();
+    println!("{}", text(
+        "💡 速度为实时的倍数，越大越快；每小时约为按片段的实际码率推算的一小时音频大小。",
+        "💡 Speed is a multiple of real time, higher is faster; per hour is the size of an hour of audio at the segment's actual bitrate.",
+    ));
+    if succeeded == 0 {
+        return Err(VideoToAudioError::InvalidInput(
+            tr!("所有编码设置都测试失败", "All encoder settings failed")
+        ));
+    }
+    Ok(())
+}
+
+/// 执行 `sync` 子命令
+///
+/// 将输出目录同步为源目录的音频镜像
+fn run_sync(
+    args: SyncArgs,
+    ui: &UserInterface,
+    config: &RuntimeConfig,
+    cancel_token: &CancellationToken,
+) -> Result<ExitCode, VideoToAudioError> {
+    let format = AudioFormat::from(args.format);
+    // 在生成同步计划前锁定，避免按过时的计划重复转换；只显示计划时不创建目录
+    let _output_lock = if args.dry_run {
+        None
+    } else {
+        fs::create_dir_all(&args.output_dir).context(text("创建输出目录", "create output directory"), &args.output_dir)?;
+        Some(lock_output_dir(&args.output_dir, config, cancel_token)?)
+    };
+    let processor = config
+        .file_processor()
+        .with_preserved_structure(Some(args.source_dir.clone()))
+        .with_overwrite_policy(OverwritePolicy::SkipUpToDate)
+        .with_excluded_dir(&args.output_dir);
+
+    let plan = SyncPlan::build(&processor, &args.source_dir, &args.output_dir, format)?;
+
+    if !config.quiet {
+        println!("{}", text("🔁 同步计划:", "🔁 Sync plan:"));
+        println!("{}", tr!("   需要转换: {} 个文件", "   To convert: {} files", plan.to_convert.len()));
+        println!("{}", tr!("   已是最新: {} 个文件", "   Up to date: {} files", plan.up_to_date.len()));
+        println!("{}", tr!("   孤立音频: {} 个文件", "   Orphaned audio: {} files", plan.orphans.len()));
+        println!();
+    }
+
+    if args.dry_run {
+        for file in &plan.to_convert {
+            println!("  + {}", file.display());
+        }
+        if args.delete_orphans {
+            for orphan in &plan.orphans {
+                println!("  - {}", orphan.display());
+            }
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut failure_count = 0;
+    if !plan.to_convert.is_empty() {
+        let options = processor.check_encoder(format)?;
+        fs::create_dir_all(&args.output_dir).context(text("创建输出目录", "create output directory"), &args.output_dir)?;
+        let (success_count, failures) = processor.batch_convert_with_results(
+            &plan.to_convert,
+            &args.output_dir,
+            options,
+            cancel_token,
+            |source_file, result, current, total| {
+                if let Err(e) = result {
+                    // 先结束同一行上的进度显示
+                    if !config.quiet {
+                        println!();
+                    }
+                    ui.show_file_failure(source_file, e);
+                }
+                if !config.quiet {
+                    ui.show_progress(current, total);
+                }
+            },
+        );
+        failure_count = failures;
+
+        if !config.quiet {
+            println!();
+            println!("{}", style::success(tr!("   ✅ 成功: {success_count} 个文件", "   ✅ Succeeded: {success_count} files")));
+            if failure_count > 0 {
+                println!("{}", style::failure(tr!("   ❌ 失败: {failure_count} 个文件", "   ❌ Failed: {failure_count} files")));
+            }
+        }
+    }
+
+    if args.delete_orphans && !plan.orphans.is_empty() {
+        let removed = plan.remove_orphans(&args.output_dir, config.trash)?;
+        if !config.quiet {
+            if config.trash {
+                println!("{}", tr!(
+                    "   🗑️  已移到回收站 {removed} 个孤立音频文件",
+                    "   🗑️  Moved {removed} orphaned audio files to the trash"
+                ));
+            } else {
+                println!("{}", tr!("   🗑️  已删除 {removed} 个孤立音频文件", "   🗑️  Deleted {removed} orphaned audio files"));
+            }
+        }
+    }
+
+    if !config.quiet {
+        println!("{}", style::success(tr!("🎉 同步完成: {}", "🎉 Sync finished: {}", args.output_dir.display())));
+    }
+
+    if config.fail_fast && failure_count > 0 {
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
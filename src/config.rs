@@ -4,10 +4,31 @@
 //! 支持多种运行模式和自定义选项。
 
 use crate::audio_format::AudioFormat;
-use crate::error::{Result, VideoToAudioError};
-use clap::{Parser, ValueEnum};
+use crate::backend::BackendKind;
+use crate::checksums::ChecksumAlgorithm;
+use crate::dedupe::DedupeMode;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::file_processor::{normalize_extension, FileProcessor, HwAccel, OverwritePolicy, DEFAULT_OUTPUT_DIR_NAME, DEFAULT_VIDEO_EXTENSIONS};
+use crate::hooks::{self, HookCommand};
+use crate::i18n::text;
+use crate::i18n::{self, Locale};
+use crate::log_file::{self, LogFile};
+use crate::loudness::{LoudnessTarget, Normalization};
+use crate::managed_ffmpeg;
+use crate::options::{self, ConversionOptions, CoverArt, FormatDefaults, MetadataPolicy, QualityPreset};
+use crate::probe::AudioTrackSelection;
+use crate::progress::ProgressFormat;
+use crate::quarantine::QuarantineMode;
+use crate::scheduler::{ConcurrencyLimits, JobScheduler};
+use crate::selection::{self, FileFilter, SortOrder};
+use crate::tag_pattern::TagPattern;
+use crate::tr;
+use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 
 /// 命令行参数定义
 /// 
@@ -20,6 +41,10 @@ use std::path::PathBuf;
     long_about = "Video2Audio-RS 是一个基于 Rust 开发的高性能批量视频转音频工具。\n支持多种视频格式，利用多核并行处理，提供友好的中文用户界面。"
 )]
 pub struct Args {
+    /// 子命令（未指定时执行常规的批量转换）
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// 源视频文件夹路径
     #[arg(
         short = 's',
@@ -33,10 +58,27 @@ pub struct Args {
         short = 'f',
         long = "format",
         value_enum,
-        help = "指定输出音频格式 [可选值: mp3, aac, opus]"
+        help = "指定输出音频格式 [可选值: mp3, aac, opus, m4b]"
     )]
     pub format: Option<CliAudioFormat>,
 
+    /// 文件列表
+    #[arg(
+        long = "from-file",
+        conflicts_with_all = ["source_dir", "watch"],
+        help = "从列表文件读取要处理的文件（每行一个路径，- 表示标准输入），不扫描目录"
+    )]
+    pub from_file: Option<PathBuf>,
+
+    /// 失败清单
+    #[arg(
+        long = "retry-failed",
+        value_name = "FILE",
+        conflicts_with_all = ["source_dir", "from_file", "watch"],
+        help = "只重新转换失败清单 (failed_files.json) 中的文件，默认沿用清单中的格式并输出到清单所在的目录"
+    )]
+    pub retry_failed: Option<PathBuf>,
+
     /// 输出目录（可选，默认为源目录下的 audio_exports）
     #[arg(
         short = 'o',
@@ -57,6 +99,7 @@ pub struct Args {
     #[arg(
         short = 'v',
         long = "verbose",
+        global = true,
         help = "启用详细输出，显示更多处理信息"
     )]
     pub verbose: bool,
@@ -65,19 +108,101 @@ pub struct Args {
     #[arg(
         short = 'q',
         long = "quiet",
+        global = true,
         help = "启用静默模式，只显示错误信息",
         conflicts_with = "verbose"
     )]
     pub quiet: bool,
 
+    /// 界面语言
+    #[arg(
+        long = "lang",
+        global = true,
+        value_name = "LANG",
+        help = "界面语言：zh-CN 或 en-US（默认使用配置文件中的 language，其次是 LANG 环境变量）"
+    )]
+    pub lang: Option<Locale>,
+
+    /// 诊断日志的过滤规则
+    #[arg(
+        long = "trace",
+        global = true,
+        value_name = "FILTER",
+        num_args = 0..=1,
+        default_missing_value = "video2audio_rs=debug",
+        help = "在标准错误流输出扫描、计划和每个文件转换的诊断日志，FILTER 的语法与 RUST_LOG 相同（默认 video2audio_rs=debug）"
+    )]
+    pub trace: Option<String>,
+
+    /// 禁用彩色输出
+    #[arg(
+        long = "no-color",
+        global = true,
+        help = "禁用彩色输出（也可设置 NO_COLOR 环境变量）"
+    )]
+    pub no_color: bool,
+
+    /// 进度显示方式
+    #[arg(
+        long = "progress",
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with = "tui",
+        help = "进度显示方式：bar 为终端进度条，json 向标准输出逐行写入 JSON 事件（不再输出其他文字）"
+    )]
+    pub progress: Option<CliProgress>,
+
     /// 并行处理线程数
     #[arg(
         short = 'j',
         long = "jobs",
+        global = true,
         help = "指定并行处理的线程数 (默认为 CPU 核心数)"
     )]
     pub jobs: Option<usize>,
 
+    /// 每个 FFmpeg 进程的线程数
+    #[arg(
+        long = "threads-per-file",
+        global = true,
+        help = "限制每个 FFmpeg 进程使用的线程数，总 CPU 占用约为并行数 × 该值"
+    )]
+    pub threads_per_file: Option<usize>,
+
+    /// 低优先级模式
+    #[arg(
+        long = "nice",
+        global = true,
+        help = "以低优先级运行 FFmpeg，减少后台转换对其他程序的影响"
+    )]
+    pub nice: bool,
+
+    /// 硬件加速解码
+    #[arg(
+        long = "hwaccel",
+        value_enum,
+        global = true,
+        help = "解码源视频时使用的硬件加速，不可用时自动改用软件解码 (默认为 none)"
+    )]
+    pub hwaccel: Option<CliHwAccel>,
+
+    /// 根据系统负载调整并发
+    #[arg(
+        long = "adaptive-jobs",
+        global = true,
+        help = "根据系统负载动态减少同时运行的编码任务"
+    )]
+    pub adaptive_jobs: bool,
+
+    /// 转换后端
+    #[arg(
+        long = "backend",
+        value_enum,
+        global = true,
+        help = "指定转换后端 (默认为 ffmpeg)"
+    )]
+    pub backend: Option<CliBackend>,
+
     /// 跳过已存在的文件
     #[arg(
         long = "skip-existing",
@@ -85,6 +210,499 @@ pub struct Args {
     )]
     pub skip_existing: bool,
 
+    /// 增量模式
+    #[arg(
+        long = "incremental",
+        help = "增量模式: 输出文件已存在且比源文件新时跳过转换"
+    )]
+    pub incremental: bool,
+
+    /// 跳过磁盘空间检查
+    #[arg(
+        long = "skip-space-check",
+        global = true,
+        help = "跳过开始前的磁盘空间检查"
+    )]
+    pub skip_space_check: bool,
+
+    /// 预检
+    #[arg(
+        long = "preflight",
+        help = "转换开始前快速检查全部输入文件（大小为 0、无法读取、文件头损坏或没有音频流），有问题的文件提前报告并跳过"
+    )]
+    pub preflight: bool,
+
+    /// 只显示转换计划
+    #[arg(
+        long = "dry-run",
+        help = "只显示转换计划：每个文件的预计输出大小、总大小，以及按一次短时间试转换估算的耗时，不转换任何文件"
+    )]
+    pub dry_run: bool,
+
+    /// 先试听预览
+    #[arg(
+        long = "preview",
+        help = "先只转换第一个文件开头的 30 秒并播放（ffplay 或系统默认播放器），确认音质后再转换全部文件；不在终端中运行时只生成预览"
+    )]
+    pub preview: bool,
+
+    /// 处理顺序
+    #[arg(
+        long = "order",
+        value_enum,
+        help = "文件的处理顺序 (默认为扫描顺序)"
+    )]
+    pub order: Option<CliSortOrder>,
+
+    /// 最大的文件优先
+    #[arg(
+        long = "largest-first",
+        conflicts_with = "newest_first",
+        help = "最大的文件优先处理（与 --order duration 一起使用时最长的文件优先）"
+    )]
+    pub largest_first: bool,
+
+    /// 最新的文件优先
+    #[arg(
+        long = "newest-first",
+        help = "最近修改的文件优先处理"
+    )]
+    pub newest_first: bool,
+
+    /// 包括纯音频文件
+    #[arg(
+        long = "include-audio-inputs",
+        global = true,
+        help = "扫描时也包括 .m4a/.mka/.weba/.flac 等纯音频文件，把混合目录统一转换为目标格式"
+    )]
+    pub include_audio_inputs: bool,
+
+    /// 最小文件大小
+    #[arg(
+        long = "min-size",
+        global = true,
+        value_parser = selection::parse_size,
+        help = "跳过小于该大小的文件，如 10MB"
+    )]
+    pub min_size: Option<u64>,
+
+    /// 最大文件大小
+    #[arg(
+        long = "max-size",
+        global = true,
+        value_parser = selection::parse_size,
+        help = "跳过大于该大小的文件，如 4GB"
+    )]
+    pub max_size: Option<u64>,
+
+    /// 最短媒体时长
+    #[arg(
+        long = "min-duration",
+        global = true,
+        value_parser = selection::parse_duration,
+        help = "跳过时长短于该值的文件，如 10s（需要 ffprobe）"
+    )]
+    pub min_duration: Option<Duration>,
+
+    /// 最长媒体时长
+    #[arg(
+        long = "max-duration",
+        global = true,
+        value_parser = selection::parse_duration,
+        help = "跳过时长超过该值的文件，如 3h（需要 ffprobe）"
+    )]
+    pub max_duration: Option<Duration>,
+
+    /// 只处理在该时间之后修改的文件
+    #[arg(
+        long = "since",
+        global = true,
+        value_parser = selection::parse_time,
+        help = "只处理在该时间之后修改的文件，如 2024-01-01 或 7d"
+    )]
+    pub since: Option<SystemTime>,
+
+    /// 只处理在该时间之前修改的文件
+    #[arg(
+        long = "until",
+        global = true,
+        value_parser = selection::parse_time,
+        help = "只处理在该时间之前修改的文件，如 2024-06-30 或 30d"
+    )]
+    pub until: Option<SystemTime>,
+
+    /// 只处理前 N 个文件
+    #[arg(
+        long = "limit",
+        help = "只处理前 N 个文件（在筛选和排序之后）"
+    )]
+    pub limit: Option<usize>,
+
+    /// 随机抽取 N 个文件
+    #[arg(
+        long = "sample",
+        help = "随机抽取 N 个文件处理，适合在大型媒体库上试用设置"
+    )]
+    pub sample: Option<usize>,
+
+    /// 挑选要转换的文件
+    #[arg(
+        long = "pick",
+        help = "转换前在列表中挑选要转换的文件，可输入关键词筛选（需要在终端中运行）"
+    )]
+    pub pick: bool,
+
+    /// 显示终端仪表盘
+    #[arg(
+        long = "tui",
+        help = "批量转换时显示全屏终端仪表盘，按 p 暂停/继续、q 取消（需启用 tui 特性）"
+    )]
+    pub tui: bool,
+
+    /// 提取全部音轨
+    #[arg(
+        long = "all-audio-tracks",
+        global = true,
+        help = "多音轨源文件的每条音轨分别输出一个文件，如 movie.track2.eng.mp3（需要 ffprobe）"
+    )]
+    pub all_audio_tracks: bool,
+
+    /// 封面
+    #[arg(
+        long = "cover",
+        global = true,
+        value_name = "COVER",
+        value_parser = options::parse_cover,
+        help = "嵌入输出文件的封面：图片文件路径、frame:00:00:05（截取视频的一帧）或 attached（使用源文件中的封面），只对 mp3 和 m4b 格式生效"
+    )]
+    pub cover: Option<CoverArt>,
+
+    /// 片段开始时间
+    #[arg(
+        long = "start",
+        global = true,
+        value_parser = options::parse_timestamp,
+        conflicts_with = "split_chapters",
+        help = "只转换从该时间开始的片段，如 00:05:00 或 300"
+    )]
+    pub start: Option<Duration>,
+
+    /// 片段结束时间
+    #[arg(
+        long = "end",
+        global = true,
+        value_parser = options::parse_timestamp,
+        conflicts_with = "split_chapters",
+        help = "只转换到该时间为止的片段（相对于源文件开头），如 01:10:00"
+    )]
+    pub end: Option<Duration>,
+
+    /// 按章节拆分
+    #[arg(
+        long = "split-chapters",
+        global = true,
+        conflicts_with = "all_audio_tracks",
+        help = "包含章节的源文件每个章节输出一个文件，如 concert/01 - Intro.mp3，并写入章节标题（需要 ffprobe）"
+    )]
+    pub split_chapters: bool,
+
+    /// 响度标准化
+    #[arg(
+        long = "normalize",
+        global = true,
+        help = "使用 EBU R128 (loudnorm) 把输出调整到一致的响度，目标值可在配置文件的 loudness_target 中设置"
+    )]
+    pub normalize: bool,
+
+    /// 双遍响度标准化
+    #[arg(
+        long = "normalize-two-pass",
+        global = true,
+        help = "先分析整个文件再标准化，结果更准确但每个文件需要多解码一次（隐含 --normalize）"
+    )]
+    pub normalize_two_pass: bool,
+
+    /// 自定义元数据标签
+    #[arg(
+        long = "tag",
+        global = true,
+        value_name = "KEY=VALUE",
+        value_parser = options::parse_tag,
+        help = "写入每个输出文件的元数据标签，可重复使用，如 --tag artist=\"Foo\" --tag album=\"Bar\""
+    )]
+    pub tags: Vec<(String, String)>,
+
+    /// 文件名标签模板
+    #[arg(
+        long = "tag-pattern",
+        global = true,
+        value_name = "PATTERN",
+        value_parser = TagPattern::parse,
+        help = "按模板解析文件名并写入标签，如 \"{artist} - {title}\"，会列出不符合模板的文件"
+    )]
+    pub tag_pattern: Option<TagPattern>,
+
+    /// 按目录生成专辑
+    #[arg(
+        long = "album-per-dir",
+        global = true,
+        help = "把每个源目录视为一张专辑：目录名写入 album 标签，按文件名顺序写入音轨号"
+    )]
+    pub album_per_dir: bool,
+
+    /// 不复制源文件的元数据
+    #[arg(
+        long = "no-metadata",
+        global = true,
+        help = "不把源文件的标题、艺术家、日期等元数据复制到输出文件"
+    )]
+    pub no_metadata: bool,
+
+    /// 自定义音频滤镜
+    #[arg(
+        long = "af",
+        global = true,
+        value_name = "FILTERGRAPH",
+        value_parser = clap::builder::NonEmptyStringValueParser::new(),
+        help = "追加自定义的 FFmpeg 音频滤镜链，如 \"highpass=f=80,dynaudnorm\"（在静音裁剪之后、响度标准化之前执行）"
+    )]
+    pub audio_filter: Option<String>,
+
+    /// 音质预设
+    #[arg(
+        long = "quality",
+        global = true,
+        value_enum,
+        value_name = "PRESET",
+        help = "音质预设：high 为各格式的默认音质，standard 兼顾音质和体积，small 体积最小（适合语音）"
+    )]
+    pub quality: Option<CliQuality>,
+
+    /// 裁剪首尾静音
+    #[arg(
+        long = "trim-silence",
+        global = true,
+        help = "去除开头和结尾的静音（低于 -50dB，两端各保留 0.5 秒），适合屏幕录像"
+    )]
+    pub trim_silence: bool,
+
+    /// 写入增益标签
+    #[arg(
+        long = "replaygain",
+        global = true,
+        help = "转换后分析每个输出文件，写入 ReplayGain 标签（Opus 为 R128 标签），音频数据保持不变"
+    )]
+    pub replay_gain: bool,
+
+    /// 按编号选择音轨
+    #[arg(
+        long = "audio-track",
+        global = true,
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["all_audio_tracks", "audio_lang"],
+        help = "提取第 N 条音轨（从 1 开始），源文件没有该音轨时转换失败（需要 ffprobe）"
+    )]
+    pub audio_track: Option<u64>,
+
+    /// 按语言选择音轨
+    #[arg(
+        long = "audio-lang",
+        global = true,
+        conflicts_with = "all_audio_tracks",
+        help = "提取语言标签匹配的音轨，如 jpn、eng，源文件没有该语言时转换失败（需要 ffprobe）"
+    )]
+    pub audio_lang: Option<String>,
+
+    /// 失败重试次数
+    #[arg(
+        long = "retries",
+        default_value_t = 0,
+        help = "失败的文件在批次末尾重新排队重试的最大次数"
+    )]
+    pub retries: usize,
+
+    /// 重试时使用的备用格式
+    #[arg(
+        long = "fallback-format",
+        value_enum,
+        requires = "retries",
+        help = "重试失败文件时改用的音频格式，例如 AAC 复制失败后改用 MP3 重新编码"
+    )]
+    pub fallback_format: Option<CliAudioFormat>,
+
+    /// 快速失败模式
+    #[arg(
+        long = "fail-fast",
+        help = "任何文件失败后立即中止批处理并以非零状态码退出"
+    )]
+    pub fail_fast: bool,
+
+    /// 继续上次中断的批处理
+    #[arg(
+        long = "resume",
+        help = "根据输出目录中的状态日志 (.v2a-state.json) 继续上次中断的批处理"
+    )]
+    pub resume: bool,
+
+    /// 生成播放列表
+    #[arg(
+        long = "playlist",
+        help = "批处理结束后在输出目录中写入列出全部输出文件的 audio_exports.m3u8 播放列表"
+    )]
+    pub playlist: bool,
+
+    /// 生成校验和清单
+    #[arg(
+        long = "checksums",
+        value_enum,
+        value_name = "ALGORITHM",
+        help = "每个文件转换完成后把校验和追加到输出目录中的清单（sha256 写入 SHA256SUMS）"
+    )]
+    pub checksums: Option<CliChecksum>,
+
+    /// 转换报告路径
+    #[arg(
+        long = "report",
+        value_name = "FILE",
+        help = "批处理结束后写入每个文件的转换结果，扩展名为 .csv 时写入 CSV，否则写入 JSON"
+    )]
+    pub report: Option<PathBuf>,
+
+    /// 日志文件路径
+    #[arg(
+        long = "log-file",
+        global = true,
+        value_name = "FILE",
+        help = "把每个文件的开始、完成、失败和 FFmpeg 错误输出写入带时间戳的日志文件，与终端输出级别无关"
+    )]
+    pub log_file: Option<PathBuf>,
+
+    /// 日志文件轮转的大小上限 (MB)
+    #[arg(
+        long = "log-max-size",
+        global = true,
+        default_value_t = log_file::DEFAULT_LOG_MAX_SIZE_MB,
+        value_name = "MB",
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "日志文件超过该大小后轮转为 .1、.2、.3 旧日志"
+    )]
+    pub log_max_size: u64,
+
+    /// 批处理期间阻止系统休眠
+    #[arg(
+        long = "inhibit-sleep",
+        conflicts_with = "no_inhibit_sleep",
+        help = "批处理期间阻止系统休眠（交互式运行时默认启用，--batch 模式下需要显式指定）"
+    )]
+    pub inhibit_sleep: bool,
+
+    /// 批处理期间允许系统休眠
+    #[arg(long = "no-inhibit-sleep", help = "批处理期间不阻止系统休眠")]
+    pub no_inhibit_sleep: bool,
+
+    /// 输出目录被锁定时等待
+    #[arg(
+        long = "wait-for-lock",
+        global = true,
+        help = "输出目录正被另一个实例处理时等待其完成，而不是直接退出"
+    )]
+    pub wait_for_lock: bool,
+
+    /// 删除文件时移到回收站
+    #[arg(
+        long = "trash",
+        global = true,
+        help = "clean 和 sync --delete-orphans 删除文件时移到系统回收站，而不是永久删除"
+    )]
+    pub trash: bool,
+
+    /// 结束后发送桌面通知
+    #[arg(long = "notify", help = "批处理结束后弹出显示成功和失败文件数的桌面通知")]
+    pub notify: bool,
+
+    /// 转换成功后执行的命令
+    #[arg(
+        long = "on-success",
+        value_name = "COMMAND",
+        value_parser = hooks::parse_hook,
+        help = "每个文件转换成功后执行的命令，可使用 {input}、{output}、{format} 占位符，如 'rclone copy {output} remote:audio'"
+    )]
+    pub on_success: Option<HookCommand>,
+
+    /// 转换失败后执行的命令
+    #[arg(
+        long = "on-failure",
+        value_name = "COMMAND",
+        value_parser = hooks::parse_hook,
+        help = "每个文件转换失败后执行的命令，可使用 {input}、{format}、{error} 占位符"
+    )]
+    pub on_failure: Option<HookCommand>,
+
+    /// 跳过转换历史中已转换过的文件
+    #[arg(
+        long = "skip-converted",
+        help = "跳过转换历史中已转换为同一格式的文件（按内容识别，即使输出文件已被移走或源文件已改名）"
+    )]
+    pub skip_converted: bool,
+
+    /// 重复源文件的处理方式
+    #[arg(
+        long = "dedupe",
+        value_enum,
+        value_name = "MODE",
+        help = "内容相同的源文件只转换一个：link 把其余文件的输出硬链接到它，skip 只在报告中注明"
+    )]
+    pub dedupe: Option<CliDedupe>,
+
+    /// 损坏源文件的隔离方式
+    #[arg(
+        long = "quarantine",
+        value_enum,
+        value_name = "MODE",
+        help = "把判定为损坏的源文件隔离到输出目录的 corrupt/ 文件夹：move 移动源文件，link 创建符号链接，并在报告中注明"
+    )]
+    pub quarantine: Option<CliQuarantine>,
+
+    /// 不记录转换历史
+    #[arg(long = "no-history", help = "本次运行不把成功的转换记录到转换历史")]
+    pub no_history: bool,
+
+    /// 忽略源目录中的按目录配置
+    #[arg(long = "no-dir-config", help = "忽略源目录中的 .video2audio.toml，所有文件使用相同的转换设置")]
+    pub no_dir_config: bool,
+
+    /// 监视模式
+    #[arg(
+        long = "watch",
+        help = "处理完现有文件后持续监视源目录，自动转换新增或修改的视频"
+    )]
+    pub watch: bool,
+
+    /// 监视模式的防抖时间（秒）
+    #[arg(
+        long = "watch-debounce",
+        default_value_t = 2,
+        value_name = "SECS",
+        help = "文件停止变化多少秒后才开始转换，避免转换仍在写入的录像"
+    )]
+    pub watch_debounce: u64,
+
+    /// HTTP API 服务监听地址
+    #[arg(
+        long = "serve",
+        value_name = "ADDR",
+        help = "以 HTTP API 服务模式运行，监听指定地址 (如 127.0.0.1:8080)，通过 REST 接口提交转换任务"
+    )]
+    pub serve: Option<String>,
+
+    /// IPC 模式
+    #[arg(
+        long = "ipc",
+        conflicts_with = "serve",
+        help = "以 JSON Lines 协议通过标准输入输出与图形前端通信"
+    )]
+    pub ipc: bool,
+
     /// 显示支持的格式列表
     #[arg(
         long = "list-formats",
@@ -92,10 +710,20 @@ pub struct Args {
     )]
     pub list_formats: bool,
 
+    /// 使用配置文件中的命名配置方案
+    #[arg(
+        long = "profile",
+        global = true,
+        value_name = "NAME",
+        help = "使用配置文件 profiles 中的命名配置方案（格式、码率、滤镜、输出目录等），命令行参数优先"
+    )]
+    pub profile: Option<String>,
+
     /// 配置文件路径
     #[arg(
         short = 'c',
         long = "config",
+        global = true,
         help = "指定配置文件路径"
     )]
     pub config_file: Option<PathBuf>,
@@ -106,35 +734,523 @@ pub struct Args {
         help = "将当前设置保存为默认配置"
     )]
     pub save_config: bool,
+
+    /// 把 JSON 配置文件迁移为 TOML
+    #[arg(
+        long = "migrate-config",
+        help = "把 JSON 配置文件（默认为 config.json）转换为同目录下的 config.toml，原文件重命名为 .json.bak"
+    )]
+    pub migrate_config: bool,
+}
+
+/// 子命令定义
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// 将输出目录同步为源目录的音频镜像
+    ///
+    /// 保留目录结构，只转换新增或修改过的视频，可选删除源文件已不存在的音频文件
+    Sync(SyncArgs),
+
+    /// 把目录中的全部视频合并为一个音频文件
+    ///
+    /// 按文件名排序后依次拼接，并在每个源文件的起点插入章节标记
+    Merge(MergeArgs),
+
+    /// 为已有的音频文件写入标签和封面
+    ///
+    /// 使用全局的 --tag、--tag-pattern 和 --cover，--tag-pattern 按音频文件名解析
+    Tag(TagArgs),
+
+    /// 检查镜像输出目录中的音频文件
+    ///
+    /// 比较每个输出文件与源文件的时长，并完整解码一遍，找出截断或损坏的文件
+    Verify(VerifyArgs),
+
+    /// 比较各种编码设置
+    ///
+    /// 把源文件的一小段依次转换为每种格式的每个音质预设，并列显示编码速度和输出大小
+    Bench(BenchArgs),
+
+    /// 清理输出目录
+    ///
+    /// 删除源文件已不存在的孤立音频、中断的转换留下的临时文件和大小为零的音频文件
+    Clean(CleanArgs),
+
+    /// 查询转换历史
+    ///
+    /// 按时间倒序列出记录在配置目录中的成功转换
+    History(HistoryArgs),
+
+    /// 统计转换历史
+    ///
+    /// 显示转换的文件数、音频总时长、使用的格式、平均速度和按月份的统计
+    Stats(StatsArgs),
+
+    /// 查看或修改配置文件
+    ///
+    /// 显示当前生效的设置及其来源，或修改、编辑、重置配置文件中的设置
+    Config(ConfigArgs),
+
+    /// 解释错误代码
+    ///
+    /// 显示错误代码（如 E010）的常见原因和解决方法，不指定代码时列出全部错误代码
+    Explain(ExplainArgs),
+
+    /// 以守护进程方式运行
+    ///
+    /// 按配置文件中的 watch_rules 同时监视多个目录，收到 SIGTERM 或 Ctrl+C 时安全退出
+    Daemon,
+
+    /// 下载静态编译的 FFmpeg
+    ///
+    /// 下载到应用数据目录并校验 SHA-256，系统中没有安装 FFmpeg 时自动使用
+    #[cfg(feature = "ffmpeg-download")]
+    DownloadFfmpeg(DownloadFfmpegArgs),
+}
+
+/// `download-ffmpeg` 子命令参数
+#[cfg(feature = "ffmpeg-download")]
+#[derive(clap::Args, Debug)]
+pub struct DownloadFfmpegArgs {
+    /// 重新下载
+    #[arg(long = "force", help = "即使已经下载过也重新下载")]
+    pub force: bool,
+}
+
+/// `merge` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct MergeArgs {
+    /// 源视频文件夹路径
+    #[arg(short = 's', long = "source", help = "要合并的视频所在目录")]
+    pub source_dir: PathBuf,
+
+    /// 输出文件路径
+    #[arg(
+        short = 'o',
+        long = "output",
+        help = "合并后的音频文件路径，没有扩展名时按格式添加"
+    )]
+    pub output: PathBuf,
+
+    /// 目标音频格式
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        help = "输出音频格式 [可选值: mp3, aac, opus, m4b]"
+    )]
+    pub format: CliAudioFormat,
+}
+
+/// `tag` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct TagArgs {
+    /// 要写入标签的音频文件
+    #[arg(required = true, value_name = "FILE", help = "要写入标签的音频文件")]
+    pub files: Vec<PathBuf>,
+}
+
+/// `history` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct HistoryArgs {
+    /// 筛选关键词
+    #[arg(
+        long = "search",
+        value_name = "TEXT",
+        help = "只列出源文件或输出文件路径中包含该文本的记录（不区分大小写）"
+    )]
+    pub search: Option<String>,
+
+    /// 最多列出的记录数
+    #[arg(long = "limit", default_value_t = 20, help = "最多列出的记录数，0 表示全部")]
+    pub limit: usize,
+}
+
+/// `stats` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct StatsArgs {
+    /// 统计的起始时间
+    #[arg(
+        long = "since",
+        value_name = "TIME",
+        value_parser = selection::parse_time,
+        help = "只统计此时间之后的转换（如 2024-01-01 或 30d）"
+    )]
+    pub since: Option<SystemTime>,
+}
+
+/// `explain` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct ExplainArgs {
+    /// 错误代码
+    #[arg(value_name = "CODE", help = "错误代码，如 E010（不区分大小写）；不指定时列出全部错误代码")]
+    pub code: Option<String>,
+}
+
+/// `config` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct ConfigArgs {
+    /// 要执行的操作
+    #[command(subcommand)]
+    pub action: ConfigAction,
+}
+
+/// `config` 子命令的操作
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// 显示当前生效的设置及其来源（默认值、配置文件、环境变量或命令行）
+    Show,
+
+    /// 修改配置文件中的一项设置
+    ///
+    /// 嵌套的设置用 `.` 连接，如 loudness_target.integrated；值为 null 时清除可选的设置
+    Set {
+        /// 设置名，如 default_format
+        key: String,
+
+        /// 新的值
+        #[arg(allow_negative_numbers = true)]
+        value: String,
+    },
+
+    /// 用 $VISUAL 或 $EDITOR 指定的编辑器打开配置文件
+    Edit,
+
+    /// 把配置文件恢复为默认设置
+    Reset {
+        /// 只恢复这一项设置，未指定时恢复全部设置（包括配置方案和监视规则）
+        key: Option<String>,
+
+        /// 恢复全部设置时不再确认
+        #[arg(short = 'y', long = "yes")]
+        yes: bool,
+    },
+}
+
+/// `clean` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct CleanArgs {
+    /// 源视频文件夹路径
+    #[arg(short = 's', long = "source", help = "源视频目录")]
+    pub source_dir: PathBuf,
+
+    /// 镜像输出目录
+    #[arg(short = 'o', long = "output", help = "要清理的音频输出目录")]
+    pub output_dir: PathBuf,
+
+    /// 输出音频格式
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        help = "输出音频格式，只有该格式的文件会被视为孤立文件 [可选值: mp3, aac, opus, m4b]"
+    )]
+    pub format: CliAudioFormat,
+
+    /// 仅显示清理计划
+    #[arg(
+        long = "dry-run",
+        help = "只列出将要删除的文件，不做任何修改"
+    )]
+    pub dry_run: bool,
+}
+
+/// `verify` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct VerifyArgs {
+    /// 源视频文件夹路径
+    #[arg(short = 's', long = "source", help = "源视频目录")]
+    pub source_dir: PathBuf,
+
+    /// 镜像输出目录
+    #[arg(short = 'o', long = "output", help = "与源目录结构对应的音频输出目录")]
+    pub output_dir: PathBuf,
+
+    /// 输出音频格式
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        help = "输出音频格式 [可选值: mp3, aac, opus, m4b]"
+    )]
+    pub format: CliAudioFormat,
+
+    /// 允许的时长误差
+    #[arg(
+        long = "tolerance",
+        default_value = "1s",
+        value_parser = selection::parse_duration,
+        help = "输出与源文件时长允许的误差，如 1s、500ms"
+    )]
+    pub tolerance: std::time::Duration,
+}
+
+/// `bench` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct BenchArgs {
+    /// 测试用的源文件
+    #[arg(short = 'i', long = "input", help = "测试用的视频文件，从开头（或 --start 指定的时间）截取一段转换")]
+    pub input: PathBuf,
+
+    /// 测试片段长度
+    #[arg(
+        long = "length",
+        default_value = "30s",
+        value_parser = selection::parse_duration,
+        help = "测试片段的长度，如 30s、2m"
+    )]
+    pub length: std::time::Duration,
+
+    /// 要测试的格式
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        value_delimiter = ',',
+        help = "只测试这些格式，可用逗号分隔多个 [可选值: mp3, aac, opus, m4b]；默认测试全部格式"
+    )]
+    pub formats: Vec<CliAudioFormat>,
+}
+
+/// `sync` 子命令参数
+#[derive(clap::Args, Debug)]
+pub struct SyncArgs {
+    /// 源视频文件夹路径
+    #[arg(short = 's', long = "source", help = "要镜像的源视频目录")]
+    pub source_dir: PathBuf,
+
+    /// 镜像输出目录
+    #[arg(short = 'o', long = "output", help = "音频镜像输出目录")]
+    pub output_dir: PathBuf,
+
+    /// 目标音频格式
+    #[arg(
+        short = 'f',
+        long = "format",
+        value_enum,
+        help = "输出音频格式 [可选值: mp3, aac, opus, m4b]"
+    )]
+    pub format: CliAudioFormat,
+
+    /// 删除孤立的音频文件
+    #[arg(
+        long = "delete-orphans",
+        help = "删除源视频已不存在的音频文件"
+    )]
+    pub delete_orphans: bool,
+
+    /// 仅显示同步计划
+    #[arg(
+        long = "dry-run",
+        help = "只显示将要转换和删除的文件，不做任何修改"
+    )]
+    pub dry_run: bool,
+}
+
+/// 命令行音频格式枚举
+/// 
+/// 用于 clap 的 ValueEnum，支持命令行参数解析
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliAudioFormat {
+    /// MP3 格式
+    Mp3,
+    /// AAC 格式
+    Aac,
+    /// Opus 格式
+    Opus,
+    /// M4B 有声书格式
+    M4b,
+}
+
+impl From<CliAudioFormat> for AudioFormat {
+    fn from(cli_format: CliAudioFormat) -> Self {
+        match cli_format {
+            CliAudioFormat::Mp3 => AudioFormat::Mp3,
+            CliAudioFormat::Aac => AudioFormat::AacCopy,
+            CliAudioFormat::Opus => AudioFormat::Opus,
+            CliAudioFormat::M4b => AudioFormat::M4b,
+        }
+    }
+}
+
+/// 命令行硬件加速枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliHwAccel {
+    /// 由 FFmpeg 自动选择
+    Auto,
+    /// macOS VideoToolbox
+    Videotoolbox,
+    /// Linux VA-API
+    Vaapi,
+    /// NVIDIA CUDA
+    Cuda,
+    /// 使用软件解码
+    None,
+}
+
+impl From<CliHwAccel> for Option<HwAccel> {
+    fn from(cli_hwaccel: CliHwAccel) -> Self {
+        match cli_hwaccel {
+            CliHwAccel::Auto => Some(HwAccel::Auto),
+            CliHwAccel::Videotoolbox => Some(HwAccel::VideoToolbox),
+            CliHwAccel::Vaapi => Some(HwAccel::Vaapi),
+            CliHwAccel::Cuda => Some(HwAccel::Cuda),
+            CliHwAccel::None => None,
+        }
+    }
+}
+
+/// 命令行处理顺序枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliSortOrder {
+    /// 按文件路径
+    Name,
+    /// 按文件大小
+    Size,
+    /// 按修改时间
+    Mtime,
+    /// 按媒体时长
+    Duration,
+}
+
+impl From<CliSortOrder> for SortOrder {
+    fn from(cli_order: CliSortOrder) -> Self {
+        match cli_order {
+            CliSortOrder::Name => SortOrder::Name,
+            CliSortOrder::Size => SortOrder::Size,
+            CliSortOrder::Mtime => SortOrder::Mtime,
+            CliSortOrder::Duration => SortOrder::Duration,
+        }
+    }
+}
+
+/// 命令行校验和算法枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliChecksum {
+    /// SHA-256
+    Sha256,
+}
+
+impl From<CliChecksum> for ChecksumAlgorithm {
+    fn from(cli_checksum: CliChecksum) -> Self {
+        match cli_checksum {
+            CliChecksum::Sha256 => ChecksumAlgorithm::Sha256,
+        }
+    }
+}
+
+/// 命令行音质预设枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliQuality {
+    /// 高音质
+    High,
+    /// 标准
+    Standard,
+    /// 小体积
+    Small,
+}
+
+impl From<CliQuality> for QualityPreset {
+    fn from(cli_quality: CliQuality) -> Self {
+        match cli_quality {
+            CliQuality::High => QualityPreset::High,
+            CliQuality::Standard => QualityPreset::Standard,
+            CliQuality::Small => QualityPreset::Small,
+        }
+    }
+}
+
+/// 命令行进度显示方式枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliProgress {
+    /// 终端进度条
+    Bar,
+    /// JSON Lines 事件
+    Json,
+}
+
+impl From<CliProgress> for ProgressFormat {
+    fn from(cli_progress: CliProgress) -> Self {
+        match cli_progress {
+            CliProgress::Bar => ProgressFormat::Bar,
+            CliProgress::Json => ProgressFormat::Json,
+        }
+    }
+}
+
+/// 命令行重复文件处理方式枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliDedupe {
+    /// 硬链接到已转换的输出
+    Link,
+    /// 只在报告中注明
+    Skip,
+}
+
+impl From<CliDedupe> for DedupeMode {
+    fn from(cli_dedupe: CliDedupe) -> Self {
+        match cli_dedupe {
+            CliDedupe::Link => DedupeMode::Link,
+            CliDedupe::Skip => DedupeMode::Skip,
+        }
+    }
+}
+
+/// 命令行损坏文件隔离方式枚举
+#[derive(ValueEnum, Clone, Debug)]
+pub enum CliQuarantine {
+    /// 移动到隔离文件夹
+    Move,
+    /// 在隔离文件夹中创建符号链接
+    Link,
 }
 
-/// 命令行音频格式枚举
-/// 
-/// 用于 clap 的 ValueEnum，支持命令行参数解析
+impl From<CliQuarantine> for QuarantineMode {
+    fn from(cli_quarantine: CliQuarantine) -> Self {
+        match cli_quarantine {
+            CliQuarantine::Move => QuarantineMode::Move,
+            CliQuarantine::Link => QuarantineMode::Link,
+        }
+    }
+}
+
+/// 命令行转换后端枚举
 #[derive(ValueEnum, Clone, Debug)]
-pub enum CliAudioFormat {
-    /// MP3 格式
-    Mp3,
-    /// AAC 格式
-    Aac,
-    /// Opus 格式
-    Opus,
+pub enum CliBackend {
+    /// FFmpeg 命令行
+    Ffmpeg,
+    /// Symphonia 纯 Rust 音频流提取
+    #[cfg(feature = "symphonia")]
+    Symphonia,
+    /// GStreamer 进程内转换
+    #[cfg(feature = "gstreamer")]
+    Gstreamer,
 }
 
-impl From<CliAudioFormat> for AudioFormat {
-    fn from(cli_format: CliAudioFormat) -> Self {
-        match cli_format {
-            CliAudioFormat::Mp3 => AudioFormat::Mp3,
-            CliAudioFormat::Aac => AudioFormat::AacCopy,
-            CliAudioFormat::Opus => AudioFormat::Opus,
+impl From<CliBackend> for BackendKind {
+    fn from(cli_backend: CliBackend) -> Self {
+        match cli_backend {
+            CliBackend::Ffmpeg => BackendKind::Ffmpeg,
+            #[cfg(feature = "symphonia")]
+            CliBackend::Symphonia => BackendKind::Symphonia,
+            #[cfg(feature = "gstreamer")]
+            CliBackend::Gstreamer => BackendKind::Gstreamer,
         }
     }
 }
 
+/// 通过环境变量覆盖设置时使用的前缀，如 `VIDEO2AUDIO_DEFAULT_FORMAT=opus`
+pub const CONFIG_ENV_PREFIX: &str = "VIDEO2AUDIO_";
+
+/// 不能用 `config set` 或环境变量修改的列表设置
+const LIST_SETTINGS: &[&str] = &["recent_source_dirs", "watch_rules", "profiles", "formats"];
+
 /// 程序配置结构
 /// 
-/// 包含所有可配置的程序选项，支持序列化和反序列化
+/// 包含所有可配置的程序选项，支持序列化和反序列化。
+/// 配置文件中缺失的字段使用默认值，因此旧版本的配置文件仍可加载。
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct Config {
     /// 默认音频格式
     pub default_format: String,
@@ -142,6 +1258,21 @@ pub struct Config {
     /// 默认并行线程数
     pub default_jobs: Option<usize>,
     
+    /// 根据系统负载调整并发
+    pub adaptive_jobs: bool,
+    
+    /// 每个 FFmpeg 进程的线程数
+    pub threads_per_file: Option<usize>,
+    
+    /// 以低优先级运行 FFmpeg
+    pub nice: bool,
+    
+    /// 硬件加速解码
+    pub hwaccel: Option<HwAccel>,
+    
+    /// 转换后端
+    pub backend: BackendKind,
+    
     /// 是否跳过已存在的文件
     pub skip_existing: bool,
     
@@ -159,6 +1290,105 @@ pub struct Config {
     
     /// 进度显示样式
     pub progress_style: String,
+    
+    /// 守护进程模式的监视规则
+    pub watch_rules: Vec<WatchRule>,
+    
+    /// 是否默认启用响度标准化
+    pub normalize: bool,
+    
+    /// 响度标准化是否使用双遍模式
+    pub normalize_two_pass: bool,
+    
+    /// 响度标准化的目标
+    pub loudness_target: LoudnessTarget,
+    
+    /// 是否默认写入增益标签
+    pub replay_gain: bool,
+    
+    /// 是否默认不复制源文件的元数据
+    pub strip_metadata: bool,
+    
+    /// 是否记录转换历史
+    pub history: bool,
+    
+    /// 在默认列表之外额外扫描的视频扩展名，如 `["mts", "m2ts", "vob"]`
+    pub extra_extensions: Vec<String>,
+    
+    /// 不再扫描的默认视频扩展名
+    pub excluded_extensions: Vec<String>,
+    
+    /// 命名的配置方案，通过 `--profile` 选择
+    pub profiles: BTreeMap<String, Profile>,
+    
+    /// 各格式的默认编码参数，键为格式名 (mp3/aac/opus/m4b)
+    pub formats: BTreeMap<String, FormatDefaults>,
+}
+
+/// 守护进程模式的监视规则
+/// 
+/// 每条规则把一个源目录中的新视频转换为指定格式并输出到指定目录
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WatchRule {
+    /// 要监视的源目录
+    pub source_dir: PathBuf,
+    
+    /// 目标音频格式 (mp3/aac/opus/m4b)
+    pub format: String,
+    
+    /// 输出目录，未设置时为源目录下的 audio_exports
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+}
+
+impl WatchRule {
+    /// 解析规则中的音频格式
+    pub fn audio_format(&self) -> Result<AudioFormat> {
+        AudioFormat::from_user_input(&self.format)
+    }
+
+    /// 获取规则的输出目录
+    pub fn resolved_output_dir(&self) -> PathBuf {
+        self.output_dir
+            .clone()
+            .unwrap_or_else(|| self.source_dir.join(DEFAULT_OUTPUT_DIR_NAME))
+    }
+}
+
+/// 命名的配置方案
+/// 
+/// 把一组常用的转换设置保存在配置文件中，通过 `--profile` 一次选用。
+/// 未设置的字段使用命令行参数或配置文件中的默认值。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    /// 目标音频格式 (mp3/aac/opus/m4b)
+    pub format: Option<String>,
+    
+    /// 音频码率 (kbps)
+    pub bitrate: Option<u32>,
+    
+    /// 采样率 (Hz)
+    pub sample_rate: Option<u32>,
+    
+    /// 音频滤镜，同 `--af`
+    pub audio_filter: Option<String>,
+    
+    /// 输出目录
+    pub output_dir: Option<PathBuf>,
+    
+    /// 是否裁剪首尾静音
+    pub trim_silence: bool,
+    
+    /// 是否启用响度标准化
+    pub normalize: bool,
+}
+
+impl Profile {
+    /// 解析配置方案中的音频格式，未设置时返回 `None`
+    pub fn audio_format(&self) -> Result<Option<AudioFormat>> {
+        self.format.as_deref().map(AudioFormat::from_user_input).transpose()
+    }
 }
 
 impl Default for Config {
@@ -166,19 +1396,86 @@ impl Default for Config {
         Self {
             default_format: "mp3".to_string(),
             default_jobs: None,
+            adaptive_jobs: false,
+            threads_per_file: None,
+            nice: false,
+            hwaccel: None,
+            backend: BackendKind::default(),
             skip_existing: false,
             verbose: false,
             quiet: false,
             recent_source_dirs: Vec::new(),
-            language: "zh-CN".to_string(),
+            language: i18n::AUTO_LANGUAGE.to_string(),
             progress_style: "detailed".to_string(),
+            watch_rules: Vec::new(),
+            normalize: false,
+            normalize_two_pass: false,
+            loudness_target: LoudnessTarget::default(),
+            replay_gain: false,
+            strip_metadata: false,
+            history: true,
+            extra_extensions: Vec::new(),
+            excluded_extensions: Vec::new(),
+            profiles: BTreeMap::new(),
+            formats: BTreeMap::new(),
+        }
+    }
+}
+
+/// 配置文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// JSON（早期版本使用的格式）
+    Json,
+
+    /// TOML
+    Toml,
+}
+
+impl ConfigFormat {
+    /// 根据扩展名判断配置文件格式，`.toml` 为 TOML，其他均视为 JSON
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(extension) if extension.eq_ignore_ascii_case("toml") => ConfigFormat::Toml,
+            _ => ConfigFormat::Json,
         }
     }
+
+    /// 解析配置文件内容
+    /// 
+    /// # 错误
+    /// 
+    /// 内容格式错误时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn parse(self, content: &str) -> Result<Config> {
+        let config = match self {
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+        };
+        config.map_err(|e| VideoToAudioError::InvalidInput(tr!("配置文件格式错误: {e}", "Malformed configuration file: {e}")))
+    }
+
+    /// 把配置序列化为文件内容
+    /// 
+    /// # 错误
+    /// 
+    /// 序列化失败时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn serialize(self, config: &Config) -> Result<String> {
+        let content = match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(config).map_err(|e| e.to_string()),
+        };
+        content.map_err(|e| VideoToAudioError::InvalidInput(tr!(
+            "配置序列化失败: {e}",
+            "Failed to serialize the configuration: {e}",
+        )))
+    }
 }
 
 impl Config {
     /// 从配置文件加载配置
     /// 
+    /// 按扩展名识别 JSON 或 TOML 格式，参见 [`ConfigFormat::from_path`]
+    /// 
     /// # 参数
     /// 
     /// * `config_path` - 配置文件路径，如果为 None 则使用默认路径
@@ -187,18 +1484,14 @@ impl Config {
     /// 
     /// 加载的配置或默认配置
     pub fn load(config_path: Option<&PathBuf>) -> Result<Self> {
-        let config_file = match config_path {
-            Some(path) => path.clone(),
-            None => Self::default_config_path()?,
-        };
+        let config_file = Self::resolve_path(config_path)?;
 
         if config_file.exists() {
-            let content = std::fs::read_to_string(&config_file)?;
-            let config: Config = serde_json::from_str(&content)
-                .map_err(|e| VideoToAudioError::InvalidInput(
-                    format!("配置文件格式错误: {e}")
-                ))?;
-            Ok(config)
+            let content = std::fs::read_to_string(&config_file).context(text(
+                "读取配置文件",
+                "read configuration file",
+            ), &config_file)?;
+            ConfigFormat::from_path(&config_file).parse(&content)
         } else {
             Ok(Config::default())
         }
@@ -206,37 +1499,267 @@ impl Config {
 
     /// 保存配置到文件
     /// 
+    /// 按扩展名写入 JSON 或 TOML 格式，参见 [`ConfigFormat::from_path`]
+    /// 
     /// # 参数
     /// 
     /// * `config_path` - 配置文件路径，如果为 None 则使用默认路径
     pub fn save(&self, config_path: Option<&PathBuf>) -> Result<()> {
-        let config_file = match config_path {
-            Some(path) => path.clone(),
-            None => Self::default_config_path()?,
-        };
+        let config_file = Self::resolve_path(config_path)?;
 
         // 确保配置目录存在
         if let Some(parent) = config_file.parent() {
-            std::fs::create_dir_all(parent)?;
+            std::fs::create_dir_all(parent).context(text("创建配置目录", "create configuration directory"), parent)?;
         }
 
-        let content = serde_json::to_string_pretty(self)
-            .map_err(|e| VideoToAudioError::InvalidInput(
-                format!("配置序列化失败: {e}")
-            ))?;
-
-        std::fs::write(&config_file, content)?;
+        let content = ConfigFormat::from_path(&config_file).serialize(self)?;
+        std::fs::write(&config_file, content).context(text("写入配置文件", "write configuration file"), &config_file)?;
         Ok(())
     }
 
+    /// 获取实际使用的配置文件路径，未指定时为默认路径
+    pub fn resolve_path(config_path: Option<&PathBuf>) -> Result<PathBuf> {
+        match config_path {
+            Some(path) => Ok(path.clone()),
+            None => Self::default_config_path(),
+        }
+    }
+
     /// 获取默认配置文件路径
-    fn default_config_path() -> Result<PathBuf> {
+    /// 
+    /// 优先使用配置目录中的 `config.toml`；只有旧版本留下的 `config.json` 时继续使用它，
+    /// 两者都不存在时使用 `config.toml`
+    pub fn default_config_path() -> Result<PathBuf> {
+        let config_dir = Self::default_config_dir()?;
+        let toml_path = config_dir.join("config.toml");
+        let json_path = config_dir.join("config.json");
+        Ok(if !toml_path.exists() && json_path.exists() { json_path } else { toml_path })
+    }
+
+    /// 获取默认配置目录
+    fn default_config_dir() -> Result<PathBuf> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| VideoToAudioError::InvalidPath(
-                "无法获取配置目录".to_string()
+                text("无法获取配置目录", "Cannot determine the configuration directory").to_string()
             ))?;
         
-        Ok(config_dir.join("video2audio-rs").join("config.json"))
+        Ok(config_dir.join("video2audio-rs"))
+    }
+
+    /// 把 JSON 配置文件迁移为同目录下同名的 TOML 文件
+    /// 
+    /// 迁移后原文件重命名为 `.json.bak`，之后只会读取 TOML 文件
+    /// 
+    /// # 返回值
+    /// 
+    /// 新的 TOML 配置文件路径
+    /// 
+    /// # 错误
+    /// 
+    /// 原文件不是 JSON 格式、无法解析，或 TOML 文件已存在时返回错误
+    pub fn migrate_to_toml(json_path: &Path) -> Result<PathBuf> {
+        if ConfigFormat::from_path(json_path) != ConfigFormat::Json {
+            return Err(VideoToAudioError::InvalidInput(tr!(
+                "不是 JSON 配置文件: {}",
+                "Not a JSON configuration file: {}",
+                json_path.display(),
+            )));
+        }
+        let toml_path = json_path.with_extension("toml");
+        if toml_path.exists() {
+            return Err(VideoToAudioError::InvalidInput(tr!(
+                "配置文件已存在: {}",
+                "The configuration file already exists: {}",
+                toml_path.display(),
+            )));
+        }
+
+        let content = std::fs::read_to_string(json_path).context(text("读取配置文件", "read configuration file"), json_path)?;
+        let config = ConfigFormat::Json.parse(&content)?;
+        std::fs::write(&toml_path, ConfigFormat::Toml.serialize(&config)?)
+            .context(text("写入配置文件", "write configuration file"), &toml_path)?;
+        std::fs::rename(json_path, json_path.with_extension("json.bak"))
+            .context(text("备份配置文件", "back up configuration file"), json_path)?;
+        Ok(toml_path)
+    }
+
+    /// 查找命名的配置方案并检查其中的格式
+    /// 
+    /// # 错误
+    /// 
+    /// 配置方案不存在或格式无效时返回错误
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        let profile = self.profiles.get(name).ok_or_else(|| {
+            let available: Vec<_> = self.profiles.keys().map(String::as_str).collect();
+            VideoToAudioError::InvalidInput(if available.is_empty() {
+                tr!("配置文件中没有配置方案 {name}", "The configuration file has no profile {name}")
+            } else {
+                tr!(
+                    "配置文件中没有配置方案 {name}（可用: {}）",
+                    "The configuration file has no profile {name} (available: {})",
+                    available.join(", "),
+                )
+            })
+        })?;
+        profile.audio_format()?;
+        Ok(profile)
+    }
+
+    /// 扫描的视频扩展名：默认列表加上 `extra_extensions`，再去掉 `excluded_extensions`
+    /// 
+    /// 扩展名不区分大小写，可以带前导的点，重复的扩展名只保留一个
+    pub fn video_extensions(&self) -> Vec<String> {
+        let excluded: Vec<_> = self.excluded_extensions.iter().map(|ext| normalize_extension(ext)).collect();
+        let mut extensions = Vec::new();
+        for ext in DEFAULT_VIDEO_EXTENSIONS.iter().copied().chain(self.extra_extensions.iter().map(String::as_str)) {
+            let ext = normalize_extension(ext);
+            if !ext.is_empty() && !excluded.contains(&ext) && !extensions.contains(&ext) {
+                extensions.push(ext);
+            }
+        }
+        extensions
+    }
+
+    /// 可以用 `config set` 或环境变量修改的设置名，嵌套的设置用 `.` 连接
+    /// 
+    /// 不包括最近使用的目录、监视规则和配置方案等列表设置，这些设置需要直接编辑配置文件
+    pub fn setting_keys() -> Vec<String> {
+        fn collect(prefix: &str, value: &serde_json::Value, keys: &mut Vec<String>) {
+            match value {
+                serde_json::Value::Object(fields) => {
+                    for (name, value) in fields {
+                        collect(&format!("{prefix}{name}."), value, keys);
+                    }
+                }
+                _ => keys.push(prefix.trim_end_matches('.').to_string()),
+            }
+        }
+
+        let mut keys = Vec::new();
+        if let Ok(serde_json::Value::Object(fields)) = serde_json::to_value(Config::default()) {
+            for (name, value) in &fields {
+                if !LIST_SETTINGS.contains(&name.as_str()) {
+                    collect(&format!("{name}."), value, &mut keys);
+                }
+            }
+        }
+        keys
+    }
+
+    /// 获取一项设置的值
+    /// 
+    /// # 错误
+    /// 
+    /// 设置名不存在时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn get_setting(&self, key: &str) -> Result<serde_json::Value> {
+        let pointer = Self::setting_pointer(key)?;
+        let value = serde_json::to_value(self)
+            .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                "配置序列化失败: {e}",
+                "Failed to serialize the configuration: {e}",
+            )))?;
+        Ok(value.pointer(&pointer).cloned().unwrap_or_default())
+    }
+
+    /// 修改一项设置
+    /// 
+    /// 值按设置的类型解析：字符串设置直接使用输入的文本，其他设置按 JSON 解析
+    /// （如 `4`、`true`、`-14.0`），`null` 清除可选的设置
+    /// 
+    /// # 错误
+    /// 
+    /// 设置名不存在或值的类型不符时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn set_setting(&mut self, key: &str, value: &str) -> Result<()> {
+        let parsed = match self.get_setting(key)? {
+            serde_json::Value::String(_) => serde_json::Value::String(value.to_string()),
+            _ => serde_json::from_str(value).unwrap_or_else(|_| serde_json::Value::String(value.to_string())),
+        };
+        self.replace_setting(key, parsed)
+            .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                "设置 {key} 的值无效: {value}（{e}）",
+                "Invalid value for setting {key}: {value} ({e})",
+            )))
+    }
+
+    /// 把一项设置恢复为默认值
+    /// 
+    /// # 错误
+    /// 
+    /// 设置名不存在时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn reset_setting(&mut self, key: &str) -> Result<()> {
+        let default = Config::default().get_setting(key)?;
+        self.replace_setting(key, default)
+    }
+
+    /// 按环境变量覆盖设置，返回被覆盖的设置名
+    /// 
+    /// 环境变量名为 [`CONFIG_ENV_PREFIX`] 加上大写的设置名，参见 [`Config::setting_env_var`]
+    /// 
+    /// # 错误
+    /// 
+    /// 环境变量的值无效时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn apply_env_overrides(&mut self) -> Result<Vec<String>> {
+        let mut applied = Vec::new();
+        for key in Self::setting_keys() {
+            let name = Self::setting_env_var(&key);
+            if let Ok(value) = std::env::var(&name) {
+                self.set_setting(&key, &value)
+                    .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                        "环境变量 {name}: {e}",
+                        "Environment variable {name}: {e}",
+                    )))?;
+                applied.push(key);
+            }
+        }
+        Ok(applied)
+    }
+
+    /// 覆盖一项设置的环境变量名，如 `default_format` 对应 `VIDEO2AUDIO_DEFAULT_FORMAT`，
+    /// `loudness_target.integrated` 对应 `VIDEO2AUDIO_LOUDNESS_TARGET_INTEGRATED`
+    pub fn setting_env_var(key: &str) -> String {
+        format!("{CONFIG_ENV_PREFIX}{}", key.replace('.', "_").to_uppercase())
+    }
+
+    /// 把设置名转换为 JSON Pointer，设置名不存在时返回错误
+    fn setting_pointer(key: &str) -> Result<String> {
+        if !Self::setting_keys().iter().any(|setting| setting == key) {
+            return Err(VideoToAudioError::InvalidInput(tr!(
+                "未知的设置: {key}，可使用 video2audio-rs config show 查看全部设置",
+                "Unknown setting: {key}, run video2audio-rs config show to list all settings"
+            )));
+        }
+        Ok(format!("/{}", key.replace('.', "/")))
+    }
+
+    /// 替换一项设置的值，替换后的配置无效时保持原配置不变
+    fn replace_setting(&mut self, key: &str, value: serde_json::Value) -> Result<()> {
+        let pointer = Self::setting_pointer(key)?;
+        let mut root = serde_json::to_value(&*self)
+            .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                "配置序列化失败: {e}",
+                "Failed to serialize the configuration: {e}",
+            )))?;
+        if let Some(slot) = root.pointer_mut(&pointer) {
+            *slot = value;
+        }
+        let config: Config = serde_json::from_value(root)
+            .map_err(|e| VideoToAudioError::InvalidInput(e.to_string()))?;
+        config.get_default_format()?;
+        i18n::parse_configured(&config.language)?;
+        *self = config;
+        Ok(())
+    }
+
+    /// 按格式整理各格式的默认编码参数
+    /// 
+    /// # 错误
+    /// 
+    /// 格式名无效时返回 [`VideoToAudioError::UnsupportedFormat`]
+    pub fn format_defaults(&self) -> Result<HashMap<AudioFormat, FormatDefaults>> {
+        self.formats
+            .iter()
+            .map(|(name, defaults)| Ok((AudioFormat::from_user_input(name)?, defaults.clone())))
+            .collect()
     }
 
     /// 添加最近使用的源目录
@@ -272,6 +1795,7 @@ impl Config {
             AudioFormat::Mp3 => "mp3".to_string(),
             AudioFormat::AacCopy => "aac".to_string(),
             AudioFormat::Opus => "opus".to_string(),
+            AudioFormat::M4b => "m4b".to_string(),
         };
     }
 }
@@ -284,6 +1808,12 @@ pub struct RuntimeConfig {
     /// 源目录路径
     pub source_dir: Option<PathBuf>,
     
+    /// 文件列表
+    pub from_file: Option<PathBuf>,
+    
+    /// 失败清单
+    pub retry_failed: Option<PathBuf>,
+    
     /// 音频格式
     pub format: Option<AudioFormat>,
     
@@ -299,17 +1829,212 @@ pub struct RuntimeConfig {
     /// 静默模式
     pub quiet: bool,
     
+    /// 进度显示方式
+    pub progress: ProgressFormat,
+    
     /// 并行线程数
     pub jobs: Option<usize>,
     
+    /// 根据系统负载调整并发
+    pub adaptive_jobs: bool,
+    
+    /// 每个 FFmpeg 进程的线程数
+    pub threads_per_file: Option<usize>,
+    
+    /// 以低优先级运行 FFmpeg
+    pub nice: bool,
+    
+    /// 硬件加速解码
+    pub hwaccel: Option<HwAccel>,
+    
+    /// 转换后端
+    pub backend: BackendKind,
+    
     /// 跳过已存在文件
     pub skip_existing: bool,
     
+    /// 增量模式
+    pub incremental: bool,
+    
+    /// 跳过磁盘空间检查
+    pub skip_space_check: bool,
+    
+    /// 预检
+    pub preflight: bool,
+    
+    /// 只显示转换计划，不转换任何文件
+    pub dry_run: bool,
+    
+    /// 先转换并试听预览片段，确认后再转换全部文件
+    pub preview: bool,
+    
+    /// 处理顺序
+    pub order: Option<SortOrder>,
+    
+    /// 最大的文件优先
+    pub largest_first: bool,
+    
+    /// 最新的文件优先
+    pub newest_first: bool,
+    
+    /// 扫描时的筛选条件
+    pub file_filter: FileFilter,
+    
+    /// 扫描的视频扩展名
+    pub extensions: Vec<String>,
+    
+    /// 是否把纯音频文件也作为输入
+    pub include_audio_inputs: bool,
+    
+    /// 只处理前 N 个文件
+    pub limit: Option<usize>,
+    
+    /// 随机抽取 N 个文件
+    pub sample: Option<usize>,
+    
+    /// 转换前挑选要转换的文件
+    pub pick: bool,
+    
+    /// 是否显示终端仪表盘
+    pub tui: bool,
+    
+    /// 提取全部音轨
+    pub all_audio_tracks: bool,
+    
+    /// 要提取的音轨
+    pub audio_track: Option<AudioTrackSelection>,
+    
+    /// 封面
+    pub cover: Option<CoverArt>,
+    
+    /// 片段开始时间
+    pub start: Option<Duration>,
+    
+    /// 片段结束时间
+    pub end: Option<Duration>,
+    
+    /// 按章节拆分
+    pub split_chapters: bool,
+    
+    /// 不复制源文件的元数据
+    pub strip_metadata: bool,
+    
+    /// 自定义元数据标签
+    pub tags: Vec<(String, String)>,
+    
+    /// 文件名标签模板
+    pub tag_pattern: Option<TagPattern>,
+    
+    /// 按目录生成专辑
+    pub album_per_dir: bool,
+    
+    /// 自定义音频滤镜
+    pub audio_filter: Option<String>,
+    
+    /// 裁剪首尾静音
+    pub trim_silence: bool,
+    
+    /// 音频码率 (kbps)
+    pub bitrate: Option<u32>,
+    
+    /// 采样率 (Hz)
+    pub sample_rate: Option<u32>,
+    
+    /// 音质预设，未指定码率时生效
+    pub quality: Option<QualityPreset>,
+    
+    /// 响度标准化设置
+    pub normalize: Option<Normalization>,
+    
+    /// 写入增益标签
+    pub replay_gain: bool,
+    
+    /// 失败重试次数
+    pub retries: usize,
+    
+    /// 重试时使用的备用格式
+    pub fallback_format: Option<AudioFormat>,
+    
+    /// 快速失败模式
+    pub fail_fast: bool,
+    
+    /// 继续上次中断的批处理
+    pub resume: bool,
+    
+    /// 生成播放列表
+    pub playlist: bool,
+    
+    /// 校验和清单的算法
+    pub checksums: Option<ChecksumAlgorithm>,
+    
+    /// 转换报告路径
+    pub report: Option<PathBuf>,
+    
+    /// 日志文件路径
+    pub log_file: Option<PathBuf>,
+    
+    /// 日志文件轮转的大小上限 (MB)
+    pub log_max_size: u64,
+    
+    /// 已打开的日志文件，由 [`open_log_file`](Self::open_log_file) 设置
+    pub log: Option<Arc<LogFile>>,
+    
+    /// 批处理期间阻止系统休眠
+    pub inhibit_sleep: bool,
+    
+    /// 输出目录被锁定时等待
+    pub wait_for_lock: bool,
+    
+    /// 删除文件时移到回收站
+    pub trash: bool,
+    
+    /// 结束后发送桌面通知
+    pub notify: bool,
+    
+    /// 转换成功后执行的命令
+    pub on_success: Option<HookCommand>,
+    
+    /// 转换失败后执行的命令
+    pub on_failure: Option<HookCommand>,
+    
+    /// 跳过转换历史中已转换过的文件
+    pub skip_converted: bool,
+    
+    /// 记录转换历史
+    pub history: bool,
+    
+    /// 重复源文件的处理方式
+    pub dedupe: Option<DedupeMode>,
+    
+    /// 损坏源文件的隔离方式
+    pub quarantine: Option<QuarantineMode>,
+    
+    /// 应用源目录中的按目录配置
+    pub dir_config: bool,
+    
+    /// 监视模式
+    pub watch: bool,
+    
+    /// 监视模式的防抖时间
+    pub watch_debounce: Duration,
+    
+    /// HTTP API 服务监听地址
+    pub serve: Option<String>,
+    
+    /// IPC 模式
+    pub ipc: bool,
+    
     /// 显示格式列表
     pub list_formats: bool,
     
     /// 保存配置
     pub save_config: bool,
+    
+    /// 各格式的默认编码参数
+    pub format_defaults: HashMap<AudioFormat, FormatDefaults>,
+    
+    /// 配置文件路径，未设置时使用默认路径
+    pub config_file: Option<PathBuf>,
 }
 
 impl RuntimeConfig {
@@ -324,17 +2049,108 @@ impl RuntimeConfig {
     /// 
     /// 合并后的运行时配置
     pub fn from_args_and_config(args: Args, config: Config) -> Self {
+        // 命令行参数优先于配置方案，配置方案优先于配置文件中的默认值
+        let profile = args
+            .profile
+            .as_ref()
+            .and_then(|name| config.profiles.get(name))
+            .cloned()
+            .unwrap_or_default();
+        // JSON 进度独占标准输出，不再输出面向终端的文字
+        let progress = args.progress.map(ProgressFormat::from).unwrap_or_default();
+        let json_progress = progress == ProgressFormat::Json;
         Self {
             source_dir: args.source_dir,
-            format: args.format.map(AudioFormat::from),
-            output_dir: args.output_dir,
+            from_file: args.from_file,
+            retry_failed: args.retry_failed,
+            format: args.format.map(AudioFormat::from).or_else(|| profile.audio_format().ok().flatten()),
+            output_dir: args.output_dir.or(profile.output_dir),
             batch_mode: args.batch_mode,
-            verbose: args.verbose || config.verbose,
-            quiet: args.quiet || config.quiet,
+            verbose: (args.verbose || config.verbose) && !json_progress,
+            quiet: args.quiet || config.quiet || json_progress,
+            progress,
             jobs: args.jobs.or(config.default_jobs),
+            adaptive_jobs: args.adaptive_jobs || config.adaptive_jobs,
+            threads_per_file: args.threads_per_file.or(config.threads_per_file),
+            nice: args.nice || config.nice,
+            hwaccel: args.hwaccel.map_or(config.hwaccel, Option::<HwAccel>::from),
+            backend: args.backend.map(BackendKind::from).unwrap_or(config.backend),
             skip_existing: args.skip_existing || config.skip_existing,
+            incremental: args.incremental,
+            skip_space_check: args.skip_space_check,
+            preflight: args.preflight,
+            dry_run: args.dry_run,
+            preview: args.preview,
+            order: args.order.map(SortOrder::from),
+            largest_first: args.largest_first,
+            newest_first: args.newest_first,
+            file_filter: FileFilter {
+                min_size: args.min_size,
+                max_size: args.max_size,
+                modified_since: args.since,
+                modified_until: args.until,
+                min_duration: args.min_duration,
+                max_duration: args.max_duration,
+            },
+            extensions: config.video_extensions(),
+            include_audio_inputs: args.include_audio_inputs,
+            limit: args.limit,
+            sample: args.sample,
+            pick: args.pick,
+            tui: args.tui,
+            all_audio_tracks: args.all_audio_tracks,
+            audio_track: args
+                .audio_track
+                .map(|number| AudioTrackSelection::Number(number as usize))
+                .or_else(|| args.audio_lang.map(AudioTrackSelection::Language)),
+            cover: args.cover,
+            start: args.start,
+            end: args.end,
+            split_chapters: args.split_chapters,
+            strip_metadata: args.no_metadata || config.strip_metadata,
+            tags: args.tags,
+            tag_pattern: args.tag_pattern,
+            album_per_dir: args.album_per_dir,
+            audio_filter: args.audio_filter.or(profile.audio_filter),
+            trim_silence: args.trim_silence || profile.trim_silence,
+            bitrate: profile.bitrate,
+            quality: args.quality.map(QualityPreset::from),
+            sample_rate: profile.sample_rate,
+            normalize: {
+                let two_pass = args.normalize_two_pass || config.normalize_two_pass;
+                (args.normalize || profile.normalize || config.normalize || two_pass)
+                    .then(|| Normalization::new(config.loudness_target).with_two_pass(two_pass))
+            },
+            replay_gain: args.replay_gain || config.replay_gain,
+            retries: args.retries,
+            fallback_format: args.fallback_format.map(AudioFormat::from),
+            fail_fast: args.fail_fast,
+            resume: args.resume,
+            playlist: args.playlist,
+            checksums: args.checksums.map(ChecksumAlgorithm::from),
+            report: args.report,
+            log_file: args.log_file,
+            log_max_size: args.log_max_size,
+            log: None,
+            inhibit_sleep: args.inhibit_sleep || (!args.batch_mode && !args.no_inhibit_sleep),
+            wait_for_lock: args.wait_for_lock,
+            trash: args.trash,
+            notify: args.notify,
+            on_success: args.on_success,
+            on_failure: args.on_failure,
+            skip_converted: args.skip_converted,
+            history: !args.no_history && config.history,
+            dedupe: args.dedupe.map(DedupeMode::from),
+            quarantine: args.quarantine.map(QuarantineMode::from),
+            dir_config: !args.no_dir_config,
+            watch: args.watch,
+            watch_debounce: Duration::from_secs(args.watch_debounce),
+            serve: args.serve,
+            ipc: args.ipc,
             list_formats: args.list_formats,
             save_config: args.save_config,
+            format_defaults: config.format_defaults().unwrap_or_default(),
+            config_file: args.config_file,
         }
     }
 
@@ -344,7 +2160,142 @@ impl RuntimeConfig {
     /// 
     /// 如果需要交互式输入返回 true
     pub fn needs_interaction(&self) -> bool {
-        !self.batch_mode && (self.source_dir.is_none() || self.format.is_none())
+        !self.batch_mode
+            && ((self.source_dir.is_none() && !self.reads_file_list()) || self.format.is_none())
+    }
+
+    /// 是否从文件列表或失败清单读取要处理的文件，而不是扫描源目录
+    pub fn reads_file_list(&self) -> bool {
+        self.from_file.is_some() || self.retry_failed.is_some()
+    }
+
+    /// 获取输出文件已存在时的处理策略
+    pub fn overwrite_policy(&self) -> OverwritePolicy {
+        if self.skip_existing {
+            OverwritePolicy::SkipExisting
+        } else if self.incremental {
+            OverwritePolicy::SkipUpToDate
+        } else {
+            OverwritePolicy::Overwrite
+        }
+    }
+
+    /// 把命令行中的转换设置（片段、元数据、标签、滤镜、响度标准化、封面）应用到转换选项
+    pub fn conversion_options(&self, options: ConversionOptions) -> ConversionOptions {
+        let mut options = options
+            .with_segment(self.start, self.end)
+            .with_trim_silence(self.trim_silence);
+        if self.strip_metadata {
+            options = options.with_metadata(MetadataPolicy::Strip);
+        }
+        for (key, value) in &self.tags {
+            options = options.with_tag(key, value);
+        }
+        if let Some(filter) = &self.audio_filter {
+            options = options.with_filter(filter);
+        }
+        if let Some(kbps) = self.bitrate {
+            options = options.with_bitrate(kbps);
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            options = options.with_sample_rate(sample_rate);
+        }
+        if let Some(quality) = self.quality {
+            options = quality.apply(options);
+        }
+        if let Some(normalization) = &self.normalize {
+            options = options.with_normalization(normalization.clone());
+        }
+        // 滤镜、码率和采样率需要重新编码，AAC 不能再直接复制音频流
+        options = options.with_reencode_if_needed();
+        match &self.cover {
+            Some(cover) => options.with_cover_art(cover.clone()),
+            None => options,
+        }
+    }
+
+    /// 获取文件的处理顺序
+    /// 
+    /// # 返回值
+    /// 
+    /// (排序依据, 是否降序)，未指定任何顺序时返回 `None`
+    pub fn sort_order(&self) -> Option<(SortOrder, bool)> {
+        if self.newest_first {
+            Some((SortOrder::Mtime, true))
+        } else if self.largest_first {
+            match self.order {
+                Some(SortOrder::Duration) => Some((SortOrder::Duration, true)),
+                _ => Some((SortOrder::Size, true)),
+            }
+        } else {
+            self.order.map(|order| (order, false))
+        }
+    }
+
+    /// 根据运行时配置创建文件处理器
+    /// 
+    /// 应用重试、快速失败、状态日志和覆盖策略等设置
+    pub fn file_processor(&self) -> FileProcessor {
+        let ffmpeg_path = managed_ffmpeg::find_ffmpeg();
+
+        // 找不到 FFmpeg 时改用不依赖外部程序的后端
+        #[cfg(feature = "symphonia")]
+        let backend = match self.backend {
+            BackendKind::Ffmpeg if ffmpeg_path.is_none() => BackendKind::Symphonia,
+            backend => backend,
+        };
+        #[cfg(not(feature = "symphonia"))]
+        let backend = self.backend;
+
+        let processor = FileProcessor::new()
+            .with_retries(self.retries)
+            .with_fallback_format(self.fallback_format)
+            .with_fail_fast(self.fail_fast)
+            .with_journal(true)
+            .with_overwrite_policy(self.overwrite_policy())
+            .with_ffmpeg_path(ffmpeg_path.unwrap_or_else(|| PathBuf::from("ffmpeg")))
+            .with_backend(backend.create())
+            .with_ffmpeg_threads(self.threads_per_file)
+            .with_low_priority(self.nice)
+            .with_show_commands(self.verbose)
+            .with_log_file(self.log.clone())
+            .with_hwaccel(self.hwaccel)
+            .with_file_filter(self.file_filter.clone())
+            .with_extensions(&self.extensions)
+            .with_audio_inputs(self.include_audio_inputs)
+            .with_all_audio_tracks(self.all_audio_tracks)
+            .with_audio_track(self.audio_track.clone())
+            .with_split_chapters(self.split_chapters)
+            .with_replay_gain(self.replay_gain)
+            .with_tag_pattern(self.tag_pattern.clone())
+            .with_album_per_dir(self.album_per_dir)
+            .with_format_defaults(self.format_defaults.clone())
+            .with_scheduler(Some(Arc::new(JobScheduler::new(self.concurrency_limits()))));
+
+        match &self.output_dir {
+            Some(output_dir) => processor.with_excluded_dir(output_dir),
+            None => processor,
+        }
+    }
+
+    /// 打开 `--log-file` 指定的日志文件，之后创建的文件处理器都会写入该日志
+    ///
+    /// # 错误
+    ///
+    /// 无法创建或打开日志文件时返回错误
+    pub fn open_log_file(&mut self) -> Result<()> {
+        if let Some(path) = &self.log_file {
+            let log = LogFile::open(path)?.with_max_size(self.log_max_size.saturating_mul(1024 * 1024));
+            self.log = Some(Arc::new(log));
+        }
+        Ok(())
+    }
+
+    /// 获取各类任务的并发上限
+    /// 
+    /// 任务总数等于线程池的线程数，编码任务使用复制任务剩下的全部线程，复制任务的并发数较小，避免磁盘来回寻道
+    pub fn concurrency_limits(&self) -> ConcurrencyLimits {
+        ConcurrencyLimits::for_threads(self.get_thread_count()).with_adaptive(self.adaptive_jobs)
     }
 
     /// 获取并行线程数
@@ -0,0 +1,250 @@
+//! # 守护进程模块
+//!
+//! 按配置文件中的监视规则同时监视多个源目录，适合在 systemd/launchd
+//! 下作为常驻转码服务运行。日志以 `key=value` 结构化格式写入
+//! [`Daemon::with_log_writer`] 指定的位置（命令行程序使用标准错误流），
+//! 便于 journald 等日志系统检索。
+
+use crate::cancellation::CancellationToken;
+use crate::config::WatchRule;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::file_processor::{FileProcessor, OverwritePolicy};
+use crate::i18n::text;
+use crate::log_file;
+use crate::tr;
+use crate::watcher::DirectoryWatcher;
+use std::fmt::Display;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// 守护进程
+///
+/// 启动时先以增量方式补齐每条规则下尚未转换的视频，然后持续监视新文件，
+/// 直到取消令牌被取消（通常由 SIGTERM/SIGINT 触发）。
+pub struct Daemon<'a> {
+    rules: &'a [WatchRule],
+    processor: FileProcessor,
+    debounce: Duration,
+    log: Option<Mutex<Box<dyn Write + Send>>>,
+}
+
+impl<'a> Daemon<'a> {
+    /// 创建守护进程
+    ///
+    /// # 参数
+    ///
+    /// * `rules` - 监视规则列表
+    /// * `processor` - 文件处理器，其覆盖策略会被替换为增量模式
+    /// * `debounce` - 监视的防抖时间
+    pub fn new(rules: &'a [WatchRule], processor: FileProcessor, debounce: Duration) -> Self {
+        Self {
+            rules,
+            processor: processor.with_overwrite_policy(OverwritePolicy::SkipUpToDate),
+            debounce,
+            log: None,
+        }
+    }
+
+    /// 设置结构化日志的输出位置，默认不输出日志
+    ///
+    /// 各条规则在各自的线程中写入日志，每行日志整行写入，不会互相穿插
+    pub fn with_log_writer(mut self, writer: impl Write + Send + 'static) -> Self {
+        self.log = Some(Mutex::new(Box::new(writer)));
+        self
+    }
+
+    /// 运行守护进程，直到取消令牌被取消
+    ///
+    /// # 错误
+    ///
+    /// 当没有配置规则或任意规则无效（目录不存在、格式错误）时返回错误；
+    /// 运行期间单条规则的监视失败只记录日志，不影响其他规则。
+    pub fn run(&self, cancel_token: &CancellationToken) -> Result<()> {
+        if self.rules.is_empty() {
+            return Err(VideoToAudioError::InvalidInput(
+                text(
+                    "配置文件中没有 watch_rules，守护进程无事可做",
+                    "The configuration file has no watch_rules, the daemon has nothing to do",
+                ).to_string()
+            ));
+        }
+
+        // 启动前校验全部规则，配置错误时立即失败而不是静默跳过
+        let mut prepared = Vec::with_capacity(self.rules.len());
+        for rule in self.rules {
+            if !rule.source_dir.is_dir() {
+                return Err(VideoToAudioError::InvalidPath(
+                    tr!("监视目录不存在: {}", "Watched directory does not exist: {}", rule.source_dir.display())
+                ));
+            }
+            let options = self.processor.check_encoder(rule.audio_format()?)?;
+            let output_dir = rule.resolved_output_dir();
+            fs::create_dir_all(&output_dir).context(text("创建输出目录", "create output directory"), &output_dir)?;
+            prepared.push((rule, options, output_dir));
+        }
+
+        self.log_event("info", "daemon_started", &[("rules", &self.rules.len())]);
+
+        thread::scope(|scope| {
+            for (index, (rule, options, output_dir)) in prepared.iter().enumerate() {
+                scope.spawn(move || {
+                    let source_dir = rule.source_dir.as_path();
+                    self.log_event("info", "rule_started", &[
+                        ("rule", &index),
+                        ("source", &source_dir.display()),
+                        ("output", &output_dir.display()),
+                        ("format", &options.format.extension()),
+                    ]);
+
+                    let on_result = |source: &Path, result: Result<std::path::PathBuf>| {
+                        match result {
+                            Ok(output) => self.log_event("info", "converted", &[
+                                ("rule", &index),
+                                ("source", &source.display()),
+                                ("output", &output.display()),
+                            ]),
+                            Err(e) => self.log_event("error", "failed", &[
+                                ("rule", &index),
+                                ("source", &source.display()),
+                                ("error", &e),
+                            ]),
+                        }
+                    };
+
+                    // 补齐守护进程未运行期间新增的视频，边扫描边转换
+                    let scanner = self.processor.clone().with_excluded_dir(output_dir);
+                    match scanner.find_video_files_iter(source_dir) {
+                        Ok(files) => {
+                            let files = files.filter_map(|file| match file {
+                                Ok(file) => Some(file),
+                                Err(e) => {
+                                    self.log_event("warn", "scan_error", &[("rule", &index), ("error", &e)]);
+                                    None
+                                }
+                            });
+                            let (success, failure) = self.processor.batch_convert_iter(
+                                files,
+                                output_dir,
+                                options.clone(),
+                                cancel_token,
+                                |source, result, _, _| {
+                                    if let Err(e) = result {
+                                        self.log_event("error", "failed", &[
+                                            ("rule", &index),
+                                            ("source", &source.display()),
+                                            ("error", e),
+                                        ]);
+                                    }
+                                },
+                            );
+                            self.log_event("info", "catch_up_done", &[
+                                ("rule", &index),
+                                ("success", &success),
+                                ("failure", &failure),
+                            ]);
+                        }
+                        Err(e) => self.log_event("error", "scan_failed", &[("rule", &index), ("error", &e)]),
+                    }
+
+                    let watcher = DirectoryWatcher::new(&self.processor, self.debounce);
+                    if let Err(e) = watcher.watch(source_dir, output_dir, options.clone(), cancel_token, on_result) {
+                        self.log_event("error", "watch_failed", &[("rule", &index), ("error", &e)]);
+                    }
+                });
+            }
+        });
+
+        self.log_event("info", "daemon_stopped", &[]);
+        Ok(())
+    }
+
+    /// 输出一行结构化日志，格式参见 [`log_file`](crate::log_file)
+    fn log_event(&self, level: &str, event: &str, fields: &[(&str, &dyn Display)]) {
+        if let Some(log) = &self.log {
+            let line = log_file::format_line(level, event, fields);
+            // 日志写入失败不影响转换
+            let _ = writeln!(log.lock().unwrap(), "{line}");
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_without_rules_fails() {
+        let daemon = Daemon::new(&[], FileProcessor::new(), Duration::from_secs(1));
+        assert!(daemon.run(&CancellationToken::new()).is_err());
+    }
+
+    #[test]
+    fn test_run_with_missing_source_fails() {
+        let rules = vec![WatchRule {
+            source_dir: "/nonexistent/recordings".into(),
+            format: "mp3".to_string(),
+            output_dir: None,
+        }];
+        let daemon = Daemon::new(&rules, FileProcessor::new(), Duration::from_secs(1));
+        match daemon.run(&CancellationToken::new()) {
+            Err(VideoToAudioError::InvalidPath(_)) => (),
+            other => panic!("应该返回 InvalidPath 错误: {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_logs_go_to_log_writer() {
+        use std::os::unix::fs::PermissionsExt;
+        use std::sync::Arc;
+
+        #[derive(Clone, Default)]
+        struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Buffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let ffmpeg = temp_dir.path().join("ffmpeg");
+        fs::write(
+            &ffmpeg,
+            "#!/bin/sh
+[ \"$1\" = -version ] && exit 0
+echo ' ------'
+echo ' A....D libmp3lame MP3'
+",
+        )
+        .unwrap();
+        fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+        let rules = vec![WatchRule {
+            source_dir: temp_dir.path().to_path_buf(),
+            format: "mp3".to_string(),
+            output_dir: None,
+        }];
+
+        // 已取消时补齐和监视都立即结束
+        let cancel_token = CancellationToken::new();
+        cancel_token.cancel();
+        let buffer = Buffer::default();
+        let processor = FileProcessor::new().with_ffmpeg_path(&ffmpeg);
+        let daemon = Daemon::new(&rules, processor, Duration::from_secs(1)).with_log_writer(buffer.clone());
+        daemon.run(&cancel_token).unwrap();
+
+        let log = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<_> = log.lines().collect();
+        assert!(lines.first().unwrap().contains("event=daemon_started"), "{log}");
+        assert!(lines.last().unwrap().contains("event=daemon_stopped"), "{log}");
+    }
+}
@@ -0,0 +1,108 @@
+//! # 重复源文件模块
+//!
+//! 按内容指纹找出内容相同但文件名不同的源文件，每组只转换一个，
+//! 其余文件硬链接到已转换的输出，或只在报告中注明。
+
+use crate::error::{IoResultExt, Result};
+use crate::history::source_fingerprint;
+use crate::i18n::text;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 重复文件的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupeMode {
+    /// 把重复文件的输出硬链接到已转换的输出（跨文件系统时复制）
+    Link,
+
+    /// 不生成输出，只在报告中注明
+    Skip,
+}
+
+/// 与另一个源文件内容相同的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Duplicate {
+    /// 重复的源文件
+    pub file: PathBuf,
+
+    /// 内容相同、实际转换的源文件
+    pub original: PathBuf,
+}
+
+/// 把文件列表分为需要转换的文件和重复文件
+///
+/// 每组内容相同的文件只保留第一个，保持原有顺序。只有大小与其他文件相同的文件
+/// 才会计算内容指纹（参见 [`source_fingerprint`]），无法读取的文件视为不重复。
+pub fn split_duplicates(files: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<Duplicate>) {
+    let sizes: Vec<_> = files.iter().map(|file| fs::metadata(file).ok().map(|m| m.len())).collect();
+    let mut size_counts: HashMap<u64, usize> = HashMap::new();
+    for size in sizes.iter().flatten() {
+        *size_counts.entry(*size).or_default() += 1;
+    }
+
+    let mut originals: HashMap<String, PathBuf> = HashMap::new();
+    let mut unique = Vec::new();
+    let mut duplicates = Vec::new();
+    for (file, size) in files.into_iter().zip(sizes) {
+        let shares_size = size.is_some_and(|size| size_counts[&size] > 1);
+        let fingerprint = shares_size.then(|| source_fingerprint(&file).ok()).flatten();
+        match fingerprint {
+            Some(fingerprint) => match originals.get(&fingerprint) {
+                Some(original) => duplicates.push(Duplicate { file, original: original.clone() }),
+                None => {
+                    originals.insert(fingerprint, file.clone());
+                    unique.push(file);
+                }
+            },
+            None => unique.push(file),
+        }
+    }
+    (unique, duplicates)
+}
+
+/// 把重复文件的输出硬链接到已转换的输出，无法硬链接时复制
+///
+/// 目标文件已存在时保留不动。
+///
+/// # 错误
+///
+/// 无法创建目录或复制文件时返回 I/O 错误
+pub fn link_output(original_output: &Path, duplicate_output: &Path) -> Result<()> {
+    if duplicate_output.exists() {
+        return Ok(());
+    }
+    if let Some(parent) = duplicate_output.parent() {
+        fs::create_dir_all(parent).context(text("创建输出目录", "create output directory"), parent)?;
+    }
+    if fs::hard_link(original_output, duplicate_output).is_err() {
+        fs::copy(original_output, duplicate_output).context(text(
+            "复制重复文件的输出",
+            "copy the output of a duplicate file",
+        ), duplicate_output)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_split_duplicates() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<_> = [("a.mp4", "same"), ("b.mp4", "diff"), ("c.mkv", "same"), ("d.mp4", "longer")]
+            .into_iter()
+            .map(|(name, content)| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, content).unwrap();
+                path
+            })
+            .collect();
+
+        let (unique, duplicates) = split_duplicates(files.clone());
+        assert_eq!(unique, [files[0].clone(), files[1].clone(), files[3].clone()]);
+        assert_eq!(duplicates, [Duplicate { file: files[2].clone(), original: files[0].clone() }]);
+    }
+}
@@ -0,0 +1,258 @@
+//! # 目录配置模块
+//!
+//! 源目录中可以放置 `.video2audio.toml`，为该目录及其子目录中的文件单独指定格式、
+//! 码率、滤镜和输出目录等设置。同一次批处理中，讲座录像和音乐视频可以分别处理。
+//!
+//! 转换每个文件时从源根目录到文件所在目录依次读取目录配置，越靠近文件的配置优先级越高，
+//! 目录配置优先于命令行参数。
+//!
+//! ```toml
+//! # 讲座/.video2audio.toml
+//! format = "opus"
+//! bitrate = 48
+//! audio_filter = "highpass=f=80"
+//! normalize = true
+//! ```
+
+use crate::audio_format::AudioFormat;
+use crate::error::{Result, VideoToAudioError};
+use crate::loudness::{LoudnessTarget, Normalization};
+use crate::options::ConversionOptions;
+use crate::remote;
+use crate::tr;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 目录配置文件的文件名
+pub const DIR_CONFIG_FILE_NAME: &str = ".video2audio.toml";
+
+/// 一个目录中的配置覆盖，未设置的字段沿用上级目录或命令行的设置
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct DirConfig {
+    /// 目标音频格式 (mp3/aac/opus/m4b)
+    pub format: Option<String>,
+
+    /// 音频码率 (kbps)
+    pub bitrate: Option<u32>,
+
+    /// 采样率 (Hz)
+    pub sample_rate: Option<u32>,
+
+    /// 音频滤镜，替换 `--af` 指定的滤镜
+    pub audio_filter: Option<String>,
+
+    /// 是否裁剪首尾静音
+    pub trim_silence: Option<bool>,
+
+    /// 是否启用响度标准化
+    pub normalize: Option<bool>,
+
+    /// 输出目录，相对路径相对于配置文件所在的目录
+    pub output_dir: Option<PathBuf>,
+}
+
+impl DirConfig {
+    /// 读取目录中的配置文件，文件不存在时返回 `None`
+    ///
+    /// # 错误
+    ///
+    /// 无法读取文件或内容格式错误时返回错误
+    pub fn load(dir: &Path) -> Result<Option<Self>> {
+        let path = dir.join(DIR_CONFIG_FILE_NAME);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let mut config: Self = toml::from_str(&fs::read_to_string(&path)?).map_err(|e| {
+            VideoToAudioError::InvalidInput(tr!(
+                "目录配置 {} 格式错误: {e}",
+                "Malformed directory configuration {}: {e}",
+                path.display(),
+            ))
+        })?;
+        config.audio_format()?;
+        config.output_dir = config.output_dir.map(|output_dir| dir.join(output_dir));
+        Ok(Some(config))
+    }
+
+    /// 解析配置中的音频格式，未设置时返回 `None`
+    pub fn audio_format(&self) -> Result<Option<AudioFormat>> {
+        self.format.as_deref().map(AudioFormat::from_user_input).transpose()
+    }
+
+    /// 用下级目录的配置覆盖已设置的字段
+    pub fn merge(&mut self, nearer: &DirConfig) {
+        let nearer = nearer.clone();
+        self.format = nearer.format.or(self.format.take());
+        self.bitrate = nearer.bitrate.or(self.bitrate);
+        self.sample_rate = nearer.sample_rate.or(self.sample_rate);
+        self.audio_filter = nearer.audio_filter.or(self.audio_filter.take());
+        self.trim_silence = nearer.trim_silence.or(self.trim_silence);
+        self.normalize = nearer.normalize.or(self.normalize);
+        self.output_dir = nearer.output_dir.or(self.output_dir.take());
+    }
+
+    /// 把配置应用到转换选项
+    ///
+    /// 改变格式时清除为原格式选择的编码器；开启响度标准化而选项中没有标准化设置时，
+    /// 使用 `normalization` 作为标准化设置
+    ///
+    /// # 错误
+    ///
+    /// 格式无效时返回 [`VideoToAudioError::UnsupportedFormat`]
+    pub fn apply(&self, options: &ConversionOptions, normalization: &Normalization) -> Result<ConversionOptions> {
+        let mut options = options.clone();
+        if let Some(format) = self.audio_format()? {
+            if format != options.format {
+                options.format = format;
+                options.encoder = None;
+            }
+        }
+        if let Some(kbps) = self.bitrate {
+            options.bitrate = Some(kbps);
+        }
+        if let Some(sample_rate) = self.sample_rate {
+            options.sample_rate = Some(sample_rate);
+        }
+        if let Some(filter) = &self.audio_filter {
+            options.filters = vec![filter.clone()];
+        }
+        if let Some(trim_silence) = self.trim_silence {
+            options.trim_silence = trim_silence;
+        }
+        match self.normalize {
+            Some(true) if options.normalize.is_none() => options.normalize = Some(normalization.clone()),
+            Some(false) => options.normalize = None,
+            _ => {}
+        }
+        Ok(options.with_reencode_if_needed())
+    }
+}
+
+/// 按源文件所在的目录查找并合并目录配置
+///
+/// 读取过的目录配置会被缓存，批处理期间修改配置文件不会生效
+#[derive(Debug)]
+pub struct DirConfigResolver {
+    root: Option<PathBuf>,
+    normalization: Normalization,
+    cache: Mutex<HashMap<PathBuf, Option<DirConfig>>>,
+}
+
+impl DirConfigResolver {
+    /// 创建目录配置查找器
+    ///
+    /// # 参数
+    ///
+    /// * `root` - 源根目录，只读取该目录及其子目录中的配置；为 `None` 时只读取文件所在目录的配置
+    pub fn new(root: Option<PathBuf>) -> Self {
+        Self {
+            root,
+            normalization: Normalization::new(LoudnessTarget::default()),
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// 设置目录配置开启响度标准化时使用的标准化设置
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// 获取适用于源文件的合并后的目录配置，没有任何目录配置时返回默认值
+    ///
+    /// # 错误
+    ///
+    /// 目录配置无法读取或格式错误时返回错误
+    pub fn resolve(&self, source_file: &Path) -> Result<DirConfig> {
+        let mut resolved = DirConfig::default();
+        if remote::is_remote_url(source_file) {
+            return Ok(resolved);
+        }
+        let Some(parent) = source_file.parent() else { return Ok(resolved) };
+
+        // 从源根目录向下合并到文件所在目录
+        let mut dirs = match &self.root {
+            Some(root) => parent.ancestors().take_while(|dir| dir.starts_with(root)).collect(),
+            None => vec![parent],
+        };
+        dirs.reverse();
+        for dir in dirs {
+            if let Some(config) = self.load_cached(dir)? {
+                resolved.merge(&config);
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// 获取源文件实际使用的转换选项和输出目录
+    ///
+    /// # 错误
+    ///
+    /// 目录配置无法读取或格式错误时返回错误
+    pub fn options_for(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: &ConversionOptions,
+    ) -> Result<(ConversionOptions, PathBuf)> {
+        let config = self.resolve(source_file)?;
+        if config == DirConfig::default() {
+            return Ok((options.clone(), output_dir.to_path_buf()));
+        }
+        let output_dir = config.output_dir.clone().unwrap_or_else(|| output_dir.to_path_buf());
+        Ok((config.apply(options, &self.normalization)?, output_dir))
+    }
+
+    fn load_cached(&self, dir: &Path) -> Result<Option<DirConfig>> {
+        if let Some(config) = self.cache.lock().unwrap().get(dir) {
+            return Ok(config.clone());
+        }
+        // 格式错误的配置不缓存，每个受影响的文件都会报告错误
+        let config = DirConfig::load(dir)?;
+        self.cache.lock().unwrap().insert(dir.to_path_buf(), config.clone());
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_nearest_dir_config_wins() {
+        let temp_dir = TempDir::new().unwrap();
+        let talks = temp_dir.path().join("talks");
+        let music = temp_dir.path().join("music");
+        fs::create_dir_all(talks.join("2024")).unwrap();
+        fs::create_dir_all(&music).unwrap();
+        fs::write(temp_dir.path().join(DIR_CONFIG_FILE_NAME), "bitrate = 192\n").unwrap();
+        fs::write(talks.join(DIR_CONFIG_FILE_NAME), "format = \"opus\"\nbitrate = 48\nnormalize = true\n").unwrap();
+        fs::write(talks.join("2024").join(DIR_CONFIG_FILE_NAME), "output_dir = \"speech\"\n").unwrap();
+
+        let resolver = DirConfigResolver::new(Some(temp_dir.path().to_path_buf()));
+        let options = ConversionOptions::new(AudioFormat::Mp3).with_filter("volume=2");
+        let output_dir = temp_dir.path().join("out");
+
+        let (talk_options, talk_output) =
+            resolver.options_for(&talks.join("2024").join("a.mp4"), &output_dir, &options).unwrap();
+        assert_eq!(talk_options.format, AudioFormat::Opus);
+        assert_eq!(talk_options.bitrate, Some(48));
+        assert_eq!(talk_options.filters, ["volume=2"]);
+        assert!(talk_options.normalize.is_some());
+        assert_eq!(talk_output, talks.join("2024").join("speech"));
+
+        let (music_options, music_output) = resolver.options_for(&music.join("b.mp4"), &output_dir, &options).unwrap();
+        assert_eq!(music_options.format, AudioFormat::Mp3);
+        assert_eq!(music_options.bitrate, Some(192));
+        assert_eq!(music_output, output_dir);
+
+        fs::write(music.join(DIR_CONFIG_FILE_NAME), "fromat = \"opus\"\n").unwrap();
+        let resolver = DirConfigResolver::new(Some(temp_dir.path().to_path_buf()));
+        assert!(resolver.options_for(&music.join("b.mp4"), &output_dir, &options).is_err());
+    }
+}
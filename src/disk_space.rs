@@ -0,0 +1,197 @@
+//! # 磁盘空间检查模块
+//!
+//! 在批处理开始前估算输出所需的空间，并与输出目录所在磁盘的剩余空间比较，
+//! 避免长时间运行到一半时因磁盘写满而失败。
+//!
+//! 在能够探测时长之前，按源文件大小的保守比例估算输出大小：
+//! 常见视频中音频只占很小一部分，按比例估算通常会偏大，宁可多估也不要少估。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::options::ConversionOptions;
+use crate::tr;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 剩余空间低于所需空间的这一倍数时给出警告
+const TIGHT_SPACE_FACTOR: f64 = 1.1;
+
+/// 输出空间估算结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpaceEstimate {
+    /// 估算的输出总大小（字节）
+    pub required: u64,
+
+    /// 输出目录所在磁盘的可用空间（字节）
+    pub available: u64,
+}
+
+impl SpaceEstimate {
+    /// 估算批量转换所需的空间
+    ///
+    /// # 参数
+    ///
+    /// * `files` - 要转换的视频文件
+    /// * `output_dir` - 输出目录，不存在时使用最近的已存在的上级目录所在的磁盘
+    /// * `options` - 转换选项
+    ///
+    /// # 错误
+    ///
+    /// 无法获取磁盘可用空间时返回错误
+    pub fn for_batch(files: &[PathBuf], output_dir: &Path, options: &ConversionOptions) -> Result<Self> {
+        let required = files
+            .iter()
+            .filter_map(|file| file.metadata().ok())
+            .map(|metadata| estimate_output_size(metadata.len(), options))
+            .sum();
+        let available = available_space(output_dir)?;
+        Ok(Self { required, available })
+    }
+
+    /// 可用空间是否足够
+    pub fn is_sufficient(&self) -> bool {
+        self.required <= self.available
+    }
+
+    /// 可用空间虽然足够但余量很小
+    pub fn is_tight(&self) -> bool {
+        self.is_sufficient() && (self.available as f64) < self.required as f64 * TIGHT_SPACE_FACTOR
+    }
+
+    /// 可用空间不足时返回错误
+    pub fn ensure_sufficient(&self) -> Result<()> {
+        if self.is_sufficient() {
+            return Ok(());
+        }
+        Err(VideoToAudioError::from(io::Error::new(
+            io::ErrorKind::StorageFull,
+            tr!(
+                "输出目录所在磁盘空间不足：预计需要 {}，可用 {}。请清理磁盘或更换输出目录，也可以使用 --skip-space-check 跳过检查",
+                "Not enough disk space for the output directory: about {} needed, {} available. Free up space or choose another output directory, or pass --skip-space-check to skip this check",
+                format_bytes(self.required),
+                format_bytes(self.available)
+            ),
+        )))
+    }
+}
+
+/// 根据源文件大小估算单个输出文件的大小
+///
+/// 输出大小与源文件大小的比例按常见视频偏大地估计；
+/// 指定码率时按与默认码率的比值缩放。
+pub fn estimate_output_size(source_size: u64, options: &ConversionOptions) -> u64 {
+    let (ratio, default_kbps) = match options.format {
+        AudioFormat::Mp3 => (0.25, 245.0),
+        AudioFormat::AacCopy => (0.25, 0.0),
+        AudioFormat::Opus => (0.2, 192.0),
+        AudioFormat::M4b => (0.15, 128.0),
+    };
+    let scale = match options.bitrate {
+        Some(kbps) if default_kbps > 0.0 => f64::from(kbps) / default_kbps,
+        _ => 1.0,
+    };
+    (source_size as f64 * ratio * scale) as u64
+}
+
+/// 获取路径所在磁盘对当前用户可用的空间（字节）
+///
+/// 路径不存在时使用最近的已存在的上级目录
+pub fn available_space(path: &Path) -> Result<u64> {
+    let existing = path
+        .ancestors()
+        .find(|ancestor| ancestor.exists())
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    Ok(platform_available_space(existing)?)
+}
+
+#[cfg(unix)]
+fn platform_available_space(path: &Path) -> io::Result<u64> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, text(
+            "路径中包含空字符",
+            "the path contains a NUL character",
+        )))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: path 是以空字符结尾的有效字符串，stat 是可写的 statvfs 结构
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn platform_available_space(path: &Path) -> io::Result<u64> {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetDiskFreeSpaceExW;
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut available = 0u64;
+    // SAFETY: wide 是以空字符结尾的宽字符串，不需要的输出参数传空指针
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, std::ptr::null_mut(), std::ptr::null_mut())
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(available)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn platform_available_space(_path: &Path) -> io::Result<u64> {
+    Ok(u64::MAX)
+}
+
+/// 把字节数格式化为便于阅读的大小
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_estimate_scales_with_bitrate() {
+        let default = estimate_output_size(1_000_000, &ConversionOptions::new(AudioFormat::Opus));
+        let half = estimate_output_size(1_000_000, &ConversionOptions::new(AudioFormat::Opus).with_bitrate(96));
+        assert_eq!(default, 200_000);
+        assert_eq!(half, 100_000);
+    }
+
+    #[test]
+    fn test_available_space_for_missing_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("not/yet/created");
+        assert!(available_space(&missing).unwrap() > 0);
+    }
+
+    #[test]
+    fn test_insufficient_space_is_refused() {
+        let estimate = SpaceEstimate { required: 2048, available: 1024 };
+        assert!(!estimate.is_sufficient());
+        match estimate.ensure_sufficient() {
+            Err(VideoToAudioError::Io { source, .. }) => assert_eq!(source.kind(), io::ErrorKind::StorageFull),
+            other => panic!("应该返回磁盘空间不足错误: {other:?}"),
+        }
+        assert!(SpaceEstimate { required: 1000, available: 1050 }.is_tight());
+        assert_eq!(format_bytes(1536), "1.5 KB");
+    }
+}
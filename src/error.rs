@@ -3,76 +3,215 @@
 //! 定义了项目中使用的所有错误类型和结果类型。
 //! 提供统一的错误处理机制，便于错误信息的管理和用户友好的错误提示。
 
-use std::fmt;
+use crate::i18n::text;
+use std::io;
+use std::path::{Path, PathBuf};
 
 /// 项目的主要错误类型
 /// 
 /// 这个枚举包含了在视频转音频过程中可能遇到的所有错误情况。
 /// 每种错误都提供了详细的上下文信息，便于调试和用户理解。
-#[derive(Debug)]
+#[derive(Debug, thiserror::Error)]
 pub enum VideoToAudioError {
     /// I/O 操作错误（文件读写、目录创建等）
-    Io(std::io::Error),
+    /// 记录失败的操作和涉及的路径，原始的 I/O 错误作为错误来源保留
+    #[error("{}: {}", text("文件操作错误", "File operation failed"), describe_io(*.operation, .path.as_deref(), .source))]
+    Io {
+        /// 失败的操作，如 "创建输出目录"；直接从 `std::io::Error` 转换时为空
+        operation: Option<&'static str>,
+
+        /// 操作涉及的文件或目录
+        path: Option<PathBuf>,
+
+        /// 原始的 I/O 错误
+        #[source]
+        source: io::Error,
+    },
     
     /// FFmpeg 执行错误
-    /// 包含 FFmpeg 的错误输出信息
+    /// 包含 FFmpeg 的错误输出信息，无法归入以下具体原因时使用
+    #[error("{}: {}", text("FFmpeg 执行错误", "FFmpeg failed"), .0)]
     FfmpegError(String),
+
+    /// 输入文件中没有音频流
+    /// 由 [`VideoToAudioError::from_ffmpeg_stderr`] 根据 FFmpeg 的错误输出识别，以下四种相同
+    #[error("{}: {}", text("输入文件中没有音频流", "The input has no audio stream"), .0)]
+    NoAudioStream(String),
+
+    /// FFmpeg 缺少所需的编码器或解码器，或目标容器不支持该编码
+    #[error("{}: {}", text("FFmpeg 不支持所需的编解码器", "FFmpeg does not support the required codec"), .0)]
+    UnsupportedCodec(String),
+
+    /// 输入文件损坏或不是有效的媒体文件
+    #[error("{}: {}", text("输入文件已损坏或不是有效的媒体文件", "The input is corrupt or not a valid media file"), .0)]
+    CorruptInput(String),
+
+    /// 没有读取输入或写入输出的权限
+    #[error("{}: {}", text("权限不足", "Permission denied"), .0)]
+    PermissionDenied(String),
+
+    /// 输出所在的磁盘已满或超出配额
+    #[error("{}: {}", text("磁盘空间不足", "Disk full"), .0)]
+    DiskFull(String),
     
     /// 文件路径相关错误
     /// 当文件路径无效或无法处理时抛出
+    #[error("{}: {}", text("无效的文件路径", "Invalid path"), .0)]
     InvalidPath(String),
     
     /// 用户输入错误
     /// 当用户输入无效数据时抛出
+    #[error("{}: {}", text("无效的用户输入", "Invalid input"), .0)]
     InvalidInput(String),
     
     /// 不支持的文件格式
     /// 当遇到不支持的视频或音频格式时抛出
+    #[error("{}: {}", text("不支持的文件格式", "Unsupported format"), .0)]
     UnsupportedFormat(String),
     
     /// 系统依赖缺失错误
     /// 当系统缺少必要的依赖（如 FFmpeg）时抛出
+    #[error("{}: {}", text("缺少系统依赖", "Missing dependency"), .0)]
     MissingDependency(String),
+
+    /// 操作已取消
+    /// 当调用方通过取消令牌中止处理时返回
+    #[error("{}", text("操作已取消", "Operation cancelled"))]
+    Cancelled,
+}
+
+/// 生成 I/O 错误的说明：`<操作> <路径>: <原始错误>`，没有上下文的部分省略
+fn describe_io(operation: Option<&str>, path: Option<&Path>, source: &io::Error) -> String {
+    match (operation, path) {
+        (Some(operation), Some(path)) => format!("{operation} {}: {source}", path.display()),
+        (Some(operation), None) => format!("{operation}: {source}"),
+        (None, Some(path)) => format!("{}: {source}", path.display()),
+        (None, None) => source.to_string(),
+    }
 }
 
-impl fmt::Display for VideoToAudioError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+/// 根据错误信息构造具体错误的函数，即各个变体的构造函数
+type ErrorConstructor = fn(String) -> VideoToAudioError;
+
+/// FFmpeg 错误输出中的特征文本和对应的错误原因，按顺序匹配，不区分大小写
+///
+/// 系统错误排在前面：磁盘写满或没有权限时 FFmpeg 往往还会输出其他看似相关的错误。
+const FFMPEG_ERROR_PATTERNS: &[(&str, ErrorConstructor)] = &[
+    ("no space left on device", VideoToAudioError::DiskFull),
+    ("disk quota exceeded", VideoToAudioError::DiskFull),
+    ("permission denied", VideoToAudioError::PermissionDenied),
+    ("operation not permitted", VideoToAudioError::PermissionDenied),
+    ("matches no streams", VideoToAudioError::NoAudioStream),
+    ("does not contain any stream", VideoToAudioError::NoAudioStream),
+    ("unknown encoder", VideoToAudioError::UnsupportedCodec),
+    ("encoder not found", VideoToAudioError::UnsupportedCodec),
+    ("decoder not found", VideoToAudioError::UnsupportedCodec),
+    ("not currently supported in container", VideoToAudioError::UnsupportedCodec),
+    ("could not find tag for codec", VideoToAudioError::UnsupportedCodec),
+    ("invalid data found when processing input", VideoToAudioError::CorruptInput),
+    ("moov atom not found", VideoToAudioError::CorruptInput),
+    ("ebml header parsing failed", VideoToAudioError::CorruptInput),
+    ("could not find codec parameters", VideoToAudioError::CorruptInput),
+];
+
+impl VideoToAudioError {
+    /// 构造带有操作和路径的 I/O 错误
+    pub fn io(operation: &'static str, path: &Path, source: io::Error) -> Self {
+        VideoToAudioError::Io { operation: Some(operation), path: Some(path.to_path_buf()), source }
+    }
+
+    /// 为缺少路径的 I/O 错误补充正在处理的文件
+    ///
+    /// 批量转换中的错误由此标明所属的源文件；已有路径的 I/O 错误和其他错误保持不变。
+    pub fn for_file(self, source_file: &Path) -> Self {
         match self {
-            VideoToAudioError::Io(err) => {
-                write!(f, "文件操作错误: {err}")
-            }
-            VideoToAudioError::FfmpegError(msg) => {
-                write!(f, "FFmpeg 执行错误: {msg}")
-            }
-            VideoToAudioError::InvalidPath(path) => {
-                write!(f, "无效的文件路径: {path}")
-            }
-            VideoToAudioError::InvalidInput(input) => {
-                write!(f, "无效的用户输入: {input}")
-            }
-            VideoToAudioError::UnsupportedFormat(format) => {
-                write!(f, "不支持的文件格式: {format}")
-            }
-            VideoToAudioError::MissingDependency(dep) => {
-                write!(f, "缺少系统依赖: {dep}")
-            }
+            VideoToAudioError::Io { operation, path: None, source } => VideoToAudioError::Io {
+                operation: operation.or(Some(text("转换", "convert"))),
+                path: Some(source_file.to_path_buf()),
+                source,
+            },
+            error => error,
+        }
+    }
+
+    /// 根据 FFmpeg 的错误输出判断失败原因
+    ///
+    /// `message` 为完整的错误信息，识别出的原因只决定错误类型；
+    /// 无法识别时返回 [`VideoToAudioError::FfmpegError`]。
+    pub fn from_ffmpeg_stderr(stderr: &str, message: String) -> Self {
+        let stderr = stderr.to_lowercase();
+        match FFMPEG_ERROR_PATTERNS.iter().find(|(pattern, _)| stderr.contains(pattern)) {
+            Some((_, variant)) => variant(message),
+            None => VideoToAudioError::FfmpegError(message),
         }
     }
-}
 
-impl std::error::Error for VideoToAudioError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    /// 固定的错误代码，随错误信息显示，参见 [`crate::error_codes`]
+    pub fn code(&self) -> &'static str {
         match self {
-            VideoToAudioError::Io(err) => Some(err),
-            _ => None,
+            VideoToAudioError::MissingDependency(_) => "E001",
+            VideoToAudioError::Io { .. } => "E002",
+            VideoToAudioError::InvalidPath(_) => "E003",
+            VideoToAudioError::InvalidInput(_) => "E004",
+            VideoToAudioError::UnsupportedFormat(_) => "E005",
+            VideoToAudioError::Cancelled => "E006",
+            VideoToAudioError::NoAudioStream(_) => "E010",
+            VideoToAudioError::UnsupportedCodec(_) => "E011",
+            VideoToAudioError::CorruptInput(_) => "E012",
+            VideoToAudioError::PermissionDenied(_) => "E013",
+            VideoToAudioError::DiskFull(_) => "E014",
+            VideoToAudioError::FfmpegError(_) => "E019",
         }
     }
+
+    /// 重试同一个文件是否可能成功
+    ///
+    /// 输入没有音频流、输入已损坏、权限不足和磁盘已满不会因立即重试而改变，批量转换不再重试这些文件；
+    /// 缺少编解码器时仍然重试，因为重试时可能改用后备格式（参见 `FileProcessor::with_fallback_format`）。
+    pub fn is_retryable(&self) -> bool {
+        !matches!(
+            self,
+            VideoToAudioError::NoAudioStream(_)
+                | VideoToAudioError::CorruptInput(_)
+                | VideoToAudioError::PermissionDenied(_)
+                | VideoToAudioError::DiskFull(_)
+                | VideoToAudioError::Cancelled
+        )
+    }
 }
 
-// 实现从标准库错误类型的自动转换
-impl From<std::io::Error> for VideoToAudioError {
-    fn from(err: std::io::Error) -> Self {
-        VideoToAudioError::Io(err)
+// 实现从标准库错误类型的自动转换，转换得到的错误没有操作和路径信息，
+// 需要时用 [`IoResultExt::context`] 补充
+impl From<io::Error> for VideoToAudioError {
+    fn from(source: io::Error) -> Self {
+        VideoToAudioError::Io { operation: None, path: None, source }
+    }
+}
+
+impl From<dialoguer::Error> for VideoToAudioError {
+    fn from(err: dialoguer::Error) -> Self {
+        VideoToAudioError::from(io::Error::from(err))
+    }
+}
+
+/// 为 I/O 结果补充失败的操作和涉及的路径
+///
+/// ```
+/// use video2audio_rs::error::IoResultExt;
+/// use std::path::Path;
+///
+/// let path = Path::new("/nonexistent/dir/file.txt");
+/// let error = std::fs::read(path).context("读取文件", path).unwrap_err();
+/// assert!(error.to_string().contains("读取文件 /nonexistent/dir/file.txt"));
+/// ```
+pub trait IoResultExt<T> {
+    /// 失败时转换为带有操作和路径的 [`VideoToAudioError::Io`]
+    fn context(self, operation: &'static str, path: &Path) -> Result<T>;
+}
+
+impl<T> IoResultExt<T> for io::Result<T> {
+    fn context(self, operation: &'static str, path: &Path) -> Result<T> {
+        self.map_err(|source| VideoToAudioError::io(operation, path, source))
     }
 }
 
@@ -90,8 +229,11 @@ mod tests {
 
     #[test]
     fn test_error_display() {
-        let io_err = VideoToAudioError::Io(io::Error::new(io::ErrorKind::NotFound, "文件未找到"));
-        assert!(io_err.to_string().contains("文件操作错误"));
+        let io_err = VideoToAudioError::from(io::Error::new(io::ErrorKind::NotFound, "文件未找到"));
+        assert_eq!(io_err.to_string(), "文件操作错误: 文件未找到");
+
+        let io_err = VideoToAudioError::io("创建输出目录", Path::new("/music/out"), io::Error::other("权限不足"));
+        assert_eq!(io_err.to_string(), "文件操作错误: 创建输出目录 /music/out: 权限不足");
 
         let ffmpeg_err = VideoToAudioError::FfmpegError("编码失败".to_string());
         assert_eq!(ffmpeg_err.to_string(), "FFmpeg 执行错误: 编码失败");
@@ -107,14 +249,53 @@ mod tests {
 
         let dep_err = VideoToAudioError::MissingDependency("ffmpeg".to_string());
         assert_eq!(dep_err.to_string(), "缺少系统依赖: ffmpeg");
+
+        assert_eq!(VideoToAudioError::Cancelled.to_string(), "操作已取消");
+    }
+
+    #[test]
+    fn test_from_ffmpeg_stderr() {
+        let classify = |stderr: &str| VideoToAudioError::from_ffmpeg_stderr(stderr, format!("转换失败: {stderr}"));
+
+        assert!(matches!(
+            classify("Stream map '0:a:0' matches no streams.\nTo ignore this, add a trailing '?' to the map."),
+            VideoToAudioError::NoAudioStream(_)
+        ));
+        assert!(matches!(
+            classify("Output file #0 does not contain any stream"),
+            VideoToAudioError::NoAudioStream(_)
+        ));
+        assert!(matches!(classify("Unknown encoder 'libfdk_aac'"), VideoToAudioError::UnsupportedCodec(_)));
+        assert!(matches!(
+            classify("/videos/a.mp4: Invalid data found when processing input"),
+            VideoToAudioError::CorruptInput(_)
+        ));
+        assert!(matches!(classify("[mov,mp4] moov atom not found"), VideoToAudioError::CorruptInput(_)));
+        assert!(matches!(classify("/audio/a.mp3.part: Permission denied"), VideoToAudioError::PermissionDenied(_)));
+        // 磁盘写满时的其他错误不影响判断
+        assert!(matches!(
+            classify("Error writing trailer: No space left on device\nError muxing a packet"),
+            VideoToAudioError::DiskFull(_)
+        ));
+
+        let unknown = classify("Conversion failed!");
+        assert_eq!(unknown.to_string(), "FFmpeg 执行错误: 转换失败: Conversion failed!");
+
+        let no_audio = classify("matches no streams");
+        assert_eq!(no_audio.to_string(), "输入文件中没有音频流: 转换失败: matches no streams");
+        assert!(!no_audio.is_retryable());
+        assert!(unknown.is_retryable());
+        assert!(classify("Unknown encoder 'libopus'").is_retryable());
+        assert!(!classify("No space left on device").is_retryable());
     }
 
     #[test]
     fn test_error_source() {
         let io_error = io::Error::new(io::ErrorKind::PermissionDenied, "权限不足");
-        let video_error = VideoToAudioError::Io(io_error);
+        let video_error = VideoToAudioError::io("读取文件", Path::new("a.mp4"), io_error);
 
-        assert!(video_error.source().is_some());
+        let source = video_error.source().expect("应该保留原始的 I/O 错误");
+        assert_eq!(source.downcast_ref::<io::Error>().unwrap().kind(), io::ErrorKind::PermissionDenied);
 
         let ffmpeg_error = VideoToAudioError::FfmpegError("测试错误".to_string());
         assert!(ffmpeg_error.source().is_none());
@@ -126,11 +307,31 @@ mod tests {
         let video_error: VideoToAudioError = io_error.into();
 
         match video_error {
-            VideoToAudioError::Io(_) => (),
+            VideoToAudioError::Io { operation: None, path: None, .. } => (),
             _ => panic!("应该转换为 Io 错误"),
         }
     }
 
+    #[test]
+    fn test_io_context() {
+        let missing = Path::new("/nonexistent/video.mp4");
+        let error = std::fs::metadata(missing).context("读取文件信息", missing).unwrap_err();
+        match &error {
+            VideoToAudioError::Io { operation: Some("读取文件信息"), path: Some(path), source } => {
+                assert_eq!(path, missing);
+                assert_eq!(source.kind(), io::ErrorKind::NotFound);
+            }
+            other => panic!("应该带有操作和路径: {other:?}"),
+        }
+
+        // 已有路径的错误保持不变，缺少路径的错误补充正在处理的文件
+        let source_file = Path::new("videos/a.mp4");
+        assert!(error.for_file(source_file).to_string().contains("读取文件信息 /nonexistent/video.mp4"));
+        let error = VideoToAudioError::from(io::Error::other("磁盘错误")).for_file(source_file);
+        assert_eq!(error.to_string(), "文件操作错误: 转换 videos/a.mp4: 磁盘错误");
+        assert!(matches!(VideoToAudioError::Cancelled.for_file(source_file), VideoToAudioError::Cancelled));
+    }
+
     #[test]
     fn test_result_type() {
         fn test_function() -> Result<String> {
@@ -0,0 +1,251 @@
+//! # 错误代码模块
+//!
+//! 每种错误都有一个固定不变的代码（参见 [`VideoToAudioError::code`]），随错误信息一起显示，
+//! 便于在文档和问题反馈中查找。`video2audio-rs explain <代码>` 显示该错误的常见原因和解决方法。
+//!
+//! 代码按类别分段：`E00x` 为环境和输入问题，`E01x` 为 FFmpeg 报告的转换失败。
+//! 已分配的代码不会改变含义，删除的错误类型也不会复用其代码。
+//!
+//! [`VideoToAudioError::code`]: crate::error::VideoToAudioError::code
+
+use crate::i18n::text;
+
+/// 一个错误代码的说明，文字均为 (中文, 英文)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ErrorCodeInfo {
+    /// 错误代码，如 `E010`
+    pub code: &'static str,
+
+    /// 对应的错误类型名称
+    pub name: &'static str,
+
+    /// 一句话说明
+    pub summary: (&'static str, &'static str),
+
+    /// 常见原因
+    pub causes: &'static [(&'static str, &'static str)],
+
+    /// 解决方法
+    pub fixes: &'static [(&'static str, &'static str)],
+}
+
+impl ErrorCodeInfo {
+    /// 当前界面语言的一句话说明
+    pub fn summary(&self) -> &'static str {
+        text(self.summary.0, self.summary.1)
+    }
+
+    /// 当前界面语言的常见原因
+    pub fn causes(&self) -> impl Iterator<Item = &'static str> {
+        self.causes.iter().map(|(zh, en)| text(zh, en))
+    }
+
+    /// 当前界面语言的解决方法
+    pub fn fixes(&self) -> impl Iterator<Item = &'static str> {
+        self.fixes.iter().map(|(zh, en)| text(zh, en))
+    }
+}
+
+/// 全部错误代码，按代码排序
+pub const ERROR_CODES: &[ErrorCodeInfo] = &[
+    ErrorCodeInfo {
+        code: "E001",
+        name: "MissingDependency",
+        summary: ("缺少 FFmpeg 等系统依赖", "A system dependency such as FFmpeg is missing"),
+        causes: &[
+            ("没有安装 FFmpeg，或 FFmpeg 不在 PATH 中", "FFmpeg is not installed or not on the PATH"),
+            ("使用的功能没有编译进来（如 --tui 需要 tui 特性）", "The feature in use is not compiled in (e.g. --tui needs the tui feature)"),
+        ],
+        fixes: &[
+            ("安装 FFmpeg：brew install ffmpeg / choco install ffmpeg / sudo apt install ffmpeg", "Install FFmpeg: brew install ffmpeg / choco install ffmpeg / sudo apt install ffmpeg"),
+            ("确认 ffmpeg -version 可以在终端中运行", "Check that ffmpeg -version runs in a terminal"),
+            ("启用 ffmpeg-download 特性时可以运行 video2audio-rs download-ffmpeg", "With the ffmpeg-download feature, run video2audio-rs download-ffmpeg"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E002",
+        name: "Io",
+        summary: ("文件操作失败", "A file operation failed"),
+        causes: &[
+            ("文件或目录不存在，或在处理过程中被移动、删除", "A file or directory does not exist or was moved or deleted during processing"),
+            ("没有读写权限", "Missing read or write permission"),
+            ("磁盘已满或网络存储暂时不可用", "The disk is full or network storage is temporarily unavailable"),
+        ],
+        fixes: &[
+            ("错误信息中列出了失败的操作和路径，先检查该路径", "The message names the failed operation and path, check that path first"),
+            ("网络存储的偶发错误可以用 --retries 自动重试", "Use --retries to retry transient network storage errors automatically"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E003",
+        name: "InvalidPath",
+        summary: ("文件路径无效", "Invalid path"),
+        causes: &[
+            ("源目录、源文件、封面或配置文件不存在", "The source directory, source file, cover image or configuration file does not exist"),
+            ("路径指向的类型不对，例如需要目录时给出了文件", "The path has the wrong type, e.g. a file where a directory is expected"),
+        ],
+        fixes: &[
+            ("检查路径拼写，必要时使用完整的绝对路径", "Check the spelling of the path and use a full absolute path if needed"),
+            ("包含空格的路径需要加引号", "Quote paths that contain spaces"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E004",
+        name: "InvalidInput",
+        summary: ("参数或输入无效", "Invalid argument or input"),
+        causes: &[
+            ("命令行参数或配置文件中的值超出范围或格式错误", "A command-line or configuration value is out of range or malformed"),
+            ("参数组合不能同时使用，例如 --end 早于 --start", "The arguments cannot be combined, e.g. --end is earlier than --start"),
+        ],
+        fixes: &[
+            ("按错误信息修正参数，运行 video2audio-rs --help 查看取值范围", "Fix the argument as described and run video2audio-rs --help for valid values"),
+            ("运行 video2audio-rs config show 检查配置文件中的设置", "Run video2audio-rs config show to check the configuration file"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E005",
+        name: "UnsupportedFormat",
+        summary: ("不支持的文件格式", "Unsupported format"),
+        causes: &[
+            ("指定的音频格式不在支持列表中", "The requested audio format is not supported"),
+            ("源文件的格式无法识别", "The source file's format is not recognized"),
+        ],
+        fixes: &[
+            ("运行 video2audio-rs --list-formats 查看支持的格式", "Run video2audio-rs --list-formats to see the supported formats"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E006",
+        name: "Cancelled",
+        summary: ("操作已取消", "Operation cancelled"),
+        causes: &[
+            ("按下了 Ctrl+C 或收到终止信号", "Ctrl+C was pressed or a termination signal was received"),
+            ("启用 --fail-fast 时其他文件转换失败", "Another file failed while --fail-fast was enabled"),
+        ],
+        fixes: &[
+            ("使用 --resume 从中断处继续", "Use --resume to continue where the batch stopped"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E010",
+        name: "NoAudioStream",
+        summary: ("输入文件中没有音频流", "The input has no audio stream"),
+        causes: &[
+            ("视频本身没有声音，例如屏幕录制或无声片段", "The video has no sound, e.g. a screen recording or a silent clip"),
+            ("--audio-track 或 --audio-lang 选择的音轨不存在", "The track selected with --audio-track or --audio-lang does not exist"),
+        ],
+        fixes: &[
+            ("用 ffprobe <文件> 确认文件中的音轨", "Check the file's tracks with ffprobe <file>"),
+            ("去掉 --audio-track/--audio-lang 或选择存在的音轨", "Remove --audio-track/--audio-lang or select an existing track"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E011",
+        name: "UnsupportedCodec",
+        summary: ("FFmpeg 不支持所需的编解码器", "FFmpeg does not support the required codec"),
+        causes: &[
+            ("FFmpeg 编译时没有包含目标格式的编码器（如 libmp3lame、libopus）", "FFmpeg was built without the encoder for the target format (e.g. libmp3lame, libopus)"),
+            ("源文件的编码无法解码", "The source's codec cannot be decoded"),
+            ("AAC 复制模式下源音频不是 AAC，无法直接放入目标容器", "In AAC copy mode the source audio is not AAC and cannot be copied into the container"),
+        ],
+        fixes: &[
+            ("运行 ffmpeg -encoders 检查可用的编码器，或安装完整版 FFmpeg", "Run ffmpeg -encoders to check the available encoders, or install a full FFmpeg build"),
+            ("换用其他输出格式，或用 --fallback-format 在重试时改用其他格式", "Choose another output format, or use --fallback-format to switch formats on retry"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E012",
+        name: "CorruptInput",
+        summary: ("输入文件已损坏或不是有效的媒体文件", "The input is corrupt or not a valid media file"),
+        causes: &[
+            ("文件没有下载或复制完整（如 MP4 缺少 moov atom）", "The file was not completely downloaded or copied (e.g. an MP4 without a moov atom)"),
+            ("扩展名是视频格式，但内容不是媒体文件", "The extension is a video format but the content is not media"),
+        ],
+        fixes: &[
+            ("重新下载或复制源文件", "Download or copy the source file again"),
+            ("用播放器或 ffprobe 确认文件可以打开", "Check that the file opens in a player or with ffprobe"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E013",
+        name: "PermissionDenied",
+        summary: ("权限不足", "Permission denied"),
+        causes: &[
+            ("源文件不可读", "The source file is not readable"),
+            ("输出目录不可写，或是只读的挂载点", "The output directory is not writable or is a read-only mount"),
+        ],
+        fixes: &[
+            ("检查源文件和输出目录的权限", "Check the permissions of the source file and output directory"),
+            ("用 --output 换到可写的输出目录", "Use --output to choose a writable output directory"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E014",
+        name: "DiskFull",
+        summary: ("磁盘空间不足", "Disk full"),
+        causes: &[
+            ("输出目录所在磁盘已满，或超出了磁盘配额", "The disk holding the output directory is full or over quota"),
+        ],
+        fixes: &[
+            ("清理磁盘或用 --output 换到空间充足的目录", "Free up space or use --output to choose a directory with enough space"),
+            ("之后用 --retry-failed 只重新转换失败的文件", "Then use --retry-failed to convert only the failed files"),
+        ],
+    },
+    ErrorCodeInfo {
+        code: "E019",
+        name: "FfmpegError",
+        summary: ("FFmpeg 执行失败，原因无法归入以上类别", "FFmpeg failed for a reason not covered above"),
+        causes: &[
+            ("转换超时", "The conversion timed out"),
+            ("远程地址无法访问", "A remote URL cannot be reached"),
+            ("FFmpeg 报告了其他错误", "FFmpeg reported another error"),
+        ],
+        fixes: &[
+            ("查看错误信息中 FFmpeg 的输出", "Read FFmpeg's output included in the message"),
+            ("加上 --verbose 显示完整的 FFmpeg 命令，复制到终端中重现", "Add --verbose to show the full FFmpeg command and rerun it in a terminal"),
+        ],
+    },
+];
+
+/// 按代码查找说明，不区分大小写
+pub fn lookup(code: &str) -> Option<&'static ErrorCodeInfo> {
+    ERROR_CODES.iter().find(|info| info.code.eq_ignore_ascii_case(code.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::VideoToAudioError;
+
+    #[test]
+    fn test_every_error_has_documented_code() {
+        let errors = [
+            VideoToAudioError::from(std::io::Error::other("x")),
+            VideoToAudioError::FfmpegError(String::new()),
+            VideoToAudioError::NoAudioStream(String::new()),
+            VideoToAudioError::UnsupportedCodec(String::new()),
+            VideoToAudioError::CorruptInput(String::new()),
+            VideoToAudioError::PermissionDenied(String::new()),
+            VideoToAudioError::DiskFull(String::new()),
+            VideoToAudioError::InvalidPath(String::new()),
+            VideoToAudioError::InvalidInput(String::new()),
+            VideoToAudioError::UnsupportedFormat(String::new()),
+            VideoToAudioError::MissingDependency(String::new()),
+            VideoToAudioError::Cancelled,
+        ];
+        for error in &errors {
+            let info = lookup(error.code()).unwrap_or_else(|| panic!("{} 没有说明", error.code()));
+            assert!(format!("{error:?}").starts_with(info.name), "{error:?} 与 {} 不对应", info.code);
+        }
+        assert_eq!(ERROR_CODES.len(), errors.len());
+
+        let codes: Vec<_> = ERROR_CODES.iter().map(|info| info.code).collect();
+        let mut sorted = codes.clone();
+        sorted.sort_unstable();
+        sorted.dedup();
+        assert_eq!(codes, sorted);
+
+        assert_eq!(lookup("e010").map(|info| info.name), Some("NoAudioStream"));
+        assert!(lookup("E999").is_none());
+    }
+}
@@ -0,0 +1,320 @@
+//! # 输出估算模块
+//!
+//! 根据 ffprobe 读取的时长和各格式的码率估算每个文件的输出大小，
+//! 并通过只转换开头几秒的试转换测出本机的编码速度，粗略估算整批转换的耗时。
+//! 在 Opus 96k、MP3 V0 等设置之间取舍时，不必先花几个小时转换一遍。
+//!
+//! 读取不到时长的文件按源文件大小的经验比例估算输出大小，不计入耗时估算。
+
+use crate::audio_format::AudioFormat;
+use crate::cancellation::CancellationToken;
+use crate::disk_space::estimate_output_size;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::{FileProcessor, OverwritePolicy};
+use crate::options::ConversionOptions;
+use crate::probe::{probe_audio_bitrate, probe_duration};
+use crate::remote::is_remote_url;
+use crate::tr;
+use rayon::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 试转换的长度
+pub const CALIBRATION_LENGTH: Duration = Duration::from_secs(10);
+
+/// MP3 各 VBR 质量档位（`-q:a 0` 到 `-q:a 9`）的平均码率 (kbps)，取自 LAME 文档
+const MP3_VBR_KBPS: [u32; 10] = [245, 225, 190, 175, 165, 130, 115, 100, 85, 65];
+
+/// 单个文件的输出估算
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputEstimate {
+    /// 源文件
+    pub source: PathBuf,
+
+    /// 源文件大小（字节），无法读取时为 0
+    pub source_bytes: u64,
+
+    /// 源文件时长，无法读取时为 `None`
+    pub duration: Option<Duration>,
+
+    /// 估算的输出大小（字节）
+    pub output_bytes: u64,
+}
+
+impl OutputEstimate {
+    /// 实际要转换的时长：设置了片段时只计算片段内的部分，时长未知时为 `None`
+    pub fn converted_duration(&self, options: &ConversionOptions) -> Option<Duration> {
+        self.duration.map(|duration| converted_duration(duration, options))
+    }
+}
+
+/// 输出音频的平均码率 (kbps)
+///
+/// # 参数
+///
+/// * `options` - 转换选项
+/// * `source_kbps` - 源文件音轨的码率，只在直接复制音频流时使用
+///
+/// # 返回值
+///
+/// 直接复制音频流但不知道源码率时为 `None`
+pub fn output_kbps(options: &ConversionOptions, source_kbps: Option<u32>) -> Option<u32> {
+    // 与 ConversionOptions::codec_args 选择编码参数的顺序一致
+    match (options.format, options.encoder.as_deref(), options.bitrate) {
+        (AudioFormat::AacCopy, None, _) => source_kbps,
+        (_, _, Some(kbps)) => Some(kbps),
+        (AudioFormat::Mp3, Some(_), None) => Some(320),
+        (_, Some(_), None) => Some(192),
+        (AudioFormat::Mp3, None, None) => {
+            let quality = usize::from(options.vbr_quality.unwrap_or(0)).min(MP3_VBR_KBPS.len() - 1);
+            Some(MP3_VBR_KBPS[quality])
+        }
+        (AudioFormat::Opus, None, None) => Some(192),
+        (AudioFormat::M4b, None, None) => Some(128),
+    }
+}
+
+/// 源文件中实际要转换的时长，设置了片段时只计算片段内的部分
+pub fn converted_duration(duration: Duration, options: &ConversionOptions) -> Duration {
+    let end = options.end.map_or(duration, |end| end.min(duration));
+    end.saturating_sub(options.start.unwrap_or_default())
+}
+
+/// 估算单个文件的输出大小（字节）
+///
+/// 知道时长和码率时按 `时长 × 码率` 计算，否则按源文件大小的经验比例估算
+///
+/// # 参数
+///
+/// * `source_bytes` - 源文件大小
+/// * `duration` - 源文件时长
+/// * `source_kbps` - 源文件音轨的码率，只在直接复制音频流时使用
+/// * `options` - 转换选项
+pub fn estimate_file_size(
+    source_bytes: u64,
+    duration: Option<Duration>,
+    source_kbps: Option<u32>,
+    options: &ConversionOptions,
+) -> u64 {
+    match (duration, output_kbps(options, source_kbps)) {
+        (Some(duration), Some(kbps)) => {
+            (converted_duration(duration, options).as_secs_f64() * f64::from(kbps) * 1000.0 / 8.0) as u64
+        }
+        _ => estimate_output_size(source_bytes, options),
+    }
+}
+
+/// 并行读取每个文件的时长（直接复制音频流时还读取音轨码率）并估算输出大小
+///
+/// 远程地址需要联网读取，只按未知时长处理
+pub fn estimate_outputs(ffmpeg_path: &Path, files: &[PathBuf], options: &ConversionOptions) -> Vec<OutputEstimate> {
+    files
+        .par_iter()
+        .map(|file| {
+            let source_bytes = fs::metadata(file).map(|metadata| metadata.len()).unwrap_or(0);
+            let local = !is_remote_url(file);
+            let duration = local.then(|| probe_duration(ffmpeg_path, file).ok()).flatten();
+            let source_kbps = (local && duration.is_some() && options.is_stream_copy())
+                .then(|| probe_audio_bitrate(ffmpeg_path, file).ok().flatten())
+                .flatten();
+            OutputEstimate {
+                source: file.clone(),
+                source_bytes,
+                duration,
+                output_bytes: estimate_file_size(source_bytes, duration, source_kbps, options),
+            }
+        })
+        .collect()
+}
+
+/// 一次试转换的结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampleEncode {
+    /// 转换的音频长度
+    pub length: Duration,
+
+    /// 转换耗时
+    pub elapsed: Duration,
+
+    /// 输出文件大小（字节）
+    pub output_bytes: u64,
+}
+
+impl SampleEncode {
+    /// 转换速度：每秒墙钟时间能转换的音频秒数（如 `40.0` 表示 40 倍速）
+    pub fn speed(&self) -> f64 {
+        self.length.as_secs_f64() / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// 输出的实际平均码率 (kbps)
+    pub fn kbps(&self) -> f64 {
+        self.output_bytes as f64 * 8.0 / 1000.0 / self.length.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// 按实际码率推算的一小时音频的输出大小（字节）
+    pub fn bytes_per_hour(&self) -> u64 {
+        (self.kbps() * 1000.0 / 8.0 * 3600.0) as u64
+    }
+}
+
+/// 从 `options.start`（未设置时从头）开始转换 `length` 长的片段，测量耗时和输出大小
+///
+/// 片段写入系统临时目录并在结束后删除，不影响输出目录
+///
+/// # 错误
+///
+/// 转换失败时返回相应的错误
+pub fn encode_sample(
+    processor: &FileProcessor,
+    source_file: &Path,
+    length: Duration,
+    options: &ConversionOptions,
+    cancel_token: &CancellationToken,
+) -> Result<SampleEncode> {
+    let mut options = options.clone().with_overwrite_policy(OverwritePolicy::Overwrite);
+    options.end = Some(options.start.unwrap_or_default() + length);
+
+    let temp_dir = std::env::temp_dir().join(format!("video2audio-sample-{}", std::process::id()));
+    let started_at = Instant::now();
+    let result = processor
+        .convert_single_file_with_cancel(source_file, &temp_dir, options, cancel_token)
+        .map(|output| fs::metadata(output).map(|metadata| metadata.len()).unwrap_or(0));
+    let elapsed = started_at.elapsed();
+    let _ = fs::remove_dir_all(&temp_dir);
+    Ok(SampleEncode { length, elapsed, output_bytes: result? })
+}
+
+/// 试转换一个文件的开头部分，测出编码速度
+///
+/// # 参数
+///
+/// * `processor` - 正式转换使用的文件处理器
+/// * `estimate` - 要试转换的文件，必须已读取到时长
+/// * `options` - 正式转换使用的转换选项
+/// * `cancel_token` - 取消令牌
+///
+/// # 返回值
+///
+/// [`SampleEncode::speed`]
+///
+/// # 错误
+///
+/// 文件时长未知、可转换的部分为空或试转换失败时返回错误
+pub fn calibrate(
+    processor: &FileProcessor,
+    estimate: &OutputEstimate,
+    options: &ConversionOptions,
+    cancel_token: &CancellationToken,
+) -> Result<f64> {
+    let length = estimate
+        .converted_duration(options)
+        .map(|duration| duration.min(CALIBRATION_LENGTH))
+        .filter(|length| !length.is_zero())
+        .ok_or_else(|| VideoToAudioError::InvalidInput(tr!(
+            "无法试转换时长未知的文件: {}",
+            "Cannot sample-convert a file of unknown duration: {}",
+            estimate.source.display()
+        )))?;
+
+    Ok(encode_sample(processor, &estimate.source, length, options, cancel_token)?.speed())
+}
+
+/// 按试转换测得的速度估算整批转换的耗时
+///
+/// 只计算已知时长的文件，多个文件按 `jobs` 个并行任务平均分配
+///
+/// # 参数
+///
+/// * `estimates` - 每个文件的估算
+/// * `options` - 转换选项
+/// * `speed` - [`calibrate`] 测得的速度
+/// * `jobs` - 并行任务数
+pub fn estimate_time(estimates: &[OutputEstimate], options: &ConversionOptions, speed: f64, jobs: usize) -> Duration {
+    let durations: Vec<Duration> = estimates
+        .iter()
+        .filter_map(|estimate| estimate.converted_duration(options))
+        .collect();
+    if durations.is_empty() || !speed.is_finite() || speed <= 0.0 {
+        return Duration::ZERO;
+    }
+    let parallel = jobs.clamp(1, durations.len());
+    let seconds = durations.iter().map(Duration::as_secs_f64).sum::<f64>() / speed / parallel as f64;
+    Duration::from_secs_f64(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(duration: Option<u64>) -> OutputEstimate {
+        OutputEstimate {
+            source: PathBuf::from("a.mp4"),
+            source_bytes: 100_000_000,
+            duration: duration.map(Duration::from_secs),
+            output_bytes: 0,
+        }
+    }
+
+    #[test]
+    fn test_output_kbps() {
+        let mp3 = ConversionOptions::new(AudioFormat::Mp3);
+        assert_eq!(output_kbps(&mp3, None), Some(245));
+        assert_eq!(output_kbps(&mp3.clone().with_vbr_quality(4), None), Some(165));
+        assert_eq!(output_kbps(&mp3.clone().with_bitrate(128), None), Some(128));
+        assert_eq!(output_kbps(&ConversionOptions::new(AudioFormat::Opus).with_bitrate(96), None), Some(96));
+        assert_eq!(output_kbps(&ConversionOptions::new(AudioFormat::M4b), None), Some(128));
+
+        // 复制音频流时使用源码率，设置了编码器后按编码器的默认码率
+        let copy = ConversionOptions::new(AudioFormat::AacCopy);
+        assert_eq!(output_kbps(&copy, Some(160)), Some(160));
+        assert_eq!(output_kbps(&copy, None), None);
+        assert_eq!(output_kbps(&copy.with_encoder("aac"), Some(160)), Some(192));
+    }
+
+    #[test]
+    fn test_estimate_file_size() {
+        // 一小时 96k 的 Opus 约 43 MB
+        let opus = ConversionOptions::new(AudioFormat::Opus).with_bitrate(96);
+        assert_eq!(estimate_file_size(0, Some(Duration::from_secs(3600)), None, &opus), 43_200_000);
+
+        // 只转换片段时按片段长度计算，片段超出源文件的部分不计
+        let clip = opus.clone().with_segment(Some(Duration::from_secs(3000)), Some(Duration::from_secs(4000)));
+        assert_eq!(estimate_file_size(0, Some(Duration::from_secs(3600)), None, &clip), 7_200_000);
+
+        // 时长未知时回退到按源文件大小估算
+        let copy = ConversionOptions::new(AudioFormat::AacCopy);
+        assert_eq!(estimate_file_size(1000, Some(Duration::from_secs(60)), None, &copy), estimate_output_size(1000, &copy));
+        assert_eq!(estimate_file_size(1000, None, None, &opus), estimate_output_size(1000, &opus));
+    }
+
+    #[test]
+    fn test_estimate_time() {
+        let options = ConversionOptions::new(AudioFormat::Mp3);
+        let estimates = [estimate(Some(600)), estimate(Some(600)), estimate(None)];
+        assert_eq!(estimate_time(&estimates, &options, 60.0, 1), Duration::from_secs(20));
+        // 并行任务数不超过已知时长的文件数
+        assert_eq!(estimate_time(&estimates, &options, 60.0, 8), Duration::from_secs(10));
+        assert_eq!(estimate_time(&[estimate(None)], &options, 60.0, 1), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_sample_encode_rates() {
+        let sample = SampleEncode {
+            length: Duration::from_secs(30),
+            elapsed: Duration::from_millis(500),
+            output_bytes: 360_000,
+        };
+        assert_eq!(sample.speed(), 60.0);
+        assert_eq!(sample.kbps(), 96.0);
+        assert_eq!(sample.bytes_per_hour(), 43_200_000);
+    }
+
+    #[test]
+    fn test_calibrate_requires_duration() {
+        let processor = FileProcessor::new();
+        let options = ConversionOptions::new(AudioFormat::Mp3);
+        let result = calibrate(&processor, &estimate(None), &options, &CancellationToken::new());
+        assert!(matches!(result, Err(VideoToAudioError::InvalidInput(_))));
+    }
+}
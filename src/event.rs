@@ -0,0 +1,62 @@
+//! # 转换事件模块
+//!
+//! 定义批量转换过程中产生的结构化事件，供异步事件流等
+//! 需要逐文件跟踪进度的调用方使用。
+
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// 批量转换事件
+///
+/// 事件按发生顺序产生：先是一个 `Started`，然后每个文件依次产生
+/// `FileStarted` 和 `FileCompleted`/`FileFailed`（不同文件的事件可能交错），
+/// 最后是一个 `Finished`。
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ConversionEvent {
+    /// 批量转换开始
+    Started {
+        /// 文件总数
+        total: usize,
+    },
+
+    /// 单个文件开始转换
+    FileStarted {
+        /// 源文件
+        source: PathBuf,
+    },
+
+    /// 单个文件转换成功
+    FileCompleted {
+        /// 源文件
+        source: PathBuf,
+        /// 输出文件
+        output: PathBuf,
+        /// 已处理的文件数
+        completed: usize,
+        /// 文件总数
+        total: usize,
+    },
+
+    /// 单个文件转换失败
+    FileFailed {
+        /// 源文件
+        source: PathBuf,
+        /// 错误信息
+        error: String,
+        /// 已处理的文件数
+        completed: usize,
+        /// 文件总数
+        total: usize,
+    },
+
+    /// 批量转换结束
+    Finished {
+        /// 成功的文件数
+        succeeded: usize,
+        /// 失败的文件数
+        failed: usize,
+        /// 是否被取消
+        cancelled: bool,
+    },
+}
@@ -0,0 +1,176 @@
+//! # 失败清单模块
+//!
+//! 批处理中有文件最终转换失败时，在输出目录中写入 `failed_files.json`，
+//! 列出每个失败的源文件和错误信息。`--retry-failed <清单>` 只重新转换清单中的文件，
+//! 不必从终端输出中逐个复制失败的路径。
+//!
+//! ```json
+//! {
+//!   "format": "mp3",
+//!   "failures": [
+//!     { "source": "/videos/a.mp4", "code": "E012", "error": "输入文件已损坏或不是有效的媒体文件: 转换失败: moov atom not found" }
+//!   ]
+//! }
+//! ```
+
+use crate::audio_format::AudioFormat;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 失败清单的文件名
+pub const FAILURE_MANIFEST_FILE_NAME: &str = "failed_files.json";
+
+/// 一个转换失败的文件
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FailedFile {
+    /// 源文件
+    pub source: PathBuf,
+
+    /// 错误代码，参见 [`crate::error_codes`]
+    pub code: String,
+
+    /// 错误信息
+    pub error: String,
+}
+
+/// 一次批处理的失败清单
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FailureManifest {
+    /// 目标音频格式（扩展名）
+    format: String,
+
+    /// 失败的文件，按完成顺序排列
+    failures: Vec<FailedFile>,
+}
+
+impl FailureManifest {
+    /// 创建空的失败清单
+    pub fn new(format: AudioFormat) -> Self {
+        Self { format: format.extension().to_string(), failures: Vec::new() }
+    }
+
+    /// 获取输出目录中的失败清单路径
+    pub fn path_in(output_dir: &Path) -> PathBuf {
+        output_dir.join(FAILURE_MANIFEST_FILE_NAME)
+    }
+
+    /// 读取失败清单
+    ///
+    /// # 错误
+    ///
+    /// 文件无法读取或格式错误时返回错误
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path).map_err(|e| {
+            VideoToAudioError::InvalidPath(tr!(
+                "无法读取失败清单 {}: {e}",
+                "Cannot read the failure manifest {}: {e}",
+                path.display(),
+            ))
+        })?;
+        serde_json::from_str(&content)
+            .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                "失败清单 {} 格式错误: {e}",
+                "Malformed failure manifest {}: {e}",
+                path.display(),
+            )))
+    }
+
+    /// 记录一个失败的文件
+    pub fn record(&mut self, source: &Path, error: &VideoToAudioError) {
+        self.failures.push(FailedFile { source: source.to_path_buf(), code: error.code().to_string(), error: error.to_string() });
+    }
+
+    /// 获取清单记录的目标音频格式
+    pub fn format(&self) -> Option<AudioFormat> {
+        AudioFormat::from_user_input(&self.format).ok()
+    }
+
+    /// 获取失败的文件
+    pub fn failures(&self) -> &[FailedFile] {
+        &self.failures
+    }
+
+    /// 获取失败的源文件，保持清单中的顺序
+    pub fn sources(&self) -> Vec<PathBuf> {
+        self.failures.iter().map(|failure| failure.source.clone()).collect()
+    }
+
+    /// 清单是否为空
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// 写入输出目录，返回清单路径
+    ///
+    /// # 错误
+    ///
+    /// 序列化或写入失败时返回错误
+    pub fn save(&self, output_dir: &Path) -> Result<PathBuf> {
+        let path = Self::path_in(output_dir);
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                "失败清单序列化失败: {e}",
+                "Failed to serialize the failure manifest: {e}",
+            )))?;
+        fs::write(&path, content).context(text("写入失败清单", "write failure manifest"), &path)?;
+        Ok(path)
+    }
+
+    /// 删除输出目录中之前留下的失败清单，清单不存在时什么也不做
+    ///
+    /// # 错误
+    ///
+    /// 删除失败时返回错误
+    pub fn remove(output_dir: &Path) -> Result<()> {
+        let path = Self::path_in(output_dir);
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(VideoToAudioError::io(text(
+                "删除失败清单",
+                "remove failure manifest",
+            ), &path, e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 默认的重试输出目录：清单所在的目录
+    pub fn output_dir_of(path: &Path) -> PathBuf {
+        match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_failure_manifest_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut manifest = FailureManifest::new(AudioFormat::Opus);
+        manifest.record(Path::new("videos/a.mp4"), &VideoToAudioError::FfmpegError("moov atom not found".into()));
+        manifest.record(Path::new("videos/b.mkv"), &VideoToAudioError::Cancelled);
+
+        let path = manifest.save(temp_dir.path()).unwrap();
+        assert_eq!(path, temp_dir.path().join(FAILURE_MANIFEST_FILE_NAME));
+        assert_eq!(FailureManifest::output_dir_of(&path), temp_dir.path());
+
+        let loaded = FailureManifest::load(&path).unwrap();
+        assert_eq!(loaded, manifest);
+        assert_eq!(loaded.format(), Some(AudioFormat::Opus));
+        assert_eq!(loaded.sources(), [PathBuf::from("videos/a.mp4"), PathBuf::from("videos/b.mkv")]);
+        assert!(loaded.failures()[0].error.contains("moov atom not found"));
+        assert_eq!(loaded.failures()[1].code, "E006");
+
+        FailureManifest::remove(temp_dir.path()).unwrap();
+        assert!(!path.exists());
+        FailureManifest::remove(temp_dir.path()).unwrap();
+        assert_eq!(FailureManifest::output_dir_of(Path::new(FAILURE_MANIFEST_FILE_NAME)), Path::new("."));
+    }
+}
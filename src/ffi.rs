@@ -0,0 +1,328 @@
+//! # C FFI 模块
+//!
+//! 通过 `extern "C"` 接口暴露核心功能，供 Swift、C/C++ 等语言直接调用转换引擎。
+//! 本模块仅在启用 `ffi` 特性时编译，对应的 C 头文件位于 `include/video2audio.h`。
+//!
+//! ## 构建动态库
+//!
+//! ```bash
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! ## 约定
+//!
+//! - 所有路径均为 UTF-8 编码、以 NUL 结尾的 C 字符串
+//! - 函数返回状态码，`V2A_OK` (0) 表示成功，失败时可通过
+//!   [`v2a_last_error`] 获取当前线程最近一次的错误信息
+//! - 本库分配的字符串和字符串数组必须用 [`v2a_string_free`] /
+//!   [`v2a_string_array_free`] 释放
+
+use crate::audio_format::AudioFormat;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::FileProcessor;
+use crate::i18n::text;
+use crate::tr;
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, c_void, CStr, CString};
+use std::path::PathBuf;
+use std::ptr;
+
+/// 成功
+pub const V2A_OK: c_int = 0;
+/// I/O 操作错误
+pub const V2A_ERR_IO: c_int = 1;
+/// FFmpeg 执行错误
+pub const V2A_ERR_FFMPEG: c_int = 2;
+/// 无效的路径或参数
+pub const V2A_ERR_INVALID_ARGUMENT: c_int = 3;
+/// 不支持的格式
+pub const V2A_ERR_UNSUPPORTED_FORMAT: c_int = 4;
+/// 缺少系统依赖
+pub const V2A_ERR_MISSING_DEPENDENCY: c_int = 5;
+/// 操作已取消
+pub const V2A_ERR_CANCELLED: c_int = 6;
+
+/// 音频格式: MP3
+pub const V2A_FORMAT_MP3: c_int = 0;
+/// 音频格式: AAC（直接复制）
+pub const V2A_FORMAT_AAC: c_int = 1;
+/// 音频格式: Opus
+pub const V2A_FORMAT_OPUS: c_int = 2;
+/// 音频格式: M4B（有声书）
+pub const V2A_FORMAT_M4B: c_int = 3;
+
+/// 批量转换的进度回调，参数为 (当前进度, 总数, 用户数据)
+pub type V2aProgressCallback = Option<extern "C" fn(current: usize, total: usize, user_data: *mut c_void)>;
+
+thread_local! {
+    /// 当前线程最近一次的错误信息
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// 获取当前线程最近一次的错误信息
+///
+/// 返回的指针在当前线程下一次调用本库函数前有效，调用方不得释放；
+/// 没有错误时返回空指针。
+#[no_mangle]
+pub extern "C" fn v2a_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// 查找目录中的所有视频文件
+///
+/// 成功时 `*out_files` 指向包含 `*out_count` 个路径的数组，需用
+/// [`v2a_string_array_free`] 释放。
+///
+/// # Safety
+///
+/// `source_dir` 必须是有效的 C 字符串，`out_files` 和 `out_count` 必须是有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn v2a_find_video_files(
+    source_dir: *const c_char,
+    out_files: *mut *mut *mut c_char,
+    out_count: *mut usize,
+) -> c_int {
+    if out_files.is_null() || out_count.is_null() {
+        return report(Err(null_argument("out_files/out_count")));
+    }
+
+    let result = path_from_c(source_dir)
+        .and_then(|dir| FileProcessor::new().find_video_files(&dir))
+        .and_then(|files| files.iter().map(|file| path_to_c(file)).collect::<Result<Vec<_>>>());
+
+    match result {
+        Ok(files) => {
+            let files: Box<[*mut c_char]> = files.into_iter().map(CString::into_raw).collect();
+            *out_count = files.len();
+            *out_files = Box::into_raw(files) as *mut *mut c_char;
+            report(Ok(()))
+        }
+        Err(e) => report(Err(e)),
+    }
+}
+
+/// 转换单个视频文件
+///
+/// `out_path` 不为空时，成功后 `*out_path` 指向输出文件路径，需用 [`v2a_string_free`] 释放。
+///
+/// # Safety
+///
+/// `source_file` 和 `output_dir` 必须是有效的 C 字符串，`out_path` 为空或为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn v2a_convert_single_file(
+    source_file: *const c_char,
+    output_dir: *const c_char,
+    format: c_int,
+    out_path: *mut *mut c_char,
+) -> c_int {
+    let result = (|| {
+        let source_file = path_from_c(source_file)?;
+        let output_dir = path_from_c(output_dir)?;
+        let format = format_from_c(format)?;
+        let output = FileProcessor::new().convert_single_file(&source_file, &output_dir, format)?;
+        path_to_c(&output)
+    })();
+
+    match result {
+        Ok(output) => {
+            if !out_path.is_null() {
+                *out_path = output.into_raw();
+            }
+            report(Ok(()))
+        }
+        Err(e) => report(Err(e)),
+    }
+}
+
+/// 批量并行转换视频文件
+///
+/// 进度回调在调用线程上串行执行。单个文件失败不会使函数返回错误，
+/// 失败数通过 `out_failure` 返回。
+///
+/// # Safety
+///
+/// `files` 必须指向 `count` 个有效的 C 字符串，`output_dir` 必须是有效的 C 字符串，
+/// `out_success` 和 `out_failure` 为空或为有效的可写指针。
+#[no_mangle]
+pub unsafe extern "C" fn v2a_batch_convert(
+    files: *const *const c_char,
+    count: usize,
+    output_dir: *const c_char,
+    format: c_int,
+    callback: V2aProgressCallback,
+    user_data: *mut c_void,
+    out_success: *mut usize,
+    out_failure: *mut usize,
+) -> c_int {
+    let prepared = (|| {
+        if files.is_null() && count > 0 {
+            return Err(null_argument("files"));
+        }
+        let files = if count == 0 {
+            Vec::new()
+        } else {
+            std::slice::from_raw_parts(files, count)
+                .iter()
+                .map(|&file| path_from_c(file))
+                .collect::<Result<Vec<_>>>()?
+        };
+        Ok((files, path_from_c(output_dir)?, format_from_c(format)?))
+    })();
+
+    let (files, output_dir, format) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => return report(Err(e)),
+    };
+
+    let (success, failure) = FileProcessor::new().batch_convert(&files, &output_dir, format, |current, total| {
+        if let Some(callback) = callback {
+            callback(current, total, user_data);
+        }
+    });
+
+    if !out_success.is_null() {
+        *out_success = success;
+    }
+    if !out_failure.is_null() {
+        *out_failure = failure;
+    }
+    report(Ok(()))
+}
+
+/// 释放本库分配的字符串
+///
+/// # Safety
+///
+/// `value` 必须为空或由本库返回且尚未释放。
+#[no_mangle]
+pub unsafe extern "C" fn v2a_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// 释放 [`v2a_find_video_files`] 返回的字符串数组
+///
+/// # Safety
+///
+/// `values` 和 `count` 必须来自同一次 [`v2a_find_video_files`] 调用且尚未释放。
+#[no_mangle]
+pub unsafe extern "C" fn v2a_string_array_free(values: *mut *mut c_char, count: usize) {
+    if values.is_null() {
+        return;
+    }
+    let values = Box::from_raw(ptr::slice_from_raw_parts_mut(values, count));
+    for &value in values.iter() {
+        v2a_string_free(value);
+    }
+}
+
+/// 记录错误信息并返回对应的状态码
+fn report(result: Result<()>) -> c_int {
+    let (code, message) = match result {
+        Ok(()) => (V2A_OK, None),
+        Err(e) => {
+            let code = match e {
+                VideoToAudioError::Io { .. } => V2A_ERR_IO,
+                // 具体的 FFmpeg 失败原因只体现在错误信息中，状态码保持不变
+                VideoToAudioError::FfmpegError(_)
+                | VideoToAudioError::NoAudioStream(_)
+                | VideoToAudioError::UnsupportedCodec(_)
+                | VideoToAudioError::CorruptInput(_)
+                | VideoToAudioError::PermissionDenied(_)
+                | VideoToAudioError::DiskFull(_) => V2A_ERR_FFMPEG,
+                VideoToAudioError::InvalidPath(_) | VideoToAudioError::InvalidInput(_) => V2A_ERR_INVALID_ARGUMENT,
+                VideoToAudioError::UnsupportedFormat(_) => V2A_ERR_UNSUPPORTED_FORMAT,
+                VideoToAudioError::MissingDependency(_) => V2A_ERR_MISSING_DEPENDENCY,
+                VideoToAudioError::Cancelled => V2A_ERR_CANCELLED,
+            };
+            // 错误信息不会包含 NUL，万一包含则退化为空字符串
+            (code, Some(CString::new(e.to_string()).unwrap_or_default()))
+        }
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    code
+}
+
+/// 将 C 字符串转换为路径
+///
+/// # Safety
+///
+/// `value` 必须为空或为有效的 C 字符串。
+unsafe fn path_from_c(value: *const c_char) -> Result<PathBuf> {
+    if value.is_null() {
+        return Err(null_argument("path"));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map(PathBuf::from)
+        .map_err(|_| VideoToAudioError::InvalidPath(text(
+            "路径不是有效的 UTF-8 字符串",
+            "The path is not valid UTF-8",
+        ).to_string()))
+}
+
+/// 将路径转换为 C 字符串
+fn path_to_c(path: &std::path::Path) -> Result<CString> {
+    CString::new(path.to_string_lossy().into_owned())
+        .map_err(|_| VideoToAudioError::InvalidPath(tr!(
+            "路径包含 NUL 字符: {}",
+            "The path contains a NUL character: {}",
+            path.display(),
+        )))
+}
+
+/// 将格式编号转换为音频格式
+fn format_from_c(format: c_int) -> Result<AudioFormat> {
+    match format {
+        V2A_FORMAT_MP3 => Ok(AudioFormat::Mp3),
+        V2A_FORMAT_AAC => Ok(AudioFormat::AacCopy),
+        V2A_FORMAT_OPUS => Ok(AudioFormat::Opus),
+        V2A_FORMAT_M4B => Ok(AudioFormat::M4b),
+        other => Err(VideoToAudioError::UnsupportedFormat(tr!("未知的格式编号: {other}", "Unknown format number: {other}"))),
+    }
+}
+
+fn null_argument(name: &str) -> VideoToAudioError {
+    VideoToAudioError::InvalidInput(tr!("参数 {name} 不能为空指针", "Argument {name} must not be a null pointer"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_video_files_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.mp4"), "fake").unwrap();
+        std::fs::write(temp_dir.path().join("notes.txt"), "text").unwrap();
+        let dir = CString::new(temp_dir.path().to_str().unwrap()).unwrap();
+
+        let mut files = ptr::null_mut();
+        let mut count = 0;
+        let code = unsafe { v2a_find_video_files(dir.as_ptr(), &mut files, &mut count) };
+
+        assert_eq!(code, V2A_OK);
+        assert_eq!(count, 1);
+        let first = unsafe { CStr::from_ptr(*files) }.to_str().unwrap();
+        assert!(first.ends_with("a.mp4"));
+        assert!(v2a_last_error().is_null());
+        unsafe { v2a_string_array_free(files, count) };
+    }
+
+    #[test]
+    fn test_convert_missing_file_sets_last_error() {
+        let source = CString::new("/nonexistent/video.mp4").unwrap();
+        let output = CString::new("/tmp").unwrap();
+
+        let code = unsafe { v2a_convert_single_file(source.as_ptr(), output.as_ptr(), V2A_FORMAT_MP3, ptr::null_mut()) };
+        assert_eq!(code, V2A_ERR_INVALID_ARGUMENT);
+        assert!(!v2a_last_error().is_null());
+
+        let code = unsafe { v2a_convert_single_file(source.as_ptr(), output.as_ptr(), 42, ptr::null_mut()) };
+        assert_eq!(code, V2A_ERR_UNSUPPORTED_FORMAT);
+    }
+}
@@ -4,12 +4,341 @@
 //! 提供高性能的并行处理能力和完善的错误处理机制。
 
 use crate::audio_format::AudioFormat;
-use crate::error::{Result, VideoToAudioError};
+use crate::backend::ConversionBackend;
+use crate::cancellation::CancellationToken;
+use crate::capabilities::FfmpegCapabilities;
+use crate::dir_config::DirConfigResolver;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::journal::{BatchJournal, JOURNAL_FILE_NAME};
+use crate::log_file::LogFile;
+use crate::options::{format_seconds, ConversionOptions, CoverArt, FormatDefaults};
+use crate::tag_pattern::TagPattern;
+use crate::probe::{self, AudioStream, AudioTrackSelection, Chapter};
+use crate::progress::{parse_progress_line, ProgressListener};
+use crate::remote;
+use crate::scheduler::{ConcurrencyLimits, JobKind, JobScheduler};
+use crate::selection::{FileFilter, SkipReason, SkippedFile};
+use crate::tr;
 use rayon::prelude::*;
-use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::sync::{Arc, Mutex};
-use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+use std::{fs, thread};
+use tracing::{debug, info, info_span, warn};
+
+/// 转换过程中临时输出文件的后缀
+///
+/// FFmpeg 先写入 `<输出文件>.part`，成功后再重命名为最终文件名，
+/// 因此任何以该后缀结尾的文件都是未完成的输出。
+pub const PARTIAL_FILE_SUFFIX: &str = ".part";
+
+/// 默认输出目录名称
+///
+/// 未指定输出目录时在源目录下创建该目录，扫描时始终跳过同名目录，
+/// 避免重复运行时把之前的输出当作输入。
+pub const DEFAULT_OUTPUT_DIR_NAME: &str = "audio_exports";
+
+/// 默认扫描的视频文件扩展名
+///
+/// 可以用 [`FileProcessor::with_extensions`] 或配置文件中的 `extra_extensions`、
+/// `excluded_extensions` 增减。
+pub const DEFAULT_VIDEO_EXTENSIONS: &[&str] = &["mp4", "mkv", "avi", "mov", "webm", "flv", "wmv", "m4v", "3gp", "ts"];
+
+/// 启用 [`FileProcessor::with_audio_inputs`] 时额外扫描的纯音频容器扩展名
+pub const AUDIO_INPUT_EXTENSIONS: &[&str] =
+    &["m4a", "m4b", "mka", "weba", "mp3", "aac", "opus", "ogg", "oga", "flac", "wav", "wma"];
+
+/// 等待 FFmpeg 进程时检查取消请求的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Windows 进程创建标志：不为控制台程序创建窗口
+#[cfg(windows)]
+const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+/// Windows 进程优先级：低于正常
+#[cfg(windows)]
+const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+
+/// 低优先级模式下子进程的 nice 值
+#[cfg(unix)]
+const LOW_PRIORITY_NICE: libc::c_int = 10;
+
+/// 创建 FFmpeg 命令
+/// 
+/// Windows 上不为 FFmpeg 创建控制台窗口，
+/// 否则从图形界面启动批处理时每个文件都会闪出一个命令行窗口。
+#[cfg(windows)]
+pub(crate) fn ffmpeg_command(program: &Path) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut command = Command::new(program);
+    command.creation_flags(windows_creation_flags(false));
+    command
+}
+
+/// FFmpeg 子进程的 Windows 进程创建标志，始终包含不创建窗口的标志
+#[cfg(windows)]
+fn windows_creation_flags(low_priority: bool) -> u32 {
+    if low_priority {
+        CREATE_NO_WINDOW | BELOW_NORMAL_PRIORITY_CLASS
+    } else {
+        CREATE_NO_WINDOW
+    }
+}
+
+/// 创建 FFmpeg 命令
+#[cfg(not(windows))]
+pub(crate) fn ffmpeg_command(program: &Path) -> Command {
+    Command::new(program)
+}
+
+/// 把程序和参数格式化为可以直接粘贴到 shell 中执行的命令行
+///
+/// 含空格或特殊字符的参数按 POSIX shell 规则加引号，非 UTF-8 的部分以替换字符显示。
+pub fn format_command_line(program: &Path, args: &[OsString]) -> String {
+    std::iter::once(program.as_os_str())
+        .chain(args.iter().map(OsString::as_os_str))
+        .map(|arg| {
+            let arg = arg.to_string_lossy();
+            match shlex::try_quote(&arg) {
+                Ok(quoted) => quoted.into_owned(),
+                // 只有包含空字符的参数无法引用，这样的参数本身也无法传给进程
+                Err(_) => format!("{arg:?}"),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 降低子进程的 CPU 和磁盘优先级
+/// 
+/// 设置失败时保持默认优先级，不影响转换本身。
+#[cfg(unix)]
+pub(crate) fn set_low_priority(command: &mut Command) {
+    use std::os::unix::process::CommandExt;
+
+    // SAFETY: 闭包在 fork 之后、exec 之前执行，只调用异步信号安全的系统调用
+    unsafe {
+        command.pre_exec(|| {
+            libc::setpriority(libc::PRIO_PROCESS as _, 0, LOW_PRIORITY_NICE);
+            // 等同于 ionice -c2 -n7：尽力而为类中的最低磁盘优先级
+            #[cfg(target_os = "linux")]
+            libc::syscall(libc::SYS_ioprio_set, 1, 0, (2 << 13) | 7);
+            Ok(())
+        });
+    }
+}
+
+/// 降低子进程的 CPU 优先级
+#[cfg(windows)]
+pub(crate) fn set_low_priority(command: &mut Command) {
+    use std::os::windows::process::CommandExt;
+
+    // 会覆盖之前设置的标志，需要同时保留不创建窗口的标志
+    command.creation_flags(windows_creation_flags(true));
+}
+
+/// 当前平台不支持调整进程优先级
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn set_low_priority(_command: &mut Command) {}
+
+/// 超过该长度的路径在 Windows 上需要使用扩展长度格式
+/// 
+/// `MAX_PATH` 为 260，创建目录时还需为 8.3 短文件名预留 12 个字符
+#[cfg(windows)]
+const WINDOWS_MAX_PATH: usize = 248;
+
+/// 将过长的路径转换为 Windows 扩展长度路径（`\\?\` 前缀）
+/// 
+/// 标准库的文件操作会自动处理长路径，但作为参数传给 FFmpeg 的路径不会，
+/// 深层嵌套的媒体库中超过 260 个字符的路径会导致 FFmpeg 无法打开文件。
+/// 扩展长度路径必须是绝对路径且不能包含 `.` 和 `..`，因此先规范化为绝对路径。
+#[cfg(windows)]
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::{Component, Prefix};
+
+    if path.as_os_str().len() < WINDOWS_MAX_PATH {
+        return path.to_path_buf();
+    }
+    let Ok(absolute) = std::path::absolute(path) else {
+        return path.to_path_buf();
+    };
+
+    let wide: Vec<u16> = absolute.as_os_str().encode_wide().collect();
+    let prefixed: Vec<u16> = match absolute.components().next() {
+        // `\\server\share\...` -> `\\?\UNC\server\share\...`
+        Some(Component::Prefix(prefix)) if matches!(prefix.kind(), Prefix::UNC(..)) => {
+            r"\\?\UNC\".encode_utf16().chain(wide[2..].iter().copied()).collect()
+        }
+        Some(Component::Prefix(prefix)) if matches!(prefix.kind(), Prefix::Disk(_)) => {
+            r"\\?\".encode_utf16().chain(wide.iter().copied()).collect()
+        }
+        // 已经是扩展长度路径或设备路径
+        _ => return absolute,
+    };
+    PathBuf::from(std::ffi::OsString::from_wide(&prefixed))
+}
+
+/// 将过长的路径转换为 Windows 扩展长度路径（其他平台原样返回）
+#[cfg(not(windows))]
+pub(crate) fn extended_length_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+/// 生成传给 FFmpeg 的路径参数
+/// 
+/// 相对路径加上 `./` 前缀，避免 `-i.mp4` 这类以 `-` 开头的文件名被当作选项，
+/// 或 `concat:x.mp4` 这类包含冒号的文件名被当作协议。
+/// 先做长路径转换（过长的相对路径会变成绝对路径），绝对路径本身就不会被误解析。
+pub(crate) fn ffmpeg_path_arg(path: &Path) -> OsString {
+    let path = extended_length_path(path);
+    let needs_prefix = path.is_relative()
+        && !matches!(path.components().next(), Some(Component::Prefix(_) | Component::CurDir));
+    if needs_prefix {
+        Path::new(".").join(path).into_os_string()
+    } else {
+        path.into_os_string()
+    }
+}
+
+/// 生成源文件对应的 FFmpeg 输入参数
+/// 
+/// 远程地址原样交给 FFmpeg，本地路径需要防止被误解析为选项或协议
+pub(crate) fn input_arg(source_file: &Path) -> OsString {
+    if remote::is_remote_url(source_file) {
+        source_file.as_os_str().to_os_string()
+    } else {
+        ffmpeg_path_arg(source_file)
+    }
+}
+
+/// 生成封面所需的额外 FFmpeg 输入
+/// 
+/// 封面图片作为单独的输入；截取帧时以截取时间再次打开源文件，不影响音频的片段设置。
+/// 封面取自源文件中的流或不嵌入封面时不需要额外输入。
+pub(crate) fn cover_input_args(options: &ConversionOptions, source_file: &Path) -> Vec<OsString> {
+    match options.cover_art() {
+        Some(CoverArt::File(cover)) => vec!["-i".into(), ffmpeg_path_arg(cover)],
+        Some(CoverArt::Frame(position)) => vec![
+            "-ss".into(),
+            format_seconds(*position).into(),
+            "-i".into(),
+            input_arg(source_file),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+/// 把文件名中不合法的字符替换为下划线
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_control() || matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|') { '_' } else { c })
+        .collect()
+}
+
+/// 把扩展名整理为小写、不含前导点的形式
+pub(crate) fn normalize_extension(ext: &str) -> String {
+    ext.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// 生成单条音轨的输出文件路径
+/// 
+/// `movie.mp3` 的第 2 条日语音轨对应 `movie.track2.jpn.mp3`
+fn track_output_path(output_path: &Path, stream: &AudioStream) -> PathBuf {
+    let mut file_name = output_path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(format!(".track{}", stream.index + 1));
+    if let Some(language) = &stream.language {
+        // 语言标签来自源文件，只保留字母数字以免产生非法文件名
+        let language: String = language.chars().filter(char::is_ascii_alphanumeric).collect();
+        if !language.is_empty() {
+            file_name.push(format!(".{language}"));
+        }
+    }
+    if let Some(extension) = output_path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    output_path.with_file_name(file_name)
+}
+
+/// 输出文件已存在时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverwritePolicy {
+    /// 总是重新转换并覆盖已存在的输出文件
+    #[default]
+    Overwrite,
+
+    /// 输出文件已存在时跳过转换
+    SkipExisting,
+
+    /// 输出文件已存在且比源文件新时跳过转换（类似 make 的增量构建）
+    SkipUpToDate,
+}
+
+impl OverwritePolicy {
+    /// 判断给定的源文件和输出文件是否需要跳过转换
+    /// 
+    /// # 参数
+    /// 
+    /// * `source_file` - 源视频文件路径
+    /// * `output_path` - 输出音频文件路径
+    /// 
+    /// # 返回值
+    /// 
+    /// 输出文件满足策略要求、无需重新转换时返回 `true`
+    pub fn should_skip(&self, source_file: &Path, output_path: &Path) -> bool {
+        match self {
+            OverwritePolicy::Overwrite => false,
+            OverwritePolicy::SkipExisting => output_path.is_file(),
+            OverwritePolicy::SkipUpToDate => {
+                let modified = |path: &Path| fs::metadata(path).and_then(|m| m.modified()).ok();
+                match (modified(source_file), modified(output_path)) {
+                    (Some(source_time), Some(output_time)) => output_time >= source_time,
+                    _ => false,
+                }
+            }
+        }
+    }
+}
+
+/// 解码源视频时使用的硬件加速
+/// 
+/// 音频编码始终在 CPU 上进行，硬件加速只用于解码源文件
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HwAccel {
+    /// 由 FFmpeg 自动选择可用的加速方式，不可用时自动使用软件解码
+    Auto,
+
+    /// macOS VideoToolbox
+    VideoToolbox,
+
+    /// Linux VA-API
+    Vaapi,
+
+    /// NVIDIA CUDA
+    Cuda,
+}
+
+impl HwAccel {
+    /// FFmpeg `-hwaccel` 参数的取值
+    pub fn ffmpeg_name(&self) -> &'static str {
+        match self {
+            HwAccel::Auto => "auto",
+            HwAccel::VideoToolbox => "videotoolbox",
+            HwAccel::Vaapi => "vaapi",
+            HwAccel::Cuda => "cuda",
+        }
+    }
+}
 
 /// 文件处理器
 /// 
@@ -18,188 +347,1541 @@ use std::fs;
 /// - 并行转换处理
 /// - 进度跟踪和错误处理
 /// - 输出目录管理
+#[derive(Debug, Clone)]
 pub struct FileProcessor {
-    /// 支持的视频文件扩展名列表
-    supported_extensions: Vec<&'static str>,
+    /// 支持的视频文件扩展名列表（小写，不含点）
+    supported_extensions: Vec<String>,
+
+    /// 失败文件的最大重试次数
+    retries: usize,
+
+    /// 重试时使用的备用音频格式（未设置时沿用原格式）
+    fallback_format: Option<AudioFormat>,
+
+    /// 是否在首个文件失败后立即中止批处理
+    fail_fast: bool,
+
+    /// 是否在输出目录中记录批处理状态日志
+    journal: bool,
+
+    /// 输出文件已存在时的处理策略
+    overwrite_policy: OverwritePolicy,
+
+    /// 保留目录结构时的源根目录
+    source_root: Option<PathBuf>,
+
+    /// FFmpeg 可执行文件路径
+    ffmpeg_path: PathBuf,
+
+    /// 扫描时跳过的目录（通常是输出目录）
+    excluded_dirs: Vec<PathBuf>,
+
+    /// 扫描时应用的筛选条件
+    file_filter: FileFilter,
+
+    /// 专用线程池（未设置时使用 Rayon 全局线程池）
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+
+    /// 自定义转换后端（未设置时使用 FFmpeg 命令行）
+    backend: Option<Arc<dyn ConversionBackend>>,
+
+    /// 按任务类型限制并发的调度器（未设置时只受线程池大小限制）
+    scheduler: Option<Arc<JobScheduler>>,
+
+    /// 每个 FFmpeg 进程使用的线程数（未设置时由 FFmpeg 自动决定）
+    ffmpeg_threads: Option<usize>,
+
+    /// 是否以低优先级运行 FFmpeg
+    low_priority: bool,
+
+    /// 是否在执行前输出完整的 FFmpeg 命令行，并把命令行附加到失败信息中
+    show_commands: bool,
+
+    /// 解码源视频时使用的硬件加速（未设置时使用软件解码）
+    hwaccel: Option<HwAccel>,
+
+    /// 是否为多音轨源文件的每条音轨分别输出一个文件
+    all_audio_tracks: bool,
+
+    /// 是否把纯音频文件也作为输入
+    audio_inputs: bool,
+
+    /// 要提取的音轨（未设置时由 FFmpeg 选择默认音轨）
+    audio_track: Option<AudioTrackSelection>,
+
+    /// 是否为包含章节的源文件的每个章节分别输出一个文件
+    split_chapters: bool,
+
+    /// 是否在转换后分析输出文件并写入 ReplayGain/R128 增益标签
+    replay_gain: bool,
+
+    /// 从源文件名解析元数据标签的模板
+    tag_pattern: Option<TagPattern>,
+
+    /// 是否把每个源目录视为一张专辑，写入专辑名和音轨号
+    album_per_dir: bool,
+
+    /// 源目录中的按目录配置（未设置时所有文件使用相同的转换选项）
+    dir_configs: Option<Arc<DirConfigResolver>>,
+
+    /// 各格式的默认编码参数
+    format_defaults: HashMap<AudioFormat, FormatDefaults>,
+
+    /// 批量转换时接收每个文件进度的监听器
+    progress: Option<Arc<dyn ProgressListener>>,
+
+    /// 记录每个文件转换过程的日志文件
+    log_file: Option<Arc<LogFile>>,
+
+    /// 是否正在重试失败的文件，重试时使用备用格式
+    retrying: bool,
+
+    /// 指定的硬件加速是否已确认不可用，克隆出的处理器共享该状态
+    hwaccel_unavailable: Arc<AtomicBool>,
 }
 
 impl FileProcessor {
     /// 创建新的文件处理器实例
     /// 
-    /// 初始化支持的视频格式列表，包括常见的视频文件格式
+    /// 初始化支持的视频格式列表，包括常见的视频文件格式（参见 [`DEFAULT_VIDEO_EXTENSIONS`]）
     pub fn new() -> Self {
         Self {
-            supported_extensions: vec![
-                "mp4", "mkv", "avi", "mov", "webm", "flv", "wmv", "m4v", "3gp", "ts"
-            ],
+            supported_extensions: DEFAULT_VIDEO_EXTENSIONS.iter().map(|ext| normalize_extension(ext)).collect(),
+            retries: 0,
+            fallback_format: None,
+            fail_fast: false,
+            journal: false,
+            overwrite_policy: OverwritePolicy::default(),
+            source_root: None,
+            ffmpeg_path: PathBuf::from("ffmpeg"),
+            excluded_dirs: Vec::new(),
+            file_filter: FileFilter::default(),
+            thread_pool: None,
+            backend: None,
+            scheduler: None,
+            ffmpeg_threads: None,
+            low_priority: false,
+            show_commands: false,
+            hwaccel: None,
+            all_audio_tracks: false,
+            audio_inputs: false,
+            audio_track: None,
+            split_chapters: false,
+            replay_gain: false,
+            tag_pattern: None,
+            album_per_dir: false,
+            dir_configs: None,
+            format_defaults: HashMap::new(),
+            progress: None,
+            log_file: None,
+            retrying: false,
+            hwaccel_unavailable: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    /// 获取支持的视频文件扩展名列表
+    /// 创建文件处理器构建器
     /// 
-    /// # 返回值
+    /// 用于在不修改环境变量的情况下定制扫描的扩展名、FFmpeg 路径、线程数等行为。
     /// 
-    /// 包含所有支持的文件扩展名的向量
-    pub fn supported_extensions(&self) -> &[&'static str] {
-        &self.supported_extensions
+    /// # 示例
+    /// 
+    /// ```rust
+    /// use video2audio_rs::{FileProcessor, OverwritePolicy};
+    /// 
+    /// let processor = FileProcessor::builder()
+    ///     .extensions(["mp4", "mkv"])
+    ///     .ffmpeg_path("/opt/ffmpeg/bin/ffmpeg")
+    ///     .overwrite_policy(OverwritePolicy::SkipExisting)
+    ///     .threads(2)
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(processor.supported_extensions(), ["mp4", "mkv"]);
+    /// ```
+    pub fn builder() -> FileProcessorBuilder {
+        FileProcessorBuilder::default()
     }
 
-    /// 在指定目录中查找所有支持的视频文件
+    /// 设置扫描的视频文件扩展名
     /// 
-    /// 递归扫描目录及其子目录，查找所有支持格式的视频文件
+    /// 扩展名不区分大小写，可以带前导的点（`.mts` 与 `mts` 相同）。
+    /// 只影响目录扫描，直接传给转换函数的文件不受限制。
+    pub fn with_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.supported_extensions = extensions.into_iter().map(|ext| normalize_extension(ext.as_ref())).collect();
+        self
+    }
+
+    /// 设置失败文件的最大重试次数
     /// 
-    /// # 参数
+    /// 批量转换中失败的文件会在本轮全部处理完后重新排队，最多重试 `retries` 次。
+    /// 适用于网络存储短暂不可用等偶发错误；输入损坏等重试也不会成功的错误
+    /// （参见 [`VideoToAudioError::is_retryable`]）不再重试。
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// 设置重试时使用的备用音频格式
     /// 
-    /// * `source_dir` - 要扫描的源目录路径
+    /// 例如 AAC 直接复制失败（源音频不是 AAC）时改用 MP3 重新编码。
+    pub fn with_fallback_format(mut self, fallback_format: Option<AudioFormat>) -> Self {
+        self.fallback_format = fallback_format;
+        self
+    }
+
+    /// 设置快速失败模式
     /// 
-    /// # 返回值
+    /// 启用后，任何文件最终失败都会立即取消批处理中剩余的工作，
+    /// 包括终止正在运行的 FFmpeg 进程。
+    pub fn with_fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// 设置是否记录批处理状态日志
     /// 
-    /// 包含所有找到的视频文件路径的向量
+    /// 启用后，批量转换会在输出目录中维护 [`JOURNAL_FILE_NAME`] 文件，
+    /// 记录计划处理的文件及每个文件的结果，中断后可据此继续。
+    /// 批处理全部成功完成时日志会被删除。
     /// 
-    /// # 错误
+    /// [`JOURNAL_FILE_NAME`]: crate::journal::JOURNAL_FILE_NAME
+    pub fn with_journal(mut self, journal: bool) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    /// 设置输出文件已存在时的处理策略
     /// 
-    /// 当目录访问失败或路径无效时返回错误
-    pub fn find_video_files(&self, source_dir: &Path) -> Result<Vec<PathBuf>> {
-        if !source_dir.exists() {
-            return Err(VideoToAudioError::InvalidPath(
-                format!("目录不存在: {}", source_dir.display())
-            ));
-        }
+    /// 被跳过的文件视为转换成功，不会调用 FFmpeg。
+    pub fn with_overwrite_policy(mut self, overwrite_policy: OverwritePolicy) -> Self {
+        self.overwrite_policy = overwrite_policy;
+        self
+    }
 
-        if !source_dir.is_dir() {
-            return Err(VideoToAudioError::InvalidPath(
-                format!("路径不是目录: {}", source_dir.display())
-            ));
-        }
+    /// 设置保留目录结构的源根目录
+    /// 
+    /// 设置后，源根目录下 `a/b/clip.mp4` 的输出为 `<输出目录>/a/b/clip.mp3`，
+    /// 而不是全部平铺在输出目录中。不在源根目录下的文件仍直接输出到输出目录。
+    pub fn with_preserved_structure(mut self, source_root: Option<PathBuf>) -> Self {
+        self.source_root = source_root;
+        self
+    }
 
-        let files: Result<Vec<PathBuf>> = walkdir::WalkDir::new(source_dir)
-            .into_iter()
-            .filter_map(|entry| {
-                match entry {
-                    Ok(e) if e.file_type().is_file() => Some(Ok(e.into_path())),
-                    Ok(_) => None, // 跳过目录
-                    Err(err) => Some(Err(VideoToAudioError::Io(
-                        std::io::Error::other(err)
-                    ))),
-                }
-            })
-            .filter(|result| {
-                match result {
-                    Ok(path) => self.is_supported_video_file(path),
-                    Err(_) => true, // 保留错误以便传播
-                }
-            })
-            .collect();
+    /// 设置 FFmpeg 可执行文件路径
+    pub fn with_ffmpeg_path(mut self, ffmpeg_path: impl Into<PathBuf>) -> Self {
+        self.ffmpeg_path = ffmpeg_path.into();
+        self
+    }
 
-        files
+    /// 设置转换后端
+    /// 
+    /// 传入 `None` 时使用 FFmpeg 命令行。异步接口总是使用 FFmpeg 命令行。
+    pub fn with_backend(mut self, backend: Option<Arc<dyn ConversionBackend>>) -> Self {
+        self.backend = backend;
+        self
     }
 
-    /// 检查文件是否为支持的视频格式
+    /// 设置按任务类型限制并发的调度器
     /// 
-    /// 通过文件扩展名判断是否为支持的视频文件
+    /// 复制音频流的任务和重新编码的任务分别受各自的上限约束，
+    /// 克隆出的处理器共享同一个调度器，上限对它们合计生效。
+    pub fn with_scheduler(mut self, scheduler: Option<Arc<JobScheduler>>) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// 设置每个 FFmpeg 进程使用的线程数
     /// 
-    /// # 参数
+    /// FFmpeg 默认会占用全部 CPU 核心，多个进程并行时整机会变得难以使用。
+    /// 限制后总 CPU 占用约为 `并行任务数 × 每个进程的线程数`。
+    pub fn with_ffmpeg_threads(mut self, threads: Option<usize>) -> Self {
+        self.ffmpeg_threads = threads;
+        self
+    }
+
+    /// 设置是否以低优先级运行 FFmpeg
     /// 
-    /// * `path` - 要检查的文件路径
+    /// Unix 上相当于 `nice -n 10` 和 `ionice -c2 -n7`（仅 Linux），
+    /// Windows 上使用“低于正常”优先级，适合在后台转换大量文件。
+    pub fn with_low_priority(mut self, low_priority: bool) -> Self {
+        self.low_priority = low_priority;
+        self
+    }
+
+    /// 设置是否显示执行的 FFmpeg 命令
     /// 
-    /// # 返回值
+    /// 开启后每次执行 FFmpeg 前把完整的命令行（参见 [`format_command_line`]）报告给进度监听器的
+    /// [`ProgressListener::ffmpeg_command`]，转换失败时错误信息中也会包含该命令行，便于手动重现失败。
+    /// 命令行同时作为 `debug` 级别的 tracing 事件记录。
+    pub fn with_show_commands(mut self, show_commands: bool) -> Self {
+        self.show_commands = show_commands;
+        self
+    }
+
+    /// 设置解码源视频时使用的硬件加速
     /// 
-    /// 如果是支持的视频文件返回 `true`，否则返回 `false`
-    fn is_supported_video_file(&self, path: &Path) -> bool {
-        path.extension()
-            .and_then(|ext| ext.to_str())
-            .map(|ext| self.supported_extensions.contains(&ext.to_lowercase().as_str()))
-            .unwrap_or(false)
+    /// 指定的加速方式不可用时，失败的文件会改用软件解码重试，
+    /// 确认不可用后本处理器（及其克隆）之后的文件直接使用软件解码。
+    pub fn with_hwaccel(mut self, hwaccel: Option<HwAccel>) -> Self {
+        self.hwaccel = hwaccel;
+        self.hwaccel_unavailable = Arc::new(AtomicBool::new(false));
+        self
     }
 
-    /// 创建输出目录
+    /// 设置是否提取多音轨源文件中的每条音轨
     /// 
-    /// 在源目录下创建 `audio_exports` 子目录用于存放转换后的音频文件
+    /// 启用后每个源文件先用 ffprobe 读取音轨信息，包含多条音轨时
+    /// 每条音轨输出一个文件，命名为 `movie.track2.eng.mp3`（音轨编号从 1 开始，
+    /// 有语言标签时附加语言）。只有一条音轨的文件照常输出为 `movie.mp3`。
+    /// 自定义后端不支持选择音轨，此设置对其无效。
+    pub fn with_all_audio_tracks(mut self, all_audio_tracks: bool) -> Self {
+        self.all_audio_tracks = all_audio_tracks;
+        self
+    }
+
+    /// 设置是否把纯音频文件也作为输入
     /// 
-    /// # 参数
+    /// 启用后扫描时还会包括 [`AUDIO_INPUT_EXTENSIONS`] 中的音频容器（如 `.m4a`、`.mka`、`.weba`），
+    /// 用于把视频和音频混杂的目录统一转换为同一种格式。输出文件与源文件路径相同时拒绝转换，
+    /// 不会覆盖源文件。
+    pub fn with_audio_inputs(mut self, audio_inputs: bool) -> Self {
+        self.audio_inputs = audio_inputs;
+        self
+    }
+
+    /// 设置要提取的音轨
     /// 
-    /// * `source_dir` - 源目录路径
+    /// 每个源文件先用 ffprobe 读取音轨信息，再通过 `-map 0:a:N` 选择音轨，
+    /// 例如提取日语评论音轨而不是 FFmpeg 默认选择的音轨。源文件中没有符合条件的
+    /// 音轨时，该文件转换失败并返回 [`VideoToAudioError::InvalidInput`]。
+    /// 自定义后端不支持选择音轨，此设置对其无效。
+    pub fn with_audio_track(mut self, audio_track: Option<AudioTrackSelection>) -> Self {
+        self.audio_track = audio_track;
+        self
+    }
+
+    /// 设置是否按章节拆分输出
     /// 
-    /// # 返回值
+    /// 启用后每个源文件先用 ffprobe 读取章节信息，包含多个章节时在以源文件名命名的
+    /// 子目录中为每个章节输出一个文件，如 `concert/01 - Intro.mp3`，并把章节标题和
+    /// 序号写入元数据。没有章节的文件照常输出。自定义后端不支持片段转换，此设置对其无效。
+    pub fn with_split_chapters(mut self, split_chapters: bool) -> Self {
+        self.split_chapters = split_chapters;
+        self
+    }
+
+    /// 设置是否写入增益标签
     /// 
-    /// 创建的输出目录路径
+    /// 启用后每个输出文件转换完成后再分析一遍响度，把结果写入 ReplayGain 标签
+    /// （Opus 为 R128 标签）。标签通过复制音频流的方式写入，音频数据不会改变，
+    /// 由支持这些标签的播放器在播放时调整音量。需要 FFmpeg。
+    pub fn with_replay_gain(mut self, replay_gain: bool) -> Self {
+        self.replay_gain = replay_gain;
+        self
+    }
+
+    /// 设置从源文件名解析元数据标签的模板
     /// 
-    /// # 错误
+    /// 文件名符合模板时，捕获的字段作为标签写入输出文件；转换选项中已有的同名标签
+    /// （如命令行的 `--tag`）优先。不符合模板的文件照常转换，不写入模板标签。
+    pub fn with_tag_pattern(mut self, tag_pattern: Option<TagPattern>) -> Self {
+        self.tag_pattern = tag_pattern;
+        self
+    }
+
+    /// 设置是否把每个源目录视为一张专辑
     /// 
-    /// 当目录创建失败时返回错误
-    pub fn create_output_directory(&self, source_dir: &Path) -> Result<PathBuf> {
-        let output_dir = source_dir.join("audio_exports");
-        
-        fs::create_dir_all(&output_dir)
-            .map_err(VideoToAudioError::Io)?;
-            
-        Ok(output_dir)
+    /// 启用后，按文件列表批量转换时源文件所在目录的名称写入 `album` 标签，并按文件名顺序
+    /// 把该文件在本次要转换的同目录文件中的位置写入 `track` 标签（如 `3/12`）。
+    /// 模板标签和转换选项中的同名标签优先。单文件转换和流式批量转换不知道完整的文件列表，不写入专辑标签。
+    pub fn with_album_per_dir(mut self, album_per_dir: bool) -> Self {
+        self.album_per_dir = album_per_dir;
+        self
     }
 
-    /// 批量并行转换视频文件
+    /// 设置源目录中的按目录配置
     /// 
-    /// 使用 Rayon 库进行并行处理，最大化利用多核 CPU 性能
+    /// 设置后转换每个文件前读取其所在目录及上级目录中的 `.video2audio.toml`，
+    /// 按其中的格式、码率、滤镜和输出目录等设置转换该文件，参见 [`crate::dir_config`]。
+    pub fn with_dir_configs(mut self, dir_configs: Option<Arc<DirConfigResolver>>) -> Self {
+        self.dir_configs = dir_configs;
+        self
+    }
+
+    /// 设置各格式的默认编码参数
     /// 
-    /// # 参数
+    /// 转换时按最终使用的格式（包括目录配置和重试时的备用格式）补全转换选项中
+    /// 未指定的码率、VBR 质量和采样率，参见 [`FormatDefaults`]。
+    pub fn with_format_defaults(mut self, format_defaults: HashMap<AudioFormat, FormatDefaults>) -> Self {
+        self.format_defaults = format_defaults;
+        self
+    }
+
+    /// 设置转换进度监听器
     /// 
-    /// * `files` - 要转换的视频文件路径列表
-    /// * `output_dir` - 输出目录路径
-    /// * `format` - 目标音频格式
-    /// * `progress_callback` - 进度回调函数，接收 (当前进度, 总数) 参数
+    /// 批量转换时每个文件开始和结束时通知监听器；使用 FFmpeg 命令行转换时
+    /// 还会读取 FFmpeg 报告的进度，参见 [`ProgressListener`]。
+    pub fn with_progress(mut self, progress: Option<Arc<dyn ProgressListener>>) -> Self {
+        self.progress = progress;
+        self
+    }
+
+    /// 设置日志文件
+    /// 
+    /// 每个文件开始和结束转换时写入一条日志，使用 FFmpeg 命令行转换时还会记录
+    /// 执行的命令和 FFmpeg 的错误输出，参见 [`LogFile`]。
+    pub fn with_log_file(mut self, log_file: Option<Arc<LogFile>>) -> Self {
+        self.log_file = log_file;
+        self
+    }
+
+    /// 添加扫描时跳过的目录
+    /// 
+    /// 通常传入输出目录：当输出目录位于源目录内部时，之前生成的文件不会被再次当作输入。
+    /// 默认输出目录 [`DEFAULT_OUTPUT_DIR_NAME`] 和包含批处理状态日志的目录总是会被跳过。
+    pub fn with_excluded_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.excluded_dirs.push(dir.into());
+        self
+    }
+
+    /// 设置扫描时应用的筛选条件
+    /// 
+    /// 只影响目录扫描，直接传给转换函数的文件不受限制。
+    pub fn with_file_filter(mut self, file_filter: FileFilter) -> Self {
+        self.file_filter = file_filter;
+        self
+    }
+
+    /// 获取输出文件已存在时的处理策略
+    pub fn overwrite_policy(&self) -> OverwritePolicy {
+        self.overwrite_policy
+    }
+
+    /// 获取失败文件的最大重试次数
+    pub fn retries(&self) -> usize {
+        self.retries
+    }
+
+    /// 是否启用了快速失败模式
+    pub fn fail_fast(&self) -> bool {
+        self.fail_fast
+    }
+
+    /// 获取 FFmpeg 可执行文件路径
+    pub fn ffmpeg_path(&self) -> &Path {
+        &self.ffmpeg_path
+    }
+
+    /// 创建执行转换的 FFmpeg 命令，按设置降低进程优先级
+    pub(crate) fn conversion_command(&self) -> Command {
+        let mut command = ffmpeg_command(&self.ffmpeg_path);
+        if self.low_priority {
+            set_low_priority(&mut command);
+        }
+        command
+    }
+
+    /// 在处理器的线程池中执行并行操作
+    /// 
+    /// 通过构建器设置了线程数时使用专用线程池，否则使用 Rayon 全局线程池。
+    pub(crate) fn install<R, F>(&self, op: F) -> R
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        match &self.thread_pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// 在调用方线程上创建处理器线程池的 Rayon 作用域，作用域中派发的任务在线程池中执行
+    fn in_place_scope<'scope, R>(&self, op: impl FnOnce(&rayon::Scope<'scope>) -> R) -> R {
+        match &self.thread_pool {
+            Some(pool) => pool.in_place_scope(op),
+            None => rayon::in_place_scope(op),
+        }
+    }
+
+    /// 并行处理文件
+    ///
+    /// 设置了调度器时，在调用方线程上按任务类型派发，只有取得名额的文件才交给线程池，
+    /// 此时 `process` 应使用不带调度器的处理器，避免重复等待名额；否则直接按列表顺序交给线程池。
+    /// 暂停期间不开始新文件，停止或取消后不再开始新文件。
+    fn for_each_dispatched<I, F>(
+        &self,
+        files: I,
+        output_dir: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+        process: F,
+    ) where
+        I: Iterator + Send,
+        I::Item: AsRef<Path> + Send,
+        F: Fn(I::Item) + Sync + Send,
+    {
+        match &self.scheduler {
+            Some(scheduler) => self.in_place_scope(|scope| {
+                let kind_of = |file: &I::Item| self.job_kind(file.as_ref(), output_dir, options);
+                scheduler.dispatch(scope, files, kind_of, cancel_token, &process);
+            }),
+            None => self.install(|| {
+                files.par_bridge().for_each(|file| {
+                    cancel_token.wait_while_paused();
+                    if cancel_token.is_stopped() {
+                        return;
+                    }
+                    process(file);
+                });
+            }),
+        }
+    }
+
+    /// 批量转换时实际执行转换的处理器：已在派发时取得名额，不再等待调度器
+    fn dispatched(&self) -> FileProcessor {
+        FileProcessor {
+            scheduler: None,
+            ..self.clone()
+        }
+    }
+
+    /// 获取支持的视频文件扩展名列表
+    /// 
+    /// # 返回值
+    /// 
+    /// 包含所有支持的文件扩展名的向量
+    pub fn supported_extensions(&self) -> Vec<&str> {
+        self.supported_extensions.iter().map(String::as_str).collect()
+    }
+
+    /// 在指定目录中查找所有支持的视频文件
+    /// 
+    /// 递归扫描目录及其子目录，查找所有支持格式的视频文件
+    /// 
+    /// # 参数
+    /// 
+    /// * `source_dir` - 要扫描的源目录路径
+    /// 
+    /// # 返回值
+    /// 
+    /// 包含所有找到的视频文件路径的向量
+    /// 
+    /// # 错误
+    /// 
+    /// 当目录访问失败或路径无效时返回错误
+    pub fn find_video_files(&self, source_dir: &Path) -> Result<Vec<PathBuf>> {
+        let _scan = info_span!("scan", dir = %source_dir.display()).entered();
+        let files: Vec<PathBuf> = self.find_video_files_iter(source_dir)?.collect::<Result<_>>()?;
+        debug!(files = files.len(), "扫描完成");
+        Ok(files)
+    }
+
+    /// 查找视频文件，同时记录扫描到但被排除的文件及原因
+    /// 
+    /// 与 [`find_video_files`](Self::find_video_files) 返回相同的文件。被排除的文件包括
+    /// 扩展名不支持的文件和不满足筛选条件的文件，不包括跳过的输出目录中的文件。
+    /// 
+    /// # 错误
+    /// 
+    /// 当目录访问失败或路径无效时返回错误
+    pub fn find_video_files_with_skipped(&self, source_dir: &Path) -> Result<(Vec<PathBuf>, Vec<SkippedFile>)> {
+        let _scan = info_span!("scan", dir = %source_dir.display()).entered();
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        for path in self.scan_files(source_dir)? {
+            let path = path?;
+            match self.skip_reason(&path) {
+                Some(reason) => skipped.push(SkippedFile { path, reason }),
+                None => files.push(path),
+            }
+        }
+        debug!(files = files.len(), skipped = skipped.len(), "扫描完成");
+        Ok((files, skipped))
+    }
+
+    /// 以迭代器形式逐个查找视频文件
+    /// 
+    /// 与 [`find_video_files`](Self::find_video_files) 扫描相同的文件，但不会先收集完整列表，
+    /// 每找到一个文件就立即产出。配合 [`batch_convert_iter`](Self::batch_convert_iter)
+    /// 可以在扫描大型网络共享目录的同时开始转换。
+    /// 
+    /// # 返回值
+    /// 
+    /// 视频文件路径的迭代器，遍历过程中遇到的访问错误作为 `Err` 产出
+    /// 
+    /// # 错误
+    /// 
+    /// 当源目录不存在或不是目录时立即返回错误
+    pub fn find_video_files_iter<'a>(
+        &'a self,
+        source_dir: &Path,
+    ) -> Result<impl Iterator<Item = Result<PathBuf>> + Send + 'a> {
+        let files = self.scan_files(source_dir)?.filter(|result| {
+            match result {
+                Ok(path) => self.skip_reason(path).is_none(),
+                Err(_) => true, // 保留错误以便传播
+            }
+        });
+        Ok(files)
+    }
+
+    /// 遍历源目录中的全部文件，跳过输出目录
+    fn scan_files(&self, source_dir: &Path) -> Result<impl Iterator<Item = Result<PathBuf>> + Send> {
+        if !source_dir.exists() {
+            return Err(VideoToAudioError::InvalidPath(
+                tr!("目录不存在: {}", "Directory does not exist: {}", source_dir.display())
+            ));
+        }
+
+        if !source_dir.is_dir() {
+            return Err(VideoToAudioError::InvalidPath(
+                tr!("路径不是目录: {}", "Not a directory: {}", source_dir.display())
+            ));
+        }
+
+        let excluded_dirs = self.excluded_dirs_under(source_dir);
+        let files = walkdir::WalkDir::new(source_dir)
+            .into_iter()
+            // 跳过之前生成的输出目录（源目录本身除外）
+            .filter_entry(move |entry| {
+                let skipped = entry.depth() > 0
+                    && entry.file_type().is_dir()
+                    && Self::is_output_dir(entry.path(), &excluded_dirs);
+                if skipped {
+                    debug!(dir = %entry.path().display(), "跳过输出目录");
+                }
+                !skipped
+            })
+            .filter_map(|entry| {
+                match entry {
+                    Ok(e) if e.file_type().is_file() => Some(Ok(e.into_path())),
+                    Ok(_) => None, // 跳过目录
+                    Err(err) => Some(Err(VideoToAudioError::Io {
+                        operation: Some(text("扫描目录", "scan directory")),
+                        path: err.path().map(Path::to_path_buf),
+                        source: std::io::Error::other(err),
+                    })),
+                }
+            });
+
+        Ok(files)
+    }
+
+    /// 判断扫描到的文件是否需要排除，返回排除的原因
+    /// 
+    /// 依次检查扩展名、大小和修改时间，最后检查需要 ffprobe 的时长
+    pub fn skip_reason(&self, path: &Path) -> Option<SkipReason> {
+        if !self.is_supported_video_file(path) {
+            let ext = path.extension().and_then(|ext| ext.to_str()).map(str::to_lowercase).unwrap_or_default();
+            return Some(if AUDIO_INPUT_EXTENSIONS.contains(&ext.as_str()) {
+                SkipReason::AudioInput
+            } else if DEFAULT_VIDEO_EXTENSIONS.contains(&ext.as_str()) {
+                SkipReason::ExcludedExtension
+            } else {
+                SkipReason::UnsupportedExtension
+            });
+        }
+        if let Some(reason) = self.file_filter.skip_reason(path) {
+            return Some(reason);
+        }
+        (!self.file_filter.matches_duration(path, &self.ffmpeg_path)).then_some(SkipReason::DurationOutOfRange)
+    }
+
+    /// 将排除目录换算为以 `source_dir` 为前缀的路径，便于与扫描到的路径直接比较
+    /// 
+    /// 排除目录与源目录可能一个是相对路径、一个是绝对路径，或经过符号链接，
+    /// 因此先规范化再计算相对位置。不在源目录内的排除目录无需处理。
+    fn excluded_dirs_under(&self, source_dir: &Path) -> Vec<PathBuf> {
+        let Ok(canonical_source) = fs::canonicalize(source_dir) else {
+            return self.excluded_dirs.clone();
+        };
+
+        self.excluded_dirs
+            .iter()
+            .filter_map(|dir| {
+                let canonical = fs::canonicalize(dir).ok()?;
+                let relative = canonical.strip_prefix(&canonical_source).ok()?;
+                Some(source_dir.join(relative))
+            })
+            .collect()
+    }
+
+    /// 判断目录是否为本工具生成的输出目录
+    fn is_output_dir(dir: &Path, excluded_dirs: &[PathBuf]) -> bool {
+        dir.file_name().is_some_and(|name| name == DEFAULT_OUTPUT_DIR_NAME)
+            || excluded_dirs.iter().any(|excluded| excluded == dir)
+            || dir.join(JOURNAL_FILE_NAME).is_file()
+    }
+
+    /// 检查文件是否为支持的视频格式
+    /// 
+    /// 通过文件扩展名判断是否为支持的视频文件
+    /// 
+    /// # 参数
+    /// 
+    /// * `path` - 要检查的文件路径
+    /// 
+    /// # 返回值
+    /// 
+    /// 如果是支持的视频文件返回 `true`，否则返回 `false`。启用
+    /// [`FileProcessor::with_audio_inputs`] 时，纯音频文件也返回 `true`
+    pub fn is_supported_video_file(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.to_lowercase())
+            .is_some_and(|ext| {
+                self.supported_extensions.contains(&ext)
+                    || (self.audio_inputs && AUDIO_INPUT_EXTENSIONS.contains(&ext.as_str()))
+            })
+    }
+
+    /// 创建输出目录
+    /// 
+    /// 在源目录下创建 [`DEFAULT_OUTPUT_DIR_NAME`] 子目录用于存放转换后的音频文件
+    /// 
+    /// # 参数
+    /// 
+    /// * `source_dir` - 源目录路径
+    /// 
+    /// # 返回值
+    /// 
+    /// 创建的输出目录路径
+    /// 
+    /// # 错误
+    /// 
+    /// 当目录创建失败时返回错误
+    pub fn create_output_directory(&self, source_dir: &Path) -> Result<PathBuf> {
+        let output_dir = source_dir.join(DEFAULT_OUTPUT_DIR_NAME);
+        
+        fs::create_dir_all(&output_dir).context(text("创建输出目录", "create output directory"), &output_dir)?;
+            
+        Ok(output_dir)
+    }
+
+    /// 批量并行转换视频文件
+    /// 
+    /// 使用 Rayon 库进行并行处理，最大化利用多核 CPU 性能。
+    /// 工作线程只负责转换并通过通道发送每个文件的结果，
+    /// 进度回调始终在调用方线程上串行执行，不会被并发调用。
+    /// 失败的文件不会输出到终端，需要逐个展示错误时使用
+    /// [`batch_convert_with_results`](Self::batch_convert_with_results)。
+    /// 
+    /// # 参数
+    /// 
+    /// * `files` - 要转换的视频文件路径列表
+    /// * `output_dir` - 输出目录路径
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`]
+    /// * `progress_callback` - 进度回调函数，接收 (当前进度, 总数) 参数
+    /// 
+    /// # 返回值
+    /// 
+    /// 返回转换结果的统计信息 (成功数, 失败数)
+    pub fn batch_convert<F>(
+        &self,
+        files: &[PathBuf],
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        progress_callback: F,
+    ) -> (usize, usize)
+    where
+        F: FnMut(usize, usize),
+    {
+        self.batch_convert_with_cancel(
+            files,
+            output_dir,
+            options,
+            &CancellationToken::new(),
+            progress_callback,
+        )
+    }
+
+    /// 可取消的批量并行转换
+    /// 
+    /// 与 [`batch_convert`](Self::batch_convert) 相同，但接受一个取消令牌。
+    /// 若设置了重试次数，失败的文件会在本轮结束后重新排队，只有最终仍失败的文件才计入失败数。
+    /// 令牌被取消后，尚未开始的文件不再处理，正在运行的 FFmpeg 进程会被终止，
+    /// 其未完成的输出文件也会被删除。被取消的文件既不计入成功也不计入失败。
+    /// 启用快速失败模式时，首个最终失败的文件会取消 `cancel_token`。
+    /// 
+    /// # 参数
+    /// 
+    /// * `files` - 要转换的视频文件路径列表
+    /// * `output_dir` - 输出目录路径
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`]
+    /// * `cancel_token` - 取消令牌
+    /// * `progress_callback` - 进度回调函数，接收 (当前进度, 总数) 参数
+    /// 
+    /// # 返回值
+    /// 
+    /// 返回转换结果的统计信息 (成功数, 失败数)
+    pub fn batch_convert_with_cancel<F>(
+        &self,
+        files: &[PathBuf],
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+        mut progress_callback: F,
+    ) -> (usize, usize)
+    where
+        F: FnMut(usize, usize),
+    {
+        self.batch_convert_with_results(
+            files,
+            output_dir,
+            options,
+            cancel_token,
+            |_, _, current, total| progress_callback(current, total),
+        )
+    }
+
+    /// 可取消的批量并行转换，并逐个报告文件的最终结果
+    /// 
+    /// 与 [`batch_convert_with_cancel`](Self::batch_convert_with_cancel) 相同，
+    /// 但回调额外接收源文件路径和该文件的最终转换结果，适合需要逐文件展示结果的前端。
+    /// 
+    /// # 参数
+    /// 
+    /// * `files` - 要转换的视频文件路径列表
+    /// * `output_dir` - 输出目录路径
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`]
+    /// * `cancel_token` - 取消令牌
+    /// * `result_callback` - 结果回调函数，接收 (源文件, 转换结果, 当前进度, 总数) 参数
+    /// 
+    /// # 返回值
+    /// 
+    /// 返回转换结果的统计信息 (成功数, 失败数)
+    pub fn batch_convert_with_results<F>(
+        &self,
+        files: &[PathBuf],
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+        mut result_callback: F,
+    ) -> (usize, usize)
+    where
+        F: FnMut(&Path, &Result<PathBuf>, usize, usize),
+    {
+        self.batch_convert_timed(
+            files,
+            output_dir,
+            options,
+            cancel_token,
+            |source_file, result, _, current, total| result_callback(source_file, result, current, total),
+        )
+    }
+
+    /// 可取消的批量并行转换，逐个报告文件的最终结果和转换耗时
+    /// 
+    /// 与 [`batch_convert_with_results`](Self::batch_convert_with_results) 相同，
+    /// 回调额外接收该文件最后一次转换尝试的耗时，适合生成转换报告。
+    /// 
+    /// # 参数
+    /// 
+    /// * `result_callback` - 结果回调函数，接收 (源文件, 转换结果, 耗时, 当前进度, 总数) 参数
+    /// 
+    /// # 返回值
+    /// 
+    /// 返回转换结果的统计信息 (成功数, 失败数)
+    pub fn batch_convert_timed<F>(
+        &self,
+        files: &[PathBuf],
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+        mut result_callback: F,
+    ) -> (usize, usize)
+    where
+        F: FnMut(&Path, &Result<PathBuf>, Duration, usize, usize),
+    {
+        let options = options.into();
+        let total_files = files.len();
+        let batch_span = info_span!("batch", output_dir = %output_dir.display(), files = total_files);
+        let _batch = batch_span.enter();
+        let success_counter = AtomicUsize::new(0);
+        let failure_counter = AtomicUsize::new(0);
+        let mut completed = 0;
+        let mut pending: Vec<&PathBuf> = files.iter().collect();
+        // 专辑的音轨号按本次要转换的全部文件计算一次，重试轮次沿用
+        let album_tags = if self.album_per_dir { self.album_tags(files) } else { HashMap::new() };
+
+        // 打开状态日志，记录每个文件的处理结果以便中断后继续
+        let mut journal = if self.journal && !files.is_empty() {
+            match BatchJournal::open(output_dir, options.format, files) {
+                Ok(journal) => Some(journal),
+                Err(e) => {
+                    warn!("{}", tr!(
+                        "无法打开状态日志，本次批处理将无法继续执行: {e}",
+                        "Cannot open the state journal, this batch cannot be resumed: {e}"
+                    ));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // 第一轮处理全部文件，之后每轮只重试上一轮失败的文件
+        for attempt in 0..=self.retries {
+            if pending.is_empty() || cancel_token.is_stopped() {
+                break;
+            }
+
+            if attempt > 0 {
+                info!(attempt, files = pending.len(), "重试失败的文件");
+            }
+
+            // 重试轮次使用备用格式（如果设置了）
+            let attempt_processor = FileProcessor {
+                retrying: self.retrying || attempt > 0,
+                ..self.clone()
+            };
+            let converter = attempt_processor.dispatched();
+            let is_last_attempt = attempt == self.retries;
+            let mut retry_queue = Vec::new();
+            let (sender, receiver) = mpsc::channel();
+
+            thread::scope(|scope| {
+                // 使用 Rayon 进行并行处理，每个文件的结果通过通道发送给调用方线程。
+                // 按列表顺序派发文件，使调用方指定的处理顺序（如最大的文件优先）生效
+                scope.spawn(|| {
+                    // 派发结束后关闭发送端，调用方线程随之结束接收
+                    let sender = sender;
+                    let files = pending.iter().copied();
+                    attempt_processor.for_each_dispatched(files, output_dir, &options, cancel_token, |source_file| {
+                        // 工作线程不会自动进入调用方线程的 span
+                        let _batch = batch_span.enter();
+
+                        let started_at = Instant::now();
+                        let file_options = album_tags
+                            .get(source_file.as_path())
+                            .map(|tags| self.with_album_tags(source_file, tags, &options));
+                        let result = converter.convert_reporting_progress(
+                            source_file,
+                            output_dir,
+                            file_options.as_ref().unwrap_or(&options),
+                            cancel_token,
+                        );
+                        let elapsed = started_at.elapsed();
+                        match &result {
+                            Ok(_) => {
+                                success_counter.fetch_add(1, Ordering::Relaxed);
+                            }
+                            Err(VideoToAudioError::Cancelled) => return,
+                            Err(e) if is_last_attempt || !e.is_retryable() => {
+                                failure_counter.fetch_add(1, Ordering::Relaxed);
+                                if self.fail_fast {
+                                    cancel_token.cancel();
+                                }
+                            }
+                            Err(_) => {}
+                        }
+
+                        // 接收端在所有发送端关闭前不会退出，发送失败可以忽略
+                        let _ = sender.send((source_file, result, elapsed));
+                    });
+                });
+
+                // 在调用方线程上按完成顺序处理结果并更新进度
+                for (source_file, result, elapsed) in receiver {
+                    // 成功的记录批量写回，失败的记录立即写回
+                    let saved = match &result {
+                        Ok(output_path) => journal.as_mut().map(|journal| {
+                            journal.record_success(source_file, output_path);
+                            journal.save_if_due().map(|_| ())
+                        }),
+                        Err(e) if !is_last_attempt && e.is_retryable() => {
+                            // 稍后重试，暂不计入进度
+                            retry_queue.push(source_file);
+                            continue;
+                        }
+                        Err(e) => journal.as_mut().map(|journal| {
+                            journal.record_failure(source_file, &e.to_string());
+                            journal.save()
+                        }),
+                    };
+                    if let Some(Err(e)) = saved {
+                        warn!("{}", tr!("无法写入状态日志: {e}", "Cannot write the state journal: {e}"));
+                    }
+
+                    completed += 1;
+                    result_callback(source_file, &result, elapsed, completed, total_files);
+                }
+            });
+
+            pending = retry_queue;
+        }
+
+        let success_count = success_counter.into_inner();
+        let failure_count = failure_counter.into_inner();
+
+        // 全部成功完成后不再需要状态日志，否则写回尚未保存的记录以便继续
+        if let Some(mut journal) = journal {
+            if failure_count == 0 && !cancel_token.is_stopped() {
+                let _ = journal.remove();
+            } else if let Err(e) = journal.save() {
+                warn!("{}", tr!("无法写入状态日志: {e}", "Cannot write the state journal: {e}"));
+            }
+        }
+
+        (success_count, failure_count)
+    }
+
+    /// 边发现边转换的批量并行转换
+    /// 
+    /// 从迭代器中逐个取出文件并立即开始转换，无需等待扫描完成，
+    /// 通常与 [`find_video_files_iter`](Self::find_video_files_iter) 配合使用。
+    /// 首轮失败的文件在迭代器耗尽后按重试设置重新处理。
+    /// 由于事先不知道计划处理的全部文件，该方法不记录批处理状态日志。
+    /// 
+    /// # 参数
+    /// 
+    /// * `files` - 要转换的视频文件路径迭代器
+    /// * `output_dir` - 输出目录路径
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`]
+    /// * `cancel_token` - 取消令牌，取消后不再从迭代器中取出文件
+    /// * `result_callback` - 结果回调函数，接收 (源文件, 转换结果, 已处理数, 已发现数) 参数
+    /// 
+    /// # 返回值
+    /// 
+    /// 返回转换结果的统计信息 (成功数, 失败数)
+    pub fn batch_convert_iter<I, F>(
+        &self,
+        files: I,
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+        mut result_callback: F,
+    ) -> (usize, usize)
+    where
+        I: IntoIterator<Item = PathBuf>,
+        I::IntoIter: Send,
+        F: FnMut(&Path, &Result<PathBuf>, usize, usize),
+    {
+        let options = options.into();
+        let files = files.into_iter();
+        let batch_span = info_span!("batch", output_dir = %output_dir.display());
+        let _batch = batch_span.enter();
+        let discovered = AtomicUsize::new(0);
+        let (mut success_count, mut failure_count, mut completed) = (0, 0, 0);
+        let mut retry_queue = Vec::new();
+        let (sender, receiver) = mpsc::channel();
+
+        let converter = self.dispatched();
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                let sender = sender;
+                let files = files
+                    // 停止或取消后停止扫描，不再取出新文件
+                    .take_while(|_| !cancel_token.is_stopped())
+                    .inspect(|_| {
+                        discovered.fetch_add(1, Ordering::Relaxed);
+                    });
+                self.for_each_dispatched(files, output_dir, &options, cancel_token, |source_file| {
+                    let _batch = batch_span.enter();
+                    let result = converter.convert_reporting_progress(&source_file, output_dir, &options, cancel_token);
+                    // 接收端在所有发送端关闭前不会退出，发送失败可以忽略
+                    let _ = sender.send((source_file, result));
+                });
+            });
+
+            for (source_file, result) in receiver {
+                match &result {
+                    Err(VideoToAudioError::Cancelled) => continue,
+                    Err(e) if self.retries > 0 && e.is_retryable() => {
+                        retry_queue.push(source_file);
+                        continue;
+                    }
+                    Ok(_) => success_count += 1,
+                    Err(_) => {
+                        failure_count += 1;
+                        if self.fail_fast {
+                            cancel_token.cancel();
+                        }
+                    }
+                }
+
+                completed += 1;
+                result_callback(&source_file, &result, completed, discovered.load(Ordering::Relaxed));
+            }
+        });
+
+        // 剩余的重试交给常规批量转换处理，重试轮次使用备用格式（如果设置了）
+        if !retry_queue.is_empty() && !cancel_token.is_stopped() {
+            let retry_processor = FileProcessor {
+                retries: self.retries - 1,
+                journal: false,
+                retrying: true,
+                ..self.clone()
+            };
+            let total = discovered.into_inner();
+            let (retry_success, retry_failure) = retry_processor.batch_convert_with_results(
+                &retry_queue,
+                output_dir,
+                options,
+                cancel_token,
+                |source_file, result, _, _| {
+                    completed += 1;
+                    result_callback(source_file, result, completed, total);
+                },
+            );
+            success_count += retry_success;
+            failure_count += retry_failure;
+        }
+
+        (success_count, failure_count)
+    }
+
+    /// 转换单个视频文件为音频
+    /// 
+    /// 调用 FFmpeg 执行实际的媒体转换操作
+    /// 
+    /// # 参数
+    /// 
+    /// * `source_file` - 源视频文件路径
+    /// * `output_dir` - 输出目录路径
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`]
+    /// 
+    /// # 返回值
+    /// 
+    /// 成功时返回输出文件路径
+    /// 
+    /// # 错误
+    /// 
+    /// 当转换失败时返回相应的错误信息
+    pub fn convert_single_file(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+    ) -> Result<PathBuf> {
+        self.convert_single_file_with_cancel(
+            source_file,
+            output_dir,
+            options,
+            &CancellationToken::new(),
+        )
+    }
+
+    /// 可取消的单文件转换
+    /// 
+    /// 与 [`convert_single_file`](Self::convert_single_file) 相同，
+    /// 但在令牌被取消时终止 FFmpeg 进程、删除未完成的输出并返回
+    /// [`VideoToAudioError::Cancelled`]。
+    pub fn convert_single_file_with_cancel(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        self.convert_logged(source_file, output_dir, &options.into(), cancel_token)
+    }
+
+    /// 按转换选项转换单个文件
+    fn convert_reporting_progress(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        if let Some(progress) = &self.progress {
+            progress.file_started(source_file);
+        }
+        let result = self.convert_logged(source_file, output_dir, options, cancel_token);
+        if let Some(progress) = &self.progress {
+            progress.file_finished(source_file);
+        }
+        result
+    }
+
+    /// 按转换选项转换单个文件，在 `convert` span 中执行并在日志文件中记录开始和结果
+    fn convert_logged(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        let _convert = info_span!("convert", source = %source_file.display()).entered();
+        if let Some(log_file) = &self.log_file {
+            log_file.file_started(source_file);
+        }
+        let started_at = Instant::now();
+        let result = self
+            .convert_with_options(source_file, output_dir, options, cancel_token)
+            .map_err(|e| e.for_file(source_file));
+        let elapsed = started_at.elapsed();
+        match &result {
+            Ok(output) => info!(output = %output.display(), ?elapsed, "转换完成"),
+            Err(VideoToAudioError::Cancelled) => info!(?elapsed, "转换已取消"),
+            Err(e) => info!(error = %e, ?elapsed, "转换失败"),
+        }
+        if let Some(log_file) = &self.log_file {
+            log_file.file_finished(source_file, &result, elapsed);
+        }
+        result
+    }
+
+    fn convert_with_options(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        let (options, output_path, skip) = self.plan_conversion(source_file, output_dir, options)?;
+        let options = &options;
+
+        // 提取全部音轨时，多音轨的源文件每条音轨输出一个文件
+        if self.all_audio_tracks && self.backend.is_none() && options.audio_stream.is_none() {
+            let streams = probe::probe_audio_streams(&self.ffmpeg_path, source_file)?;
+            if streams.len() > 1 {
+                return self.convert_audio_tracks(source_file, &output_path, &streams, options, cancel_token);
+            }
+        }
+
+        // 按章节拆分时，包含多个章节的源文件每个章节输出一个文件
+        if self.split_chapters && self.backend.is_none() && options.start.is_none() && options.end.is_none() {
+            let chapters = probe::probe_chapters(&self.ffmpeg_path, source_file)?;
+            if chapters.len() > 1 {
+                let options = self.select_audio_track(source_file, options)?;
+                return self.convert_chapters(source_file, &output_path, &chapters, &options, cancel_token);
+            }
+        }
+
+        // 输出文件满足覆盖策略时直接跳过
+        if skip {
+            return Ok(output_path);
+        }
+
+        let options = self.select_audio_track(source_file, options)?;
+        self.convert_to(source_file, &output_path, &options, cancel_token)?;
+        Ok(output_path)
+    }
+
+    /// 确定单个文件实际使用的转换选项和输出路径，以及是否因覆盖策略跳过
+    /// 
+    /// 依次应用目录配置、重试时的备用格式、格式默认参数和文件名标签模板
+    fn plan_conversion(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: &ConversionOptions,
+    ) -> Result<(ConversionOptions, PathBuf, bool)> {
+        let _plan = info_span!("plan").entered();
+        let (options, output_dir) = self.effective_options(source_file, output_dir, options)?;
+        let (output_path, skip) = self.resolve_output(source_file, &output_dir, &options)?;
+        debug!(output = %output_path.display(), format = options.format.extension(), skip, "已确定输出文件");
+        Ok((self.apply_tag_pattern(source_file, &options), output_path, skip))
+    }
+
+    /// 合并目录配置、备用格式和格式默认参数后，单个文件实际使用的转换选项和输出目录
+    fn effective_options(
+        &self,
+        source_file: &Path,
+        output_dir: &Path,
+        options: &ConversionOptions,
+    ) -> Result<(ConversionOptions, PathBuf)> {
+        let (mut options, output_dir) = match &self.dir_configs {
+            Some(dir_configs) => dir_configs.options_for(source_file, output_dir, options)?,
+            None => (options.clone(), output_dir.to_path_buf()),
+        };
+        if let Some(fallback) = self.fallback_format.filter(|_| self.retrying) {
+            options.format = fallback;
+            options.encoder = None;
+        }
+        if let Some(defaults) = self.format_defaults.get(&options.format) {
+            options = defaults.apply(options);
+        }
+        Ok((options, output_dir))
+    }
+
+    /// 判断单个文件的任务类型，供调度器派发时使用
+    ///
+    /// 无法确定转换选项的文件很快就会失败，按复制任务处理
+    fn job_kind(&self, source_file: &Path, output_dir: &Path, options: &ConversionOptions) -> JobKind {
+        self.effective_options(source_file, output_dir, options)
+            .map_or(JobKind::Copy, |(options, _)| JobKind::for_options(&options, self.backend.is_some()))
+    }
+
+    /// 完成转换选项中需要读取源文件的设置
+    /// 
+    /// 把 [`CoverArt::Attached`] 解析为源文件中的封面流，并在双遍响度标准化时分析源文件
+    pub(crate) fn prepare_options(
+        &self,
+        source_file: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<ConversionOptions> {
+        let mut options = self.measure_for_normalization(source_file, options, cancel_token)?;
+        self.resolve_cover(source_file, &mut options)?;
+        Ok(options)
+    }
+
+    /// 把 [`CoverArt::Attached`] 解析为源文件中的封面流，源文件没有封面时不嵌入封面
+    pub(crate) fn resolve_cover(&self, source_file: &Path, options: &mut ConversionOptions) -> Result<()> {
+        if options.cover == Some(CoverArt::Attached) && options.format.supports_cover() {
+            options.cover = probe::probe_attached_picture(&self.ffmpeg_path, source_file)?.map(CoverArt::Stream);
+        }
+        Ok(())
+    }
+
+    /// 按标签模板解析源文件名，返回加入捕获字段和专辑标签的转换选项
+    fn apply_tag_pattern(&self, source_file: &Path, options: &ConversionOptions) -> ConversionOptions {
+        let captured = self
+            .tag_pattern
+            .as_ref()
+            .and_then(|pattern| pattern.captures_file(source_file))
+            .unwrap_or_default();
+        // 模板标签写在前面，FFmpeg 以最后一次设置为准，因此已有的标签优先
+        let mut tags = captured;
+        tags.extend(options.tags.iter().cloned());
+        ConversionOptions { tags, ..options.clone() }
+    }
+
+    /// 以源文件所在目录为专辑，为要转换的每个文件生成专辑名和音轨号标签
+    /// 
+    /// 同一目录中的文件按文件名顺序编号，总数是该目录中要转换的文件数，
+    /// 已被时长过滤、`--pick`、`--limit` 等排除的文件不计入
+    fn album_tags(&self, files: &[PathBuf]) -> HashMap<PathBuf, Vec<(String, String)>> {
+        let mut albums: HashMap<&Path, Vec<&PathBuf>> = HashMap::new();
+        for file in files {
+            let dir = match file.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            albums.entry(dir).or_default().push(file);
+        }
+
+        let mut tags = HashMap::new();
+        for (dir, mut tracks) in albums {
+            // 规范化后才能取得 "." 或 ".." 这类路径的目录名
+            let album = fs::canonicalize(dir)
+                .ok()
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|name| name.to_string_lossy().into_owned());
+            tracks.sort();
+            tracks.dedup();
+            let total = tracks.len();
+            for (index, track) in tracks.into_iter().enumerate() {
+                let mut file_tags: Vec<_> = album.iter().map(|album| ("album".to_string(), album.clone())).collect();
+                file_tags.push(("track".to_string(), format!("{}/{total}", index + 1)));
+                tags.insert(track.clone(), file_tags);
+            }
+        }
+        tags
+    }
+
+    /// 把专辑标签加入转换选项
+    ///
+    /// 专辑标签的优先级最低：模板解析出的同名字段和转换选项中已有的标签优先
+    fn with_album_tags(
+        &self,
+        source_file: &Path,
+        album_tags: &[(String, String)],
+        options: &ConversionOptions,
+    ) -> ConversionOptions {
+        let captured = self
+            .tag_pattern
+            .as_ref()
+            .and_then(|pattern| pattern.captures_file(source_file))
+            .unwrap_or_default();
+        let mut tags: Vec<_> = album_tags
+            .iter()
+            .filter(|(key, _)| !captured.iter().any(|(captured_key, _)| captured_key == key))
+            .cloned()
+            .collect();
+        tags.extend(options.tags.iter().cloned());
+        ConversionOptions { tags, ..options.clone() }
+    }
+
+    /// 按编号或语言选择音轨，返回设置了音轨序号的转换选项
+    fn select_audio_track(&self, source_file: &Path, options: &ConversionOptions) -> Result<ConversionOptions> {
+        match &self.audio_track {
+            Some(audio_track) if self.backend.is_none() && options.audio_stream.is_none() => {
+                let streams = probe::probe_audio_streams(&self.ffmpeg_path, source_file)?;
+                Ok(ConversionOptions {
+                    audio_stream: Some(audio_track.resolve(&streams)?),
+                    ..options.clone()
+                })
+            }
+            _ => Ok(options.clone()),
+        }
+    }
+
+    /// 把每个章节分别转换为一个文件
+    /// 
+    /// # 返回值
+    /// 
+    /// 第一个章节的输出文件路径
+    fn convert_chapters(
+        &self,
+        source_file: &Path,
+        output_path: &Path,
+        chapters: &[Chapter],
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        let chapter_dir = output_path.with_extension("");
+        let overwrite_policy = options.overwrite_policy.unwrap_or(self.overwrite_policy);
+        // 序号至少两位，章节很多时按总数补齐，保证文件按名称排序即为章节顺序
+        let width = chapters.len().to_string().len().max(2);
+        let mut first_output = None;
+
+        for (i, chapter) in chapters.iter().enumerate() {
+            let number = i + 1;
+            let title = chapter.title.clone().unwrap_or_else(|| tr!("第 {number} 章", "Chapter {number}"));
+            let chapter_path = chapter_dir.join(format!(
+                "{number:0width$} - {}.{}",
+                sanitize_file_name(&title),
+                options.format.extension()
+            ));
+            if !overwrite_policy.should_skip(source_file, &chapter_path) {
+                let chapter_options = options
+                    .clone()
+                    .with_segment(Some(chapter.start), Some(chapter.end))
+                    .with_tag("title", title)
+                    .with_tag("track", format!("{number}/{}", chapters.len()));
+                self.convert_to(source_file, &chapter_path, &chapter_options, cancel_token)?;
+            }
+            first_output.get_or_insert(chapter_path);
+        }
+
+        Ok(first_output.unwrap_or_else(|| output_path.to_path_buf()))
+    }
+
+    /// 把每条音轨分别转换为一个文件
+    /// 
+    /// # 返回值
+    /// 
+    /// 第一条音轨的输出文件路径
+    fn convert_audio_tracks(
+        &self,
+        source_file: &Path,
+        output_path: &Path,
+        streams: &[AudioStream],
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<PathBuf> {
+        let overwrite_policy = options.overwrite_policy.unwrap_or(self.overwrite_policy);
+        let mut first_output = None;
+
+        for stream in streams {
+            let track_path = track_output_path(output_path, stream);
+            if !overwrite_policy.should_skip(source_file, &track_path) {
+                let track_options = ConversionOptions {
+                    audio_stream: Some(stream.index),
+                    ..options.clone()
+                };
+                self.convert_to(source_file, &track_path, &track_options, cancel_token)?;
+            }
+            first_output.get_or_insert(track_path);
+        }
+
+        Ok(first_output.unwrap_or_else(|| output_path.to_path_buf()))
+    }
+
+    /// 把源文件转换为指定的输出文件
+    fn convert_to(
+        &self,
+        source_file: &Path,
+        output_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        // 检查转换后端是否可用
+        match &self.backend {
+            Some(backend) => backend.check_available()?,
+            None => self.check_ffmpeg_availability()?,
+        }
+
+        // 保留目录结构时，输出文件可能位于尚未创建的子目录中
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).context(text("创建输出目录", "create output directory"), parent)?;
+        }
+
+        // 单独转换文件时等待对应任务类型的空闲名额，名额在转换结束后释放；
+        // 批量转换在派发时已取得名额，见 for_each_dispatched
+        let _permit = match &self.scheduler {
+            Some(scheduler) => {
+                let kind = JobKind::for_options(options, self.backend.is_some());
+                Some(scheduler.acquire(kind, cancel_token)?)
+            }
+            None => None,
+        };
+
+        // 执行转换
+        match &self.backend {
+            Some(backend) => {
+                Self::execute_backend_conversion(backend.as_ref(), source_file, output_path, options, cancel_token)?
+            }
+            None => {
+                let options = self.prepare_options(source_file, options, cancel_token)?;
+                self.execute_ffmpeg_with_hwaccel_fallback(source_file, output_path, &options, cancel_token)?
+            }
+        }
+
+        self.apply_replay_gain(output_path, options, cancel_token)
+    }
+
+    /// 启用增益标签时分析输出文件并写入标签，参见 [`FileProcessor::with_replay_gain`]
+    pub(crate) fn apply_replay_gain(
+        &self,
+        output_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        if self.replay_gain {
+            self.write_gain_tags(output_path, options, cancel_token)?;
+        }
+        Ok(())
+    }
+
+    /// 验证源文件并确定输出文件路径
     /// 
     /// # 返回值
     /// 
-    /// 返回转换结果的统计信息 (成功数, 失败数)
-    pub fn batch_convert<F>(
+    /// (输出文件路径, 是否因覆盖策略而跳过转换)
+    pub(crate) fn resolve_output(
         &self,
-        files: &[PathBuf],
+        source_file: &Path,
         output_dir: &Path,
-        format: AudioFormat,
-        progress_callback: F,
-    ) -> (usize, usize)
-    where
-        F: Fn(usize, usize) + Send + Sync,
-    {
-        let total_files = files.len();
-        let progress_counter = Arc::new(Mutex::new(0));
-        let success_counter = Arc::new(Mutex::new(0));
-        let failure_counter = Arc::new(Mutex::new(0));
-
-        // 使用 Rayon 进行并行处理
-        files.par_iter().for_each(|source_file| {
-            match self.convert_single_file(source_file, output_dir, format) {
-                Ok(_) => {
-                    let mut success_count = success_counter.lock().unwrap();
-                    *success_count += 1;
-                }
-                Err(e) => {
-                    let mut failure_count = failure_counter.lock().unwrap();
-                    *failure_count += 1;
-                    
-                    // 输出错误信息到标准错误流
-                    eprintln!(
-                        "\n❌ [失败] 处理文件 '{}' 时出错: {}",
-                        source_file.display(),
-                        e
-                    );
-                }
-            }
-
-            // 更新进度
-            let mut count = progress_counter.lock().unwrap();
-            *count += 1;
-            progress_callback(*count, total_files);
-        });
+        options: &ConversionOptions,
+    ) -> Result<(PathBuf, bool)> {
+        // 远程地址由 FFmpeg 读取，无法事先检查
+        if !remote::is_remote_url(source_file) && !source_file.exists() {
+            return Err(VideoToAudioError::InvalidPath(
+                tr!("源文件不存在: {}", "Source file does not exist: {}", source_file.display())
+            ));
+        }
 
-        let success_count = *success_counter.lock().unwrap();
-        let failure_count = *failure_counter.lock().unwrap();
-        
-        (success_count, failure_count)
+        let output_path = self.output_path_for(source_file, output_dir, options.format)?;
+        // 音频输入转换为同一格式并输出到源目录时，输出会覆盖源文件
+        if output_path == source_file {
+            return Err(VideoToAudioError::InvalidInput(
+                tr!(
+                    "输出文件与源文件相同: {}，请使用其他输出目录或格式",
+                    "The output file is the source file: {}, use another output directory or format",
+                    source_file.display(),
+                )
+            ));
+        }
+        let overwrite_policy = options.overwrite_policy.unwrap_or(self.overwrite_policy);
+        let skip = overwrite_policy.should_skip(source_file, &output_path);
+        Ok((output_path, skip))
     }
 
-    /// 转换单个视频文件为音频
+    /// 构建输出文件路径
     /// 
-    /// 调用 FFmpeg 执行实际的媒体转换操作
+    /// 根据源文件名和目标格式生成输出文件的完整路径。
+    /// 设置了保留目录结构时，输出文件位于与源文件相对路径对应的子目录中。
+    /// 源为远程地址时，文件名取自地址路径的最后一段。
     /// 
     /// # 参数
     /// 
@@ -209,120 +1891,386 @@ impl FileProcessor {
     /// 
     /// # 返回值
     /// 
-    /// 成功时返回输出文件路径
-    /// 
-    /// # 错误
-    /// 
-    /// 当转换失败时返回相应的错误信息
-    pub fn convert_single_file(
+    /// 输出音频文件的完整路径
+    pub fn output_path_for(
         &self,
         source_file: &Path,
         output_dir: &Path,
         format: AudioFormat,
     ) -> Result<PathBuf> {
-        // 验证源文件
-        if !source_file.exists() {
-            return Err(VideoToAudioError::InvalidPath(
-                format!("源文件不存在: {}", source_file.display())
-            ));
+        let file_stem = match source_file.to_str() {
+            Some(url) if remote::is_remote_url(source_file) => remote::url_file_stem(url),
+            _ => source_file.file_stem().map(|stem| stem.to_os_string()),
         }
+        .ok_or_else(|| VideoToAudioError::InvalidPath(
+            tr!("无法获取文件名: {}", "Cannot get the file name: {}", source_file.display())
+        ))?;
 
-        // 构建输出文件路径
-        let output_path = self.build_output_path(source_file, output_dir, format)?;
-
-        // 检查 FFmpeg 是否可用
-        self.check_ffmpeg_availability()?;
+        // 保留原始文件名字节，不是有效 UTF-8 的文件名也不会被替换成乱码
+        let mut output_filename = file_stem;
+        output_filename.push(".");
+        output_filename.push(format.extension());
 
-        // 执行转换
-        self.execute_ffmpeg_conversion(source_file, &output_path, format)?;
+        // 保留源目录结构：输出目录 + 源文件相对于源根目录的父路径
+        let relative_parent = self
+            .source_root
+            .as_deref()
+            .and_then(|root| source_file.strip_prefix(root).ok())
+            .and_then(Path::parent);
 
-        Ok(output_path)
+        match relative_parent {
+            Some(parent) => Ok(output_dir.join(parent).join(output_filename)),
+            None => Ok(output_dir.join(output_filename)),
+        }
     }
 
-    /// 构建输出文件路径
+    /// 检查 FFmpeg 是否支持转换所需的编码器
     /// 
-    /// 根据源文件名和目标格式生成输出文件的完整路径
-    fn build_output_path(
-        &self,
-        source_file: &Path,
-        output_dir: &Path,
-        format: AudioFormat,
-    ) -> Result<PathBuf> {
-        let file_stem = source_file
-            .file_stem()
-            .ok_or_else(|| VideoToAudioError::InvalidPath(
-                format!("无法获取文件名: {}", source_file.display())
-            ))?
-            .to_string_lossy();
+    /// 应在批处理开始前调用一次。默认编码器不可用时自动改用备选编码器，
+    /// 此时返回的选项中 `encoder` 字段会被设置为所选的备选编码器。
+    /// 
+    /// # 错误
+    /// 
+    /// 当 FFmpeg 无法执行，或没有任何可用的编码器时返回带有解决办法的错误
+    pub fn check_encoder(&self, options: impl Into<ConversionOptions>) -> Result<ConversionOptions> {
+        let mut options = options.into();
+
+        // 自定义后端不使用 FFmpeg 编码器
+        if self.backend.is_some() {
+            return Ok(options);
+        }
+
+        let capabilities = FfmpegCapabilities::detect(&self.ffmpeg_path)?;
+
+        match options.encoder.as_deref() {
+            Some(encoder) if !capabilities.has_encoder(encoder) => {
+                return Err(VideoToAudioError::MissingDependency(tr!(
+                    "当前 FFmpeg 不支持指定的编码器 {encoder}",
+                    "This FFmpeg does not support the encoder {encoder}"
+                )));
+            }
+            Some(_) => {}
+            None => {
+                let default_encoder = options.format.encoder_candidates().first().copied();
+                let selected = capabilities.select_encoder(options.format)?;
+                if selected != default_encoder {
+                    options.encoder = selected.map(String::from);
+                }
+            }
+        }
 
-        let output_filename = format!("{}.{}", file_stem, format.extension());
-        Ok(output_dir.join(output_filename))
+        Ok(options)
     }
 
     /// 检查 FFmpeg 是否可用
     /// 
     /// 验证系统中是否安装了 FFmpeg 并且可以正常执行
-    fn check_ffmpeg_availability(&self) -> Result<()> {
-        Command::new("ffmpeg")
+    pub(crate) fn check_ffmpeg_availability(&self) -> Result<()> {
+        ffmpeg_command(&self.ffmpeg_path)
             .arg("-version")
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .status()
             .map_err(|_| VideoToAudioError::MissingDependency(
-                "FFmpeg 未安装或不在系统 PATH 中。请安装 FFmpeg 后重试。".to_string()
+                text(
+                    "FFmpeg 未安装或不在系统 PATH 中。请安装 FFmpeg 后重试。",
+                    "FFmpeg is not installed or not in the system PATH. Install FFmpeg and try again.",
+                ).to_string()
             ))?;
         
         Ok(())
     }
 
+    /// 执行 FFmpeg 转换，指定的硬件加速不可用时改用软件解码重试
+    /// 
+    /// `auto` 模式由 FFmpeg 自行回退，不需要重试；超时和取消也不重试。
+    fn execute_ffmpeg_with_hwaccel_fallback(
+        &self,
+        source_file: &Path,
+        output_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        let hwaccel = self.active_hwaccel();
+        let started_at = Instant::now();
+        let result = self.execute_ffmpeg_conversion(source_file, output_path, options, cancel_token);
+
+        let timed_out = options.timeout.is_some_and(|timeout| started_at.elapsed() >= timeout);
+        match result {
+            // 硬件解码失败时 FFmpeg 可能报告为输入损坏或缺少解码器
+            Err(VideoToAudioError::FfmpegError(_) | VideoToAudioError::CorruptInput(_) | VideoToAudioError::UnsupportedCodec(_))
+                if !timed_out && hwaccel.is_some_and(|hwaccel| hwaccel != HwAccel::Auto) =>
+            {
+                info!(hwaccel = ?hwaccel, "硬件加速失败，改用软件解码重试");
+                let software = FileProcessor { hwaccel: None, ..self.clone() };
+                software.execute_ffmpeg_conversion(source_file, output_path, options, cancel_token)?;
+                // 软件解码成功，说明失败的原因是硬件加速不可用
+                self.hwaccel_unavailable.store(true, Ordering::Relaxed);
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    /// 当前实际使用的硬件加速
+    fn active_hwaccel(&self) -> Option<HwAccel> {
+        self.hwaccel.filter(|_| !self.hwaccel_unavailable.load(Ordering::Relaxed))
+    }
+
     /// 执行 FFmpeg 转换命令
     /// 
-    /// 构建并执行 FFmpeg 命令进行实际的媒体转换
+    /// 构建并执行 FFmpeg 命令进行实际的媒体转换，参见 [`run_ffmpeg`](Self::run_ffmpeg)
     fn execute_ffmpeg_conversion(
         &self,
         source_file: &Path,
         output_path: &Path,
-        format: AudioFormat,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
     ) -> Result<()> {
-        let source_str = source_file.to_str()
-            .ok_or_else(|| VideoToAudioError::InvalidPath(
-                "源文件路径包含无效字符".to_string()
-            ))?;
+        let partial_path = Self::partial_output_path(output_path);
+        let args = self.ffmpeg_args(source_file, &partial_path, options);
+        let progress = self.progress.clone().map(|listener| {
+            // 只转换片段时以片段时长为准；远程地址的时长需要联网读取，不报告百分比
+            let total = options.segment_duration().or_else(|| {
+                (!remote::is_remote_url(source_file))
+                    .then(|| probe::probe_duration(&self.ffmpeg_path, source_file).ok())
+                    .flatten()
+            });
+            (listener, total)
+        });
+        self.run_ffmpeg_with_progress(&args, source_file, output_path, options.timeout, progress, cancel_token)
+    }
 
-        let output_str = output_path.to_str()
-            .ok_or_else(|| VideoToAudioError::InvalidPath(
-                "输出文件路径包含无效字符".to_string()
-            ))?;
+    /// 运行 FFmpeg 并在成功后生成输出文件
+    /// 
+    /// `args` 必须把输出写入 [`partial_output_path`](Self::partial_output_path) 对应的临时文件，
+    /// 成功后再原子地重命名为最终文件，因此崩溃、中断或磁盘写满都不会留下看似完整的半成品。
+    /// 进程运行期间会定期检查取消令牌，被取消或超时时终止进程并删除临时文件。
+    /// `source_file` 只用于生成错误信息。
+    pub(crate) fn run_ffmpeg(
+        &self,
+        args: &[OsString],
+        source_file: &Path,
+        output_path: &Path,
+        timeout: Option<Duration>,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        self.run_ffmpeg_with_progress(args, source_file, output_path, timeout, None, cancel_token)
+    }
+
+    /// 运行 FFmpeg，参见 [`run_ffmpeg`](Self::run_ffmpeg)
+    /// 
+    /// `progress` 为进度监听器和本次输出的总时长，设置时通过 `-progress pipe:1`
+    /// 读取 FFmpeg 报告的进度并转发给监听器
+    fn run_ffmpeg_with_progress(
+        &self,
+        args: &[OsString],
+        source_file: &Path,
+        output_path: &Path,
+        timeout: Option<Duration>,
+        progress: Option<(Arc<dyn ProgressListener>, Option<Duration>)>,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        let partial_path = Self::partial_output_path(output_path);
+        let started_at = Instant::now();
+
+        // 启动 FFmpeg 进程，需要报告进度时从标准输出读取进度
+        let mut full_args: Vec<OsString> = Vec::with_capacity(args.len() + 3);
+        if progress.is_some() {
+            full_args.extend(["-progress", "pipe:1", "-nostats"].map(OsString::from));
+        }
+        full_args.extend_from_slice(args);
+        let command_line = (self.show_commands || self.log_file.is_some() || tracing::enabled!(tracing::Level::DEBUG))
+            .then(|| format_command_line(&self.ffmpeg_path, &full_args));
+        if let Some(command_line) = &command_line {
+            debug!(command = %command_line, "执行 FFmpeg");
+            if let Some(log_file) = &self.log_file {
+                log_file.ffmpeg_command(source_file, command_line);
+            }
+        }
+        let command_line = command_line.filter(|_| self.show_commands);
+        // 库不直接输出到终端，由进度监听器决定如何显示命令
+        if let (Some(command_line), Some(listener)) = (&command_line, &self.progress) {
+            listener.ffmpeg_command(source_file, command_line);
+        }
+
+        let mut command = self.conversion_command();
+        command.stdout(if progress.is_some() { Stdio::piped() } else { Stdio::null() });
+        let mut child = command
+            .args(&full_args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context(text("启动 FFmpeg", "start FFmpeg"), &self.ffmpeg_path)?;
+
+        // 进度读取线程在 FFmpeg 关闭标准输出（进程结束或被终止）后退出
+        let stdout_pipe = child.stdout.take();
+        let progress_reader = progress.zip(stdout_pipe).map(|((listener, total), pipe)| {
+            let source_file = source_file.to_path_buf();
+            thread::spawn(move || {
+                for line in BufReader::new(pipe).lines().map_while(std::result::Result::ok) {
+                    if let Some(position) = parse_progress_line(&line) {
+                        listener.file_progress(&source_file, position, total);
+                    }
+                }
+            })
+        });
+
+        // 在独立线程中读取错误输出，避免管道写满导致进程阻塞
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = thread::spawn(move || {
+            let mut stderr = Vec::new();
+            if let Some(pipe) = stderr_pipe.as_mut() {
+                let _ = pipe.read_to_end(&mut stderr);
+            }
+            stderr
+        });
+
+        // 等待进程结束，期间响应取消请求和超时
+        let status = loop {
+            if let Some(status) = child.try_wait().context(text("等待 FFmpeg", "wait for FFmpeg"), &self.ffmpeg_path)? {
+                break status;
+            }
+
+            let timed_out = timeout.is_some_and(|timeout| started_at.elapsed() >= timeout);
+            if cancel_token.is_cancelled() || timed_out {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = stderr_reader.join();
+                if let Some(reader) = progress_reader {
+                    let _ = reader.join();
+                }
+                let _ = fs::remove_file(&partial_path);
+                return Err(match timeout {
+                    Some(timeout) if timed_out => Self::timeout_error(timeout),
+                    _ => VideoToAudioError::Cancelled,
+                });
+            }
+
+            thread::sleep(CANCEL_POLL_INTERVAL);
+        };
+
+        let stderr = stderr_reader.join().unwrap_or_default();
+        if let Some(reader) = progress_reader {
+            let _ = reader.join();
+        }
+
+        if !status.success() {
+            let _ = fs::remove_file(&partial_path);
+            let stderr = String::from_utf8_lossy(&stderr);
+            let mut message = match source_file.to_str() {
+                Some(url) if remote::is_remote_url(source_file) => remote::describe_remote_error(url, &stderr),
+                _ => tr!("转换失败: {stderr}", "Conversion failed: {stderr}"),
+            };
+            if let Some(command_line) = command_line {
+                message = tr!("{}\n命令: {command_line}", "{}\nCommand: {command_line}", message.trim_end());
+            }
+            return Err(VideoToAudioError::from_ffmpeg_stderr(&stderr, message));
+        }
+
+        if !stderr.is_empty() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            debug!(stderr = %stderr.trim_end(), "FFmpeg 错误输出");
+            if let Some(log_file) = &self.log_file {
+                log_file.ffmpeg_stderr(source_file, &stderr);
+            }
+        }
+
+        // 转换成功后再替换为最终文件名
+        fs::rename(&partial_path, output_path).map_err(|e| {
+            let _ = fs::remove_file(&partial_path);
+            VideoToAudioError::io(text("写入输出文件", "write output file"), output_path, e)
+        })?;
+
+        Ok(())
+    }
+
+    /// 使用自定义后端转换
+    /// 
+    /// 与 FFmpeg 命令行相同，先写入临时文件，成功后再重命名为最终文件
+    fn execute_backend_conversion(
+        backend: &dyn ConversionBackend,
+        source_file: &Path,
+        output_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        let partial_path = Self::partial_output_path(output_path);
+
+        if let Err(e) = backend.convert(source_file, &partial_path, options, cancel_token) {
+            let _ = fs::remove_file(&partial_path);
+            return Err(e);
+        }
 
-        // 构建 FFmpeg 命令参数
-        let mut args = vec![
+        fs::rename(&partial_path, output_path).map_err(|e| {
+            let _ = fs::remove_file(&partial_path);
+            VideoToAudioError::io(text("写入输出文件", "write output file"), output_path, e)
+        })
+    }
+
+    /// 构建 FFmpeg 命令参数
+    /// 
+    /// 输出写入 `partial_path`，由于临时文件的扩展名无法推断封装格式，需显式指定。
+    /// 路径以 `OsStr` 原样传给 FFmpeg，因此不是有效 UTF-8 的文件名也能正常转换；
+    /// 路径参数经过 [`ffmpeg_path_arg`] 处理，文件名不会被误解析为选项或协议。
+    /// 设置了每个进程的线程数时，解码和编码都使用 `-threads` 限制。
+    pub(crate) fn ffmpeg_args(
+        &self,
+        source_file: &Path,
+        partial_path: &Path,
+        options: &ConversionOptions,
+    ) -> Vec<OsString> {
+        let mut args: Vec<OsString> = [
             "-y",                    // 覆盖已存在的文件
             "-hide_banner",          // 隐藏版本信息
             "-loglevel", "error",    // 只显示错误信息
-            "-i", source_str,        // 输入文件
-            "-vn",                   // 不包含视频流
-        ];
-
-        // 添加格式特定的参数
-        args.extend(format.ffmpeg_args());
-        args.push(output_str);
-
-        // 执行 FFmpeg 命令
-        let output = Command::new("ffmpeg")
-            .args(&args)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(VideoToAudioError::Io)?;
+        ].map(OsString::from).into();
 
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            return Err(VideoToAudioError::FfmpegError(
-                format!("转换失败: {stderr}")
-            ));
+        // -threads 写在输入前限制解码线程，写在输出前限制编码线程
+        let threads_args: Vec<OsString> = match self.ffmpeg_threads {
+            Some(threads) => vec!["-threads".into(), threads.to_string().into()],
+            None => Vec::new(),
+        };
+        args.extend(threads_args.iter().cloned());
+        if let Some(hwaccel) = self.active_hwaccel() {
+            args.extend(["-hwaccel", hwaccel.ffmpeg_name()].map(OsString::from));
+        }
+        args.extend(options.input_args().into_iter().map(OsString::from));
+        args.push("-i".into());      // 输入文件
+        args.push(input_arg(source_file));
+        args.extend(cover_input_args(options, source_file));
+        if options.cover_art().is_some() {
+            // 同时输出封面时需要显式选择音轨，未指定音轨时使用第一条
+            if options.audio_stream.is_none() {
+                args.extend(["-map", "0:a:0"].map(OsString::from));
+            }
+        } else {
+            args.push("-vn".into()); // 不包含视频流
         }
 
-        Ok(())
+        // 添加格式和转换选项对应的参数
+        args.extend(options.codec_args().into_iter().map(OsString::from));
+        args.extend(options.cover_args(1).into_iter().map(OsString::from));
+        args.extend(threads_args);
+        args.extend(["-f", options.format.muxer()].map(OsString::from));
+        args.push(ffmpeg_path_arg(partial_path));
+        args
+    }
+
+    /// 构造转换超时错误
+    pub(crate) fn timeout_error(timeout: Duration) -> VideoToAudioError {
+        VideoToAudioError::FfmpegError(tr!(
+            "转换超时（超过 {}），已终止 FFmpeg",
+            "Conversion timed out (over {}), FFmpeg was stopped",
+            humantime::format_duration(timeout)
+        ))
+    }
+
+    /// 获取输出文件对应的临时文件路径
+    pub(crate) fn partial_output_path(output_path: &Path) -> PathBuf {
+        let mut partial = output_path.as_os_str().to_os_string();
+        partial.push(PARTIAL_FILE_SUFFIX);
+        PathBuf::from(partial)
     }
 }
 
@@ -331,3 +2279,399 @@ impl Default for FileProcessor {
         Self::new()
     }
 }
+
+/// 文件处理器构建器
+/// 
+/// 通过 [`FileProcessor::builder`] 创建，未设置的选项与 [`FileProcessor::new`] 相同。
+#[derive(Debug, Default)]
+pub struct FileProcessorBuilder {
+    processor: FileProcessor,
+    threads: Option<usize>,
+}
+
+impl FileProcessorBuilder {
+    /// 设置要扫描的视频文件扩展名（不含点，不区分大小写）
+    pub fn extensions<I>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = &'static str>,
+    {
+        self.processor.supported_extensions = extensions.into_iter().map(normalize_extension).collect();
+        self
+    }
+
+    /// 设置 FFmpeg 可执行文件路径，默认在系统 PATH 中查找 `ffmpeg`
+    pub fn ffmpeg_path(mut self, ffmpeg_path: impl Into<PathBuf>) -> Self {
+        self.processor.ffmpeg_path = ffmpeg_path.into();
+        self
+    }
+
+    /// 设置输出文件已存在时的处理策略
+    pub fn overwrite_policy(mut self, overwrite_policy: OverwritePolicy) -> Self {
+        self.processor.overwrite_policy = overwrite_policy;
+        self
+    }
+
+    /// 设置自定义转换后端，参见 [`FileProcessor::with_backend`]
+    pub fn backend(mut self, backend: impl ConversionBackend + 'static) -> Self {
+        self.processor.backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// 添加扫描时跳过的目录，参见 [`FileProcessor::with_excluded_dir`]
+    pub fn exclude_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.processor.excluded_dirs.push(dir.into());
+        self
+    }
+
+    /// 设置批量转换使用的线程数
+    /// 
+    /// 设置后批量转换在专用线程池中运行，不影响 Rayon 全局线程池。
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// 按任务类型设置并发上限，参见 [`FileProcessor::with_scheduler`]
+    pub fn concurrency(mut self, limits: ConcurrencyLimits) -> Self {
+        self.processor.scheduler = Some(Arc::new(JobScheduler::new(limits)));
+        self
+    }
+
+    /// 设置每个 FFmpeg 进程使用的线程数，参见 [`FileProcessor::with_ffmpeg_threads`]
+    pub fn ffmpeg_threads(mut self, threads: usize) -> Self {
+        self.processor.ffmpeg_threads = Some(threads);
+        self
+    }
+
+    /// 设置是否以低优先级运行 FFmpeg，参见 [`FileProcessor::with_low_priority`]
+    pub fn low_priority(mut self, low_priority: bool) -> Self {
+        self.processor.low_priority = low_priority;
+        self
+    }
+
+    /// 设置是否显示执行的 FFmpeg 命令，参见 [`FileProcessor::with_show_commands`]
+    pub fn show_commands(mut self, show_commands: bool) -> Self {
+        self.processor.show_commands = show_commands;
+        self
+    }
+
+    /// 设置日志文件，参见 [`FileProcessor::with_log_file`]
+    pub fn log_file(mut self, log_file: LogFile) -> Self {
+        self.processor.log_file = Some(Arc::new(log_file));
+        self
+    }
+
+    /// 设置解码时使用的硬件加速，参见 [`FileProcessor::with_hwaccel`]
+    pub fn hwaccel(mut self, hwaccel: HwAccel) -> Self {
+        self.processor.hwaccel = Some(hwaccel);
+        self
+    }
+
+    /// 设置是否提取每条音轨，参见 [`FileProcessor::with_all_audio_tracks`]
+    pub fn all_audio_tracks(mut self, all_audio_tracks: bool) -> Self {
+        self.processor.all_audio_tracks = all_audio_tracks;
+        self
+    }
+
+    /// 设置要提取的音轨，参见 [`FileProcessor::with_audio_track`]
+    pub fn audio_track(mut self, audio_track: AudioTrackSelection) -> Self {
+        self.processor.audio_track = Some(audio_track);
+        self
+    }
+
+    /// 设置是否按章节拆分输出，参见 [`FileProcessor::with_split_chapters`]
+    pub fn split_chapters(mut self, split_chapters: bool) -> Self {
+        self.processor.split_chapters = split_chapters;
+        self
+    }
+
+    /// 设置是否写入增益标签，参见 [`FileProcessor::with_replay_gain`]
+    pub fn replay_gain(mut self, replay_gain: bool) -> Self {
+        self.processor.replay_gain = replay_gain;
+        self
+    }
+
+    /// 设置从源文件名解析标签的模板，参见 [`FileProcessor::with_tag_pattern`]
+    pub fn tag_pattern(mut self, tag_pattern: TagPattern) -> Self {
+        self.processor.tag_pattern = Some(tag_pattern);
+        self
+    }
+
+    /// 设置是否把每个源目录视为一张专辑，参见 [`FileProcessor::with_album_per_dir`]
+    pub fn album_per_dir(mut self, album_per_dir: bool) -> Self {
+        self.processor.album_per_dir = album_per_dir;
+        self
+    }
+
+    /// 设置某个格式的默认编码参数，参见 [`FileProcessor::with_format_defaults`]
+    pub fn format_defaults(mut self, format: AudioFormat, defaults: FormatDefaults) -> Self {
+        self.processor.format_defaults.insert(format, defaults);
+        self
+    }
+
+    /// 设置转换进度监听器，参见 [`FileProcessor::with_progress`]
+    pub fn progress(mut self, progress: impl ProgressListener + 'static) -> Self {
+        self.processor.progress = Some(Arc::new(progress));
+        self
+    }
+
+    /// 设置源目录中的按目录配置，参见 [`FileProcessor::with_dir_configs`]
+    pub fn dir_configs(mut self, dir_configs: DirConfigResolver) -> Self {
+        self.processor.dir_configs = Some(Arc::new(dir_configs));
+        self
+    }
+
+    /// 设置失败文件的最大重试次数，参见 [`FileProcessor::with_retries`]
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.processor.retries = retries;
+        self
+    }
+
+    /// 设置重试时使用的备用音频格式，参见 [`FileProcessor::with_fallback_format`]
+    pub fn fallback_format(mut self, fallback_format: Option<AudioFormat>) -> Self {
+        self.processor.fallback_format = fallback_format;
+        self
+    }
+
+    /// 设置扫描时应用的筛选条件，参见 [`FileProcessor::with_file_filter`]
+    pub fn file_filter(mut self, file_filter: FileFilter) -> Self {
+        self.processor.file_filter = file_filter;
+        self
+    }
+
+    /// 设置快速失败模式，参见 [`FileProcessor::with_fail_fast`]
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.processor.fail_fast = fail_fast;
+        self
+    }
+
+    /// 设置是否记录批处理状态日志，参见 [`FileProcessor::with_journal`]
+    pub fn journal(mut self, journal: bool) -> Self {
+        self.processor.journal = journal;
+        self
+    }
+
+    /// 设置保留目录结构的源根目录，参见 [`FileProcessor::with_preserved_structure`]
+    pub fn preserved_structure(mut self, source_root: Option<PathBuf>) -> Self {
+        self.processor.source_root = source_root;
+        self
+    }
+
+    /// 构建文件处理器
+    /// 
+    /// # 错误
+    /// 
+    /// 当扩展名列表为空、线程数为 0 或线程池创建失败时返回错误
+    pub fn build(self) -> Result<FileProcessor> {
+        let mut processor = self.processor;
+
+        if processor.supported_extensions.is_empty() {
+            return Err(VideoToAudioError::InvalidInput(
+                text("视频文件扩展名列表不能为空", "The list of video file extensions must not be empty").to_string()
+            ));
+        }
+
+        if let Some(threads) = self.threads {
+            if threads == 0 {
+                return Err(VideoToAudioError::InvalidInput(
+                    text("线程数必须大于 0", "The number of threads must be greater than 0").to_string()
+                ));
+            }
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .map_err(|e| VideoToAudioError::InvalidInput(
+                    tr!("无法创建线程池: {e}", "Cannot create the thread pool: {e}")
+                ))?;
+            processor.thread_pool = Some(Arc::new(pool));
+        }
+
+        Ok(processor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_command_line_quotes_arguments() {
+        let args: Vec<OsString> = ["-i", "my video.mp4", "-b:a", "192k", "it's.mp3"].map(OsString::from).to_vec();
+        assert_eq!(
+            format_command_line(Path::new("/usr/bin/ffmpeg"), &args),
+            r#"/usr/bin/ffmpeg -i 'my video.mp4' -b:a 192k "it's.mp3""#
+        );
+    }
+
+    #[test]
+    fn test_ffmpeg_command_runs_program() {
+        let (shell, args) = if cfg!(windows) { ("cmd", ["/C", "echo ok"]) } else { ("sh", ["-c", "echo ok"]) };
+        let mut command = ffmpeg_command(Path::new(shell));
+        set_low_priority(&mut command);
+        let output = command.args(args).output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_creation_flags_hide_console() {
+        assert_eq!(windows_creation_flags(false), CREATE_NO_WINDOW);
+        // 低优先级模式覆盖创建标志时仍然不创建窗口
+        assert_eq!(windows_creation_flags(true) & CREATE_NO_WINDOW, CREATE_NO_WINDOW);
+        assert_eq!(windows_creation_flags(true) & BELOW_NORMAL_PRIORITY_CLASS, BELOW_NORMAL_PRIORITY_CLASS);
+    }
+
+    #[test]
+    fn test_ffmpeg_args_protect_hostile_file_names() {
+        let args = FileProcessor::new().ffmpeg_args(
+            Path::new("-i.mp4"),
+            Path::new("concat:out.mp3.part"),
+            &ConversionOptions::new(AudioFormat::Mp3),
+        );
+
+        let input = args.iter().position(|arg| arg == "-i").unwrap();
+        assert_eq!(Path::new(&args[input + 1]), Path::new(".").join("-i.mp4"));
+        assert_eq!(Path::new(args.last().unwrap()), Path::new(".").join("concat:out.mp3.part"));
+    }
+
+    #[test]
+    fn test_ffmpeg_args_limit_threads() {
+        let processor = FileProcessor::builder().ffmpeg_threads(2).build().unwrap();
+        let args = processor.ffmpeg_args(
+            Path::new("in.mp4"),
+            Path::new("out.mp3.part"),
+            &ConversionOptions::new(AudioFormat::Mp3),
+        );
+
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+        let input = args.iter().position(|arg| arg == "-i").unwrap();
+        let output = args.iter().position(|arg| arg == "-f").unwrap();
+        assert_eq!(args[input - 2..input], ["-threads", "2"]);
+        assert_eq!(args[output - 2..output], ["-threads", "2"]);
+    }
+
+    #[test]
+    fn test_ffmpeg_args_attach_cover() {
+        let options = ConversionOptions::new(AudioFormat::M4b).with_cover("cover.jpg");
+        let args = FileProcessor::new().ffmpeg_args(Path::new("in.mp4"), Path::new("out.m4b.part"), &options);
+
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+        assert!(!args.contains(&"-vn".into()));
+        assert!(args.windows(2).any(|pair| pair == ["-i", "./cover.jpg"]));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0:a:0"]));
+        assert!(args.windows(2).any(|pair| pair == ["-disposition:v:0", "attached_pic"]));
+        assert!(args.windows(2).any(|pair| pair == ["-f", "mp4"]));
+    }
+
+    #[test]
+    fn test_ffmpeg_args_cover_from_frame() {
+        let options = ConversionOptions::new(AudioFormat::Mp3).with_cover_art(CoverArt::Frame(Duration::from_secs(5)));
+        let args = FileProcessor::new().ffmpeg_args(Path::new("in.mp4"), Path::new("out.mp3.part"), &options);
+
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string_lossy()).collect();
+        assert!(args.windows(4).any(|window| window == ["-ss", "5.000", "-i", "./in.mp4"]));
+        assert!(args.windows(2).any(|pair| pair == ["-map", "1:v:0"]));
+        assert!(args.windows(2).any(|pair| pair == ["-frames:v", "1"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ffmpeg_path_arg_keeps_absolute_paths() {
+        assert_eq!(ffmpeg_path_arg(Path::new("/videos/-y.mp4")), "/videos/-y.mp4");
+        assert_eq!(ffmpeg_path_arg(Path::new("./-y.mp4")), "./-y.mp4");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_ffmpeg_path_arg_extends_long_relative_paths() {
+        let nested = "nested\\".repeat(40);
+        for relative in [format!("{nested}-y.mp4"), format!(".\\{nested}-y.mp4")] {
+            let expected = std::path::absolute(&relative).unwrap();
+            let arg = PathBuf::from(ffmpeg_path_arg(Path::new(&relative)));
+            assert_eq!(arg, PathBuf::from(format!(r"\\?\{}", expected.display())));
+        }
+    }
+
+    #[test]
+    fn test_album_tags_number_tracks_by_name() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let album_dir = temp_dir.path().join("Live at Bar");
+        fs::create_dir_all(album_dir.join("extras")).unwrap();
+        for name in ["02 b.mkv", "01 a.mp4", "10 c.mp4", "notes.txt", "extras/bonus.mp4"] {
+            fs::write(album_dir.join(name), "").unwrap();
+        }
+
+        // "03 skipped.mp4" 不在要转换的列表中（如被 --limit 排除），不计入音轨数
+        fs::write(album_dir.join("03 skipped.mp4"), "").unwrap();
+        let files: Vec<PathBuf> = ["10 c.mp4", "02 b.mkv", "01 a.mp4", "extras/bonus.mp4"]
+            .into_iter()
+            .map(|name| album_dir.join(name))
+            .collect();
+        let processor = FileProcessor::new()
+            .with_album_per_dir(true)
+            .with_tag_pattern(Some(TagPattern::parse("{track} {title}").unwrap()));
+        let album_tags = processor.album_tags(&files);
+        assert_eq!(
+            album_tags[&album_dir.join("02 b.mkv")],
+            [("album".to_string(), "Live at Bar".to_string()), ("track".to_string(), "2/3".to_string())]
+        );
+        assert_eq!(album_tags[&album_dir.join("10 c.mp4")][1], ("track".to_string(), "3/3".to_string()));
+
+        // 模板解析出的音轨号和已有的专辑标签优先
+        let source_file = album_dir.join("02 b.mkv");
+        let options = ConversionOptions::new(AudioFormat::Mp3).with_tag("album", "Encore");
+        let options = processor.with_album_tags(&source_file, &album_tags[&source_file], &options);
+        let options = processor.apply_tag_pattern(&source_file, &options);
+        assert_eq!(
+            options.tags,
+            [
+                ("track".to_string(), "02".to_string()),
+                ("title".to_string(), "b".to_string()),
+                ("album".to_string(), "Live at Bar".to_string()),
+                ("album".to_string(), "Encore".to_string()),
+            ]
+        );
+        assert_eq!(album_tags[&album_dir.join("extras/bonus.mp4")][1], ("track".to_string(), "1/1".to_string()));
+    }
+
+    #[test]
+    fn test_audio_inputs_never_overwrite_source() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for name in ["clip.mp4", "song.m4a", "voice.FLAC", "notes.txt"] {
+            fs::write(temp_dir.path().join(name), "fake").unwrap();
+        }
+
+        let processor = FileProcessor::new();
+        assert!(!processor.is_supported_video_file(&temp_dir.path().join("song.m4a")));
+        let processor = processor.with_audio_inputs(true);
+        let mut files = processor.find_video_files(temp_dir.path()).unwrap();
+        files.sort();
+        assert_eq!(files, ["clip.mp4", "song.m4a", "voice.FLAC"].map(|name| temp_dir.path().join(name)));
+
+        // 输出到源目录且格式相同时会覆盖源文件，必须拒绝
+        let options = ConversionOptions::new(AudioFormat::AacCopy);
+        let mut song = temp_dir.path().join("song.m4a");
+        assert!(processor.resolve_output(&song, temp_dir.path(), &options).is_ok());
+        song.set_extension("aac");
+        fs::write(&song, "fake").unwrap();
+        assert!(matches!(
+            processor.resolve_output(&song, temp_dir.path(), &options),
+            Err(VideoToAudioError::InvalidInput(_))
+        ));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_extended_length_path() {
+        let short = Path::new(r"C:\videos\clip.mp4");
+        assert_eq!(extended_length_path(short), short);
+
+        let nested = "nested\\".repeat(40);
+        let disk = PathBuf::from(format!(r"C:\{nested}clip.mp4"));
+        assert_eq!(extended_length_path(&disk), PathBuf::from(format!(r"\\?\C:\{nested}clip.mp4")));
+
+        let unc = PathBuf::from(format!(r"\\nas\media\{nested}clip.mp4"));
+        assert_eq!(extended_length_path(&unc), PathBuf::from(format!(r"\\?\UNC\nas\media\{nested}clip.mp4")));
+    }
+}
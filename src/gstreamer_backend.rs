@@ -0,0 +1,550 @@
+//! # GStreamer 转换后端
+//!
+//! 在进程内通过 GStreamer 完成转换，不启动任何外部程序，适合不允许附带或启动 FFmpeg
+//! 的环境（例如部分应用商店分发的程序）。仅在启用 `gstreamer` 特性时编译。
+//!
+//! GStreamer 库（`libgstreamer-1.0` 及其依赖的 GLib）在第一次使用时动态加载，
+//! 编译时不需要 GStreamer 的开发文件；运行时缺少库或所需的插件时，
+//! [`ConversionBackend::check_available`] 会给出缺少的组件。
+//!
+//! 各格式使用的插件元素：
+//!
+//! - MP3：`lamemp3enc`（gst-plugins-good）
+//! - Opus：`opusenc`、`oggmux`（gst-plugins-base）
+//! - M4B：`fdkaacenc`、`avenc_aac` 或 `voaacenc` 之一，以及 `mp4mux`
+//! - AAC：`aacparse`，直接复制音频流，不重新编码
+//!
+//! 该后端不支持滤镜、静音裁剪、响度标准化、音轨选择、片段、标签和封面设置，
+//! 也不能指定 FFmpeg 的编码器名称；码率、MP3 的 VBR 质量和采样率可以正常使用。
+
+use crate::audio_format::AudioFormat;
+use crate::backend::ConversionBackend;
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::estimate::output_kbps;
+use crate::file_processor::FileProcessor;
+use crate::options::ConversionOptions;
+use crate::tr;
+use libloading::Library;
+use std::ffi::{c_char, c_int, c_uint, c_void, CStr, CString};
+use std::path::Path;
+use std::ptr;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// 等待管道消息的间隔，期间检查取消和超时
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 所有格式都需要的插件元素
+const CORE_ELEMENTS: &[&str] = &["filesrc", "filesink", "decodebin", "parsebin", "audioconvert", "audioresample"];
+
+/// M4B 可用的 AAC 编码器，按音质从高到低排列
+const AAC_ENCODERS: &[&str] = &["fdkaacenc", "avenc_aac", "voaacenc"];
+
+/// 动态库的文件名：GStreamer、GObject、GLib
+#[cfg(target_os = "macos")]
+const LIBRARY_NAMES: [&str; 3] = ["libgstreamer-1.0.0.dylib", "libgobject-2.0.0.dylib", "libglib-2.0.0.dylib"];
+#[cfg(windows)]
+const LIBRARY_NAMES: [&str; 3] = ["gstreamer-1.0-0.dll", "gobject-2.0-0.dll", "glib-2.0-0.dll"];
+#[cfg(not(any(target_os = "macos", windows)))]
+const LIBRARY_NAMES: [&str; 3] = ["libgstreamer-1.0.so.0", "libgobject-2.0.so.0", "libglib-2.0.so.0"];
+
+/// `GstState`
+const GST_STATE_NULL: c_int = 1;
+const GST_STATE_PLAYING: c_int = 4;
+
+/// `GstStateChangeReturn` 中的失败
+const GST_STATE_CHANGE_FAILURE: c_int = 0;
+
+/// `GstMessageType`
+const GST_MESSAGE_EOS: c_uint = 1 << 0;
+const GST_MESSAGE_ERROR: c_uint = 1 << 1;
+
+/// 基于 GStreamer 的转换后端
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GStreamerBackend;
+
+impl GStreamerBackend {
+    /// 创建后端实例
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ConversionBackend for GStreamerBackend {
+    fn name(&self) -> &'static str {
+        "gstreamer"
+    }
+
+    fn check_available(&self) -> Result<()> {
+        Gst::get()?.require(CORE_ELEMENTS)
+    }
+
+    fn convert(
+        &self,
+        source_file: &Path,
+        partial_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        check_options(options)?;
+        let gst = Gst::get()?;
+        let aac_encoder = AAC_ENCODERS.iter().copied().find(|name| gst.has_element(name));
+        let description = pipeline_description(options, aac_encoder)?;
+        gst.require(&format_elements(options.format))?;
+
+        let pipeline = gst.parse_launch(&description)?;
+        pipeline.set_location("src", source_file)?;
+        pipeline.set_location("sink", partial_path)?;
+        pipeline.run(options.timeout, cancel_token)
+    }
+}
+
+/// 拒绝该后端不支持的选项，而不是静默忽略
+fn check_options(options: &ConversionOptions) -> Result<()> {
+    let stream_copy = options.is_stream_copy();
+    if options.encoder.is_some()
+        || !options.filter_chain().is_empty()
+        || options.audio_stream.is_some()
+        || options.start.is_some()
+        || options.end.is_some()
+        || !options.tags.is_empty()
+        || (options.cover.is_some() && options.format.supports_cover())
+        || (stream_copy && (options.bitrate.is_some() || options.sample_rate.is_some()))
+    {
+        return Err(VideoToAudioError::InvalidInput(tr!(
+            "GStreamer 后端不支持编码器、滤镜、静音裁剪、响度标准化、音轨选择、片段、标签和封面设置，AAC 直接复制音频流时也不能设置码率和采样率",
+            "The GStreamer backend does not support encoder, filter, silence trimming, loudness normalization, audio track, clip, tag or cover settings, and AAC stream copies cannot set a bitrate or sample rate"
+        )));
+    }
+    Ok(())
+}
+
+/// 目标格式需要的插件元素（不含 [`CORE_ELEMENTS`] 和 M4B 的 AAC 编码器）
+fn format_elements(format: AudioFormat) -> Vec<&'static str> {
+    match format {
+        AudioFormat::Mp3 => vec!["lamemp3enc"],
+        AudioFormat::AacCopy => vec!["aacparse"],
+        AudioFormat::Opus => vec!["opusenc", "oggmux"],
+        AudioFormat::M4b => vec!["mp4mux"],
+    }
+}
+
+/// 生成 `gst_parse_launch` 使用的管道描述
+///
+/// 源文件和输出文件的路径不写入描述，而是之后设置到名为 `src` 和 `sink` 的元素上，
+/// 避免路径中的引号和空格破坏描述的语法
+///
+/// # 参数
+///
+/// * `options` - 转换选项，需已通过 [`check_options`] 检查
+/// * `aac_encoder` - 可用的 AAC 编码器，只有 M4B 需要
+///
+/// # 错误
+///
+/// M4B 格式没有可用的 AAC 编码器时返回 [`VideoToAudioError::MissingDependency`]
+fn pipeline_description(options: &ConversionOptions, aac_encoder: Option<&str>) -> Result<String> {
+    if options.is_stream_copy() {
+        return Ok("filesrc name=src ! parsebin ! aacparse ! audio/mpeg,stream-format=adts ! filesink name=sink".to_string());
+    }
+
+    let kbps = output_kbps(options, None).unwrap_or(192);
+    let encoder = match options.format {
+        AudioFormat::Mp3 => match options.bitrate {
+            Some(kbps) => format!("lamemp3enc target=bitrate bitrate={kbps} cbr=true"),
+            None => format!("lamemp3enc target=quality quality={}", options.vbr_quality.unwrap_or(0)),
+        },
+        AudioFormat::Opus => format!("opusenc bitrate={} ! oggmux", kbps * 1000),
+        AudioFormat::M4b => {
+            let encoder = aac_encoder.ok_or_else(|| VideoToAudioError::MissingDependency(tr!(
+                "GStreamer 中没有可用的 AAC 编码器，请安装提供 {} 之一的插件",
+                "GStreamer has no AAC encoder, install a plugin providing one of {}",
+                AAC_ENCODERS.join(", ")
+            )))?;
+            format!("{encoder} bitrate={} ! mp4mux", kbps * 1000)
+        }
+        AudioFormat::AacCopy => unreachable!("未设置编码器的 AAC 直接复制音频流"),
+    };
+    let caps = options.sample_rate.map(|rate| format!("audio/x-raw,rate={rate} ! ")).unwrap_or_default();
+    Ok(format!(
+        "filesrc name=src ! decodebin ! audioconvert ! audioresample ! {caps}{encoder} ! filesink name=sink"
+    ))
+}
+
+/// `GError`
+#[repr(C)]
+#[allow(dead_code)]
+struct GError {
+    domain: u32,
+    code: c_int,
+    message: *mut c_char,
+}
+
+/// `GstMessage` 开头的部分，只用来读取消息类型
+#[repr(C)]
+#[allow(dead_code)]
+struct GstMessage {
+    // GstMiniObject
+    gtype: usize,
+    refcount: c_int,
+    lockstate: c_int,
+    flags: c_uint,
+    copy: *mut c_void,
+    dispose: *mut c_void,
+    free: *mut c_void,
+    priv_uint: c_uint,
+    priv_pointer: *mut c_void,
+    message_type: c_uint,
+}
+
+/// 动态加载的 GStreamer 函数
+struct Gst {
+    init_check: unsafe extern "C" fn(*mut c_int, *mut c_void, *mut *mut GError) -> c_int,
+    parse_launch: unsafe extern "C" fn(*const c_char, *mut *mut GError) -> *mut c_void,
+    bin_get_by_name: unsafe extern "C" fn(*mut c_void, *const c_char) -> *mut c_void,
+    element_set_state: unsafe extern "C" fn(*mut c_void, c_int) -> c_int,
+    element_get_bus: unsafe extern "C" fn(*mut c_void) -> *mut c_void,
+    element_factory_find: unsafe extern "C" fn(*const c_char) -> *mut c_void,
+    bus_timed_pop_filtered: unsafe extern "C" fn(*mut c_void, u64, c_uint) -> *mut GstMessage,
+    message_parse_error: unsafe extern "C" fn(*mut GstMessage, *mut *mut GError, *mut *mut c_char),
+    mini_object_unref: unsafe extern "C" fn(*mut c_void),
+    object_unref: unsafe extern "C" fn(*mut c_void),
+    object_set: unsafe extern "C" fn(*mut c_void, *const c_char, ...),
+    error_free: unsafe extern "C" fn(*mut GError),
+    free: unsafe extern "C" fn(*mut c_void),
+    /// 保持动态库加载，上面的函数指针才有效
+    _libraries: Vec<Library>,
+}
+
+impl Gst {
+    /// 加载并初始化 GStreamer，只在第一次调用时执行
+    fn get() -> Result<&'static Self> {
+        static GST: OnceLock<std::result::Result<Gst, String>> = OnceLock::new();
+        GST.get_or_init(Self::load)
+            .as_ref()
+            .map_err(|message| VideoToAudioError::MissingDependency(message.clone()))
+    }
+
+    fn load() -> std::result::Result<Self, String> {
+        let libraries = LIBRARY_NAMES
+            .iter()
+            .map(|name| {
+                // SAFETY: 加载的是 GStreamer 和 GLib 的系统库，初始化代码没有额外要求
+                unsafe { Library::new(name) }.map_err(|e| tr!(
+                    "无法加载 GStreamer 库 {}: {}",
+                    "Cannot load the GStreamer library {}: {}",
+                    name, e
+                ))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let [gstreamer, gobject, glib] = [&libraries[0], &libraries[1], &libraries[2]];
+
+        // SAFETY: 每个字段的类型都与同名 C 函数在 GStreamer 1.x 和 GLib 2.x 中的签名一致
+        let gst = unsafe {
+            Self {
+                init_check: symbol(gstreamer, "gst_init_check")?,
+                parse_launch: symbol(gstreamer, "gst_parse_launch")?,
+                bin_get_by_name: symbol(gstreamer, "gst_bin_get_by_name")?,
+                element_set_state: symbol(gstreamer, "gst_element_set_state")?,
+                element_get_bus: symbol(gstreamer, "gst_element_get_bus")?,
+                element_factory_find: symbol(gstreamer, "gst_element_factory_find")?,
+                bus_timed_pop_filtered: symbol(gstreamer, "gst_bus_timed_pop_filtered")?,
+                message_parse_error: symbol(gstreamer, "gst_message_parse_error")?,
+                mini_object_unref: symbol(gstreamer, "gst_mini_object_unref")?,
+                object_unref: symbol(gstreamer, "gst_object_unref")?,
+                object_set: symbol(gobject, "g_object_set")?,
+                error_free: symbol(glib, "g_error_free")?,
+                free: symbol(glib, "g_free")?,
+                _libraries: Vec::new(),
+            }
+        };
+
+        let mut error = ptr::null_mut();
+        // SAFETY: 不传入命令行参数，error 是可写的指针
+        if unsafe { (gst.init_check)(ptr::null_mut(), ptr::null_mut(), &mut error) } == 0 {
+            let message = gst.take_error(error);
+            return Err(tr!("GStreamer 初始化失败: {}", "GStreamer failed to initialize: {}", message));
+        }
+        Ok(Self { _libraries: libraries, ..gst })
+    }
+
+    /// 插件元素是否已安装
+    fn has_element(&self, name: &str) -> bool {
+        let Ok(name) = CString::new(name) else {
+            return false;
+        };
+        // SAFETY: name 是以 NUL 结尾的字符串，返回的工厂持有引用，需要释放
+        unsafe {
+            let factory = (self.element_factory_find)(name.as_ptr());
+            if factory.is_null() {
+                return false;
+            }
+            (self.object_unref)(factory);
+        }
+        true
+    }
+
+    /// 检查插件元素是否都已安装
+    ///
+    /// # 错误
+    ///
+    /// 缺少元素时返回 [`VideoToAudioError::MissingDependency`]，列出所有缺少的元素
+    fn require(&self, elements: &[&str]) -> Result<()> {
+        let missing: Vec<_> = elements.iter().copied().filter(|name| !self.has_element(name)).collect();
+        if missing.is_empty() {
+            return Ok(());
+        }
+        Err(VideoToAudioError::MissingDependency(tr!(
+            "GStreamer 缺少插件元素 {}，请安装 gst-plugins-base、gst-plugins-good 等插件包",
+            "GStreamer lacks the plugin elements {}, install plugin packages such as gst-plugins-base and gst-plugins-good",
+            missing.join(", ")
+        )))
+    }
+
+    /// 读取并释放 `GError`
+    fn take_error(&self, error: *mut GError) -> String {
+        if error.is_null() {
+            return String::new();
+        }
+        // SAFETY: error 是 GStreamer 返回的有效 GError，读取后释放
+        unsafe {
+            let message = CStr::from_ptr((*error).message).to_string_lossy().into_owned();
+            (self.error_free)(error);
+            message
+        }
+    }
+
+    /// 按描述创建管道
+    fn parse_launch(&'static self, description: &str) -> Result<Pipeline> {
+        let description = CString::new(description).map_err(|e| VideoToAudioError::InvalidInput(e.to_string()))?;
+        let mut error = ptr::null_mut();
+        // SAFETY: description 以 NUL 结尾，error 是可写的指针
+        let element = unsafe { (self.parse_launch)(description.as_ptr(), &mut error) };
+        // 部分元素无法创建时可能同时返回管道和错误，此时管道在这里释放
+        let pipeline = (!element.is_null()).then(|| Pipeline { gst: self, element });
+        match pipeline {
+            Some(pipeline) if error.is_null() => Ok(pipeline),
+            _ => Err(VideoToAudioError::MissingDependency(tr!(
+                "无法创建 GStreamer 管道: {}",
+                "Cannot create the GStreamer pipeline: {}",
+                self.take_error(error)
+            ))),
+        }
+    }
+}
+
+/// 从动态库中读取函数指针
+///
+/// # Safety
+///
+/// `T` 必须是与该 C 函数签名一致的函数指针类型
+unsafe fn symbol<T: Copy>(library: &Library, name: &str) -> std::result::Result<T, String> {
+    let symbol = unsafe { library.get::<T>(name.as_bytes()) }.map_err(|e| tr!(
+        "GStreamer 库缺少函数 {}: {}",
+        "The GStreamer library lacks the function {}: {}",
+        name, e
+    ))?;
+    Ok(*symbol)
+}
+
+/// GStreamer 管道，释放（drop）时停止并释放所有元素
+struct Pipeline {
+    gst: &'static Gst,
+    element: *mut c_void,
+}
+
+impl Pipeline {
+    /// 设置 `filesrc` 或 `filesink` 元素的文件路径
+    fn set_location(&self, element_name: &str, path: &Path) -> Result<()> {
+        let location = path
+            .to_str()
+            .and_then(|path| CString::new(path).ok())
+            .ok_or_else(|| VideoToAudioError::InvalidPath(tr!(
+                "GStreamer 后端只支持 UTF-8 路径: {}",
+                "The GStreamer backend only supports UTF-8 paths: {}",
+                path.display()
+            )))?;
+        let element_name = CString::new(element_name).expect("元素名称不含 NUL");
+        // SAFETY: 管道有效，返回的元素持有引用，设置属性后释放；g_object_set 的参数列表以 NULL 结尾
+        unsafe {
+            let element = (self.gst.bin_get_by_name)(self.element, element_name.as_ptr());
+            assert!(!element.is_null(), "管道描述中缺少元素 {element_name:?}");
+            (self.gst.object_set)(element, c"location".as_ptr(), location.as_ptr(), ptr::null::<c_char>());
+            (self.gst.object_unref)(element);
+        }
+        Ok(())
+    }
+
+    /// 运行管道直到结束
+    ///
+    /// # 错误
+    ///
+    /// 管道报告错误时返回 [`VideoToAudioError::UnsupportedFormat`]，
+    /// 超时或被取消时返回相应的错误
+    fn run(&self, timeout: Option<Duration>, cancel_token: &CancellationToken) -> Result<()> {
+        let gst = self.gst;
+        // SAFETY: 管道有效
+        if unsafe { (gst.element_set_state)(self.element, GST_STATE_PLAYING) } == GST_STATE_CHANGE_FAILURE {
+            return Err(VideoToAudioError::UnsupportedFormat(tr!(
+                "GStreamer 管道无法启动",
+                "The GStreamer pipeline cannot start"
+            )));
+        }
+
+        // SAFETY: 管道有效，返回的消息总线持有引用，结束时释放
+        let bus = unsafe { (gst.element_get_bus)(self.element) };
+        let started_at = Instant::now();
+        let result = loop {
+            if cancel_token.is_cancelled() {
+                break Err(VideoToAudioError::Cancelled);
+            }
+            if let Some(timeout) = timeout.filter(|timeout| started_at.elapsed() >= *timeout) {
+                break Err(FileProcessor::timeout_error(timeout));
+            }
+
+            let wait = u64::try_from(POLL_INTERVAL.as_nanos()).unwrap_or(u64::MAX);
+            // SAFETY: 消息总线有效，返回的消息持有引用，读取后释放
+            let message = unsafe { (gst.bus_timed_pop_filtered)(bus, wait, GST_MESSAGE_EOS | GST_MESSAGE_ERROR) };
+            if message.is_null() {
+                continue;
+            }
+            // SAFETY: message 是有效的 GstMessage
+            unsafe {
+                let outcome = if (*message).message_type == GST_MESSAGE_ERROR {
+                    let mut error = ptr::null_mut();
+                    let mut debug = ptr::null_mut();
+                    (gst.message_parse_error)(message, &mut error, &mut debug);
+                    (gst.free)(debug.cast());
+                    Err(VideoToAudioError::UnsupportedFormat(tr!(
+                        "GStreamer 转换失败: {}",
+                        "GStreamer conversion failed: {}",
+                        gst.take_error(error)
+                    )))
+                } else {
+                    Ok(())
+                };
+                (gst.mini_object_unref)(message.cast());
+                break outcome;
+            }
+        };
+        // SAFETY: bus 由 gst_element_get_bus 返回
+        unsafe { (gst.object_unref)(bus) };
+        result
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        // SAFETY: 管道有效，停止后释放唯一的引用
+        unsafe {
+            (self.gst.element_set_state)(self.element, GST_STATE_NULL);
+            (self.gst.object_unref)(self.element);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pipeline_description() {
+        let mp3 = ConversionOptions::new(AudioFormat::Mp3);
+        let description = pipeline_description(&mp3, None).unwrap();
+        assert!(description.starts_with("filesrc name=src ! decodebin ! audioconvert"));
+        assert!(description.ends_with("lamemp3enc target=quality quality=0 ! filesink name=sink"));
+
+        let opus = ConversionOptions::new(AudioFormat::Opus).with_bitrate(96).with_sample_rate(48000);
+        let description = pipeline_description(&opus, None).unwrap();
+        assert!(description.contains("audio/x-raw,rate=48000 ! opusenc bitrate=96000 ! oggmux"));
+
+        let m4b = ConversionOptions::new(AudioFormat::M4b);
+        assert!(pipeline_description(&m4b, Some("avenc_aac")).unwrap().contains("avenc_aac bitrate=128000 ! mp4mux"));
+        assert!(matches!(pipeline_description(&m4b, None), Err(VideoToAudioError::MissingDependency(_))));
+
+        let aac = ConversionOptions::new(AudioFormat::AacCopy);
+        assert!(pipeline_description(&aac, None).unwrap().contains("parsebin ! aacparse"));
+    }
+
+    #[test]
+    fn test_unsupported_options_rejected() {
+        let options = ConversionOptions::new(AudioFormat::Mp3);
+        assert!(check_options(&options).is_ok());
+        assert!(check_options(&options.clone().with_bitrate(128)).is_ok());
+        assert!(check_options(&options.clone().with_segment(Some(Duration::from_secs(5)), None)).is_err());
+        assert!(check_options(&options.with_encoder("libmp3lame")).is_err());
+        assert!(check_options(&ConversionOptions::new(AudioFormat::AacCopy).with_bitrate(128)).is_err());
+    }
+
+    #[test]
+    fn test_pipeline_runs_until_end_of_stream() {
+        // 只使用核心元素复制文件，没有安装 GStreamer 的环境跳过
+        let Ok(gst) = Gst::get() else {
+            return;
+        };
+        if gst.require(&["filesrc", "filesink"]).is_err() {
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("source 'quoted\" name.bin");
+        fs::write(&source, b"payload").unwrap();
+        let output = temp_dir.path().join("copy.part");
+
+        let pipeline = gst.parse_launch("filesrc name=src ! filesink name=sink").unwrap();
+        pipeline.set_location("src", &source).unwrap();
+        pipeline.set_location("sink", &output).unwrap();
+        pipeline.run(None, &CancellationToken::new()).unwrap();
+        drop(pipeline);
+        assert_eq!(fs::read(&output).unwrap(), b"payload");
+
+        // 源文件不存在时返回 GStreamer 报告的错误
+        let pipeline = gst.parse_launch("filesrc name=src ! filesink name=sink").unwrap();
+        pipeline.set_location("src", &temp_dir.path().join("missing.bin")).unwrap();
+        pipeline.set_location("sink", &output).unwrap();
+        assert!(pipeline.run(None, &CancellationToken::new()).is_err());
+
+        assert!(gst.parse_launch("no-such-element-v2a").is_err());
+    }
+
+    #[test]
+    fn test_convert_wav_when_available() {
+        // 没有安装 GStreamer 或所需插件的环境只检查错误类型
+        let backend = GStreamerBackend::new();
+        let elements = [CORE_ELEMENTS, &["wavparse", "opusenc", "oggmux"]].concat();
+        match backend.check_available().and_then(|()| Gst::get()?.require(&elements)) {
+            Ok(()) => {}
+            Err(VideoToAudioError::MissingDependency(message)) => {
+                assert!(!message.is_empty());
+                return;
+            }
+            Err(e) => panic!("意外的错误: {e}"),
+        }
+
+        // 1 秒 8 kHz 单声道 16 位静音
+        let temp_dir = TempDir::new().unwrap();
+        let source = temp_dir.path().join("silence 'quoted\".wav");
+        let samples = vec![0u8; 16000];
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(36 + samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(b"WAVEfmt ");
+        wav.extend_from_slice(&16u32.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&1u16.to_le_bytes());
+        wav.extend_from_slice(&8000u32.to_le_bytes());
+        wav.extend_from_slice(&16000u32.to_le_bytes());
+        wav.extend_from_slice(&2u16.to_le_bytes());
+        wav.extend_from_slice(&16u16.to_le_bytes());
+        wav.extend_from_slice(b"data");
+        wav.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&samples);
+        fs::write(&source, wav).unwrap();
+
+        let output = temp_dir.path().join("out.opus.part");
+        let options = ConversionOptions::new(AudioFormat::Opus).with_bitrate(64).with_sample_rate(48000);
+        backend.convert(&source, &output, &options, &CancellationToken::new()).unwrap();
+        assert!(fs::read(&output).unwrap().starts_with(b"OggS"));
+    }
+}
@@ -0,0 +1,318 @@
+//! # 转换历史模块
+//!
+//! 在配置目录的 `history.jsonl` 中记录每次成功的转换，每行一条 JSON 记录，
+//! 只追加不改写，中断时最多丢失最后一行。
+//!
+//! 记录以源文件的内容指纹为键，因此即使输出文件已被移走或源文件被改名，
+//! 也能识别出已经转换过的文件，配合 `--skip-converted` 避免重复转换。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::probe;
+use crate::tagging::format_from_path;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// 计算指纹时从文件开头和结尾各读取的字节数
+const FINGERPRINT_CHUNK: u64 = 1 << 20;
+
+/// 一次成功的转换
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    /// 源文件的内容指纹，参见 [`source_fingerprint`]
+    pub source_hash: String,
+
+    /// 转换时的源文件路径
+    pub source: PathBuf,
+
+    /// 输出文件路径
+    pub output: PathBuf,
+
+    /// 输出格式的扩展名
+    pub format: String,
+
+    /// 转换完成的时间（Unix 时间戳，秒）
+    pub converted_at: u64,
+
+    /// 源文件大小（字节）
+    pub input_bytes: u64,
+
+    /// 输出文件大小（字节）
+    pub output_bytes: u64,
+
+    /// 输出音频的时长（秒），需要 ffprobe
+    pub duration_seconds: Option<f64>,
+
+    /// 转换耗时（秒）
+    pub elapsed_seconds: f64,
+}
+
+impl HistoryRecord {
+    /// 为刚完成的转换生成记录
+    ///
+    /// 输出格式以输出文件的扩展名为准，无法识别时使用 `format`
+    ///
+    /// # 错误
+    ///
+    /// 无法读取源文件或输出文件时返回 I/O 错误
+    pub fn new(
+        ffmpeg_path: &Path,
+        source: &Path,
+        output: &Path,
+        format: AudioFormat,
+        elapsed: Duration,
+    ) -> Result<Self> {
+        Ok(Self {
+            source_hash: source_fingerprint(source)?,
+            source: source.to_path_buf(),
+            output: output.to_path_buf(),
+            format: format_from_path(output).unwrap_or(format).extension().to_string(),
+            converted_at: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+            input_bytes: fs::metadata(source)?.len(),
+            output_bytes: fs::metadata(output)?.len(),
+            duration_seconds: probe::probe_duration(ffmpeg_path, output).ok().map(|d| d.as_secs_f64()),
+            elapsed_seconds: elapsed.as_secs_f64(),
+        })
+    }
+
+    /// 转换完成的时间
+    pub fn converted_at(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.converted_at)
+    }
+}
+
+/// 转换历史的统计
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HistoryStats {
+    /// 转换的文件数
+    pub files: usize,
+
+    /// 输出音频的总时长（秒），不含没有时长记录的转换
+    pub audio_seconds: f64,
+
+    /// 有时长记录的转换的总耗时（秒）
+    pub elapsed_seconds: f64,
+
+    /// 源文件总大小（字节）
+    pub input_bytes: u64,
+
+    /// 输出文件总大小（字节）
+    pub output_bytes: u64,
+
+    /// 每种格式的转换文件数
+    pub formats: BTreeMap<String, usize>,
+
+    /// 按月份（UTC，如 `2024-01`）统计的转换文件数和音频时长（秒）
+    pub months: BTreeMap<String, (usize, f64)>,
+}
+
+impl HistoryStats {
+    /// 统计一组记录
+    pub fn from_records<'a>(records: impl IntoIterator<Item = &'a HistoryRecord>) -> Self {
+        let mut stats = Self::default();
+        for record in records {
+            let audio_seconds = record.duration_seconds.unwrap_or(0.0);
+            stats.files += 1;
+            if record.duration_seconds.is_some() {
+                stats.audio_seconds += audio_seconds;
+                stats.elapsed_seconds += record.elapsed_seconds;
+            }
+            stats.input_bytes += record.input_bytes;
+            stats.output_bytes += record.output_bytes;
+            *stats.formats.entry(record.format.clone()).or_default() += 1;
+
+            let month = humantime::format_rfc3339_seconds(record.converted_at()).to_string()[..7].to_string();
+            let entry = stats.months.entry(month).or_default();
+            entry.0 += 1;
+            entry.1 += audio_seconds;
+        }
+        stats
+    }
+
+    /// 平均转换速度（音频时长与转换耗时之比），没有时长记录时为 `None`
+    pub fn speed(&self) -> Option<f64> {
+        (self.elapsed_seconds > 0.0).then(|| self.audio_seconds / self.elapsed_seconds)
+    }
+}
+
+/// 计算源文件的内容指纹
+///
+/// 视频文件通常很大，指纹只对文件大小以及开头和结尾各 1 MiB 的内容做 SHA-256，
+/// 足以区分不同的视频，又不必读取整个文件。
+///
+/// # 错误
+///
+/// 无法读取文件时返回 I/O 错误
+pub fn source_fingerprint(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hasher = Sha256::new();
+    hasher.update(size.to_le_bytes());
+
+    let mut buffer = Vec::with_capacity(FINGERPRINT_CHUNK as usize);
+    (&mut file).take(FINGERPRINT_CHUNK).read_to_end(&mut buffer)?;
+    hasher.update(&buffer);
+    if size > FINGERPRINT_CHUNK {
+        buffer.clear();
+        file.seek(SeekFrom::Start(size.saturating_sub(FINGERPRINT_CHUNK).max(FINGERPRINT_CHUNK)))?;
+        file.read_to_end(&mut buffer)?;
+        hasher.update(&buffer);
+    }
+
+    Ok(hasher.finalize().iter().map(|byte| format!("{byte:02x}")).collect())
+}
+
+/// 转换历史
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+    records: Vec<HistoryRecord>,
+}
+
+impl History {
+    /// 默认的历史文件路径（配置目录下的 `video2audio-rs/history.jsonl`）
+    ///
+    /// # 错误
+    ///
+    /// 无法获取配置目录时返回 [`VideoToAudioError::InvalidPath`]
+    pub fn default_path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| VideoToAudioError::InvalidPath(text(
+                "无法获取配置目录",
+                "Cannot determine the configuration directory",
+            ).to_string()))?;
+        Ok(config_dir.join("video2audio-rs").join("history.jsonl"))
+    }
+
+    /// 读取历史文件，文件不存在时返回空的历史
+    ///
+    /// 无法解析的行（如写入时中断留下的半行）会被忽略
+    ///
+    /// # 错误
+    ///
+    /// 文件存在但无法读取时返回 I/O 错误
+    pub fn load(path: &Path) -> Result<Self> {
+        let records = match fs::read_to_string(path) {
+            Ok(content) => content.lines().filter_map(|line| serde_json::from_str(line).ok()).collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self { path: path.to_path_buf(), records })
+    }
+
+    /// 全部记录，按记录顺序（最早的在前）
+    pub fn records(&self) -> &[HistoryRecord] {
+        &self.records
+    }
+
+    /// 查找内容指纹相同、且转换为指定格式的最近一条记录
+    pub fn find(&self, source_hash: &str, format: AudioFormat) -> Option<&HistoryRecord> {
+        self.records
+            .iter()
+            .rev()
+            .find(|record| record.source_hash == source_hash && record.format == format.extension())
+    }
+
+    /// 追加一条记录，历史文件及其目录不存在时自动创建
+    ///
+    /// # 错误
+    ///
+    /// 无法写入历史文件时返回 I/O 错误
+    pub fn record(&mut self, record: HistoryRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context(text("创建历史记录目录", "create history directory"), parent)?;
+        }
+        let line = serde_json::to_string(&record).map_err(io::Error::other)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{line}")?;
+        self.records.push(record);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprint_ignores_name_but_not_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let content: Vec<u8> = (0..3 * FINGERPRINT_CHUNK).map(|i| (i % 251) as u8).collect();
+        let mut changed = content.clone();
+        *changed.last_mut().unwrap() ^= 1;
+        for (name, bytes) in [("a.mp4", &content), ("renamed.mkv", &content), ("changed.mp4", &changed)] {
+            fs::write(temp_dir.path().join(name), bytes).unwrap();
+        }
+
+        let fingerprint = |name: &str| source_fingerprint(&temp_dir.path().join(name)).unwrap();
+        assert_eq!(fingerprint("a.mp4"), fingerprint("renamed.mkv"));
+        assert_ne!(fingerprint("a.mp4"), fingerprint("changed.mp4"));
+    }
+
+    #[test]
+    fn test_history_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("config/history.jsonl");
+        let record = HistoryRecord {
+            source_hash: "abc".to_string(),
+            source: PathBuf::from("videos/a.mp4"),
+            output: PathBuf::from("audio/a.mp3"),
+            format: "mp3".to_string(),
+            converted_at: 1_700_000_000,
+            input_bytes: 1000,
+            output_bytes: 100,
+            duration_seconds: Some(60.0),
+            elapsed_seconds: 1.5,
+        };
+
+        let mut history = History::load(&path).unwrap();
+        history.record(record.clone()).unwrap();
+        // 中断时留下的半行被忽略
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"{\"source_hash\":").unwrap();
+
+        let history = History::load(&path).unwrap();
+        assert_eq!(history.records(), [record]);
+        assert!(history.find("abc", AudioFormat::Mp3).is_some());
+        assert!(history.find("abc", AudioFormat::Opus).is_none());
+    }
+
+    #[test]
+    fn test_stats() {
+        let record = |format: &str, converted_at, duration_seconds| HistoryRecord {
+            source_hash: String::new(),
+            source: PathBuf::new(),
+            output: PathBuf::new(),
+            format: format.to_string(),
+            converted_at,
+            input_bytes: 1000,
+            output_bytes: 100,
+            duration_seconds,
+            elapsed_seconds: 10.0,
+        };
+        // 2024-01-31 和 2024-02-01
+        let records = [
+            record("mp3", 1_706_659_200, Some(3600.0)),
+            record("mp3", 1_706_745_600, Some(1800.0)),
+            record("opus", 1_706_745_600, None),
+        ];
+
+        let stats = HistoryStats::from_records(&records);
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.audio_seconds, 5400.0);
+        assert_eq!(stats.input_bytes, 3000);
+        assert_eq!(stats.speed(), Some(270.0));
+        assert_eq!(stats.formats, BTreeMap::from([("mp3".to_string(), 2), ("opus".to_string(), 1)]));
+        assert_eq!(
+            stats.months,
+            BTreeMap::from([("2024-01".to_string(), (1, 3600.0)), ("2024-02".to_string(), (2, 1800.0))])
+        );
+        assert_eq!(HistoryStats::from_records(&[]).speed(), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! # 转换钩子模块
+//!
+//! 每个文件转换成功或失败后执行用户指定的命令，便于衔接上传、打标签或通知脚本。
+//!
+//! 命令模板按 shell 规则拆分为参数后直接执行，不经过 shell，文件名中的特殊字符不会被解释。
+//! 需要管道或重定向时，可以显式使用 `sh -c '...'`。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{Result, VideoToAudioError};
+use crate::tr;
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+
+/// 钩子命令模板
+///
+/// 参数中可以使用以下占位符：
+///
+/// - `{input}` - 源文件路径
+/// - `{output}` - 输出文件路径，转换失败时为空
+/// - `{format}` - 输出格式的扩展名
+/// - `{error}` - 错误信息，转换成功时为空
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookCommand {
+    args: Vec<String>,
+}
+
+/// 执行钩子时的文件信息
+#[derive(Debug, Clone, Copy)]
+pub struct HookContext<'a> {
+    /// 源文件
+    pub input: &'a Path,
+
+    /// 输出文件，转换失败时为 `None`
+    pub output: Option<&'a Path>,
+
+    /// 输出格式
+    pub format: AudioFormat,
+
+    /// 错误信息，转换成功时为 `None`
+    pub error: Option<&'a str>,
+}
+
+impl HookCommand {
+    /// 生成替换占位符后的命令行参数，第一个元素为程序
+    ///
+    /// 整个参数恰好是 `{input}` 或 `{output}` 时直接使用原路径，非 UTF-8 路径也能原样传递
+    pub fn args(&self, context: &HookContext) -> Vec<OsString> {
+        let output = context.output.unwrap_or(Path::new(""));
+        self.args
+            .iter()
+            .map(|arg| match arg.as_str() {
+                "{input}" => context.input.as_os_str().to_os_string(),
+                "{output}" => output.as_os_str().to_os_string(),
+                _ => arg
+                    .replace("{input}", &context.input.to_string_lossy())
+                    .replace("{output}", &output.to_string_lossy())
+                    .replace("{format}", context.format.extension())
+                    .replace("{error}", context.error.unwrap_or_default())
+                    .into(),
+            })
+            .collect()
+    }
+
+    /// 执行钩子并等待结束
+    ///
+    /// 钩子继承标准输出和标准错误，标准输入为空
+    ///
+    /// # 错误
+    ///
+    /// 无法启动命令时返回 I/O 错误
+    pub fn run(&self, context: &HookContext) -> Result<ExitStatus> {
+        let args = self.args(context);
+        let status = Command::new(&args[0]).args(&args[1..]).stdin(Stdio::null()).status()?;
+        Ok(status)
+    }
+}
+
+/// 解析钩子命令模板，按 shell 规则处理引号和转义
+///
+/// # 错误
+///
+/// 命令为空或引号不配对时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_hook(input: &str) -> Result<HookCommand> {
+    match shlex::split(input) {
+        Some(args) if !args.is_empty() => Ok(HookCommand { args }),
+        _ => Err(VideoToAudioError::InvalidInput(tr!(
+            "无效的钩子命令: {input}（示例: 'rclone copy {{output}} remote:audio'）",
+            "Invalid hook command: {input} (example: 'rclone copy {{output}} remote:audio')"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hook_args() {
+        let hook = parse_hook("notify-send '转换完成: {input}' {output} --format={format}").unwrap();
+        let context = HookContext {
+            input: Path::new("videos/my talk.mp4"),
+            output: Some(Path::new("audio/my talk.mp3")),
+            format: AudioFormat::Mp3,
+            error: None,
+        };
+        assert_eq!(
+            hook.args(&context),
+            ["notify-send", "转换完成: videos/my talk.mp4", "audio/my talk.mp3", "--format=mp3"].map(OsString::from)
+        );
+
+        let hook = parse_hook("echo {output} {error}").unwrap();
+        let context = HookContext { output: None, error: Some("moov atom not found"), ..context };
+        assert_eq!(hook.args(&context), ["echo", "", "moov atom not found"].map(OsString::from));
+
+        assert!(parse_hook("").is_err());
+        assert!(parse_hook("echo 'unclosed").is_err());
+    }
+}
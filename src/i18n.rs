@@ -0,0 +1,398 @@
+//! # 界面语言模块
+//!
+//! 界面文字支持简体中文 (`zh-CN`) 和英文 (`en-US`)。语言按以下顺序确定：
+//!
+//! 1. 命令行参数 `--lang`
+//! 2. 配置文件中的 `language`（默认为 `auto`，表示跟随系统）
+//! 3. 环境变量 `LC_ALL`、`LC_MESSAGES`、`LANG`
+//! 4. 简体中文
+//!
+//! 译文与原文写在同一处：[`tr!`](crate::tr) 宏同时接收中文和英文的格式字符串，
+//! 按当前语言格式化其中一个，不需要维护单独的消息文件。
+//! 命令行帮助的英文译文按参数名集中在本模块中。
+
+use crate::error::{Result, VideoToAudioError};
+use std::ffi::OsString;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 配置文件中表示跟随系统语言的值
+pub const AUTO_LANGUAGE: &str = "auto";
+
+/// 当前的界面语言
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(Locale::ZhCn as u8);
+
+/// 界面语言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    /// 简体中文
+    #[default]
+    ZhCn,
+    /// 英文
+    EnUs,
+}
+
+impl Locale {
+    /// 解析语言标签，如 `zh-CN`、`en`、`en_US.UTF-8`，不支持的语言返回 `None`
+    ///
+    /// 只比较主语言，其他地区的中文和英文分别使用简体中文和美式英文
+    pub fn parse(tag: &str) -> Option<Self> {
+        let language = tag.split(['-', '_', '.', '@']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "zh" => Some(Self::ZhCn),
+            "en" => Some(Self::EnUs),
+            _ => None,
+        }
+    }
+
+    /// 语言标签
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Self::ZhCn => "zh-CN",
+            Self::EnUs => "en-US",
+        }
+    }
+
+    /// 从 `LC_ALL`、`LC_MESSAGES`、`LANG` 中第一个非空的环境变量读取系统语言
+    ///
+    /// 系统语言为 `C`/`POSIX` 时使用英文，为其他不支持的语言时返回 `None`
+    pub fn from_env() -> Option<Self> {
+        let value = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())?;
+        match value.as_str() {
+            "C" | "POSIX" => Some(Self::EnUs),
+            value => Self::parse(value),
+        }
+    }
+
+    /// 从命令行参数中找出 `--lang` 指定的语言
+    ///
+    /// 在 clap 解析参数之前调用，使 `--help` 也使用指定的语言
+    pub fn from_args(args: impl IntoIterator<Item = OsString>) -> Option<Self> {
+        let mut args = args.into_iter().map(|arg| arg.to_string_lossy().into_owned());
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+            if arg == "--lang" {
+                return args.next().and_then(|value| Self::parse(&value));
+            }
+            if let Some(value) = arg.strip_prefix("--lang=") {
+                return Self::parse(value);
+            }
+        }
+        None
+    }
+
+    /// 按命令行参数、配置文件、系统语言的顺序确定界面语言
+    ///
+    /// # 参数
+    ///
+    /// * `cli` - `--lang` 指定的语言
+    /// * `configured` - 配置文件中的 `language`
+    ///
+    /// # 错误
+    ///
+    /// 配置文件中的语言无效时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn resolve(cli: Option<Locale>, configured: &str) -> Result<Self> {
+        if let Some(locale) = cli {
+            return Ok(locale);
+        }
+        if let Some(locale) = parse_configured(configured)? {
+            return Ok(locale);
+        }
+        Ok(Self::from_env().unwrap_or_default())
+    }
+}
+
+impl std::str::FromStr for Locale {
+    type Err = String;
+
+    fn from_str(tag: &str) -> std::result::Result<Self, Self::Err> {
+        Self::parse(tag).ok_or_else(|| crate::tr!(
+            "不支持的语言 {tag}，可选值: zh-CN, en-US",
+            "Unsupported language {tag}, possible values: zh-CN, en-US",
+        ))
+    }
+}
+
+/// 解析配置文件中的 `language`，为 `auto` 或空时返回 `None`
+///
+/// # 错误
+///
+/// 语言无效时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_configured(language: &str) -> Result<Option<Locale>> {
+    if language.is_empty() || language.eq_ignore_ascii_case(AUTO_LANGUAGE) {
+        return Ok(None);
+    }
+    Locale::parse(language).map(Some).ok_or_else(|| {
+        VideoToAudioError::InvalidInput(crate::tr!(
+            "不支持的界面语言 {language}，可选值: auto, zh-CN, en-US",
+            "Unsupported interface language {language}, possible values: auto, zh-CN, en-US",
+        ))
+    })
+}
+
+/// 设置当前的界面语言
+pub fn set_locale(locale: Locale) {
+    CURRENT_LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+/// 当前的界面语言
+pub fn locale() -> Locale {
+    match CURRENT_LOCALE.load(Ordering::Relaxed) {
+        value if value == Locale::EnUs as u8 => Locale::EnUs,
+        _ => Locale::ZhCn,
+    }
+}
+
+/// 按当前语言选择不需要格式化的文字
+pub fn text(zh: &'static str, en: &'static str) -> &'static str {
+    match locale() {
+        Locale::ZhCn => zh,
+        Locale::EnUs => en,
+    }
+}
+
+/// 按当前语言格式化中文或英文的格式字符串，返回 `String`
+///
+/// 两个格式字符串可以引用同名的变量，也可以共用其后的格式化参数：
+///
+/// ```
+/// use video2audio_rs::tr;
+///
+/// let count = 3;
+/// let message = tr!("找到 {count} 个视频文件", "Found {count} video files");
+/// assert!(message.contains('3'));
+/// ```
+#[macro_export]
+macro_rules! tr {
+    ($zh:literal, $en:literal $(, $($args:tt)*)?) => {
+        match $crate::i18n::locale() {
+            $crate::i18n::Locale::ZhCn => format!($zh $(, $($args)*)?),
+            $crate::i18n::Locale::EnUs => format!($en $(, $($args)*)?),
+        }
+    };
+}
+
+/// 命令行帮助的英文译文：(子命令, 参数名, 帮助文字)，子命令为空表示顶层参数
+const ARG_HELP_EN: &[(&str, &str, &str)] = &[
+    ("", "source_dir", "Source directory containing the video files"),
+    ("", "format", "Output audio format [possible values: mp3, aac, opus, m4b]"),
+    ("", "from_file", "Read the files to process from a list file (one path per line, - for stdin) instead of scanning a directory"),
+    ("", "retry_failed", "Convert only the files in a failure manifest (failed_files.json), by default with its format and into the directory containing it"),
+    ("", "output_dir", "Output directory for the audio files"),
+    ("", "batch_mode", "Batch mode: skip all interactive prompts"),
+    ("", "verbose", "Verbose output with more processing details"),
+    ("", "quiet", "Quiet mode: only show errors"),
+    ("", "lang", "Interface language: zh-CN or en-US (defaults to the language setting, then LANG)"),
+    ("", "trace", "Write diagnostic logs for scanning, planning and each conversion to stderr; FILTER uses the RUST_LOG syntax (default: video2audio_rs=debug)"),
+    ("", "no_color", "Disable colored output (the NO_COLOR environment variable works too)"),
+    ("", "progress", "Progress display: bar for terminal progress bars, json for JSON events on stdout (no other text)"),
+    ("", "jobs", "Number of files converted in parallel (defaults to the number of CPU cores)"),
+    ("", "threads_per_file", "Limit the threads used by each FFmpeg process; total CPU usage is about jobs × this value"),
+    ("", "nice", "Run FFmpeg at low priority to reduce the impact of background conversions"),
+    ("", "hwaccel", "Hardware acceleration for decoding the source videos, falls back to software decoding (default: none)"),
+    ("", "adaptive_jobs", "Dynamically reduce the number of concurrent encodes based on system load"),
+    ("", "backend", "Conversion backend (default: ffmpeg)"),
+    ("", "skip_existing", "Skip files whose output already exists"),
+    ("", "incremental", "Incremental mode: skip files whose output exists and is newer than the source"),
+    ("", "skip_space_check", "Skip the disk space check before starting"),
+    ("", "preflight", "Quickly check all inputs before converting (empty, unreadable, corrupt header or no audio stream); problem files are reported up front and skipped"),
+    ("", "preview", "First convert only the first 30 seconds of the first file and play it (ffplay or the default player), then convert all files once you're happy with the quality; outside a terminal only the preview is created"),
+    ("", "dry_run", "Only show the conversion plan: estimated output size per file and in total, plus a time estimate from a short test encode, without converting anything"),
+    ("", "order", "Processing order of the files (default: scan order)"),
+    ("", "largest_first", "Process the largest files first (the longest first with --order duration)"),
+    ("", "newest_first", "Process the most recently modified files first"),
+    ("", "include_audio_inputs", "Also scan audio-only files such as .m4a/.mka/.weba/.flac, to convert a mixed folder to one format"),
+    ("", "min_size", "Skip files smaller than this size, e.g. 10MB"),
+    ("", "max_size", "Skip files larger than this size, e.g. 4GB"),
+    ("", "min_duration", "Skip files shorter than this, e.g. 10s (requires ffprobe)"),
+    ("", "max_duration", "Skip files longer than this, e.g. 3h (requires ffprobe)"),
+    ("", "since", "Only process files modified after this time, e.g. 2024-01-01 or 7d"),
+    ("", "until", "Only process files modified before this time, e.g. 2024-06-30 or 30d"),
+    ("", "limit", "Only process the first N files (after filtering and sorting)"),
+    ("", "sample", "Process N randomly chosen files, useful for trying settings on a large library"),
+    ("", "pick", "Pick the files to convert from a list before converting, with keyword filtering (requires a terminal)"),
+    ("", "tui", "Show a full-screen terminal dashboard during batch conversion; p pauses/resumes, q cancels (requires the tui feature)"),
+    ("", "all_audio_tracks", "Write each audio track of multi-track sources to its own file, e.g. movie.track2.eng.mp3 (requires ffprobe)"),
+    ("", "cover", "Cover art to embed: an image path, frame:00:00:05 (a video frame) or attached (the source's cover); mp3 and m4b only"),
+    ("", "start", "Only convert from this time on, e.g. 00:05:00 or 300"),
+    ("", "end", "Only convert up to this time (from the start of the source), e.g. 01:10:00"),
+    ("", "split_chapters", "Write each chapter of sources with chapters to its own file, e.g. concert/01 - Intro.mp3, tagged with the chapter title (requires ffprobe)"),
+    ("", "normalize", "Normalize the output to a consistent loudness with EBU R128 (loudnorm); the target is the loudness_target setting"),
+    ("", "normalize_two_pass", "Analyze the whole file before normalizing; more accurate but decodes each file twice (implies --normalize)"),
+    ("", "tags", "Metadata tag to write to every output file, repeatable, e.g. --tag artist=\"Foo\" --tag album=\"Bar\""),
+    ("", "tag_pattern", "Parse tags from file names with a pattern such as \"{artist} - {title}\"; lists files that don't match"),
+    ("", "album_per_dir", "Treat each source directory as an album: the directory name becomes the album tag, track numbers follow file name order"),
+    ("", "no_metadata", "Don't copy the source's title, artist, date and other metadata to the output"),
+    ("", "audio_filter", "Append a custom FFmpeg audio filter chain, e.g. \"highpass=f=80,dynaudnorm\" (after silence trimming, before loudness normalization)"),
+    ("", "quality", "Quality preset: high is each format's default quality, standard balances quality and size, small is smallest (good for speech)"),
+    ("", "trim_silence", "Trim silence at the start and end (below -50dB, keeping 0.5s at each end), good for screen recordings"),
+    ("", "replay_gain", "Analyze each output after converting and write ReplayGain tags (R128 tags for Opus) without changing the audio"),
+    ("", "audio_track", "Extract audio track N (starting at 1); conversion fails if the source has no such track (requires ffprobe)"),
+    ("", "audio_lang", "Extract the audio track with this language tag, e.g. jpn or eng; conversion fails if there is none (requires ffprobe)"),
+    ("", "retries", "Maximum number of times a failed file is re-queued at the end of the batch"),
+    ("", "fallback_format", "Audio format used when retrying failed files, e.g. re-encode to MP3 after an AAC copy fails"),
+    ("", "fail_fast", "Abort the batch with a non-zero exit code as soon as any file fails"),
+    ("", "resume", "Resume an interrupted batch from the state journal (.v2a-state.json) in the output directory"),
+    ("", "playlist", "Write an audio_exports.m3u8 playlist of all outputs to the output directory after the batch"),
+    ("", "checksums", "Append each output's checksum to a manifest in the output directory (sha256 writes SHA256SUMS)"),
+    ("", "report", "Write each file's result after the batch; CSV for a .csv extension, JSON otherwise"),
+    ("", "log_file", "Write timestamped start, finish and failure entries with FFmpeg error output to a log file, regardless of console verbosity"),
+    ("", "log_max_size", "Rotate the log file to .1, .2, .3 backups once it grows past this size"),
+    ("", "inhibit_sleep", "Prevent the system from sleeping during the batch (on by default when interactive, must be given with --batch)"),
+    ("", "no_inhibit_sleep", "Don't prevent the system from sleeping during the batch"),
+    ("", "wait_for_lock", "Wait for another instance processing the output directory to finish instead of exiting"),
+    ("", "trash", "Move files deleted by clean and sync --delete-orphans to the system trash instead of deleting them permanently"),
+    ("", "notify", "Show a desktop notification with the number of succeeded and failed files after the batch"),
+    ("", "on_success", "Command to run after each successful conversion; supports {input}, {output} and {format}, e.g. 'rclone copy {output} remote:audio'"),
+    ("", "on_failure", "Command to run after each failed conversion; supports {input}, {format} and {error}"),
+    ("", "skip_converted", "Skip files the conversion history has already converted to the same format (matched by content, even if moved or renamed)"),
+    ("", "dedupe", "Convert only one of several identical sources: link hard-links the others' outputs to it, skip only notes them in the report"),
+    ("", "quarantine", "Quarantine sources found to be corrupt in the output directory's corrupt/ folder: move moves them, link creates symlinks; both are noted in the report"),
+    ("", "no_history", "Don't record this run's successful conversions in the conversion history"),
+    ("", "no_dir_config", "Ignore .video2audio.toml files in the source directory; all files use the same settings"),
+    ("", "watch", "Keep watching the source directory after processing existing files and convert new or modified videos"),
+    ("", "watch_debounce", "Seconds a file must stop changing before it is converted, to avoid converting recordings still being written"),
+    ("", "serve", "Run as an HTTP API service on the given address (e.g. 127.0.0.1:8080) accepting conversion jobs over REST"),
+    ("", "ipc", "Talk to a graphical front end over stdin/stdout with the JSON Lines protocol"),
+    ("", "list_formats", "Show all supported video and audio formats"),
+    ("", "profile", "Use a named profile from the profiles setting (format, bitrate, filters, output directory...); command line options take precedence"),
+    ("", "config_file", "Path of the configuration file"),
+    ("", "save_config", "Save the current settings as the defaults"),
+    ("", "migrate_config", "Convert the JSON configuration file (config.json by default) to config.toml next to it, renaming the original to .json.bak"),
+    ("download-ffmpeg", "force", "Download again even if already downloaded"),
+    ("merge", "source_dir", "Directory containing the videos to merge"),
+    ("merge", "output", "Path of the merged audio file; the format's extension is added if missing"),
+    ("merge", "format", "Output audio format [possible values: mp3, aac, opus, m4b]"),
+    ("tag", "files", "Audio files to tag"),
+    ("history", "search", "Only list records whose source or output path contains this text (case-insensitive)"),
+    ("history", "limit", "Maximum number of records to list, 0 for all"),
+    ("stats", "since", "Only count conversions after this time (e.g. 2024-01-01 or 30d)"),
+    ("explain", "code", "Error code such as E010 (case-insensitive); lists all error codes when omitted"),
+    ("clean", "source_dir", "Source video directory"),
+    ("clean", "output_dir", "Audio output directory to clean"),
+    ("clean", "format", "Output audio format; only files of this format count as orphans [possible values: mp3, aac, opus, m4b]"),
+    ("clean", "dry_run", "Only list the files that would be deleted without changing anything"),
+    ("verify", "source_dir", "Source video directory"),
+    ("verify", "output_dir", "Audio output directory mirroring the source directory"),
+    ("verify", "format", "Output audio format [possible values: mp3, aac, opus, m4b]"),
+    ("verify", "tolerance", "Allowed difference between output and source durations, e.g. 1s or 500ms"),
+    ("bench", "input", "Video file to test with; a segment from the start (or from --start) is converted"),
+    ("bench", "length", "Length of the test segment, e.g. 30s or 2m"),
+    ("bench", "formats", "Only test these formats, comma-separated [possible values: mp3, aac, opus, m4b]; all formats by default"),
+    ("sync", "source_dir", "Source video directory to mirror"),
+    ("sync", "output_dir", "Audio mirror output directory"),
+    ("sync", "format", "Output audio format [possible values: mp3, aac, opus, m4b]"),
+    ("sync", "delete_orphans", "Delete audio files whose source video no longer exists"),
+    ("sync", "dry_run", "Only show the files that would be converted and deleted without changing anything"),
+];
+
+/// 子命令说明的英文译文：(子命令, 简短说明, 详细说明)
+const COMMAND_ABOUT_EN: &[(&str, &str, &str)] = &[
+    ("", "High-performance batch video to audio converter", "Video2Audio-RS is a high-performance batch video to audio converter written in Rust.\nIt supports many video formats and converts files in parallel on all CPU cores."),
+    ("sync", "Sync an output directory as an audio mirror of the source directory", "Keeps the directory structure, converts only new or modified videos and can delete audio whose source no longer exists"),
+    ("merge", "Merge all videos in a directory into one audio file", "Concatenates the files in file name order and adds a chapter marker at the start of each source"),
+    ("tag", "Write tags and cover art to existing audio files", "Uses the global --tag, --tag-pattern and --cover; --tag-pattern parses the audio file names"),
+    ("verify", "Check the audio files in a mirror output directory", "Compares each output's duration with its source and decodes it fully to find truncated or corrupt files"),
+    ("bench", "Compare encoder settings", "Converts a short segment of a file with each format and quality preset and shows the encoding speed and output size side by side"),
+    ("clean", "Clean up an output directory", "Deletes orphaned audio whose source no longer exists, temporary files left by interrupted conversions and empty audio files"),
+    ("history", "Query the conversion history", "Lists the successful conversions recorded in the configuration directory, newest first"),
+    ("stats", "Show conversion history statistics", "Shows the number of files, total audio duration, formats used, average speed and monthly totals"),
+    ("explain", "Explain an error code", "Shows the common causes of an error code (e.g. E010) and how to fix them; lists all error codes when no code is given"),
+    ("config", "Show or change the configuration file", "Shows the effective settings and where they come from, or sets, edits or resets settings in the configuration file"),
+    ("daemon", "Run as a daemon", "Watches the directories in the watch_rules setting and exits cleanly on SIGTERM or Ctrl+C"),
+    ("download-ffmpeg", "Download a static FFmpeg build", "Downloads to the application data directory and verifies the SHA-256; used automatically when FFmpeg is not installed"),
+];
+
+/// 把命令行帮助替换为指定语言的译文
+///
+/// clap 的帮助文字来自 [`Args`](crate::config::Args) 上的中文属性，其他语言在此替换；
+/// 没有译文的参数保留中文帮助。可选值的中文说明来自枚举的文档注释，无法替换，
+/// 改为只在帮助末尾列出可选值。
+pub fn localize_command(mut command: clap::Command, locale: Locale) -> clap::Command {
+    if locale == Locale::ZhCn {
+        return command;
+    }
+    let localize = |name: &str, command: clap::Command| {
+        let mut command = command.mut_args(|arg| {
+            let id = arg.get_id().as_str();
+            let Some((_, _, help)) = ARG_HELP_EN.iter().find(|(command, arg_id, _)| *command == name && *arg_id == id)
+            else {
+                return arg;
+            };
+            // 开关参数也报告 true/false 两个可选值，只处理接收值的参数
+            let values: Vec<_> = match arg.get_action().takes_values() {
+                true => arg.get_possible_values().iter().map(|value| value.get_name().to_string()).collect(),
+                false => Vec::new(),
+            };
+            if values.is_empty() || help.contains("[possible values") {
+                arg.help(*help).hide_possible_values(!values.is_empty())
+            } else {
+                arg.help(format!("{help} [possible values: {}]", values.join(", "))).hide_possible_values(true)
+            }
+        });
+        if let Some((_, about, long_about)) = COMMAND_ABOUT_EN.iter().find(|(command, _, _)| *command == name) {
+            command = command.about(*about).long_about(*long_about);
+        }
+        command
+    };
+
+    let names: Vec<String> = command.get_subcommands().map(|sub| sub.get_name().to_string()).collect();
+    for name in names {
+        command = command.mut_subcommand(&name, |sub| localize(&name, sub));
+    }
+    localize("", command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::CommandFactory;
+
+    #[test]
+    fn test_parse_locale() {
+        assert_eq!(Locale::parse("zh-CN"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("zh_TW.UTF-8"), Some(Locale::ZhCn));
+        assert_eq!(Locale::parse("en_GB.UTF-8"), Some(Locale::EnUs));
+        assert_eq!(Locale::parse("EN"), Some(Locale::EnUs));
+        assert_eq!(Locale::parse("de_DE.UTF-8"), None);
+        assert_eq!("fr".parse::<Locale>().map_err(|e| e.contains("zh-CN")), Err(true));
+
+        assert_eq!(parse_configured("auto").unwrap(), None);
+        assert_eq!(parse_configured("en-US").unwrap(), Some(Locale::EnUs));
+        assert!(parse_configured("klingon").is_err());
+        assert_eq!(Locale::resolve(Some(Locale::EnUs), "zh-CN").unwrap(), Locale::EnUs);
+        assert_eq!(Locale::resolve(None, "en").unwrap(), Locale::EnUs);
+    }
+
+    #[test]
+    fn test_locale_from_args() {
+        let args = |args: &[&str]| Locale::from_args(args.iter().map(OsString::from));
+        assert_eq!(args(&["video2audio-rs", "--lang", "en", "--help"]), Some(Locale::EnUs));
+        assert_eq!(args(&["video2audio-rs", "-q", "--lang=zh-CN"]), Some(Locale::ZhCn));
+        assert_eq!(args(&["video2audio-rs", "--", "--lang", "en"]), None);
+        assert_eq!(args(&["video2audio-rs"]), None);
+    }
+
+    #[test]
+    fn test_english_help_covers_all_arguments() {
+        let command = localize_command(crate::config::Args::command(), Locale::EnUs);
+        let has_chinese = |text: String| text.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c));
+
+        let mut commands = vec![("", &command)];
+        commands.extend(command.get_subcommands().map(|sub| (sub.get_name(), sub)));
+        for (name, command) in commands {
+            assert!(!has_chinese(command.get_about().map(ToString::to_string).unwrap_or_default()), "{name}");
+            for arg in command.get_arguments() {
+                let help = arg.get_help().map(ToString::to_string).unwrap_or_default();
+                assert!(!has_chinese(help), "{name} {}", arg.get_id());
+                if arg.get_action().takes_values() && !arg.get_possible_values().is_empty() {
+                    assert!(arg.is_hide_possible_values_set(), "{name} {}", arg.get_id());
+                }
+            }
+        }
+    }
+}
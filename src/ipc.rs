@@ -0,0 +1,360 @@
+//! # 标准输入输出 IPC 模块
+//!
+//! 为 Tauri/Electron 等图形前端提供稳定的机器协议：从标准输入逐行读取
+//! JSON 命令，向标准输出逐行写入 JSON 事件 (JSON Lines)，前端无需解析
+//! 面向终端的中文输出。错误详情仍写入标准错误流，不会混入事件流。
+//!
+//! ## 命令
+//!
+//! ```text
+//! {"cmd": "formats"}
+//! {"cmd": "scan", "source": "/videos"}
+//! {"cmd": "convert", "source": "/videos", "format": "mp3", "output_dir": "/audio"}
+//! {"cmd": "cancel"}
+//! {"cmd": "shutdown"}
+//! ```
+//!
+//! ## 事件
+//!
+//! 启动时输出 `ready`，之后每条命令产生一个或多个事件：`formats`、`discovered`、
+//! `started`、`progress`（每个文件一条）、`finished`，出错时为 `error`。
+
+use crate::audio_format::AudioFormat;
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::FileProcessor;
+use crate::server::resolve_job_files;
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 协议版本，协议发生不兼容变化时递增
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// 等待命令的超时，决定检查 Ctrl+C 的频率
+const COMMAND_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 前端发送的命令
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum IpcCommand {
+    /// 列出支持的音频格式
+    Formats,
+
+    /// 扫描源目录中的视频文件
+    Scan {
+        /// 源目录
+        source: PathBuf,
+    },
+
+    /// 转换视频文件或目录
+    Convert {
+        /// 源视频文件或目录
+        source: PathBuf,
+        /// 目标音频格式 (mp3/aac/opus/m4b)
+        format: String,
+        /// 输出目录，未设置时为源目录下的 audio_exports
+        #[serde(default)]
+        output_dir: Option<PathBuf>,
+    },
+
+    /// 取消正在进行的转换
+    Cancel,
+
+    /// 结束会话
+    Shutdown,
+}
+
+/// 输出给前端的事件
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum IpcEvent {
+    /// 会话已就绪
+    Ready {
+        /// 协议版本
+        protocol: u32,
+        /// 程序版本
+        version: &'static str,
+    },
+
+    /// 支持的音频格式
+    Formats {
+        /// 格式名称（扩展名）列表
+        formats: Vec<&'static str>,
+    },
+
+    /// 扫描结果
+    Discovered {
+        /// 源路径
+        source: PathBuf,
+        /// 找到的视频文件
+        files: Vec<PathBuf>,
+    },
+
+    /// 转换开始
+    Started {
+        /// 文件总数
+        total: usize,
+        /// 输出目录
+        output_dir: PathBuf,
+    },
+
+    /// 单个文件处理完成
+    Progress {
+        /// 源文件
+        source: PathBuf,
+        /// 成功时的输出文件
+        output: Option<PathBuf>,
+        /// 失败时的错误信息
+        error: Option<String>,
+        /// 已处理的文件数
+        completed: usize,
+        /// 文件总数
+        total: usize,
+    },
+
+    /// 转换结束
+    Finished {
+        /// 成功的文件数
+        succeeded: usize,
+        /// 失败的文件数
+        failed: usize,
+        /// 是否被取消
+        cancelled: bool,
+    },
+
+    /// 命令无效或执行失败
+    Error {
+        /// 错误信息
+        message: String,
+    },
+}
+
+/// IPC 会话
+///
+/// 在独立线程中读取命令，在当前线程中依次执行并输出事件。
+/// `cancel` 命令由读取线程直接处理，因此可以中断正在进行的转换。
+pub struct IpcSession {
+    processor: FileProcessor,
+}
+
+impl IpcSession {
+    /// 创建 IPC 会话
+    pub fn new(processor: FileProcessor) -> Self {
+        Self { processor }
+    }
+
+    /// 运行会话，直到输入结束、收到 `shutdown` 命令或 `cancel_token` 被取消
+    ///
+    /// # 参数
+    ///
+    /// * `reader` - 命令输入，通常为标准输入
+    /// * `writer` - 事件输出，通常为标准输出
+    /// * `cancel_token` - 进程级取消令牌，取消后结束会话
+    ///
+    /// # 错误
+    ///
+    /// 当事件无法写入（例如前端已关闭管道）时返回错误
+    pub fn run<R, W>(&self, reader: R, mut writer: W, cancel_token: &CancellationToken) -> Result<()>
+    where
+        R: BufRead + Send + 'static,
+        W: Write,
+    {
+        let current_job = Arc::new(Mutex::new(cancel_token.child_token()));
+        let receiver = spawn_command_reader(reader, Arc::clone(&current_job));
+
+        emit(&mut writer, &IpcEvent::Ready {
+            protocol: PROTOCOL_VERSION,
+            version: env!("CARGO_PKG_VERSION"),
+        })?;
+
+        while !cancel_token.is_cancelled() {
+            let command = match receiver.recv_timeout(COMMAND_POLL_INTERVAL) {
+                Ok(command) => command,
+                Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            };
+
+            match command {
+                Ok(IpcCommand::Shutdown) => break,
+                // 取消已由读取线程处理
+                Ok(IpcCommand::Cancel) => {}
+                Ok(command) => {
+                    let job_token = cancel_token.child_token();
+                    *lock(&current_job) = job_token.clone();
+                    self.execute(command, &mut writer, &job_token)?;
+                }
+                Err(message) => emit(&mut writer, &IpcEvent::Error { message })?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// 执行一条命令并输出事件
+    fn execute<W: Write>(
+        &self,
+        command: IpcCommand,
+        writer: &mut W,
+        job_token: &CancellationToken,
+    ) -> Result<()> {
+        match command {
+            IpcCommand::Formats => emit(writer, &IpcEvent::Formats {
+                formats: AudioFormat::all_formats().into_iter().map(|f| f.extension()).collect(),
+            }),
+            IpcCommand::Scan { source } => match self.processor.find_video_files(&source) {
+                Ok(files) => emit(writer, &IpcEvent::Discovered { source, files }),
+                Err(e) => emit(writer, &IpcEvent::Error { message: e.to_string() }),
+            },
+            IpcCommand::Convert { source, format, output_dir } => {
+                let prepared = AudioFormat::from_user_input(&format).and_then(|format| {
+                    resolve_job_files(&self.processor, &source, output_dir.as_deref())
+                        .map(|(files, output_dir, lock)| (format, files, output_dir, lock))
+                });
+                let (format, files, output_dir, _output_lock) = match prepared {
+                    Ok(prepared) => prepared,
+                    Err(e) => return emit(writer, &IpcEvent::Error { message: e.to_string() }),
+                };
+
+                emit(writer, &IpcEvent::Discovered { source, files: files.clone() })?;
+                emit(writer, &IpcEvent::Started { total: files.len(), output_dir: output_dir.clone() })?;
+
+                // 回调中无法直接返回错误，记录第一个写入错误，结束后再返回
+                let mut write_error = None;
+                let (succeeded, failed) = self.processor.batch_convert_with_results(
+                    &files,
+                    &output_dir,
+                    format,
+                    job_token,
+                    |source, result, completed, total| {
+                        let event = IpcEvent::Progress {
+                            source: source.to_path_buf(),
+                            output: result.as_ref().ok().cloned(),
+                            error: result.as_ref().err().map(|e| e.to_string()),
+                            completed,
+                            total,
+                        };
+                        if write_error.is_none() {
+                            if let Err(e) = emit(writer, &event) {
+                                // 前端已断开，没有继续转换的意义
+                                job_token.cancel();
+                                write_error = Some(e);
+                            }
+                        }
+                    },
+                );
+                if let Some(e) = write_error {
+                    return Err(e);
+                }
+
+                emit(writer, &IpcEvent::Finished {
+                    succeeded,
+                    failed,
+                    cancelled: job_token.is_cancelled(),
+                })
+            }
+            IpcCommand::Cancel | IpcCommand::Shutdown => Ok(()),
+        }
+    }
+}
+
+/// 在后台线程中逐行读取并解析命令
+///
+/// `cancel` 命令会立即取消当前任务的令牌，然后与其他命令一样转发给会话线程。
+fn spawn_command_reader<R>(
+    reader: R,
+    current_job: Arc<Mutex<CancellationToken>>,
+) -> mpsc::Receiver<std::result::Result<IpcCommand, String>>
+where
+    R: BufRead + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in reader.lines() {
+            let Ok(line) = line else { break };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let command = serde_json::from_str::<IpcCommand>(&line)
+                .map_err(|e| tr!("无法解析命令: {e}", "Cannot parse the command: {e}"));
+            if command == Ok(IpcCommand::Cancel) {
+                lock(&current_job).cancel();
+            }
+            if sender.send(command).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// 输出一个事件（单行 JSON）
+fn emit<W: Write>(writer: &mut W, event: &IpcEvent) -> Result<()> {
+    let line = serde_json::to_string(event)
+        .map_err(|e| VideoToAudioError::InvalidInput(tr!("事件序列化失败: {e}", "Failed to serialize the event: {e}")))?;
+    writeln!(writer, "{line}")?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn lock(job: &Mutex<CancellationToken>) -> std::sync::MutexGuard<'_, CancellationToken> {
+    job.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use std::io::Cursor;
+    use tempfile::TempDir;
+
+    fn run_session(input: String) -> Vec<Value> {
+        let mut output = Vec::new();
+        IpcSession::new(FileProcessor::new())
+            .run(Cursor::new(input.into_bytes()), &mut output, &CancellationToken::new())
+            .unwrap();
+        String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_formats_and_invalid_command() {
+        let events = run_session("{\"cmd\":\"formats\"}\nnot json\n".to_string());
+
+        assert_eq!(events[0]["event"], "ready");
+        assert_eq!(events[1]["event"], "formats");
+        assert_eq!(events[1]["formats"].as_array().unwrap().len(), AudioFormat::all_formats().len());
+        assert_eq!(events[2]["event"], "error");
+    }
+
+    #[test]
+    fn test_convert_emits_progress_per_file() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::write(temp_dir.path().join("a.mp4"), "fake").unwrap();
+        std::fs::write(temp_dir.path().join("b.mkv"), "fake").unwrap();
+        let output_dir = temp_dir.path().join("out");
+
+        let command = serde_json::json!({
+            "cmd": "convert",
+            "source": temp_dir.path(),
+            "format": "mp3",
+            "output_dir": output_dir,
+        });
+        let events = run_session(format!("{command}\n{{\"cmd\":\"shutdown\"}}\n{{\"cmd\":\"formats\"}}\n"));
+        let kinds: Vec<&str> = events.iter().map(|e| e["event"].as_str().unwrap()).collect();
+
+        // shutdown 之后的命令不再执行
+        assert_eq!(kinds, ["ready", "discovered", "started", "progress", "progress", "finished"]);
+        assert_eq!(events[4]["completed"], 2);
+        assert_eq!(events[5]["failed"], 2);
+        assert_eq!(events[5]["cancelled"], false);
+    }
+}
@@ -0,0 +1,289 @@
+//! # 批处理状态日志模块
+//!
+//! 将批处理的计划文件、已完成的输出和失败记录持久化到输出目录中的
+//! `.v2a-state.json`，使被重启或崩溃中断的长时间批处理可以通过
+//! `--resume` 从中断处继续。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// 状态日志文件名
+pub const JOURNAL_FILE_NAME: &str = ".v2a-state.json";
+
+/// 成功转换这么多个文件后写回一次状态日志，参见 [`BatchJournal::save_if_due`]
+pub const SAVE_EVERY_FILES: usize = 25;
+
+/// 距上次写入超过这么长时间后写回一次状态日志，参见 [`BatchJournal::save_if_due`]
+pub const SAVE_EVERY: Duration = Duration::from_secs(10);
+
+/// 批处理状态日志
+///
+/// 记录一次批处理计划处理的全部源文件，以及每个文件的完成或失败状态。
+/// 上万个文件的批处理每个文件都重写整个日志的代价过高，因此成功的记录按文件数或时间间隔
+/// 批量写回，失败、取消和批处理结束时立即写回；进程崩溃时最多重做最近一批已完成的文件。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BatchJournal {
+    /// 目标音频格式（扩展名）
+    format: String,
+
+    /// 计划处理的源文件（保持原始顺序）
+    planned: Vec<PathBuf>,
+
+    /// 已完成的文件：源文件 -> 输出文件
+    completed: BTreeMap<PathBuf, PathBuf>,
+
+    /// 失败的文件：源文件 -> 错误信息
+    failed: BTreeMap<PathBuf, String>,
+
+    /// 日志文件路径（不序列化）
+    #[serde(skip)]
+    path: PathBuf,
+
+    /// 上次写回后新增的记录数（不序列化）
+    #[serde(skip)]
+    unsaved: usize,
+
+    /// 上次写回的时间（不序列化）
+    #[serde(skip)]
+    saved_at: Option<Instant>,
+}
+
+impl BatchJournal {
+    /// 获取输出目录中的状态日志路径
+    pub fn path_in(output_dir: &Path) -> PathBuf {
+        output_dir.join(JOURNAL_FILE_NAME)
+    }
+
+    /// 为新的批处理创建状态日志
+    ///
+    /// # 参数
+    ///
+    /// * `output_dir` - 输出目录，日志文件保存在其中
+    /// * `format` - 目标音频格式
+    /// * `planned` - 计划处理的源文件列表
+    pub fn new(output_dir: &Path, format: AudioFormat, planned: &[PathBuf]) -> Self {
+        Self {
+            format: format.extension().to_string(),
+            planned: planned.to_vec(),
+            completed: BTreeMap::new(),
+            failed: BTreeMap::new(),
+            path: Self::path_in(output_dir),
+            unsaved: 0,
+            saved_at: None,
+        }
+    }
+
+    /// 从输出目录加载状态日志
+    ///
+    /// # 返回值
+    ///
+    /// 日志存在时返回 `Some`，不存在时返回 `None`
+    ///
+    /// # 错误
+    ///
+    /// 当日志文件无法读取或格式错误时返回错误
+    pub fn load(output_dir: &Path) -> Result<Option<Self>> {
+        let path = Self::path_in(output_dir);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path).context(text("读取状态日志", "read state journal"), &path)?;
+        let mut journal: BatchJournal = serde_json::from_str(&content)
+            .map_err(|e| VideoToAudioError::InvalidInput(
+                tr!("状态日志格式错误: {e}", "Malformed state journal: {e}")
+            ))?;
+        journal.path = path;
+        Ok(Some(journal))
+    }
+
+    /// 打开用于记录本次批处理的状态日志
+    ///
+    /// 如果输出目录中已有同格式的日志，则保留其中的完成记录并补充新计划的文件；
+    /// 否则创建新的日志。
+    pub fn open(output_dir: &Path, format: AudioFormat, planned: &[PathBuf]) -> Result<Self> {
+        match Self::load(output_dir)? {
+            Some(mut journal) if journal.format() == Some(format) => {
+                let mut known: HashSet<&Path> = journal.planned.iter().map(PathBuf::as_path).collect();
+                let added: Vec<PathBuf> = planned.iter().filter(|source| known.insert(source)).cloned().collect();
+                journal.planned.extend(added);
+                Ok(journal)
+            }
+            _ => Ok(Self::new(output_dir, format, planned)),
+        }
+    }
+
+    /// 获取日志记录的目标音频格式
+    pub fn format(&self) -> Option<AudioFormat> {
+        AudioFormat::from_user_input(&self.format).ok()
+    }
+
+    /// 获取计划处理的源文件
+    pub fn planned(&self) -> &[PathBuf] {
+        &self.planned
+    }
+
+    /// 获取尚未完成的源文件（包括之前失败的文件），保持计划顺序
+    pub fn pending(&self) -> Vec<PathBuf> {
+        self.planned
+            .iter()
+            .filter(|source| !self.completed.contains_key(*source))
+            .cloned()
+            .collect()
+    }
+
+    /// 获取已完成的文件数
+    pub fn completed_count(&self) -> usize {
+        self.completed.len()
+    }
+
+    /// 获取失败记录
+    pub fn failures(&self) -> &BTreeMap<PathBuf, String> {
+        &self.failed
+    }
+
+    /// 检查源文件是否已完成
+    pub fn is_completed(&self, source: &Path) -> bool {
+        self.completed.contains_key(source)
+    }
+
+    /// 记录转换成功的文件
+    pub fn record_success(&mut self, source: &Path, output: &Path) {
+        self.failed.remove(source);
+        self.completed.insert(source.to_path_buf(), output.to_path_buf());
+        self.unsaved += 1;
+    }
+
+    /// 记录转换失败的文件
+    pub fn record_failure(&mut self, source: &Path, error: &str) {
+        self.failed.insert(source.to_path_buf(), error.to_string());
+        self.unsaved += 1;
+    }
+
+    /// 上次写回后新增了 [`SAVE_EVERY_FILES`] 条记录，或距上次写回超过 [`SAVE_EVERY`] 时写回磁盘
+    ///
+    /// # 返回值
+    ///
+    /// 本次是否写回了磁盘
+    pub fn save_if_due(&mut self) -> Result<bool> {
+        let due = self.unsaved >= SAVE_EVERY_FILES
+            || (self.unsaved > 0 && self.saved_at.is_none_or(|saved_at| saved_at.elapsed() >= SAVE_EVERY));
+        if due {
+            self.save()?;
+        }
+        Ok(due)
+    }
+
+    /// 将日志写回磁盘
+    ///
+    /// 先写入临时文件再重命名，避免写入过程中断导致日志损坏。
+    pub fn save(&mut self) -> Result<()> {
+        let content = serde_json::to_string(self)
+            .map_err(|e| VideoToAudioError::InvalidInput(
+                tr!("状态日志序列化失败: {e}", "Failed to serialize the state journal: {e}")
+            ))?;
+
+        let temp_path = self.path.with_extension("json.tmp");
+        fs::write(&temp_path, content).context(text("写入状态日志", "write state journal"), &temp_path)?;
+        fs::rename(&temp_path, &self.path).context(text("写入状态日志", "write state journal"), &self.path)?;
+        self.unsaved = 0;
+        self.saved_at = Some(Instant::now());
+        Ok(())
+    }
+
+    /// 删除日志文件
+    ///
+    /// 批处理全部成功完成后调用，下次运行将从头开始。
+    pub fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path).context(text("删除状态日志", "remove state journal"), &self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pending_excludes_completed() {
+        let temp_dir = TempDir::new().unwrap();
+        let planned = vec![PathBuf::from("a.mp4"), PathBuf::from("b.mp4"), PathBuf::from("c.mp4")];
+        let mut journal = BatchJournal::new(temp_dir.path(), AudioFormat::Mp3, &planned);
+
+        journal.record_success(Path::new("b.mp4"), Path::new("out/b.mp3"));
+        journal.record_failure(Path::new("c.mp4"), "转换失败");
+
+        assert_eq!(journal.pending(), vec![PathBuf::from("a.mp4"), PathBuf::from("c.mp4")]);
+        assert!(journal.is_completed(Path::new("b.mp4")));
+        assert_eq!(journal.failures().len(), 1);
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let planned = vec![PathBuf::from("a.mp4"), PathBuf::from("b.mp4")];
+        let mut journal = BatchJournal::new(temp_dir.path(), AudioFormat::Opus, &planned);
+        journal.record_success(Path::new("a.mp4"), Path::new("out/a.opus"));
+        journal.save().unwrap();
+
+        let loaded = BatchJournal::load(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(loaded.format(), Some(AudioFormat::Opus));
+        assert_eq!(loaded.planned(), planned.as_slice());
+        assert_eq!(loaded.pending(), vec![PathBuf::from("b.mp4")]);
+
+        loaded.remove().unwrap();
+        assert!(BatchJournal::load(temp_dir.path()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_success_clears_previous_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let planned = vec![PathBuf::from("a.mp4")];
+        let mut journal = BatchJournal::new(temp_dir.path(), AudioFormat::Mp3, &planned);
+
+        journal.record_failure(Path::new("a.mp4"), "网络中断");
+        journal.record_success(Path::new("a.mp4"), Path::new("out/a.mp3"));
+
+        assert!(journal.failures().is_empty());
+        assert!(journal.pending().is_empty());
+    }
+
+    #[test]
+    fn test_open_adds_each_new_file_once() {
+        let temp_dir = TempDir::new().unwrap();
+        let planned = vec![PathBuf::from("a.mp4"), PathBuf::from("b.mp4")];
+        BatchJournal::new(temp_dir.path(), AudioFormat::Mp3, &planned).save().unwrap();
+
+        let replanned = [PathBuf::from("b.mp4"), PathBuf::from("c.mp4"), PathBuf::from("c.mp4"), PathBuf::from("a.mp4")];
+        let journal = BatchJournal::open(temp_dir.path(), AudioFormat::Mp3, &replanned).unwrap();
+        assert_eq!(journal.planned(), [PathBuf::from("a.mp4"), PathBuf::from("b.mp4"), PathBuf::from("c.mp4")]);
+    }
+
+    #[test]
+    fn test_save_if_due_batches_successes() {
+        let temp_dir = TempDir::new().unwrap();
+        let planned: Vec<PathBuf> = (0..=SAVE_EVERY_FILES).map(|i| PathBuf::from(format!("{i}.mp4"))).collect();
+        let mut journal = BatchJournal::new(temp_dir.path(), AudioFormat::Mp3, &planned);
+        assert!(!journal.save_if_due().unwrap());
+
+        // 第一条记录立即写回，之后攒够 SAVE_EVERY_FILES 条再写回
+        journal.record_success(&planned[0], Path::new("out/0.mp3"));
+        assert!(journal.save_if_due().unwrap());
+        for (i, source) in planned[1..].iter().enumerate() {
+            journal.record_success(source, Path::new("out.mp3"));
+            assert_eq!(journal.save_if_due().unwrap(), i + 1 == SAVE_EVERY_FILES);
+        }
+        let loaded = BatchJournal::load(temp_dir.path()).unwrap().unwrap();
+        assert!(loaded.pending().is_empty());
+    }
+}
@@ -0,0 +1,213 @@
+//! # 键盘控制模块
+//!
+//! 在终端中交互运行批量转换时，按键控制批处理的进行：
+//!
+//! - `p` 暂停：不再开始新文件，正在转换的文件照常完成
+//! - `r` 恢复
+//! - `q` 停止：正在转换的文件完成后结束，之后可以用 `--resume` 继续
+//!
+//! 读取按键时关闭终端的行缓冲和回显，但保留中断信号，Ctrl+C 仍然取消批处理。
+//! 目前只支持 Unix 终端，其他平台上不读取按键。
+
+use crate::cancellation::CancellationToken;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// 检查是否需要结束读取的间隔
+const KEY_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 按键对应的操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyAction {
+    /// 暂停开始新文件
+    Pause,
+
+    /// 恢复暂停的批处理
+    Resume,
+
+    /// 正在转换的文件完成后结束批处理
+    Stop,
+}
+
+impl KeyAction {
+    /// 按键对应的操作，不区分大小写；其他按键返回 `None`
+    pub fn from_key(key: char) -> Option<Self> {
+        match key.to_ascii_lowercase() {
+            'p' => Some(Self::Pause),
+            'r' => Some(Self::Resume),
+            'q' => Some(Self::Stop),
+            _ => None,
+        }
+    }
+
+    /// 把操作应用到取消令牌
+    pub fn apply(self, cancel_token: &CancellationToken) {
+        match self {
+            Self::Pause => cancel_token.pause(),
+            Self::Resume => cancel_token.resume(),
+            Self::Stop => cancel_token.stop(),
+        }
+    }
+}
+
+/// 在后台线程中读取按键，释放（drop）时停止读取并恢复终端设置
+#[derive(Debug)]
+pub struct KeyboardControl {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl KeyboardControl {
+    /// 开始读取按键
+    ///
+    /// 按键的操作直接作用于取消令牌，然后调用 `on_action`（如显示提示）。
+    ///
+    /// # 返回值
+    ///
+    /// 标准输入不是终端或当前平台不支持时返回 `None`
+    pub fn start(
+        cancel_token: CancellationToken,
+        on_action: impl Fn(KeyAction) + Send + 'static,
+    ) -> Option<Self> {
+        let input = platform::RawInput::enable()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+        let thread = thread::spawn(move || {
+            while !thread_stop.load(Ordering::SeqCst) {
+                match input.read_key(KEY_POLL_INTERVAL) {
+                    Ok(Some(key)) => {
+                        if let Some(action) = KeyAction::from_key(key) {
+                            action.apply(&cancel_token);
+                            on_action(action);
+                        }
+                    }
+                    Ok(None) => {}
+                    // 标准输入已关闭
+                    Err(_) => break,
+                }
+            }
+            // input 在这里释放，恢复终端设置
+        });
+        Some(Self { stop, thread: Some(thread) })
+    }
+}
+
+impl Drop for KeyboardControl {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(unix)]
+mod platform {
+    use std::io;
+    use std::time::Duration;
+
+    /// 关闭了行缓冲和回显的标准输入，释放时恢复原来的终端设置
+    pub struct RawInput {
+        original: libc::termios,
+    }
+
+    impl RawInput {
+        pub fn enable() -> Option<Self> {
+            let fd = libc::STDIN_FILENO;
+            // SAFETY: isatty 只读取文件描述符的状态
+            if unsafe { libc::isatty(fd) } != 1 {
+                return None;
+            }
+            let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+            // SAFETY: termios 是可写的 termios 结构
+            if unsafe { libc::tcgetattr(fd, &mut termios) } != 0 {
+                return None;
+            }
+            let original = termios;
+            // 保留 ISIG，Ctrl+C 仍然发送中断信号
+            termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+            termios.c_cc[libc::VMIN] = 1;
+            termios.c_cc[libc::VTIME] = 0;
+            // SAFETY: termios 是由 tcgetattr 读取后修改的有效结构
+            if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &termios) } != 0 {
+                return None;
+            }
+            Some(Self { original })
+        }
+
+        /// 等待最多 `timeout` 读取一个按键，超时返回 `None`，标准输入关闭时返回错误
+        pub fn read_key(&self, timeout: Duration) -> io::Result<Option<char>> {
+            let mut pollfd = libc::pollfd { fd: libc::STDIN_FILENO, events: libc::POLLIN, revents: 0 };
+            let timeout = i32::try_from(timeout.as_millis()).unwrap_or(i32::MAX);
+            // SAFETY: pollfd 是有效的可写结构，数量为 1
+            let ready = unsafe { libc::poll(&mut pollfd, 1, timeout) };
+            if ready < 0 {
+                let error = io::Error::last_os_error();
+                // 收到信号（如 Ctrl+C）时 poll 提前返回
+                return if error.kind() == io::ErrorKind::Interrupted { Ok(None) } else { Err(error) };
+            }
+            if ready == 0 {
+                return Ok(None);
+            }
+            let mut byte = 0u8;
+            // SAFETY: byte 是可写的 1 字节缓冲区
+            let read = unsafe { libc::read(libc::STDIN_FILENO, (&mut byte as *mut u8).cast(), 1) };
+            match read {
+                1 => Ok(Some(char::from(byte))),
+                0 => Err(io::ErrorKind::UnexpectedEof.into()),
+                _ => Err(io::Error::last_os_error()),
+            }
+        }
+    }
+
+    impl Drop for RawInput {
+        fn drop(&mut self) {
+            // SAFETY: original 是 enable 时读取的终端设置
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+            }
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod platform {
+    use std::io;
+    use std::time::Duration;
+
+    /// 当前平台不支持读取单个按键
+    pub struct RawInput;
+
+    impl RawInput {
+        pub fn enable() -> Option<Self> {
+            None
+        }
+
+        pub fn read_key(&self, _timeout: Duration) -> io::Result<Option<char>> {
+            Ok(None)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_actions() {
+        assert_eq!(KeyAction::from_key('p'), Some(KeyAction::Pause));
+        assert_eq!(KeyAction::from_key('R'), Some(KeyAction::Resume));
+        assert_eq!(KeyAction::from_key('q'), Some(KeyAction::Stop));
+        assert_eq!(KeyAction::from_key('x'), None);
+
+        let token = CancellationToken::new();
+        KeyAction::Pause.apply(&token);
+        assert!(token.is_paused());
+        KeyAction::Resume.apply(&token);
+        assert!(!token.is_paused());
+        KeyAction::Stop.apply(&token);
+        assert!(token.is_stopped() && !token.is_cancelled());
+    }
+}
@@ -5,9 +5,64 @@
 //! ## 主要模块
 //! 
 //! - [`audio_format`] - 音频格式定义和处理
+//! - `async_processor` - 基于 tokio 的异步转换接口（需启用 `async` 特性）
+//! - [`backend`] - 可替换的转换后端
+//! - [`bench`] - 比较各格式和音质预设的编码速度和输出大小
 //! - [`file_processor`] - 文件处理和转换逻辑
+//! - [`options`] - 单次转换的选项（码率、采样率、滤镜等）
 //! - [`user_interface`] - 用户交互界面
 //! - [`error`] - 错误处理类型定义
+//! - [`estimate`] - 估算输出大小和转换耗时，用于 `--dry-run` 和转换计划
+//! - [`error_codes`] - 固定的错误代码及其原因和解决方法
+//! - [`event`] - 批量转换的结构化事件
+//! - [`failures`] - 批处理失败文件的清单，供 `--retry-failed` 重新转换
+//! - [`capabilities`] - FFmpeg 编码器能力检测
+//! - [`checksums`] - 输出文件的校验和清单
+//! - [`clean`] - 清理输出目录中的孤立音频、临时文件和空文件
+//! - [`cancellation`] - 批量转换的取消控制
+//! - [`disk_space`] - 批处理开始前的磁盘空间检查
+//! - `ffi` - C 语言接口（需启用 `ffi` 特性）
+//! - [`history`] - 记录成功转换的转换历史
+//! - [`hooks`] - 每个文件转换成功或失败后执行的命令
+//! - [`i18n`] - 界面语言（简体中文和英文）
+//! - [`ipc`] - 面向图形前端的 JSON Lines 标准输入输出协议
+//! - [`journal`] - 可恢复批处理的状态日志
+//! - [`keyboard`] - 批量转换期间按键暂停、恢复和停止
+//! - [`lock`] - 防止多个实例同时处理同一输出目录的锁文件
+//! - [`log_file`] - 按大小轮转的转换日志文件
+//! - [`loudness`] - 基于 EBU R128 的响度标准化和增益标签
+//! - [`merge`] - 把多个视频的音频拼接为一个带章节标记的文件
+//! - [`notification`] - 批处理结束后的桌面通知
+//! - [`playlist`] - 批处理输出文件的 M3U 播放列表
+//! - [`power`] - 批处理期间阻止系统休眠
+//! - [`preflight`] - 转换开始前对全部输入文件的快速预检
+//! - [`preview`] - 批量转换前转换并试听第一个文件开头的一段
+//! - [`probe`] - 通过 ffprobe 读取媒体时长、音轨和章节
+//! - [`progress`] - 批量转换中每个文件的进度报告
+//! - [`quarantine`] - 把损坏的源文件隔离到 `corrupt/` 文件夹
+//! - [`managed_ffmpeg`] - 下载到应用数据目录中的托管 FFmpeg（下载功能需启用 `ffmpeg-download` 特性）
+//! - [`dedupe`] - 内容相同的源文件只转换一次
+//! - [`dir_config`] - 源目录中 `.video2audio.toml` 的按目录配置覆盖
+//! - [`daemon`] - 按配置规则运行的常驻转码服务
+//! - [`scheduler`] - 按任务类型限制并发的调度器
+//! - [`remote`] - http(s)/rtmp 等远程地址输入
+//! - [`report`] - 批处理结束后的 JSON/CSV 转换报告
+//! - [`selection`] - 文件的筛选、抽样和处理顺序
+//! - [`style`] - 终端输出的成功、失败和警告配色
+//! - [`server`] - 本机 HTTP API 服务和任务队列
+//! - [`sync`] - 源目录与音频输出目录的镜像同步
+//! - [`tag_pattern`] - 从文件名解析元数据标签的模板
+//! - [`tagging`] - 转换完成后写入或修正标签和封面（不经过 FFmpeg 的原生写入需启用 `lofty` 特性）
+//! - `symphonia_backend` - 不依赖 FFmpeg 的纯 Rust 音频流提取后端（需启用 `symphonia` 特性）
+//! - `gstreamer_backend` - 在进程内通过 GStreamer 转换的后端（需启用 `gstreamer` 特性）
+//! - `tui` - 批量转换的终端仪表盘（需启用 `tui` 特性）
+//! - [`verify`] - 检查已有输出文件的完整性
+//! - [`watcher`] - 监视源目录并自动转换新视频
+//! 
+//! ## 诊断日志
+//! 
+//! 扫描、批处理、输出计划和每个文件的转换分别在 `scan`、`batch`、`plan` 和 `convert`
+//! span 中执行，诊断信息通过 [`tracing`](https://docs.rs/tracing) 交给调用方安装的 subscriber。
 //! 
 //! ## 使用示例
 //! 
@@ -24,14 +79,91 @@
 //! ```
 
 pub mod audio_format;
+#[cfg(feature = "async")]
+pub mod async_processor;
+pub mod backend;
+pub mod bench;
+pub mod cancellation;
+pub mod capabilities;
+pub mod checksums;
+pub mod clean;
 pub mod config;
+pub mod daemon;
+pub mod dedupe;
+pub mod dir_config;
+pub mod disk_space;
 pub mod error;
+pub mod estimate;
+pub mod error_codes;
+pub mod event;
+pub mod failures;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod file_processor;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod ipc;
+pub mod journal;
+pub mod keyboard;
+pub mod lock;
+pub mod log_file;
+pub mod loudness;
+pub mod managed_ffmpeg;
+pub mod merge;
+pub mod notification;
+pub mod options;
+pub mod playlist;
+pub mod power;
+pub mod preflight;
+pub mod preview;
+pub mod probe;
+pub mod progress;
+pub mod quarantine;
+pub mod remote;
+pub mod report;
+pub mod scheduler;
+pub mod selection;
+pub mod server;
+pub mod style;
+pub mod sync;
+pub mod tag_pattern;
+pub mod tagging;
+#[cfg(feature = "gstreamer")]
+pub mod gstreamer_backend;
+#[cfg(feature = "symphonia")]
+pub mod symphonia_backend;
+#[cfg(feature = "tui")]
+pub mod tui;
 pub mod user_interface;
+pub mod verify;
+pub mod watcher;
 
 // 重新导出主要类型，方便外部使用
 pub use audio_format::AudioFormat;
-pub use config::{Args, Config, RuntimeConfig};
+pub use backend::{BackendKind, ConversionBackend};
+pub use cancellation::CancellationToken;
+pub use capabilities::FfmpegCapabilities;
+pub use config::{Args, Command, Config, ConfigFormat, Profile, RuntimeConfig, WatchRule};
+pub use daemon::Daemon;
+pub use dir_config::{DirConfig, DirConfigResolver};
 pub use error::{Result, VideoToAudioError};
-pub use file_processor::FileProcessor;
-pub use user_interface::UserInterface;
+pub use event::ConversionEvent;
+pub use failures::FailureManifest;
+pub use file_processor::{FileProcessor, FileProcessorBuilder, HwAccel, OverwritePolicy};
+pub use i18n::Locale;
+pub use ipc::IpcSession;
+pub use journal::BatchJournal;
+pub use log_file::LogFile;
+pub use loudness::{LoudnessTarget, Normalization};
+pub use options::{ConversionOptions, CoverArt, FormatDefaults, MetadataPolicy, QualityPreset};
+pub use probe::AudioTrackSelection;
+pub use progress::{JsonProgress, ProgressEvent, ProgressFormat, ProgressListener};
+pub use scheduler::{ConcurrencyLimits, JobScheduler};
+pub use selection::{FileFilter, SortOrder};
+pub use server::JobQueue;
+pub use sync::SyncPlan;
+pub use tag_pattern::TagPattern;
+pub use tagging::TagUpdate;
+pub use user_interface::{BatchProgress, BatchSummary, ConversionPlan, UserInterface};
+pub use watcher::DirectoryWatcher;
@@ -0,0 +1,138 @@
+//! # 输出目录锁模块
+//!
+//! 在输出目录中创建咨询锁文件 `.v2a.lock`，防止多个实例（如重叠的 cron 任务）
+//! 同时向同一目录转换，重复转换文件或争抢同一个输出。
+//!
+//! 锁由操作系统的文件锁实现，进程退出（包括崩溃）时自动释放。
+//! 锁文件本身会保留在输出目录中，删除它可能使等待中的实例锁住已删除的文件。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::tr;
+use std::fs::{self, File, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 锁文件的文件名
+pub const LOCK_FILE_NAME: &str = ".v2a.lock";
+
+/// 等待锁时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 输出目录锁，释放（drop）时解锁
+#[derive(Debug)]
+pub struct OutputLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl OutputLock {
+    /// 尝试锁定输出目录
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回锁；目录已被其他实例锁定时返回 `None`
+    ///
+    /// # 错误
+    ///
+    /// 无法创建或锁定锁文件时返回 I/O 错误
+    pub fn try_acquire(output_dir: &Path) -> Result<Option<Self>> {
+        let path = output_dir.join(LOCK_FILE_NAME);
+        let mut file = File::options().read(true).write(true).create(true).truncate(false).open(&path)?;
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        // 写入进程号，便于其他实例提示是谁持有锁
+        file.set_len(0)?;
+        writeln!(file, "{}", std::process::id())?;
+        Ok(Some(Self { file, path }))
+    }
+
+    /// 锁定输出目录，已被其他实例锁定时等待其释放
+    ///
+    /// # 错误
+    ///
+    /// 等待期间被取消时返回 [`VideoToAudioError::Cancelled`]，无法锁定时返回 I/O 错误
+    pub fn acquire_waiting(output_dir: &Path, cancel_token: &CancellationToken) -> Result<Self> {
+        loop {
+            if let Some(lock) = Self::try_acquire(output_dir)? {
+                return Ok(lock);
+            }
+            if cancel_token.is_cancelled() {
+                return Err(VideoToAudioError::Cancelled);
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    /// 锁文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for OutputLock {
+    fn drop(&mut self) {
+        let _ = self.file.unlock();
+    }
+}
+
+/// 读取持有输出目录锁的进程号，无法读取时返回 `None`
+pub fn lock_holder(output_dir: &Path) -> Option<u32> {
+    fs::read_to_string(output_dir.join(LOCK_FILE_NAME)).ok()?.trim().parse().ok()
+}
+
+/// 查找包含指定路径的输出目录：从路径向上最近的、存在锁文件的目录
+///
+/// 锁文件只会在作为输出目录使用过的目录中创建，不在任何输出目录中时返回 `None`
+pub fn containing_output_dir(path: &Path) -> Option<PathBuf> {
+    let path = std::path::absolute(path).ok()?;
+    path.ancestors()
+        .find(|dir| dir.join(LOCK_FILE_NAME).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// 生成输出目录已被锁定时的错误
+pub fn locked_error(output_dir: &Path) -> VideoToAudioError {
+    let holder = lock_holder(output_dir).map(|pid| format!(" (PID {pid})")).unwrap_or_default();
+    VideoToAudioError::InvalidInput(tr!(
+        "输出目录 {} 正在被另一个实例{holder}处理，可使用 --wait-for-lock 等待其完成",
+        "The output directory {} is in use by another instance{holder}, pass --wait-for-lock to wait for it to finish",
+        output_dir.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_excludes_second_holder() {
+        let temp_dir = TempDir::new().unwrap();
+        let lock = OutputLock::try_acquire(temp_dir.path()).unwrap().unwrap();
+        assert_eq!(lock_holder(temp_dir.path()), Some(std::process::id()));
+
+        // 同一进程中另外打开的文件句柄同样受锁限制
+        assert!(OutputLock::try_acquire(temp_dir.path()).unwrap().is_none());
+
+        drop(lock);
+        assert!(OutputLock::try_acquire(temp_dir.path()).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_containing_output_dir_finds_nearest_locked_ancestor() {
+        let temp_dir = TempDir::new().unwrap();
+        let album = temp_dir.path().join("audio/album");
+        fs::create_dir_all(&album).unwrap();
+        let song = album.join("song.mp3");
+        assert_eq!(containing_output_dir(&song), None);
+
+        drop(OutputLock::try_acquire(&temp_dir.path().join("audio")).unwrap());
+        assert_eq!(containing_output_dir(&song), Some(temp_dir.path().join("audio")));
+    }
+}
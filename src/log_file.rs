@@ -0,0 +1,228 @@
+//! # 日志文件模块
+//!
+//! `--log-file` 把每个文件的开始、完成、失败、执行的 FFmpeg 命令和 FFmpeg 错误输出
+//! 写入日志文件，与终端的输出级别无关，常驻服务和监视模式可以据此事后排查问题。
+//!
+//! 每行一条 `key=value` 结构化日志，与 `daemon` 子命令写入标准错误流的格式相同：
+//!
+//! ```text
+//! ts=2024-05-01T08:30:00Z level=info event=file_started source=/videos/a.mp4
+//! ts=2024-05-01T08:30:04Z level=info event=file_done source=/videos/a.mp4 output=/audio/a.mp3 elapsed=3.9s
+//! ts=2024-05-01T08:30:05Z level=error event=file_failed source=/videos/b.mp4 elapsed=0.2s error="..."
+//! ```
+//!
+//! 日志文件超过大小上限时重命名为 `<文件名>.1`，已有的旧日志依次后移为 `.2`、`.3`，
+//! 最多保留 [`LOG_BACKUPS`] 个。
+
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::i18n::text;
+use std::fmt::Display;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// 默认的日志文件大小上限 (MB)
+pub const DEFAULT_LOG_MAX_SIZE_MB: u64 = 10;
+
+/// 轮转时保留的旧日志文件数
+pub const LOG_BACKUPS: usize = 3;
+
+/// 按大小轮转的日志文件
+///
+/// 多个工作线程可以同时写入，每条日志完整地写在一行中。
+#[derive(Debug)]
+pub struct LogFile {
+    path: PathBuf,
+    max_size: u64,
+    state: Mutex<LogState>,
+}
+
+#[derive(Debug)]
+struct LogState {
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    /// 以追加方式打开日志文件，必要时创建所在目录
+    ///
+    /// # 错误
+    ///
+    /// 无法创建目录或打开文件时返回错误
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let state = LogState::open(&path)?;
+        Ok(Self {
+            path,
+            max_size: DEFAULT_LOG_MAX_SIZE_MB * 1024 * 1024,
+            state: Mutex::new(state),
+        })
+    }
+
+    /// 设置轮转前日志文件的大小上限（字节）
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写入一条日志
+    ///
+    /// 写入失败时忽略，日志不影响转换本身
+    pub fn log(&self, level: &str, event: &str, fields: &[(&str, &dyn Display)]) {
+        let line = format_line(level, event, fields);
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if state.size > 0 && state.size + line.len() as u64 + 1 > self.max_size {
+            if let Ok(rotated) = self.rotate() {
+                *state = rotated;
+            }
+        }
+        if writeln!(state.file, "{line}").is_ok() {
+            state.size += line.len() as u64 + 1;
+        }
+    }
+
+    /// 记录文件开始转换
+    pub fn file_started(&self, source: &Path) {
+        self.log("info", "file_started", &[("source", &source.display())]);
+    }
+
+    /// 记录文件的转换结果
+    pub fn file_finished(&self, source: &Path, result: &Result<PathBuf>, elapsed: Duration) {
+        let elapsed = format!("{:.1}s", elapsed.as_secs_f64());
+        match result {
+            Ok(output) => self.log("info", "file_done", &[
+                ("source", &source.display()),
+                ("output", &output.display()),
+                ("elapsed", &elapsed),
+            ]),
+            Err(VideoToAudioError::Cancelled) => {
+                self.log("warn", "file_cancelled", &[("source", &source.display()), ("elapsed", &elapsed)])
+            }
+            Err(e) => self.log("error", "file_failed", &[
+                ("source", &source.display()),
+                ("elapsed", &elapsed),
+                ("error", e),
+            ]),
+        }
+    }
+
+    /// 记录执行的 FFmpeg 命令行
+    pub fn ffmpeg_command(&self, source: &Path, command_line: &str) {
+        self.log("debug", "ffmpeg_command", &[("source", &source.display()), ("command", &command_line)]);
+    }
+
+    /// 记录转换成功时 FFmpeg 仍然输出的错误信息，失败时的错误输出包含在 `file_failed` 中
+    pub fn ffmpeg_stderr(&self, source: &Path, stderr: &str) {
+        self.log("warn", "ffmpeg_stderr", &[("source", &source.display()), ("stderr", &stderr.trim_end())]);
+    }
+
+    /// 把当前日志和旧日志依次后移，然后重新创建日志文件
+    fn rotate(&self) -> Result<LogState> {
+        for index in (1..LOG_BACKUPS).rev() {
+            let from = self.backup_path(index);
+            if from.exists() {
+                fs::rename(&from, self.backup_path(index + 1)).context(text("轮转日志文件", "rotate log file"), &from)?;
+            }
+        }
+        fs::rename(&self.path, self.backup_path(1)).context(text("轮转日志文件", "rotate log file"), &self.path)?;
+        LogState::open(&self.path)
+    }
+
+    /// 第 `index` 个旧日志文件的路径
+    fn backup_path(&self, index: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{index}"));
+        PathBuf::from(path)
+    }
+}
+
+impl LogState {
+    fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+            fs::create_dir_all(parent).context(text("创建日志目录", "create log directory"), parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .context(text("打开日志文件", "open log file"), path)?;
+        let size = file.metadata().context(text("读取日志文件", "read log file"), path)?.len();
+        Ok(Self { file, size })
+    }
+}
+
+/// 生成一行结构化日志
+///
+/// 格式: `ts=<RFC3339> level=<级别> event=<事件> key=value ...`，
+/// 包含空白、引号或等号的值会加上双引号并转义，多行的值也因此保持在一行中。
+pub(crate) fn format_line(level: &str, event: &str, fields: &[(&str, &dyn Display)]) -> String {
+    let mut line = format!(
+        "ts={} level={level} event={event}",
+        humantime::format_rfc3339_seconds(SystemTime::now())
+    );
+    for (key, value) in fields {
+        line.push(' ');
+        line.push_str(key);
+        line.push('=');
+        line.push_str(&quote_value(&value.to_string()));
+    }
+    line
+}
+
+/// 按 logfmt 规则为值加引号
+fn quote_value(value: &str) -> String {
+    if value.is_empty() || value.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') {
+        format!("{value:?}")
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_quote_value() {
+        assert_eq!(quote_value("plain"), "plain");
+        assert_eq!(quote_value("/path/with space.mp4"), "\"/path/with space.mp4\"");
+        assert_eq!(quote_value("a=b"), "\"a=b\"");
+        assert_eq!(quote_value(""), "\"\"");
+    }
+
+    #[test]
+    fn test_log_file_records_results_and_rotates() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("logs/convert.log");
+        let log = LogFile::open(&path).unwrap().with_max_size(300);
+
+        let source = Path::new("videos/a b.mp4");
+        log.file_started(source);
+        let failed = Err(VideoToAudioError::FfmpegError("转换失败: moov atom not found\n".to_string()));
+        log.file_finished(source, &failed, Duration::from_millis(1500));
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("event=file_started source=\"videos/a b.mp4\""));
+        assert!(lines[1].contains("level=error event=file_failed"));
+        assert!(lines[1].contains("elapsed=1.5s"));
+        assert!(lines[1].contains("moov atom not found\\n"));
+
+        // 超过上限后轮转，最多保留 LOG_BACKUPS 个旧日志
+        for _ in 0..(LOG_BACKUPS + 2) * 4 {
+            log.file_started(source);
+        }
+        assert!(fs::metadata(&path).unwrap().len() <= 300);
+        assert!(log.backup_path(LOG_BACKUPS).exists());
+        assert!(!log.backup_path(LOG_BACKUPS + 1).exists());
+    }
+}
@@ -0,0 +1,405 @@
+//! # 响度模块
+//!
+//! 基于 FFmpeg 的 `loudnorm` 滤镜（EBU R128 算法）把来源各异的输出调整到一致的响度。
+//!
+//! - 单遍模式：转换时实时估计响度并动态调整，速度快但不够精确
+//! - 双遍模式：先完整分析一遍源文件，再用测量结果做线性调整，结果更准确，
+//!   代价是每个文件需要多解码一次
+//!
+//! 也可以不改变音频，只把输出文件的测量结果写入 ReplayGain/R128 增益标签，
+//! 由播放器在播放时调整音量。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::audio_format::AudioFormat;
+use crate::file_processor::{input_arg, FileProcessor};
+use crate::i18n::text;
+use crate::options::{format_seconds, ConversionOptions};
+use crate::tagging::TagUpdate;
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ffi::OsString;
+use std::io::Read;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// `loudnorm` 滤镜输出的采样率
+///
+/// 滤镜内部会把音频升采样到 192kHz，输出前需要重新指定采样率
+pub const LOUDNORM_SAMPLE_RATE: u32 = 48000;
+
+/// ReplayGain 2.0 的参考响度 (LUFS)
+const REPLAYGAIN_REFERENCE: f64 = -18.0;
+
+/// Opus 的 R128 增益标签的参考响度 (LUFS)
+const R128_REFERENCE: f64 = -23.0;
+
+/// 等待分析进程时检查取消请求的间隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 响度标准化的目标
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct LoudnessTarget {
+    /// 目标综合响度 (LUFS)
+    pub integrated: f64,
+
+    /// 最大真峰值 (dBTP)
+    pub true_peak: f64,
+
+    /// 目标响度范围 (LU)
+    pub range: f64,
+}
+
+impl Default for LoudnessTarget {
+    /// 播客和流媒体常用的 -16 LUFS
+    fn default() -> Self {
+        Self {
+            integrated: -16.0,
+            true_peak: -1.5,
+            range: 11.0,
+        }
+    }
+}
+
+/// 第一遍分析得到的源文件响度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LoudnessMeasurement {
+    /// 综合响度 (LUFS)
+    pub integrated: f64,
+
+    /// 真峰值 (dBTP)
+    pub true_peak: f64,
+
+    /// 响度范围 (LU)
+    pub range: f64,
+
+    /// 门限 (LUFS)
+    pub threshold: f64,
+
+    /// 目标偏移 (LU)
+    pub offset: f64,
+}
+
+/// 响度标准化设置
+#[derive(Debug, Clone, PartialEq)]
+pub struct Normalization {
+    /// 标准化目标
+    pub target: LoudnessTarget,
+
+    /// 是否使用双遍模式
+    pub two_pass: bool,
+
+    /// 双遍模式第一遍的测量结果，转换时自动填写
+    pub measured: Option<LoudnessMeasurement>,
+}
+
+impl Normalization {
+    /// 创建单遍标准化设置
+    pub fn new(target: LoudnessTarget) -> Self {
+        Self {
+            target,
+            two_pass: false,
+            measured: None,
+        }
+    }
+
+    /// 设置是否使用双遍模式
+    pub fn with_two_pass(mut self, two_pass: bool) -> Self {
+        self.two_pass = two_pass;
+        self
+    }
+
+    /// 是否还需要分析源文件（双遍模式且尚无测量结果）
+    pub fn needs_measurement(&self) -> bool {
+        self.two_pass && self.measured.is_none()
+    }
+
+    /// 生成 `loudnorm` 滤镜
+    ///
+    /// 已有测量结果时使用线性调整，否则为单遍动态调整
+    pub fn filter(&self) -> String {
+        let target = &self.target;
+        let mut filter = format!(
+            "loudnorm=I={}:TP={}:LRA={}",
+            target.integrated, target.true_peak, target.range
+        );
+        if let Some(measured) = &self.measured {
+            filter.push_str(&format!(
+                ":measured_I={}:measured_TP={}:measured_LRA={}:measured_thresh={}:offset={}:linear=true",
+                measured.integrated, measured.true_peak, measured.range, measured.threshold, measured.offset
+            ));
+        }
+        filter
+    }
+}
+
+/// 从 `loudnorm=print_format=json` 的输出中解析测量结果
+///
+/// 滤镜把 JSON 打印在 FFmpeg 错误输出的末尾，各字段的值都是字符串
+pub fn parse_measurement(stderr: &str) -> Option<LoudnessMeasurement> {
+    let start = stderr.rfind('{')?;
+    let end = start + stderr[start..].find('}')?;
+    let json: Value = serde_json::from_str(&stderr[start..=end]).ok()?;
+    let field = |name: &str| -> Option<f64> {
+        json.get(name)?
+            .as_str()?
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+    };
+    Some(LoudnessMeasurement {
+        integrated: field("input_i")?,
+        true_peak: field("input_tp")?,
+        range: field("input_lra")?,
+        threshold: field("input_thresh")?,
+        offset: field("target_offset")?,
+    })
+}
+
+/// 根据测量结果生成增益标签
+///
+/// Opus 按 RFC 7845 使用 `R128_TRACK_GAIN`（相对 -23 LUFS，Q7.8 定点整数），
+/// 其他格式使用 ReplayGain 2.0 的 `REPLAYGAIN_TRACK_GAIN`（相对 -18 LUFS）和
+/// `REPLAYGAIN_TRACK_PEAK`（线性峰值）
+pub fn gain_tags(measured: &LoudnessMeasurement, format: AudioFormat) -> Vec<(String, String)> {
+    match format {
+        AudioFormat::Opus => {
+            let gain = ((R128_REFERENCE - measured.integrated) * 256.0)
+                .round()
+                .clamp(i16::MIN as f64, i16::MAX as f64);
+            vec![("R128_TRACK_GAIN".to_string(), format!("{}", gain as i16))]
+        }
+        _ => vec![
+            (
+                "REPLAYGAIN_TRACK_GAIN".to_string(),
+                format!("{:.2} dB", REPLAYGAIN_REFERENCE - measured.integrated),
+            ),
+            (
+                "REPLAYGAIN_TRACK_PEAK".to_string(),
+                format!("{:.6}", 10f64.powf(measured.true_peak / 20.0)),
+            ),
+        ],
+    }
+}
+
+impl FileProcessor {
+    /// 分析输出文件的响度并写入增益标签
+    ///
+    /// 标签通过 [`FileProcessor::write_tags`] 写入，音频数据保持不变。
+    pub(crate) fn write_gain_tags(
+        &self,
+        output_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        self.check_ffmpeg_availability()?;
+
+        let analysis = ConversionOptions {
+            timeout: options.timeout,
+            ..ConversionOptions::new(options.format)
+        };
+        let measured = self.measure_loudness(
+            output_path,
+            &analysis,
+            &Normalization::new(LoudnessTarget::default()),
+            cancel_token,
+        )?;
+
+        let update = TagUpdate {
+            tags: gain_tags(&measured, options.format),
+            cover: None,
+        };
+        self.write_tags_as(output_path, options.format, &update, options.timeout, cancel_token)
+    }
+
+    /// 双遍标准化时先分析源文件，返回填入测量结果的转换选项
+    ///
+    /// 未启用双遍标准化或已有测量结果时原样返回
+    pub(crate) fn measure_for_normalization(
+        &self,
+        source_file: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<ConversionOptions> {
+        match &options.normalize {
+            Some(normalization) if normalization.needs_measurement() => {
+                let measured = self.measure_loudness(source_file, options, normalization, cancel_token)?;
+                Ok(ConversionOptions {
+                    normalize: Some(Normalization {
+                        measured: Some(measured),
+                        ..normalization.clone()
+                    }),
+                    ..options.clone()
+                })
+            }
+            _ => Ok(options.clone()),
+        }
+    }
+
+    /// 分析源文件的响度（双遍标准化的第一遍）
+    ///
+    /// 分析使用与转换相同的音轨、片段和滤镜，只解码不写入文件。
+    ///
+    /// # 错误
+    ///
+    /// 被取消时返回 [`VideoToAudioError::Cancelled`]；分析失败或源文件没有
+    /// 可测量的音频（如全程静音）时返回 [`VideoToAudioError::FfmpegError`]
+    fn measure_loudness(
+        &self,
+        source_file: &Path,
+        options: &ConversionOptions,
+        normalization: &Normalization,
+        cancel_token: &CancellationToken,
+    ) -> Result<LoudnessMeasurement> {
+        let mut args: Vec<OsString> = ["-hide_banner", "-nostats"].map(OsString::from).into();
+        args.extend(options.input_args().into_iter().map(OsString::from));
+        args.push("-i".into());
+        args.push(input_arg(source_file));
+        if let Some(audio_stream) = options.audio_stream {
+            args.extend(["-map".into(), format!("0:a:{audio_stream}").into()]);
+        }
+        args.push("-vn".into());
+
+        let single_pass = Normalization {
+            measured: None,
+            ..normalization.clone()
+        };
+        let mut filters = options.processing_filters();
+        filters.push(format!("{}:print_format=json", single_pass.filter()));
+        args.extend(["-af".into(), filters.join(",").into()]);
+        if let Some(duration) = options.segment_duration() {
+            args.extend(["-t".into(), format_seconds(duration).into()]);
+        }
+        args.extend(["-f", "null", "-"].map(OsString::from));
+
+        let (status, stderr) = self.run_analysis(&args, options.timeout, cancel_token)?;
+        if !status.success() {
+            return Err(VideoToAudioError::FfmpegError(tr!(
+                "响度分析失败: {}",
+                "Loudness analysis failed: {}",
+                stderr.trim(),
+            )));
+        }
+
+        parse_measurement(&stderr).ok_or_else(|| VideoToAudioError::FfmpegError(tr!(
+            "无法测量响度（源文件可能没有声音）: {}",
+            "Cannot measure the loudness (the source may be silent): {}",
+            source_file.display()
+        )))
+    }
+
+    /// 运行不产生输出文件的 FFmpeg 分析（输出到 `-f null -`），返回退出状态和错误输出
+    ///
+    /// 进程运行期间会定期检查取消令牌，被取消或超时时终止进程
+    pub(crate) fn run_analysis(
+        &self,
+        args: &[OsString],
+        timeout: Option<Duration>,
+        cancel_token: &CancellationToken,
+    ) -> Result<(ExitStatus, String)> {
+        let started_at = Instant::now();
+        let mut child = self
+            .conversion_command()
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .context(text("启动 FFmpeg", "start FFmpeg"), self.ffmpeg_path())?;
+
+        let mut stderr_pipe = child.stderr.take();
+        let stderr_reader = thread::spawn(move || {
+            let mut stderr = Vec::new();
+            if let Some(pipe) = stderr_pipe.as_mut() {
+                let _ = pipe.read_to_end(&mut stderr);
+            }
+            stderr
+        });
+
+        let status = loop {
+            if let Some(status) = child.try_wait().context(text("等待 FFmpeg", "wait for FFmpeg"), self.ffmpeg_path())? {
+                break status;
+            }
+
+            let timed_out = timeout.is_some_and(|timeout| started_at.elapsed() >= timeout);
+            if cancel_token.is_cancelled() || timed_out {
+                let _ = child.kill();
+                let _ = child.wait();
+                let _ = stderr_reader.join();
+                return Err(match timeout {
+                    Some(timeout) if timed_out => Self::timeout_error(timeout),
+                    _ => VideoToAudioError::Cancelled,
+                });
+            }
+
+            thread::sleep(CANCEL_POLL_INTERVAL);
+        };
+
+        let stderr = String::from_utf8_lossy(&stderr_reader.join().unwrap_or_default()).into_owned();
+        Ok((status, stderr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_OUTPUT: &str = r#"[Parsed_loudnorm_0 @ 0x5581]
+{
+	"input_i" : "-27.61",
+	"input_tp" : "-4.47",
+	"input_lra" : "18.06",
+	"input_thresh" : "-39.20",
+	"output_i" : "-16.58",
+	"output_tp" : "-1.50",
+	"output_lra" : "14.78",
+	"output_thresh" : "-27.71",
+	"normalization_type" : "dynamic",
+	"target_offset" : "0.58"
+}
+"#;
+
+    #[test]
+    fn test_parse_measurement() {
+        let measured = parse_measurement(SAMPLE_OUTPUT).unwrap();
+        assert_eq!(measured.integrated, -27.61);
+        assert_eq!(measured.threshold, -39.2);
+        assert_eq!(measured.offset, 0.58);
+        assert!(parse_measurement("Invalid data found when processing input").is_none());
+        assert!(parse_measurement(&SAMPLE_OUTPUT.replace("-27.61", "-inf")).is_none());
+    }
+
+    #[test]
+    fn test_gain_tags() {
+        let measured = parse_measurement(SAMPLE_OUTPUT).unwrap();
+        assert_eq!(
+            gain_tags(&measured, AudioFormat::Mp3),
+            [
+                ("REPLAYGAIN_TRACK_GAIN".to_string(), "9.61 dB".to_string()),
+                ("REPLAYGAIN_TRACK_PEAK".to_string(), "0.597723".to_string()),
+            ]
+        );
+        assert_eq!(
+            gain_tags(&measured, AudioFormat::Opus),
+            [("R128_TRACK_GAIN".to_string(), "1180".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_normalization_filter() {
+        let mut normalization = Normalization::new(LoudnessTarget::default()).with_two_pass(true);
+        assert_eq!(normalization.filter(), "loudnorm=I=-16:TP=-1.5:LRA=11");
+
+        normalization.measured = parse_measurement(SAMPLE_OUTPUT);
+        assert_eq!(
+            normalization.filter(),
+            "loudnorm=I=-16:TP=-1.5:LRA=11:measured_I=-27.61:measured_TP=-4.47:\
+             measured_LRA=18.06:measured_thresh=-39.2:offset=0.58:linear=true"
+        );
+    }
+}
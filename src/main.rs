@@ -8,35 +8,158 @@
 //! - 多核并行处理
 //! - 实时进度显示
 
-use clap::Parser;
-use video2audio_rs::{Args, AudioFormat, Config, FileProcessor, RuntimeConfig, UserInterface, VideoToAudioError};
+mod commands;
+
+use clap::{CommandFactory, FromArgMatches};
+use std::io::IsTerminal;
+use std::process::ExitCode;
+use video2audio_rs::{
+    Args, AudioFormat, BatchJournal, BatchProgress, BatchSummary, CancellationToken, Command, Config, ConfigFormat,
+    ConversionOptions, ConversionPlan, CoverArt, DirConfigResolver, DirectoryWatcher, FileProcessor, IpcSession,
+    JsonProgress, Normalization, OverwritePolicy, ProgressEvent, ProgressFormat, ProgressListener, RuntimeConfig,
+    UserInterface, VideoToAudioError,
+};
+use video2audio_rs::checksums::ChecksumManifest;
+use video2audio_rs::dedupe::{link_output, split_duplicates, DedupeMode};
+use video2audio_rs::disk_space::{format_bytes, SpaceEstimate};
+use video2audio_rs::estimate::{calibrate, estimate_outputs, estimate_time, OutputEstimate};
+use video2audio_rs::error::IoResultExt;
+use video2audio_rs::failures::FailureManifest;
+use video2audio_rs::preflight::Preflight;
+use video2audio_rs::preview::{create_preview, play};
+use video2audio_rs::quarantine::{quarantine, quarantine_dir, QuarantineMode};
+use video2audio_rs::file_processor::{AUDIO_INPUT_EXTENSIONS, DEFAULT_OUTPUT_DIR_NAME};
+use video2audio_rs::history::{source_fingerprint, History, HistoryRecord};
+use video2audio_rs::hooks::HookContext;
+use video2audio_rs::i18n::{self, text, Locale};
+use video2audio_rs::keyboard::{KeyAction, KeyboardControl};
+use video2audio_rs::notification::{batch_notification, send_notification};
+use video2audio_rs::playlist::write_playlist;
+use video2audio_rs::power::SleepInhibitor;
+use video2audio_rs::remote::is_remote_url;
+use video2audio_rs::report::{write_report, ReportEntry};
+use video2audio_rs::selection::{read_file_list_from, sample_files, sort_files};
+use video2audio_rs::style;
+use video2audio_rs::tagging::format_from_path;
+use video2audio_rs::tr;
+#[cfg(feature = "tui")]
+use video2audio_rs::tui::Dashboard;
 
 /// 程序主入口点
 ///
+/// 出错时显示错误信息和错误代码，并以状态码 1 退出；
+/// 部分文件失败等已经显示过原因的情况由 `run` 直接返回状态码，
+/// 这样退出前仍会释放输出目录锁、写完日志文件并恢复终端设置
+fn main() -> ExitCode {
+    match run() {
+        Ok(code) => code,
+        Err(e) => {
+            UserInterface::new().show_error(&e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
 /// 协调各个模块完成完整的视频转音频流程：
 /// 1. 解析命令行参数和配置
 /// 2. 根据模式选择交互式或批处理流程
 /// 3. 执行视频转音频处理
 /// 4. 显示处理结果和统计信息
-fn main() -> Result<(), VideoToAudioError> {
-    // 解析命令行参数
-    let args = Args::parse();
+fn run() -> Result<ExitCode, VideoToAudioError> {
+    // 解析命令行参数；配置文件尚未读取，--help 的语言由 --lang 或系统语言决定
+    let locale = Locale::from_args(std::env::args_os()).or_else(Locale::from_env).unwrap_or_default();
+    i18n::set_locale(locale);
+    let mut command = i18n::localize_command(Args::command(), locale);
+    if std::env::args_os().any(|arg| arg == "--no-color") {
+        command = command.color(clap::ColorChoice::Never);
+    }
+    let matches = command.get_matches();
+    let mut args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let command = args.command.take();
+    if args.no_color {
+        style::disable_colors();
+    }
+    init_tracing(args.trace.as_deref())?;
+
+    // config 子命令自行读取配置文件，配置文件格式错误时也可以用它修复
+    if let Some(Command::Config(config_args)) = command {
+        return commands::run_config(config_args, args, &UserInterface::new()).map(|()| ExitCode::SUCCESS);
+    }
+
+    // 把 JSON 配置文件迁移为 TOML
+    if args.migrate_config {
+        let json_path = Config::resolve_path(args.config_file.as_ref())?;
+        let toml_path = Config::migrate_to_toml(&json_path)?;
+        println!("{}", style::success(tr!("✅ 配置已迁移到 {}", "✅ Configuration migrated to {}", toml_path.display())));
+        return Ok(ExitCode::SUCCESS);
+    }
 
     // 加载配置文件
+    // 环境变量只影响本次运行，保存配置时不写入配置文件
     let mut config = Config::load(args.config_file.as_ref())?;
+    let mut effective_config = config.clone();
+    effective_config.apply_env_overrides()?;
+    i18n::set_locale(Locale::resolve(args.lang, &effective_config.language)?);
+    if let Some(name) = &args.profile {
+        effective_config.profile(name)?;
+    }
+    effective_config.format_defaults()?;
+    let legacy_config = args.config_file.is_none()
+        && Config::default_config_path().is_ok_and(|path| path.exists() && ConfigFormat::from_path(&path) == ConfigFormat::Json);
 
     // 创建运行时配置
-    let runtime_config = RuntimeConfig::from_args_and_config(args, config.clone());
+    let mut runtime_config = RuntimeConfig::from_args_and_config(args, effective_config.clone());
+    runtime_config.open_log_file()?;
+
+    // --retry-failed 只重新转换失败清单中的文件，默认沿用清单中的格式和所在的输出目录
+    let retry_manifest = runtime_config.retry_failed.as_deref().map(FailureManifest::load).transpose()?;
+    if let (Some(manifest), Some(path)) = (&retry_manifest, &runtime_config.retry_failed) {
+        runtime_config.format = runtime_config.format.or(manifest.format());
+        if runtime_config.output_dir.is_none() {
+            runtime_config.output_dir = Some(FailureManifest::output_dir_of(path));
+        }
+    }
+
+    // 片段的结束时间必须晚于开始时间
+    if let (Some(start), Some(end)) = (runtime_config.start, runtime_config.end) {
+        if end <= start {
+            return Err(VideoToAudioError::InvalidInput(
+                tr!("--end 必须晚于 --start", "--end must be later than --start")
+            ));
+        }
+    }
+
+    if let Some(CoverArt::File(cover)) = &runtime_config.cover {
+        if !cover.is_file() {
+            return Err(VideoToAudioError::InvalidPath(
+                tr!("封面图片不存在: {}", "Cover image not found: {}", cover.display())
+            ));
+        }
+    }
+    if let (None, Some(format)) = (&command, runtime_config.format) {
+        check_cover_format(&runtime_config, format)?;
+    }
 
     // 处理特殊命令
     if runtime_config.list_formats {
-        show_supported_formats();
-        return Ok(());
+        show_supported_formats(&runtime_config);
+        return Ok(ExitCode::SUCCESS);
     }
 
     // 初始化组件
     let ui = UserInterface::new();
-    let processor = FileProcessor::new();
+    let mut processor = runtime_config.file_processor();
+
+    #[cfg(not(feature = "tui"))]
+    if runtime_config.tui {
+        return Err(VideoToAudioError::MissingDependency(
+            tr!("终端仪表盘未编译，请使用 --features tui 重新构建", "The terminal dashboard is not compiled in, rebuild with --features tui")
+        ));
+    }
+
+    if runtime_config.tui && !(std::io::stdin().is_terminal() && std::io::stdout().is_terminal()) {
+        return Err(VideoToAudioError::InvalidInput(tr!("--tui 需要在终端中运行", "--tui requires a terminal")));
+    }
 
     // 设置并行线程数
     if let Some(jobs) = runtime_config.jobs {
@@ -44,85 +167,614 @@ fn main() -> Result<(), VideoToAudioError> {
             .num_threads(jobs)
             .build_global()
             .map_err(|e| VideoToAudioError::InvalidInput(
-                format!("无法设置线程池: {e}")
+                tr!("无法设置线程池: {e}", "Cannot set up the thread pool: {e}")
             ))?;
     }
 
+    // 按下 Ctrl+C 或收到终止信号时取消处理，正在运行的 FFmpeg 进程会被终止并清理临时文件
+    let cancel_token = CancellationToken::new();
+    let handler_token = cancel_token.clone();
+    ctrlc::set_handler(move || handler_token.cancel())
+        .map_err(|e| VideoToAudioError::InvalidInput(
+            tr!("无法注册中断信号处理: {e}", "Cannot register the interrupt handler: {e}")
+        ))?;
+
+    // 执行子命令
+    if let Some(command) = command {
+        return commands::run(command, &ui, &runtime_config, &effective_config, &cancel_token);
+    }
+
+    // HTTP API 服务模式：持续接收任务，直到按下 Ctrl+C
+    if let Some(ref addr) = runtime_config.serve {
+        if !runtime_config.quiet {
+            println!("{}", tr!(
+                "🌐 HTTP API 服务已启动: http://{addr}，按 Ctrl+C 停止...",
+                "🌐 HTTP API service running at http://{addr}, press Ctrl+C to stop..."
+            ));
+        }
+        video2audio_rs::server::serve(addr, processor, &cancel_token)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // IPC 模式：标准输出只用于 JSON 事件，不显示任何界面文字
+    if runtime_config.ipc {
+        let stdin = std::io::BufReader::new(std::io::stdin());
+        return IpcSession::new(processor).run(stdin, std::io::stdout(), &cancel_token).map(|()| ExitCode::SUCCESS);
+    }
+
     // 根据模式选择处理流程
-    let (source_path, chosen_format, output_dir) = if runtime_config.needs_interaction() {
+    let interactive = runtime_config.needs_interaction();
+    // JSON 进度事件写入标准输出，交互提问会混入事件流
+    let json_progress = if runtime_config.progress == ProgressFormat::Json {
+        if interactive {
+            return Err(VideoToAudioError::InvalidInput(
+                tr!(
+                    "--progress json 不能用于交互式模式，请同时指定 --source 和 --format",
+                    "--progress json cannot be used interactively, specify both --source and --format"
+                )
+            ));
+        }
+        Some(std::sync::Arc::new(JsonProgress::new(std::io::stdout())))
+    } else {
+        None
+    };
+    let (source_path, chosen_format, output_dir) = if interactive {
         // 交互式模式
-        interactive_mode(&ui, &processor, &runtime_config)?
+        if legacy_config {
+            println!("{}", text(
+                "💡 配置文件仍为旧的 JSON 格式，运行 video2audio-rs --migrate-config 可迁移为更易编辑的 TOML 格式",
+                "💡 The configuration file still uses the old JSON format, run video2audio-rs --migrate-config to migrate to the easier to edit TOML format",
+            ));
+        }
+        let selection = interactive_mode(&ui, &processor, &mut runtime_config, &effective_config.recent_source_dirs)?;
+        check_cover_format(&runtime_config, selection.1)?;
+        // 交互式选择的输出目录和覆盖策略需要应用到文件处理器
+        processor = runtime_config.file_processor();
+        selection
     } else {
         // 批处理模式
         batch_mode(&processor, &runtime_config)?
     };
 
-    // 查找视频文件
-    let files_to_process = processor.find_video_files(&source_path)?;
+    // 锁定输出目录直到程序结束，防止重叠运行的实例重复转换同一批文件
+    let _output_lock = commands::lock_output_dir(&output_dir, &runtime_config, &cancel_token)?;
+
+    // 源目录中的 .video2audio.toml 为所在子目录单独指定格式、码率和输出目录等设置
+    let processor = if runtime_config.dir_config {
+        let root = source_path.is_dir().then(|| source_path.clone());
+        let normalization = runtime_config
+            .normalize
+            .clone()
+            .unwrap_or_else(|| Normalization::new(effective_config.loudness_target));
+        let dir_configs = DirConfigResolver::new(root).with_normalization(normalization);
+        processor.with_dir_configs(Some(std::sync::Arc::new(dir_configs)))
+    } else {
+        processor
+    };
+
+    // 查找视频文件（继续模式下优先使用状态日志中尚未完成的文件）
+    let files_to_process = if runtime_config.resume {
+        resume_pending_files(&output_dir, chosen_format, &runtime_config)?
+    } else {
+        None
+    };
+    // 扫描源目录时记录被排除的文件，找不到任何文件时可以说明原因
+    let mut skipped_files = Vec::new();
+    let mut files_to_process = match (files_to_process, &retry_manifest, &runtime_config.from_file) {
+        (Some(files), _, _) => files,
+        (None, Some(manifest), _) => manifest.sources(),
+        (None, None, Some(list)) => read_file_list_from(list)?,
+        // 源为远程地址时只处理这一个输入
+        (None, None, None) if is_remote_url(&source_path) => vec![source_path.clone()],
+        (None, None, None) => {
+            let (files, skipped) = processor.find_video_files_with_skipped(&source_path)?;
+            skipped_files = skipped;
+            files
+        }
+    };
+
+    // 转换历史：记录本次成功的转换，并跳过以前已转换为同一格式的文件
+    let mut history = (runtime_config.history || runtime_config.skip_converted)
+        .then(load_history)
+        .flatten();
+    if runtime_config.skip_converted {
+        if let Some(history) = &history {
+            let before = files_to_process.len();
+            files_to_process.retain(|file| {
+                source_fingerprint(file).map_or(true, |hash| history.find(&hash, chosen_format).is_none())
+            });
+            let skipped = before - files_to_process.len();
+            if skipped > 0 && !runtime_config.quiet {
+                println!("{}", tr!("⏭️  跳过 {skipped} 个已转换过的文件", "⏭️  Skipping {skipped} already converted files"));
+            }
+        }
+    }
+    if !runtime_config.history {
+        history = None;
+    }
+
+    // 内容相同的源文件只转换第一个，其余文件在批处理结束后处理
+    let duplicates = if runtime_config.dedupe.is_some() {
+        let (unique, duplicates) = split_duplicates(files_to_process);
+        files_to_process = unique;
+        if !duplicates.is_empty() && !runtime_config.quiet {
+            println!("{}", tr!(
+                "🔁 {} 个文件与其他源文件内容相同，不再重复转换",
+                "🔁 {} files are identical to other sources and won't be converted again",
+                duplicates.len()
+            ));
+        }
+        duplicates
+    } else {
+        Vec::new()
+    };
+    // 先抽样再排序，最后截取前 N 个文件
+    if let Some(count) = runtime_config.sample {
+        sample_files(&mut files_to_process, count);
+    }
+    if let Some((order, descending)) = runtime_config.sort_order() {
+        sort_files(&mut files_to_process, order, descending, &processor);
+    }
+    if let Some(limit) = runtime_config.limit {
+        files_to_process.truncate(limit);
+    }
+
+    // 挑选要转换的文件：指定 --pick 时总是显示列表，在终端中交互运行时询问是否需要挑选
+    let terminal = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    let pick = runtime_config.pick
+        || (interactive && terminal && files_to_process.len() > 1 && ui.confirm(text("是否挑选要转换的文件？", "Pick the files to convert?"), false)?);
+    if pick && !files_to_process.is_empty() {
+        if !terminal {
+            return Err(VideoToAudioError::InvalidInput(tr!("--pick 需要在终端中运行", "--pick requires a terminal")));
+        }
+        files_to_process = ui.pick_files(files_to_process, &source_path)?;
+    }
+
+    // 预检：有问题的文件提前报告并从本次转换中排除，写入失败清单
+    let preflight_failures = if runtime_config.preflight && !files_to_process.is_empty() {
+        run_preflight(&mut files_to_process, &processor, &ui, &runtime_config, &cancel_token)
+    } else {
+        Vec::new()
+    };
+    if runtime_config.fail_fast && !preflight_failures.is_empty() {
+        return Err(VideoToAudioError::InvalidInput(tr!(
+            "预检发现 {} 个有问题的文件，--fail-fast 时不开始转换",
+            "Preflight found {} problem files, not starting because of --fail-fast",
+            preflight_failures.len()
+        )));
+    }
     let total_files = files_to_process.len();
+    let confirm_plan = interactive && total_files > 0;
+    let show_plan = confirm_plan || runtime_config.dry_run;
+    if let Some(json_progress) = &json_progress {
+        json_progress.emit(&ProgressEvent::ScanDone { total: total_files, output_dir: output_dir.clone() })?;
+    }
 
-    // 显示扫描结果（除非是静默模式）
-    if !runtime_config.quiet {
+    // 详细模式下，或者没有要转换的文件时，说明扫描排除了哪些文件
+    if !runtime_config.quiet && (runtime_config.verbose || total_files == 0) {
+        ui.show_skipped_files(&skipped_files, &source_path, runtime_config.verbose);
+    }
+
+    // 显示扫描结果（除非是静默模式），需要确认时改为在开始前显示转换计划；全部文件都没有通过预检时不再显示
+    if !runtime_config.quiet && !show_plan && (total_files > 0 || preflight_failures.is_empty()) {
         ui.show_files_found(total_files, &output_dir);
     }
 
-    if total_files == 0 {
+    // 列出不符合标签模板的文件，这些文件照常转换但不写入模板标签
+    if let Some(pattern) = &runtime_config.tag_pattern {
+        let unmatched: Vec<_> = files_to_process
+            .iter()
+            .filter(|file| pattern.captures_file(file).is_none())
+            .collect();
+        if !unmatched.is_empty() && !runtime_config.quiet {
+            println!("{}", style::warning(tr!(
+                "⚠️  {} 个文件名不符合标签模板，不会写入模板标签:",
+                "⚠️  {} file names don't match the tag pattern and won't get pattern tags:",
+                unmatched.len()
+            )));
+            for file in unmatched {
+                println!("   - {}", file.display());
+            }
+        }
+    }
+
+    if total_files == 0 && !runtime_config.watch {
+        if !preflight_failures.is_empty() && !runtime_config.dry_run {
+            // 全部文件都没有通过预检
+            let mut failures = FailureManifest::new(chosen_format);
+            for (source_file, e) in &preflight_failures {
+                failures.record(source_file, e);
+            }
+            save_failure_manifest(&failures, &output_dir, &runtime_config)?;
+            if let Some(mode) = runtime_config.quarantine {
+                let corrupt: Vec<_> = preflight_failures
+                    .iter()
+                    .filter(|(_, e)| matches!(e, VideoToAudioError::CorruptInput(_)))
+                    .map(|(source_file, _)| source_file.clone())
+                    .collect();
+                quarantine_corrupt_files(&corrupt, mode, &source_path, &output_dir, &runtime_config);
+            }
+        } else if !runtime_config.quiet {
+            println!("{}", text("未找到任何视频文件，程序退出。", "No video files found, exiting."));
+        }
+        if let Some(json_progress) = &json_progress {
+            json_progress.emit(&ProgressEvent::BatchDone {
+                succeeded: 0,
+                failed: 0,
+                cancelled: false,
+                elapsed_seconds: 0.0,
+            })?;
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    // 在处理第一个文件前确认 FFmpeg 支持所选格式的编码器
+    let options = runtime_config.conversion_options(processor.check_encoder(chosen_format)?);
+    if let Some(encoder) = &options.encoder {
         if !runtime_config.quiet {
-            println!("未找到任何视频文件，程序退出。");
+            println!("{}", style::warning(tr!(
+                "⚠️  当前 FFmpeg 缺少默认编码器，改用 {encoder} 编码器",
+                "⚠️  This FFmpeg lacks the default encoder, using {encoder} instead"
+            )));
+        }
+    }
+
+    // 确认输出目录所在磁盘有足够的空间
+    if !runtime_config.skip_space_check && !runtime_config.dry_run {
+        check_disk_space(&files_to_process, &output_dir, &options, &runtime_config)?;
+    }
+
+    // --preview 先转换第一个文件开头的一段供试听，确认音质后再转换全部文件
+    if let Some(first) = files_to_process.first().filter(|_| runtime_config.preview) {
+        if !run_preview(first, &processor, &options, &ui, terminal, &cancel_token)? {
+            return Ok(ExitCode::SUCCESS);
         }
-        return Ok(());
     }
 
-    // 执行批量转换
-    let (success_count, failure_count) = processor.batch_convert(
+    // 交互式模式下先显示转换计划，用户确认后才开始转换；--dry-run 只显示计划
+    if show_plan {
+        let estimates = estimate_outputs(processor.ffmpeg_path(), &files_to_process, &options);
+        let estimated_time = estimate_conversion_time(&processor, &estimates, &options, &runtime_config, &cancel_token);
+        let plan = ConversionPlan::new(&files_to_process, &options, &output_dir)
+            .with_estimates(&estimates)
+            .with_estimated_time(estimated_time);
+        if runtime_config.dry_run {
+            ui.show_output_estimates(&estimates, &source_path);
+            ui.show_conversion_plan(&plan);
+            println!("{}", text("--dry-run: 没有转换任何文件。", "--dry-run: no files were converted."));
+            return Ok(ExitCode::SUCCESS);
+        }
+        ui.show_conversion_plan(&plan);
+        if !ui.confirm(text("确认开始转换？", "Start converting?"), true)? {
+            println!("{}", text("已取消，没有转换任何文件。", "Cancelled, no files were converted."));
+            return Ok(ExitCode::SUCCESS);
+        }
+    }
+
+    // 每个文件完成后立即写入校验和清单，中断时也留下有效的部分清单
+    let mut manifest = runtime_config
+        .checksums
+        .map(|algorithm| ChecksumManifest::open(&output_dir, algorithm))
+        .transpose()?;
+
+    // 执行批量转换，生成播放列表和报告时记录每个源文件的结果
+    let mut outputs = Vec::new();
+    let mut converted = std::collections::HashMap::new();
+    let mut report_entries = Vec::new();
+    let mut failures = FailureManifest::new(options.format);
+    // 损坏的源文件在批处理结束后统一隔离，转换过程中不改动源目录
+    let mut corrupt = Vec::new();
+    for (source_file, e) in preflight_failures {
+        failures.record(&source_file, &e);
+        let is_corrupt = matches!(e, VideoToAudioError::CorruptInput(_));
+        if runtime_config.report.is_some() {
+            let result = Err(e);
+            let elapsed = std::time::Duration::ZERO;
+            report_entries.push(ReportEntry::new(processor.ffmpeg_path(), &source_file, &result, options.format, elapsed));
+        }
+        if is_corrupt && runtime_config.quarantine.is_some() {
+            corrupt.push(source_file);
+        }
+    }
+    let mut summary = BatchSummary::new(total_files);
+    let started_at = std::time::Instant::now();
+    // 休眠抑制锁持续到批量转换结束；交互式运行默认启用，拿不到锁时只在详细模式下提示
+    let sleep_inhibitor = if runtime_config.inhibit_sleep {
+        match SleepInhibitor::acquire(text("正在批量转换视频", "Converting videos")) {
+            Ok(inhibitor) => Some(inhibitor),
+            Err(e) => {
+                if runtime_config.verbose || runtime_config.batch_mode {
+                    eprintln!("{}", style::warning(format!("⚠️  {e}")).for_stderr());
+                }
+                None
+            }
+        }
+    } else {
+        None
+    };
+    // 总进度条加上每个正在转换的文件各一个进度条；--tui 时改为显示终端仪表盘
+    #[cfg(feature = "tui")]
+    let dashboard = if runtime_config.tui && total_files > 0 {
+        let workers = rayon::current_num_threads().min(total_files);
+        Some(std::sync::Arc::new(Dashboard::start(total_files, workers, cancel_token.clone())?))
+    } else {
+        None
+    };
+    let progress = (!runtime_config.quiet && !runtime_config.tui)
+        .then(|| std::sync::Arc::new(BatchProgress::new(total_files)));
+    let listener = progress
+        .clone()
+        .map(|progress| progress as std::sync::Arc<dyn ProgressListener>)
+        .or_else(|| json_progress.clone().map(|json_progress| json_progress as std::sync::Arc<dyn ProgressListener>));
+    #[cfg(feature = "tui")]
+    let listener = listener.or_else(|| dashboard.clone().map(|dashboard| dashboard as std::sync::Arc<dyn ProgressListener>));
+    // 在终端中运行时按 p 暂停、r 恢复、q 停止；仪表盘自行处理按键
+    let keyboard = (terminal && progress.is_some() && json_progress.is_none())
+        .then(|| start_keyboard_control(&cancel_token, progress.clone()))
+        .flatten();
+    let (success_count, failure_count) = processor.clone().with_progress(listener).batch_convert_timed(
         &files_to_process,
         &output_dir,
-        chosen_format,
-        |current, total| {
-            if !runtime_config.quiet {
-                ui.show_progress(current, total);
+        options.clone(),
+        &cancel_token,
+        |source_file, result, elapsed, current, total| {
+            // 目录配置可能为该文件指定了其他格式，以输出文件的扩展名为准
+            let format = result
+                .as_ref()
+                .ok()
+                .and_then(|output_path| format_from_path(output_path))
+                .unwrap_or(options.format);
+            if let Ok(output_path) = result {
+                summary.record(source_file, output_path);
+                record_checksum(manifest.as_mut(), output_path);
+                if let Some(history) = history.as_mut() {
+                    record_history(history, &processor, source_file, output_path, format, elapsed);
+                }
+                if runtime_config.playlist {
+                    outputs.push((source_file.to_path_buf(), output_path.clone()));
+                }
+                if !duplicates.is_empty() {
+                    converted.insert(source_file.to_path_buf(), output_path.clone());
+                }
+            }
+            run_hook(source_file, result, format, &runtime_config);
+            // 取消的文件不算失败，下次运行时可以用 --resume 接着处理
+            match result {
+                Err(VideoToAudioError::Cancelled) | Ok(_) => {}
+                Err(e) => failures.record(source_file, e),
+            }
+            if let (Err(VideoToAudioError::CorruptInput(_)), Some(_)) = (result, runtime_config.quarantine) {
+                corrupt.push(source_file.to_path_buf());
+            }
+            // 仪表盘自行显示失败的文件；进度条需要先隐藏，错误信息才不会与其混在一起
+            if let (Err(e), false) = (result, runtime_config.tui) {
+                match &progress {
+                    Some(progress) => progress.suspend(|| ui.show_file_failure(source_file, e)),
+                    None => ui.show_file_failure(source_file, e),
+                }
+            }
+            if runtime_config.report.is_some() {
+                report_entries.push(ReportEntry::new(processor.ffmpeg_path(), source_file, result, format, elapsed));
+            }
+            if let Some(progress) = &progress {
+                progress.file_done(current, total);
+            }
+            if let Some(json_progress) = &json_progress {
+                let _ = json_progress.emit(&ProgressEvent::file_done(source_file, result, elapsed, current, total));
+            }
+            #[cfg(feature = "tui")]
+            if let Some(dashboard) = &dashboard {
+                let result = result.as_deref().map_err(ToString::to_string);
+                dashboard.file_done(source_file, result, current, total);
             }
         },
     );
+    // 先恢复终端设置，再显示后续信息
+    drop(keyboard);
+    if let Some(progress) = &progress {
+        progress.finish();
+    }
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = &dashboard {
+        dashboard.finish();
+    }
+    summary.elapsed = started_at.elapsed();
+    drop(sleep_inhibitor);
+    if let Some(json_progress) = &json_progress {
+        json_progress.emit(&ProgressEvent::BatchDone {
+            succeeded: success_count,
+            failed: failure_count,
+            cancelled: cancel_token.is_cancelled(),
+            elapsed_seconds: summary.elapsed.as_secs_f64(),
+        })?;
+    }
+
+    // 重复文件跟随内容相同的源文件：链接到它的输出，或只在报告中注明
+    let mut linked = 0;
+    for duplicate in &duplicates {
+        // 原文件转换失败时不处理重复文件，下次运行时重新判断
+        let Some(original_output) = converted.get(&duplicate.original) else { continue };
+        let output = match runtime_config.dedupe {
+            Some(DedupeMode::Link) => {
+                let format = format_from_path(original_output).unwrap_or(options.format);
+                let linked_output = processor
+                    .output_path_for(&duplicate.file, &output_dir, format)
+                    .and_then(|output| link_output(original_output, &output).map(|()| output));
+                match linked_output {
+                    Ok(output) => {
+                        linked += 1;
+                        Some(output)
+                    }
+                    Err(e) => {
+                        eprintln!("{}", style::warning(tr!(
+                            "⚠️  无法为 '{}' 链接输出文件: {e}",
+                            "⚠️  Cannot link the output for '{}': {e}",
+                            duplicate.file.display()
+                        )).for_stderr());
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        if let Some(output) = &output {
+            if runtime_config.playlist {
+                outputs.push((duplicate.file.clone(), output.clone()));
+            }
+        }
+        if runtime_config.report.is_some() {
+            report_entries.push(ReportEntry::duplicate(
+                processor.ffmpeg_path(),
+                &duplicate.file,
+                &duplicate.original,
+                output.as_deref(),
+                options.format,
+            ));
+        }
+    }
+    if linked > 0 && !runtime_config.quiet {
+        println!("{}", tr!("🔗 已为 {linked} 个重复文件链接输出", "🔗 Linked outputs for {linked} duplicate files"));
+    }
+
+    // 报告条目需要先读取源文件大小，移动源文件放在生成报告条目之后
+    if let Some(mode) = runtime_config.quarantine {
+        let quarantined = quarantine_corrupt_files(&corrupt, mode, &source_path, &output_dir, &runtime_config);
+        for entry in &mut report_entries {
+            entry.quarantined = quarantined.get(&entry.source).cloned();
+        }
+    }
+
+    // 并行转换的完成顺序不固定，播放列表和报告按源文件的处理顺序排列，重复文件排在最后
+    let order: std::collections::HashMap<_, _> = files_to_process
+        .iter()
+        .chain(duplicates.iter().map(|duplicate| &duplicate.file))
+        .enumerate()
+        .map(|(index, file)| (file, index))
+        .collect();
+    if runtime_config.playlist && !outputs.is_empty() {
+        outputs.sort_by_key(|(source_file, _)| order.get(source_file).copied());
+        let outputs: Vec<_> = outputs.into_iter().map(|(_, output_path)| output_path).collect();
+        let playlist = write_playlist(&output_dir, &outputs)?;
+        if !runtime_config.quiet {
+            println!("{}", tr!("📃 播放列表已写入: {}", "📃 Playlist written to {}", playlist.display()));
+        }
+    }
+    if let Some(report) = &runtime_config.report {
+        report_entries.sort_by_key(|entry| order.get(&entry.source).copied());
+        write_report(report, &report_entries)?;
+        if !runtime_config.quiet {
+            println!("{}", tr!("📝 转换报告已写入: {}", "📝 Report written to {}", report.display()));
+        }
+    }
+
+    // 有文件失败时写入失败清单；全部成功后删除之前留下的清单
+    if !failures.is_empty() {
+        save_failure_manifest(&failures, &output_dir, &runtime_config)?;
+    } else if !cancel_token.is_stopped() {
+        FailureManifest::remove(&output_dir)?;
+    }
+
+    if runtime_config.notify && total_files > 0 {
+        let (title, body) = batch_notification(success_count, failure_count, cancel_token.is_cancelled());
+        if let Err(e) = send_notification(&title, &body) {
+            eprintln!("{}", style::warning(format!("⚠️  {e}")).for_stderr());
+        }
+    }
 
     // 显示完成信息
-    if !runtime_config.quiet {
-        ui.show_completion(total_files, &output_dir);
+    if !runtime_config.quiet && total_files > 0 {
+        ui.show_completion(&summary, &output_dir);
 
         // 显示详细统计信息
         if failure_count > 0 || runtime_config.verbose {
-            println!("📊 处理统计:");
-            println!("   ✅ 成功: {success_count} 个文件");
+            println!("{}", text("📊 处理统计:", "📊 Summary:"));
+            println!("{}", style::success(tr!("   ✅ 成功: {success_count} 个文件", "   ✅ Succeeded: {success_count} files")));
             if failure_count > 0 {
-                println!("   ❌ 失败: {failure_count} 个文件");
-                println!("   建议检查失败文件的格式或完整性");
+                println!("{}", style::failure(tr!("   ❌ 失败: {failure_count} 个文件", "   ❌ Failed: {failure_count} files")));
+                println!("{}", text("   建议检查失败文件的格式或完整性", "   Check whether the failed files are corrupt or in an unusual format"));
             }
         }
+
+        let unfinished = total_files.saturating_sub(success_count + failure_count);
+        if cancel_token.is_stopped() && !cancel_token.is_cancelled() && unfinished > 0 {
+            println!("{}", style::warning(tr!(
+                "⏹️  已停止，还有 {unfinished} 个文件没有转换，使用 --resume 继续",
+                "⏹️  Stopped with {unfinished} files not converted yet, use --resume to continue"
+            )));
+        }
+    }
+
+    // 监视模式：持续转换新增或修改的视频，直到按下 Ctrl+C
+    if runtime_config.watch && !cancel_token.is_stopped() {
+        watch_source_directory(
+            &processor,
+            &source_path,
+            &output_dir,
+            options,
+            manifest.as_mut(),
+            &cancel_token,
+            &runtime_config,
+        )?;
     }
 
     // 更新配置（添加最近使用的目录）
-    config.add_recent_source_dir(&source_path.to_string_lossy());
+    if !runtime_config.reads_file_list() && !is_remote_url(&source_path) {
+        let recent_before = config.recent_source_dirs.clone();
+        config.add_recent_source_dir(&source_path.to_string_lossy());
+
+        // 交互式选择的源目录自动记住，下次运行时可以按编号选择
+        let chosen_interactively = runtime_config.needs_interaction() && runtime_config.source_dir.is_none();
+        if chosen_interactively && !runtime_config.save_config && config.recent_source_dirs != recent_before {
+            if let Err(e) = config.save(runtime_config.config_file.as_ref()) {
+                eprintln!("{}", style::warning(tr!("⚠️  无法保存最近使用的目录: {e}", "⚠️  Cannot save the recent folders: {e}")).for_stderr());
+            }
+        }
+    }
 
     // 保存配置（如果需要）
     if runtime_config.save_config {
-        config.save(runtime_config.output_dir.as_ref())?;
+        config.save(runtime_config.config_file.as_ref())?;
         if !runtime_config.quiet {
-            println!("✅ 配置已保存");
+            println!("{}", style::success(text("✅ 配置已保存", "✅ Configuration saved")));
         }
     }
 
-    Ok(())
+    // 快速失败模式下出现失败时以非零状态码退出
+    if runtime_config.fail_fast && failure_count > 0 {
+        eprintln!("{}", style::failure(text(
+            "⛔ 快速失败模式: 批处理已在首个失败后中止",
+            "⛔ Fail-fast mode: the batch was aborted after the first failure",
+        )).for_stderr());
+        return Ok(ExitCode::FAILURE);
+    }
+
+    Ok(ExitCode::SUCCESS)
 }
 
-/// 显示支持的格式列表
-fn show_supported_formats() {
-    println!("📋 支持的文件格式:");
+/// 显示支持的格式列表，输入格式包括配置文件中增减的扩展名，以及启用时的纯音频输入
+fn show_supported_formats(config: &RuntimeConfig) {
+    println!("{}", text("📋 支持的文件格式:", "📋 Supported formats:"));
     println!();
 
-    println!("🎬 输入格式 (视频):");
-    let processor = FileProcessor::new();
-    let extensions = processor.supported_extensions();
-    for (i, ext) in extensions.iter().enumerate() {
+    println!("{}", text("🎬 输入格式 (视频):", "🎬 Input formats (video):"));
+    show_extensions(config.extensions.iter().map(String::as_str));
+    if config.include_audio_inputs {
+        println!("{}", text("🎧 输入格式 (音频):", "🎧 Input formats (audio):"));
+        show_extensions(AUDIO_INPUT_EXTENSIONS.iter().copied());
+    }
+
+    println!("{}", text("🎵 输出格式 (音频):", "🎵 Output formats (audio):"));
+    for format in AudioFormat::all_formats() {
+        println!("  {} - {}",
+                format.extension().to_uppercase(),
+                format.description());
+    }
+    println!();
+}
+
+/// 每行 5 个显示扩展名
+fn show_extensions<'a>(extensions: impl Iterator<Item = &'a str>) {
+    for (i, ext) in extensions.enumerate() {
         if i % 5 == 0 && i > 0 {
             println!();
         }
@@ -130,34 +782,453 @@ fn show_supported_formats() {
     }
     println!();
     println!();
+}
 
-    println!("🎵 输出格式 (音频):");
-    for format in AudioFormat::all_formats() {
-        println!("  {} - {}",
-                format.extension().to_uppercase(),
-                format.description());
+/// 转换预览片段，在终端中询问是否播放以及是否继续转换全部文件
+///
+/// 返回 `false` 表示不继续转换；不在终端中运行时只生成预览
+fn run_preview(
+    source_file: &std::path::Path,
+    processor: &FileProcessor,
+    options: &ConversionOptions,
+    ui: &UserInterface,
+    terminal: bool,
+    cancel_token: &CancellationToken,
+) -> Result<bool, VideoToAudioError> {
+    println!("{}", tr!("🎧 正在转换预览片段: {}", "🎧 Converting a preview: {}", source_file.display()));
+    let preview = create_preview(processor, source_file, options, cancel_token)?;
+    println!("{}", style::success(tr!("✅ 预览已保存: {}", "✅ Preview saved: {}", preview.display())));
+    if !terminal {
+        println!("{}", text("不在终端中运行，只生成预览，没有转换全部文件。", "Not running in a terminal, only the preview was created."));
+        return Ok(false);
     }
-    println!();
+
+    if ui.confirm(text("是否播放预览？", "Play the preview?"), true)? {
+        if let Err(e) = play(processor.ffmpeg_path(), &preview) {
+            eprintln!("{}", style::warning(tr!(
+                "⚠️  无法播放预览，请手动打开: {e}",
+                "⚠️  Cannot play the preview, please open it manually: {e}"
+            )).for_stderr());
+        }
+    }
+    let proceed = ui.confirm(text("音质满意，开始转换全部文件？", "Happy with the quality? Convert all files?"), true)?;
+    if !proceed {
+        println!("{}", text("已取消，没有转换任何文件。", "Cancelled, no files were converted."));
+    }
+    Ok(proceed)
+}
+
+/// 开始读取按键控制批处理，每次按键后显示批处理的状态
+///
+/// 标准输入不是终端或当前平台不支持时返回 `None`
+fn start_keyboard_control(
+    cancel_token: &CancellationToken,
+    progress: Option<std::sync::Arc<BatchProgress>>,
+) -> Option<KeyboardControl> {
+    let show = |progress: &Option<std::sync::Arc<BatchProgress>>, message: &str| match progress {
+        Some(progress) => progress.suspend(|| println!("{message}")),
+        None => println!("{message}"),
+    };
+    show(&progress, text(
+        "⌨️  按 p 暂停、r 恢复、q 在正在转换的文件完成后停止",
+        "⌨️  Press p to pause, r to resume, q to stop after the running files",
+    ));
+    KeyboardControl::start(cancel_token.clone(), move |action| {
+        let message = match action {
+            KeyAction::Pause => text(
+                "⏸️  已暂停：正在转换的文件完成后不再开始新文件，按 r 恢复",
+                "⏸️  Paused: no new files will start after the running ones, press r to resume",
+            ),
+            KeyAction::Resume => text("▶️  已恢复", "▶️  Resumed"),
+            KeyAction::Stop => text(
+                "⏹️  正在停止：等待正在转换的文件完成...",
+                "⏹️  Stopping: waiting for the running files to finish...",
+            ),
+        };
+        show(&progress, message);
+    })
+}
+
+/// 试转换第一个已知时长的文件，按测得的速度估算整批转换的耗时
+///
+/// 试转换失败时不估算耗时，只在详细模式下说明原因
+fn estimate_conversion_time(
+    processor: &FileProcessor,
+    estimates: &[OutputEstimate],
+    options: &ConversionOptions,
+    config: &RuntimeConfig,
+    cancel_token: &CancellationToken,
+) -> Option<std::time::Duration> {
+    let sample = estimates
+        .iter()
+        .find(|estimate| estimate.converted_duration(options).is_some_and(|duration| !duration.is_zero()))?;
+    match calibrate(processor, sample, options, cancel_token) {
+        Ok(speed) => Some(estimate_time(estimates, options, speed, rayon::current_num_threads())),
+        Err(e) => {
+            if config.verbose {
+                eprintln!("{}", style::warning(tr!(
+                    "⚠️  试转换失败，无法估算耗时: {e}",
+                    "⚠️  Test encode failed, can't estimate the time: {e}"
+                )).for_stderr());
+            }
+            None
+        }
+    }
+}
+
+/// 预检全部输入文件，从列表中移除有问题的文件并逐个显示原因
+///
+/// 返回有问题的文件；找不到 ffprobe 时只检查文件大小和读取权限
+fn run_preflight(
+    files: &mut Vec<std::path::PathBuf>,
+    processor: &FileProcessor,
+    ui: &UserInterface,
+    config: &RuntimeConfig,
+    cancel_token: &CancellationToken,
+) -> Vec<(std::path::PathBuf, VideoToAudioError)> {
+    let preflight = Preflight::new(processor.ffmpeg_path());
+    if !config.quiet {
+        println!("{}", tr!("🔍 预检 {} 个文件...", "🔍 Checking {} files...", files.len()));
+        if !preflight.probes_headers() {
+            println!("{}", style::warning(text(
+                "⚠️  找不到 ffprobe，预检只检查文件大小和读取权限",
+                "⚠️  ffprobe not found, only checking file sizes and read permissions",
+            )));
+        }
+    }
+
+    let issues = preflight.run(files, cancel_token);
+    if !issues.is_empty() {
+        let excluded: std::collections::HashSet<_> = issues.iter().map(|(file, _)| file.clone()).collect();
+        files.retain(|file| !excluded.contains(file));
+        if !config.quiet {
+            println!("{}", style::warning(tr!(
+                "⚠️  预检发现 {} 个有问题的文件，本次不转换:",
+                "⚠️  Preflight found {} problem files, they won't be converted:",
+                issues.len()
+            )));
+        }
+        for (file, e) in &issues {
+            ui.show_file_failure(file, e);
+        }
+    }
+    issues
+}
+
+/// 把损坏的源文件隔离到输出目录的 `corrupt/` 文件夹
+///
+/// 返回源文件到隔离位置的对应关系，无法隔离的文件只给出警告
+fn quarantine_corrupt_files(
+    files: &[std::path::PathBuf],
+    mode: QuarantineMode,
+    source_path: &std::path::Path,
+    output_dir: &std::path::Path,
+    config: &RuntimeConfig,
+) -> std::collections::HashMap<std::path::PathBuf, std::path::PathBuf> {
+    let dir = quarantine_dir(output_dir);
+    let source_root = source_path.is_dir().then_some(source_path);
+    let mut quarantined = std::collections::HashMap::new();
+    for file in files {
+        match quarantine(file, source_root, &dir, mode) {
+            Ok(target) => {
+                quarantined.insert(file.clone(), target);
+            }
+            Err(e) => eprintln!("{}", style::warning(tr!(
+                "⚠️  无法隔离损坏的文件 '{}': {e}",
+                "⚠️  Cannot quarantine the corrupt file '{}': {e}",
+                file.display()
+            )).for_stderr()),
+        }
+    }
+    if !quarantined.is_empty() && !config.quiet {
+        println!("{}", tr!(
+            "🗃️  已把 {} 个损坏的文件隔离到: {}",
+            "🗃️  Quarantined {} corrupt files in {}",
+            quarantined.len(),
+            dir.display()
+        ));
+    }
+    quarantined
+}
+
+/// 写入失败清单，并提示用 `--retry-failed` 重新转换
+fn save_failure_manifest(
+    failures: &FailureManifest,
+    output_dir: &std::path::Path,
+    config: &RuntimeConfig,
+) -> Result<(), VideoToAudioError> {
+    let path = failures.save(output_dir)?;
+    if !config.quiet {
+        println!("{}", tr!(
+            "📋 失败清单已写入: {}，使用 --retry-failed {} 只重新转换失败的文件",
+            "📋 Failure manifest written to {}, use --retry-failed {} to convert only the failed files",
+            path.display(),
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+/// 检查输出目录所在磁盘的剩余空间
+/// 
+/// 空间不足时拒绝开始，余量很小时给出警告
+fn check_disk_space(
+    files: &[std::path::PathBuf],
+    output_dir: &std::path::Path,
+    options: &ConversionOptions,
+    config: &RuntimeConfig,
+) -> Result<(), VideoToAudioError> {
+    let estimate = SpaceEstimate::for_batch(files, output_dir, options)?;
+    estimate.ensure_sufficient()?;
+
+    if estimate.is_tight() && !config.quiet {
+        println!("{}", style::warning(tr!(
+            "⚠️  输出目录所在磁盘剩余空间紧张：预计需要 {}，可用 {}",
+            "⚠️  The output disk is nearly full: about {} needed, {} available",
+            format_bytes(estimate.required),
+            format_bytes(estimate.available)
+        )));
+    }
+    Ok(())
+}
+
+/// 监视源目录并自动转换新增或修改的视频
+fn watch_source_directory(
+    processor: &FileProcessor,
+    source_path: &std::path::Path,
+    output_dir: &std::path::Path,
+    options: ConversionOptions,
+    mut manifest: Option<&mut ChecksumManifest>,
+    cancel_token: &CancellationToken,
+    config: &RuntimeConfig,
+) -> Result<(), VideoToAudioError> {
+    if !config.quiet {
+        println!("{}", tr!(
+            "👀 正在监视 {} 中的新视频，按 Ctrl+C 停止...",
+            "👀 Watching {} for new videos, press Ctrl+C to stop...",
+            source_path.display()
+        ));
+    }
+
+    let watcher = DirectoryWatcher::new(processor, config.watch_debounce);
+    let format = options.format;
+    watcher.watch(source_path, output_dir, options, cancel_token, |source_file, result| {
+        run_hook(source_file, &result, format, config);
+        match result {
+            Ok(output_path) => {
+                record_checksum(manifest.as_deref_mut(), &output_path);
+                if !config.quiet {
+                    println!("{}", style::success(format!("✅ {} -> {}", source_file.display(), output_path.display())));
+                }
+            }
+            Err(e) => UserInterface::new().show_file_failure(source_file, &e),
+        }
+    })?;
+
+    if !config.quiet {
+        println!("{}", text("⏹️  已停止监视", "⏹️  Stopped watching"));
+    }
+    Ok(())
+}
+
+/// 把输出文件的校验和写入清单，失败时只给出警告
+fn record_checksum(manifest: Option<&mut ChecksumManifest>, output_path: &std::path::Path) {
+    if let Some(manifest) = manifest {
+        if let Err(e) = manifest.record(output_path) {
+            eprintln!("{}", style::warning(tr!("⚠️  无法写入 {} 的校验和: {e}", "⚠️  Cannot write the checksum of {}: {e}", output_path.display())).for_stderr());
+        }
+    }
+}
+
+/// 根据转换结果执行 `--on-success` 或 `--on-failure` 钩子，钩子出错时只给出警告
+fn run_hook(
+    source_file: &std::path::Path,
+    result: &Result<std::path::PathBuf, VideoToAudioError>,
+    format: AudioFormat,
+    config: &RuntimeConfig,
+) {
+    let error = result.as_ref().err().map(|e| e.to_string());
+    let (hook, context) = match result {
+        Ok(output_path) => (
+            &config.on_success,
+            HookContext { input: source_file, output: Some(output_path), format, error: None },
+        ),
+        Err(_) => (
+            &config.on_failure,
+            HookContext { input: source_file, output: None, format, error: error.as_deref() },
+        ),
+    };
+    let Some(hook) = hook else { return };
+    match hook.run(&context) {
+        Ok(status) if status.success() => {}
+        Ok(status) => eprintln!("{}", style::warning(tr!(
+            "⚠️  '{}' 的钩子命令退出状态 {status}",
+            "⚠️  The hook command for '{}' exited with {status}",
+            source_file.display()
+        )).for_stderr()),
+        Err(e) => eprintln!("{}", style::warning(tr!(
+            "⚠️  无法执行 '{}' 的钩子命令: {e}",
+            "⚠️  Cannot run the hook command for '{}': {e}",
+            source_file.display()
+        )).for_stderr()),
+    }
+}
+
+/// 加载默认位置的转换历史，失败时只给出警告
+fn load_history() -> Option<History> {
+    match History::default_path().and_then(|path| History::load(&path)) {
+        Ok(history) => Some(history),
+        Err(e) => {
+            eprintln!("{}", style::warning(tr!("⚠️  无法读取转换历史: {e}", "⚠️  Cannot read the conversion history: {e}")).for_stderr());
+            None
+        }
+    }
+}
+
+/// 把成功的转换追加到转换历史，失败时只给出警告
+fn record_history(
+    history: &mut History,
+    processor: &FileProcessor,
+    source_file: &std::path::Path,
+    output_path: &std::path::Path,
+    format: AudioFormat,
+    elapsed: std::time::Duration,
+) {
+    let record = HistoryRecord::new(processor.ffmpeg_path(), source_file, output_path, format, elapsed);
+    if let Err(e) = record.and_then(|record| history.record(record)) {
+        eprintln!("{}", style::warning(tr!(
+            "⚠️  无法记录 {} 的转换历史: {e}",
+            "⚠️  Cannot record the conversion history of {}: {e}",
+            source_file.display()
+        )).for_stderr());
+    }
+}
+
+/// 从状态日志中获取上次中断后尚未完成的文件
+///
+/// 输出目录中没有状态日志时返回 `None`，由调用方重新扫描源目录
+fn resume_pending_files(
+    output_dir: &std::path::Path,
+    format: AudioFormat,
+    config: &RuntimeConfig,
+) -> Result<Option<Vec<std::path::PathBuf>>, VideoToAudioError> {
+    let Some(journal) = BatchJournal::load(output_dir)? else {
+        if !config.quiet {
+            println!("{}", text(
+                "ℹ️  未找到可继续的批处理状态，将重新扫描源目录",
+                "ℹ️  No batch state to resume, scanning the source directory again",
+            ));
+        }
+        return Ok(None);
+    };
+
+    if journal.format() != Some(format) {
+        return Err(VideoToAudioError::InvalidInput(tr!(
+            "状态日志中记录的格式与当前选择的格式 ({}) 不一致，请使用相同的 --format 继续",
+            "The state journal was recorded for a different format than the selected one ({}), resume with the same --format",
+            format.extension()
+        )));
+    }
+
+    let pending = journal.pending();
+    if !config.quiet {
+        println!("{}", tr!(
+            "♻️  继续上次中断的批处理: 已完成 {} 个，剩余 {} 个文件",
+            "♻️  Resuming the interrupted batch: {} done, {} files left",
+            journal.completed_count(),
+            pending.len()
+        ));
+    }
+
+    Ok(Some(pending))
+}
+
+/// 从文件列表读取时使用的源目录
+/// 
+/// 列表中的文件没有共同的源目录，未指定输出目录时输出到当前目录下的默认输出目录
+fn list_source_path() -> std::path::PathBuf {
+    std::path::PathBuf::from(".")
+}
+
+/// 创建输出目录
+/// 
+/// 未指定输出目录时在源目录下创建默认输出目录，源为远程地址时在当前目录下创建
+fn prepare_output_dir(
+    processor: &FileProcessor,
+    config: &RuntimeConfig,
+    source_path: &std::path::Path,
+) -> Result<std::path::PathBuf, VideoToAudioError> {
+    if let Some(ref dir) = config.output_dir {
+        std::fs::create_dir_all(dir).context(text("创建输出目录", "create output directory"), dir)?;
+        return Ok(dir.clone());
+    }
+
+    if is_remote_url(source_path) {
+        processor.create_output_directory(&list_source_path())
+    } else {
+        processor.create_output_directory(source_path)
+    }
+}
+
+/// 初始化诊断日志
+///
+/// `--trace` 优先于 `RUST_LOG`。两者都未设置时只在标准错误流显示本程序的警告，
+/// 不显示时间和模块名；设置后显示完整的时间、级别、span 和模块名。
+fn init_tracing(trace: Option<&str>) -> Result<(), VideoToAudioError> {
+    use tracing_subscriber::EnvFilter;
+
+    let (filter, detailed) = match trace {
+        Some(directives) => {
+            let filter = EnvFilter::try_new(directives).map_err(|e| VideoToAudioError::InvalidInput(
+                tr!("无效的 --trace 过滤规则 '{directives}': {e}", "Invalid --trace filter '{directives}': {e}")
+            ))?;
+            (filter, true)
+        }
+        None => match EnvFilter::try_from_default_env() {
+            Ok(filter) => (filter, true),
+            Err(_) => (EnvFilter::new("video2audio_rs=warn"), false),
+        },
+    };
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(style::stderr_colors_enabled());
+    if detailed {
+        subscriber.init();
+    } else {
+        subscriber.without_time().with_target(false).init();
+    }
+    Ok(())
 }
 
 /// 交互式模式处理
 fn interactive_mode(
     ui: &UserInterface,
     processor: &FileProcessor,
-    config: &RuntimeConfig
+    config: &mut RuntimeConfig,
+    recent_dirs: &[String],
 ) -> Result<(std::path::PathBuf, AudioFormat, std::path::PathBuf), VideoToAudioError> {
     // 显示欢迎信息
     if !config.quiet {
         ui.show_welcome();
     }
 
-    // 获取源目录
-    let source_dir = if let Some(ref dir) = config.source_dir {
-        dir.to_string_lossy().to_string()
-    } else {
-        ui.get_source_directory()?
+    // 获取源目录（从文件列表读取时不需要源目录）
+    let source_path = match (&config.source_dir, config.reads_file_list()) {
+        (Some(dir), _) => dir.clone(),
+        (None, true) => list_source_path(),
+        (None, false) => std::path::PathBuf::from(ui.get_source_directory(recent_dirs)?),
     };
-    let source_path = std::path::PathBuf::from(&source_dir);
+
+    // 标准输入已用于读取文件列表，无法再交互式输入
+    if config.format.is_none() && config.from_file.as_deref() == Some(std::path::Path::new("-")) {
+        return Err(VideoToAudioError::InvalidInput(
+            tr!(
+                "从标准输入读取文件列表时需要指定音频格式 (--format)",
+                "Reading the file list from stdin requires an audio format (--format)"
+            )
+        ));
+    }
 
     // 获取音频格式
     let chosen_format = if let Some(format) = config.format {
@@ -166,41 +1237,69 @@ fn interactive_mode(
         ui.select_audio_format()?
     };
 
+    // 命令行中没有指定的输出目录、覆盖策略和音质逐项询问，直接按回车使用默认值
+    if config.output_dir.is_none() {
+        let default = if is_remote_url(&source_path) { list_source_path() } else { source_path.clone() };
+        config.output_dir = ui.get_output_directory(&default.join(DEFAULT_OUTPUT_DIR_NAME))?;
+    }
+    if !config.skip_existing && !config.incremental {
+        match ui.select_overwrite_policy()? {
+            OverwritePolicy::SkipExisting => config.skip_existing = true,
+            OverwritePolicy::SkipUpToDate => config.incremental = true,
+            OverwritePolicy::Overwrite => {}
+        }
+    }
+    if chosen_format != AudioFormat::AacCopy && config.bitrate.is_none() && config.quality.is_none() {
+        config.quality = Some(ui.select_quality_preset()?);
+    }
+
     // 创建输出目录
-    let output_dir = if let Some(ref dir) = config.output_dir {
-        std::fs::create_dir_all(dir)?;
-        dir.clone()
-    } else {
-        processor.create_output_directory(&source_path)?
-    };
+    let output_dir = prepare_output_dir(processor, config, &source_path)?;
 
     Ok((source_path, chosen_format, output_dir))
 }
 
+/// 检查输出格式能否嵌入 `--cover` 指定的封面
+///
+/// 只有 MP3 和 M4B 能嵌入封面：AAC 输出为不带容器的 ADTS 流，Opus 输出暂不支持封面，
+/// 这时报错而不是静默丢弃封面
+fn check_cover_format(config: &RuntimeConfig, format: AudioFormat) -> Result<(), VideoToAudioError> {
+    if config.cover.is_some() && !format.supports_cover() {
+        return Err(VideoToAudioError::InvalidInput(tr!(
+            "{} 格式不能嵌入封面，--cover 只能用于 MP3 和 M4B",
+            "The {} format cannot hold cover art, --cover only works with MP3 and M4B",
+            format.extension().to_uppercase()
+        )));
+    }
+    Ok(())
+}
+
 /// 批处理模式处理
 fn batch_mode(
     processor: &FileProcessor,
     config: &RuntimeConfig
 ) -> Result<(std::path::PathBuf, AudioFormat, std::path::PathBuf), VideoToAudioError> {
     // 验证必需的参数
-    let source_path = config.source_dir.as_ref()
-        .ok_or_else(|| VideoToAudioError::InvalidInput(
-            "批处理模式需要指定源目录 (--source)".to_string()
-        ))?
-        .clone();
+    let source_path = match (&config.source_dir, config.reads_file_list()) {
+        (Some(dir), _) => dir.clone(),
+        (None, true) => list_source_path(),
+        (None, false) => {
+            return Err(VideoToAudioError::InvalidInput(
+                tr!(
+                    "批处理模式需要指定源目录 (--source) 或文件列表 (--from-file)",
+                    "Batch mode requires a source directory (--source) or a file list (--from-file)"
+                )
+            ));
+        }
+    };
 
     let chosen_format = config.format
         .ok_or_else(|| VideoToAudioError::InvalidInput(
-            "批处理模式需要指定音频格式 (--format)".to_string()
+            tr!("批处理模式需要指定音频格式 (--format)", "Batch mode requires an audio format (--format)")
         ))?;
 
     // 创建输出目录
-    let output_dir = if let Some(ref dir) = config.output_dir {
-        std::fs::create_dir_all(dir)?;
-        dir.clone()
-    } else {
-        processor.create_output_directory(&source_path)?
-    };
+    let output_dir = prepare_output_dir(processor, config, &source_path)?;
 
     Ok((source_path, chosen_format, output_dir))
 }
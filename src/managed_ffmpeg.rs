@@ -0,0 +1,347 @@
+//! # 托管 FFmpeg 模块
+//!
+//! 管理下载到应用数据目录中的 FFmpeg。系统 PATH 中找不到 FFmpeg 时，
+//! [`find_ffmpeg`] 会自动改用托管的版本，用户无需自行安装。
+//!
+//! 下载功能仅在启用 `ffmpeg-download` 特性时编译：
+//! 为当前系统和架构下载静态编译的 FFmpeg，校验 SHA-256 后解压到数据目录。
+//!
+//! 校验和与压缩包来自同一个下载源，且下载地址指向不断更新的最新构建，
+//! 因此校验只能发现传输中的损坏或不完整的下载，无法防范下载源本身被篡改。
+//! 对此有要求时请通过系统的包管理器安装 FFmpeg。
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// 可执行文件名
+const FFMPEG_BINARY: &str = if cfg!(windows) { "ffmpeg.exe" } else { "ffmpeg" };
+
+/// 托管 FFmpeg 所在的目录
+///
+/// 位于系统数据目录下，如 Linux 上的 `~/.local/share/video2audio-rs/ffmpeg`
+pub fn managed_ffmpeg_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("video2audio-rs").join("ffmpeg"))
+}
+
+/// 托管 FFmpeg 可执行文件的路径（不保证已下载）
+pub fn managed_ffmpeg_path() -> Option<PathBuf> {
+    managed_ffmpeg_dir().map(|dir| dir.join(FFMPEG_BINARY))
+}
+
+/// 查找可用的 FFmpeg
+///
+/// 优先使用系统 PATH 中的 FFmpeg（返回 `ffmpeg`）；找不到时如果已下载托管版本则返回托管版本的路径。
+/// 两者都不存在时返回 `None`。
+pub fn find_ffmpeg() -> Option<PathBuf> {
+    if find_in_path(FFMPEG_BINARY).is_some() {
+        return Some(PathBuf::from("ffmpeg"));
+    }
+
+    managed_ffmpeg_path().filter(|path| path.is_file())
+}
+
+/// 确定要使用的 FFmpeg
+///
+/// 与 [`find_ffmpeg`] 相同，但找不到时仍返回 `ffmpeg`，由转换时给出未安装的提示。
+pub fn resolve_ffmpeg_path() -> PathBuf {
+    find_ffmpeg().unwrap_or_else(|| PathBuf::from("ffmpeg"))
+}
+
+/// 在 PATH 环境变量的目录中查找可执行文件
+fn find_in_path(binary: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(binary))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(feature = "ffmpeg-download")]
+pub use download::download_ffmpeg;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_managed_ffmpeg_path_in_data_dir() {
+        if let Some(path) = managed_ffmpeg_path() {
+            assert!(path.ends_with(Path::new("video2audio-rs").join("ffmpeg").join(FFMPEG_BINARY)));
+        }
+    }
+}
+
+#[cfg(feature = "ffmpeg-download")]
+mod download {
+    use super::{managed_ffmpeg_dir, FFMPEG_BINARY};
+    use crate::error::{IoResultExt, Result, VideoToAudioError};
+    use crate::i18n::text;
+    use crate::tr;
+    use sha2::{Digest, Sha256};
+    use std::fs::{self, File};
+    use std::io::{self, BufReader, Read, Write};
+    use std::path::{Path, PathBuf};
+
+    /// BtbN/FFmpeg-Builds 的最新静态构建
+    ///
+    /// 最新构建会随上游更新而变化，无法在源代码中固定期望的校验和
+    const BTBN_RELEASE_URL: &str = "https://github.com/BtbN/FFmpeg-Builds/releases/download/latest";
+
+    /// 压缩包格式
+    #[derive(Debug, Clone, Copy)]
+    enum ArchiveKind {
+        TarXz,
+        Zip,
+    }
+
+    /// 当前平台对应的下载来源
+    #[derive(Debug)]
+    struct Release {
+        /// 压缩包地址
+        archive_url: String,
+        /// 校验和文件地址
+        checksum_url: String,
+        /// 校验和文件中对应的条目名，`None` 表示文件中只有一个校验和
+        checksum_entry: Option<String>,
+        /// 压缩包格式
+        kind: ArchiveKind,
+    }
+
+    impl Release {
+        /// 根据当前操作系统和架构选择下载来源
+        fn for_current_platform() -> Result<Self> {
+            let btbn = |name: &str, kind| Self {
+                archive_url: format!("{BTBN_RELEASE_URL}/{name}"),
+                checksum_url: format!("{BTBN_RELEASE_URL}/checksums.sha256"),
+                checksum_entry: Some(name.to_string()),
+                kind,
+            };
+            let macos = |arch: &str| {
+                let archive_url =
+                    format!("https://ffmpeg.martin-riedl.de/redirect/latest/macos/{arch}/release/ffmpeg.zip");
+                Self {
+                    checksum_url: format!("{archive_url}.sha256"),
+                    archive_url,
+                    checksum_entry: None,
+                    kind: ArchiveKind::Zip,
+                }
+            };
+
+            match (std::env::consts::OS, std::env::consts::ARCH) {
+                ("linux", "x86_64") => Ok(btbn("ffmpeg-master-latest-linux64-gpl.tar.xz", ArchiveKind::TarXz)),
+                ("linux", "aarch64") => Ok(btbn("ffmpeg-master-latest-linuxarm64-gpl.tar.xz", ArchiveKind::TarXz)),
+                ("windows", "x86_64") => Ok(btbn("ffmpeg-master-latest-win64-gpl.zip", ArchiveKind::Zip)),
+                ("macos", "x86_64") => Ok(macos("amd64")),
+                ("macos", "aarch64") => Ok(macos("arm64")),
+                (os, arch) => Err(VideoToAudioError::UnsupportedFormat(tr!(
+                    "没有适用于 {os}/{arch} 的 FFmpeg 静态构建，请手动安装 FFmpeg",
+                    "No static FFmpeg build is available for {os}/{arch}, install FFmpeg manually"
+                ))),
+            }
+        }
+    }
+
+    /// 下载 FFmpeg 到应用数据目录
+    ///
+    /// 压缩包下载完成后先校验 SHA-256，再解压出 `ffmpeg` 可执行文件。
+    /// 校验和从同一个下载源获取，只保证下载完整（见模块文档）。
+    /// 已经下载过且 `force` 为 `false` 时直接返回已有的路径。
+    ///
+    /// # 参数
+    ///
+    /// * `force` - 是否重新下载
+    /// * `progress_callback` - 下载进度回调，参数为已下载字节数和总字节数（未知时为 `None`）
+    ///
+    /// # 返回值
+    ///
+    /// 托管 FFmpeg 可执行文件的路径
+    ///
+    /// # 错误
+    ///
+    /// 当前平台没有可用的构建、网络错误、校验失败或解压失败时返回错误
+    pub fn download_ffmpeg<F>(force: bool, mut progress_callback: F) -> Result<PathBuf>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let install_dir = managed_ffmpeg_dir().ok_or_else(|| VideoToAudioError::InvalidPath(
+            text("无法确定应用数据目录", "Cannot determine the application data directory").to_string()
+        ))?;
+        let binary_path = install_dir.join(FFMPEG_BINARY);
+        if binary_path.is_file() && !force {
+            return Ok(binary_path);
+        }
+
+        let release = Release::for_current_platform()?;
+        fs::create_dir_all(&install_dir).context(text("创建 FFmpeg 安装目录", "create FFmpeg install directory"), &install_dir)?;
+
+        let expected = fetch_checksum(&release)?;
+        let archive_path = install_dir.join("download.part");
+        let actual = fetch_archive(&release.archive_url, &archive_path, &mut progress_callback)?;
+        if !actual.eq_ignore_ascii_case(&expected) {
+            let _ = fs::remove_file(&archive_path);
+            return Err(download_error(tr!(
+                "校验失败：期望 SHA-256 为 {expected}，实际为 {actual}",
+                "Checksum mismatch: expected SHA-256 {expected}, got {actual}"
+            )));
+        }
+
+        // 先解压到临时文件再重命名，避免留下不完整的可执行文件
+        let partial_path = install_dir.join(format!("{FFMPEG_BINARY}.part"));
+        let extracted = extract_binary(&archive_path, release.kind, &partial_path);
+        let _ = fs::remove_file(&archive_path);
+        if let Err(e) = extracted {
+            let _ = fs::remove_file(&partial_path);
+            return Err(e);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&partial_path, fs::Permissions::from_mode(0o755))
+                .context(text("设置可执行权限", "set executable permission"), &partial_path)?;
+        }
+        fs::rename(&partial_path, &binary_path).context(text("安装 FFmpeg", "install FFmpeg"), &binary_path)?;
+
+        Ok(binary_path)
+    }
+
+    /// 获取压缩包的 SHA-256
+    fn fetch_checksum(release: &Release) -> Result<String> {
+        let body = ureq::get(&release.checksum_url)
+            .call()
+            .map_err(|e| download_error(tr!("无法获取校验和: {e}", "Cannot fetch the checksum: {e}")))?
+            .into_string()?;
+
+        parse_checksum(&body, release.checksum_entry.as_deref()).ok_or_else(|| download_error(
+            tr!("校验和文件中没有找到对应的条目: {}", "The checksum file has no entry for {}", release.checksum_url)
+        ))
+    }
+
+    /// 解析 `sha256sum` 格式的校验和文件
+    ///
+    /// 每行形如 `<hash>  <文件名>`，未指定文件名时取第一行的哈希值
+    fn parse_checksum(body: &str, entry: Option<&str>) -> Option<String> {
+        body.lines().find_map(|line| {
+            let mut columns = line.split_whitespace();
+            let hash = columns.next()?;
+            let name = columns.next().map(|name| name.trim_start_matches('*'));
+            match entry {
+                Some(entry) if name != Some(entry) => None,
+                _ => Some(hash.to_string()),
+            }
+        })
+    }
+
+    /// 下载压缩包，返回其 SHA-256
+    fn fetch_archive<F>(url: &str, destination: &Path, progress_callback: &mut F) -> Result<String>
+    where
+        F: FnMut(u64, Option<u64>),
+    {
+        let response = ureq::get(url)
+            .call()
+            .map_err(|e| download_error(tr!("下载失败: {e}", "Download failed: {e}")))?;
+        let total = response
+            .header("Content-Length")
+            .and_then(|length| length.parse().ok());
+
+        let mut reader = response.into_reader();
+        let mut file = File::create(destination)?;
+        let mut hasher = Sha256::new();
+        let mut buffer = [0u8; 64 * 1024];
+        let mut downloaded = 0u64;
+
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    let _ = fs::remove_file(destination);
+                    return Err(e.into());
+                }
+            };
+            hasher.update(&buffer[..read]);
+            file.write_all(&buffer[..read])?;
+            downloaded += read as u64;
+            progress_callback(downloaded, total);
+        }
+        file.sync_all()?;
+
+        Ok(hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect())
+    }
+
+    /// 从压缩包中解压出 FFmpeg 可执行文件
+    ///
+    /// 不同构建的目录结构不同，取文件名为 `ffmpeg`（Windows 上为 `ffmpeg.exe`）的第一个条目
+    fn extract_binary(archive_path: &Path, kind: ArchiveKind, destination: &Path) -> Result<()> {
+        let archive = BufReader::new(File::open(archive_path)?);
+        let is_binary = |path: &Path| path.file_name().is_some_and(|name| name == FFMPEG_BINARY);
+
+        match kind {
+            ArchiveKind::TarXz => {
+                let mut archive = tar::Archive::new(xz2::read::XzDecoder::new(archive));
+                for entry in archive.entries()? {
+                    let mut entry = entry?;
+                    if entry.header().entry_type().is_file() && is_binary(&entry.path()?) {
+                        io::copy(&mut entry, &mut File::create(destination)?)?;
+                        return Ok(());
+                    }
+                }
+            }
+            ArchiveKind::Zip => {
+                let mut archive = zip::ZipArchive::new(archive)
+                    .map_err(|e| download_error(tr!("无法读取压缩包: {e}", "Cannot read the archive: {e}")))?;
+                for index in 0..archive.len() {
+                    let mut entry = archive
+                        .by_index(index)
+                        .map_err(|e| download_error(tr!("无法读取压缩包: {e}", "Cannot read the archive: {e}")))?;
+                    let is_match = entry.is_file() && entry.enclosed_name().is_some_and(|path| is_binary(&path));
+                    if is_match {
+                        io::copy(&mut entry, &mut File::create(destination)?)?;
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        Err(download_error(tr!("压缩包中没有找到 {FFMPEG_BINARY}", "{FFMPEG_BINARY} not found in the archive")))
+    }
+
+    fn download_error(message: String) -> VideoToAudioError {
+        VideoToAudioError::from(io::Error::other(message))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_parse_checksum() {
+            let body = "\
+aaa111  ffmpeg-master-latest-linux64-gpl.tar.xz
+bbb222 *ffmpeg-master-latest-win64-gpl.zip
+";
+            assert_eq!(
+                parse_checksum(body, Some("ffmpeg-master-latest-win64-gpl.zip")).as_deref(),
+                Some("bbb222")
+            );
+            assert_eq!(parse_checksum("ccc333\n", None).as_deref(), Some("ccc333"));
+            assert_eq!(parse_checksum(body, Some("missing.zip")), None);
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! # 合并模块
+//!
+//! 把多个视频的音频按顺序拼接为一个输出文件，并在每个源文件的起点插入章节标记，
+//! 适合把系列课程转换为一个有声书式的文件。
+//!
+//! 各个源文件的音频参数（采样率、声道数、编码）可以不同，拼接时统一重新编码，
+//! 因此 AAC 格式也会使用 `aac` 编码器重新编码，而不是直接复制音频流。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::file_processor::{cover_input_args, ffmpeg_path_arg, input_arg, FileProcessor};
+use crate::i18n::text;
+use crate::options::ConversionOptions;
+use crate::probe::{self, Chapter};
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+impl FileProcessor {
+    /// 把多个视频的音频按顺序拼接为一个文件
+    ///
+    /// 每个源文件的起点插入一个以文件名为标题的章节。调用方负责排序，
+    /// 文件按传入的顺序拼接。
+    ///
+    /// # 参数
+    ///
+    /// * `files` - 要合并的源文件
+    /// * `output_path` - 输出文件路径
+    /// * `options` - 转换选项，音轨选择和片段设置对合并无效
+    /// * `cancel_token` - 取消令牌
+    ///
+    /// # 错误
+    ///
+    /// 没有源文件时返回 [`VideoToAudioError::InvalidInput`]；
+    /// 无法读取某个源文件的时长或 FFmpeg 执行失败时返回相应的错误
+    pub fn merge_files(
+        &self,
+        files: &[PathBuf],
+        output_path: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        let options = options.into();
+        let Some(first_file) = files.first() else {
+            return Err(VideoToAudioError::InvalidInput(text("没有要合并的文件", "No files to merge").to_string()));
+        };
+        self.check_ffmpeg_availability()?;
+
+        // 根据每个源文件的时长计算章节位置
+        let mut chapters = Vec::with_capacity(files.len());
+        let mut position = Duration::ZERO;
+        for file in files {
+            if cancel_token.is_cancelled() {
+                return Err(VideoToAudioError::Cancelled);
+            }
+            let duration = probe::probe_duration(self.ffmpeg_path(), file)?;
+            chapters.push(Chapter {
+                start: position,
+                end: position + duration,
+                title: file.file_stem().map(|stem| stem.to_string_lossy().into_owned()),
+            });
+            position += duration;
+        }
+
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).context(text("创建输出目录", "create output directory"), parent)?;
+        }
+
+        // 章节信息通过 FFmpeg 的元数据文件传入，与临时输出文件放在一起
+        let mut metadata_path = Self::partial_output_path(output_path).into_os_string();
+        metadata_path.push(".chapters");
+        let metadata_path = PathBuf::from(metadata_path);
+        fs::write(&metadata_path, ffmetadata(&chapters))?;
+
+        // 源文件中的封面和截取的帧都取自第一个源文件
+        let mut options = options;
+        self.resolve_cover(first_file, &mut options)?;
+        let args = self.merge_args(files, &metadata_path, output_path, &options);
+        let result = self.run_ffmpeg(&args, first_file, output_path, options.timeout, cancel_token);
+        let _ = fs::remove_file(&metadata_path);
+        result?;
+        self.apply_replay_gain(output_path, &options, cancel_token)
+    }
+
+    /// 生成合并用的 FFmpeg 参数
+    fn merge_args(
+        &self,
+        files: &[PathBuf],
+        metadata_path: &Path,
+        output_path: &Path,
+        options: &ConversionOptions,
+    ) -> Vec<OsString> {
+        let mut args: Vec<OsString> = ["-y", "-hide_banner", "-loglevel", "error"].map(OsString::from).into();
+
+        for file in files {
+            args.push("-i".into());
+            args.push(input_arg(file));
+        }
+        args.extend(["-f", "ffmetadata", "-i"].map(OsString::from));
+        args.push(ffmpeg_path_arg(metadata_path));
+        args.extend(cover_input_args(options, &files[0]));
+
+        // 用 concat 滤镜拼接每个输入的第一条音轨，再接上用户指定的滤镜和单遍响度标准化
+        let mut filter: String = (0..files.len()).map(|i| format!("[{i}:a:0]")).collect();
+        filter.push_str(&format!("concat=n={}:v=0:a=1", files.len()));
+        for extra in &options.filter_chain() {
+            filter.push(',');
+            filter.push_str(extra);
+        }
+        filter.push_str("[merged]");
+        args.extend(["-filter_complex".into(), filter.into(), "-map".into(), "[merged]".into()]);
+
+        // 滤镜输出无法直接复制，AAC 格式改用 aac 编码器
+        let encode_options = ConversionOptions {
+            encoder: options
+                .encoder
+                .clone()
+                .or_else(|| options.is_stream_copy().then(|| "aac".to_string())),
+            sample_rate: options.output_sample_rate(),
+            trim_silence: false,
+            filters: Vec::new(),
+            normalize: None,
+            audio_stream: None,
+            start: None,
+            end: None,
+            ..options.clone()
+        };
+        args.extend(encode_options.codec_args().into_iter().map(OsString::from));
+        args.extend(options.cover_args(files.len() + 1).into_iter().map(OsString::from));
+
+        // 章节取自元数据文件，全局元数据取自第一个源文件
+        args.extend(["-map_chapters".into(), files.len().to_string().into()]);
+        args.extend(["-f", options.format.muxer()].map(OsString::from));
+        args.push(ffmpeg_path_arg(&Self::partial_output_path(output_path)));
+        args
+    }
+}
+
+/// 生成 FFmpeg 元数据文件（`ffmetadata` 格式）的内容
+///
+/// 时间以毫秒为单位，标题中的特殊字符按格式要求用反斜杠转义
+pub fn ffmetadata(chapters: &[Chapter]) -> String {
+    let mut content = String::from(";FFMETADATA1\n");
+    for chapter in chapters {
+        content.push_str("[CHAPTER]\nTIMEBASE=1/1000\n");
+        content.push_str(&format!("START={}\n", chapter.start.as_millis()));
+        content.push_str(&format!("END={}\n", chapter.end.as_millis()));
+        if let Some(title) = &chapter.title {
+            let escaped: String = title
+                .chars()
+                .flat_map(|c| match c {
+                    '=' | ';' | '#' | '\\' | '\n' => vec!['\\', c],
+                    c => vec![c],
+                })
+                .collect();
+            content.push_str(&format!("title={escaped}\n"));
+        }
+    }
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_format::AudioFormat;
+
+    #[test]
+    fn test_ffmetadata() {
+        let chapters = [
+            Chapter { start: Duration::ZERO, end: Duration::from_millis(61_500), title: Some("01=intro".to_string()) },
+            Chapter { start: Duration::from_millis(61_500), end: Duration::from_secs(120), title: None },
+        ];
+        assert_eq!(
+            ffmetadata(&chapters),
+            ";FFMETADATA1\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=0\nEND=61500\ntitle=01\\=intro\n\
+             [CHAPTER]\nTIMEBASE=1/1000\nSTART=61500\nEND=120000\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_args_reencode_aac() {
+        let files = [PathBuf::from("a.mp4"), PathBuf::from("b.mp4")];
+        let args = FileProcessor::new().merge_args(
+            &files,
+            Path::new("out.m4a.partial.chapters"),
+            Path::new("out.m4a"),
+            &ConversionOptions::new(AudioFormat::AacCopy).with_filter("loudnorm"),
+        );
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        let filter = args.iter().position(|arg| arg == "-filter_complex").unwrap();
+        assert_eq!(args[filter + 1], "[0:a:0][1:a:0]concat=n=2:v=0:a=1,loudnorm[merged]");
+        assert!(args.windows(2).any(|pair| pair == ["-c:a", "aac"]));
+        assert!(args.windows(2).any(|pair| pair == ["-map_chapters", "2"]));
+        assert!(!args.iter().any(|arg| arg == "-af" || arg == "copy"));
+    }
+}
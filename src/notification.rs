@@ -0,0 +1,107 @@
+//! # 桌面通知模块
+//!
+//! 批处理结束后弹出系统桌面通知，长时间的批处理在后台运行时也不会错过结果。
+//!
+//! 通过各平台自带的工具发送通知：Linux 等使用 `notify-send`，macOS 使用 `osascript`，
+//! Windows 使用 PowerShell 调用系统的 Toast 通知。
+
+use crate::error::{Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::tr;
+use std::process::{Command, Stdio};
+
+/// 通知中显示的应用名称
+const APP_NAME: &str = "video2audio-rs";
+
+/// 生成批处理结束时的通知标题和正文
+pub fn batch_notification(success_count: usize, failure_count: usize, cancelled: bool) -> (String, String) {
+    let title = if cancelled {
+        text("转换已取消", "Conversion cancelled")
+    } else if failure_count > 0 {
+        text("转换完成，部分文件失败", "Conversion finished with failures")
+    } else {
+        text("转换完成", "Conversion finished")
+    };
+    let mut body = tr!("成功 {success_count} 个文件", "{success_count} files succeeded");
+    if failure_count > 0 {
+        body.push_str(&tr!("，失败 {failure_count} 个文件", ", {failure_count} files failed"));
+    }
+    (format!("{APP_NAME}: {title}"), body)
+}
+
+/// 发送桌面通知并等待发送完成
+///
+/// # 错误
+///
+/// 找不到通知工具或工具执行失败时返回 [`VideoToAudioError::MissingDependency`]
+pub fn send_notification(title: &str, body: &str) -> Result<()> {
+    let status = notification_command(title, body)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match status {
+        Ok(status) if status.success() => Ok(()),
+        _ => Err(VideoToAudioError::MissingDependency(
+            text(
+                "无法发送桌面通知，请确认系统提供通知工具（Linux 上为 notify-send）",
+                "Cannot send a desktop notification, make sure the system provides a notification tool (notify-send on Linux)",
+            ).to_string(),
+        )),
+    }
+}
+
+/// 构造当前平台发送通知的命令，标题和正文作为参数或环境变量传递，不需要转义
+#[cfg(target_os = "macos")]
+fn notification_command(title: &str, body: &str) -> Command {
+    let mut command = Command::new("osascript");
+    command
+        .args(["-e", "on run argv", "-e", "display notification (item 2 of argv) with title (item 1 of argv)"])
+        .args(["-e", "end run", title, body]);
+    command
+}
+
+/// 构造当前平台发送通知的命令，标题和正文作为参数或环境变量传递，不需要转义
+#[cfg(windows)]
+fn notification_command(title: &str, body: &str) -> Command {
+    const SCRIPT: &str = "\
+        [Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null; \
+        $xml = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02); \
+        $text = $xml.GetElementsByTagName('text'); \
+        $text.Item(0).AppendChild($xml.CreateTextNode($env:V2A_NOTIFY_TITLE)) > $null; \
+        $text.Item(1).AppendChild($xml.CreateTextNode($env:V2A_NOTIFY_BODY)) > $null; \
+        [Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($env:V2A_NOTIFY_APP).Show([Windows.UI.Notifications.ToastNotification]::new($xml))";
+    let mut command = Command::new("powershell");
+    command
+        .args(["-NoProfile", "-NonInteractive", "-Command", SCRIPT])
+        .env("V2A_NOTIFY_APP", APP_NAME)
+        .env("V2A_NOTIFY_TITLE", title)
+        .env("V2A_NOTIFY_BODY", body);
+    command
+}
+
+/// 构造当前平台发送通知的命令，标题和正文作为参数或环境变量传递，不需要转义
+#[cfg(not(any(target_os = "macos", windows)))]
+fn notification_command(title: &str, body: &str) -> Command {
+    let mut command = Command::new("notify-send");
+    command.arg(format!("--app-name={APP_NAME}")).args(["--", title, body]);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_notification() {
+        assert_eq!(
+            batch_notification(12, 0, false),
+            ("video2audio-rs: 转换完成".to_string(), "成功 12 个文件".to_string())
+        );
+        assert_eq!(
+            batch_notification(10, 2, false),
+            ("video2audio-rs: 转换完成，部分文件失败".to_string(), "成功 10 个文件，失败 2 个文件".to_string())
+        );
+        assert_eq!(batch_notification(3, 0, true).0, "video2audio-rs: 转换已取消");
+    }
+}
@@ -0,0 +1,782 @@
+//! # 转换选项模块
+//!
+//! 将单次转换的全部参数（格式、码率、采样率、滤镜、静音裁剪、响度标准化、元数据、封面、覆盖策略、超时、片段）
+//! 集中到 [`ConversionOptions`] 中，避免转换函数的位置参数不断增加。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::OverwritePolicy;
+use crate::i18n::text;
+use crate::loudness::{Normalization, LOUDNORM_SAMPLE_RATE};
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// 裁剪首尾静音的滤镜
+///
+/// `silenceremove` 只能去除开头的静音，因此把音频反转后再处理一次来去除结尾的静音，
+/// 两端各保留 0.5 秒。反转需要把整段音频缓存在内存中。
+pub const TRIM_SILENCE_FILTER: &str = "silenceremove=start_periods=1:start_threshold=-50dB:start_silence=0.5,\
+     areverse,silenceremove=start_periods=1:start_threshold=-50dB:start_silence=0.5,areverse";
+
+/// 封面来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoverArt {
+    /// 图片文件（JPEG 或 PNG）
+    File(PathBuf),
+
+    /// 截取源视频指定时间的一帧
+    Frame(Duration),
+
+    /// 源文件中嵌入的封面，转换前解析为 [`CoverArt::Stream`]；源文件没有封面时不嵌入
+    Attached,
+
+    /// 源文件中的指定流，值为 FFmpeg 的流序号
+    Stream(usize),
+}
+
+/// 单个格式的默认编码参数，对应配置文件中的 `[formats.<格式>]`
+///
+/// 只在转换选项没有指定对应参数时生效，命令行、配置方案和目录配置中的设置优先。
+///
+/// ```toml
+/// [formats.mp3]
+/// vbr_quality = 2
+///
+/// [formats.opus]
+/// bitrate = "96k"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FormatDefaults {
+    /// 音频码率 (kbps)，配置文件中可以写作 `128` 或 `"128k"`
+    #[serde(with = "bitrate_kbps")]
+    pub bitrate: Option<u32>,
+
+    /// VBR 质量（0 最好，9 最小），只对 MP3 生效，设置了码率时不使用
+    pub vbr_quality: Option<u8>,
+
+    /// 采样率 (Hz)
+    pub sample_rate: Option<u32>,
+}
+
+impl FormatDefaults {
+    /// 把未指定的编码参数设置为默认值
+    pub fn apply(&self, options: ConversionOptions) -> ConversionOptions {
+        ConversionOptions {
+            bitrate: options.bitrate.or(self.bitrate),
+            vbr_quality: options.vbr_quality.or(self.vbr_quality),
+            sample_rate: options.sample_rate.or(self.sample_rate),
+            ..options
+        }
+        .with_reencode_if_needed()
+    }
+}
+
+/// 音质预设，按格式选择码率或 VBR 质量
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QualityPreset {
+    /// 各格式的默认音质（MP3 VBR 0、Opus 192k、M4B 128k）
+    #[default]
+    High,
+
+    /// 兼顾音质和体积（MP3 VBR 4、Opus 128k、M4B 96k）
+    Standard,
+
+    /// 体积最小，适合语音（MP3 VBR 7、Opus 64k、M4B 64k）
+    Small,
+}
+
+impl QualityPreset {
+    /// 所有预设，按音质从高到低排列
+    pub fn all() -> [QualityPreset; 3] {
+        [QualityPreset::High, QualityPreset::Standard, QualityPreset::Small]
+    }
+
+    /// 预设在命令行中的名称，与 `--quality` 的取值相同
+    pub fn name(&self) -> &'static str {
+        match self {
+            QualityPreset::High => "high",
+            QualityPreset::Standard => "standard",
+            QualityPreset::Small => "small",
+        }
+    }
+
+    /// 预设的中文说明
+    pub fn description(&self) -> &'static str {
+        match self {
+            QualityPreset::High => text("高音质 (体积较大)", "High quality (larger files)"),
+            QualityPreset::Standard => text("标准 (兼顾音质和体积)", "Standard (balanced quality and size)"),
+            QualityPreset::Small => text("小体积 (适合语音)", "Small (good for speech)"),
+        }
+    }
+
+    /// 把预设的码率或 VBR 质量应用到转换选项
+    ///
+    /// 已指定码率或 VBR 质量，或直接复制音频流时不做修改
+    pub fn apply(&self, options: ConversionOptions) -> ConversionOptions {
+        if options.bitrate.is_some() || options.vbr_quality.is_some() || options.is_stream_copy() {
+            return options;
+        }
+        match (options.format, self) {
+            (_, QualityPreset::High) => options,
+            (AudioFormat::Mp3, QualityPreset::Standard) => options.with_vbr_quality(4),
+            (AudioFormat::Mp3, QualityPreset::Small) => options.with_vbr_quality(7),
+            (AudioFormat::M4b, QualityPreset::Standard) => options.with_bitrate(96),
+            (_, QualityPreset::Standard) => options.with_bitrate(128),
+            (_, QualityPreset::Small) => options.with_bitrate(64),
+        }
+    }
+}
+
+/// 以 `"128k"` 形式读写码率，读取时也接受整数
+mod bitrate_kbps {
+    use crate::tr;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bitrate {
+        Kbps(u32),
+        Text(String),
+    }
+
+    pub fn serialize<S: Serializer>(bitrate: &Option<u32>, serializer: S) -> Result<S::Ok, S::Error> {
+        match bitrate {
+            Some(kbps) => serializer.serialize_str(&format!("{kbps}k")),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u32>, D::Error> {
+        match Option::<Bitrate>::deserialize(deserializer)? {
+            None => Ok(None),
+            Some(Bitrate::Kbps(kbps)) => Ok(Some(kbps)),
+            Some(Bitrate::Text(text)) => {
+                let digits = text.trim().trim_end_matches(['k', 'K']);
+                digits
+                    .parse()
+                    .map(Some)
+                    .map_err(|_| serde::de::Error::custom(tr!(
+                        "无效的码率: {text}（示例: \"128k\"）",
+                        "Invalid bitrate: {text} (example: \"128k\")"
+                    )))
+            }
+        }
+    }
+}
+
+/// 元数据处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MetadataPolicy {
+    /// 把源文件容器中的全局元数据（标题、艺术家、日期等）复制到输出文件
+    #[default]
+    Keep,
+
+    /// 不写入任何源文件元数据
+    Strip,
+}
+
+/// 转换选项
+///
+/// 可以直接从 [`AudioFormat`] 转换得到使用默认参数的选项，因此接受
+/// `impl Into<ConversionOptions>` 的函数也可以直接传入音频格式。
+///
+/// # 示例
+///
+/// ```rust
+/// use video2audio_rs::{AudioFormat, ConversionOptions, MetadataPolicy};
+/// use std::time::Duration;
+///
+/// let options = ConversionOptions::new(AudioFormat::Opus)
+///     .with_bitrate(128)
+///     .with_sample_rate(48000)
+///     .with_filter("loudnorm")
+///     .with_metadata(MetadataPolicy::Strip)
+///     .with_timeout(Duration::from_secs(600));
+/// assert_eq!(options.format, AudioFormat::Opus);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConversionOptions {
+    /// 目标音频格式
+    pub format: AudioFormat,
+
+    /// 音频编码器，未设置时使用格式的默认编码器
+    ///
+    /// AAC 格式默认直接复制音频流，设置编码器（如 `aac`）后改为重新编码
+    pub encoder: Option<String>,
+
+    /// 音频码率 (kbps)，未设置时使用格式的默认设置
+    pub bitrate: Option<u32>,
+
+    /// MP3 的 VBR 质量（0 最好，9 最小），未设置时为 0；设置了码率时不使用
+    pub vbr_quality: Option<u8>,
+
+    /// 采样率 (Hz)，未设置时保持源采样率
+    pub sample_rate: Option<u32>,
+
+    /// 是否裁剪首尾的静音，在其他滤镜之前执行
+    pub trim_silence: bool,
+
+    /// 音频滤镜，按顺序组成 FFmpeg 的 `-af` 滤镜链
+    pub filters: Vec<String>,
+
+    /// 响度标准化，在其他滤镜之后执行
+    pub normalize: Option<Normalization>,
+
+    /// 元数据处理策略
+    pub metadata: MetadataPolicy,
+
+    /// 覆盖策略，未设置时使用文件处理器的策略
+    pub overwrite_policy: Option<OverwritePolicy>,
+
+    /// 单个文件的转换超时，超时后终止 FFmpeg 并视为失败
+    pub timeout: Option<Duration>,
+
+    /// 要提取的音轨序号（从 0 开始，只计算音频流），未设置时由 FFmpeg 选择默认音轨
+    pub audio_stream: Option<usize>,
+
+    /// 片段的开始时间，未设置时从头开始
+    pub start: Option<Duration>,
+
+    /// 片段的结束时间（相对于源文件开头），未设置时到结尾为止
+    pub end: Option<Duration>,
+
+    /// 写入输出文件的元数据标签，如 `("title", "Intro")`
+    pub tags: Vec<(String, String)>,
+
+    /// 嵌入输出文件的封面，只对支持封面的格式（MP3、M4B）生效
+    pub cover: Option<CoverArt>,
+}
+
+impl ConversionOptions {
+    /// 创建使用默认参数的转换选项
+    pub fn new(format: AudioFormat) -> Self {
+        Self {
+            format,
+            encoder: None,
+            bitrate: None,
+            vbr_quality: None,
+            sample_rate: None,
+            trim_silence: false,
+            filters: Vec::new(),
+            normalize: None,
+            metadata: MetadataPolicy::default(),
+            overwrite_policy: None,
+            timeout: None,
+            audio_stream: None,
+            start: None,
+            end: None,
+            tags: Vec::new(),
+            cover: None,
+        }
+    }
+
+    /// 设置音频编码器（如 `libmp3lame`、`opus`）
+    pub fn with_encoder(mut self, encoder: impl Into<String>) -> Self {
+        self.encoder = Some(encoder.into());
+        self
+    }
+
+    /// 设置音频码率 (kbps)
+    pub fn with_bitrate(mut self, kbps: u32) -> Self {
+        self.bitrate = Some(kbps);
+        self
+    }
+
+    /// 设置 MP3 的 VBR 质量（0 最好，9 最小）
+    pub fn with_vbr_quality(mut self, quality: u8) -> Self {
+        self.vbr_quality = Some(quality);
+        self
+    }
+
+    /// 设置采样率 (Hz)
+    pub fn with_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// 设置是否裁剪首尾的静音，参见 [`TRIM_SILENCE_FILTER`]
+    pub fn with_trim_silence(mut self, trim_silence: bool) -> Self {
+        self.trim_silence = trim_silence;
+        self
+    }
+
+    /// 追加一个音频滤镜
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filters.push(filter.into());
+        self
+    }
+
+    /// 设置响度标准化
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalize = Some(normalization);
+        self
+    }
+
+    /// 设置元数据处理策略
+    pub fn with_metadata(mut self, metadata: MetadataPolicy) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// 设置覆盖策略，覆盖文件处理器的设置
+    pub fn with_overwrite_policy(mut self, overwrite_policy: OverwritePolicy) -> Self {
+        self.overwrite_policy = Some(overwrite_policy);
+        self
+    }
+
+    /// 设置单个文件的转换超时
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 设置要提取的音轨序号（从 0 开始，只计算音频流）
+    pub fn with_audio_stream(mut self, audio_stream: usize) -> Self {
+        self.audio_stream = Some(audio_stream);
+        self
+    }
+
+    /// 只转换源文件中的一个片段
+    pub fn with_segment(mut self, start: Option<Duration>, end: Option<Duration>) -> Self {
+        self.start = start;
+        self.end = end;
+        self
+    }
+
+    /// 添加一个元数据标签，后添加的同名标签覆盖先添加的
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// 设置封面图片
+    pub fn with_cover(self, cover: impl Into<PathBuf>) -> Self {
+        self.with_cover_art(CoverArt::File(cover.into()))
+    }
+
+    /// 设置封面来源
+    pub fn with_cover_art(mut self, cover: CoverArt) -> Self {
+        self.cover = Some(cover);
+        self
+    }
+
+    /// 实际嵌入的封面
+    ///
+    /// 目标格式不支持封面，或 [`CoverArt::Attached`] 尚未解析时为 `None`
+    pub fn cover_art(&self) -> Option<&CoverArt> {
+        self.cover
+            .as_ref()
+            .filter(|cover| self.format.supports_cover() && **cover != CoverArt::Attached)
+    }
+
+    /// 生成嵌入封面的 FFmpeg 参数
+    ///
+    /// `input_index` 是封面图片或截取封面的源文件在 FFmpeg 输入中的序号，
+    /// 封面取自源文件中的流时不使用
+    pub fn cover_args(&self, input_index: usize) -> Vec<String> {
+        let (stream, codec): (String, &[&str]) = match self.cover_art() {
+            None | Some(CoverArt::Attached) => return Vec::new(),
+            Some(CoverArt::File(_)) => (format!("{input_index}:v:0"), &["-c:v", "copy"]),
+            // 截取的帧重新编码为 JPEG，只保留一帧
+            Some(CoverArt::Frame(_)) => (format!("{input_index}:v:0"), &["-frames:v", "1", "-c:v", "mjpeg"]),
+            Some(CoverArt::Stream(index)) => (format!("0:{index}"), &["-c:v", "copy"]),
+        };
+        let mut args = vec!["-map".to_string(), stream];
+        args.extend(codec.iter().map(|arg| arg.to_string()));
+        args.extend(["-disposition:v:0", "attached_pic"].map(String::from));
+        args
+    }
+
+    /// 是否直接复制音频流而不重新编码
+    pub fn is_stream_copy(&self) -> bool {
+        self.format == AudioFormat::AacCopy && self.encoder.is_none()
+    }
+
+    /// 设置了滤镜、码率或采样率时，直接复制音频流的 AAC 改用 `aac` 编码器重新编码
+    pub fn with_reencode_if_needed(mut self) -> Self {
+        if self.is_stream_copy()
+            && (!self.filter_chain().is_empty() || self.bitrate.is_some() || self.sample_rate.is_some())
+        {
+            self.encoder = Some("aac".to_string());
+        }
+        self
+    }
+
+    /// 响度标准化之前的滤镜：静音裁剪和用户滤镜
+    pub fn processing_filters(&self) -> Vec<String> {
+        let mut filters = Vec::with_capacity(self.filters.len() + 1);
+        if self.trim_silence {
+            filters.push(TRIM_SILENCE_FILTER.to_string());
+        }
+        filters.extend(self.filters.iter().cloned());
+        filters
+    }
+
+    /// 完整的滤镜链：静音裁剪、用户滤镜，最后是响度标准化
+    pub fn filter_chain(&self) -> Vec<String> {
+        let mut filters = self.processing_filters();
+        if let Some(normalization) = &self.normalize {
+            filters.push(normalization.filter());
+        }
+        filters
+    }
+
+    /// 输出的采样率
+    ///
+    /// 未指定时保持源采样率；启用响度标准化时 `loudnorm` 会改变采样率，
+    /// 因此使用 [`LOUDNORM_SAMPLE_RATE`]
+    pub fn output_sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+            .or_else(|| self.normalize.as_ref().map(|_| LOUDNORM_SAMPLE_RATE))
+    }
+
+    /// 片段的时长，未设置结束时间时为 `None`
+    pub fn segment_duration(&self) -> Option<Duration> {
+        self.end.map(|end| end.saturating_sub(self.start.unwrap_or_default()))
+    }
+
+    /// 生成写在输入文件之前的 FFmpeg 参数
+    ///
+    /// 开始时间写在输入前，FFmpeg 会直接定位到该位置，而不是从头解码
+    pub fn input_args(&self) -> Vec<String> {
+        match self.start {
+            Some(start) => vec!["-ss".to_string(), format_seconds(start)],
+            None => Vec::new(),
+        }
+    }
+
+    /// 生成编码相关的 FFmpeg 参数（不含输入输出）
+    ///
+    /// AAC 直接复制音频流时不会重新编码，码率、采样率和滤镜均被忽略。
+    pub fn codec_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = Vec::new();
+
+        if let Some(audio_stream) = self.audio_stream {
+            args.extend(["-map".to_string(), format!("0:a:{audio_stream}")]);
+        }
+
+        // 显式指定元数据来源，避免附加的封面或章节输入被当作元数据来源
+        let metadata_source = match self.metadata {
+            MetadataPolicy::Keep => "0",
+            MetadataPolicy::Strip => "-1",
+        };
+        args.extend(["-map_metadata".to_string(), metadata_source.to_string()]);
+        for (key, value) in &self.tags {
+            args.extend(["-metadata".to_string(), format!("{key}={value}")]);
+        }
+
+        if self.start.is_some() || self.end.is_some() {
+            // 源文件的章节时间与片段对不上，不再写入输出文件
+            args.extend(["-map_chapters".to_string(), "-1".to_string()]);
+        }
+        if let Some(duration) = self.segment_duration() {
+            // 开始时间写在输入前时输出的时间戳从 0 开始，因此使用时长而不是结束时间
+            args.extend(["-t".to_string(), format_seconds(duration)]);
+        }
+
+        match (self.format, self.encoder.as_deref(), self.bitrate) {
+            (AudioFormat::AacCopy, None, _) => args.extend(self.format.ffmpeg_args().into_iter().map(String::from)),
+            (_, Some(encoder), bitrate) => {
+                // 备选编码器不一定支持 VBR 质量参数，统一使用固定码率
+                let default_kbps = if self.format == AudioFormat::Mp3 { 320 } else { 192 };
+                args.extend(["-c:a".to_string(), encoder.to_string()]);
+                args.extend(["-b:a".to_string(), format!("{}k", bitrate.unwrap_or(default_kbps))]);
+                // FFmpeg 内置的 opus 编码器仍是实验性的，且只支持 48kHz
+                if encoder == "opus" {
+                    args.extend(["-strict", "-2"].map(String::from));
+                    if self.output_sample_rate().is_none() {
+                        args.extend(["-ar", "48000"].map(String::from));
+                    }
+                }
+            }
+            (AudioFormat::Mp3, None, Some(kbps)) => {
+                args.extend(["-b:a".to_string(), format!("{kbps}k")]);
+            }
+            (AudioFormat::Mp3, None, None) => {
+                args.extend(["-q:a".to_string(), self.vbr_quality.unwrap_or(0).to_string()]);
+            }
+            (AudioFormat::Opus, None, Some(kbps)) => {
+                args.extend(["-c:a", "libopus", "-b:a"].map(String::from));
+                args.push(format!("{kbps}k"));
+            }
+            (AudioFormat::M4b, None, Some(kbps)) => {
+                args.extend(["-c:a", "aac", "-b:a"].map(String::from));
+                args.push(format!("{kbps}k"));
+            }
+            _ => args.extend(self.format.ffmpeg_args().into_iter().map(String::from)),
+        }
+
+        if !self.is_stream_copy() {
+            if let Some(sample_rate) = self.output_sample_rate() {
+                args.extend(["-ar".to_string(), sample_rate.to_string()]);
+            }
+            let filters = self.filter_chain();
+            if !filters.is_empty() {
+                args.extend(["-af".to_string(), filters.join(",")]);
+            }
+        }
+
+        args
+    }
+}
+
+/// 解析源文件中的时间位置
+///
+/// 支持 `01:10:00`、`05:30.5` 这样的时钟格式，不带单位的秒数 `90`，
+/// 以及 `1h10m` 这样的时长写法。
+///
+/// # 错误
+///
+/// 格式无效时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_timestamp(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    let invalid = || VideoToAudioError::InvalidInput(tr!(
+        "无效的时间: {input}（示例: 00:05:00、90、1h10m）",
+        "Invalid time: {input} (examples: 00:05:00, 90, 1h10m)",
+    ));
+
+    if input.contains(':') {
+        let parts: Vec<&str> = input.split(':').collect();
+        if parts.len() > 3 {
+            return Err(invalid());
+        }
+        let (seconds, minutes_and_hours) = parts.split_last().ok_or_else(invalid)?;
+        let mut total = seconds
+            .parse::<f64>()
+            .ok()
+            .filter(|seconds| seconds.is_finite() && *seconds >= 0.0 && *seconds < 60.0)
+            .ok_or_else(invalid)?;
+        for (unit, part) in minutes_and_hours.iter().rev().enumerate() {
+            let value = part.parse::<u64>().map_err(|_| invalid())?;
+            total += value as f64 * 60f64.powi(unit as i32 + 1);
+        }
+        return Ok(Duration::from_secs_f64(total));
+    }
+
+    match input.parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(Duration::from_secs_f64(seconds)),
+        Ok(_) => Err(invalid()),
+        Err(_) => humantime::parse_duration(input).map_err(|_| invalid()),
+    }
+}
+
+/// 解析 `KEY=VALUE` 形式的元数据标签
+///
+/// 值可以为空，表示清除该标签；值中可以包含 `=`。
+///
+/// # 错误
+///
+/// 缺少 `=` 或键为空时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_tag(input: &str) -> Result<(String, String)> {
+    match input.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(VideoToAudioError::InvalidInput(tr!(
+            "无效的标签: {input}（示例: artist=\"Foo\"）",
+            "Invalid tag: {input} (example: artist=\"Foo\")",
+        ))),
+    }
+}
+
+/// 解析封面来源
+///
+/// `attached` 表示使用源文件中嵌入的封面，`frame:<时间>` 表示截取源视频在该时间的一帧
+/// （时间格式同 [`parse_timestamp`]），其他值视为图片文件路径。
+///
+/// # 错误
+///
+/// 截取时间无效时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_cover(input: &str) -> Result<CoverArt> {
+    if input == "attached" {
+        return Ok(CoverArt::Attached);
+    }
+    match input.strip_prefix("frame:") {
+        Some(timestamp) => Ok(CoverArt::Frame(parse_timestamp(timestamp)?)),
+        None => Ok(CoverArt::File(PathBuf::from(input))),
+    }
+}
+
+/// 把时间格式化为 FFmpeg 接受的秒数，精确到毫秒
+pub(crate) fn format_seconds(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64())
+}
+
+impl From<AudioFormat> for ConversionOptions {
+    fn from(format: AudioFormat) -> Self {
+        Self::new(format)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_options_match_format_args() {
+        let options = ConversionOptions::from(AudioFormat::Mp3);
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-q:a", "0"]);
+    }
+
+    #[test]
+    fn test_format_defaults_fill_unset_options() {
+        let mp3: FormatDefaults = serde_json::from_str(r#"{"vbr_quality": 2}"#).unwrap();
+        let options = mp3.apply(ConversionOptions::new(AudioFormat::Mp3));
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-q:a", "2"]);
+
+        // 指定码率时不使用 VBR 质量
+        let options = mp3.apply(ConversionOptions::new(AudioFormat::Mp3).with_bitrate(256));
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-b:a", "256k"]);
+
+        let m4b: FormatDefaults = serde_json::from_str(r#"{"bitrate": "64k"}"#).unwrap();
+        assert_eq!(m4b.bitrate, Some(64));
+        let options = m4b.apply(ConversionOptions::new(AudioFormat::M4b));
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-c:a", "aac", "-b:a", "64k"]);
+        assert_eq!(serde_json::to_string(&m4b).unwrap(), r#"{"bitrate":"64k","vbr_quality":null,"sample_rate":null}"#);
+
+        assert!(serde_json::from_str::<FormatDefaults>(r#"{"bitrate": "fast"}"#).is_err());
+    }
+
+    #[test]
+    fn test_quality_preset() {
+        let options = QualityPreset::Standard.apply(ConversionOptions::new(AudioFormat::Mp3));
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-q:a", "4"]);
+        let options = QualityPreset::Small.apply(ConversionOptions::new(AudioFormat::Opus));
+        assert_eq!(options.bitrate, Some(64));
+        assert_eq!(QualityPreset::High.apply(ConversionOptions::new(AudioFormat::Opus)).bitrate, None);
+
+        // 已指定码率或直接复制音频流时不修改
+        let options = QualityPreset::Small.apply(ConversionOptions::new(AudioFormat::Mp3).with_bitrate(192));
+        assert_eq!(options.bitrate, Some(192));
+        assert_eq!(QualityPreset::Small.apply(ConversionOptions::new(AudioFormat::AacCopy)).bitrate, None);
+    }
+
+    #[test]
+    fn test_codec_args_with_overrides() {
+        let options = ConversionOptions::new(AudioFormat::Opus)
+            .with_bitrate(96)
+            .with_sample_rate(48000)
+            .with_filter("highpass=f=80")
+            .with_filter("loudnorm")
+            .with_metadata(MetadataPolicy::Strip);
+
+        assert_eq!(
+            options.codec_args(),
+            ["-map_metadata", "-1", "-c:a", "libopus", "-b:a", "96k", "-ar", "48000", "-af", "highpass=f=80,loudnorm"]
+        );
+    }
+
+    #[test]
+    fn test_codec_args_with_fallback_encoder() {
+        let options = ConversionOptions::new(AudioFormat::Opus).with_encoder("opus");
+        assert_eq!(
+            options.codec_args(),
+            ["-map_metadata", "0", "-c:a", "opus", "-b:a", "192k", "-strict", "-2", "-ar", "48000"]
+        );
+    }
+
+    #[test]
+    fn test_codec_args_select_audio_stream() {
+        let options = ConversionOptions::new(AudioFormat::AacCopy).with_audio_stream(1);
+        assert_eq!(options.codec_args(), ["-map", "0:a:1", "-map_metadata", "0", "-c:a", "copy"]);
+    }
+
+    #[test]
+    fn test_normalization_args() {
+        let options = ConversionOptions::new(AudioFormat::Mp3)
+            .with_filter("highpass=f=80")
+            .with_normalization(Normalization::new(Default::default()));
+        assert_eq!(
+            options.codec_args(),
+            ["-map_metadata", "0", "-q:a", "0", "-ar", "48000", "-af", "highpass=f=80,loudnorm=I=-16:TP=-1.5:LRA=11"]
+        );
+    }
+
+    #[test]
+    fn test_trim_silence_runs_first() {
+        let options = ConversionOptions::new(AudioFormat::Mp3)
+            .with_filter("highpass=f=80")
+            .with_trim_silence(true);
+        let filters = options.filter_chain();
+        assert_eq!(filters, [TRIM_SILENCE_FILTER, "highpass=f=80"]);
+        assert!(filters[0].starts_with("silenceremove=") && filters[0].ends_with(",areverse"));
+    }
+
+    #[test]
+    fn test_aac_with_encoder_reencodes() {
+        let mut options = ConversionOptions::new(AudioFormat::AacCopy).with_filter("loudnorm");
+        options.encoder = Some("aac".to_string());
+        assert!(!options.is_stream_copy());
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-c:a", "aac", "-b:a", "192k", "-af", "loudnorm"]);
+    }
+
+    #[test]
+    fn test_cover_only_for_supported_formats() {
+        let m4b = ConversionOptions::new(AudioFormat::M4b).with_cover("cover.jpg");
+        assert_eq!(m4b.cover_art(), Some(&CoverArt::File(PathBuf::from("cover.jpg"))));
+        assert_eq!(m4b.cover_args(1), ["-map", "1:v:0", "-c:v", "copy", "-disposition:v:0", "attached_pic"]);
+
+        let opus = ConversionOptions::new(AudioFormat::Opus).with_cover("cover.jpg");
+        assert_eq!(opus.cover_art(), None);
+        assert!(opus.cover_args(1).is_empty());
+    }
+
+    #[test]
+    fn test_cover_from_source() {
+        let frame = ConversionOptions::new(AudioFormat::Mp3).with_cover_art(parse_cover("frame:00:00:05").unwrap());
+        assert_eq!(frame.cover, Some(CoverArt::Frame(Duration::from_secs(5))));
+        assert_eq!(
+            frame.cover_args(1),
+            ["-map", "1:v:0", "-frames:v", "1", "-c:v", "mjpeg", "-disposition:v:0", "attached_pic"]
+        );
+
+        let attached = ConversionOptions::new(AudioFormat::Mp3).with_cover_art(parse_cover("attached").unwrap());
+        assert_eq!(attached.cover_art(), None);
+        let resolved = attached.with_cover_art(CoverArt::Stream(2));
+        assert_eq!(resolved.cover_args(1)[..2], ["-map", "0:2"]);
+
+        assert_eq!(parse_cover("art/cover.png").unwrap(), CoverArt::File(PathBuf::from("art/cover.png")));
+        assert!(parse_cover("frame:soon").is_err());
+    }
+
+    #[test]
+    fn test_segment_and_tag_args() {
+        let options = ConversionOptions::new(AudioFormat::AacCopy)
+            .with_segment(Some(Duration::from_secs(60)), Some(Duration::from_millis(90_500)))
+            .with_tag("title", "Intro");
+        assert_eq!(options.input_args(), ["-ss", "60.000"]);
+        assert_eq!(
+            options.codec_args(),
+            ["-map_metadata", "0", "-metadata", "title=Intro", "-map_chapters", "-1", "-t", "30.500", "-c:a", "copy"]
+        );
+    }
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(parse_tag("artist=Foo").unwrap(), ("artist".to_string(), "Foo".to_string()));
+        assert_eq!(parse_tag("comment=a=b").unwrap(), ("comment".to_string(), "a=b".to_string()));
+        assert_eq!(parse_tag("album=").unwrap(), ("album".to_string(), String::new()));
+        assert!(parse_tag("artist").is_err());
+        assert!(parse_tag("=Foo").is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("01:10:00").unwrap(), Duration::from_secs(4200));
+        assert_eq!(parse_timestamp("05:30.5").unwrap(), Duration::from_millis(330_500));
+        assert_eq!(parse_timestamp("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_timestamp("1h10m").unwrap(), Duration::from_secs(4200));
+        assert!(parse_timestamp("1:75").is_err());
+        assert!(parse_timestamp("1:2:3:4").is_err());
+        assert!(parse_timestamp("soon").is_err());
+    }
+
+    #[test]
+    fn test_stream_copy_ignores_encoding_options() {
+        let options = ConversionOptions::new(AudioFormat::AacCopy)
+            .with_bitrate(128)
+            .with_filter("loudnorm");
+        assert_eq!(options.codec_args(), ["-map_metadata", "0", "-c:a", "copy"]);
+    }
+}
@@ -0,0 +1,73 @@
+//! # 播放列表模块
+//!
+//! 批处理结束后在输出目录中写入 UTF-8 编码的 M3U 播放列表，
+//! 方便把整个输出目录复制到手机或导入播放器。
+
+use crate::error::Result;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// 播放列表的文件名
+pub const PLAYLIST_FILE_NAME: &str = "audio_exports.m3u8";
+
+/// 生成播放列表的内容
+///
+/// 输出目录中的文件使用以 `/` 分隔的相对路径，其他位置的文件保留原路径。
+/// 文件按传入的顺序列出。
+pub fn playlist_content(output_dir: &Path, files: &[PathBuf]) -> String {
+    let mut content = String::from("#EXTM3U\n");
+    for file in files {
+        content.push_str(&relative_entry(output_dir, file));
+        content.push('\n');
+    }
+    content
+}
+
+/// 生成清单中使用的路径：`base` 中的文件为以 `/` 分隔的相对路径，其他文件保留原路径
+pub(crate) fn relative_entry(base: &Path, file: &Path) -> String {
+    match file.strip_prefix(base) {
+        Ok(relative) => relative
+            .components()
+            .filter_map(|component| match component {
+                Component::Normal(part) => Some(part.to_string_lossy()),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("/"),
+        Err(_) => file.to_string_lossy().into_owned(),
+    }
+}
+
+/// 在输出目录中写入播放列表，已存在时覆盖
+///
+/// # 返回值
+///
+/// 播放列表文件的路径
+///
+/// # 错误
+///
+/// 无法写入文件时返回 I/O 错误
+pub fn write_playlist(output_dir: &Path, files: &[PathBuf]) -> Result<PathBuf> {
+    let path = output_dir.join(PLAYLIST_FILE_NAME);
+    fs::write(&path, playlist_content(output_dir, files))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playlist_content() {
+        let output_dir = Path::new("out");
+        let files = [
+            output_dir.join("Live/01 Intro.mp3"),
+            output_dir.join("Étude.mp3"),
+            PathBuf::from("/elsewhere/clip.mp3"),
+        ];
+        assert_eq!(
+            playlist_content(output_dir, &files),
+            "#EXTM3U\nLive/01 Intro.mp3\nÉtude.mp3\n/elsewhere/clip.mp3\n"
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! # 防止系统休眠模块
+//!
+//! 批处理期间阻止系统进入睡眠，避免笔记本在批处理中途休眠、留下不完整的输出文件。
+//!
+//! - Linux：通过 `systemd-inhibit` 持有 logind 的休眠抑制锁
+//! - macOS：运行 `caffeinate -i`
+//! - Windows：调用 `SetThreadExecutionState`
+//!
+//! 只阻止系统休眠，不阻止屏幕关闭或锁屏。
+
+use crate::error::{Result, VideoToAudioError};
+use crate::i18n::text;
+
+/// 休眠抑制锁，释放（drop）时恢复系统的休眠设置
+#[derive(Debug)]
+pub struct SleepInhibitor {
+    #[cfg(not(windows))]
+    child: std::process::Child,
+}
+
+impl SleepInhibitor {
+    /// 获取休眠抑制锁
+    ///
+    /// # 参数
+    ///
+    /// * `reason` - 显示给系统的原因（Linux 上可通过 `systemd-inhibit --list` 查看）
+    ///
+    /// # 错误
+    ///
+    /// 当前系统没有可用的休眠抑制工具时返回 [`VideoToAudioError::MissingDependency`]
+    #[cfg(not(windows))]
+    pub fn acquire(reason: &str) -> Result<Self> {
+        use std::process::{Command, Stdio};
+
+        // 子进程在本进程退出时自动结束，即使本进程被强制终止也不会残留抑制锁
+        let pid = std::process::id().to_string();
+        let mut command = if cfg!(target_os = "macos") {
+            let mut command = Command::new("caffeinate");
+            command.args(["-i", "-w", &pid]);
+            command
+        } else {
+            let mut command = Command::new("systemd-inhibit");
+            command
+                .args(["--what=sleep:idle", "--who=video2audio-rs", "--mode=block"])
+                .arg(format!("--why={reason}"))
+                .args(["tail", &format!("--pid={pid}"), "-f", "/dev/null"]);
+            command
+        };
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|_| VideoToAudioError::MissingDependency(Self::missing_tool_message()))?;
+
+        // 工具启动后立即退出说明无法获取抑制锁（如没有 logind）
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        if let Ok(Some(_)) = child.try_wait() {
+            return Err(VideoToAudioError::MissingDependency(Self::missing_tool_message()));
+        }
+        Ok(Self { child })
+    }
+
+    /// 获取休眠抑制锁
+    ///
+    /// 抑制锁与当前线程绑定，需要在同一线程中释放。
+    ///
+    /// # 参数
+    ///
+    /// * `reason` - 显示给系统的原因，Windows 上不使用
+    ///
+    /// # 错误
+    ///
+    /// 系统调用失败时返回 [`VideoToAudioError::MissingDependency`]
+    #[cfg(windows)]
+    pub fn acquire(_reason: &str) -> Result<Self> {
+        use windows_sys::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS, ES_SYSTEM_REQUIRED};
+
+        // SAFETY: SetThreadExecutionState 只修改当前线程的电源请求状态，没有指针参数
+        if unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED) } == 0 {
+            return Err(VideoToAudioError::MissingDependency(Self::missing_tool_message()));
+        }
+        Ok(Self {})
+    }
+
+    fn missing_tool_message() -> String {
+        if cfg!(target_os = "macos") {
+            text("无法阻止系统休眠: caffeinate 不可用", "Cannot prevent system sleep: caffeinate is unavailable").to_string()
+        } else if cfg!(windows) {
+            text(
+                "无法阻止系统休眠: SetThreadExecutionState 调用失败",
+                "Cannot prevent system sleep: SetThreadExecutionState failed",
+            ).to_string()
+        } else {
+            text(
+                "无法阻止系统休眠: 需要 systemd-inhibit（systemd-logind）",
+                "Cannot prevent system sleep: systemd-inhibit (systemd-logind) is required",
+            ).to_string()
+        }
+    }
+}
+
+impl Drop for SleepInhibitor {
+    #[cfg(not(windows))]
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+
+    #[cfg(windows)]
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS};
+
+        // SAFETY: 同 acquire，只清除当前线程的电源请求
+        unsafe {
+            SetThreadExecutionState(ES_CONTINUOUS);
+        }
+    }
+}
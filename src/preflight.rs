@@ -0,0 +1,132 @@
+//! # 预检模块
+//!
+//! 在批量转换开始前快速检查所有输入文件，把有问题的文件提前报告出来并从本次转换中排除，
+//! 而不是转换到第 4,800 个文件时才因为一个无法读取的文件失败。
+//!
+//! 每个文件依次检查：
+//! 1. 文件存在且大小不为 0
+//! 2. 当前用户可以读取文件
+//! 3. ffprobe 能够解析文件头，并且文件中有音频流（找不到 ffprobe 时跳过这一步）
+//!
+//! 发现的问题使用与转换失败相同的错误类型，便于显示错误代码和写入失败清单。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::ffmpeg_command;
+use crate::i18n::text;
+use crate::probe::{ffprobe_path, probe_audio_streams};
+use crate::remote;
+use crate::tr;
+use rayon::prelude::*;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+/// 输入文件的预检
+#[derive(Debug, Clone)]
+pub struct Preflight {
+    ffmpeg_path: PathBuf,
+    probe: bool,
+}
+
+impl Preflight {
+    /// 创建预检，ffprobe 可以运行时才检查文件头
+    pub fn new(ffmpeg_path: &Path) -> Self {
+        let probe = ffmpeg_command(&ffprobe_path(ffmpeg_path))
+            .arg("-version")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success());
+        Self { ffmpeg_path: ffmpeg_path.to_path_buf(), probe }
+    }
+
+    /// 是否用 ffprobe 检查文件头
+    pub fn probes_headers(&self) -> bool {
+        self.probe
+    }
+
+    /// 检查单个文件，远程地址不检查
+    ///
+    /// # 错误
+    ///
+    /// 文件不存在时返回 [`VideoToAudioError::InvalidPath`]，大小为 0 或无法解析时返回
+    /// [`VideoToAudioError::CorruptInput`]，无法读取时返回 [`VideoToAudioError::PermissionDenied`]，
+    /// 没有音频流时返回 [`VideoToAudioError::NoAudioStream`]
+    pub fn check(&self, file: &Path) -> Result<()> {
+        if remote::is_remote_url(file) {
+            return Ok(());
+        }
+
+        let metadata = fs::metadata(file)
+            .map_err(|_| VideoToAudioError::InvalidPath(tr!(
+                "源文件不存在: {}",
+                "Source file does not exist: {}",
+                file.display(),
+            )))?;
+        if metadata.len() == 0 {
+            return Err(VideoToAudioError::CorruptInput(tr!("文件大小为 0: {}", "The file is empty: {}", file.display())));
+        }
+
+        File::open(file).map_err(|e| match e.kind() {
+            io::ErrorKind::PermissionDenied => {
+                VideoToAudioError::PermissionDenied(tr!(
+                    "无法读取源文件: {}",
+                    "Cannot read the source file: {}",
+                    file.display(),
+                ))
+            }
+            _ => VideoToAudioError::io(text("读取源文件", "read source file"), file, e),
+        })?;
+
+        if self.probe {
+            let streams = probe_audio_streams(&self.ffmpeg_path, file).map_err(|e| match e {
+                // ffprobe 的错误输出包含在错误信息中，按转换失败的规则判断原因
+                VideoToAudioError::FfmpegError(message) => VideoToAudioError::from_ffmpeg_stderr(&message.clone(), message),
+                e => e,
+            })?;
+            if streams.is_empty() {
+                return Err(VideoToAudioError::NoAudioStream(tr!("没有音频流: {}", "No audio stream: {}", file.display())));
+            }
+        }
+        Ok(())
+    }
+
+    /// 并行检查全部文件，按输入顺序返回有问题的文件和原因
+    ///
+    /// 取消后不再检查剩余的文件
+    pub fn run(&self, files: &[PathBuf], cancel_token: &CancellationToken) -> Vec<(PathBuf, VideoToAudioError)> {
+        files
+            .par_iter()
+            .filter(|_| !cancel_token.is_cancelled())
+            .filter_map(|file| self.check(file).err().map(|e| (file.clone(), e)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preflight_reports_problem_files_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let empty = temp_dir.path().join("empty.mp4");
+        let good = temp_dir.path().join("good.mp4");
+        let missing = temp_dir.path().join("missing.mp4");
+        fs::write(&empty, "").unwrap();
+        fs::write(&good, "video").unwrap();
+
+        // 不检查文件头，只检查大小和读取权限
+        let preflight = Preflight { ffmpeg_path: PathBuf::from("ffmpeg"), probe: false };
+        let issues = preflight.run(&[empty.clone(), good, missing.clone()], &CancellationToken::new());
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].0, empty);
+        assert!(matches!(issues[0].1, VideoToAudioError::CorruptInput(_)));
+        assert_eq!(issues[1].0, missing);
+        assert!(matches!(issues[1].1, VideoToAudioError::InvalidPath(_)));
+    }
+}
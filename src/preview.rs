@@ -0,0 +1,113 @@
+//! # 预览模块
+//!
+//! 批量转换前只转换第一个文件开头的一段，并用 ffplay 或系统默认的播放器试听，
+//! 确认码率等设置合适后再开始完整转换，不必因为选错码率把整批文件重新转换一遍。
+//!
+//! 预览文件保存在系统临时目录的 `video2audio-preview` 文件夹中，不写入输出目录。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::file_processor::{ffmpeg_command, FileProcessor, OverwritePolicy};
+use crate::i18n::text;
+use crate::options::ConversionOptions;
+use crate::probe::companion_tool_path;
+use crate::tr;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// 预览片段的长度
+pub const PREVIEW_LENGTH: Duration = Duration::from_secs(30);
+
+/// 保存预览文件的文件夹
+pub fn preview_dir() -> PathBuf {
+    std::env::temp_dir().join("video2audio-preview")
+}
+
+/// 转换源文件开头（设置了片段时为片段开头）的一段作为预览
+///
+/// # 返回值
+///
+/// 预览文件路径，同名的旧预览文件会被覆盖
+///
+/// # 错误
+///
+/// 转换失败时返回相应的错误
+pub fn create_preview(
+    processor: &FileProcessor,
+    source_file: &Path,
+    options: &ConversionOptions,
+    cancel_token: &CancellationToken,
+) -> Result<PathBuf> {
+    processor.convert_single_file_with_cancel(source_file, &preview_dir(), preview_options(options), cancel_token)
+}
+
+/// 只转换 [`PREVIEW_LENGTH`] 长的片段，片段本身更短时保持不变
+fn preview_options(options: &ConversionOptions) -> ConversionOptions {
+    let mut options = options.clone().with_overwrite_policy(OverwritePolicy::Overwrite);
+    let end = options.start.unwrap_or_default() + PREVIEW_LENGTH;
+    options.end = Some(options.end.map_or(end, |current| current.min(end)));
+    options
+}
+
+/// 播放预览文件
+///
+/// 优先使用随 FFmpeg 一起安装的 ffplay，在终端中播放完毕（或按 `q`）后返回；
+/// 找不到 ffplay 时用系统默认的播放器打开
+///
+/// # 错误
+///
+/// ffplay 和系统默认的打开方式都无法执行时返回 I/O 错误
+pub fn play(ffmpeg_path: &Path, file: &Path) -> Result<()> {
+    let ffplay = ffmpeg_command(&companion_tool_path(ffmpeg_path, "ffplay"))
+        .args(["-hide_banner", "-loglevel", "error", "-nodisp", "-autoexit"])
+        .arg(file)
+        .status();
+    match ffplay {
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => open_with_default_player(file).context(text(
+            "打开预览文件",
+            "open preview file",
+        ), file),
+        Err(e) => Err(VideoToAudioError::io(text("播放预览文件", "play preview file"), file, e)),
+    }
+}
+
+/// 用系统默认的播放器打开文件，不等待播放结束
+fn open_with_default_player(file: &Path) -> io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        Command::new("xdg-open")
+    };
+    let status = command.arg(file).stdout(Stdio::null()).stderr(Stdio::null()).status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(tr!("打开方式退出码 {status}", "The opener exited with {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_format::AudioFormat;
+
+    #[test]
+    fn test_preview_options_limit_length() {
+        let options = ConversionOptions::new(AudioFormat::Mp3);
+        assert_eq!(preview_options(&options).end, Some(PREVIEW_LENGTH));
+        assert_eq!(preview_options(&options).overwrite_policy, Some(OverwritePolicy::Overwrite));
+
+        // 设置了片段时从片段开头截取，片段更短时保持不变
+        let clip = options.clone().with_segment(Some(Duration::from_secs(60)), None);
+        assert_eq!(preview_options(&clip).end, Some(Duration::from_secs(90)));
+        let short = options.with_segment(Some(Duration::from_secs(60)), Some(Duration::from_secs(70)));
+        assert_eq!(preview_options(&short).end, Some(Duration::from_secs(70)));
+    }
+}
@@ -0,0 +1,402 @@
+//! # 媒体信息探测模块
+//!
+//! 通过 FFmpeg 附带的 `ffprobe` 读取源文件的时长、音轨、章节、封面等信息，
+//! 用于按时长排序和筛选文件、选择音轨、按章节拆分输出以及嵌入封面。
+
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::{ffmpeg_command, ffmpeg_path_arg};
+use crate::i18n::text;
+use crate::tr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+/// 根据 FFmpeg 路径推断 ffprobe 路径
+///
+/// 优先使用与 FFmpeg 同目录的 ffprobe（保留 `.exe` 等扩展名），
+/// 不存在时（例如只下载了 ffmpeg 的托管版本）在系统 PATH 中查找 `ffprobe`。
+pub fn ffprobe_path(ffmpeg_path: &Path) -> PathBuf {
+    companion_tool_path(ffmpeg_path, "ffprobe")
+}
+
+/// 根据 FFmpeg 路径推断随 FFmpeg 一起发布的其他工具（`ffprobe`、`ffplay`）的路径
+pub(crate) fn companion_tool_path(ffmpeg_path: &Path, name: &str) -> PathBuf {
+    let mut file_name = std::ffi::OsString::from(name);
+    if let Some(extension) = ffmpeg_path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+
+    match ffmpeg_path.parent() {
+        Some(parent) if parent.join(&file_name).is_file() => parent.join(file_name),
+        _ => PathBuf::from(file_name),
+    }
+}
+
+/// 源文件中的一条音轨
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioStream {
+    /// 音轨序号（从 0 开始，只计算音频流），对应 FFmpeg 的 `-map 0:a:N`
+    pub index: usize,
+
+    /// 语言标签（如 `eng`、`jpn`），未标注或为 `und` 时为 `None`
+    pub language: Option<String>,
+}
+
+/// 要提取的音轨
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AudioTrackSelection {
+    /// 按编号选择，从 1 开始
+    Number(usize),
+
+    /// 选择第一条语言标签匹配的音轨（如 `jpn`，不区分大小写）
+    Language(String),
+}
+
+impl AudioTrackSelection {
+    /// 在源文件的音轨中查找选中的音轨
+    ///
+    /// # 返回值
+    ///
+    /// 选中音轨的序号（从 0 开始），用于 `-map 0:a:N`
+    ///
+    /// # 错误
+    ///
+    /// 源文件中没有符合条件的音轨时返回 [`VideoToAudioError::InvalidInput`]
+    pub fn resolve(&self, streams: &[AudioStream]) -> Result<usize> {
+        match self {
+            Self::Number(number) => streams
+                .iter()
+                .find(|stream| stream.index + 1 == *number)
+                .map(|stream| stream.index)
+                .ok_or_else(|| VideoToAudioError::InvalidInput(tr!(
+                    "源文件没有第 {number} 条音轨（共 {} 条）",
+                    "The source has no audio track {number} ({} in total)",
+                    streams.len()
+                ))),
+            Self::Language(language) => streams
+                .iter()
+                .find(|stream| stream.language.as_deref().is_some_and(|l| l.eq_ignore_ascii_case(language)))
+                .map(|stream| stream.index)
+                .ok_or_else(|| {
+                    let available: Vec<&str> = streams
+                        .iter()
+                        .map(|stream| stream.language.as_deref().unwrap_or(text("未标注", "unknown")))
+                        .collect();
+                    VideoToAudioError::InvalidInput(tr!(
+                        "源文件没有语言为 {language} 的音轨（现有音轨: {}）",
+                        "The source has no audio track in language {language} (tracks: {})",
+                        if available.is_empty() { text("无", "none").to_string() } else { available.join(", ") }
+                    ))
+                }),
+        }
+    }
+}
+
+/// 读取媒体文件中的全部音轨
+///
+/// # 错误
+///
+/// ffprobe 无法执行时返回 [`VideoToAudioError::MissingDependency`]，
+/// 文件无法解析时返回 [`VideoToAudioError::FfmpegError`]
+pub fn probe_audio_streams(ffmpeg_path: &Path, file: &Path) -> Result<Vec<AudioStream>> {
+    let output = ffmpeg_command(&ffprobe_path(ffmpeg_path))
+        .args(["-v", "error", "-select_streams", "a", "-show_entries", "stream=index:stream_tags=language", "-of", "csv=p=0"])
+        .arg(ffmpeg_path_arg(file))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| VideoToAudioError::MissingDependency(
+            text(
+                "ffprobe 未安装或不在系统 PATH 中。ffprobe 通常随 FFmpeg 一起安装。",
+                "ffprobe is not installed or not in the system PATH. ffprobe is usually installed together with FFmpeg.",
+            ).to_string()
+        ))?;
+
+    if !output.status.success() {
+        return Err(VideoToAudioError::FfmpegError(tr!(
+            "无法读取音轨信息 ({}): {}",
+            "Cannot read the audio tracks ({}): {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_audio_streams(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `ffprobe -show_entries stream=index:stream_tags=language -of csv=p=0` 的输出
+///
+/// 每行对应一条音轨，形如 `1,eng`；没有语言标签时只有流序号
+pub fn parse_audio_streams(output: &str) -> Vec<AudioStream> {
+    output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let language = line
+                .split(',')
+                .nth(1)
+                .map(str::trim)
+                .filter(|language| !language.is_empty() && *language != "und")
+                .map(String::from);
+            AudioStream { index, language }
+        })
+        .collect()
+}
+
+/// 查找媒体文件中嵌入的封面
+///
+/// # 返回值
+///
+/// 第一个标记为封面 (`attached_pic`) 的视频流的序号，没有封面时为 `None`
+///
+/// # 错误
+///
+/// ffprobe 无法执行时返回 [`VideoToAudioError::MissingDependency`]，
+/// 文件无法解析时返回 [`VideoToAudioError::FfmpegError`]
+pub fn probe_attached_picture(ffmpeg_path: &Path, file: &Path) -> Result<Option<usize>> {
+    let output = ffmpeg_command(&ffprobe_path(ffmpeg_path))
+        .args(["-v", "error", "-select_streams", "v", "-show_entries", "stream=index:stream_disposition=attached_pic", "-of", "csv=p=0"])
+        .arg(ffmpeg_path_arg(file))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| VideoToAudioError::MissingDependency(
+            text(
+                "ffprobe 未安装或不在系统 PATH 中。ffprobe 通常随 FFmpeg 一起安装。",
+                "ffprobe is not installed or not in the system PATH. ffprobe is usually installed together with FFmpeg.",
+            ).to_string()
+        ))?;
+
+    if !output.status.success() {
+        return Err(VideoToAudioError::FfmpegError(tr!(
+            "无法读取封面信息 ({}): {}",
+            "Cannot read the cover art ({}): {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_attached_picture(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `ffprobe -show_entries stream=index:stream_disposition=attached_pic -of csv=p=0` 的输出
+///
+/// 每行对应一个视频流，形如 `2,1`，第二个字段为 1 表示该流是封面
+pub fn parse_attached_picture(output: &str) -> Option<usize> {
+    output.lines().find_map(|line| {
+        let (index, attached) = line.split_once(',')?;
+        (attached.trim() == "1").then(|| index.trim().parse().ok()).flatten()
+    })
+}
+
+/// 源文件中的一个章节
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chapter {
+    /// 开始时间
+    pub start: Duration,
+
+    /// 结束时间
+    pub end: Duration,
+
+    /// 章节标题，未设置时为 `None`
+    pub title: Option<String>,
+}
+
+/// 读取媒体文件中的全部章节
+///
+/// # 错误
+///
+/// ffprobe 无法执行时返回 [`VideoToAudioError::MissingDependency`]，
+/// 文件无法解析时返回 [`VideoToAudioError::FfmpegError`]
+pub fn probe_chapters(ffmpeg_path: &Path, file: &Path) -> Result<Vec<Chapter>> {
+    let output = ffmpeg_command(&ffprobe_path(ffmpeg_path))
+        .args(["-v", "error", "-show_entries", "chapter=start_time,end_time:chapter_tags=title", "-of", "csv=p=0"])
+        .arg(ffmpeg_path_arg(file))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| VideoToAudioError::MissingDependency(
+            text(
+                "ffprobe 未安装或不在系统 PATH 中。ffprobe 通常随 FFmpeg 一起安装。",
+                "ffprobe is not installed or not in the system PATH. ffprobe is usually installed together with FFmpeg.",
+            ).to_string()
+        ))?;
+
+    if !output.status.success() {
+        return Err(VideoToAudioError::FfmpegError(tr!(
+            "无法读取章节信息 ({}): {}",
+            "Cannot read the chapters ({}): {}",
+            file.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    Ok(parse_chapters(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 `ffprobe -show_entries chapter=start_time,end_time:chapter_tags=title -of csv=p=0` 的输出
+///
+/// 每行对应一个章节，形如 `0.000000,61.500000,Intro`；标题包含逗号或引号时
+/// 整个字段带双引号，内部的双引号写成两个。时间无法解析的行会被忽略。
+pub fn parse_chapters(output: &str) -> Vec<Chapter> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ',');
+            let mut time = || {
+                fields
+                    .next()?
+                    .trim()
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                    .map(Duration::from_secs_f64)
+            };
+            let start = time()?;
+            let end = time()?;
+            let title = fields.next().map(|title| {
+                let title = title.trim();
+                match title.strip_prefix('"').and_then(|title| title.strip_suffix('"')) {
+                    Some(quoted) => quoted.replace("\"\"", "\""),
+                    None => title.to_string(),
+                }
+            });
+            (end > start).then(|| Chapter {
+                start,
+                end,
+                title: title.filter(|title| !title.is_empty()),
+            })
+        })
+        .collect()
+}
+
+/// 读取媒体文件的时长
+///
+/// # 错误
+///
+/// ffprobe 无法执行时返回 [`VideoToAudioError::MissingDependency`]，
+/// 文件无法解析或没有时长信息时返回 [`VideoToAudioError::FfmpegError`]
+pub fn probe_duration(ffmpeg_path: &Path, file: &Path) -> Result<Duration> {
+    let output = ffmpeg_command(&ffprobe_path(ffmpeg_path))
+        .args(["-v", "error", "-show_entries", "format=duration", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(ffmpeg_path_arg(file))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| VideoToAudioError::MissingDependency(
+            text(
+                "ffprobe 未安装或不在系统 PATH 中。ffprobe 通常随 FFmpeg 一起安装。",
+                "ffprobe is not installed or not in the system PATH. ffprobe is usually installed together with FFmpeg.",
+            ).to_string()
+        ))?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+        .map(Duration::from_secs_f64)
+        .ok_or_else(|| VideoToAudioError::FfmpegError(tr!(
+            "无法读取文件时长: {}",
+            "Cannot read the duration: {}",
+            file.display()
+        )))
+}
+
+/// 读取媒体文件第一条音轨的码率 (kbps)
+///
+/// 封装格式没有记录音轨码率时（如部分 MKV 文件）返回 `None`
+///
+/// # 错误
+///
+/// ffprobe 无法执行时返回 [`VideoToAudioError::MissingDependency`]
+pub fn probe_audio_bitrate(ffmpeg_path: &Path, file: &Path) -> Result<Option<u32>> {
+    let output = ffmpeg_command(&ffprobe_path(ffmpeg_path))
+        .args(["-v", "error", "-select_streams", "a:0", "-show_entries", "stream=bit_rate", "-of", "default=noprint_wrappers=1:nokey=1"])
+        .arg(ffmpeg_path_arg(file))
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|_| VideoToAudioError::MissingDependency(
+            text(
+                "ffprobe 未安装或不在系统 PATH 中。ffprobe 通常随 FFmpeg 一起安装。",
+                "ffprobe is not installed or not in the system PATH. ffprobe is usually installed together with FFmpeg.",
+            ).to_string()
+        ))?;
+
+    Ok(parse_bitrate(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// 解析 ffprobe 输出的码率（每秒比特数），转换为 kbps；`N/A` 或为 0 时返回 `None`
+fn parse_bitrate(output: &str) -> Option<u32> {
+    output
+        .lines()
+        .next()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .filter(|&bits| bits > 0)
+        .map(|bits| u32::try_from(bits.div_ceil(1000)).unwrap_or(u32::MAX))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_bitrate() {
+        assert_eq!(parse_bitrate("128000\n"), Some(128));
+        assert_eq!(parse_bitrate("95999"), Some(96));
+        assert_eq!(parse_bitrate("N/A\n"), None);
+        assert_eq!(parse_bitrate("0"), None);
+        assert_eq!(parse_bitrate(""), None);
+    }
+
+    #[test]
+    fn test_parse_audio_streams() {
+        let streams = parse_audio_streams("1,jpn\n2,und\n3\n\n");
+        assert_eq!(streams.len(), 3);
+        assert_eq!(streams[0], AudioStream { index: 0, language: Some("jpn".to_string()) });
+        assert_eq!(streams[1].language, None);
+        assert_eq!(streams[2], AudioStream { index: 2, language: None });
+    }
+
+    #[test]
+    fn test_resolve_audio_track_selection() {
+        let streams = parse_audio_streams("1,eng\n2,jpn\n");
+        assert_eq!(AudioTrackSelection::Number(2).resolve(&streams).unwrap(), 1);
+        assert_eq!(AudioTrackSelection::Language("JPN".to_string()).resolve(&streams).unwrap(), 1);
+        assert!(matches!(
+            AudioTrackSelection::Number(3).resolve(&streams),
+            Err(VideoToAudioError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            AudioTrackSelection::Language("fra".to_string()).resolve(&streams),
+            Err(VideoToAudioError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_attached_picture() {
+        assert_eq!(parse_attached_picture("0,0\n3,1\n"), Some(3));
+        assert_eq!(parse_attached_picture("0,0\n"), None);
+        assert_eq!(parse_attached_picture(""), None);
+    }
+
+    #[test]
+    fn test_parse_chapters() {
+        let chapters = parse_chapters("0.000000,61.500000,Intro\n61.500000,120.000000,\"Encore, \"\"live\"\"\"\n120.000000,180.000000\nN/A,1.0,Broken\n");
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[0].end, Duration::from_millis(61_500));
+        assert_eq!(chapters[0].title.as_deref(), Some("Intro"));
+        assert_eq!(chapters[1].title.as_deref(), Some("Encore, \"live\""));
+        assert_eq!(chapters[2].title, None);
+    }
+
+    #[test]
+    fn test_ffprobe_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert_eq!(ffprobe_path(Path::new("ffmpeg")), Path::new("ffprobe"));
+        assert_eq!(ffprobe_path(&temp_dir.path().join("ffmpeg.exe")), Path::new("ffprobe.exe"));
+
+        std::fs::write(temp_dir.path().join("ffprobe.exe"), "").unwrap();
+        assert_eq!(ffprobe_path(&temp_dir.path().join("ffmpeg.exe")), temp_dir.path().join("ffprobe.exe"));
+    }
+}
@@ -0,0 +1,251 @@
+//! # 转换进度模块
+//!
+//! 批量转换时报告每个文件的开始、进度和结束，界面可以据此显示正在处理哪些文件。
+//!
+//! 使用 FFmpeg 命令行转换时通过 `-progress pipe:1` 读取已输出的时长，
+//! 再与 ffprobe 读取的源文件时长比较得到百分比；自定义后端只报告开始和结束。
+//!
+//! `--progress json` 时由 [`JsonProgress`] 向标准输出逐行写入 [`ProgressEvent`]
+//! (JSON Lines)，脚本和图形界面无需解析面向终端的进度条：
+//!
+//! ```text
+//! {"event":"scan_done","total":2,"output_dir":"/audio"}
+//! {"event":"file_started","source":"/videos/a.mp4"}
+//! {"event":"file_progress","source":"/videos/a.mp4","position_seconds":12.5,"total_seconds":60.0,"percent":20.8}
+//! {"event":"file_done","source":"/videos/a.mp4","output":"/audio/a.mp3","error":null,"error_code":null,"elapsed_seconds":3.1,"completed":1,"total":2}
+//! {"event":"batch_done","succeeded":2,"failed":0,"cancelled":false,"elapsed_seconds":6.4}
+//! ```
+
+use crate::error::{Result, VideoToAudioError};
+use crate::tr;
+use serde::Serialize;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 转换进度监听器
+///
+/// 方法在执行转换的工作线程上调用，不同文件的调用可能交错。
+pub trait ProgressListener: fmt::Debug + Send + Sync {
+    /// 文件开始转换，失败重试时会再次调用
+    fn file_started(&self, source: &Path);
+
+    /// 文件已输出到 `position`
+    ///
+    /// `total` 为本次输出的总时长，无法读取时为 `None`。按章节或音轨拆分时，
+    /// 同一个源文件的每个输出分别从零开始报告。
+    fn file_progress(&self, source: &Path, position: Duration, total: Option<Duration>);
+
+    /// 文件转换结束，无论成功与否
+    fn file_finished(&self, source: &Path);
+
+    /// 即将为 `source` 执行 FFmpeg，`command_line` 为完整的命令行
+    ///
+    /// 只在处理器开启了 [`with_show_commands`](crate::FileProcessor::with_show_commands) 时调用，
+    /// 由界面决定是否以及如何显示，默认忽略
+    fn ffmpeg_command(&self, _source: &Path, _command_line: &str) {}
+}
+
+/// 批量转换的进度显示方式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProgressFormat {
+    /// 面向终端的进度条
+    #[default]
+    Bar,
+    /// 逐行输出 JSON 事件
+    Json,
+}
+
+/// `--progress json` 输出的进度事件
+///
+/// 先输出一个 `scan_done`，然后每个文件依次产生 `file_started`、若干 `file_progress`
+/// 和 `file_done`（不同文件的事件可能交错），最后是一个 `batch_done`。
+/// 失败重试时同一个文件会再次产生 `file_started`。
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// 扫描结束，即将开始转换
+    ScanDone {
+        /// 待转换的文件数
+        total: usize,
+        /// 输出目录
+        output_dir: PathBuf,
+    },
+
+    /// 单个文件开始转换
+    FileStarted {
+        /// 源文件
+        source: PathBuf,
+    },
+
+    /// 单个文件的转换进度
+    FileProgress {
+        /// 源文件
+        source: PathBuf,
+        /// 已输出的时长（秒）
+        position_seconds: f64,
+        /// 本次输出的总时长（秒），无法读取时为 `null`
+        total_seconds: Option<f64>,
+        /// 完成百分比，不知道总时长时为 `null`
+        percent: Option<f64>,
+    },
+
+    /// 单个文件处理完成
+    FileDone {
+        /// 源文件
+        source: PathBuf,
+        /// 成功时的输出文件
+        output: Option<PathBuf>,
+        /// 失败时的错误信息
+        error: Option<String>,
+        /// 失败时的错误代码，参见 [`crate::error_codes`]
+        error_code: Option<&'static str>,
+        /// 转换耗时（秒）
+        elapsed_seconds: f64,
+        /// 已处理的文件数
+        completed: usize,
+        /// 文件总数
+        total: usize,
+    },
+
+    /// 批量转换结束
+    BatchDone {
+        /// 成功的文件数
+        succeeded: usize,
+        /// 失败的文件数
+        failed: usize,
+        /// 是否被取消
+        cancelled: bool,
+        /// 总耗时（秒）
+        elapsed_seconds: f64,
+    },
+}
+
+impl ProgressEvent {
+    /// 由单个文件的转换结果生成 `file_done` 事件
+    pub fn file_done(
+        source: &Path,
+        result: &Result<PathBuf>,
+        elapsed: Duration,
+        completed: usize,
+        total: usize,
+    ) -> Self {
+        Self::FileDone {
+            source: source.to_path_buf(),
+            output: result.as_ref().ok().cloned(),
+            error: result.as_ref().err().map(ToString::to_string),
+            error_code: result.as_ref().err().map(VideoToAudioError::code),
+            elapsed_seconds: elapsed.as_secs_f64(),
+            completed,
+            total,
+        }
+    }
+}
+
+/// 逐行写入 JSON 进度事件的监听器
+///
+/// 每个事件写完后立即刷新，多个工作线程的事件不会混在同一行中。
+#[derive(Debug)]
+pub struct JsonProgress<W> {
+    writer: Mutex<W>,
+}
+
+impl<W: Write> JsonProgress<W> {
+    /// 创建向 `writer` 写入事件的监听器
+    pub fn new(writer: W) -> Self {
+        Self { writer: Mutex::new(writer) }
+    }
+
+    /// 写入一个事件
+    ///
+    /// # 错误
+    ///
+    /// 序列化或写入失败时返回错误
+    pub fn emit(&self, event: &ProgressEvent) -> Result<()> {
+        let line = serde_json::to_string(event)
+            .map_err(|e| VideoToAudioError::InvalidInput(tr!(
+                "进度事件序列化失败: {e}",
+                "Failed to serialize the progress event: {e}",
+            )))?;
+        let mut writer = self.writer.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        writeln!(writer, "{line}")?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// 取回底层的输出
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<W: Write + Send + fmt::Debug> ProgressListener for JsonProgress<W> {
+    fn file_started(&self, source: &Path) {
+        // 读取方已关闭管道时不影响转换本身
+        let _ = self.emit(&ProgressEvent::FileStarted { source: source.to_path_buf() });
+    }
+
+    fn file_progress(&self, source: &Path, position: Duration, total: Option<Duration>) {
+        let percent = total
+            .filter(|total| !total.is_zero())
+            .map(|total| (position.as_secs_f64() / total.as_secs_f64() * 100.0).min(100.0));
+        let _ = self.emit(&ProgressEvent::FileProgress {
+            source: source.to_path_buf(),
+            position_seconds: position.as_secs_f64(),
+            total_seconds: total.map(|total| total.as_secs_f64()),
+            percent,
+        });
+    }
+
+    fn file_finished(&self, _source: &Path) {}
+}
+
+/// 解析 FFmpeg `-progress` 输出中的一行，返回已输出的时长
+///
+/// 较旧的 FFmpeg 只输出 `out_time_ms`，其单位实际上也是微秒
+pub fn parse_progress_line(line: &str) -> Option<Duration> {
+    let (key, value) = line.trim().split_once('=')?;
+    match key {
+        "out_time_us" | "out_time_ms" => value.parse().ok().map(Duration::from_micros),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_progress_line() {
+        assert_eq!(parse_progress_line("out_time_us=61500000"), Some(Duration::from_millis(61_500)));
+        assert_eq!(parse_progress_line("out_time_ms=2000000\n"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_progress_line("out_time_us=N/A"), None);
+        assert_eq!(parse_progress_line("out_time=00:01:01.500000"), None);
+        assert_eq!(parse_progress_line("progress=end"), None);
+    }
+
+    #[test]
+    fn test_json_progress_writes_one_event_per_line() {
+        let progress = JsonProgress::new(Vec::new());
+        let source = Path::new("videos/a.mp4");
+        progress.file_started(source);
+        progress.file_progress(source, Duration::from_secs(15), Some(Duration::from_secs(60)));
+        progress.file_progress(source, Duration::from_secs(15), None);
+        let failed = Err(VideoToAudioError::FfmpegError("moov atom not found".to_string()));
+        progress.emit(&ProgressEvent::file_done(source, &failed, Duration::from_secs(2), 1, 1)).unwrap();
+
+        let output = String::from_utf8(progress.into_inner()).unwrap();
+        let events: Vec<serde_json::Value> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0]["event"], "file_started");
+        assert_eq!(events[1]["event"], "file_progress");
+        assert_eq!(events[1]["percent"], 25.0);
+        assert!(events[2]["percent"].is_null());
+        assert_eq!(events[3]["event"], "file_done");
+        assert!(events[3]["output"].is_null());
+        assert!(events[3]["error"].as_str().unwrap().contains("moov"));
+        assert_eq!(events[3]["error_code"], "E019");
+    }
+}
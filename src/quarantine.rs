@@ -0,0 +1,151 @@
+//! # 损坏文件隔离模块
+//!
+//! 被判定为损坏（[`VideoToAudioError::CorruptInput`]）的源文件可以移动或符号链接到输出目录的
+//! `corrupt/` 文件夹中，并在转换报告中注明，转换老录像带等素材后不必再逐个查找需要重新采集的文件。
+//!
+//! 隔离文件夹保留源文件相对于源目录的子目录结构，同名文件自动加上序号。
+//!
+//! [`VideoToAudioError::CorruptInput`]: crate::error::VideoToAudioError::CorruptInput
+
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::tr;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// 隔离文件夹的名称
+pub const QUARANTINE_DIR_NAME: &str = "corrupt";
+
+/// 损坏文件的隔离方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuarantineMode {
+    /// 把源文件移动到隔离文件夹
+    Move,
+
+    /// 在隔离文件夹中创建指向源文件的符号链接，源文件保留不动
+    Link,
+}
+
+/// 获取输出目录中的隔离文件夹路径
+pub fn quarantine_dir(output_dir: &Path) -> PathBuf {
+    output_dir.join(QUARANTINE_DIR_NAME)
+}
+
+/// 隔离一个损坏的源文件，返回它在隔离文件夹中的路径
+///
+/// # 参数
+///
+/// * `source_file` - 损坏的源文件
+/// * `source_root` - 源目录，源文件位于其中时保留子目录结构
+/// * `dir` - 隔离文件夹
+/// * `mode` - 移动或符号链接
+///
+/// # 错误
+///
+/// 无法创建目录、移动文件或创建符号链接时返回 I/O 错误
+pub fn quarantine(source_file: &Path, source_root: Option<&Path>, dir: &Path, mode: QuarantineMode) -> Result<PathBuf> {
+    let relative = source_root
+        .and_then(|root| source_file.strip_prefix(root).ok())
+        .filter(|relative| !relative.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+        .or_else(|| source_file.file_name().map(PathBuf::from))
+        .ok_or_else(|| VideoToAudioError::InvalidPath(tr!(
+            "无法获取文件名: {}",
+            "Cannot get the file name: {}",
+            source_file.display(),
+        )))?;
+    let target = available_path(&dir.join(relative));
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent).context(text("创建隔离文件夹", "create quarantine folder"), parent)?;
+    }
+
+    match mode {
+        QuarantineMode::Move => move_file(source_file, &target)?,
+        QuarantineMode::Link => {
+            // 链接使用绝对路径，隔离文件夹移动到别处后仍然有效
+            let source_file = fs::canonicalize(source_file).context(text("读取源文件", "read source file"), source_file)?;
+            symlink_file(&source_file, &target).context(text("创建符号链接", "create symbolic link"), &target)?;
+        }
+    }
+    Ok(target)
+}
+
+/// 目标已存在时在文件名后加上序号，如 `clip (1).mp4`
+fn available_path(target: &Path) -> PathBuf {
+    let mut candidate = target.to_path_buf();
+    let mut index = 1;
+    // symlink_metadata 也能发现指向已删除文件的链接
+    while fs::symlink_metadata(&candidate).is_ok() {
+        let mut name = target.file_stem().map(OsString::from).unwrap_or_default();
+        name.push(format!(" ({index})"));
+        if let Some(extension) = target.extension() {
+            name.push(".");
+            name.push(extension);
+        }
+        candidate = target.with_file_name(name);
+        index += 1;
+    }
+    candidate
+}
+
+/// 移动文件，跨文件系统时复制后删除源文件
+fn move_file(source_file: &Path, target: &Path) -> Result<()> {
+    if fs::rename(source_file, target).is_err() {
+        fs::copy(source_file, target).context(text("复制损坏的文件", "copy corrupt file"), target)?;
+        fs::remove_file(source_file).context(text("删除已隔离的源文件", "remove quarantined source file"), source_file)?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn symlink_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(original, link)
+}
+
+#[cfg(windows)]
+fn symlink_file(original: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_file(original, link)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn symlink_file(_original: &Path, _link: &Path) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, text(
+        "当前平台不支持符号链接",
+        "symbolic links are not supported on this platform",
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_quarantine_keeps_structure_and_avoids_collisions() {
+        let temp_dir = TempDir::new().unwrap();
+        let source_root = temp_dir.path().join("videos");
+        fs::create_dir_all(source_root.join("tape1")).unwrap();
+        let nested = source_root.join("tape1/clip.mp4");
+        let top = source_root.join("clip.mp4");
+        fs::write(&nested, "bad").unwrap();
+        fs::write(&top, "bad").unwrap();
+        let dir = quarantine_dir(&temp_dir.path().join("audio"));
+
+        let moved = quarantine(&nested, Some(&source_root), &dir, QuarantineMode::Move).unwrap();
+        assert_eq!(moved, dir.join("tape1/clip.mp4"));
+        assert!(!nested.exists());
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "bad");
+
+        let linked = quarantine(&top, Some(&source_root), &dir, QuarantineMode::Link).unwrap();
+        assert_eq!(linked, dir.join("clip.mp4"));
+        assert!(top.exists());
+        #[cfg(unix)]
+        assert!(fs::symlink_metadata(&linked).unwrap().file_type().is_symlink());
+
+        // 不在源目录中的文件直接放在隔离文件夹下，同名时加序号
+        let linked_again = quarantine(&top, None, &dir, QuarantineMode::Link).unwrap();
+        assert_eq!(linked_again, dir.join("clip (1).mp4"));
+    }
+}
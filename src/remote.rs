@@ -0,0 +1,116 @@
+//! # 远程输入模块
+//!
+//! 识别 http(s)/rtmp 等网络地址形式的输入。这类输入不是本地文件，
+//! 会原样作为 `-i` 的参数交给 FFmpeg 读取，输出文件名从地址的路径部分推断。
+
+use crate::file_processor::sanitize_file_name;
+use crate::i18n::text;
+use std::ffi::OsString;
+use std::path::Path;
+
+/// 支持的远程输入协议
+const REMOTE_SCHEMES: [&str; 4] = ["http://", "https://", "rtmp://", "rtmps://"];
+
+/// 判断输入是否为远程地址
+pub fn is_remote_url(source: &Path) -> bool {
+    source.to_str().is_some_and(|source| {
+        REMOTE_SCHEMES
+            .iter()
+            .any(|scheme| source.len() > scheme.len() && source[..scheme.len()].eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// 从远程地址推断输出文件名（不含扩展名）
+///
+/// 使用路径的最后一段并去掉扩展名，例如 `https://example.com/vod/live%20show.mp4?t=1`
+/// 得到 `live show`；路径为空时使用主机名。
+pub fn url_file_stem(url: &str) -> Option<OsString> {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let without_query = without_scheme
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default();
+
+    let (host, path) = without_query.split_once('/').unwrap_or((without_query, ""));
+    let segment = path.rsplit('/').find(|segment| !segment.is_empty());
+    let name = match segment {
+        Some(segment) => {
+            let decoded = percent_decode(segment);
+            match decoded.rsplit_once('.') {
+                Some((stem, _)) if !stem.is_empty() => stem.to_string(),
+                _ => decoded,
+            }
+        }
+        None => host.split(':').next().unwrap_or(host).to_string(),
+    };
+
+    // 去掉在文件名中不合法的字符
+    let name = sanitize_file_name(&name);
+    let name = name.trim();
+    (!name.is_empty() && name != "." && name != "..").then(|| OsString::from(name))
+}
+
+/// 解码 URL 中的百分号编码，解码结果不是有效 UTF-8 时保留原文
+fn percent_decode(segment: &str) -> String {
+    let bytes = segment.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes.get(i + 1..i + 3).and_then(|hex| std::str::from_utf8(hex).ok());
+        match (bytes[i], hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| segment.to_string())
+}
+
+/// 根据 FFmpeg 的错误输出给出远程输入失败的原因
+pub fn describe_remote_error(url: &str, stderr: &str) -> String {
+    let lower = stderr.to_lowercase();
+    let reason = if lower.contains("404") {
+        text("服务器返回 404，链接可能已失效", "The server returned 404, the link may be dead")
+    } else if lower.contains("403") || lower.contains("401") {
+        text("服务器拒绝访问，链接可能需要登录或已过期", "The server denied access, the link may need a login or may have expired")
+    } else if lower.contains("resolve") || lower.contains("name or service not known") {
+        text("无法解析主机名，请检查网络连接和链接地址", "Cannot resolve the host name, check the network connection and the URL")
+    } else if lower.contains("connection refused") || lower.contains("timed out") {
+        text("无法连接到服务器，请检查网络连接", "Cannot connect to the server, check the network connection")
+    } else if lower.contains("protocol not found") {
+        text(
+            "当前 FFmpeg 不支持该协议，请安装带网络支持的完整版 FFmpeg",
+            "This FFmpeg does not support the protocol, install a full FFmpeg build with network support",
+        )
+    } else {
+        text("读取远程输入失败", "Failed to read the remote input")
+    };
+    format!("{reason} ({url}): {}", stderr.trim())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_remote_url() {
+        assert!(is_remote_url(Path::new("https://example.com/live.m3u8")));
+        assert!(is_remote_url(Path::new("RTMP://example.com/app/stream")));
+        assert!(!is_remote_url(Path::new("videos/https.mp4")));
+        assert!(!is_remote_url(Path::new("http://")));
+    }
+
+    #[test]
+    fn test_url_file_stem() {
+        let stem = |url| url_file_stem(url).map(|s| s.into_string().unwrap());
+        assert_eq!(stem("https://example.com/vod/live%20show.mp4?t=1").as_deref(), Some("live show"));
+        assert_eq!(stem("rtmp://example.com:1935/app/stream").as_deref(), Some("stream"));
+        assert_eq!(stem("https://example.com:8443/").as_deref(), Some("example.com"));
+        assert_eq!(stem("https://example.com/a%2Fb.mp4").as_deref(), Some("a_b"));
+    }
+}
@@ -0,0 +1,214 @@
+//! # 转换报告模块
+//!
+//! 批处理结束后把每个文件的转换结果写入 JSON 或 CSV 报告，
+//! 便于导入电子表格统计，不必解析控制台输出。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{IoResultExt, Result};
+use crate::i18n::text;
+use crate::probe;
+use crate::tagging::format_from_path;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 单个文件的转换结果
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ReportEntry {
+    /// 源文件
+    pub source: PathBuf,
+
+    /// 输出文件，转换失败时为空
+    pub output: Option<PathBuf>,
+
+    /// 输出格式的扩展名
+    pub format: String,
+
+    /// 源文件大小（字节）
+    pub input_bytes: Option<u64>,
+
+    /// 输出文件大小（字节）
+    pub output_bytes: Option<u64>,
+
+    /// 输出音频的时长（秒），需要 ffprobe
+    pub duration_seconds: Option<f64>,
+
+    /// 转换耗时（秒）
+    pub elapsed_seconds: f64,
+
+    /// 错误信息，转换成功时为空
+    pub error: Option<String>,
+
+    /// 内容相同、实际转换的源文件，源文件不是重复文件时为空
+    pub duplicate_of: Option<PathBuf>,
+
+    /// 损坏的源文件在隔离文件夹中的路径，没有隔离时为空
+    pub quarantined: Option<PathBuf>,
+}
+
+impl ReportEntry {
+    /// 根据转换结果生成报告条目，读取文件大小和输出时长
+    ///
+    /// # 参数
+    ///
+    /// * `ffmpeg_path` - 用于定位 ffprobe 的 FFmpeg 路径
+    /// * `source` - 源文件
+    /// * `result` - 转换结果
+    /// * `format` - 请求的输出格式，输出文件的扩展名不同（如使用了备用格式）时以扩展名为准
+    /// * `elapsed` - 转换耗时
+    pub fn new(
+        ffmpeg_path: &Path,
+        source: &Path,
+        result: &Result<PathBuf>,
+        format: AudioFormat,
+        elapsed: Duration,
+    ) -> Self {
+        let output = result.as_ref().ok();
+        Self {
+            source: source.to_path_buf(),
+            output: output.cloned(),
+            format: output.and_then(|output| format_from_path(output)).unwrap_or(format).extension().to_string(),
+            input_bytes: fs::metadata(source).ok().map(|m| m.len()),
+            output_bytes: output.and_then(|output| fs::metadata(output).ok()).map(|m| m.len()),
+            duration_seconds: output
+                .and_then(|output| probe::probe_duration(ffmpeg_path, output).ok())
+                .map(|duration| duration.as_secs_f64()),
+            elapsed_seconds: elapsed.as_secs_f64(),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            duplicate_of: None,
+            quarantined: None,
+        }
+    }
+
+    /// 为没有单独转换的重复源文件生成报告条目
+    ///
+    /// `output` 为硬链接的输出文件，只注明重复时为 `None`
+    pub fn duplicate(
+        ffmpeg_path: &Path,
+        source: &Path,
+        original: &Path,
+        output: Option<&Path>,
+        format: AudioFormat,
+    ) -> Self {
+        let mut entry = match output {
+            Some(output) => Self::new(ffmpeg_path, source, &Ok(output.to_path_buf()), format, Duration::ZERO),
+            None => Self {
+                source: source.to_path_buf(),
+                output: None,
+                format: format.extension().to_string(),
+                input_bytes: fs::metadata(source).ok().map(|m| m.len()),
+                output_bytes: None,
+                duration_seconds: None,
+                elapsed_seconds: 0.0,
+                error: None,
+                duplicate_of: None,
+                quarantined: None,
+            },
+        };
+        entry.duplicate_of = Some(original.to_path_buf());
+        entry
+    }
+}
+
+/// 把报告写入文件，扩展名为 `.csv` 时写入 CSV，否则写入 JSON
+///
+/// # 错误
+///
+/// 无法写入文件时返回 I/O 错误
+pub fn write_report(path: &Path, entries: &[ReportEntry]) -> Result<()> {
+    let is_csv = path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    let content = if is_csv {
+        report_csv(entries)
+    } else {
+        serde_json::to_string_pretty(entries).map_err(std::io::Error::other)? + "\n"
+    };
+    fs::write(path, content).context(text("写入转换报告", "write conversion report"), path)?;
+    Ok(())
+}
+
+/// 生成 CSV 格式的报告，第一行为表头，缺少的值留空
+pub fn report_csv(entries: &[ReportEntry]) -> String {
+    let mut content =
+        String::from("source,output,format,input_bytes,output_bytes,duration_seconds,elapsed_seconds,error,duplicate_of,quarantined\n");
+    for entry in entries {
+        let fields = [
+            csv_field(&entry.source.to_string_lossy()),
+            csv_field(&entry.output.as_ref().map(|output| output.to_string_lossy()).unwrap_or_default()),
+            csv_field(&entry.format),
+            entry.input_bytes.map(|bytes| bytes.to_string()).unwrap_or_default(),
+            entry.output_bytes.map(|bytes| bytes.to_string()).unwrap_or_default(),
+            entry.duration_seconds.map(|seconds| format!("{seconds:.3}")).unwrap_or_default(),
+            format!("{:.3}", entry.elapsed_seconds),
+            csv_field(entry.error.as_deref().unwrap_or_default()),
+            csv_field(&entry.duplicate_of.as_ref().map(|original| original.to_string_lossy()).unwrap_or_default()),
+            csv_field(&entry.quarantined.as_ref().map(|quarantined| quarantined.to_string_lossy()).unwrap_or_default()),
+        ];
+        content.push_str(&fields.join(","));
+        content.push('\n');
+    }
+    content
+}
+
+/// 按 RFC 4180 转义 CSV 字段：包含逗号、引号或换行时加引号，引号写两次
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_csv() {
+        let entries = [
+            ReportEntry {
+                source: PathBuf::from("videos/a, b.mp4"),
+                output: Some(PathBuf::from("audio/a, b.mp3")),
+                format: "mp3".to_string(),
+                input_bytes: Some(1_000_000),
+                output_bytes: Some(96_000),
+                duration_seconds: Some(6.0),
+                elapsed_seconds: 0.4567,
+                error: None,
+                duplicate_of: None,
+                quarantined: None,
+            },
+            ReportEntry {
+                source: PathBuf::from("videos/c.mp4"),
+                output: None,
+                format: "mp3".to_string(),
+                input_bytes: Some(10),
+                output_bytes: None,
+                duration_seconds: None,
+                elapsed_seconds: 0.1,
+                error: Some("转换失败: \"moov atom not found\"".to_string()),
+                duplicate_of: None,
+                quarantined: Some(PathBuf::from("audio/corrupt/c.mp4")),
+            },
+            ReportEntry {
+                source: PathBuf::from("videos/d.mp4"),
+                output: None,
+                format: "mp3".to_string(),
+                input_bytes: Some(1_000_000),
+                output_bytes: None,
+                duration_seconds: None,
+                elapsed_seconds: 0.0,
+                error: None,
+                duplicate_of: Some(PathBuf::from("videos/a, b.mp4")),
+                quarantined: None,
+            },
+        ];
+        assert_eq!(
+            report_csv(&entries),
+            "source,output,format,input_bytes,output_bytes,duration_seconds,elapsed_seconds,error,duplicate_of,quarantined\n\
+             \"videos/a, b.mp4\",\"audio/a, b.mp3\",mp3,1000000,96000,6.000,0.457,,,\n\
+             videos/c.mp4,,mp3,10,,,0.100,\"转换失败: \"\"moov atom not found\"\"\",,audio/corrupt/c.mp4\n\
+             videos/d.mp4,,mp3,1000000,,,0.000,,\"videos/a, b.mp4\",\n"
+        );
+    }
+}
@@ -0,0 +1,505 @@
+//! # 并发调度模块
+//!
+//! 按任务类型限制同时运行的转换数量。复制音频流主要受磁盘读写速度限制，
+//! 重新编码（MP3、Opus）主要受 CPU 限制：两者共用同一个并发数时，
+//! 要么复制任务过多导致磁盘来回寻道，要么编码任务过少导致 CPU 空闲。
+//!
+//! [`JobScheduler`] 为两类任务分别设置上限，并可根据系统负载
+//! 动态减少编码任务的数量，为其他程序留出 CPU。
+//!
+//! 批量转换通过 [`JobScheduler::dispatch`] 在派发时取得名额：只有取得名额的文件才交给线程池，
+//! 线程池中的线程不会因为等待名额而阻塞；一类任务的名额用尽时先派发另一类任务。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::options::ConversionOptions;
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// 默认同时运行的复制任务上限
+///
+/// 复制任务几乎不占用 CPU，更多的并发只会让磁盘在多个文件之间来回寻道
+pub const DEFAULT_COPY_JOBS: usize = 4;
+
+/// 等待空闲名额时检查取消请求和系统负载的间隔
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 两次读取系统负载之间的最短间隔
+const LOAD_SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 派发时最多预先取出的等待中的任务数
+///
+/// 一类任务的名额用尽时，最多向后查看这么多任务寻找另一类任务
+const DISPATCH_LOOKAHEAD: usize = 64;
+
+/// 转换任务的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JobKind {
+    /// 直接复制音频流，主要受磁盘读写速度限制
+    Copy,
+
+    /// 重新编码音频，主要受 CPU 限制
+    Encode,
+}
+
+impl JobKind {
+    /// 根据转换选项判断任务类型
+    ///
+    /// 自定义后端（例如 Symphonia）只提取音频流，视为复制任务
+    pub fn for_options(options: &ConversionOptions, custom_backend: bool) -> Self {
+        if custom_backend || options.is_stream_copy() {
+            Self::Copy
+        } else {
+            Self::Encode
+        }
+    }
+}
+
+/// 各类任务的并发上限
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConcurrencyLimits {
+    /// 同时运行的任务总数上限，通常等于线程池的线程数
+    pub total: usize,
+
+    /// 同时运行的复制任务上限
+    pub copy: usize,
+
+    /// 同时运行的编码任务上限
+    ///
+    /// 实际上限还会扣除正在运行的复制任务：复制任务占用的线程不能再用于编码
+    pub encode: usize,
+
+    /// 是否根据系统负载动态减少编码任务
+    pub adaptive: bool,
+}
+
+impl ConcurrencyLimits {
+    /// 根据总线程数计算各类任务的上限
+    ///
+    /// 编码任务最多使用复制任务剩下的全部线程，复制任务最多使用 [`DEFAULT_COPY_JOBS`] 个
+    pub fn for_threads(threads: usize) -> Self {
+        let threads = threads.max(1);
+        Self {
+            total: threads,
+            copy: threads.min(DEFAULT_COPY_JOBS),
+            encode: threads,
+            adaptive: false,
+        }
+    }
+
+    /// 设置是否根据系统负载动态减少编码任务
+    pub fn with_adaptive(mut self, adaptive: bool) -> Self {
+        self.adaptive = adaptive;
+        self
+    }
+}
+
+/// 按任务类型限制并发的调度器
+///
+/// 多个文件处理器可以共享同一个调度器，例如 HTTP 服务中同时运行的多个任务，
+/// 此时上限对所有任务合计生效。
+#[derive(Debug)]
+pub struct JobScheduler {
+    limits: ConcurrencyLimits,
+    state: Mutex<SchedulerState>,
+    available: Condvar,
+}
+
+/// 调度器的内部状态
+#[derive(Debug, Default)]
+struct SchedulerState {
+    running_copy: usize,
+    running_encode: usize,
+
+    /// 最近一次读取的系统负载及读取时间
+    load_sample: Option<(Instant, f64)>,
+}
+
+impl JobScheduler {
+    /// 创建调度器
+    pub fn new(limits: ConcurrencyLimits) -> Self {
+        Self {
+            limits,
+            state: Mutex::new(SchedulerState::default()),
+            available: Condvar::new(),
+        }
+    }
+
+    /// 获取并发上限
+    pub fn limits(&self) -> ConcurrencyLimits {
+        self.limits
+    }
+
+    /// 等待指定类型的任务名额
+    ///
+    /// 返回的 [`JobPermit`] 被丢弃时释放名额。该方法会阻塞当前线程，
+    /// 不应在线程池的工作线程中调用；批量转换请使用 [`dispatch`](Self::dispatch)。
+    ///
+    /// # 错误
+    ///
+    /// 等待期间令牌被取消时返回 [`VideoToAudioError::Cancelled`]
+    pub fn acquire(&self, kind: JobKind, cancel_token: &CancellationToken) -> Result<JobPermit<'_>> {
+        let mut state = self.lock_state();
+        loop {
+            if cancel_token.is_cancelled() {
+                return Err(VideoToAudioError::Cancelled);
+            }
+            if self.has_capacity(&mut state, kind) {
+                return Ok(self.grant(&mut state, kind));
+            }
+            state = self.wait(state);
+        }
+    }
+
+    /// 有空闲名额时立即取得，否则返回 `None`
+    pub fn try_acquire(&self, kind: JobKind) -> Option<JobPermit<'_>> {
+        let mut state = self.lock_state();
+        self.has_capacity(&mut state, kind).then(|| self.grant(&mut state, kind))
+    }
+
+    /// 在 Rayon 作用域中按任务类型派发任务
+    ///
+    /// 只有取得名额的任务才交给线程池执行，名额在任务结束后释放，线程池中的线程不会因为等待名额而阻塞。
+    /// 按列表顺序派发，一类任务的名额用尽时先派发排在后面的另一类任务，同类任务之间保持原来的顺序。
+    /// 暂停期间不派发新任务，停止或取消后不再派发，已派发的任务照常完成。
+    ///
+    /// 派发在调用方线程上进行，`scope` 应通过 `in_place_scope` 创建，
+    /// 否则派发本身会占用线程池中的一个线程。
+    ///
+    /// # 参数
+    ///
+    /// * `scope` - 执行任务的 Rayon 作用域
+    /// * `items` - 要执行的任务，只在需要时才从迭代器中取出
+    /// * `kind_of` - 判断任务类型
+    /// * `cancel_token` - 取消令牌
+    /// * `run` - 在线程池中执行单个任务
+    pub fn dispatch<'scope, T, K, F>(
+        &'scope self,
+        scope: &rayon::Scope<'scope>,
+        items: impl IntoIterator<Item = T>,
+        mut kind_of: K,
+        cancel_token: &CancellationToken,
+        run: &'scope F,
+    ) where
+        T: Send + 'scope,
+        K: FnMut(&T) -> JobKind,
+        F: Fn(T) + Sync,
+    {
+        let mut items = items.into_iter();
+        let mut queue = DispatchQueue::default();
+        let mut exhausted = false;
+        loop {
+            cancel_token.wait_while_paused();
+            if cancel_token.is_stopped() {
+                break;
+            }
+
+            if let Some((item, permit)) = self.take_ready(&mut queue) {
+                scope.spawn(move |_| {
+                    let _permit = permit;
+                    run(item);
+                });
+                continue;
+            }
+
+            // 没有可以立即派发的任务时，继续取出任务寻找另一类任务
+            if !exhausted && queue.len() < DISPATCH_LOOKAHEAD {
+                match items.next() {
+                    Some(item) => {
+                        let kind = kind_of(&item);
+                        queue.push(kind, item);
+                    }
+                    None => exhausted = true,
+                }
+                continue;
+            }
+            if queue.is_empty() {
+                break;
+            }
+
+            // 等待正在运行的任务释放名额
+            let mut state = self.lock_state();
+            if !queue.kinds_in_order().any(|kind| self.has_capacity(&mut state, kind)) {
+                drop(self.wait(state));
+            }
+        }
+    }
+
+    /// 取出队列中最靠前的、有空闲名额的任务
+    fn take_ready<T>(&self, queue: &mut DispatchQueue<T>) -> Option<(T, JobPermit<'_>)> {
+        let mut state = self.lock_state();
+        let kind = queue.kinds_in_order().find(|&kind| self.has_capacity(&mut state, kind))?;
+        let permit = self.grant(&mut state, kind);
+        queue.pop(kind).map(|item| (item, permit))
+    }
+
+    /// 是否可以再开始一个指定类型的任务
+    fn has_capacity(&self, state: &mut SchedulerState, kind: JobKind) -> bool {
+        if state.running_copy + state.running_encode >= self.limits.total {
+            return false;
+        }
+        match kind {
+            JobKind::Copy => state.running_copy < self.limits.copy,
+            JobKind::Encode => state.running_encode < self.encode_limit(state),
+        }
+    }
+
+    /// 占用一个名额
+    fn grant(&self, state: &mut SchedulerState, kind: JobKind) -> JobPermit<'_> {
+        match kind {
+            JobKind::Copy => state.running_copy += 1,
+            JobKind::Encode => state.running_encode += 1,
+        }
+        JobPermit { scheduler: self, kind }
+    }
+
+    /// 等待名额释放，最多等待 [`WAIT_POLL_INTERVAL`]，以便检查取消请求和系统负载
+    fn wait<'a>(&self, state: MutexGuard<'a, SchedulerState>) -> MutexGuard<'a, SchedulerState> {
+        self.available
+            .wait_timeout(state, WAIT_POLL_INTERVAL)
+            .map(|(state, _)| state)
+            .unwrap_or_else(|e| e.into_inner().0)
+    }
+
+    /// 当前允许的编码任务上限
+    ///
+    /// 正在运行的复制任务占用的线程不能用于编码；
+    /// 启用动态调整时，再扣除其他程序造成的负载，最少保留一个编码任务
+    fn encode_limit(&self, state: &mut SchedulerState) -> usize {
+        let limit = self.limits.encode.min(self.limits.total.saturating_sub(state.running_copy));
+        if !self.limits.adaptive {
+            return limit;
+        }
+
+        let load = match state.load_sample {
+            Some((sampled_at, load)) if sampled_at.elapsed() < LOAD_SAMPLE_INTERVAL => Some(load),
+            _ => {
+                let load = system_load();
+                state.load_sample = load.map(|load| (Instant::now(), load));
+                load
+            }
+        };
+        let Some(load) = load else {
+            return limit;
+        };
+
+        let cpus = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1) as f64;
+        // 本程序正在运行的编码任务也计入系统负载，需要先扣除
+        let other_load = (load - state.running_encode as f64).max(0.0);
+        let spare = (cpus - other_load).floor().max(1.0) as usize;
+        spare.min(limit)
+    }
+
+    /// 释放任务名额
+    fn release(&self, kind: JobKind) {
+        let mut state = self.lock_state();
+        match kind {
+            JobKind::Copy => state.running_copy -= 1,
+            JobKind::Encode => state.running_encode -= 1,
+        }
+        drop(state);
+        self.available.notify_all();
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, SchedulerState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// 等待派发的任务，按类型分别排队并记录原来的顺序
+struct DispatchQueue<T> {
+    copy: VecDeque<(usize, T)>,
+    encode: VecDeque<(usize, T)>,
+    next_index: usize,
+}
+
+impl<T> Default for DispatchQueue<T> {
+    fn default() -> Self {
+        Self { copy: VecDeque::new(), encode: VecDeque::new(), next_index: 0 }
+    }
+}
+
+impl<T> DispatchQueue<T> {
+    fn push(&mut self, kind: JobKind, item: T) {
+        let index = self.next_index;
+        self.next_index += 1;
+        self.queue_mut(kind).push_back((index, item));
+    }
+
+    fn pop(&mut self, kind: JobKind) -> Option<T> {
+        self.queue_mut(kind).pop_front().map(|(_, item)| item)
+    }
+
+    fn len(&self) -> usize {
+        self.copy.len() + self.encode.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 各类队列开头任务的类型，排在列表前面的先返回
+    fn kinds_in_order(&self) -> impl Iterator<Item = JobKind> {
+        let mut heads: Vec<_> = [(JobKind::Copy, self.copy.front()), (JobKind::Encode, self.encode.front())]
+            .into_iter()
+            .filter_map(|(kind, head)| head.map(|(index, _)| (*index, kind)))
+            .collect();
+        heads.sort_unstable_by_key(|&(index, _)| index);
+        heads.into_iter().map(|(_, kind)| kind)
+    }
+
+    fn queue_mut(&mut self, kind: JobKind) -> &mut VecDeque<(usize, T)> {
+        match kind {
+            JobKind::Copy => &mut self.copy,
+            JobKind::Encode => &mut self.encode,
+        }
+    }
+}
+
+/// 任务名额，被丢弃时释放
+#[derive(Debug)]
+pub struct JobPermit<'a> {
+    scheduler: &'a JobScheduler,
+    kind: JobKind,
+}
+
+impl Drop for JobPermit<'_> {
+    fn drop(&mut self) {
+        self.scheduler.release(self.kind);
+    }
+}
+
+/// 读取最近一分钟的系统平均负载
+#[cfg(unix)]
+fn system_load() -> Option<f64> {
+    let mut load = [0.0f64; 1];
+    // SAFETY: load 是长度为 1 的可写数组
+    let count = unsafe { libc::getloadavg(load.as_mut_ptr(), 1) };
+    (count == 1).then_some(load[0])
+}
+
+/// 当前平台无法读取系统负载，不做动态调整
+#[cfg(not(unix))]
+fn system_load() -> Option<f64> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::audio_format::AudioFormat;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_job_kind_for_options() {
+        let copy = ConversionOptions::new(AudioFormat::AacCopy);
+        let encode = ConversionOptions::new(AudioFormat::Opus);
+        assert_eq!(JobKind::for_options(&copy, false), JobKind::Copy);
+        assert_eq!(JobKind::for_options(&encode, false), JobKind::Encode);
+        assert_eq!(JobKind::for_options(&encode, true), JobKind::Copy);
+        assert_eq!(ConcurrencyLimits::for_threads(16).copy, DEFAULT_COPY_JOBS);
+    }
+
+    #[test]
+    fn test_scheduler_limits_each_kind_separately() {
+        let scheduler = Arc::new(JobScheduler::new(ConcurrencyLimits {
+            total: 3,
+            copy: 1,
+            encode: 2,
+            adaptive: false,
+        }));
+        let token = CancellationToken::new();
+
+        let _copy = scheduler.acquire(JobKind::Copy, &token).unwrap();
+        let _encode_a = scheduler.acquire(JobKind::Encode, &token).unwrap();
+        let _encode_b = scheduler.acquire(JobKind::Encode, &token).unwrap();
+
+        // 名额用尽时等待，直到取消
+        let waiting = Arc::new(AtomicUsize::new(0));
+        let handle = {
+            let scheduler = Arc::clone(&scheduler);
+            let token = token.clone();
+            let waiting = Arc::clone(&waiting);
+            thread::spawn(move || {
+                waiting.fetch_add(1, Ordering::SeqCst);
+                scheduler.acquire(JobKind::Copy, &token).map(|_| ())
+            })
+        };
+        while waiting.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        }
+        token.cancel();
+        assert!(matches!(handle.join().unwrap(), Err(VideoToAudioError::Cancelled)));
+    }
+
+    #[test]
+    fn test_running_copies_reduce_encode_limit() {
+        let scheduler = JobScheduler::new(ConcurrencyLimits::for_threads(4));
+        let _copies = [scheduler.try_acquire(JobKind::Copy).unwrap(), scheduler.try_acquire(JobKind::Copy).unwrap()];
+        let _encodes = [scheduler.try_acquire(JobKind::Encode).unwrap(), scheduler.try_acquire(JobKind::Encode).unwrap()];
+        // 两个复制任务占用了两个线程，编码任务只能使用剩下的两个
+        assert!(scheduler.try_acquire(JobKind::Encode).is_none());
+        assert!(scheduler.try_acquire(JobKind::Copy).is_none());
+    }
+
+    #[test]
+    fn test_dispatch_mixed_batch_respects_limits_without_blocking_threads() {
+        let limits = ConcurrencyLimits { total: 4, copy: 2, encode: 3, adaptive: false };
+        let scheduler = JobScheduler::new(limits);
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+
+        // 复制任务排在前面，复制名额用尽后应先派发后面的编码任务
+        let jobs: Vec<_> = (0..8).map(|_| JobKind::Copy).chain((0..8).map(|_| JobKind::Encode)).collect();
+        let running = [AtomicUsize::new(0), AtomicUsize::new(0)];
+        let peak = [AtomicUsize::new(0), AtomicUsize::new(0)];
+        let running_total = AtomicUsize::new(0);
+        let peak_total = AtomicUsize::new(0);
+        let first_encode_started_before_copies = AtomicUsize::new(usize::MAX);
+        let copies_done = AtomicUsize::new(0);
+
+        let run = |kind: JobKind| {
+            let index = usize::from(kind == JobKind::Encode);
+            let now = running[index].fetch_add(1, Ordering::SeqCst) + 1;
+            peak[index].fetch_max(now, Ordering::SeqCst);
+            let total = running_total.fetch_add(1, Ordering::SeqCst) + 1;
+            peak_total.fetch_max(total, Ordering::SeqCst);
+            if kind == JobKind::Encode {
+                let _ = first_encode_started_before_copies.compare_exchange(
+                    usize::MAX,
+                    copies_done.load(Ordering::SeqCst),
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                );
+            }
+            thread::sleep(Duration::from_millis(20));
+            running_total.fetch_sub(1, Ordering::SeqCst);
+            running[index].fetch_sub(1, Ordering::SeqCst);
+            if kind == JobKind::Copy {
+                copies_done.fetch_add(1, Ordering::SeqCst);
+            }
+        };
+        pool.in_place_scope(|scope| {
+            scheduler.dispatch(scope, jobs, |&kind| kind, &CancellationToken::new(), &run);
+        });
+
+        assert!(peak[0].load(Ordering::SeqCst) <= limits.copy);
+        assert!(peak[1].load(Ordering::SeqCst) <= limits.encode);
+        // 两类任务合计占满全部线程，没有线程因为等待名额而空闲
+        assert_eq!(peak_total.load(Ordering::SeqCst), limits.total);
+        assert!(first_encode_started_before_copies.load(Ordering::SeqCst) < 8);
+        assert_eq!(copies_done.load(Ordering::SeqCst), 8);
+    }
+
+    #[test]
+    fn test_permit_released_on_drop() {
+        let scheduler = JobScheduler::new(ConcurrencyLimits::for_threads(1));
+        let token = CancellationToken::new();
+        drop(scheduler.acquire(JobKind::Encode, &token).unwrap());
+        assert!(scheduler.acquire(JobKind::Encode, &token).is_ok());
+    }
+}
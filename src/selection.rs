@@ -0,0 +1,522 @@
+//! # 文件选择模块
+//!
+//! 控制扫描到的文件中处理哪些、以什么顺序交给线程池处理。
+//! 文件按顺序派发，先处理耗时最长的文件可以避免批处理末尾只剩一两个
+//! 大文件在运行、其余线程空闲的情况。
+//!
+//! 在大型媒体库上试用新设置时，可以先随机抽取少量文件或只处理前几个文件。
+//! [`FileFilter`] 在扫描阶段排除不需要的文件，例如很小的无用片段或巨大的原始录像。
+//! 也可以用 [`read_file_list`] 从列表文件读取要处理的文件，与 `find`、`fzf` 等工具组合使用。
+//! 扫描时被排除的文件及原因用 [`SkippedFile`] 表示，找不到任何文件时可以据此说明原因。
+
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::FileProcessor;
+use crate::i18n::text;
+use crate::probe;
+use crate::remote;
+use crate::tr;
+use rayon::prelude::*;
+use std::fmt;
+use std::fs;
+use std::io::{self, BufRead};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// 文件处理顺序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// 按文件路径
+    Name,
+
+    /// 按文件大小
+    Size,
+
+    /// 按修改时间
+    Mtime,
+
+    /// 按媒体时长（需要 ffprobe）
+    Duration,
+}
+
+/// 扫描到的文件被排除的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum SkipReason {
+    /// 扩展名不是支持的视频格式
+    UnsupportedExtension,
+
+    /// 纯音频文件，需要 `--include-audio-inputs`
+    AudioInput,
+
+    /// 扩展名在配置文件的 `excluded_extensions` 中
+    ExcludedExtension,
+
+    /// 小于 `--min-size`
+    TooSmall,
+
+    /// 大于 `--max-size`
+    TooLarge,
+
+    /// 修改时间不在 `--since`/`--until` 范围内
+    ModifiedOutOfRange,
+
+    /// 时长不在 `--min-duration`/`--max-duration` 范围内，或无法读取时长
+    DurationOutOfRange,
+
+    /// 无法读取文件信息
+    Unreadable,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SkipReason::UnsupportedExtension => text("不是支持的视频格式", "not a supported video format"),
+            SkipReason::AudioInput => {
+                text("纯音频文件（使用 --include-audio-inputs 包括）", "audio-only file (include with --include-audio-inputs)")
+            }
+            SkipReason::ExcludedExtension => {
+                text("扩展名在配置文件的 excluded_extensions 中", "extension listed in excluded_extensions in the configuration")
+            }
+            SkipReason::TooSmall => text("小于 --min-size", "smaller than --min-size"),
+            SkipReason::TooLarge => text("大于 --max-size", "larger than --max-size"),
+            SkipReason::ModifiedOutOfRange => text("修改时间不在 --since/--until 范围内", "modified outside --since/--until"),
+            SkipReason::DurationOutOfRange => {
+                text("时长不在 --min-duration/--max-duration 范围内", "duration outside --min-duration/--max-duration")
+            }
+            SkipReason::Unreadable => text("无法读取文件信息", "cannot read file information"),
+        })
+    }
+}
+
+/// 扫描时被排除的文件
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFile {
+    /// 文件路径
+    pub path: PathBuf,
+
+    /// 排除的原因
+    pub reason: SkipReason,
+}
+
+/// 扫描时应用的文件筛选条件
+///
+/// 未设置的条件不做限制。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileFilter {
+    /// 最小文件大小（字节）
+    pub min_size: Option<u64>,
+
+    /// 最大文件大小（字节）
+    pub max_size: Option<u64>,
+
+    /// 只保留在该时间之后（含）修改的文件
+    pub modified_since: Option<SystemTime>,
+
+    /// 只保留在该时间之前修改的文件
+    pub modified_until: Option<SystemTime>,
+
+    /// 最短媒体时长（需要 ffprobe）
+    pub min_duration: Option<Duration>,
+
+    /// 最长媒体时长（需要 ffprobe）
+    pub max_duration: Option<Duration>,
+}
+
+impl FileFilter {
+    /// 是否没有设置任何条件
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 判断文件是否满足大小和修改时间条件
+    ///
+    /// 设置了条件但无法读取文件信息时视为不满足
+    pub fn matches(&self, path: &Path) -> bool {
+        self.skip_reason(path).is_none()
+    }
+
+    /// 检查大小和修改时间条件，不满足时返回原因
+    pub fn skip_reason(&self, path: &Path) -> Option<SkipReason> {
+        if self.min_size.is_none()
+            && self.max_size.is_none()
+            && self.modified_since.is_none()
+            && self.modified_until.is_none()
+        {
+            return None;
+        }
+
+        let Ok(metadata) = fs::metadata(path) else {
+            return Some(SkipReason::Unreadable);
+        };
+        let size = metadata.len();
+        if self.min_size.is_some_and(|min| size < min) {
+            return Some(SkipReason::TooSmall);
+        }
+        if self.max_size.is_some_and(|max| size > max) {
+            return Some(SkipReason::TooLarge);
+        }
+
+        let modified_matches = match (self.modified_since, self.modified_until) {
+            (None, None) => true,
+            (since, until) => metadata.modified().is_ok_and(|modified| {
+                since.is_none_or(|since| modified >= since) && until.is_none_or(|until| modified < until)
+            }),
+        };
+        (!modified_matches).then_some(SkipReason::ModifiedOutOfRange)
+    }
+
+    /// 判断文件是否满足时长条件
+    ///
+    /// 需要为每个文件启动一次 ffprobe，应在其他条件之后检查。
+    /// 设置了条件但无法读取时长时视为不满足。
+    pub fn matches_duration(&self, path: &Path, ffmpeg_path: &Path) -> bool {
+        if self.min_duration.is_none() && self.max_duration.is_none() {
+            return true;
+        }
+
+        probe::probe_duration(ffmpeg_path, path).is_ok_and(|duration| {
+            self.min_duration.is_none_or(|min| duration >= min)
+                && self.max_duration.is_none_or(|max| duration <= max)
+        })
+    }
+}
+
+/// 解析时长，如 `10s`、`1m30s`，不带单位的数字按秒计算
+///
+/// # 错误
+///
+/// 格式无效时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+    if let Ok(seconds) = input.parse::<f64>() {
+        if seconds.is_finite() && seconds >= 0.0 {
+            return Ok(Duration::from_secs_f64(seconds));
+        }
+    }
+    humantime::parse_duration(input)
+        .map_err(|_| VideoToAudioError::InvalidInput(tr!(
+            "无效的时长: {input}（示例: 10s、1m30s、90）",
+            "Invalid duration: {input} (examples: 10s, 1m30s, 90)",
+        )))
+}
+
+/// 解析时间点，支持绝对日期和相对时长
+///
+/// - 绝对时间：`2024-01-01` 或 `2024-01-01 08:30:00`（按 UTC 解释）
+/// - 相对时长：`7d`、`12h`、`2weeks` 等，表示从现在往前推算的时间
+///
+/// # 错误
+///
+/// 两种格式都无法识别时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_time(input: &str) -> Result<SystemTime> {
+    let input = input.trim();
+
+    if let Ok(duration) = humantime::parse_duration(input) {
+        return SystemTime::now()
+            .checked_sub(duration)
+            .ok_or_else(|| VideoToAudioError::InvalidInput(tr!("时长过长: {input}", "Duration too long: {input}")));
+    }
+
+    // 只有日期时从当天零点开始
+    let timestamp = if input.len() == "2024-01-01".len() {
+        format!("{input} 00:00:00")
+    } else {
+        input.to_string()
+    };
+    humantime::parse_rfc3339_weak(&timestamp).map_err(|_| VideoToAudioError::InvalidInput(tr!(
+        "无法识别的时间: {input}（示例: 2024-01-01、2024-01-01 08:30:00、7d）",
+        "Unrecognized time: {input} (examples: 2024-01-01, 2024-01-01 08:30:00, 7d)"
+    )))
+}
+
+/// 从列表文件读取要处理的文件，`-` 表示标准输入
+///
+/// 参见 [`read_file_list`]
+///
+/// # 错误
+///
+/// 列表文件无法读取或包含无效路径时返回错误
+pub fn read_file_list_from(list: &Path) -> Result<Vec<PathBuf>> {
+    if list == Path::new("-") {
+        read_file_list(io::stdin().lock())
+    } else {
+        let file = fs::File::open(list).map_err(|e| VideoToAudioError::InvalidPath(tr!(
+            "无法打开文件列表 {}: {e}",
+            "Cannot open the file list {}: {e}",
+            list.display()
+        )))?;
+        read_file_list(io::BufReader::new(file))
+    }
+}
+
+/// 读取每行一个路径的文件列表
+///
+/// 忽略空行和行尾的 `\r`。相对路径相对于当前工作目录，
+/// http(s)/rtmp 等远程地址不检查是否存在，直接交给 FFmpeg 读取。
+/// 所有路径都会被检查，任何一行无效时返回列出全部无效行（含行号）的错误。
+///
+/// # 错误
+///
+/// 读取失败，或存在不存在、不是文件的路径时返回错误
+pub fn read_file_list(mut reader: impl BufRead) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut problems = Vec::new();
+    let mut line = Vec::new();
+    let mut line_number = 0;
+
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        line_number += 1;
+        while line.last().is_some_and(|&b| b == b'\n' || b == b'\r') {
+            line.pop();
+        }
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            let path = path_from_bytes(&line);
+            if remote::is_remote_url(&path) {
+                files.push(path);
+            } else if !path.exists() {
+                problems.push(tr!(
+                    "第 {line_number} 行: 文件不存在: {}",
+                    "Line {line_number}: file does not exist: {}",
+                    path.display(),
+                ));
+            } else if !path.is_file() {
+                problems.push(tr!("第 {line_number} 行: 不是文件: {}", "Line {line_number}: not a file: {}", path.display()));
+            } else {
+                files.push(path);
+            }
+        }
+        line.clear();
+    }
+
+    if problems.is_empty() {
+        Ok(files)
+    } else {
+        Err(VideoToAudioError::InvalidInput(tr!(
+            "文件列表中有 {} 个无效路径:\n  {}",
+            "The file list has {} invalid paths:\n  {}",
+            problems.len(),
+            problems.join("\n  ")
+        )))
+    }
+}
+
+/// 把列表中的一行转换为路径，Unix 上保留不是有效 UTF-8 的文件名
+#[cfg(unix)]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(std::ffi::OsStr::from_bytes(bytes))
+}
+
+/// 把列表中的一行转换为路径
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// 解析文件大小，如 `500K`、`10MB`、`1.5G` 或不带单位的字节数
+///
+/// 单位不区分大小写，按 1024 进制换算。
+///
+/// # 错误
+///
+/// 格式无效时返回 [`VideoToAudioError::InvalidInput`]
+pub fn parse_size(input: &str) -> Result<u64> {
+    let input = input.trim();
+    let split = input
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(input.len());
+    let (number, unit) = input.split_at(split);
+
+    let multiplier: u64 = match unit.trim().to_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(VideoToAudioError::InvalidInput(tr!("无法识别的大小单位: {input}", "Unrecognized size unit: {input}"))),
+    };
+    let number: f64 = number
+        .parse()
+        .map_err(|_| VideoToAudioError::InvalidInput(tr!("无效的文件大小: {input}", "Invalid file size: {input}")))?;
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+/// 按指定顺序排列文件
+///
+/// 默认升序，`descending` 为 `true` 时降序（最大、最新或最长的文件在前）。
+/// 无法读取大小、修改时间或时长的文件排在最后。
+///
+/// # 参数
+///
+/// * `files` - 要排序的文件
+/// * `order` - 排序依据
+/// * `descending` - 是否降序
+/// * `processor` - 按时长排序时用于定位 ffprobe 的文件处理器
+pub fn sort_files(files: &mut [PathBuf], order: SortOrder, descending: bool, processor: &FileProcessor) {
+    match order {
+        SortOrder::Name => {
+            files.sort();
+            if descending {
+                files.reverse();
+            }
+        }
+        SortOrder::Size => sort_by_key(files, descending, |file| fs::metadata(file).ok().map(|m| m.len())),
+        SortOrder::Mtime => sort_by_key(files, descending, |file| {
+            fs::metadata(file).and_then(|m| m.modified()).ok()
+        }),
+        SortOrder::Duration => sort_by_key(files, descending, |file| {
+            probe::probe_duration(processor.ffmpeg_path(), file).ok()
+        }),
+    }
+}
+
+/// 随机抽取指定数量的文件
+///
+/// 抽中的文件保持原来的相对顺序；文件数不超过 `count` 时保留全部文件。
+pub fn sample_files(files: &mut Vec<PathBuf>, count: usize) {
+    if files.len() <= count {
+        return;
+    }
+
+    let mut indices: Vec<usize> = (0..files.len()).collect();
+    fastrand::shuffle(&mut indices);
+    let mut keep = vec![false; files.len()];
+    for &index in &indices[..count] {
+        keep[index] = true;
+    }
+
+    let mut keep = keep.into_iter();
+    files.retain(|_| keep.next().unwrap_or(false));
+}
+
+/// 按并行计算的键排序，缺少键的文件排在最后
+fn sort_by_key<K, F>(files: &mut [PathBuf], descending: bool, key: F)
+where
+    K: Ord + Send,
+    F: Fn(&PathBuf) -> Option<K> + Sync,
+{
+    // 读取时长需要为每个文件启动 ffprobe，因此并行计算排序键
+    let mut keyed: Vec<(Option<K>, PathBuf)> = files
+        .par_iter()
+        .map(|file| (key(file), file.clone()))
+        .collect();
+
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) if descending => b.cmp(a),
+        (Some(a), Some(b)) => a.cmp(b),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+
+    for (slot, (_, file)) in files.iter_mut().zip(keyed) {
+        *slot = file;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024").unwrap(), 1024);
+        assert_eq!(parse_size("500K").unwrap(), 500 * 1024);
+        assert_eq!(parse_size("1.5 GB").unwrap(), 1536 * 1024 * 1024);
+        assert!(parse_size("10 parsecs").is_err());
+        assert!(parse_size("MB").is_err());
+    }
+
+    #[test]
+    fn test_size_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("clip.mp4");
+        fs::write(&file, vec![0u8; 100]).unwrap();
+
+        let filter = FileFilter { min_size: Some(50), max_size: Some(200), ..Default::default() };
+        assert!(filter.matches(&file));
+        assert!(!FileFilter { min_size: Some(101), ..Default::default() }.matches(&file));
+        assert!(!FileFilter { max_size: Some(99), ..Default::default() }.matches(&file));
+        assert!(!filter.matches(&temp_dir.path().join("missing.mp4")));
+    }
+
+    #[test]
+    fn test_parse_time() {
+        let date = parse_time("2024-01-01").unwrap();
+        assert_eq!(date, humantime::parse_rfc3339("2024-01-01T00:00:00Z").unwrap());
+        assert!(parse_time("2024-01-01 08:30:00").unwrap() > date);
+
+        let week_ago = parse_time("7d").unwrap();
+        let elapsed = SystemTime::now().duration_since(week_ago).unwrap();
+        assert!(elapsed >= std::time::Duration::from_secs(7 * 24 * 3600));
+        assert!(parse_time("last tuesday").is_err());
+    }
+
+    #[test]
+    fn test_modified_time_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("clip.mp4");
+        fs::write(&file, "fake").unwrap();
+
+        let recent = FileFilter { modified_since: Some(parse_time("1h").unwrap()), ..Default::default() };
+        assert!(recent.matches(&file));
+        let old = FileFilter { modified_until: Some(parse_time("1h").unwrap()), ..Default::default() };
+        assert!(!old.matches(&file));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("1m30s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("2.5").unwrap(), Duration::from_millis(2500));
+        assert!(parse_duration("-3").is_err());
+    }
+
+    #[test]
+    fn test_read_file_list_reports_line_numbers() {
+        let temp_dir = TempDir::new().unwrap();
+        let clip = temp_dir.path().join("clip.mp4");
+        fs::write(&clip, "fake").unwrap();
+
+        let list = format!("{}\r\n\n{}\n", clip.display(), temp_dir.path().display());
+        assert_eq!(read_file_list(list.as_bytes()).unwrap_err().to_string().matches("第 3 行").count(), 1);
+
+        let list = format!("{}\n\n", clip.display());
+        assert_eq!(read_file_list(list.as_bytes()).unwrap(), [clip]);
+    }
+
+    #[test]
+    fn test_sample_keeps_relative_order() {
+        let files: Vec<PathBuf> = (0..20).map(|i| PathBuf::from(format!("{i:02}.mp4"))).collect();
+
+        let mut sampled = files.clone();
+        sample_files(&mut sampled, 5);
+        assert_eq!(sampled.len(), 5);
+        assert!(sampled.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(sampled.iter().all(|file| files.contains(file)));
+
+        let mut all = files.clone();
+        sample_files(&mut all, 50);
+        assert_eq!(all, files);
+    }
+
+    #[test]
+    fn test_sort_by_size_descending() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files: Vec<PathBuf> = [("small.mp4", 1), ("large.mp4", 30), ("medium.mp4", 10)]
+            .iter()
+            .map(|(name, size)| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, vec![0u8; *size]).unwrap();
+                path
+            })
+            .collect();
+        files.push(temp_dir.path().join("missing.mp4"));
+
+        sort_files(&mut files, SortOrder::Size, true, &FileProcessor::new());
+
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, ["large.mp4", "medium.mp4", "small.mp4", "missing.mp4"]);
+    }
+}
@@ -0,0 +1,444 @@
+//! # HTTP API 服务模块
+//!
+//! 提供一个只面向本机的小型 REST API，供家庭自动化等系统提交转换任务，
+//! 而无需每个文件都调用一次命令行。任务在内部队列中依次执行，
+//! 每个任务内部仍使用 [`FileProcessor`] 的并行批量转换。
+//!
+//! ## 接口
+//!
+//! | 方法 | 路径 | 说明 |
+//! |------|------|------|
+//! | `GET` | `/formats` | 列出支持的音频格式 |
+//! | `GET` | `/jobs` | 列出所有任务 |
+//! | `POST` | `/jobs` | 提交任务，请求体 `{"source": "...", "format": "mp3", "output_dir": "..."}` |
+//! | `GET` | `/jobs/{id}` | 查询任务状态和进度 |
+//! | `DELETE` | `/jobs/{id}` | 取消任务 |
+
+use crate::audio_format::AudioFormat;
+use crate::cancellation::CancellationToken;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::file_processor::FileProcessor;
+use crate::i18n::text;
+use crate::lock::{locked_error, OutputLock};
+use crate::tr;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// 等待 HTTP 请求的超时，决定检查取消请求的频率
+const REQUEST_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 任务状态
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// 排队等待中
+    Queued,
+    /// 正在转换
+    Running,
+    /// 已完成（可能包含失败的文件）
+    Completed,
+    /// 任务本身失败（如源目录无法扫描）
+    Failed,
+    /// 已被取消
+    Cancelled,
+}
+
+/// 提交任务的请求体
+#[derive(Deserialize, Debug, Clone)]
+pub struct JobRequest {
+    /// 源视频文件或目录
+    pub source: PathBuf,
+
+    /// 目标音频格式 (mp3/aac/opus/m4b)
+    pub format: String,
+
+    /// 输出目录，未设置时为源目录下的 audio_exports
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+}
+
+/// 任务状态快照，作为 API 响应返回
+#[derive(Serialize, Debug, Clone)]
+pub struct JobStatus {
+    /// 任务编号
+    pub id: u64,
+    /// 当前状态
+    pub state: JobState,
+    /// 源视频文件或目录
+    pub source: PathBuf,
+    /// 目标音频格式扩展名
+    pub format: &'static str,
+    /// 输出目录（开始运行后确定）
+    pub output_dir: Option<PathBuf>,
+    /// 文件总数
+    pub total: usize,
+    /// 已处理的文件数
+    pub completed: usize,
+    /// 成功的文件数
+    pub succeeded: usize,
+    /// 失败的文件数
+    pub failed: usize,
+    /// 每个失败文件的错误信息
+    pub failures: Vec<FileFailure>,
+    /// 任务级错误信息
+    pub error: Option<String>,
+}
+
+/// 任务中单个文件的失败
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FileFailure {
+    /// 源文件
+    pub source: PathBuf,
+    /// 错误信息
+    pub error: String,
+    /// 错误代码，参见 [`crate::error_codes`]
+    pub code: &'static str,
+}
+
+/// 队列中的任务
+struct Job {
+    status: JobStatus,
+    request: JobRequest,
+    format: AudioFormat,
+    cancel_token: CancellationToken,
+}
+
+/// 任务队列
+///
+/// 提交的任务按顺序在后台工作线程中执行，状态可随时查询。
+pub struct JobQueue {
+    jobs: Arc<Mutex<BTreeMap<u64, Job>>>,
+    sender: mpsc::Sender<u64>,
+    next_id: AtomicU64,
+}
+
+impl JobQueue {
+    /// 创建任务队列并启动后台工作线程
+    pub fn new(processor: FileProcessor) -> Self {
+        let jobs: Arc<Mutex<BTreeMap<u64, Job>>> = Arc::new(Mutex::new(BTreeMap::new()));
+        let (sender, receiver) = mpsc::channel::<u64>();
+
+        let worker_jobs = Arc::clone(&jobs);
+        thread::spawn(move || {
+            for id in receiver {
+                run_job(&processor, &worker_jobs, id);
+            }
+        });
+
+        Self {
+            jobs,
+            sender,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// 提交任务
+    ///
+    /// # 错误
+    ///
+    /// 当源路径不存在或格式无效时返回错误
+    pub fn submit(&self, request: JobRequest) -> Result<JobStatus> {
+        if !request.source.exists() {
+            return Err(VideoToAudioError::InvalidPath(
+                tr!("源路径不存在: {}", "Source path does not exist: {}", request.source.display())
+            ));
+        }
+        let format = AudioFormat::from_user_input(&request.format)?;
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let status = JobStatus {
+            id,
+            state: JobState::Queued,
+            source: request.source.clone(),
+            format: format.extension(),
+            output_dir: request.output_dir.clone(),
+            total: 0,
+            completed: 0,
+            succeeded: 0,
+            failed: 0,
+            failures: Vec::new(),
+            error: None,
+        };
+
+        self.lock_jobs().insert(id, Job {
+            status: status.clone(),
+            request,
+            format,
+            cancel_token: CancellationToken::new(),
+        });
+        // 工作线程与队列同生命周期，发送不会失败
+        let _ = self.sender.send(id);
+
+        Ok(status)
+    }
+
+    /// 查询任务状态
+    pub fn status(&self, id: u64) -> Option<JobStatus> {
+        self.lock_jobs().get(&id).map(|job| job.status.clone())
+    }
+
+    /// 列出所有任务状态
+    pub fn list(&self) -> Vec<JobStatus> {
+        self.lock_jobs().values().map(|job| job.status.clone()).collect()
+    }
+
+    /// 取消任务
+    ///
+    /// 排队中的任务不会再开始，运行中的任务会终止正在进行的转换。
+    pub fn cancel(&self, id: u64) -> Option<JobStatus> {
+        let mut jobs = self.lock_jobs();
+        let job = jobs.get_mut(&id)?;
+        job.cancel_token.cancel();
+        if job.status.state == JobState::Queued {
+            job.status.state = JobState::Cancelled;
+        }
+        Some(job.status.clone())
+    }
+
+    /// 处理一个 API 请求
+    ///
+    /// 与具体的 HTTP 实现无关，便于测试。
+    ///
+    /// # 返回值
+    ///
+    /// (HTTP 状态码, JSON 响应体)
+    pub fn handle(&self, method: &str, url: &str, body: &str) -> (u16, Value) {
+        let path = url.split('?').next().unwrap_or("").trim_end_matches('/');
+        let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+        match (method, segments.as_slice()) {
+            ("GET", ["formats"]) => {
+                let formats: Vec<Value> = AudioFormat::all_formats()
+                    .into_iter()
+                    .map(|format| json!({
+                        "name": format.extension(),
+                        "description": format.description(),
+                    }))
+                    .collect();
+                (200, json!(formats))
+            }
+            ("GET", ["jobs"]) => (200, json!(self.list())),
+            ("POST", ["jobs"]) => {
+                let request: JobRequest = match serde_json::from_str(body) {
+                    Ok(request) => request,
+                    Err(e) => return error_response(400, &tr!("请求体格式错误: {e}", "Malformed request body: {e}")),
+                };
+                match self.submit(request) {
+                    Ok(status) => (202, json!(status)),
+                    Err(e) => error_response(400, &e.to_string()),
+                }
+            }
+            ("GET", ["jobs", id]) => match id.parse().ok().and_then(|id| self.status(id)) {
+                Some(status) => (200, json!(status)),
+                None => error_response(404, text("任务不存在", "No such job")),
+            },
+            ("DELETE", ["jobs", id]) => match id.parse().ok().and_then(|id| self.cancel(id)) {
+                Some(status) => (200, json!(status)),
+                None => error_response(404, text("任务不存在", "No such job")),
+            },
+            (_, ["formats"]) | (_, ["jobs"]) | (_, ["jobs", _]) => {
+                error_response(405, text("不支持的请求方法", "Method not allowed"))
+            }
+            _ => error_response(404, text("接口不存在", "Not found")),
+        }
+    }
+
+    fn lock_jobs(&self) -> std::sync::MutexGuard<'_, BTreeMap<u64, Job>> {
+        self.jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// 启动 HTTP 服务，直到取消令牌被取消
+///
+/// # 参数
+///
+/// * `addr` - 监听地址，例如 `127.0.0.1:8080`
+/// * `processor` - 执行转换的文件处理器
+/// * `cancel_token` - 取消令牌，取消后停止服务并终止所有任务
+///
+/// # 错误
+///
+/// 当地址无法绑定时返回错误
+pub fn serve(addr: &str, processor: FileProcessor, cancel_token: &CancellationToken) -> Result<()> {
+    let server = tiny_http::Server::http(addr).map_err(|e| {
+        VideoToAudioError::from(std::io::Error::other(tr!("无法监听 {addr}: {e}", "Cannot listen on {addr}: {e}")))
+    })?;
+    let queue = JobQueue::new(processor);
+    let content_type = tiny_http::Header::from_bytes("Content-Type", "application/json; charset=utf-8")
+        .expect("静态响应头必然有效");
+
+    while !cancel_token.is_cancelled() {
+        let Some(mut request) = server.recv_timeout(REQUEST_POLL_INTERVAL)? else {
+            continue;
+        };
+
+        let mut body = String::new();
+        let (status, response) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => queue.handle(request.method().as_str(), request.url(), &body),
+            Err(e) => error_response(400, &tr!("无法读取请求体: {e}", "Cannot read the request body: {e}")),
+        };
+
+        let response = tiny_http::Response::from_string(response.to_string())
+            .with_status_code(status)
+            .with_header(content_type.clone());
+        // 客户端提前断开连接不影响服务
+        let _ = request.respond(response);
+    }
+
+    for job in queue.list() {
+        queue.cancel(job.id);
+    }
+    Ok(())
+}
+
+/// 执行单个任务并更新其状态
+fn run_job(processor: &FileProcessor, jobs: &Mutex<BTreeMap<u64, Job>>, id: u64) {
+    let lock = || jobs.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let update = |f: &dyn Fn(&mut JobStatus)| {
+        if let Some(job) = lock().get_mut(&id) {
+            f(&mut job.status);
+        }
+    };
+
+    let (request, format, cancel_token) = {
+        let mut jobs = lock();
+        let Some(job) = jobs.get_mut(&id) else { return };
+        if job.cancel_token.is_cancelled() {
+            return;
+        }
+        job.status.state = JobState::Running;
+        (job.request.clone(), job.format, job.cancel_token.clone())
+    };
+
+    let prepared = resolve_job_files(processor, &request.source, request.output_dir.as_deref());
+
+    let (files, output_dir, _output_lock) = match prepared {
+        Ok(prepared) => prepared,
+        Err(e) => {
+            update(&|status| {
+                status.state = JobState::Failed;
+                status.error = Some(e.to_string());
+            });
+            return;
+        }
+    };
+
+    update(&|status| {
+        status.total = files.len();
+        status.output_dir = Some(output_dir.clone());
+    });
+
+    let (succeeded, failed) = processor.batch_convert_with_results(
+        &files,
+        &output_dir,
+        format,
+        &cancel_token,
+        |source, result, current, _total| {
+            update(&|status| {
+                status.completed = current;
+                if let Err(e) = result {
+                    status.failures.push(FileFailure { source: source.to_path_buf(), error: e.to_string(), code: e.code() });
+                }
+            });
+        },
+    );
+
+    update(&|status| {
+        status.succeeded = succeeded;
+        status.failed = failed;
+        status.state = if cancel_token.is_cancelled() {
+            JobState::Cancelled
+        } else {
+            JobState::Completed
+        };
+    });
+}
+
+/// 确定任务要转换的文件和输出目录
+///
+/// 源路径为目录时扫描其中的视频文件，为文件时只转换该文件；
+/// 未指定输出目录时在源目录（或源文件所在目录）下创建默认输出目录。
+/// 返回的锁在任务结束前一直持有，输出目录已被其他实例锁定时任务直接失败。
+pub(crate) fn resolve_job_files(
+    processor: &FileProcessor,
+    source: &Path,
+    output_dir: Option<&Path>,
+) -> Result<(Vec<PathBuf>, PathBuf, OutputLock)> {
+    let (files, base_dir) = if source.is_dir() {
+        let scanner = match output_dir {
+            Some(dir) => processor.clone().with_excluded_dir(dir),
+            None => processor.clone(),
+        };
+        (scanner.find_video_files(source)?, source.to_path_buf())
+    } else {
+        let parent = source.parent().map(PathBuf::from).unwrap_or_default();
+        (vec![source.to_path_buf()], parent)
+    };
+    let output_dir = match output_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir).context(text("创建输出目录", "create output directory"), dir)?;
+            dir.to_path_buf()
+        }
+        None => processor.create_output_directory(&base_dir)?,
+    };
+    let lock = OutputLock::try_acquire(&output_dir)?.ok_or_else(|| locked_error(&output_dir))?;
+    Ok((files, output_dir, lock))
+}
+
+/// 构造错误响应
+fn error_response(status: u16, message: &str) -> (u16, Value) {
+    (status, json!({ "error": message }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_formats_endpoint() {
+        let queue = JobQueue::new(FileProcessor::new());
+        let (status, body) = queue.handle("GET", "/formats", "");
+        assert_eq!(status, 200);
+        assert_eq!(body.as_array().unwrap().len(), AudioFormat::all_formats().len());
+    }
+
+    #[test]
+    fn test_submit_and_query_job() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = JobQueue::new(FileProcessor::new());
+
+        let request = json!({ "source": temp_dir.path(), "format": "opus" }).to_string();
+        let (status, body) = queue.handle("POST", "/jobs", &request);
+        assert_eq!(status, 202);
+        assert_eq!(body["format"], "opus");
+
+        let id = body["id"].as_u64().unwrap();
+        let (status, body) = queue.handle("GET", &format!("/jobs/{id}"), "");
+        assert_eq!(status, 200);
+        assert_eq!(body["id"], id);
+
+        let (status, body) = queue.handle("GET", "/jobs", "");
+        assert_eq!(status, 200);
+        assert_eq!(body.as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        let queue = JobQueue::new(FileProcessor::new());
+
+        assert_eq!(queue.handle("POST", "/jobs", "not json").0, 400);
+        let missing = json!({ "source": "/nonexistent/video.mp4", "format": "mp3" }).to_string();
+        assert_eq!(queue.handle("POST", "/jobs", &missing).0, 400);
+        assert_eq!(queue.handle("GET", "/jobs/999", "").0, 404);
+        assert_eq!(queue.handle("DELETE", "/jobs/abc", "").0, 404);
+        assert_eq!(queue.handle("PUT", "/formats", "").0, 405);
+        assert_eq!(queue.handle("GET", "/unknown", "").0, 404);
+    }
+}
@@ -0,0 +1,52 @@
+//! # 终端配色模块
+//!
+//! 成功信息显示为绿色，失败为红色，警告为黄色。
+//!
+//! 标准输出或标准错误不是终端、设置了 `NO_COLOR` 环境变量或指定了 `--no-color` 时
+//! 不输出颜色。写入标准错误的文本需要调用 [`console::StyledObject::for_stderr`]，
+//! 按标准错误是否为终端判断。
+
+use console::StyledObject;
+use std::fmt::Display;
+
+/// 成功信息（绿色）
+pub fn success<D: Display>(text: D) -> StyledObject<D> {
+    console::style(text).green()
+}
+
+/// 失败信息（红色）
+pub fn failure<D: Display>(text: D) -> StyledObject<D> {
+    console::style(text).red()
+}
+
+/// 警告信息（黄色）
+pub fn warning<D: Display>(text: D) -> StyledObject<D> {
+    console::style(text).yellow()
+}
+
+/// 标准错误流是否输出颜色
+pub fn stderr_colors_enabled() -> bool {
+    console::colors_enabled_stderr()
+}
+
+/// 关闭标准输出和标准错误的彩色输出
+///
+/// 同时影响进度条和交互菜单的配色
+pub fn disable_colors() {
+    console::set_colors_enabled(false);
+    console::set_colors_enabled_stderr(false);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_styles_only_add_color_codes() {
+        let styled = success("✅ 完成").force_styling(true).to_string();
+        assert!(styled.contains("\x1b[32m"));
+        assert_eq!(console::strip_ansi_codes(&styled), "✅ 完成");
+        assert!(failure("x").force_styling(true).to_string().contains("\x1b[31m"));
+        assert!(warning("x").force_styling(true).to_string().contains("\x1b[33m"));
+    }
+}
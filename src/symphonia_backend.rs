@@ -0,0 +1,428 @@
+//! # Symphonia 转换后端
+//!
+//! 使用纯 Rust 的 Symphonia 解封装 MP4/MKV，直接提取其中的 AAC、MP3 或 Opus 音频流，
+//! 不需要任何外部程序。仅在启用 `symphonia` 特性时编译。
+//!
+//! 该后端不会重新编码：输出格式必须与源音频流的编码一致，
+//! 码率、采样率和滤镜等需要重新编码的选项都不受支持。
+//! AAC 输出为 ADTS 流，Opus 输出为 Ogg 封装，MP3 帧直接写出。
+
+use crate::audio_format::AudioFormat;
+use crate::backend::ConversionBackend;
+use crate::cancellation::CancellationToken;
+use crate::error::{IoResultExt, Result, VideoToAudioError};
+use crate::file_processor::FileProcessor;
+use crate::i18n::text;
+use crate::options::ConversionOptions;
+use crate::tr;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+use symphonia::core::codecs::{CodecParameters, CodecType, CODEC_TYPE_AAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// 基于 Symphonia 的音频流提取后端
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SymphoniaBackend;
+
+impl SymphoniaBackend {
+    /// 创建后端实例
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl ConversionBackend for SymphoniaBackend {
+    fn name(&self) -> &'static str {
+        "symphonia"
+    }
+
+    fn check_available(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn convert(
+        &self,
+        source_file: &Path,
+        partial_path: &Path,
+        options: &ConversionOptions,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        if options.encoder.is_some()
+            || options.bitrate.is_some()
+            || options.sample_rate.is_some()
+            || !options.filter_chain().is_empty()
+            || options.audio_stream.is_some()
+            || options.start.is_some()
+            || options.end.is_some()
+            || !options.tags.is_empty()
+            || (options.cover.is_some() && options.format.supports_cover())
+        {
+            return Err(VideoToAudioError::InvalidInput(
+                text(
+                    "Symphonia 后端只能完整提取默认音频流，不支持编码器、码率、采样率、滤镜、静音裁剪、响度标准化、音轨选择、片段、标签和封面设置",
+                    "The Symphonia backend can only extract the whole default audio stream and does not support encoder, bitrate, sample rate, filter, silence trimming, loudness normalization, track selection, clip, tag or cover settings",
+                ).to_string()
+            ));
+        }
+
+        let codec = match options.format {
+            AudioFormat::AacCopy => CODEC_TYPE_AAC,
+            AudioFormat::Mp3 => CODEC_TYPE_MP3,
+            AudioFormat::Opus => CODEC_TYPE_OPUS,
+            AudioFormat::M4b => {
+                return Err(VideoToAudioError::UnsupportedFormat(
+                    text(
+                        "Symphonia 后端不支持 M4B 格式，请使用 FFmpeg",
+                        "The Symphonia backend does not support M4B, use FFmpeg",
+                    ).to_string()
+                ));
+            }
+        };
+
+        let mut hint = Hint::new();
+        if let Some(extension) = source_file.extension().and_then(|ext| ext.to_str()) {
+            hint.with_extension(extension);
+        }
+        let stream = MediaSourceStream::new(Box::new(File::open(source_file).context(text(
+            "打开源文件",
+            "open source file",
+        ), source_file)?), Default::default());
+        let mut reader = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .map_err(|e| VideoToAudioError::UnsupportedFormat(tr!(
+                "Symphonia 无法解析 {}: {e}",
+                "Symphonia cannot parse {}: {e}", source_file.display()
+            )))?
+            .format;
+
+        let track = reader
+            .tracks()
+            .iter()
+            .find(|track| track.codec_params.codec == codec)
+            .ok_or_else(|| VideoToAudioError::UnsupportedFormat(tr!(
+                "源文件中没有可直接提取为 {} 的音频流（Symphonia 后端不会重新编码）",
+                "The source has no audio stream that can be extracted as {} as is (the Symphonia backend does not re-encode)",
+                options.format.extension().to_uppercase()
+            )))?;
+        let track_id = track.id;
+
+        let file = BufWriter::new(File::create(partial_path)?);
+        let mut writer = StreamWriter::new(codec, &track.codec_params, file)?;
+        let started_at = Instant::now();
+
+        loop {
+            if cancel_token.is_cancelled() {
+                return Err(VideoToAudioError::Cancelled);
+            }
+            if let Some(timeout) = options.timeout.filter(|timeout| started_at.elapsed() >= *timeout) {
+                return Err(FileProcessor::timeout_error(timeout));
+            }
+
+            let packet = match reader.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(SymphoniaError::ResetRequired) => break,
+                Err(SymphoniaError::IoError(e)) => return Err(VideoToAudioError::io(text(
+                    "读取音频数据",
+                    "read audio data",
+                ), source_file, e)),
+                Err(e) => return Err(VideoToAudioError::UnsupportedFormat(tr!(
+                    "读取音频数据失败: {e}",
+                    "Failed to read audio data: {e}"
+                ))),
+            };
+            if packet.track_id() == track_id {
+                writer.write_packet(&packet.data)?;
+            }
+        }
+
+        writer.finish()
+    }
+}
+
+/// 把解封装得到的数据包写成独立的音频文件
+enum StreamWriter<W: Write> {
+    /// 每个 AAC 帧前加上 ADTS 头
+    Adts { writer: W, header: AdtsHeader },
+    /// MP3 帧本身就能直接播放
+    Raw { writer: W },
+    /// Opus 数据包封装为 Ogg 页
+    Ogg { writer: OggWriter<W>, pending: Option<Vec<u8>>, granule: u64 },
+}
+
+impl<W: Write> StreamWriter<W> {
+    fn new(codec: CodecType, params: &CodecParameters, writer: W) -> Result<Self> {
+        Ok(match codec {
+            CODEC_TYPE_AAC => Self::Adts { writer, header: AdtsHeader::from_params(params)? },
+            CODEC_TYPE_OPUS => {
+                let mut writer = OggWriter::new(writer);
+                writer.write_page(&opus_head(params), 0, OGG_BEGIN_OF_STREAM)?;
+                writer.write_page(&opus_tags(), 0, 0)?;
+                Self::Ogg { writer, pending: None, granule: 0 }
+            }
+            _ => Self::Raw { writer },
+        })
+    }
+
+    fn write_packet(&mut self, data: &[u8]) -> Result<()> {
+        match self {
+            Self::Adts { writer, header } => {
+                writer.write_all(&header.encode(data.len())?)?;
+                writer.write_all(data)?;
+            }
+            Self::Raw { writer } => writer.write_all(data)?,
+            // 最后一个数据包所在的页需要标记流结束，因此延迟写出一个数据包
+            Self::Ogg { writer, pending, granule } => {
+                if let Some(previous) = pending.replace(data.to_vec()) {
+                    *granule += opus_packet_samples(&previous);
+                    writer.write_page(&previous, *granule, 0)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        let mut writer = match self {
+            Self::Adts { writer, .. } | Self::Raw { writer } => writer,
+            Self::Ogg { mut writer, pending, granule } => {
+                let last = pending.unwrap_or_default();
+                let granule = granule + opus_packet_samples(&last);
+                writer.write_page(&last, granule, OGG_END_OF_STREAM)?;
+                writer.inner
+            }
+        };
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// ADTS 头中不随帧变化的字段
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct AdtsHeader {
+    /// AAC 规格（audio object type - 1）
+    profile: u8,
+    /// 采样率索引
+    frequency_index: u8,
+    /// 声道配置
+    channel_config: u8,
+}
+
+/// ADTS 采样率索引表
+const ADTS_SAMPLE_RATES: [u32; 13] = [
+    96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350,
+];
+
+impl AdtsHeader {
+    /// 从 AudioSpecificConfig 或编码参数中读取 ADTS 头字段
+    fn from_params(params: &CodecParameters) -> Result<Self> {
+        if let Some(&[first, second, ..]) = params.extra_data.as_deref() {
+            return Ok(Self {
+                profile: (first >> 3).saturating_sub(1).min(3),
+                frequency_index: ((first & 0x07) << 1) | (second >> 7),
+                channel_config: (second >> 3) & 0x0F,
+            });
+        }
+
+        // 没有 AudioSpecificConfig 时按 AAC-LC 处理
+        let frequency_index = params
+            .sample_rate
+            .and_then(|rate| ADTS_SAMPLE_RATES.iter().position(|&r| r == rate))
+            .ok_or_else(|| VideoToAudioError::UnsupportedFormat(
+                text("无法确定 AAC 音频流的采样率", "Cannot determine the sample rate of the AAC stream").to_string()
+            ))?;
+        let channel_config = params.channels.map_or(2, |channels| channels.count());
+        Ok(Self {
+            profile: 1,
+            frequency_index: frequency_index as u8,
+            channel_config: channel_config as u8,
+        })
+    }
+
+    /// 生成一帧的 7 字节 ADTS 头（不含 CRC）
+    fn encode(&self, payload_len: usize) -> Result<[u8; 7]> {
+        let frame_len = payload_len + 7;
+        if frame_len > 0x1FFF {
+            return Err(VideoToAudioError::UnsupportedFormat(
+                text("AAC 帧过大，无法封装为 ADTS", "The AAC frame is too large for ADTS").to_string()
+            ));
+        }
+        let frame_len = frame_len as u16;
+
+        Ok([
+            0xFF,
+            0xF1,
+            (self.profile << 6) | (self.frequency_index << 2) | (self.channel_config >> 2),
+            ((self.channel_config & 0x03) << 6) | (frame_len >> 11) as u8,
+            (frame_len >> 3) as u8,
+            ((frame_len & 0x07) << 5) as u8 | 0x1F,
+            0xFC,
+        ])
+    }
+}
+
+/// Ogg 页的流开始标记
+const OGG_BEGIN_OF_STREAM: u8 = 0x02;
+
+/// Ogg 页的流结束标记
+const OGG_END_OF_STREAM: u8 = 0x04;
+
+/// 最简单的 Ogg 封装：每个数据包单独占一页
+struct OggWriter<W: Write> {
+    inner: W,
+    serial: u32,
+    sequence: u32,
+}
+
+impl<W: Write> OggWriter<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, serial: 0x7632_6121, sequence: 0 }
+    }
+
+    fn write_page(&mut self, packet: &[u8], granule: u64, flags: u8) -> Result<()> {
+        // 分段表：每段最多 255 字节，以不足 255 的一段结尾
+        let mut lacing = vec![255u8; packet.len() / 255];
+        lacing.push((packet.len() % 255) as u8);
+        if lacing.len() > 255 {
+            return Err(VideoToAudioError::UnsupportedFormat(
+                text("Opus 数据包过大，无法封装为 Ogg", "The Opus packet is too large for Ogg").to_string()
+            ));
+        }
+
+        let mut page = Vec::with_capacity(27 + lacing.len() + packet.len());
+        page.extend_from_slice(b"OggS");
+        page.push(0);
+        page.push(flags);
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.sequence.to_le_bytes());
+        page.extend_from_slice(&[0; 4]);
+        page.push(lacing.len() as u8);
+        page.extend_from_slice(&lacing);
+        page.extend_from_slice(packet);
+
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+
+        self.inner.write_all(&page)?;
+        self.sequence += 1;
+        Ok(())
+    }
+}
+
+/// Ogg 页校验和（多项式 0x04C11DB7，不反转，初值为 0）
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0u32, |mut crc, &byte| {
+        crc ^= u32::from(byte) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 != 0 { (crc << 1) ^ 0x04C1_1DB7 } else { crc << 1 };
+        }
+        crc
+    })
+}
+
+/// 生成 OpusHead 头
+///
+/// MKV 的 CodecPrivate 本身就是 OpusHead；MP4 中的 dOps 盒字段为大端序、版本号为 0，需要转换。
+fn opus_head(params: &CodecParameters) -> Vec<u8> {
+    match params.extra_data.as_deref() {
+        Some(head) if head.len() >= 19 && head.starts_with(b"OpusHead") && head[8] != 0 => head.to_vec(),
+        Some(dops) if dops.len() >= 19 && dops.starts_with(b"OpusHead") => {
+            let mut head = b"OpusHead".to_vec();
+            head.push(1);
+            head.push(dops[9]);
+            head.extend_from_slice(&u16::from_be_bytes([dops[10], dops[11]]).to_le_bytes());
+            head.extend_from_slice(&u32::from_be_bytes([dops[12], dops[13], dops[14], dops[15]]).to_le_bytes());
+            head.extend_from_slice(&i16::from_be_bytes([dops[16], dops[17]]).to_le_bytes());
+            head.extend_from_slice(&dops[18..]);
+            head
+        }
+        _ => {
+            let channels = params.channels.map_or(2, |channels| channels.count()) as u8;
+            let pre_skip = params.delay.unwrap_or(0) as u16;
+            let mut head = b"OpusHead".to_vec();
+            head.extend_from_slice(&[1, channels]);
+            head.extend_from_slice(&pre_skip.to_le_bytes());
+            head.extend_from_slice(&params.sample_rate.unwrap_or(48000).to_le_bytes());
+            head.extend_from_slice(&[0, 0, 0]);
+            head
+        }
+    }
+}
+
+/// 生成不含任何注释的 OpusTags 头
+fn opus_tags() -> Vec<u8> {
+    let vendor = concat!("video2audio-rs ", env!("CARGO_PKG_VERSION"));
+    let mut tags = b"OpusTags".to_vec();
+    tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+    tags.extend_from_slice(vendor.as_bytes());
+    tags.extend_from_slice(&0u32.to_le_bytes());
+    tags
+}
+
+/// 根据 TOC 字节计算 Opus 数据包包含的采样数（48kHz）
+fn opus_packet_samples(packet: &[u8]) -> u64 {
+    let Some(&toc) = packet.first() else { return 0 };
+    let config = toc >> 3;
+    let frame_size: u64 = match config {
+        0..=11 => [480, 960, 1920, 2880][usize::from(config % 4)],
+        12..=15 => [480, 960][usize::from(config % 2)],
+        _ => [120, 240, 480, 960][usize::from(config % 4)],
+    };
+    let frames = match toc & 0x03 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => packet.get(1).map_or(0, |&count| u64::from(count & 0x3F)),
+    };
+    frame_size * frames
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adts_header_from_audio_specific_config() {
+        // AAC-LC, 44.1kHz, 立体声
+        let mut params = CodecParameters::new();
+        params.with_extra_data(vec![0x12, 0x10].into_boxed_slice());
+        let header = AdtsHeader::from_params(&params).unwrap();
+        assert_eq!(header, AdtsHeader { profile: 1, frequency_index: 4, channel_config: 2 });
+        assert_eq!(header.encode(100).unwrap(), [0xFF, 0xF1, 0x50, 0x80, 0x0D, 0x7F, 0xFC]);
+    }
+
+    #[test]
+    fn test_ogg_page_layout() {
+        let mut buffer = Vec::new();
+        let mut writer = OggWriter::new(&mut buffer);
+        writer.write_page(&[0xAB; 300], 960, OGG_END_OF_STREAM).unwrap();
+
+        assert_eq!(&buffer[..4], b"OggS");
+        assert_eq!(buffer[5], OGG_END_OF_STREAM);
+        assert_eq!(u64::from_le_bytes(buffer[6..14].try_into().unwrap()), 960);
+        assert_eq!(&buffer[26..29], &[2, 255, 45]);
+        assert_eq!(buffer.len(), 27 + 2 + 300);
+
+        // 校验和字段置零后重新计算应与写入的值一致
+        let stored = u32::from_le_bytes(buffer[22..26].try_into().unwrap());
+        buffer[22..26].copy_from_slice(&[0; 4]);
+        assert_eq!(ogg_crc(&buffer), stored);
+    }
+
+    #[test]
+    fn test_opus_packet_samples() {
+        // CELT 20ms 单帧
+        assert_eq!(opus_packet_samples(&[0xF8, 0x00]), 960);
+        // SILK 60ms 两帧
+        assert_eq!(opus_packet_samples(&[0x19]), 5760);
+        // 任意帧数：3 帧 CELT 10ms
+        assert_eq!(opus_packet_samples(&[0xF3, 0x03]), 1440);
+        assert_eq!(opus_packet_samples(&[]), 0);
+    }
+}
@@ -0,0 +1,192 @@
+//! # 目录同步模块
+//!
+//! 将输出目录维护为源目录的“音频镜像”：保留目录结构，只转换新增或
+//! 修改过的视频，并可选地删除源文件已不存在的孤立音频文件。
+
+use crate::audio_format::AudioFormat;
+use crate::error::{IoResultExt, Result};
+use crate::file_processor::{FileProcessor, OverwritePolicy};
+use crate::i18n::text;
+use crate::tr;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 同步计划
+///
+/// 对比源目录与输出目录后得到的待办事项。
+#[derive(Debug, Default)]
+pub struct SyncPlan {
+    /// 新增或已修改、需要转换的源文件
+    pub to_convert: Vec<PathBuf>,
+
+    /// 输出已是最新、无需处理的源文件
+    pub up_to_date: Vec<PathBuf>,
+
+    /// 源文件已不存在的孤立输出文件
+    pub orphans: Vec<PathBuf>,
+}
+
+impl SyncPlan {
+    /// 对比源目录和输出目录，生成同步计划
+    ///
+    /// `processor` 应通过 [`FileProcessor::with_preserved_structure`] 设置为以
+    /// `source_root` 为根，这样输出路径才能与源目录结构一一对应。
+    ///
+    /// # 参数
+    ///
+    /// * `processor` - 用于发现视频文件和计算输出路径的文件处理器
+    /// * `source_root` - 源目录
+    /// * `output_root` - 镜像输出目录
+    /// * `format` - 目标音频格式，只有该格式的输出文件会被视为孤立文件候选
+    ///
+    /// # 错误
+    ///
+    /// 当目录无法访问时返回错误
+    pub fn build(
+        processor: &FileProcessor,
+        source_root: &Path,
+        output_root: &Path,
+        format: AudioFormat,
+    ) -> Result<Self> {
+        let mut plan = SyncPlan::default();
+        let mut expected_outputs = HashSet::new();
+
+        for source_file in processor.find_video_files(source_root)? {
+            let output_path = processor.output_path_for(&source_file, output_root, format)?;
+            if OverwritePolicy::SkipUpToDate.should_skip(&source_file, &output_path) {
+                plan.up_to_date.push(source_file);
+            } else {
+                plan.to_convert.push(source_file);
+            }
+            expected_outputs.insert(output_path);
+        }
+
+        if output_root.is_dir() {
+            for entry in walkdir::WalkDir::new(output_root).into_iter().filter_map(|e| e.ok()) {
+                let path = entry.path();
+                let is_target_format = path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(format.extension()));
+
+                if entry.file_type().is_file() && is_target_format && !expected_outputs.contains(path) {
+                    plan.orphans.push(path.to_path_buf());
+                }
+            }
+        }
+
+        Ok(plan)
+    }
+
+    /// 删除孤立的输出文件
+    ///
+    /// 删除后会清理因此变空的子目录（输出根目录本身保留）。
+    /// `to_trash` 为 `true` 时移到系统回收站，可以恢复。
+    ///
+    /// # 返回值
+    ///
+    /// 成功删除的文件数
+    pub fn remove_orphans(&self, output_root: &Path, to_trash: bool) -> Result<usize> {
+        for orphan in &self.orphans {
+            remove_file(orphan, to_trash)?;
+        }
+        remove_empty_dirs(output_root);
+
+        Ok(self.orphans.len())
+    }
+}
+
+/// 删除文件，`to_trash` 为 `true` 时移到系统回收站
+pub(crate) fn remove_file(path: &Path, to_trash: bool) -> Result<()> {
+    if to_trash {
+        trash::delete(path).map_err(|e| std::io::Error::other(tr!(
+            "无法移到回收站 {}: {e}",
+            "Cannot move {} to the trash: {e}",
+            path.display(),
+        )))?;
+    } else {
+        fs::remove_file(path).context(text("删除文件", "remove file"), path)?;
+    }
+    Ok(())
+}
+
+/// 自底向上删除 `output_root` 下的空目录（根目录本身保留），非空目录删除失败时直接忽略
+pub(crate) fn remove_empty_dirs(output_root: &Path) {
+    for entry in walkdir::WalkDir::new(output_root)
+        .min_depth(1)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if entry.file_type().is_dir() {
+            let _ = fs::remove_dir(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+    use tempfile::TempDir;
+
+    fn touch(path: &Path, modified: SystemTime) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "content").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_build_plan_classifies_files() {
+        let source = TempDir::new().unwrap();
+        let output = TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let earlier = now - Duration::from_secs(60);
+
+        // 已同步、源文件较新、新增、孤立四种情况
+        touch(&source.path().join("a/synced.mp4"), earlier);
+        touch(&output.path().join("a/synced.mp3"), now);
+        touch(&source.path().join("a/changed.mp4"), now);
+        touch(&output.path().join("a/changed.mp3"), earlier);
+        touch(&source.path().join("b/new.mkv"), now);
+        touch(&output.path().join("c/removed.mp3"), now);
+        // 其他格式的输出不受影响
+        touch(&output.path().join("c/other.opus"), now);
+
+        let processor = FileProcessor::new()
+            .with_preserved_structure(Some(source.path().to_path_buf()));
+        let plan = SyncPlan::build(&processor, source.path(), output.path(), AudioFormat::Mp3).unwrap();
+
+        let mut to_convert = plan.to_convert.clone();
+        to_convert.sort();
+        assert_eq!(
+            to_convert,
+            vec![source.path().join("a/changed.mp4"), source.path().join("b/new.mkv")]
+        );
+        assert_eq!(plan.up_to_date, vec![source.path().join("a/synced.mp4")]);
+        assert_eq!(plan.orphans, vec![output.path().join("c/removed.mp3")]);
+    }
+
+    #[test]
+    fn test_remove_orphans_cleans_empty_directories() {
+        let output = TempDir::new().unwrap();
+        let orphan = output.path().join("old/deep/gone.mp3");
+        touch(&orphan, SystemTime::now());
+
+        let plan = SyncPlan {
+            orphans: vec![orphan.clone()],
+            ..SyncPlan::default()
+        };
+        assert_eq!(plan.remove_orphans(output.path(), false).unwrap(), 1);
+
+        assert!(!orphan.exists());
+        assert!(!output.path().join("old").exists());
+        assert!(output.path().exists());
+    }
+}
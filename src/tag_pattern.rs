@@ -0,0 +1,163 @@
+//! # 文件名标签模板模块
+//!
+//! 按 `{artist} - {title}` 这样的模板解析源文件名（不含扩展名），
+//! 把捕获的字段作为元数据标签写入输出文件。
+
+use crate::error::{Result, VideoToAudioError};
+use crate::i18n::text;
+use crate::tr;
+use std::path::Path;
+
+/// 模板中的一段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    /// 需要原样匹配的文字
+    Literal(String),
+
+    /// 捕获为标签的字段
+    Field(String),
+}
+
+/// 文件名标签模板
+///
+/// 字段写在花括号中，字段之间必须有分隔文字。每个字段匹配到下一段分隔文字第一次
+/// 出现的位置，最后一个字段匹配剩余的全部内容，因此 `{artist} - {title}` 解析
+/// `A - B - C` 时标题为 `B - C`。捕获的值会去掉首尾空白，任何字段为空时视为不匹配。
+///
+/// # 示例
+///
+/// ```rust
+/// use video2audio_rs::TagPattern;
+///
+/// let pattern = TagPattern::parse("{artist} - {title}").unwrap();
+/// let tags = pattern.captures("Foo - Bar").unwrap();
+/// assert_eq!(tags, [("artist".to_string(), "Foo".to_string()), ("title".to_string(), "Bar".to_string())]);
+/// assert!(pattern.captures("Untitled").is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagPattern {
+    segments: Vec<Segment>,
+}
+
+impl TagPattern {
+    /// 解析模板
+    ///
+    /// # 错误
+    ///
+    /// 模板没有字段、花括号不成对、字段名为空或两个字段相邻时返回
+    /// [`VideoToAudioError::InvalidInput`]
+    pub fn parse(pattern: &str) -> Result<Self> {
+        let invalid = |reason: &str| {
+            VideoToAudioError::InvalidInput(tr!(
+                "无效的标签模板 {pattern}: {reason}（示例: \"{{artist}} - {{title}}\"）",
+                "Invalid tag pattern {pattern}: {reason} (example: \"{{artist}} - {{title}}\")",
+            ))
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = pattern;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest.find('}').ok_or_else(|| invalid(text("缺少 }", "missing }")))?;
+                    let name = rest[1..end].trim();
+                    if name.is_empty() || name.contains('{') {
+                        return Err(invalid(text("字段名为空", "empty field name")));
+                    }
+                    if matches!(segments.last(), Some(Segment::Field(_))) {
+                        return Err(invalid(text("字段之间需要分隔文字", "fields must be separated by text")));
+                    }
+                    segments.push(Segment::Field(name.to_string()));
+                    rest = &rest[end + 1..];
+                }
+                found => {
+                    let end = found.unwrap_or(rest.len());
+                    if rest[..end].contains('}') {
+                        return Err(invalid(text("缺少 {", "missing {")));
+                    }
+                    segments.push(Segment::Literal(rest[..end].to_string()));
+                    rest = &rest[end..];
+                }
+            }
+        }
+
+        if !segments.iter().any(|segment| matches!(segment, Segment::Field(_))) {
+            return Err(invalid(text("没有字段", "no fields")));
+        }
+        Ok(Self { segments })
+    }
+
+    /// 从文件名中捕获字段
+    ///
+    /// # 返回值
+    ///
+    /// 按模板顺序排列的 `(字段名, 值)`，文件名不符合模板时为 `None`
+    pub fn captures(&self, stem: &str) -> Option<Vec<(String, String)>> {
+        let mut tags = Vec::new();
+        let mut rest = stem;
+        let mut segments = self.segments.iter().peekable();
+        while let Some(segment) = segments.next() {
+            match segment {
+                Segment::Literal(literal) => rest = rest.strip_prefix(literal.as_str())?,
+                Segment::Field(name) => {
+                    let end = match segments.peek() {
+                        Some(Segment::Literal(next)) => rest.find(next.as_str())?,
+                        _ => rest.len(),
+                    };
+                    let value = rest[..end].trim();
+                    if value.is_empty() {
+                        return None;
+                    }
+                    tags.push((name.clone(), value.to_string()));
+                    rest = &rest[end..];
+                }
+            }
+        }
+        rest.is_empty().then_some(tags)
+    }
+
+    /// 从源文件名（不含扩展名）中捕获字段
+    pub fn captures_file(&self, source_file: &Path) -> Option<Vec<(String, String)>> {
+        self.captures(&source_file.file_stem()?.to_string_lossy())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_captures() {
+        let pattern = TagPattern::parse("{track}. {artist} - {title}").unwrap();
+        assert_eq!(
+            pattern.captures("03. Foo - Bar - Live").unwrap(),
+            tags(&[("track", "03"), ("artist", "Foo"), ("title", "Bar - Live")])
+        );
+        assert_eq!(pattern.captures("Foo - Bar"), None);
+        assert_eq!(pattern.captures("03.  - Bar"), None);
+
+        let suffix = TagPattern::parse("{title} (live)").unwrap();
+        assert_eq!(suffix.captures("Encore (live)").unwrap(), tags(&[("title", "Encore")]));
+        assert_eq!(suffix.captures("Encore (live) 2"), None);
+    }
+
+    #[test]
+    fn test_captures_file() {
+        let pattern = TagPattern::parse("{artist} - {title}").unwrap();
+        assert_eq!(
+            pattern.captures_file(Path::new("videos/Foo - Bar.mp4")).unwrap(),
+            tags(&[("artist", "Foo"), ("title", "Bar")])
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for pattern in ["no fields", "{artist}{title}", "{artist", "artist}", "{} - {title}"] {
+            assert!(TagPattern::parse(pattern).is_err(), "{pattern}");
+        }
+    }
+}
@@ -0,0 +1,365 @@
+//! # 标签写入模块
+//!
+//! 在 FFmpeg 转换完成后写入或修正输出文件的标签、音轨号和封面，
+//! 增益标签、`tag` 子命令等都通过 [`FileProcessor::write_tags`] 写入。
+//!
+//! 默认通过 FFmpeg 复制音频流重新封装，适用于所有输出格式；启用 `lofty` 特性后，
+//! 所有容器（MP3、FLAC、Opus、M4A/M4B 等）都通过同一条 lofty 写入路径直接修改标签，
+//! 不需要 FFmpeg，也不会重写音频数据。
+
+use crate::audio_format::AudioFormat;
+use crate::cancellation::CancellationToken;
+use crate::error::Result;
+#[cfg(not(feature = "lofty"))]
+use crate::error::VideoToAudioError;
+#[cfg(not(feature = "lofty"))]
+use crate::file_processor::ffmpeg_path_arg;
+use crate::file_processor::FileProcessor;
+#[cfg(not(feature = "lofty"))]
+use crate::tr;
+#[cfg(not(feature = "lofty"))]
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 要写入音频文件的标签
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagUpdate {
+    /// 标签，键使用 FFmpeg 的元数据名称（如 `artist`、`track`），值为空时清除该标签
+    pub tags: Vec<(String, String)>,
+
+    /// 替换现有封面的图片，通过 FFmpeg 写入时只对支持封面的格式（MP3、M4B）生效
+    pub cover: Option<PathBuf>,
+}
+
+impl TagUpdate {
+    /// 创建空的标签更新
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个标签
+    pub fn with_tag(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// 设置封面图片
+    pub fn with_cover(mut self, cover: impl Into<PathBuf>) -> Self {
+        self.cover = Some(cover.into());
+        self
+    }
+
+    /// 是否没有任何需要写入的内容
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty() && self.cover.is_none()
+    }
+}
+
+/// 根据扩展名判断音频文件的格式
+pub fn format_from_path(path: &Path) -> Option<AudioFormat> {
+    let extension = path.extension()?.to_str()?;
+    AudioFormat::all_formats()
+        .into_iter()
+        .find(|format| format.extension().eq_ignore_ascii_case(extension))
+}
+
+impl FileProcessor {
+    /// 写入或修正音频文件的标签和封面
+    ///
+    /// 文件格式由扩展名决定，启用 `lofty` 特性时由 lofty 识别，
+    /// 不限于本工具的输出格式。已有的其他标签保持不变。
+    ///
+    /// # 错误
+    ///
+    /// 扩展名不是支持的输出格式时返回 [`UnsupportedFormat`](crate::error::VideoToAudioError::UnsupportedFormat)；
+    /// 需要 FFmpeg 但无法执行，或 lofty 无法识别文件、写入失败时返回相应的错误
+    #[cfg_attr(feature = "lofty", allow(unused_variables))]
+    pub fn write_tags(&self, path: &Path, update: &TagUpdate, cancel_token: &CancellationToken) -> Result<()> {
+        #[cfg(feature = "lofty")]
+        return if update.is_empty() { Ok(()) } else { lofty_tags::write(path, update) };
+
+        #[cfg(not(feature = "lofty"))]
+        {
+            let format = format_from_path(path).ok_or_else(|| {
+                VideoToAudioError::UnsupportedFormat(tr!(
+                    "无法识别音频文件的格式: {}",
+                    "Cannot recognize the audio file format: {}",
+                    path.display(),
+                ))
+            })?;
+            self.write_tags_as(path, format, update, None, cancel_token)
+        }
+    }
+
+    /// 按指定格式写入标签，`timeout` 限制 FFmpeg 重新封装的时间
+    ///
+    /// 启用 `lofty` 特性时格式由 lofty 识别，`format` 和 `timeout` 不起作用
+    #[cfg_attr(feature = "lofty", allow(unused_variables))]
+    pub(crate) fn write_tags_as(
+        &self,
+        path: &Path,
+        format: AudioFormat,
+        update: &TagUpdate,
+        timeout: Option<Duration>,
+        cancel_token: &CancellationToken,
+    ) -> Result<()> {
+        if update.is_empty() {
+            return Ok(());
+        }
+
+        #[cfg(feature = "lofty")]
+        return lofty_tags::write(path, update);
+
+        #[cfg(not(feature = "lofty"))]
+        {
+            self.check_ffmpeg_availability()?;
+            let args = Self::retag_args(path, format, update);
+            self.run_ffmpeg(&args, path, path, timeout, cancel_token)
+        }
+    }
+
+    /// 生成以复制音频流的方式重新封装并写入标签的 FFmpeg 参数
+    ///
+    /// ADTS 格式的 AAC 没有元数据，标签写入文件末尾的 APEv2 标签；
+    /// MP4 容器需要允许写入自定义标签。
+    #[cfg(not(feature = "lofty"))]
+    fn retag_args(path: &Path, format: AudioFormat, update: &TagUpdate) -> Vec<OsString> {
+        let mut args: Vec<OsString> = ["-y", "-hide_banner", "-loglevel", "error", "-i"].map(OsString::from).into();
+        args.push(ffmpeg_path_arg(path));
+
+        match update.cover.as_deref().filter(|_| format.supports_cover()) {
+            // 替换封面时只保留原文件的音频流
+            Some(cover) => {
+                args.push("-i".into());
+                args.push(ffmpeg_path_arg(cover));
+                args.extend(["-map", "0:a", "-map", "1:v:0", "-c", "copy", "-disposition:v:0", "attached_pic"].map(OsString::from));
+            }
+            None => args.extend(["-map", "0", "-c", "copy"].map(OsString::from)),
+        }
+        for (key, value) in &update.tags {
+            args.extend(["-metadata".into(), format!("{key}={value}").into()]);
+        }
+
+        match format {
+            AudioFormat::AacCopy => args.extend(["-write_apetag", "1"].map(OsString::from)),
+            AudioFormat::M4b => args.extend(["-movflags", "use_metadata_tags"].map(OsString::from)),
+            AudioFormat::Mp3 | AudioFormat::Opus => {}
+        }
+        args.extend(["-f", format.muxer()].map(OsString::from));
+        args.push(ffmpeg_path_arg(&Self::partial_output_path(path)));
+        args
+    }
+}
+
+/// 通过 lofty 直接修改各种容器的标签
+#[cfg(feature = "lofty")]
+mod lofty_tags {
+    use super::TagUpdate;
+    use crate::error::{Result, VideoToAudioError};
+    use crate::i18n::text;
+    use lofty::config::{ParseOptions, WriteOptions};
+    use lofty::error::LoftyError;
+    use lofty::file::TaggedFileExt;
+    use lofty::picture::{MimeType, Picture, PictureType};
+    use lofty::probe::Probe;
+    use lofty::tag::{ItemKey, Tag, TagExt, TagType};
+    use std::fs;
+    use std::path::Path;
+
+    /// 写入文件的主标签（MP3 为 ID3v2，FLAC 和 Opus 为 Vorbis Comment，M4A/M4B 为 ilst），
+    /// 保留其中已有的其他标签
+    pub(super) fn write(path: &Path, update: &TagUpdate) -> Result<()> {
+        let tag_error = |e: LoftyError| VideoToAudioError::io(text(
+            "写入标签",
+            "write tags",
+        ), path, std::io::Error::other(e));
+
+        // 只修改标签，不需要解析音频属性
+        let tagged_file = Probe::open(path)
+            .map_err(tag_error)?
+            .options(ParseOptions::new().read_properties(false))
+            .guess_file_type()?
+            .read()
+            .map_err(tag_error)?;
+        let tag_type = tagged_file.primary_tag_type();
+        let mut tag = tagged_file.tag(tag_type).cloned().unwrap_or_else(|| Tag::new(tag_type));
+
+        for (key, value) in &update.tags {
+            set_tag(&mut tag, key, value);
+        }
+        if let Some(cover) = &update.cover {
+            let data = fs::read(cover)?;
+            tag.remove_picture_type(PictureType::CoverFront);
+            tag.push_picture(Picture::new_unchecked(
+                PictureType::CoverFront,
+                Some(image_mime_type(cover)),
+                None,
+                data,
+            ));
+        }
+        tag.save_to_path(path, WriteOptions::default()).map_err(tag_error)
+    }
+
+    /// 按 FFmpeg 的元数据名称写入对应的通用标签项，值为空时清除
+    ///
+    /// 音轨号和碟号的 `序号/总数` 拆成两项，由 lofty 按各格式的习惯合并写入
+    fn set_tag(tag: &mut Tag, key: &str, value: &str) {
+        let (number_key, total_key) = match key.to_ascii_lowercase().as_str() {
+            "track" => (ItemKey::TrackNumber, ItemKey::TrackTotal),
+            "disc" => (ItemKey::DiscNumber, ItemKey::DiscTotal),
+            _ => {
+                let item_key = item_key(tag.tag_type(), key);
+                tag.remove_key(&item_key);
+                if !value.is_empty() {
+                    tag.insert_text(item_key, value.to_string());
+                }
+                return;
+            }
+        };
+
+        tag.remove_key(&number_key);
+        tag.remove_key(&total_key);
+        let (number, total) = value.split_once('/').unwrap_or((value, ""));
+        if !number.trim().is_empty() {
+            tag.insert_text(number_key, number.trim().to_string());
+        }
+        if !total.trim().is_empty() {
+            tag.insert_text(total_key, total.trim().to_string());
+        }
+    }
+
+    /// FFmpeg 元数据名称对应的通用标签项，其他名称作为自定义标签
+    fn item_key(tag_type: TagType, key: &str) -> ItemKey {
+        match key.to_ascii_lowercase().as_str() {
+            "title" => ItemKey::TrackTitle,
+            "artist" => ItemKey::TrackArtist,
+            "album" => ItemKey::AlbumTitle,
+            "album_artist" => ItemKey::AlbumArtist,
+            "date" => ItemKey::RecordingDate,
+            "genre" => ItemKey::Genre,
+            "composer" => ItemKey::Composer,
+            "comment" => ItemKey::Comment,
+            "replaygain_track_gain" => ItemKey::ReplayGainTrackGain,
+            "replaygain_track_peak" => ItemKey::ReplayGainTrackPeak,
+            "replaygain_album_gain" => ItemKey::ReplayGainAlbumGain,
+            "replaygain_album_peak" => ItemKey::ReplayGainAlbumPeak,
+            // MP4 的自定义标签使用 iTunes 的自由格式项，与 FFmpeg 的 use_metadata_tags 不同但更通用
+            _ if tag_type == TagType::Mp4Ilst => ItemKey::Unknown(format!("----:com.apple.iTunes:{key}")),
+            _ => ItemKey::Unknown(key.to_string()),
+        }
+    }
+
+    /// 根据扩展名推断图片类型
+    fn image_mime_type(path: &Path) -> MimeType {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("png") => MimeType::Png,
+            _ => MimeType::Jpeg,
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use lofty::tag::Accessor;
+
+        /// 两个 128 kbps、44.1 kHz 的空 MPEG-1 Layer III 帧
+        fn mp3_file() -> Vec<u8> {
+            let mut frame = vec![0u8; 417];
+            frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+            frame.repeat(2)
+        }
+
+        /// 只有 STREAMINFO 块（44.1 kHz、双声道、16 位）的 FLAC 文件
+        fn flac_file() -> Vec<u8> {
+            let mut data = b"fLaC".to_vec();
+            data.extend([0x80, 0x00, 0x00, 0x22]);
+            data.extend([0x10, 0x00, 0x10, 0x00, 0, 0, 0, 0, 0, 0]);
+            data.extend([0x0A, 0xC4, 0x42, 0xF0, 0, 0, 0, 0]);
+            data.extend([0u8; 16]);
+            data
+        }
+
+        /// 只有 ftyp、空 moov 和空 mdat 的 M4A 文件
+        fn m4a_file() -> Vec<u8> {
+            let mut data = Vec::new();
+            data.extend(24u32.to_be_bytes());
+            data.extend(b"ftypM4A \0\0\0\0M4A isom");
+            data.extend(8u32.to_be_bytes());
+            data.extend(b"moov");
+            data.extend(8u32.to_be_bytes());
+            data.extend(b"mdat");
+            data
+        }
+
+        fn read_tag(path: &Path) -> Tag {
+            let tagged_file = Probe::open(path)
+                .unwrap()
+                .options(ParseOptions::new().read_properties(false))
+                .read()
+                .unwrap();
+            tagged_file.primary_tag().unwrap().clone()
+        }
+
+        #[test]
+        fn test_write_tags_to_mp3_flac_and_m4a() {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let cover = temp_dir.path().join("cover.png");
+            fs::write(&cover, b"\x89PNG\r\n\x1a\n").unwrap();
+
+            for (name, data) in [("song.mp3", mp3_file()), ("song.flac", flac_file()), ("song.m4a", m4a_file())] {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, data).unwrap();
+
+                let update = TagUpdate::new()
+                    .with_tag("artist", "Foo")
+                    .with_tag("track", "3/12")
+                    .with_tag("REPLAYGAIN_TRACK_GAIN", "-6.00 dB")
+                    .with_cover(&cover);
+                write(&path, &update).unwrap();
+                let tag = read_tag(&path);
+                assert_eq!(tag.artist().as_deref(), Some("Foo"), "{name}");
+                assert_eq!((tag.track(), tag.track_total()), (Some(3), Some(12)), "{name}");
+                assert_eq!(tag.get_string(&ItemKey::ReplayGainTrackGain), Some("-6.00 dB"), "{name}");
+                assert_eq!(tag.pictures().len(), 1, "{name}");
+                assert_eq!(tag.pictures()[0].mime_type(), Some(&MimeType::Png), "{name}");
+
+                // 再次写入时修改已有标签、清除音轨号，其他标签和封面保持不变
+                write(&path, &TagUpdate::new().with_tag("artist", "Bar").with_tag("track", "")).unwrap();
+                let tag = read_tag(&path);
+                assert_eq!(tag.artist().as_deref(), Some("Bar"), "{name}");
+                assert_eq!(tag.track(), None, "{name}");
+                assert_eq!(tag.get_string(&ItemKey::ReplayGainTrackGain), Some("-6.00 dB"), "{name}");
+                assert_eq!(tag.pictures().len(), 1, "{name}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_path() {
+        assert_eq!(format_from_path(Path::new("a/song.MP3")), Some(AudioFormat::Mp3));
+        assert_eq!(format_from_path(Path::new("book.m4b")), Some(AudioFormat::M4b));
+        assert_eq!(format_from_path(Path::new("video.mp4")), None);
+    }
+
+    #[cfg(not(feature = "lofty"))]
+    #[test]
+    fn test_retag_args_replace_cover() {
+        let update = TagUpdate::new().with_tag("album", "Live").with_cover("cover.jpg");
+        let args = FileProcessor::retag_args(Path::new("book.m4b"), AudioFormat::M4b, &update);
+        let args: Vec<_> = args.iter().map(|arg| arg.to_string_lossy().into_owned()).collect();
+        assert!(args.windows(4).any(|window| window == ["-map", "0:a", "-map", "1:v:0"]));
+        assert!(args.windows(2).any(|pair| pair == ["-metadata", "album=Live"]));
+        assert!(args.windows(2).any(|pair| pair == ["-movflags", "use_metadata_tags"]));
+
+        // Opus 不支持封面，只复制全部流并写入标签
+        let args = FileProcessor::retag_args(Path::new("song.opus"), AudioFormat::Opus, &update);
+        assert!(args.windows(2).any(|pair| pair == ["-map", "0"]));
+        assert!(!args.iter().any(|arg| arg.to_string_lossy().contains("cover.jpg")));
+    }
+}
@@ -0,0 +1,351 @@
+//! # 终端仪表盘模块
+//!
+//! `--tui` 模式下在终端的备用屏幕中显示批量转换的实时仪表盘（需启用 `tui` 特性）：
+//!
+//! - 总进度、成功和失败数、已用时间和预计剩余时间
+//! - 每个工作线程正在转换的文件、进度和转换速度（相对于实时播放的倍数）
+//! - 滚动显示的完成和失败记录
+//!
+//! 按 `p` 暂停或继续开始新文件，按 `q`、`Esc` 或 `Ctrl+C` 取消批处理。
+//! 仪表盘在独立线程中刷新，作为 [`ProgressListener`] 接收各文件的进度。
+
+use crate::cancellation::CancellationToken;
+use crate::error::Result;
+use crate::i18n::text;
+use crate::progress::ProgressListener;
+use crate::tr;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Gauge, List, ListItem, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// 界面刷新和按键检查的间隔
+const REFRESH_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 日志最多保留的记录数
+const MAX_LOG_LINES: usize = 500;
+
+/// 正在转换的文件
+#[derive(Debug, Clone)]
+struct ActiveFile {
+    source: PathBuf,
+    started_at: Instant,
+    position: Duration,
+    total: Option<Duration>,
+}
+
+impl ActiveFile {
+    /// 转换速度：已输出的时长相对于实际耗时的倍数
+    fn speed(&self) -> Option<f64> {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        (elapsed > 0.0 && !self.position.is_zero()).then(|| self.position.as_secs_f64() / elapsed)
+    }
+
+    /// 完成比例，不知道总时长时为 `None`
+    fn ratio(&self) -> Option<f64> {
+        self.total
+            .filter(|total| !total.is_zero())
+            .map(|total| (self.position.as_secs_f64() / total.as_secs_f64()).min(1.0))
+    }
+}
+
+/// 仪表盘显示的批处理状态
+#[derive(Debug)]
+struct DashboardState {
+    total: usize,
+    completed: usize,
+    failed: usize,
+    started_at: Instant,
+    /// 工作槽位，每个槽位同时只转换一个文件
+    slots: Vec<Option<ActiveFile>>,
+    log: VecDeque<(bool, String)>,
+}
+
+impl DashboardState {
+    fn new(total: usize, workers: usize) -> Self {
+        Self {
+            total,
+            completed: 0,
+            failed: 0,
+            started_at: Instant::now(),
+            slots: vec![None; workers.max(1)],
+            log: VecDeque::new(),
+        }
+    }
+
+    fn slot_of(&mut self, source: &Path) -> Option<&mut ActiveFile> {
+        self.slots.iter_mut().flatten().find(|file| file.source == source)
+    }
+
+    /// 按已完成文件的平均耗时估算剩余时间
+    fn eta(&self) -> Option<Duration> {
+        let remaining = self.total.checked_sub(self.completed).filter(|&remaining| remaining > 0)?;
+        (self.completed > 0).then(|| self.started_at.elapsed() / self.completed as u32 * remaining as u32)
+    }
+}
+
+/// 批量转换的终端仪表盘，释放（drop）时恢复终端
+#[derive(Debug)]
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+    stop: Arc<AtomicBool>,
+    render_thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Dashboard {
+    /// 进入备用屏幕并开始显示仪表盘
+    ///
+    /// # 参数
+    ///
+    /// * `total` - 文件总数
+    /// * `workers` - 同时转换的文件数，决定显示的工作槽位数
+    /// * `cancel_token` - 按键暂停和取消时操作的令牌
+    ///
+    /// # 错误
+    ///
+    /// 无法切换终端模式（如标准输出不是终端）时返回 I/O 错误
+    pub fn start(total: usize, workers: usize, cancel_token: CancellationToken) -> Result<Self> {
+        let mut terminal = ratatui::try_init()?;
+        let state = Arc::new(Mutex::new(DashboardState::new(total, workers)));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let render_state = Arc::clone(&state);
+        let render_stop = Arc::clone(&stop);
+        let render_thread = thread::spawn(move || {
+            while !render_stop.load(Ordering::SeqCst) {
+                let _ = terminal.draw(|frame| render(frame, &render_state.lock().unwrap(), cancel_token.is_paused()));
+                if let Ok(true) = event::poll(REFRESH_INTERVAL) {
+                    if let Ok(Event::Key(key)) = event::read() {
+                        if key.kind != KeyEventKind::Press {
+                            continue;
+                        }
+                        match key.code {
+                            KeyCode::Char('p') if cancel_token.is_paused() => cancel_token.resume(),
+                            KeyCode::Char('p') => cancel_token.pause(),
+                            KeyCode::Char('q') | KeyCode::Esc => cancel_token.cancel(),
+                            // 原始模式下 Ctrl+C 不会产生中断信号
+                            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => cancel_token.cancel(),
+                            _ => {}
+                        }
+                    }
+                }
+            }
+            ratatui::restore();
+        });
+
+        Ok(Self { state, stop, render_thread: Mutex::new(Some(render_thread)) })
+    }
+
+    /// 记录一个文件的最终结果，更新总进度和日志
+    ///
+    /// # 参数
+    ///
+    /// * `source` - 源文件
+    /// * `result` - 输出文件或错误信息
+    /// * `completed` - 已处理的文件数
+    /// * `total` - 文件总数
+    pub fn file_done(&self, source: &Path, result: std::result::Result<&Path, String>, completed: usize, total: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.completed = completed;
+        state.total = total;
+        let name = display_name(source);
+        let entry = match result {
+            Ok(output) => (true, format!("✅ {name} → {}", display_name(output))),
+            Err(error) => {
+                state.failed += 1;
+                (false, format!("❌ {name}: {}", error.lines().next().unwrap_or_default()))
+            }
+        };
+        state.log.push_back(entry);
+        if state.log.len() > MAX_LOG_LINES {
+            state.log.pop_front();
+        }
+    }
+
+    /// 停止刷新并恢复终端，可以重复调用
+    pub fn finish(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(render_thread) = self.render_thread.lock().unwrap().take() {
+            let _ = render_thread.join();
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl ProgressListener for Dashboard {
+    fn file_started(&self, source: &Path) {
+        let mut state = self.state.lock().unwrap();
+        let file = ActiveFile { source: source.to_path_buf(), started_at: Instant::now(), position: Duration::ZERO, total: None };
+        match state.slots.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => *slot = Some(file),
+            // 实际并发数超过槽位数时追加槽位
+            None => state.slots.push(Some(file)),
+        }
+    }
+
+    fn file_progress(&self, source: &Path, position: Duration, total: Option<Duration>) {
+        if let Some(file) = self.state.lock().unwrap().slot_of(source) {
+            file.position = position;
+            file.total = total;
+        }
+    }
+
+    fn file_finished(&self, source: &Path) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(slot) = state.slots.iter_mut().find(|slot| slot.as_ref().is_some_and(|file| file.source == source)) {
+            *slot = None;
+        }
+    }
+}
+
+/// 绘制仪表盘
+fn render(frame: &mut Frame, state: &DashboardState, paused: bool) {
+    let [header, workers, log, footer] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(state.slots.len() as u16 + 3),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    let ratio = if state.total > 0 { state.completed as f64 / state.total as f64 } else { 0.0 };
+    let mut label = tr!(
+        "{}/{} ({:.0}%)  成功 {}  失败 {}  已用 {}",
+        "{}/{} ({:.0}%)  succeeded {}  failed {}  elapsed {}",
+        state.completed,
+        state.total,
+        ratio * 100.0,
+        state.completed - state.failed,
+        state.failed,
+        format_duration(state.started_at.elapsed())
+    );
+    if let Some(eta) = state.eta() {
+        label.push_str(&tr!("  剩余约 {}", "  ETA {}", format_duration(eta)));
+    }
+    let title = if paused {
+        text(" 总进度（已暂停，正在转换的文件完成后停止） ", " Overall (paused, stopping after the running files) ")
+    } else {
+        text(" 总进度 ", " Overall ")
+    };
+    frame.render_widget(
+        Gauge::default()
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .gauge_style(Style::default().fg(if paused { Color::Yellow } else { Color::Green }))
+            .ratio(ratio)
+            .label(label),
+        header,
+    );
+
+    let rows = state.slots.iter().enumerate().map(|(index, slot)| {
+        let cells = match slot {
+            Some(file) => [
+                format!("{}", index + 1),
+                display_name(&file.source),
+                file.ratio().map_or_else(|| format_duration(file.position), |ratio| format!("{:.0}%", ratio * 100.0)),
+                file.speed().map_or_else(String::new, |speed| format!("{speed:.1}x")),
+                format_duration(file.started_at.elapsed()),
+            ],
+            None => [format!("{}", index + 1), text("空闲", "idle").to_string(), String::new(), String::new(), String::new()],
+        };
+        Row::new(cells)
+    });
+    frame.render_widget(
+        Table::new(
+            rows,
+            [Constraint::Length(3), Constraint::Fill(1), Constraint::Length(8), Constraint::Length(8), Constraint::Length(8)],
+        )
+        .header(
+            Row::new([
+                "#",
+                text("文件", "File"),
+                text("进度", "Progress"),
+                text("速度", "Speed"),
+                text("耗时", "Time"),
+            ])
+            .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .block(Block::default().borders(Borders::ALL).title(text(" 工作线程 ", " Workers "))),
+        workers,
+    );
+
+    // 只显示放得下的最新记录
+    let visible = log.height.saturating_sub(2) as usize;
+    let items: Vec<_> = state
+        .log
+        .iter()
+        .skip(state.log.len().saturating_sub(visible))
+        .map(|(ok, line)| {
+            ListItem::new(line.as_str()).style(Style::default().fg(if *ok { Color::Reset } else { Color::Red }))
+        })
+        .collect();
+    frame.render_widget(List::new(items).block(Block::default().borders(Borders::ALL).title(text(" 日志 ", " Log "))), log);
+
+    let keys = if paused {
+        text(" p 继续  q 取消", " p resume  q cancel")
+    } else {
+        text(" p 暂停  q 取消", " p pause  q cancel")
+    };
+    frame.render_widget(Paragraph::new(Line::from(keys)).style(Style::default().fg(Color::DarkGray)), footer);
+}
+
+/// 以 `1:02:03` 或 `02:03` 的形式显示时长
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    match seconds / 3600 {
+        0 => format!("{:02}:{:02}", seconds / 60, seconds % 60),
+        hours => format!("{hours}:{:02}:{:02}", seconds / 60 % 60, seconds % 60),
+    }
+}
+
+/// 显示用的文件名
+fn display_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| path.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    #[test]
+    fn test_render_dashboard() {
+        let mut state = DashboardState::new(3, 2);
+        state.slots[0] = Some(ActiveFile {
+            source: PathBuf::from("videos/lecture.mp4"),
+            started_at: Instant::now(),
+            position: Duration::from_secs(30),
+            total: Some(Duration::from_secs(120)),
+        });
+        state.completed = 1;
+        state.failed = 1;
+        state.log.push_back((false, "❌ raw.mkv: moov atom not found".to_string()));
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 16)).unwrap();
+        terminal.draw(|frame| render(frame, &state, true)).unwrap();
+        // 宽字符后面的单元格是空格，比较前去掉所有空格
+        let screen: String = terminal.backend().buffer().content().iter().map(|cell| cell.symbol().trim()).collect();
+        for expected in ["lecture.mp4", "25%", "moov", "已暂停", "1/3"] {
+            assert!(screen.contains(expected), "仪表盘中缺少 {expected}");
+        }
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(75)), "01:15");
+        assert_eq!(format_duration(Duration::from_secs(3723)), "1:02:03");
+    }
+}
@@ -1,13 +1,270 @@
 //! # 用户界面模块
 //! 
 //! 处理所有用户交互逻辑，包括输入获取、格式选择和进度显示。
-//! 提供友好的中文界面和清晰的操作提示。
+//! 提供友好的中文和英文界面和清晰的操作提示，语言见 [`i18n`](crate::i18n)。
+//!
+//! 在终端中运行时使用 `dialoguer` 的方向键菜单和带默认值的输入框；
+//! 标准输入被重定向（如脚本通过管道输入答案）时逐行读取标准输入。
 
 use crate::audio_format::AudioFormat;
-use crate::config::RuntimeConfig;
+use crate::disk_space::{estimate_output_size, format_bytes};
 use crate::error::{Result, VideoToAudioError};
-use std::io::{self, Write};
-use std::time::{Duration, Instant};
+use crate::estimate::OutputEstimate;
+use crate::file_processor::OverwritePolicy;
+use crate::i18n::text;
+use crate::options::{ConversionOptions, QualityPreset};
+use crate::progress::ProgressListener;
+use crate::selection::{SkipReason, SkippedFile};
+use crate::style;
+use crate::tr;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{BasicHistory, Confirm, FuzzySelect, History, Input, Select};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 批处理的完成统计
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BatchSummary {
+    /// 处理的文件数
+    pub total_files: usize,
+
+    /// 成功转换的源文件总大小（字节）
+    pub input_bytes: u64,
+
+    /// 输出文件总大小（字节）
+    pub output_bytes: u64,
+
+    /// 批处理的总耗时
+    pub elapsed: Duration,
+}
+
+impl BatchSummary {
+    /// 创建只包含文件数的统计
+    pub fn new(total_files: usize) -> Self {
+        Self { total_files, ..Self::default() }
+    }
+
+    /// 累加一个成功转换的文件的源文件和输出文件大小，无法读取的大小按 0 计算
+    pub fn record(&mut self, source_file: &Path, output_file: &Path) {
+        let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        self.input_bytes += size(source_file);
+        self.output_bytes += size(output_file);
+    }
+
+    /// 输出相对输入节省的空间百分比，没有输入时为 `None`
+    pub fn saved_percent(&self) -> Option<f64> {
+        (self.input_bytes > 0).then(|| (1.0 - self.output_bytes as f64 / self.input_bytes as f64) * 100.0)
+    }
+
+    /// 平均处理速度（每秒处理的源文件字节数），耗时为零时为 `None`
+    pub fn bytes_per_second(&self) -> Option<f64> {
+        (!self.elapsed.is_zero()).then(|| self.input_bytes as f64 / self.elapsed.as_secs_f64())
+    }
+}
+
+/// 开始转换前显示给用户确认的批处理计划
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversionPlan {
+    /// 要转换的文件数
+    pub file_count: usize,
+
+    /// 源文件总大小（字节）
+    pub input_bytes: u64,
+
+    /// 已读取到时长的文件的总时长
+    pub duration: Duration,
+
+    /// 无法读取时长的文件数
+    pub unknown_durations: usize,
+
+    /// 目标音频格式
+    pub format: AudioFormat,
+
+    /// 输出目录
+    pub output_dir: PathBuf,
+
+    /// 估算的输出总大小（字节）
+    pub estimated_output_bytes: u64,
+
+    /// 按试转换速度估算的转换耗时，未试转换时为 `None`
+    pub estimated_time: Option<Duration>,
+}
+
+impl ConversionPlan {
+    /// 根据要转换的文件和转换选项生成计划，无法读取大小的文件按 0 计算
+    ///
+    /// 时长需要探测源文件，由调用方通过 [`ConversionPlan::with_durations`] 填入
+    pub fn new(files: &[PathBuf], options: &ConversionOptions, output_dir: &Path) -> Self {
+        let sizes: Vec<u64> = files.iter().map(|file| fs::metadata(file).map(|m| m.len()).unwrap_or(0)).collect();
+        Self {
+            file_count: files.len(),
+            input_bytes: sizes.iter().sum(),
+            duration: Duration::ZERO,
+            unknown_durations: files.len(),
+            format: options.format,
+            output_dir: output_dir.to_path_buf(),
+            estimated_output_bytes: sizes.iter().map(|&size| estimate_output_size(size, options)).sum(),
+            estimated_time: None,
+        }
+    }
+
+    /// 填入 [`estimate_outputs`](crate::estimate::estimate_outputs) 的结果：每个文件的时长和按码率估算的输出大小
+    pub fn with_estimates(mut self, estimates: &[OutputEstimate]) -> Self {
+        self = self.with_durations(estimates.iter().map(|estimate| estimate.duration));
+        self.estimated_output_bytes = estimates.iter().map(|estimate| estimate.output_bytes).sum();
+        self
+    }
+
+    /// 填入估算的转换耗时
+    pub fn with_estimated_time(mut self, estimated_time: Option<Duration>) -> Self {
+        self.estimated_time = estimated_time;
+        self
+    }
+
+    /// 填入每个文件的时长，`None` 表示无法读取
+    pub fn with_durations(mut self, durations: impl IntoIterator<Item = Option<Duration>>) -> Self {
+        self.duration = Duration::ZERO;
+        self.unknown_durations = 0;
+        for duration in durations {
+            match duration {
+                Some(duration) => self.duration += duration,
+                None => self.unknown_durations += 1,
+            }
+        }
+        self
+    }
+}
+
+/// 进度条的刷新间隔
+const PROGRESS_TICK_INTERVAL: Duration = Duration::from_millis(120);
+
+/// 批量转换的进度条：一个总进度条，加上每个正在转换的文件各一个进度条
+///
+/// 作为 [`ProgressListener`] 传给文件处理器后，文件开始转换时添加进度条，
+/// 结束时移除；总进度由调用方在收到每个文件的结果后通过 [`BatchProgress::file_done`] 更新。
+/// 标准错误不是终端时不显示。
+#[derive(Debug)]
+pub struct BatchProgress {
+    multi: MultiProgress,
+    overall: ProgressBar,
+    files: Mutex<HashMap<PathBuf, ProgressBar>>,
+}
+
+impl BatchProgress {
+    /// 创建进度条
+    ///
+    /// # 参数
+    ///
+    /// * `total` - 文件总数
+    pub fn new(total: usize) -> Self {
+        Self::with_draw_target(total, ProgressDrawTarget::stderr())
+    }
+
+    fn with_draw_target(total: usize, target: ProgressDrawTarget) -> Self {
+        let multi = MultiProgress::with_draw_target(target);
+        let overall = multi.add(ProgressBar::new(total as u64));
+        overall.set_style(
+            ProgressStyle::with_template(text(
+                "🔄 总进度 [{bar:40.green/white}] {pos}/{len} ({percent}%) 已用 {elapsed} 剩余约 {eta}",
+                "🔄 Overall [{bar:40.green/white}] {pos}/{len} ({percent}%) elapsed {elapsed} ETA {eta}",
+            ))
+            .expect("进度条模板有效")
+            .progress_chars("=> "),
+        );
+        overall.enable_steady_tick(PROGRESS_TICK_INTERVAL);
+        Self { multi, overall, files: Mutex::new(HashMap::new()) }
+    }
+
+    /// 一个文件处理完成（成功或最终失败），更新总进度
+    ///
+    /// # 参数
+    ///
+    /// * `completed` - 已处理的文件数
+    /// * `total` - 文件总数，边扫描边转换时会随发现的文件增加
+    pub fn file_done(&self, completed: usize, total: usize) {
+        self.overall.set_length(total as u64);
+        self.overall.set_position(completed as u64);
+    }
+
+    /// 正在转换的文件数
+    pub fn active_files(&self) -> usize {
+        self.files.lock().unwrap().len()
+    }
+
+    /// 暂时隐藏进度条执行 `f`，避免期间输出的文字与进度条混在一起
+    pub fn suspend<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.multi.suspend(f)
+    }
+
+    /// 结束显示，保留总进度条的最终状态
+    pub fn finish(&self) {
+        for (_, bar) in self.files.lock().unwrap().drain() {
+            bar.finish_and_clear();
+        }
+        self.overall.finish();
+    }
+}
+
+impl ProgressListener for BatchProgress {
+    fn file_started(&self, source: &Path) {
+        let bar = self.multi.insert_before(&self.overall, ProgressBar::new_spinner());
+        bar.set_style(
+            ProgressStyle::with_template("   {spinner:.cyan} {wide_msg} {elapsed:>6}").expect("进度条模板有效"),
+        );
+        bar.set_message(file_display_name(source));
+        bar.enable_steady_tick(PROGRESS_TICK_INTERVAL);
+        if let Some(previous) = self.files.lock().unwrap().insert(source.to_path_buf(), bar) {
+            previous.finish_and_clear();
+        }
+    }
+
+    fn file_progress(&self, source: &Path, position: Duration, total: Option<Duration>) {
+        let files = self.files.lock().unwrap();
+        let Some(bar) = files.get(source) else { return };
+        let Some(total) = total.filter(|total| !total.is_zero()) else { return };
+
+        // 知道总时长后从转圈改为百分比进度条
+        let total_ms = total.as_millis() as u64;
+        if bar.length() != Some(total_ms) {
+            bar.set_style(
+                ProgressStyle::with_template("   {wide_msg} [{bar:25.cyan/blue}] {percent:>3}%")
+                    .expect("进度条模板有效")
+                    .progress_chars("=> "),
+            );
+            bar.set_length(total_ms);
+        }
+        bar.set_position((position.as_millis() as u64).min(total_ms));
+    }
+
+    fn file_finished(&self, source: &Path) {
+        if let Some(bar) = self.files.lock().unwrap().remove(source) {
+            bar.finish_and_clear();
+            self.multi.remove(&bar);
+        }
+    }
+
+    fn ffmpeg_command(&self, _source: &Path, command_line: &str) {
+        let line = tr!("🔧 执行: {}", "🔧 Running: {}", command_line);
+        // 显示在进度条上方，不打乱进度条；不显示进度条时直接输出
+        if self.multi.is_hidden() {
+            eprintln!("{line}");
+        } else {
+            let _ = self.multi.println(line);
+        }
+    }
+}
+
+/// 进度条中显示的文件名，取不到文件名时显示完整路径
+fn file_display_name(source: &Path) -> String {
+    source
+        .file_name()
+        .map_or_else(|| source.display().to_string(), |name| name.to_string_lossy().into_owned())
+}
 
 /// 用户界面管理器
 ///
@@ -17,25 +274,14 @@ use std::time::{Duration, Instant};
 /// - 进度反馈
 /// - 错误提示
 pub struct UserInterface {
-    /// 进度跟踪器
-    progress_tracker: Option<ProgressTracker>,
-}
-
-/// 进度跟踪器
-///
-/// 用于跟踪处理进度和计算预计完成时间
-struct ProgressTracker {
-    start_time: std::time::Instant,
-    last_update: std::time::Instant,
-    total_files: usize,
+    /// 标准输入和标准错误都连接到终端，可以使用方向键菜单
+    terminal: bool,
 }
 
 impl UserInterface {
     /// 创建新的用户界面实例
     pub fn new() -> Self {
-        Self {
-            progress_tracker: None,
-        }
+        Self { terminal: io::stdin().is_terminal() && io::stderr().is_terminal() }
     }
 
     /// 显示程序欢迎信息
@@ -43,13 +289,16 @@ impl UserInterface {
     /// 在程序启动时显示标题和基本信息
     pub fn show_welcome(&self) {
         println!("╔══════════════════════════════════════════════════════════════╗");
-        println!("║                批量视频转音频工具 (高并发版)                 ║");
+        println!("{}", text(
+            "║                批量视频转音频工具 (高并发版)                 ║",
+            "║              Batch Video to Audio Converter                 ║",
+        ));
         println!("║                   Video2Audio-RS v0.1.0                     ║");
         println!("╚══════════════════════════════════════════════════════════════╝");
         println!();
-        println!("🎵 支持多种视频格式转换为高质量音频文件");
-        println!("⚡ 利用多核 CPU 并行处理，大幅提升转换速度");
-        println!("🛠️  基于 FFmpeg 引擎，确保转换质量和兼容性");
+        println!("{}", text("🎵 支持多种视频格式转换为高质量音频文件", "🎵 Converts many video formats to high-quality audio"));
+        println!("{}", text("⚡ 利用多核 CPU 并行处理，大幅提升转换速度", "⚡ Converts in parallel on all CPU cores"));
+        println!("{}", text("🛠️  基于 FFmpeg 引擎，确保转换质量和兼容性", "🛠️  Built on FFmpeg for quality and compatibility"));
         println!();
     }
 
@@ -67,8 +316,16 @@ impl UserInterface {
     /// 
     /// # 错误
     /// 
-    /// 当输入操作失败时返回 I/O 错误
+    /// 当输入操作失败时返回 I/O 错误；不在终端中运行且输入为空时返回错误
     pub fn get_user_input(&self, prompt: &str) -> Result<String> {
+        if self.terminal {
+            // 输入为空时重新提示，不返回错误
+            let input: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt_text(prompt))
+                .interact_text()?;
+            return Ok(input.trim().to_string());
+        }
+
         print!("{prompt}");
         io::stdout().flush()?;
         
@@ -80,13 +337,143 @@ impl UserInterface {
         // 检查空输入
         if input.is_empty() {
             return Err(VideoToAudioError::InvalidInput(
-                "输入不能为空，请重新输入".to_string()
+                tr!("输入不能为空，请重新输入", "Input cannot be empty, please try again")
             ));
         }
         
         Ok(input)
     }
 
+    /// 获取用户输入，直接按回车时返回默认值
+    /// 
+    /// # 错误
+    /// 
+    /// 当输入操作失败时返回 I/O 错误
+    pub fn get_input_with_default(&self, prompt: &str, default: &str) -> Result<String> {
+        if self.terminal {
+            let input: String = Input::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt_text(prompt))
+                .default(default.to_string())
+                .interact_text()?;
+            return Ok(input.trim().to_string());
+        }
+
+        print!("{}", tr!("{prompt}[回车使用默认值: {default}]: ", "{prompt}[Enter for default: {default}]: "));
+        io::stdout().flush()?;
+
+        let mut buffer = String::new();
+        io::stdin().read_line(&mut buffer)?;
+
+        let input = buffer.trim();
+        Ok(if input.is_empty() { default } else { input }.to_string())
+    }
+
+    /// 询问用户是否继续，直接按回车时返回 `default`
+    /// 
+    /// # 错误
+    /// 
+    /// 当输入操作失败时返回 I/O 错误
+    pub fn confirm(&self, prompt: &str, default: bool) -> Result<bool> {
+        if self.terminal {
+            return Ok(Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt_text(prompt))
+                .default(default)
+                .interact()?);
+        }
+
+        loop {
+            print!("{prompt} {}: ", if default { "[Y/n]" } else { "[y/N]" });
+            io::stdout().flush()?;
+
+            let mut buffer = String::new();
+            io::stdin().read_line(&mut buffer)?;
+
+            match buffer.trim() {
+                "" => return Ok(default),
+                "y" | "Y" | "yes" | "是" => return Ok(true),
+                "n" | "N" | "no" | "否" => return Ok(false),
+                _ => println!("{}", style::failure(text("❌ 请输入 y 或 n", "❌ Please enter y or n"))),
+            }
+        }
+    }
+
+    /// 显示编号选项并让用户选择，直接按回车时选择默认项
+    /// 
+    /// # 返回值
+    /// 
+    /// 所选选项的序号（从 0 开始）
+    fn select_option(&self, title: &str, options: &[&str], default: usize) -> Result<usize> {
+        if self.terminal {
+            let choice = Select::with_theme(&ColorfulTheme::default())
+                .with_prompt(prompt_text(title))
+                .items(options)
+                .default(default)
+                .interact()?;
+            println!();
+            return Ok(choice);
+        }
+
+        loop {
+            println!("{title}");
+            for (index, option) in options.iter().enumerate() {
+                let marker = if index == default { text("（默认）", " (default)") } else { "" };
+                println!("   {}. {option}{marker}", index + 1);
+            }
+
+            let input = self.get_input_with_default(text("请输入选项 ", "Enter an option "), &(default + 1).to_string())?;
+            match input.parse::<usize>() {
+                Ok(choice) if (1..=options.len()).contains(&choice) => {
+                    println!("{}", tr!("✓ 已选择: {}", "✓ Selected: {}", options[choice - 1]));
+                    println!();
+                    return Ok(choice - 1);
+                }
+                _ => {
+                    println!("{}", style::failure(tr!("❌ 无效输入，请输入 1-{}", "❌ Invalid input, please enter 1-{}", options.len())));
+                    println!();
+                }
+            }
+        }
+    }
+
+    /// 让用户指定输出目录
+    /// 
+    /// # 参数
+    /// 
+    /// * `default` - 直接按回车时使用的默认输出目录
+    /// 
+    /// # 返回值
+    /// 
+    /// 用户指定的目录；使用默认目录时返回 `None`
+    pub fn get_output_directory(&self, default: &Path) -> Result<Option<PathBuf>> {
+        let default = default.to_string_lossy();
+        let input = self.get_input_with_default(text("📂 输出目录 ", "📂 Output directory "), &default)?;
+        println!();
+        Ok((input != default).then(|| PathBuf::from(input)))
+    }
+
+    /// 让用户选择输出文件已存在时的处理策略
+    pub fn select_overwrite_policy(&self) -> Result<OverwritePolicy> {
+        let policies = [OverwritePolicy::Overwrite, OverwritePolicy::SkipExisting, OverwritePolicy::SkipUpToDate];
+        let choice = self.select_option(
+            text("📄 输出文件已存在时:", "📄 When the output file exists:"),
+            &[
+                text("重新转换并覆盖", "Convert again and overwrite"),
+                text("跳过", "Skip"),
+                text("仅当源文件更新过时重新转换", "Convert again only if the source is newer"),
+            ],
+            0,
+        )?;
+        Ok(policies[choice])
+    }
+
+    /// 让用户选择音质预设
+    pub fn select_quality_preset(&self) -> Result<QualityPreset> {
+        let presets = QualityPreset::all();
+        let descriptions: Vec<_> = presets.iter().map(QualityPreset::description).collect();
+        let choice = self.select_option(text("🎚️  请选择音质:", "🎚️  Choose the quality:"), &descriptions, 0)?;
+        Ok(presets[choice])
+    }
+
     /// 让用户选择音频格式
     /// 
     /// 显示格式选择菜单，处理用户选择并返回对应的音频格式
@@ -97,11 +484,21 @@ impl UserInterface {
     /// 
     /// # 错误
     /// 
-    /// 当用户输入无效选项时返回错误
+    /// 当输入操作失败时返回 I/O 错误
     pub fn select_audio_format(&self) -> Result<AudioFormat> {
+        if self.terminal {
+            let formats = AudioFormat::all_formats();
+            let descriptions: Vec<_> = formats.iter().map(AudioFormat::description).collect();
+            let choice = self.select_option(text("🎵 请选择目标音频格式", "🎵 Choose the output audio format"), &descriptions, 0)?;
+            return Ok(formats[choice]);
+        }
+
         loop {
             println!("┌─────────────────────────────────────────────────────────────┐");
-            println!("│                    请选择目标音频格式                        │");
+            println!("{}", text(
+                "│                    请选择目标音频格式                        │",
+                "│               Choose the output audio format                │",
+            ));
             println!("├─────────────────────────────────────────────────────────────┤");
             
             // 动态显示所有可用格式
@@ -112,22 +509,22 @@ impl UserInterface {
             println!("└─────────────────────────────────────────────────────────────┘");
             println!();
 
-            match self.get_user_input("请输入选项 (1-3): ") {
+            match self.get_user_input(text("请输入选项 (1-4): ", "Enter an option (1-4): ")) {
                 Ok(choice_str) => {
                     match AudioFormat::from_user_input(&choice_str) {
                         Ok(format) => {
-                            println!("✓ 已选择格式: {}", format.description());
+                            println!("{}", tr!("✓ 已选择格式: {}", "✓ Selected format: {}", format.description()));
                             println!();
                             return Ok(format);
                         }
                         Err(_) => {
-                            println!("❌ 无效输入，请输入 1, 2, 或 3");
+                            println!("{}", style::failure(text("❌ 无效输入，请输入 1, 2, 3 或 4", "❌ Invalid input, please enter 1, 2, 3 or 4")));
                             println!();
                         }
                     }
                 }
                 Err(e) => {
-                    println!("❌ 输入错误: {e}");
+                    println!("{}", style::failure(tr!("❌ 输入错误: {e}", "❌ Input error: {e}")));
                     println!();
                 }
             }
@@ -136,7 +533,13 @@ impl UserInterface {
 
     /// 获取并验证源目录路径
     /// 
-    /// 提示用户输入视频文件夹路径，并验证路径的有效性
+    /// 提示用户输入视频文件夹路径，并验证路径的有效性。
+    /// 最近使用的目录中仍然存在的目录会列为编号选项，输入编号即可选择；
+    /// 在终端中运行时用方向键选择，输入路径时可以用 ↑/↓ 浏览最近使用的目录。
+    /// 
+    /// # 参数
+    /// 
+    /// * `recent_dirs` - 最近使用的源目录，最近的在前
     /// 
     /// # 返回值
     /// 
@@ -145,40 +548,94 @@ impl UserInterface {
     /// # 错误
     /// 
     /// 当路径无效或不是目录时返回错误
-    pub fn get_source_directory(&self) -> Result<String> {
+    pub fn get_source_directory(&self, recent_dirs: &[String]) -> Result<String> {
+        // 已删除或未挂载的目录不再列出
+        let recent: Vec<&str> = recent_dirs
+            .iter()
+            .map(String::as_str)
+            .filter(|dir| Path::new(dir).is_dir())
+            .collect();
+
+        if self.terminal {
+            return self.prompt_source_directory(&recent);
+        }
+
         loop {
-            println!("📁 请指定要处理的视频文件夹:");
-            println!("   提示: 程序会自动扫描该文件夹及其所有子文件夹");
+            println!("{}", text("📁 请指定要处理的视频文件夹:", "📁 Which video folder should be processed?"));
+            println!("{}", text(
+                "   提示: 程序会自动扫描该文件夹及其所有子文件夹",
+                "   Tip: the folder and all of its subfolders are scanned",
+            ));
             println!();
 
-            match self.get_user_input("请输入文件夹的完整路径: ") {
-                Ok(source_dir) => {
-                    let path = std::path::Path::new(&source_dir);
-                    
-                    if !path.exists() {
-                        println!("❌ 错误: 路径 '{source_dir}' 不存在，请检查路径是否正确");
-                        println!();
-                        continue;
-                    }
-                    
-                    if !path.is_dir() {
-                        println!("❌ 错误: '{source_dir}' 不是一个文件夹，请输入文件夹路径");
+            let prompt = if recent.is_empty() {
+                text("请输入文件夹的完整路径: ", "Enter the full path of the folder: ")
+            } else {
+                println!("{}", text("   最近使用的文件夹:", "   Recent folders:"));
+                for (index, dir) in recent.iter().enumerate() {
+                    println!("   {}. {dir}", index + 1);
+                }
+                println!();
+                text("请输入编号或文件夹的完整路径: ", "Enter a number or the full path of the folder: ")
+            };
+
+            match self.get_user_input(prompt) {
+                Ok(input) => {
+                    let source_dir = pick_recent_dir(&input, &recent).unwrap_or(input);
+                    if let Err(message) = validate_source_dir(&source_dir) {
+                        println!("{}", style::failure(tr!("❌ 错误: {message}", "❌ Error: {message}")));
                         println!();
                         continue;
                     }
                     
-                    println!("✓ 源目录验证成功: {source_dir}");
+                    println!("{}", tr!("✓ 源目录验证成功: {source_dir}", "✓ Source folder: {source_dir}"));
                     println!();
                     return Ok(source_dir);
                 }
                 Err(e) => {
-                    println!("❌ 输入错误: {e}");
+                    println!("{}", style::failure(tr!("❌ 输入错误: {e}", "❌ Input error: {e}")));
                     println!();
                 }
             }
         }
     }
 
+    /// 在终端中用方向键选择最近使用的目录，或输入新的目录
+    fn prompt_source_directory(&self, recent: &[&str]) -> Result<String> {
+        let theme = ColorfulTheme::default();
+        println!("{}", text(
+            "📁 请指定要处理的视频文件夹（程序会自动扫描该文件夹及其所有子文件夹）",
+            "📁 Which video folder should be processed? (the folder and all of its subfolders are scanned)",
+        ));
+
+        if !recent.is_empty() {
+            let mut items = recent.to_vec();
+            items.push(text("输入其他文件夹...", "Enter another folder..."));
+            let choice = Select::with_theme(&theme)
+                .with_prompt(text("最近使用的文件夹", "Recent folders"))
+                .items(&items)
+                .default(0)
+                .interact()?;
+            if choice < recent.len() {
+                println!();
+                return Ok(recent[choice].to_string());
+            }
+        }
+
+        // 最近使用的目录放入输入历史，最近的在最前
+        let mut history = BasicHistory::new().no_duplicates(true);
+        for dir in recent.iter().rev() {
+            history.write(&dir.to_string());
+        }
+        let source_dir: String = Input::with_theme(&theme)
+            .with_prompt(text("文件夹的完整路径", "Full path of the folder"))
+            .validate_with(|input: &String| validate_source_dir(input.trim()))
+            .history_with(&mut history)
+            .interact_text()?;
+        println!();
+        Ok(source_dir.trim().to_string())
+    }
+
     /// 显示文件发现结果
     /// 
     /// 显示找到的视频文件数量和即将开始的处理信息
@@ -189,15 +646,157 @@ impl UserInterface {
     /// * `output_dir` - 输出目录路径
     pub fn show_files_found(&self, file_count: usize, output_dir: &std::path::Path) {
         if file_count == 0 {
-            println!("📂 扫描完成，但未找到任何支持的视频文件");
-            println!("   支持的格式: MP4, MKV, AVI, MOV, WEBM, FLV, WMV");
+            println!("{}", text("📂 扫描完成，但未找到任何支持的视频文件", "📂 Scan finished, but no supported video files were found"));
+            println!("{}", text("   支持的格式: MP4, MKV, AVI, MOV, WEBM, FLV, WMV", "   Supported formats: MP4, MKV, AVI, MOV, WEBM, FLV, WMV"));
             return;
         }
 
-        println!("📊 扫描结果:");
-        println!("   找到 {file_count} 个视频文件");
-        println!("   输出目录: {}", output_dir.display());
-        println!("   开始并行转换处理...");
+        println!("{}", text("📊 扫描结果:", "📊 Scan results:"));
+        println!("{}", tr!("   找到 {file_count} 个视频文件", "   Found {file_count} video files"));
+        println!("{}", tr!("   输出目录: {}", "   Output directory: {}", output_dir.display()));
+        println!("{}", text("   开始并行转换处理...", "   Starting parallel conversion..."));
+        println!();
+    }
+
+    /// 显示扫描时被排除的文件，按原因分组统计
+    /// 
+    /// # 参数
+    /// 
+    /// * `skipped` - 被排除的文件
+    /// * `base` - 显示相对路径时的基准目录
+    /// * `list_files` - 是否在每个原因下列出全部文件（详细模式），否则只显示数量
+    pub fn show_skipped_files(&self, skipped: &[SkippedFile], base: &Path, list_files: bool) {
+        if skipped.is_empty() {
+            return;
+        }
+
+        let mut groups: std::collections::BTreeMap<SkipReason, Vec<&Path>> = std::collections::BTreeMap::new();
+        for file in skipped {
+            groups.entry(file.reason).or_default().push(&file.path);
+        }
+        println!("{}", tr!("🙈 扫描时排除了 {} 个文件:", "🙈 Excluded {} files while scanning:", skipped.len()));
+        for (reason, files) in &groups {
+            println!("{}", tr!("   {} 个文件: {reason}", "   {} files: {reason}", files.len()));
+            if list_files {
+                let mut files = files.clone();
+                files.sort();
+                for file in files {
+                    println!("      {}", file.strip_prefix(base).unwrap_or(file).display());
+                }
+            }
+        }
+        if !list_files {
+            println!("{}", text("   使用 --verbose 列出这些文件", "   Use --verbose to list these files"));
+        }
+        println!();
+    }
+
+    /// 让用户在列表中挑选要转换的文件
+    /// 
+    /// 所有文件默认选中。输入关键词可以模糊筛选列表，回车切换光标所在文件是否转换，
+    /// 选择列表第一项完成挑选。
+    /// 
+    /// # 参数
+    /// 
+    /// * `files` - 扫描到的文件
+    /// * `base` - 列表中显示相对路径时的基准目录
+    /// 
+    /// # 返回值
+    /// 
+    /// 保持原顺序的选中文件
+    /// 
+    /// # 错误
+    /// 
+    /// 不在终端中运行或终端操作失败时返回 I/O 错误
+    pub fn pick_files(&self, files: Vec<PathBuf>, base: &Path) -> Result<Vec<PathBuf>> {
+        let labels: Vec<_> = files.iter().map(|file| file_label(file, base)).collect();
+        let mut selected = vec![true; files.len()];
+        let mut cursor = 0;
+        loop {
+            let count = selected.iter().filter(|&&checked| checked).count();
+            let mut items = vec![tr!(
+                "✅ 完成挑选（已选择 {count}/{} 个文件）",
+                "✅ Done ({count}/{} files selected)",
+                files.len()
+            )];
+            items.extend(
+                labels
+                    .iter()
+                    .zip(&selected)
+                    .map(|(label, &checked)| format!("[{}] {label}", if checked { "x" } else { " " })),
+            );
+
+            let choice = FuzzySelect::with_theme(&ColorfulTheme::default())
+                .with_prompt(text("输入关键词筛选，回车切换是否转换该文件", "Type to filter, Enter toggles whether a file is converted"))
+                .items(&items)
+                .default(cursor)
+                .interact()?;
+            if choice == 0 {
+                break;
+            }
+            selected[choice - 1] = !selected[choice - 1];
+            cursor = choice;
+        }
+        println!();
+
+        Ok(files.into_iter().zip(selected).filter_map(|(file, checked)| checked.then_some(file)).collect())
+    }
+
+    /// 显示转换计划，供用户在开始转换前确认
+    pub fn show_conversion_plan(&self, plan: &ConversionPlan) {
+        println!("{}", text("📋 转换计划:", "📋 Conversion plan:"));
+        println!("{}", tr!("   文件数量: {} 个", "   Files: {}", plan.file_count));
+        println!("{}", tr!("   源文件大小: {}", "   Source size: {}", format_bytes(plan.input_bytes)));
+        if plan.unknown_durations == plan.file_count {
+            println!("{}", text("   总时长: 未知", "   Total duration: unknown"));
+        } else {
+            let duration = humantime::format_duration(Duration::from_secs(plan.duration.as_secs()));
+            if plan.unknown_durations > 0 {
+                println!("{}", tr!(
+                    "   总时长: {duration}（{} 个文件无法读取时长）",
+                    "   Total duration: {duration} ({} files without a known duration)",
+                    plan.unknown_durations
+                ));
+            } else {
+                println!("{}", tr!("   总时长: {duration}", "   Total duration: {duration}"));
+            }
+        }
+        println!("{}", tr!("   目标格式: {}", "   Output format: {}", plan.format.description()));
+        println!("{}", tr!("   输出目录: {}", "   Output directory: {}", plan.output_dir.display()));
+        println!("{}", tr!(
+            "   预计输出大小: 约 {}",
+            "   Estimated output size: about {}",
+            format_bytes(plan.estimated_output_bytes)
+        ));
+        if let Some(estimated_time) = plan.estimated_time {
+            let estimated_time = humantime::format_duration(Duration::from_secs(estimated_time.as_secs().max(1)));
+            println!("{}", tr!(
+                "   预计耗时: 约 {estimated_time}（按试转换的速度粗略估算）",
+                "   Estimated time: about {estimated_time} (rough, based on a short test encode)"
+            ));
+        }
+        println!();
+    }
+
+    /// 显示每个文件的时长和预计输出大小
+    ///
+    /// # 参数
+    ///
+    /// * `estimates` - 每个文件的估算
+    /// * `base` - 显示相对路径时的基准目录
+    pub fn show_output_estimates(&self, estimates: &[OutputEstimate], base: &Path) {
+        println!("{}", text("📏 预计输出:", "📏 Estimated output:"));
+        for estimate in estimates {
+            let duration = estimate.duration.map_or_else(
+                || text("时长未知", "unknown duration").to_string(),
+                |duration| humantime::format_duration(Duration::from_secs(duration.as_secs())).to_string(),
+            );
+            println!(
+                "   {}  ({duration})  → {}",
+                estimate.source.strip_prefix(base).unwrap_or(&estimate.source).display(),
+                format_bytes(estimate.output_bytes)
+            );
+        }
         println!();
     }
 
@@ -215,55 +814,116 @@ impl UserInterface {
         } else {
             0
         };
-        print!("\r🔄 处理进度: {current}/{total} ({percentage}%)");
+        print!("{}", tr!("\r🔄 处理进度: {current}/{total} ({percentage}%)", "\r🔄 Progress: {current}/{total} ({percentage}%)"));
         io::stdout().flush().unwrap_or(());
     }
 
     /// 显示处理完成信息
     /// 
-    /// 显示转换完成的总结信息
+    /// 显示转换完成的总结信息，统计中有文件大小和耗时时还会显示节省的空间和平均速度
     /// 
     /// # 参数
     /// 
-    /// * `total_files` - 总处理文件数
+    /// * `summary` - 批处理统计
     /// * `output_dir` - 输出目录路径
-    pub fn show_completion(&self, total_files: usize, output_dir: &std::path::Path) {
+    pub fn show_completion(&self, summary: &BatchSummary, output_dir: &Path) {
         println!();
-        println!("🎉 转换完成!");
-        println!("   共处理 {total_files} 个文件");
-        println!("   所有音频文件已保存至: {}", output_dir.display());
+        println!("{}", style::success(text("🎉 转换完成!", "🎉 Conversion finished!")));
+        println!("{}", tr!("   共处理 {} 个文件", "   Processed {} files", summary.total_files));
+        if let Some(saved) = summary.saved_percent() {
+            println!("{}", tr!(
+                "   输入 {} → 输出 {}，节省 {saved:.1}%",
+                "   Input {} → output {}, {saved:.1}% smaller",
+                format_bytes(summary.input_bytes),
+                format_bytes(summary.output_bytes)
+            ));
+        }
+        if !summary.elapsed.is_zero() {
+            let elapsed = humantime::format_duration(Duration::from_secs(summary.elapsed.as_secs().max(1)));
+            match summary.bytes_per_second() {
+                Some(speed) if summary.input_bytes > 0 => {
+                    println!("{}", tr!(
+                        "   总耗时 {elapsed}，平均 {}/s",
+                        "   Took {elapsed}, {}/s on average",
+                        format_bytes(speed as u64)
+                    ));
+                }
+                _ => println!("{}", tr!("   总耗时 {elapsed}", "   Took {elapsed}")),
+            }
+        }
+        println!("{}", tr!("   所有音频文件已保存至: {}", "   All audio files were saved to: {}", output_dir.display()));
         println!();
-        println!("感谢使用 Video2Audio-RS! 🎵");
+        println!("{}", text("感谢使用 Video2Audio-RS! 🎵", "Thanks for using Video2Audio-RS! 🎵"));
     }
 
     /// 显示错误信息
     /// 
+    /// 在标准错误流显示单个文件的转换失败，附带错误代码
+    /// 
+    /// # 参数
+    /// 
+    /// * `source_file` - 失败的源文件
+    /// * `error` - 失败原因
+    pub fn show_file_failure(&self, source_file: &Path, error: &VideoToAudioError) {
+        let code = error.code();
+        eprintln!(
+            "{}",
+            style::failure(tr!(
+                "❌ [失败] 处理文件 '{}' 时出错 [{code}]: {}",
+                "❌ [failed] '{}' [{code}]: {}",
+                source_file.display(),
+                error
+            ))
+            .for_stderr()
+        );
+    }
+
     /// 以用户友好的方式显示错误信息
     /// 
+    /// 在标准错误流显示错误和错误代码，并提示用 `explain` 子命令查看原因和解决方法
+    /// 
     /// # 参数
     /// 
     /// * `error` - 要显示的错误
     pub fn show_error(&self, error: &VideoToAudioError) {
-        println!("❌ 发生错误: {error}");
+        let code = error.code();
+        eprintln!("{}", style::failure(tr!("❌ 发生错误 [{code}]: {error}", "❌ Error [{code}]: {error}")).for_stderr());
         
         // 根据错误类型提供额外的帮助信息
         match error {
             VideoToAudioError::MissingDependency(_) => {
-                println!("💡 解决方案:");
-                println!("   请安装 FFmpeg 并确保其在系统 PATH 中");
-                println!("   macOS: brew install ffmpeg");
-                println!("   Windows: choco install ffmpeg");
-                println!("   Linux: sudo apt install ffmpeg");
+                eprintln!("{}", text("💡 解决方案:", "💡 How to fix:"));
+                eprintln!("{}", text("   请安装 FFmpeg 并确保其在系统 PATH 中", "   Install FFmpeg and make sure it is on the PATH"));
+                eprintln!("   macOS: brew install ffmpeg");
+                eprintln!("   Windows: choco install ffmpeg");
+                eprintln!("   Linux: sudo apt install ffmpeg");
+                if cfg!(feature = "ffmpeg-download") {
+                    eprintln!("{}", text(
+                        "   或运行 video2audio-rs download-ffmpeg 自动下载",
+                        "   or run video2audio-rs download-ffmpeg to download it",
+                    ));
+                }
             }
             VideoToAudioError::InvalidPath(_) => {
-                println!("💡 请检查路径是否正确，确保使用完整的绝对路径");
+                eprintln!("{}", text(
+                    "💡 请检查路径是否正确，确保使用完整的绝对路径",
+                    "💡 Check that the path is correct and use a full absolute path",
+                ));
             }
             VideoToAudioError::UnsupportedFormat(_) => {
-                println!("💡 当前支持的视频格式: MP4, MKV, AVI, MOV, WEBM, FLV, WMV");
+                eprintln!("{}", text(
+                    "💡 当前支持的视频格式: MP4, MKV, AVI, MOV, WEBM, FLV, WMV",
+                    "💡 Supported video formats: MP4, MKV, AVI, MOV, WEBM, FLV, WMV",
+                ));
             }
             _ => {}
         }
-        println!();
+        if !matches!(error, VideoToAudioError::Cancelled) {
+            eprintln!("{}", tr!(
+                "💡 运行 video2audio-rs explain {code} 查看常见原因和解决方法",
+                "💡 Run video2audio-rs explain {code} for common causes and fixes"
+            ));
+        }
     }
 }
 
@@ -272,3 +932,149 @@ impl Default for UserInterface {
         Self::new()
     }
 }
+
+/// 去掉行式提示末尾的冒号和空格，菜单组件会自行添加提示符
+fn prompt_text(prompt: &str) -> &str {
+    prompt.trim().trim_end_matches([':', '：']).trim_end()
+}
+
+/// 检查源目录是否存在且是文件夹，返回显示给用户的错误信息
+fn validate_source_dir(source_dir: &str) -> std::result::Result<(), String> {
+    let path = Path::new(source_dir);
+    if !path.exists() {
+        return Err(tr!(
+            "路径 '{source_dir}' 不存在，请检查路径是否正确",
+            "'{source_dir}' does not exist, please check the path"
+        ));
+    }
+    if !path.is_dir() {
+        return Err(tr!(
+            "'{source_dir}' 不是一个文件夹，请输入文件夹路径",
+            "'{source_dir}' is not a folder, please enter a folder path"
+        ));
+    }
+    Ok(())
+}
+
+/// 挑选列表中显示的文件：相对于基准目录的路径和文件大小
+fn file_label(file: &Path, base: &Path) -> String {
+    let path = file.strip_prefix(base).unwrap_or(file);
+    match fs::metadata(file) {
+        Ok(metadata) => format!("{}  ({})", path.display(), format_bytes(metadata.len())),
+        Err(_) => path.display().to_string(),
+    }
+}
+
+/// 输入为最近使用目录的编号（从 1 开始）时返回对应的目录
+fn pick_recent_dir(input: &str, recent: &[&str]) -> Option<String> {
+    let index = input.parse::<usize>().ok()?.checked_sub(1)?;
+    recent.get(index).map(|dir| dir.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_recent_dir() {
+        let recent = ["/mnt/nas/videos", "/home/me/clips"];
+        assert_eq!(pick_recent_dir("2", &recent).as_deref(), Some("/home/me/clips"));
+        assert_eq!(pick_recent_dir("0", &recent), None);
+        assert_eq!(pick_recent_dir("3", &recent), None);
+        assert_eq!(pick_recent_dir("/mnt/nas/videos", &recent), None);
+    }
+
+    #[test]
+    fn test_prompt_text_and_source_dir_validation() {
+        assert_eq!(prompt_text("请输入选项 (1-4): "), "请输入选项 (1-4)");
+        assert_eq!(prompt_text("📄 输出文件已存在时:"), "📄 输出文件已存在时");
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.mp4");
+        fs::write(&file, "video").unwrap();
+        assert!(validate_source_dir(&temp_dir.path().to_string_lossy()).is_ok());
+        assert!(validate_source_dir(&file.to_string_lossy()).unwrap_err().contains("不是一个文件夹"));
+        assert!(validate_source_dir("/no/such/dir").unwrap_err().contains("不存在"));
+    }
+
+    #[test]
+    fn test_file_label() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let video = temp_dir.path().join("raw").join("capture.mkv");
+        fs::create_dir_all(video.parent().unwrap()).unwrap();
+        fs::write(&video, vec![0u8; 2048]).unwrap();
+        let expected = format!("{}  (2.0 KB)", Path::new("raw").join("capture.mkv").display());
+        assert_eq!(file_label(&video, temp_dir.path()), expected);
+        assert_eq!(file_label(Path::new("/elsewhere/a.mp4"), temp_dir.path()), "/elsewhere/a.mp4");
+    }
+
+    #[test]
+    fn test_batch_progress_tracks_active_files() {
+        let progress = BatchProgress::with_draw_target(2, ProgressDrawTarget::hidden());
+        let video = Path::new("videos/lecture.mp4");
+        progress.file_started(video);
+        progress.file_started(Path::new("videos/raw.mkv"));
+        assert_eq!(progress.active_files(), 2);
+
+        progress.file_progress(video, Duration::from_secs(30), Some(Duration::from_secs(120)));
+        let bar = progress.files.lock().unwrap()[video].clone();
+        assert_eq!(bar.length(), Some(120_000));
+        assert_eq!(bar.position(), 30_000);
+
+        progress.file_finished(video);
+        progress.file_done(1, 2);
+        assert_eq!(progress.active_files(), 1);
+        assert_eq!(progress.overall.position(), 1);
+        assert_eq!(file_display_name(video), "lecture.mp4");
+    }
+
+    #[test]
+    fn test_conversion_plan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let video = temp_dir.path().join("a.mp4");
+        fs::write(&video, vec![0u8; 1000]).unwrap();
+        let files = [video, temp_dir.path().join("missing.mp4")];
+        let options = ConversionOptions::new(AudioFormat::Mp3);
+
+        let plan = ConversionPlan::new(&files, &options, temp_dir.path());
+        assert_eq!(plan.file_count, 2);
+        assert_eq!(plan.input_bytes, 1000);
+        assert_eq!(plan.estimated_output_bytes, estimate_output_size(1000, &options));
+        assert_eq!(plan.unknown_durations, 2);
+
+        let plan = plan.with_durations([Some(Duration::from_secs(90)), None]);
+        assert_eq!(plan.duration, Duration::from_secs(90));
+        assert_eq!(plan.unknown_durations, 1);
+        assert_eq!(plan.estimated_time, None);
+
+        let estimates = [OutputEstimate {
+            source: files[0].clone(),
+            source_bytes: 1000,
+            duration: Some(Duration::from_secs(60)),
+            output_bytes: 1_440_000,
+        }];
+        let plan = ConversionPlan::new(&files[..1], &options, temp_dir.path())
+            .with_estimates(&estimates)
+            .with_estimated_time(Some(Duration::from_secs(2)));
+        assert_eq!(plan.duration, Duration::from_secs(60));
+        assert_eq!(plan.unknown_durations, 0);
+        assert_eq!(plan.estimated_output_bytes, 1_440_000);
+        assert_eq!(plan.estimated_time, Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_batch_summary() {
+        let summary = BatchSummary {
+            total_files: 3,
+            input_bytes: 1_000_000,
+            output_bytes: 80_000,
+            elapsed: Duration::from_secs(4),
+        };
+        assert_eq!(summary.saved_percent(), Some(92.0));
+        assert_eq!(summary.bytes_per_second(), Some(250_000.0));
+
+        let empty = BatchSummary::new(0);
+        assert_eq!(empty.saved_percent(), None);
+        assert_eq!(empty.bytes_per_second(), None);
+    }
+}
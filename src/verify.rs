@@ -0,0 +1,181 @@
+//! # 输出校验模块
+//!
+//! 检查输出目录中已有的音频文件：能否完整解码，时长是否与源文件一致。
+//! 用于找出崩溃或磁盘写满等原因留下的截断文件。
+//!
+//! 输出目录需要与源目录结构对应（如 `sync` 子命令生成的镜像目录），
+//! 使用了 `--start`/`--end` 或裁剪静音的输出时长本来就与源文件不同，会被报告为不一致。
+
+use crate::audio_format::AudioFormat;
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::{input_arg, FileProcessor};
+use crate::probe;
+use crate::tr;
+use rayon::prelude::*;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 默认允许的时长误差
+pub const DEFAULT_DURATION_TOLERANCE: Duration = Duration::from_secs(1);
+
+/// 输出文件的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyIssue {
+    /// 无法读取源文件或输出文件的时长
+    ProbeFailed(String),
+
+    /// 时长与源文件相差超过允许的误差
+    DurationMismatch {
+        /// 源文件时长
+        source: Duration,
+        /// 输出文件时长
+        output: Duration,
+    },
+
+    /// 解码时出错
+    DecodeFailed(String),
+}
+
+impl fmt::Display for VerifyIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyIssue::ProbeFailed(message) => f.write_str(&tr!(
+                "无法读取时长: {message}",
+                "Cannot read the duration: {message}",
+            )),
+            VerifyIssue::DurationMismatch { source, output } => f.write_str(&tr!(
+                "时长不一致: 源文件 {:.1} 秒，输出 {:.1} 秒",
+                "Duration mismatch: source {:.1} s, output {:.1} s",
+                source.as_secs_f64(),
+                output.as_secs_f64()
+            )),
+            VerifyIssue::DecodeFailed(message) => f.write_str(&tr!("解码失败: {message}", "Decoding failed: {message}")),
+        }
+    }
+}
+
+/// 校验结果
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// 检查过的输出文件数
+    pub checked: usize,
+
+    /// 还没有输出文件的源文件
+    pub missing: Vec<PathBuf>,
+
+    /// 有问题的输出文件及其问题，按路径排序
+    pub issues: Vec<(PathBuf, VerifyIssue)>,
+}
+
+impl FileProcessor {
+    /// 检查输出目录中与源文件对应的全部音频文件
+    ///
+    /// `self` 应通过 [`FileProcessor::with_preserved_structure`] 设置为以 `source_root` 为根，
+    /// 这样才能找到每个源文件对应的输出文件。文件并行检查。
+    ///
+    /// # 错误
+    ///
+    /// 目录无法访问、FFmpeg 无法执行或被取消时返回相应的错误
+    pub fn verify_outputs(
+        &self,
+        source_root: &Path,
+        output_root: &Path,
+        format: AudioFormat,
+        tolerance: Duration,
+        cancel_token: &CancellationToken,
+    ) -> Result<VerifyReport> {
+        self.check_ffmpeg_availability()?;
+
+        let mut report = VerifyReport::default();
+        let mut pairs = Vec::new();
+        for source_file in self.find_video_files(source_root)? {
+            let output_path = self.output_path_for(&source_file, output_root, format)?;
+            if output_path.is_file() {
+                pairs.push((source_file, output_path));
+            } else {
+                report.missing.push(source_file);
+            }
+        }
+
+        let results: Vec<_> = self.install(|| {
+            pairs
+                .par_iter()
+                .map(|(source_file, output_path)| {
+                    self.verify_output(source_file, output_path, tolerance, cancel_token)
+                        .map(|issue| issue.map(|issue| (output_path.clone(), issue)))
+                })
+                .collect::<Result<_>>()
+        })?;
+
+        report.checked = pairs.len();
+        report.issues = results.into_iter().flatten().collect();
+        report.issues.sort_by(|(a, _), (b, _)| a.cmp(b));
+        Ok(report)
+    }
+
+    /// 检查单个输出文件
+    ///
+    /// 先比较时长，再完整解码一遍输出文件。
+    ///
+    /// # 返回值
+    ///
+    /// 发现的问题，文件正常时为 `None`
+    ///
+    /// # 错误
+    ///
+    /// FFmpeg 无法执行或被取消时返回相应的错误
+    pub fn verify_output(
+        &self,
+        source_file: &Path,
+        output_path: &Path,
+        tolerance: Duration,
+        cancel_token: &CancellationToken,
+    ) -> Result<Option<VerifyIssue>> {
+        if cancel_token.is_cancelled() {
+            return Err(VideoToAudioError::Cancelled);
+        }
+
+        let durations = probe::probe_duration(self.ffmpeg_path(), source_file)
+            .and_then(|source| Ok((source, probe::probe_duration(self.ffmpeg_path(), output_path)?)));
+        match durations {
+            Ok((source, output)) if source.abs_diff(output) > tolerance => {
+                return Ok(Some(VerifyIssue::DurationMismatch { source, output }));
+            }
+            Ok(_) => {}
+            Err(VideoToAudioError::FfmpegError(message)) => return Ok(Some(VerifyIssue::ProbeFailed(message))),
+            Err(e) => return Err(e),
+        }
+
+        // 以错误级别日志解码全部音频，任何错误输出都说明文件有损坏
+        let mut args: Vec<OsString> = ["-hide_banner", "-nostats", "-v", "error", "-i"].map(OsString::from).into();
+        args.push(input_arg(output_path));
+        args.extend(["-map", "0:a", "-f", "null", "-"].map(OsString::from));
+        let (status, stderr) = self.run_analysis(&args, None, cancel_token)?;
+        let message = stderr.lines().map(str::trim).find(|line| !line.is_empty());
+        Ok(match (status.success(), message) {
+            (true, None) => None,
+            (_, Some(message)) => Some(VerifyIssue::DecodeFailed(message.to_string())),
+            (false, None) => Some(VerifyIssue::DecodeFailed(tr!(
+                "FFmpeg 退出状态 {status}",
+                "FFmpeg exit status {status}",
+            ))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_display() {
+        let issue = VerifyIssue::DurationMismatch {
+            source: Duration::from_secs(600),
+            output: Duration::from_millis(312_400),
+        };
+        assert_eq!(issue.to_string(), "时长不一致: 源文件 600.0 秒，输出 312.4 秒");
+    }
+}
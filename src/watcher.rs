@@ -0,0 +1,142 @@
+//! # 目录监视模块
+//!
+//! 基于 `notify` 监视源目录中新增或修改的视频文件，在文件写入稳定
+//! （一段时间内不再变化）后自动转换。适合录屏软件持续写入的文件夹。
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Result, VideoToAudioError};
+use crate::file_processor::FileProcessor;
+use crate::options::ConversionOptions;
+use crate::tr;
+use notify::{EventKind, RecursiveMode, Watcher};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// 默认的防抖时间
+///
+/// 文件在这段时间内没有新的变化才会被视为写入完成
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// 等待文件系统事件的超时，决定检查取消请求和防抖到期的频率
+const EVENT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// 目录监视器
+///
+/// 持续监视源目录，对稳定下来的新视频文件调用 [`FileProcessor`] 进行转换
+pub struct DirectoryWatcher<'a> {
+    processor: &'a FileProcessor,
+    debounce: Duration,
+}
+
+impl<'a> DirectoryWatcher<'a> {
+    /// 创建目录监视器
+    ///
+    /// # 参数
+    ///
+    /// * `processor` - 用于判断视频文件和执行转换的文件处理器
+    /// * `debounce` - 防抖时间
+    pub fn new(processor: &'a FileProcessor, debounce: Duration) -> Self {
+        Self { processor, debounce }
+    }
+
+    /// 开始监视，直到取消令牌被取消
+    ///
+    /// 每个转换完成的文件都会在当前线程上调用一次 `on_result`。
+    ///
+    /// # 参数
+    ///
+    /// * `source_dir` - 要监视的源目录（递归）
+    /// * `output_dir` - 输出目录，其中的变化会被忽略
+    /// * `options` - 转换选项，也可以直接传入 [`AudioFormat`](crate::AudioFormat)
+    /// * `cancel_token` - 取消令牌，取消后停止监视并返回
+    /// * `on_result` - 单个文件转换结果的回调
+    ///
+    /// # 错误
+    ///
+    /// 当无法建立目录监视时返回错误
+    pub fn watch<F>(
+        &self,
+        source_dir: &Path,
+        output_dir: &Path,
+        options: impl Into<ConversionOptions>,
+        cancel_token: &CancellationToken,
+        mut on_result: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&Path, Result<PathBuf>),
+    {
+        let options = options.into();
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(sender).map_err(watch_error)?;
+        watcher
+            .watch(source_dir, RecursiveMode::Recursive)
+            .map_err(watch_error)?;
+
+        // 最近一次发生变化的时间，用于防抖
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+        while !cancel_token.is_cancelled() {
+            match receiver.recv_timeout(EVENT_POLL_INTERVAL) {
+                Ok(Ok(event)) => {
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in event.paths {
+                            if self.processor.is_supported_video_file(&path)
+                                && !path.starts_with(output_dir)
+                            {
+                                pending.insert(path, Instant::now());
+                            }
+                        }
+                    }
+                }
+                Ok(Err(e)) => return Err(watch_error(e)),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+
+            let ready: Vec<PathBuf> = pending
+                .iter()
+                .filter(|(_, changed_at)| changed_at.elapsed() >= self.debounce)
+                .map(|(path, _)| path.clone())
+                .collect();
+            if ready.is_empty() {
+                continue;
+            }
+            for path in &ready {
+                pending.remove(path);
+            }
+
+            // 文件可能在防抖期间被删除或重命名
+            let results: Vec<_> = self.processor.install(|| {
+                ready
+                    .par_iter()
+                    .filter(|path| path.is_file())
+                    .map(|path| {
+                        let result = self.processor.convert_single_file_with_cancel(
+                            path,
+                            output_dir,
+                            options.clone(),
+                            cancel_token,
+                        );
+                        (path, result)
+                    })
+                    .collect()
+            });
+
+            for (path, result) in results {
+                if !matches!(result, Err(VideoToAudioError::Cancelled)) {
+                    on_result(path, result);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// 将 notify 的错误转换为项目错误类型
+fn watch_error(err: notify::Error) -> VideoToAudioError {
+    VideoToAudioError::from(std::io::Error::other(tr!("目录监视失败: {err}", "Directory watching failed: {err}")))
+}
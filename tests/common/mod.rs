@@ -2,6 +2,9 @@
 //! 
 //! 提供测试中使用的公共工具函数和辅助类型
 
+// 并非每个测试文件都会用到全部辅助函数
+#![allow(dead_code)]
+
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -286,3 +289,34 @@ impl Default for TestEnvironment {
         Self::new()
     }
 }
+
+/// 假 ffmpeg 转换时执行的默认脚本：把全部参数写入输出文件（最后一个参数）
+pub const FAKE_FFMPEG_WRITE_ARGS: &str = "for last; do :; done\necho \"$*\" > \"$last\"\n";
+
+/// 在 `dir/bin` 中创建假的 ffmpeg 和 ffprobe，返回该目录
+///
+/// 假 ffmpeg 响应 `-version`，`-encoders` 时只列出 libmp3lame，转换时把参数写入输出文件；
+/// `ffprobe_body` 为假 ffprobe 的脚本内容（不含 `#!/bin/sh`）
+#[cfg(unix)]
+pub fn fake_ffmpeg_bin(dir: &Path, ffprobe_body: &str) -> PathBuf {
+    fake_ffmpeg_bin_with(dir, ffprobe_body, FAKE_FFMPEG_WRITE_ARGS)
+}
+
+/// 与 [`fake_ffmpeg_bin`] 相同，但假 ffmpeg 转换时执行 `convert_body`（不含 `#!/bin/sh`）
+#[cfg(unix)]
+pub fn fake_ffmpeg_bin_with(dir: &Path, ffprobe_body: &str, convert_body: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = dir.join("bin");
+    fs::create_dir_all(&bin).expect("无法创建 bin 目录");
+    let ffmpeg = format!(
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\n\
+         case \"$*\" in *-encoders*) echo ' ------'; echo ' A....D libmp3lame MP3'; exit 0;; esac\n\
+         {convert_body}"
+    );
+    for (tool, script) in [("ffmpeg", ffmpeg), ("ffprobe", format!("#!/bin/sh\n{ffprobe_body}"))] {
+        fs::write(bin.join(tool), script).expect("无法创建假的 FFmpeg 脚本");
+        fs::set_permissions(bin.join(tool), fs::Permissions::from_mode(0o755)).expect("无法设置脚本权限");
+    }
+    bin
+}
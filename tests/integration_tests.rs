@@ -5,7 +5,10 @@
 use std::fs;
 use std::path::Path;
 use tempfile::TempDir;
-use video2audio_rs::{AudioFormat, FileProcessor, UserInterface, VideoToAudioError};
+use video2audio_rs::{
+    AudioFormat, BatchJournal, CancellationToken, ConversionBackend, ConversionOptions, DirectoryWatcher, FileProcessor,
+    HwAccel, OverwritePolicy, UserInterface, VideoToAudioError,
+};
 
 /// 测试工具模块
 mod common;
@@ -163,7 +166,7 @@ fn test_error_handling_chain() {
     let video_error: VideoToAudioError = io_error.into();
     
     match video_error {
-        VideoToAudioError::Io(_) => (),
+        VideoToAudioError::Io { .. } => (),
         _ => panic!("应该转换为 Io 错误"),
     }
 }
@@ -175,12 +178,14 @@ fn test_format_parsing_comprehensive() {
         ("1", AudioFormat::Mp3),
         ("2", AudioFormat::AacCopy),
         ("3", AudioFormat::Opus),
+        ("4", AudioFormat::M4b),
         ("mp3", AudioFormat::Mp3),
         ("MP3", AudioFormat::Mp3),
         ("aac", AudioFormat::AacCopy),
         ("AAC", AudioFormat::AacCopy),
         ("opus", AudioFormat::Opus),
         ("OPUS", AudioFormat::Opus),
+        ("m4b", AudioFormat::M4b),
     ];
     
     for (input, expected) in test_cases {
@@ -189,7 +194,7 @@ fn test_format_parsing_comprehensive() {
     }
     
     // 测试无效输入
-    let invalid_inputs = vec!["0", "4", "invalid", "", "   ", "mp4"];
+    let invalid_inputs = vec!["0", "5", "invalid", "", "   ", "mp4"];
     for input in invalid_inputs {
         assert!(AudioFormat::from_user_input(input).is_err(), "输入 '{input}' 应该返回错误");
     }
@@ -231,6 +236,1731 @@ fn test_progress_callback() {
     assert!(progress_calls.lock().unwrap().is_empty());
 }
 
+#[test]
+fn test_progress_callback_sequential_with_failures() {
+    let temp_dir = TempDir::new().unwrap();
+    let processor = FileProcessor::new();
+    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
+
+    // 不存在的源文件会在调用 FFmpeg 之前失败
+    let files: Vec<_> = (0..5)
+        .map(|i| temp_dir.path().join(format!("missing{i}.mp4")))
+        .collect();
+
+    let mut progress_calls = Vec::new();
+    let (success, failure) = processor.batch_convert(
+        &files,
+        &output_dir,
+        AudioFormat::Mp3,
+        |current, total| progress_calls.push((current, total)),
+    );
+
+    assert_eq!(success, 0);
+    assert_eq!(failure, files.len());
+
+    // 回调在调用方线程上串行执行，进度严格递增
+    let expected: Vec<_> = (1..=files.len()).map(|i| (i, files.len())).collect();
+    assert_eq!(progress_calls, expected);
+}
+
+#[test]
+fn test_batch_convert_cancelled_before_start() {
+    let temp_dir = TempDir::new().unwrap();
+    let processor = FileProcessor::new();
+    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
+
+    let files: Vec<_> = (0..3)
+        .map(|i| temp_dir.path().join(format!("video{i}.mp4")))
+        .collect();
+
+    let token = CancellationToken::new();
+    token.cancel();
+
+    let mut progress_calls = 0;
+    let (success, failure) = processor.batch_convert_with_cancel(
+        &files,
+        &output_dir,
+        AudioFormat::Mp3,
+        &token,
+        |_current, _total| progress_calls += 1,
+    );
+
+    // 已取消的批次不会处理任何文件
+    assert_eq!((success, failure), (0, 0));
+    assert_eq!(progress_calls, 0);
+}
+
+#[test]
+fn test_batch_convert_retries_report_final_outcome_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let processor = FileProcessor::new()
+        .with_retries(2)
+        .with_fallback_format(Some(AudioFormat::Mp3));
+    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
+
+    let files: Vec<_> = (0..3)
+        .map(|i| temp_dir.path().join(format!("missing{i}.mp4")))
+        .collect();
+
+    let mut progress_calls = Vec::new();
+    let (success, failure) = processor.batch_convert(
+        &files,
+        &output_dir,
+        AudioFormat::AacCopy,
+        |current, total| progress_calls.push((current, total)),
+    );
+
+    // 每个文件重试后仍失败，但只计入一次失败和一次进度
+    assert_eq!((success, failure), (0, 3));
+    assert_eq!(progress_calls, vec![(1, 3), (2, 3), (3, 3)]);
+}
+
+#[test]
+fn test_fail_fast_stops_batch() {
+    let temp_dir = TempDir::new().unwrap();
+    let processor = FileProcessor::new().with_fail_fast(true);
+    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
+
+    let files: Vec<_> = (0..200)
+        .map(|i| temp_dir.path().join(format!("missing{i}.mp4")))
+        .collect();
+
+    let token = CancellationToken::new();
+    let (success, failure) = processor.batch_convert_with_cancel(
+        &files,
+        &output_dir,
+        AudioFormat::Mp3,
+        &token,
+        |_current, _total| {},
+    );
+
+    assert_eq!(success, 0);
+    assert!(failure >= 1);
+    assert!(token.is_cancelled(), "首个失败后应取消批处理");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_preflight_skips_problem_files() {
+    let temp_dir = TempDir::new().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("empty.mp4"), "").unwrap();
+    fs::write(videos.join("silent.mp4"), "no audio").unwrap();
+    fs::write(videos.join("good.mp4"), "video").unwrap();
+
+    // 假 ffprobe 对 silent.mp4 不输出任何音轨，假 ffmpeg 把参数写入输出文件
+    let bin = common::fake_ffmpeg_bin(
+        temp_dir.path(),
+        "for last; do :; done\ncase \"$last\" in *silent*) ;; *) echo 1,eng;; esac\n",
+    );
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--batch", "--quiet", "--preflight", "--skip-space-check", "--format", "mp3", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("[E012]") && stderr.contains("[E010]"), "{stderr}");
+
+    let output_dir = videos.join("audio_exports");
+    assert!(output_dir.join("good.mp3").exists());
+    assert!(!output_dir.join("silent.mp3").exists());
+    let manifest = video2audio_rs::FailureManifest::load(&output_dir.join("failed_files.json")).unwrap();
+    assert_eq!(manifest.sources(), [videos.join("empty.mp4"), videos.join("silent.mp4")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_dry_run_estimates_without_converting() {
+    let temp_dir = TempDir::new().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("lecture.mp4"), "video").unwrap();
+
+    // 假 ffprobe 报告 10 分钟的时长，假 ffmpeg 把参数写入输出文件
+    let bin = common::fake_ffmpeg_bin(temp_dir.path(), "echo 600\n");
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--lang", "en", "--batch", "--dry-run", "--format", "mp3", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // MP3 V0 约 245 kbps，10 分钟约 17.5 MB
+    assert!(stdout.contains("lecture.mp4  (10m)  → 17.5 MB"), "{stdout}");
+    assert!(stdout.contains("Estimated time: about"), "{stdout}");
+    assert!(!videos.join("audio_exports/lecture.mp3").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_preview_outside_terminal_only_creates_preview() {
+    let temp_dir = TempDir::new().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("a.mp4"), "video").unwrap();
+    fs::write(videos.join("b.mp4"), "video").unwrap();
+
+    let bin = common::fake_ffmpeg_bin(temp_dir.path(), "echo 600\n");
+
+    let tmp = temp_dir.path().join("tmp");
+    fs::create_dir(&tmp).unwrap();
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .env("TMPDIR", &tmp)
+        .args(["--batch", "--preview", "--skip-space-check", "--format", "mp3", "--order", "name", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // 只转换第一个文件的前 30 秒，保存在临时目录中
+    let preview = fs::read_to_string(tmp.join("video2audio-preview/a.mp3")).unwrap();
+    assert!(preview.contains("-t 30"), "{preview}");
+    assert!(!tmp.join("video2audio-preview/b.mp3").exists());
+    assert!(!videos.join("audio_exports/a.mp3").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_bench_lists_each_setting() {
+    let temp_dir = TempDir::new().unwrap();
+    let input = temp_dir.path().join("sample.mp4");
+    fs::write(&input, "video").unwrap();
+
+    let bin = common::fake_ffmpeg_bin(temp_dir.path(), "echo 600\n");
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--lang", "en", "bench", "--length", "5s", "--format", "mp3,opus", "--input"])
+        .arg(&input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // 每个预设一行，缺少编码器的格式显示错误而不中断测试
+    assert!(stdout.contains("Testing 6 encoder settings on a 5s segment"), "{stdout}");
+    for row in ["MP3    high      V0", "MP3    standard  V4", "MP3    small     V7", "OPUS   small            ❌"] {
+        assert!(stdout.contains(row), "{row}: {stdout}");
+    }
+    // 试转换的片段不留在源文件旁边
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+}
+
+#[test]
+fn test_cli_explain_error_code() {
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .args(["--lang", "en", "explain", "e010"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("E010 (NoAudioStream)"), "{stdout}");
+    assert!(stdout.contains("How to fix:"), "{stdout}");
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .args(["--lang", "en", "explain", "E999"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[E004]"));
+}
+
+#[test]
+fn test_cli_rejects_cover_for_formats_without_artwork() {
+    let temp_dir = TempDir::new().unwrap();
+    let cover = temp_dir.path().join("cover.jpg");
+    fs::write(&cover, "image").unwrap();
+
+    for format in ["aac", "opus"] {
+        let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+            .unwrap()
+            .args(["--lang", "en", "--batch", "--format", format, "--cover"])
+            .arg(&cover)
+            .arg("--source")
+            .arg(temp_dir.path())
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains("[E004]") && stderr.contains("only works with MP3 and M4B"), "{stderr}");
+    }
+}
+
+#[test]
+fn test_cli_fail_fast_exit_code() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("broken.mp4"), "not a real video").unwrap();
+
+    assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .args(["--batch", "--quiet", "--fail-fast", "--format", "mp3", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_fail_fast_stops_after_conversion_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("a-broken.mp4"), "not a real video").unwrap();
+    fs::write(videos.join("b-broken.mp4"), "not a real video").unwrap();
+
+    // 能列出编码器、但每次转换都失败的假 FFmpeg
+    let bin = common::fake_ffmpeg_bin_with(temp_dir.path(), "exit 1\n", "echo 'moov atom not found' >&2\nexit 1\n");
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--batch", "--quiet", "--skip-space-check", "--fail-fast", "--jobs", "1", "--format", "mp3", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1), "{stderr}");
+    // 首个失败后中止，不再转换第二个文件
+    assert_eq!(stderr.matches("moov atom not found").count(), 1, "{stderr}");
+    assert!(stderr.contains("⛔"), "{stderr}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_reports_each_failure_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("broken.mp4"), "not a real video").unwrap();
+
+    // 能列出编码器、但每次转换都失败的假 FFmpeg
+    let bin = common::fake_ffmpeg_bin_with(temp_dir.path(), "exit 1\n", "echo 'moov atom not found' >&2\nexit 1\n");
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--batch", "--quiet", "--skip-space-check", "--format", "mp3", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("moov atom not found").count(), 1, "{stderr}");
+    assert!(stderr.contains("[E012]"), "{stderr}");
+
+    // 失败的文件写入失败清单，--retry-failed 沿用清单中的格式和输出目录只重试这些文件
+    let manifest_path = videos.join("audio_exports").join("failed_files.json");
+    let manifest = video2audio_rs::FailureManifest::load(&manifest_path).unwrap();
+    assert_eq!(manifest.sources(), [videos.join("broken.mp4")]);
+    assert_eq!(manifest.format(), Some(AudioFormat::Mp3));
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--batch", "--quiet", "--skip-space-check", "--retry-failed"])
+        .arg(&manifest_path)
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(stderr.matches("moov atom not found").count(), 1, "{stderr}");
+    assert_eq!(video2audio_rs::FailureManifest::load(&manifest_path).unwrap().sources(), manifest.sources());
+
+    // 损坏的文件链接到隔离文件夹并在报告中注明
+    let report = temp_dir.path().join("report.json");
+    assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", &bin)
+        .env("HOME", temp_dir.path())
+        .args(["--batch", "--quiet", "--skip-space-check", "--format", "mp3", "--quarantine", "link", "--source"])
+        .arg(&videos)
+        .arg("--report")
+        .arg(&report)
+        .output()
+        .unwrap();
+    let quarantined = videos.join("audio_exports").join("corrupt").join("broken.mp4");
+    assert!(fs::symlink_metadata(&quarantined).unwrap().file_type().is_symlink());
+    assert!(videos.join("broken.mp4").exists());
+    let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(report[0]["quarantined"], quarantined.to_string_lossy().as_ref());
+}
+
+#[test]
+fn test_batch_journal_records_failures_for_resume() {
+    let temp_dir = TempDir::new().unwrap();
+    let processor = FileProcessor::new().with_journal(true);
+    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
+
+    let files: Vec<_> = (0..3)
+        .map(|i| temp_dir.path().join(format!("missing{i}.mp4")))
+        .collect();
+
+    let (_, failure) = processor.batch_convert(&files, &output_dir, AudioFormat::Opus, |_, _| {});
+    assert_eq!(failure, 3);
+
+    // 失败的文件保留在日志中，继续时会被重新处理
+    let journal = BatchJournal::load(&output_dir).unwrap().expect("应该写入状态日志");
+    assert_eq!(journal.format(), Some(AudioFormat::Opus));
+    assert_eq!(journal.pending(), files);
+    assert_eq!(journal.failures().len(), 3);
+}
+
+#[test]
+fn test_incremental_skips_up_to_date_outputs() {
+    use std::time::{Duration, SystemTime};
+
+    let temp_dir = TempDir::new().unwrap();
+    let processor = FileProcessor::new().with_overwrite_policy(OverwritePolicy::SkipUpToDate);
+    let output_dir = processor.create_output_directory(temp_dir.path()).unwrap();
+
+    let source = temp_dir.path().join("clip.mp4");
+    let output = output_dir.join("clip.mp3");
+    fs::write(&source, "fake video content").unwrap();
+    fs::write(&output, "fake audio content").unwrap();
+
+    let now = SystemTime::now();
+    let set_mtime = |path: &Path, time: SystemTime| {
+        fs::File::options().write(true).open(path).unwrap().set_modified(time).unwrap();
+    };
+
+    // 输出比源文件新：直接跳过，不调用 FFmpeg
+    set_mtime(&source, now - Duration::from_secs(60));
+    set_mtime(&output, now);
+    let result = processor.convert_single_file(&source, &output_dir, AudioFormat::Mp3);
+    assert_eq!(result.unwrap(), output);
+
+    // 源文件更新后需要重新转换（伪造的视频内容会导致转换失败）
+    set_mtime(&source, now + Duration::from_secs(60));
+    assert!(processor.convert_single_file(&source, &output_dir, AudioFormat::Mp3).is_err());
+}
+
+#[test]
+fn test_watcher_picks_up_new_video() {
+    use std::time::Duration;
+
+    let temp_dir = TempDir::new().unwrap();
+    let output_dir = temp_dir.path().join("audio_exports");
+    let processor = FileProcessor::new();
+    let token = CancellationToken::new();
+
+    // 监视开始后再写入新视频；超时后强制停止，避免测试挂起
+    let writer_dir = temp_dir.path().to_path_buf();
+    let timeout_token = token.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(500));
+        fs::write(writer_dir.join("recording.mp4"), "fake video content").unwrap();
+        std::thread::sleep(Duration::from_secs(10));
+        timeout_token.cancel();
+    });
+
+    let mut seen = Vec::new();
+    DirectoryWatcher::new(&processor, Duration::from_millis(100))
+        .watch(temp_dir.path(), &output_dir, AudioFormat::Mp3, &token, |source, _result| {
+            seen.push(source.to_path_buf());
+            token.cancel();
+        })
+        .unwrap();
+
+    assert_eq!(seen, vec![temp_dir.path().join("recording.mp4")]);
+}
+
+#[test]
+fn test_builder_configures_processor() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("clip.mp4"), "fake").unwrap();
+    fs::write(temp_dir.path().join("clip.mkv"), "fake").unwrap();
+
+    let processor = FileProcessor::builder()
+        .extensions(["mkv"])
+        .ffmpeg_path("/nonexistent/bin/ffmpeg")
+        .threads(2)
+        .build()
+        .unwrap();
+
+    // 只扫描指定的扩展名
+    let files = processor.find_video_files(temp_dir.path()).unwrap();
+    assert_eq!(files, vec![temp_dir.path().join("clip.mkv")]);
+
+    // 使用指定的 FFmpeg 路径
+    let result = processor.convert_single_file(&files[0], temp_dir.path(), AudioFormat::Mp3);
+    match result {
+        Err(VideoToAudioError::MissingDependency(_)) => (),
+        other => panic!("应该返回 MissingDependency 错误: {other:?}"),
+    }
+
+    // 专用线程池中的批量转换同样报告每个文件
+    let (success, failure) = processor.batch_convert(&files, temp_dir.path(), AudioFormat::Mp3, |_, _| {});
+    assert_eq!((success, failure), (0, 1));
+}
+
+#[test]
+fn test_builder_rejects_invalid_options() {
+    assert!(FileProcessor::builder().threads(0).build().is_err());
+    assert!(FileProcessor::builder().extensions([]).build().is_err());
+}
+
+#[test]
+fn test_configured_video_extensions() {
+    use video2audio_rs::Config;
+
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["clip.mp4", "camcorder.MTS", "dvd.vob", "old.ts"] {
+        fs::write(temp_dir.path().join(name), "fake").unwrap();
+    }
+
+    // 配置文件中增减的扩展名不区分大小写，可以带点
+    let config = Config {
+        extra_extensions: vec![".MTS".into(), "vob".into(), "mp4".into()],
+        excluded_extensions: vec!["ts".into()],
+        ..Config::default()
+    };
+    let extensions = config.video_extensions();
+    assert_eq!(extensions.iter().filter(|ext| *ext == "mp4").count(), 1);
+    assert!(extensions.contains(&"mts".to_string()) && !extensions.contains(&"ts".to_string()));
+
+    let processor = FileProcessor::new().with_extensions(&extensions);
+    let mut files = processor.find_video_files(temp_dir.path()).unwrap();
+    files.sort();
+    let expected: Vec<_> =
+        ["camcorder.MTS", "clip.mp4", "dvd.vob"].iter().map(|name| temp_dir.path().join(name)).collect();
+    assert_eq!(files, expected);
+}
+
+#[test]
+fn test_scan_records_skipped_files() {
+    use video2audio_rs::selection::{FileFilter, SkipReason, SkippedFile};
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("audio_exports")).unwrap();
+    for (name, size) in [("big.mp4", 2048), ("tiny.mkv", 10), ("notes.txt", 1), ("song.m4a", 1), ("old.ts", 2048)] {
+        fs::write(temp_dir.path().join(name), vec![0; size]).unwrap();
+    }
+    // 输出目录中的文件不算被排除
+    fs::write(temp_dir.path().join("audio_exports").join("big.mp3"), "x").unwrap();
+
+    let processor = FileProcessor::new()
+        .with_extensions(["mp4", "mkv"])
+        .with_file_filter(FileFilter { min_size: Some(1024), ..FileFilter::default() });
+    let (files, mut skipped) = processor.find_video_files_with_skipped(temp_dir.path()).unwrap();
+    assert_eq!(files, [temp_dir.path().join("big.mp4")]);
+    assert_eq!(files, processor.find_video_files(temp_dir.path()).unwrap());
+
+    skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    let expected = [
+        ("notes.txt", SkipReason::UnsupportedExtension),
+        ("old.ts", SkipReason::ExcludedExtension),
+        ("song.m4a", SkipReason::AudioInput),
+        ("tiny.mkv", SkipReason::TooSmall),
+    ]
+    .map(|(name, reason)| SkippedFile { path: temp_dir.path().join(name), reason });
+    assert_eq!(skipped, expected);
+}
+
+#[test]
+fn test_scan_skips_generated_output_directories() {
+    let temp_dir = TempDir::new().unwrap();
+    for name in [
+        "keep.mp4",
+        "nested/keep.mkv",
+        "audio_exports/old.mp4",
+        "nested/audio_exports/old.mp4",
+        "custom_out/old.mp4",
+        "resumable/old.mp4",
+        "resumable/.v2a-state.json",
+    ] {
+        let path = temp_dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "fake").unwrap();
+    }
+
+    let processor = FileProcessor::new().with_excluded_dir(temp_dir.path().join("custom_out"));
+    let mut files = processor.find_video_files(temp_dir.path()).unwrap();
+    files.sort();
+
+    assert_eq!(
+        files,
+        vec![temp_dir.path().join("keep.mp4"), temp_dir.path().join("nested/keep.mkv")]
+    );
+
+    // 直接扫描输出目录本身时不会跳过
+    let inside = processor.find_video_files(&temp_dir.path().join("audio_exports")).unwrap();
+    assert_eq!(inside.len(), 1);
+}
+
+#[test]
+fn test_streaming_discovery_and_conversion() {
+    let temp_dir = TempDir::new().unwrap();
+    for name in ["a.mp4", "nested/b.mkv", "nested/deeper/c.avi", "notes.txt"] {
+        let path = temp_dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "fake").unwrap();
+    }
+    let processor = FileProcessor::new();
+
+    assert!(processor.find_video_files_iter(&temp_dir.path().join("missing")).is_err());
+
+    let files = processor
+        .find_video_files_iter(temp_dir.path())
+        .unwrap()
+        .filter_map(|result| result.ok());
+
+    let mut reported = Vec::new();
+    let (success, failure) = processor.batch_convert_iter(
+        files,
+        &temp_dir.path().join("out"),
+        AudioFormat::Mp3,
+        &CancellationToken::new(),
+        |source, result, completed, discovered| {
+            assert!(result.is_err());
+            assert!(completed <= discovered);
+            reported.push(source.to_path_buf());
+        },
+    );
+
+    assert_eq!((success, failure), (0, 3));
+    reported.sort();
+    let mut expected = processor.find_video_files(temp_dir.path()).unwrap();
+    expected.sort();
+    assert_eq!(reported, expected);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_conversion_timeout_kills_ffmpeg() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::{Duration, Instant};
+
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("clip.mp4");
+    fs::write(&video, "fake").unwrap();
+
+    // 用一个一直不退出的脚本代替 FFmpeg
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(&fake_ffmpeg, "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nexec sleep 30\n").unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&fake_ffmpeg).build().unwrap();
+    let options = ConversionOptions::new(AudioFormat::Mp3).with_timeout(Duration::from_millis(200));
+
+    let started_at = Instant::now();
+    let result = processor.convert_single_file(&video, &temp_dir.path().join("out"), options);
+    assert!(started_at.elapsed() < Duration::from_secs(10));
+    match result {
+        Err(VideoToAudioError::FfmpegError(message)) => assert!(message.contains("超时")),
+        other => panic!("应该返回超时错误: {other:?}"),
+    }
+    assert!(!temp_dir.path().join("out/clip.mp3.part").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_failure_includes_ffmpeg_command_when_shown() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("my clip.mp4");
+    fs::write(&video, "fake").unwrap();
+
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(&fake_ffmpeg, "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\necho 'moov atom not found' >&2\nexit 1\n").unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output_dir = temp_dir.path().join("out");
+    let processor = FileProcessor::builder().ffmpeg_path(&fake_ffmpeg).build().unwrap();
+    match processor.convert_single_file(&video, &output_dir, AudioFormat::Mp3) {
+        Err(VideoToAudioError::CorruptInput(message)) => assert!(!message.contains("命令:")),
+        other => panic!("应该识别为输入文件损坏: {other:?}"),
+    }
+
+    let processor = processor.with_show_commands(true);
+    match processor.convert_single_file(&video, &output_dir, AudioFormat::Mp3) {
+        Err(VideoToAudioError::CorruptInput(message)) => {
+            assert!(message.contains("moov atom not found"));
+            assert!(message.contains(&format!("命令: {}", fake_ffmpeg.display())));
+            assert!(message.contains(&format!("'{}'", video.display())));
+        }
+        other => panic!("应该识别为输入文件损坏: {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_encoder_falls_back_to_available_encoder() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+
+    // 模拟一个缺少 libopus、只有内置 opus 编码器的精简版 FFmpeg
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &fake_ffmpeg,
+        "#!/bin/sh\necho ' ------'\necho ' A....D opus   Opus'\necho ' A....D aac    AAC'\n",
+    )
+    .unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&fake_ffmpeg).build().unwrap();
+
+    let options = processor.check_encoder(AudioFormat::Opus).unwrap();
+    assert_eq!(options.encoder.as_deref(), Some("opus"));
+    assert_eq!(processor.check_encoder(AudioFormat::AacCopy).unwrap().encoder, None);
+
+    match processor.check_encoder(AudioFormat::Mp3) {
+        Err(VideoToAudioError::MissingDependency(message)) => assert!(message.contains("libmp3lame")),
+        other => panic!("应该返回 MissingDependency 错误: {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_convert_non_utf8_path() {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    // Latin-1 编码的 "vidéo.mp4"，不是有效的 UTF-8
+    let video = temp_dir.path().join(OsStr::from_bytes(b"vid\xe9o.mp4"));
+    fs::write(&video, "fake").unwrap();
+
+    // 只创建输出文件（最后一个参数）的假 FFmpeg
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(&fake_ffmpeg, "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\n: > \"$last\"\n").unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&fake_ffmpeg).build().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let output = processor.convert_single_file(&video, &output_dir, AudioFormat::Mp3).unwrap();
+
+    assert_eq!(output, output_dir.join(OsStr::from_bytes(b"vid\xe9o.mp3")));
+    assert!(output.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_convert_hostile_file_names() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let names = ["-i.mp4", "-y.mkv", "concat:clip.mp4"];
+    let files: Vec<_> = names
+        .iter()
+        .map(|name| {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, "fake").unwrap();
+            path
+        })
+        .collect();
+
+    // 输入必须紧跟在 -i 之后，输出必须是最后一个参数
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &fake_ffmpeg,
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\n\
+         while [ \"$1\" != -i ]; do shift; done\n[ -f \"$2\" ] || exit 1\n\
+         for last; do :; done\n: > \"$last\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&fake_ffmpeg).build().unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let (success, failure) = processor.batch_convert(&files, &output_dir, AudioFormat::Mp3, |_, _| {});
+
+    assert_eq!((success, failure), (3, 0));
+    for name in ["-i.mp3", "-y.mp3", "concat:clip.mp3"] {
+        assert!(output_dir.join(name).exists(), "{name} 应该被转换");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_low_priority_ffmpeg() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("clip.mp4");
+    fs::write(&video, "fake").unwrap();
+
+    // 把 FFmpeg 进程的 nice 值写入输出文件
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &fake_ffmpeg,
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\nnice > \"$last\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(&fake_ffmpeg)
+        .low_priority(true)
+        .build()
+        .unwrap();
+    let output = processor
+        .convert_single_file(&video, temp_dir.path(), AudioFormat::Mp3)
+        .unwrap();
+
+    let baseline: i32 = String::from_utf8(std::process::Command::new("nice").output().unwrap().stdout)
+        .unwrap()
+        .trim()
+        .parse()
+        .unwrap();
+    let niceness: i32 = fs::read_to_string(output).unwrap().trim().parse().unwrap();
+    assert_eq!(niceness, (baseline + 10).min(19));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_format_defaults_from_config() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::{Config, ConfigFormat};
+
+    let config: Config = ConfigFormat::Toml
+        .parse("[formats.opus]\nbitrate = \"128k\"\n\n[formats.mp3]\nvbr_quality = 2\n")
+        .unwrap();
+    let defaults = config.format_defaults().unwrap();
+    let round_trip = ConfigFormat::Toml.parse(&ConfigFormat::Toml.serialize(&config).unwrap()).unwrap();
+    assert_eq!(round_trip.formats, config.formats);
+
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("clip.mp4");
+    fs::write(&video, "fake").unwrap();
+
+    // 把 FFmpeg 的参数写入输出文件
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &fake_ffmpeg,
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\necho \"$*\" > \"$last\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::new().with_ffmpeg_path(&fake_ffmpeg).with_format_defaults(defaults);
+    let output = processor.convert_single_file(&video, temp_dir.path(), AudioFormat::Opus).unwrap();
+    assert!(fs::read_to_string(output).unwrap().contains("-c:a libopus -b:a 128k"));
+    let output = processor.convert_single_file(&video, temp_dir.path(), AudioFormat::Mp3).unwrap();
+    assert!(fs::read_to_string(output).unwrap().contains("-q:a 2"));
+
+    // 转换选项中指定的码率优先
+    let options = ConversionOptions::new(AudioFormat::Opus).with_bitrate(64);
+    let output = processor.convert_single_file(&video, temp_dir.path(), options).unwrap();
+    assert!(fs::read_to_string(output).unwrap().contains("-b:a 64k"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_progress_listener_receives_ffmpeg_progress() {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use video2audio_rs::ProgressListener;
+
+    #[derive(Debug, Default)]
+    struct Recorder(Mutex<Vec<String>>);
+
+    impl ProgressListener for Recorder {
+        fn file_started(&self, source: &Path) {
+            self.0.lock().unwrap().push(format!("started {}", source.display()));
+        }
+
+        fn file_progress(&self, _source: &Path, position: Duration, total: Option<Duration>) {
+            self.0.lock().unwrap().push(format!("{}/{:?}", position.as_millis(), total.map(|t| t.as_millis())));
+        }
+
+        fn file_finished(&self, source: &Path) {
+            self.0.lock().unwrap().push(format!("finished {}", source.display()));
+        }
+
+        fn ffmpeg_command(&self, source: &Path, command_line: &str) {
+            self.0.lock().unwrap().push(format!("command {}: {command_line}", source.display()));
+        }
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("clip.mp4");
+    fs::write(&video, "fake").unwrap();
+
+    // FFmpeg 在标准输出报告进度，ffprobe 报告 2 秒的时长
+    let bin = common::fake_ffmpeg_bin_with(
+        temp_dir.path(),
+        "echo 2.0\n",
+        "for last; do :; done\n: > \"$last\"\n\
+         printf 'out_time_us=500000\\nout_time=00:00:00.500000\\nout_time_us=2000000\\nprogress=end\\n'\n",
+    );
+    let fake_ffmpeg = bin.join("ffmpeg");
+
+    let recorder = Arc::new(Recorder::default());
+    let processor = FileProcessor::new()
+        .with_ffmpeg_path(&fake_ffmpeg)
+        .with_progress(Some(recorder.clone()));
+    let output_dir = temp_dir.path().join("out");
+    fs::create_dir_all(&output_dir).unwrap();
+    let (success, failure) = processor.batch_convert(std::slice::from_ref(&video), &output_dir, AudioFormat::Mp3, |_, _| {});
+    assert_eq!((success, failure), (1, 0));
+
+    let events = recorder.0.lock().unwrap().clone();
+    assert_eq!(
+        events,
+        [
+            format!("started {}", video.display()),
+            "500/Some(2000)".to_string(),
+            "2000/Some(2000)".to_string(),
+            format!("finished {}", video.display()),
+        ]
+    );
+
+    // 显示命令时命令行交给监听器，由界面决定如何显示
+    recorder.0.lock().unwrap().clear();
+    let processor = processor.with_show_commands(true);
+    let (success, failure) = processor.batch_convert(std::slice::from_ref(&video), &output_dir, AudioFormat::Mp3, |_, _| {});
+    assert_eq!((success, failure), (1, 0));
+    let events = recorder.0.lock().unwrap().clone();
+    let command = events.iter().find(|event| event.starts_with("command ")).unwrap();
+    assert!(command.starts_with(&format!("command {}: {}", video.display(), fake_ffmpeg.display())), "{command}");
+    assert!(command.contains("-progress pipe:1"), "{command}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_unavailable_hwaccel_falls_back_to_software() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let log = temp_dir.path().join("calls.log");
+
+    // 记录每次调用，带 -hwaccel 参数时模拟加速器不可用
+    let fake_ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &fake_ffmpeg,
+        format!(
+            "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\necho \"$*\" >> '{}'\n\
+             case \"$*\" in *-hwaccel*) exit 1;; esac\nfor last; do :; done\n: > \"$last\"\n",
+            log.display()
+        ),
+    )
+    .unwrap();
+    fs::set_permissions(&fake_ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(&fake_ffmpeg)
+        .hwaccel(HwAccel::Vaapi)
+        .build()
+        .unwrap();
+    let output_dir = temp_dir.path().join("out");
+    for name in ["a.mp4", "b.mp4"] {
+        let video = temp_dir.path().join(name);
+        fs::write(&video, "fake").unwrap();
+        assert!(processor.convert_single_file(&video, &output_dir, AudioFormat::Mp3).is_ok());
+    }
+
+    // 第一个文件先尝试硬件加速再回退，第二个文件直接使用软件解码
+    let calls = fs::read_to_string(&log).unwrap();
+    let hwaccel_calls: Vec<bool> = calls.lines().map(|line| line.contains("-hwaccel vaapi")).collect();
+    assert_eq!(hwaccel_calls, [true, false, false]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_duration_filter_uses_ffprobe() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+    use video2audio_rs::FileFilter;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    fs::create_dir(&source_dir).unwrap();
+    // 测试文件的内容就是 ffprobe 报告的时长（秒）
+    for (name, seconds) in [("accident.mp4", "2.0"), ("clip.mp4", "42.5"), ("broken.mp4", "N/A")] {
+        fs::write(source_dir.join(name), seconds).unwrap();
+    }
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let ffprobe = bin_dir.join("ffprobe");
+    fs::write(&ffprobe, "#!/bin/sh\nfor last; do :; done\ncat \"$last\"\n").unwrap();
+    fs::set_permissions(&ffprobe, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(bin_dir.join("ffmpeg"))
+        .file_filter(FileFilter {
+            min_duration: Some(Duration::from_secs(10)),
+            ..Default::default()
+        })
+        .build()
+        .unwrap();
+
+    let files = processor.find_video_files(&source_dir).unwrap();
+    assert_eq!(files, [source_dir.join("clip.mp4")]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_convert_remote_url() {
+    let temp_dir = TempDir::new().unwrap();
+    let url = "https://example.com/vod/live%20show.mp4?token=abc";
+
+    // 远程地址必须原样跟在 -i 之后
+    let fake_ffmpeg = common::fake_ffmpeg_bin_with(
+        temp_dir.path(),
+        "exit 1\n",
+        &format!(
+            "while [ \"$1\" != -i ]; do shift; done\n[ \"$2\" = '{url}' ] || exit 1\n\
+             for last; do :; done\n: > \"$last\"\n"
+        ),
+    )
+    .join("ffmpeg");
+
+    let processor = FileProcessor::builder().ffmpeg_path(&fake_ffmpeg).build().unwrap();
+    let output = processor
+        .convert_single_file(Path::new(url), temp_dir.path(), AudioFormat::Mp3)
+        .unwrap();
+
+    assert_eq!(output, temp_dir.path().join("live show.mp3"));
+    assert!(output.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_all_audio_tracks_outputs_one_file_per_track() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("movie.mkv"), "").unwrap();
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let write_script = |name: &str, script: &str| {
+        let path = bin_dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    write_script("ffprobe", "#!/bin/sh\nprintf '1,eng\\n2,jpn\\n'\n");
+    // 只接受 -map 0:a:N 选择的音轨，输出文件内容记录音轨序号
+    write_script(
+        "ffmpeg",
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\n\
+         while [ \"$1\" != -map ]; do shift; done\ntrack=$2\n\
+         for last; do :; done\necho \"$track\" > \"$last\"\n",
+    );
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(bin_dir.join("ffmpeg"))
+        .all_audio_tracks(true)
+        .build()
+        .unwrap();
+    let output_dir = temp_dir.path().join("audio");
+    let output = processor
+        .convert_single_file(&source_dir.join("movie.mkv"), &output_dir, AudioFormat::Mp3)
+        .unwrap();
+
+    assert_eq!(output, output_dir.join("movie.track1.eng.mp3"));
+    assert_eq!(fs::read_to_string(&output).unwrap().trim(), "0:a:0");
+    assert_eq!(fs::read_to_string(output_dir.join("movie.track2.jpn.mp3")).unwrap().trim(), "0:a:1");
+    assert!(!output_dir.join("movie.mp3").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_audio_lang_selects_track_and_fails_when_missing() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::AudioTrackSelection;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    fs::create_dir(&source_dir).unwrap();
+    // 测试文件的内容就是 ffprobe 报告的音轨列表
+    fs::write(source_dir.join("anime.mkv"), "1,eng\n2,jpn\n").unwrap();
+    fs::write(source_dir.join("movie.mkv"), "1,eng\n").unwrap();
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let write_script = |name: &str, script: &str| {
+        let path = bin_dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    write_script("ffprobe", "#!/bin/sh\nfor last; do :; done\ncat \"$last\"\n");
+    write_script(
+        "ffmpeg",
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\n\
+         while [ \"$1\" != -map ]; do shift; done\ntrack=$2\n\
+         for last; do :; done\necho \"$track\" > \"$last\"\n",
+    );
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(bin_dir.join("ffmpeg"))
+        .audio_track(AudioTrackSelection::Language("jpn".to_string()))
+        .build()
+        .unwrap();
+    let output_dir = temp_dir.path().join("audio");
+    let files = [source_dir.join("anime.mkv"), source_dir.join("movie.mkv")];
+    let mut failures = Vec::new();
+    let (success, failed) = processor.batch_convert_with_results(
+        &files,
+        &output_dir,
+        AudioFormat::Opus,
+        &CancellationToken::new(),
+        |source, result, _, _| {
+            if let Err(e) = result {
+                failures.push((source.to_path_buf(), e.to_string()));
+            }
+        },
+    );
+
+    assert_eq!((success, failed), (1, 1));
+    assert_eq!(fs::read_to_string(output_dir.join("anime.opus")).unwrap().trim(), "0:a:1");
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].0, files[1]);
+    assert!(failures[0].1.contains("jpn"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_split_chapters_outputs_one_file_per_chapter() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    fs::create_dir(&source_dir).unwrap();
+    // 测试文件的内容就是 ffprobe 报告的章节列表
+    fs::write(source_dir.join("concert.mkv"), "0.000000,60.000000,Intro\n60.000000,95.500000,Encore/Live\n").unwrap();
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let write_script = |name: &str, script: &str| {
+        let path = bin_dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    write_script("ffprobe", "#!/bin/sh\nfor last; do :; done\ncat \"$last\"\n");
+    // 输出文件内容记录全部参数
+    write_script(
+        "ffmpeg",
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\necho \"$@\" > \"$last\"\n",
+    );
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(bin_dir.join("ffmpeg"))
+        .split_chapters(true)
+        .build()
+        .unwrap();
+    let output_dir = temp_dir.path().join("audio");
+    let output = processor
+        .convert_single_file(&source_dir.join("concert.mkv"), &output_dir, AudioFormat::Mp3)
+        .unwrap();
+
+    assert_eq!(output, output_dir.join("concert").join("01 - Intro.mp3"));
+    let first = fs::read_to_string(&output).unwrap();
+    assert!(first.contains("-t 60.000") && first.contains("title=Intro") && first.contains("track=1/2"));
+    let second = fs::read_to_string(output_dir.join("concert").join("02 - Encore_Live.mp3")).unwrap();
+    assert!(second.contains("-ss 60.000 -i") && second.contains("-t 35.500") && second.contains("title=Encore/Live"));
+    assert!(!output_dir.join("concert.mp3").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_merge_files_inserts_chapter_markers() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("lectures");
+    fs::create_dir(&source_dir).unwrap();
+    // 测试文件的内容就是 ffprobe 报告的时长（秒）
+    let files = [source_dir.join("01 intro.mp4"), source_dir.join("02 basics.mp4")];
+    fs::write(&files[0], "90.5").unwrap();
+    fs::write(&files[1], "60").unwrap();
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let write_script = |name: &str, script: &str| {
+        let path = bin_dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    write_script("ffprobe", "#!/bin/sh\nfor last; do :; done\ncat \"$last\"\n");
+    // 把章节元数据文件的内容写入输出文件
+    write_script(
+        "ffmpeg",
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\n\
+         while [ \"$1\" != ffmetadata ]; do shift; done\ncat \"${3#file:}\" > \"$last\"\n",
+    );
+
+    let processor = FileProcessor::builder().ffmpeg_path(bin_dir.join("ffmpeg")).build().unwrap();
+    let output = temp_dir.path().join("course.mp3");
+    processor
+        .merge_files(&files, &output, AudioFormat::Mp3, &CancellationToken::new())
+        .unwrap();
+
+    let metadata = fs::read_to_string(&output).unwrap();
+    assert!(metadata.contains("START=0\nEND=90500\ntitle=01 intro"));
+    assert!(metadata.contains("START=90500\nEND=150500\ntitle=02 basics"));
+    // 章节元数据文件在合并结束后被删除
+    assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_two_pass_normalize_uses_measured_loudness() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::{LoudnessTarget, Normalization};
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("lecture.mp4");
+    fs::write(&source, "fake video").unwrap();
+
+    // 分析时（输出为 -）打印 loudnorm 的测量结果，转换时把参数写入输出文件
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &ffmpeg,
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\n\
+         if [ \"$last\" = - ]; then\n\
+         printf '{\\n\"input_i\" : \"-27.61\",\\n\"input_tp\" : \"-4.47\",\\n\"input_lra\" : \"18.06\",\\n\
+         \"input_thresh\" : \"-39.20\",\\n\"target_offset\" : \"0.58\"\\n}\\n' >&2\n\
+         else echo \"$*\" > \"$last\"; fi\n",
+    )
+    .unwrap();
+    fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&ffmpeg).build().unwrap();
+    let target = LoudnessTarget { integrated: -19.0, ..Default::default() };
+    let options = ConversionOptions::new(AudioFormat::Mp3)
+        .with_normalization(Normalization::new(target).with_two_pass(true));
+    let output = processor.convert_single_file(&source, temp_dir.path(), options).unwrap();
+
+    let args = fs::read_to_string(output).unwrap();
+    assert!(args.contains("loudnorm=I=-19:TP=-1.5:LRA=11:measured_I=-27.61:measured_TP=-4.47"));
+    assert!(args.contains("linear=true"));
+    assert!(args.contains("-ar 48000"));
+}
+
+// 启用 lofty 特性时标签由 lofty 直接写入，不经过 FFmpeg
+#[cfg(all(unix, not(feature = "lofty")))]
+#[test]
+fn test_replay_gain_tags_written_by_stream_copy() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("episode.mp4");
+    fs::write(&source, "fake video").unwrap();
+
+    // 分析输出文件时打印测量结果，转换和写入标签时把参数写入输出文件
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &ffmpeg,
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\n\
+         if [ \"$last\" = - ]; then\n\
+         printf '{\\n\"input_i\" : \"-23.00\",\\n\"input_tp\" : \"-6.02\",\\n\"input_lra\" : \"5.00\",\\n\
+         \"input_thresh\" : \"-33.00\",\\n\"target_offset\" : \"0.00\"\\n}\\n' >&2\n\
+         else echo \"$*\" > \"$last\"; fi\n",
+    )
+    .unwrap();
+    fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&ffmpeg).replay_gain(true).build().unwrap();
+    // M4B 需要允许写入自定义标签
+    let output = processor.convert_single_file(&source, temp_dir.path(), AudioFormat::M4b).unwrap();
+
+    let args = fs::read_to_string(output).unwrap();
+    assert!(args.contains("-map 0 -c copy"));
+    assert!(args.contains("-metadata REPLAYGAIN_TRACK_GAIN=5.00 dB"));
+    assert!(args.contains("-metadata REPLAYGAIN_TRACK_PEAK=0.500035"));
+    assert!(args.contains("-movflags use_metadata_tags"));
+    assert!(!temp_dir.path().join("episode.m4b.part").exists());
+}
+
+#[test]
+fn test_custom_audio_filter_forces_aac_reencode() {
+    use clap::Parser;
+    use video2audio_rs::{Args, Config, RuntimeConfig};
+
+    let args = Args::parse_from(["video2audio-rs", "--af", "highpass=f=80,dynaudnorm", "--trim-silence"]);
+    let runtime_config = RuntimeConfig::from_args_and_config(args, Config::default());
+    let options = runtime_config.conversion_options(ConversionOptions::new(AudioFormat::AacCopy));
+
+    assert_eq!(options.encoder.as_deref(), Some("aac"));
+    let codec_args = options.codec_args();
+    let filters = &codec_args[codec_args.iter().position(|arg| arg == "-af").unwrap() + 1];
+    assert!(filters.starts_with("silenceremove="));
+    assert!(filters.ends_with(",highpass=f=80,dynaudnorm"));
+
+    assert!(Args::try_parse_from(["video2audio-rs", "--af", ""]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_attached_cover_reuses_embedded_artwork() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::CoverArt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    fs::create_dir(&source_dir).unwrap();
+    // 测试文件的内容就是 ffprobe 报告的视频流列表
+    fs::write(source_dir.join("clip.mp4"), "0,0\n2,1\n").unwrap();
+    fs::write(source_dir.join("plain.mp4"), "0,0\n").unwrap();
+
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    let write_script = |name: &str, script: &str| {
+        let path = bin_dir.join(name);
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    };
+    write_script("ffprobe", "#!/bin/sh\nfor last; do :; done\ncat \"$last\"\n");
+    write_script(
+        "ffmpeg",
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\necho \"$*\" > \"$last\"\n",
+    );
+
+    let processor = FileProcessor::builder().ffmpeg_path(bin_dir.join("ffmpeg")).build().unwrap();
+    let output_dir = temp_dir.path().join("audio");
+    let options = ConversionOptions::new(AudioFormat::Mp3).with_cover_art(CoverArt::Attached);
+    for name in ["clip.mp4", "plain.mp4"] {
+        processor.convert_single_file(&source_dir.join(name), &output_dir, options.clone()).unwrap();
+    }
+
+    let with_cover = fs::read_to_string(output_dir.join("clip.mp3")).unwrap();
+    assert!(with_cover.contains("-map 0:2 -c:v copy -disposition:v:0 attached_pic"));
+    let without_cover = fs::read_to_string(output_dir.join("plain.mp3")).unwrap();
+    assert!(without_cover.contains("-vn") && !without_cover.contains("attached_pic"));
+}
+
+#[test]
+fn test_repeated_tag_options() {
+    use clap::Parser;
+    use video2audio_rs::{Args, Config, RuntimeConfig};
+
+    let args = Args::parse_from(["video2audio-rs", "--tag", "artist=Foo", "--tag", "album=Live at Bar"]);
+    let runtime_config = RuntimeConfig::from_args_and_config(args, Config::default());
+    let codec_args = runtime_config.conversion_options(ConversionOptions::new(AudioFormat::Mp3)).codec_args();
+
+    assert!(codec_args.windows(2).any(|pair| pair == ["-metadata", "artist=Foo"]));
+    assert!(codec_args.windows(2).any(|pair| pair == ["-metadata", "album=Live at Bar"]));
+    assert!(Args::try_parse_from(["video2audio-rs", "--tag", "artist"]).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tag_pattern_tags_matching_files() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::TagPattern;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    fs::create_dir(&source_dir).unwrap();
+    fs::write(source_dir.join("Foo - Bar.mp4"), "fake video").unwrap();
+    fs::write(source_dir.join("untitled.mp4"), "fake video").unwrap();
+
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(&ffmpeg, "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\necho \"$*\" > \"$last\"\n").unwrap();
+    fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(&ffmpeg)
+        .tag_pattern(TagPattern::parse("{artist} - {title}").unwrap())
+        .build()
+        .unwrap();
+    let output_dir = temp_dir.path().join("audio");
+    let options = ConversionOptions::new(AudioFormat::Mp3).with_tag("album", "Live");
+    for name in ["Foo - Bar.mp4", "untitled.mp4"] {
+        processor.convert_single_file(&source_dir.join(name), &output_dir, options.clone()).unwrap();
+    }
+
+    let matched = fs::read_to_string(output_dir.join("Foo - Bar.mp3")).unwrap();
+    assert!(matched.contains("-metadata artist=Foo -metadata title=Bar -metadata album=Live"));
+    let unmatched = fs::read_to_string(output_dir.join("untitled.mp3")).unwrap();
+    assert!(unmatched.contains("-metadata album=Live") && !unmatched.contains("artist="));
+}
+
+// 启用 lofty 特性时标签由 lofty 直接写入，不经过 FFmpeg
+#[cfg(all(unix, not(feature = "lofty")))]
+#[test]
+fn test_write_tags_to_existing_output() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::TagUpdate;
+
+    let temp_dir = TempDir::new().unwrap();
+    let audio = temp_dir.path().join("book.m4b");
+    fs::write(&audio, "fake audio").unwrap();
+    let cover = temp_dir.path().join("cover.jpg");
+    fs::write(&cover, "fake image").unwrap();
+
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(&ffmpeg, "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\necho \"$*\" > \"$last\"\n").unwrap();
+    fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&ffmpeg).build().unwrap();
+    let update = TagUpdate::new().with_tag("track", "2/10").with_cover(&cover);
+    processor.write_tags(&audio, &update, &CancellationToken::new()).unwrap();
+
+    let args = fs::read_to_string(&audio).unwrap();
+    assert!(args.contains("-map 0:a -map 1:v:0 -c copy -disposition:v:0 attached_pic"));
+    assert!(args.contains("-metadata track=2/10"));
+    assert!(!temp_dir.path().join("book.m4b.part").exists());
+
+    let unknown = temp_dir.path().join("notes.txt");
+    assert!(matches!(
+        processor.write_tags(&unknown, &update, &CancellationToken::new()),
+        Err(VideoToAudioError::UnsupportedFormat(_))
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_verify_reports_truncated_and_corrupt_outputs() {
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+    use video2audio_rs::verify::VerifyIssue;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source_dir = temp_dir.path().join("videos");
+    let output_dir = temp_dir.path().join("audio");
+    fs::create_dir_all(source_dir.join("season1")).unwrap();
+    fs::create_dir_all(output_dir.join("season1")).unwrap();
+    // 测试文件的内容就是 ffprobe 报告的时长（秒）
+    for (name, source, output) in [("ok", "600", "600.4"), ("short", "600", "312"), ("corrupt", "60", "60")] {
+        fs::write(source_dir.join(format!("season1/{name}.mp4")), source).unwrap();
+        fs::write(output_dir.join(format!("season1/{name}.mp3")), output).unwrap();
+    }
+    fs::write(source_dir.join("pending.mp4"), "30").unwrap();
+
+    // 解码名为 corrupt 的文件时报告错误
+    let bin_dir = temp_dir.path().join("bin");
+    fs::create_dir(&bin_dir).unwrap();
+    fs::write(bin_dir.join("ffprobe"), "#!/bin/sh\nfor last; do :; done\ncat \"$last\"\n").unwrap();
+    fs::write(
+        bin_dir.join("ffmpeg"),
+        "#!/bin/sh\ncase \"$*\" in *corrupt*) echo 'Invalid data found when processing input' >&2 ;; esac\n",
+    )
+    .unwrap();
+    for tool in ["ffprobe", "ffmpeg"] {
+        fs::set_permissions(bin_dir.join(tool), fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    let processor = FileProcessor::builder()
+        .ffmpeg_path(bin_dir.join("ffmpeg"))
+        .build()
+        .unwrap()
+        .with_preserved_structure(Some(source_dir.clone()));
+    let report = processor
+        .verify_outputs(&source_dir, &output_dir, AudioFormat::Mp3, Duration::from_secs(1), &CancellationToken::new())
+        .unwrap();
+
+    assert_eq!(report.checked, 3);
+    assert_eq!(report.missing, [source_dir.join("pending.mp4")]);
+    assert_eq!(
+        report.issues,
+        [
+            (
+                output_dir.join("season1/corrupt.mp3"),
+                VerifyIssue::DecodeFailed("Invalid data found when processing input".to_string())
+            ),
+            (
+                output_dir.join("season1/short.mp3"),
+                VerifyIssue::DurationMismatch { source: Duration::from_secs(600), output: Duration::from_secs(312) }
+            ),
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_batch_report_records_each_file() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::report::{write_report, ReportEntry};
+
+    let temp_dir = TempDir::new().unwrap();
+    let files = [temp_dir.path().join("good.mp4"), temp_dir.path().join("bad.mp4")];
+    for file in &files {
+        fs::write(file, "fake video").unwrap();
+    }
+
+    // 转换名为 bad 的文件时失败
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(
+        &ffmpeg,
+        "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\ncase \"$*\" in *bad*) echo 'moov atom not found' >&2; exit 1 ;; esac\n\
+         for last; do :; done\necho converted > \"$last\"\n",
+    )
+    .unwrap();
+    fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&ffmpeg).build().unwrap();
+    let output_dir = temp_dir.path().join("audio");
+    let mut entries = Vec::new();
+    processor.batch_convert_timed(
+        &files,
+        &output_dir,
+        AudioFormat::Mp3,
+        &CancellationToken::new(),
+        |source, result, elapsed, _, _| entries.push(ReportEntry::new(&ffmpeg, source, result, AudioFormat::Mp3, elapsed)),
+    );
+    entries.sort_by(|a, b| a.source.cmp(&b.source));
+
+    let report = temp_dir.path().join("report.json");
+    write_report(&report, &entries).unwrap();
+    let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&report).unwrap()).unwrap();
+    assert_eq!(json[0]["output"], serde_json::Value::Null);
+    assert!(json[0]["error"].as_str().unwrap().contains("moov atom not found"));
+    assert_eq!(json[1]["output"], output_dir.join("good.mp3").to_str().unwrap());
+    assert_eq!(json[1]["format"], "mp3");
+    assert_eq!(json[1]["input_bytes"], 10);
+    assert_eq!(json[1]["output_bytes"], 10);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_history_recognizes_moved_and_renamed_files() {
+    use std::os::unix::fs::PermissionsExt;
+    use video2audio_rs::history::{source_fingerprint, History, HistoryRecord};
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("lecture.mp4");
+    fs::write(&source, "fake video").unwrap();
+    let ffmpeg = temp_dir.path().join("ffmpeg");
+    fs::write(&ffmpeg, "#!/bin/sh\n[ \"$1\" = -version ] && exit 0\nfor last; do :; done\necho \"$*\" > \"$last\"\n").unwrap();
+    fs::set_permissions(&ffmpeg, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let processor = FileProcessor::builder().ffmpeg_path(&ffmpeg).build().unwrap();
+    let history_path = temp_dir.path().join("config/history.jsonl");
+    let mut history = History::load(&history_path).unwrap();
+    processor.batch_convert_timed(
+        std::slice::from_ref(&source),
+        &temp_dir.path().join("audio"),
+        AudioFormat::Mp3,
+        &CancellationToken::new(),
+        |source, result, elapsed, _, _| {
+            let record = HistoryRecord::new(&ffmpeg, source, result.as_ref().unwrap(), AudioFormat::Mp3, elapsed);
+            history.record(record.unwrap()).unwrap();
+        },
+    );
+
+    // 移走输出文件并给源文件改名后，仍能从历史中认出这个文件
+    fs::remove_dir_all(temp_dir.path().join("audio")).unwrap();
+    let renamed = temp_dir.path().join("renamed.mkv");
+    fs::rename(&source, &renamed).unwrap();
+
+    let history = History::load(&history_path).unwrap();
+    let hash = source_fingerprint(&renamed).unwrap();
+    let record = history.find(&hash, AudioFormat::Mp3).unwrap();
+    assert_eq!(record.source, source);
+    assert_eq!(record.input_bytes, 10);
+    assert!(history.find(&hash, AudioFormat::Opus).is_none());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hook_command_receives_file_paths() {
+    use video2audio_rs::hooks::{parse_hook, HookContext};
+
+    let temp_dir = TempDir::new().unwrap();
+    let log = temp_dir.path().join("hook.log");
+    let hook = parse_hook(&format!("sh -c 'echo \"$0|$1\" >> \"$2\"' {{input}} {{output}} {}", log.display())).unwrap();
+
+    let context = HookContext {
+        input: Path::new("videos/a b.mp4"),
+        output: Some(Path::new("audio/a b.mp3")),
+        format: AudioFormat::Mp3,
+        error: None,
+    };
+    assert!(hook.run(&context).unwrap().success());
+    assert_eq!(fs::read_to_string(&log).unwrap(), "videos/a b.mp4|audio/a b.mp3\n");
+}
+
+#[test]
+fn test_config_settings_by_key() {
+    use video2audio_rs::Config;
+
+    let mut config = Config::default();
+    let keys = Config::setting_keys();
+    assert!(keys.contains(&"default_jobs".to_string()));
+    assert!(keys.contains(&"loudness_target.integrated".to_string()));
+    assert!(!keys.contains(&"profiles".to_string()));
+
+    config.set_setting("default_jobs", "4").unwrap();
+    config.set_setting("default_format", "opus").unwrap();
+    config.set_setting("loudness_target.integrated", "-14").unwrap();
+    assert_eq!(config.default_jobs, Some(4));
+    assert_eq!(config.get_default_format().unwrap(), AudioFormat::Opus);
+    assert_eq!(config.loudness_target.integrated, -14.0);
+
+    // 无效的值和未知的设置不修改配置
+    assert!(config.set_setting("default_format", "wav").is_err());
+    assert!(config.set_setting("nice", "maybe").is_err());
+    assert!(config.set_setting("no_such_key", "1").is_err());
+    assert_eq!(config.get_default_format().unwrap(), AudioFormat::Opus);
+
+    config.set_setting("default_jobs", "null").unwrap();
+    assert_eq!(config.default_jobs, None);
+    config.reset_setting("loudness_target.integrated").unwrap();
+    assert_eq!(config.loudness_target.integrated, -16.0);
+
+    assert_eq!(Config::setting_env_var("loudness_target.integrated"), "VIDEO2AUDIO_LOUDNESS_TARGET_INTEGRATED");
+}
+
+#[test]
+fn test_config_toml_round_trip_and_migration() {
+    use std::path::PathBuf;
+    use video2audio_rs::{Config, ConfigFormat, WatchRule};
+
+    let temp_dir = TempDir::new().unwrap();
+    let mut config = Config { default_jobs: Some(4), normalize: true, ..Config::default() };
+    config.watch_rules.push(WatchRule {
+        source_dir: PathBuf::from("/videos/inbox"),
+        format: "opus".to_string(),
+        output_dir: None,
+    });
+
+    // TOML 配置按扩展名识别，字段与 JSON 配置相同
+    let toml_path = temp_dir.path().join("settings.toml");
+    config.save(Some(&toml_path)).unwrap();
+    let content = fs::read_to_string(&toml_path).unwrap();
+    assert!(content.contains("default_jobs = 4"));
+    assert!(content.contains("[[watch_rules]]"));
+    let loaded = Config::load(Some(&toml_path)).unwrap();
+    assert_eq!(ConfigFormat::Toml.serialize(&loaded).unwrap(), content);
+
+    // 迁移后原 JSON 文件保留为 .json.bak
+    let json_path = temp_dir.path().join("config.json");
+    config.save(Some(&json_path)).unwrap();
+    let migrated = Config::migrate_to_toml(&json_path).unwrap();
+    assert_eq!(migrated, temp_dir.path().join("config.toml"));
+    assert!(!json_path.exists());
+    assert!(temp_dir.path().join("config.json.bak").exists());
+    assert_eq!(fs::read_to_string(&migrated).unwrap(), content);
+    assert!(Config::migrate_to_toml(&temp_dir.path().join("config.toml")).is_err());
+
+    // 读写失败时错误信息包含出错的路径
+    let missing = temp_dir.path().join("missing.json");
+    let error = Config::migrate_to_toml(&missing).unwrap_err();
+    assert!(error.to_string().contains(&missing.display().to_string()), "{error}");
+}
+
+#[test]
+fn test_profile_fills_in_unset_options() {
+    use clap::Parser;
+    use std::path::PathBuf;
+    use video2audio_rs::{Args, Config, Profile, RuntimeConfig};
+
+    let mut config = Config::default();
+    config.profiles.insert(
+        "podcast".to_string(),
+        Profile {
+            format: Some("opus".to_string()),
+            bitrate: Some(48),
+            audio_filter: Some("highpass=f=80".to_string()),
+            output_dir: Some(PathBuf::from("/audio/podcasts")),
+            ..Profile::default()
+        },
+    );
+    assert!(config.profile("music").is_err());
+
+    let args = Args::parse_from(["video2audio-rs", "--profile", "podcast", "-o", "/tmp/out"]);
+    let runtime_config = RuntimeConfig::from_args_and_config(args, config);
+    assert_eq!(runtime_config.format, Some(AudioFormat::Opus));
+    // 命令行参数优先于配置方案
+    assert_eq!(runtime_config.output_dir, Some(PathBuf::from("/tmp/out")));
+
+    let codec_args = runtime_config.conversion_options(ConversionOptions::new(AudioFormat::Opus)).codec_args().join(" ");
+    assert!(codec_args.contains("-c:a libopus -b:a 48k"));
+    assert!(codec_args.contains("-af highpass=f=80"));
+}
+
+/// 直接复制源文件的测试后端
+#[derive(Debug)]
+struct CopyBackend;
+
+impl ConversionBackend for CopyBackend {
+    fn name(&self) -> &'static str {
+        "copy"
+    }
+
+    fn check_available(&self) -> video2audio_rs::Result<()> {
+        Ok(())
+    }
+
+    fn convert(
+        &self,
+        source_file: &Path,
+        partial_path: &Path,
+        _options: &ConversionOptions,
+        _cancel_token: &CancellationToken,
+    ) -> video2audio_rs::Result<()> {
+        fs::copy(source_file, partial_path)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn test_custom_backend_replaces_ffmpeg() {
+    let temp_dir = TempDir::new().unwrap();
+    let video = temp_dir.path().join("clip.mp4");
+    fs::write(&video, "audio data").unwrap();
+
+    // 即使 FFmpeg 路径无效，也应该使用自定义后端完成转换
+    let processor = FileProcessor::builder()
+        .ffmpeg_path("/nonexistent/ffmpeg")
+        .backend(CopyBackend)
+        .build()
+        .unwrap();
+    assert!(processor.check_encoder(AudioFormat::Opus).unwrap().encoder.is_none());
+
+    let output_dir = temp_dir.path().join("out");
+    let output = processor.convert_single_file(&video, &output_dir, AudioFormat::Mp3).unwrap();
+    assert_eq!(output, output_dir.join("clip.mp3"));
+    assert_eq!(fs::read_to_string(&output).unwrap(), "audio data");
+    assert!(!output_dir.join("clip.mp3.part").exists());
+}
+
+#[test]
+fn test_dir_config_overrides_subtree() {
+    use video2audio_rs::dir_config::{DirConfigResolver, DIR_CONFIG_FILE_NAME};
+
+    let temp_dir = TempDir::new().unwrap();
+    let talks = temp_dir.path().join("talks");
+    fs::create_dir_all(&talks).unwrap();
+    fs::write(talks.join(DIR_CONFIG_FILE_NAME), "format = \"opus\"\noutput_dir = \"speech\"\n").unwrap();
+    let talk = talks.join("lecture.mp4");
+    let song = temp_dir.path().join("song.mp4");
+    fs::write(&talk, "talk").unwrap();
+    fs::write(&song, "song").unwrap();
+
+    let processor = FileProcessor::builder()
+        .backend(CopyBackend)
+        .dir_configs(DirConfigResolver::new(Some(temp_dir.path().to_path_buf())))
+        .build()
+        .unwrap();
+    let output_dir = temp_dir.path().join("out");
+    let mut outputs = Vec::new();
+    let (success, failure) = processor.batch_convert_with_results(
+        &[talk, song],
+        &output_dir,
+        AudioFormat::Mp3,
+        &CancellationToken::new(),
+        |_, result, _, _| outputs.push(result.as_ref().unwrap().clone()),
+    );
+    assert_eq!((success, failure), (2, 0));
+    outputs.sort();
+    assert_eq!(outputs, [output_dir.join("song.mp3"), talks.join("speech").join("lecture.opus")]);
+}
+
 #[test]
 fn test_output_directory_structure() {
     let temp_dir = TempDir::new().unwrap();
@@ -303,7 +2033,7 @@ fn test_comprehensive_workflow() {
     ui.show_progress(1, 1);
     
     // 6. 显示完成信息
-    ui.show_completion(files.len(), &output_dir);
+    ui.show_completion(&video2audio_rs::BatchSummary::new(files.len()), &output_dir);
     
     // 整个流程应该没有 panic
 }
@@ -325,3 +2055,266 @@ fn test_error_display_formatting() {
         ui.show_error(&error); // 不应该 panic
     }
 }
+
+#[test]
+fn test_json_progress_silences_text_output() {
+    use clap::Parser;
+    use video2audio_rs::{Args, Config, ProgressFormat, RuntimeConfig};
+
+    let config = Config { verbose: true, ..Config::default() };
+    let args = Args::parse_from(["video2audio-rs", "--progress", "json"]);
+    let runtime_config = RuntimeConfig::from_args_and_config(args, config.clone());
+    assert_eq!(runtime_config.progress, ProgressFormat::Json);
+    assert!(runtime_config.quiet);
+    assert!(!runtime_config.verbose);
+
+    let runtime_config = RuntimeConfig::from_args_and_config(Args::parse_from(["video2audio-rs"]), config);
+    assert_eq!(runtime_config.progress, ProgressFormat::Bar);
+    assert!(!runtime_config.quiet);
+
+    assert!(Args::try_parse_from(["video2audio-rs", "--progress", "json", "--tui"]).is_err());
+}
+
+#[test]
+fn test_scan_reports_to_caller_subscriber() {
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("a.mp4"), "fake").unwrap();
+    fs::create_dir(temp_dir.path().join("audio_exports")).unwrap();
+
+    let buffer = Buffer::default();
+    let writer = buffer.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .with_ansi(false)
+        .with_writer(move || writer.clone())
+        .finish();
+    let files = tracing::subscriber::with_default(subscriber, || {
+        FileProcessor::new().find_video_files(temp_dir.path()).unwrap()
+    });
+    assert_eq!(files.len(), 1);
+
+    let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    assert!(output.contains("scan{dir="));
+    assert!(output.contains("files=1"));
+    assert!(output.contains("audio_exports"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_clean_keeps_partials_while_output_is_locked() {
+    use video2audio_rs::lock::OutputLock;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("videos");
+    let output = temp_dir.path().join("audio");
+    fs::create_dir_all(&source).unwrap();
+    fs::create_dir_all(&output).unwrap();
+    fs::write(source.join("a.mp4"), "video").unwrap();
+    let partial = output.join("a.mp3.part");
+    fs::write(&partial, "half").unwrap();
+
+    let clean = || {
+        assert_cmd::Command::cargo_bin("video2audio-rs")
+            .unwrap()
+            .env("HOME", temp_dir.path())
+            .args(["--lang", "en", "clean", "--format", "mp3", "--source"])
+            .arg(&source)
+            .arg("--output")
+            .arg(&output)
+            .output()
+            .unwrap()
+    };
+
+    // 另一个实例正在转换时不删除它写了一半的临时文件
+    let lock = OutputLock::try_acquire(&output).unwrap().unwrap();
+    let result = clean();
+    assert!(!result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("--wait-for-lock"));
+    assert!(partial.exists());
+
+    drop(lock);
+    let result = clean();
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+    assert!(!partial.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_subcommands_print_no_chinese_in_english() {
+    use std::ffi::OsString;
+
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("videos");
+    let output = temp_dir.path().join("audio");
+    fs::create_dir_all(&source).unwrap();
+    fs::create_dir_all(&output).unwrap();
+    fs::write(source.join("a.mp4"), "video").unwrap();
+    fs::write(output.join("orphan.mp3"), "audio").unwrap();
+    fs::write(output.join("a.mp3.part"), "half").unwrap();
+    let audio = temp_dir.path().join("song.mp3");
+    fs::write(&audio, "audio").unwrap();
+
+    let clean = |extra: &[&str]| -> Vec<OsString> {
+        let mut args: Vec<OsString> = ["clean", "--format", "mp3"].iter().chain(extra).map(OsString::from).collect();
+        args.extend(["--source".into(), source.clone().into(), "--output".into(), output.clone().into()]);
+        args
+    };
+    // 每项为 (参数, 是否成功)，`tag` 没有要写入的内容，用来检查错误信息
+    let runs: Vec<(Vec<OsString>, bool)> = vec![
+        (clean(&["--dry-run"]), true),
+        (clean(&[]), true),
+        (vec!["stats".into()], true),
+        (vec!["history".into()], true),
+        (vec!["config".into(), "show".into()], true),
+        (vec!["tag".into(), audio.clone().into()], false),
+        (vec!["explain".into()], true),
+    ];
+    for (args, succeeds) in runs {
+        let result = assert_cmd::Command::cargo_bin("video2audio-rs")
+            .unwrap()
+            .env("HOME", temp_dir.path())
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("config"))
+            .env("XDG_DATA_HOME", temp_dir.path().join("data"))
+            .args(["--lang", "en"])
+            .args(&args)
+            .output()
+            .unwrap();
+        let text = format!("{}{}", String::from_utf8_lossy(&result.stdout), String::from_utf8_lossy(&result.stderr));
+        assert_eq!(result.status.success(), succeeds, "{args:?}: {text}");
+        assert!(!text.is_empty(), "{args:?}");
+        // 英文界面下不应出现任何中文
+        assert!(!text.chars().any(|c| ('\u{4e00}'..='\u{9fff}').contains(&c)), "{args:?}: {text}");
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_tag_waits_for_output_dir_lock() {
+    use video2audio_rs::lock::OutputLock;
+
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("audio");
+    fs::create_dir_all(output.join("album")).unwrap();
+    let song = output.join("album/song.mp3");
+    fs::write(&song, "audio").unwrap();
+
+    // 文件所在的输出目录正被另一个实例处理
+    let _lock = OutputLock::try_acquire(&output).unwrap().unwrap();
+    let result = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("HOME", temp_dir.path())
+        .args(["--lang", "en", "--tag", "album=Live", "tag"])
+        .arg(&song)
+        .output()
+        .unwrap();
+    assert!(!result.status.success());
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    assert!(stderr.contains("--wait-for-lock"), "{stderr}");
+    assert_eq!(fs::read_to_string(&song).unwrap(), "audio");
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_batch_holds_sleep_inhibitor_while_converting() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let videos = temp_dir.path().join("videos");
+    fs::create_dir(&videos).unwrap();
+    fs::write(videos.join("lecture.mp4"), "video").unwrap();
+
+    // 假 systemd-inhibit 记录进程号和参数后一直运行；假 ffmpeg 转换时检查它是否仍在运行
+    let bin = common::fake_ffmpeg_bin_with(
+        temp_dir.path(),
+        "echo 600\n",
+        &format!(
+            "kill -0 \"$(cut -d ' ' -f 1 \"$HOME/inhibit\")\" && touch \"$HOME/inhibited\"\n{}",
+            common::FAKE_FFMPEG_WRITE_ARGS
+        ),
+    );
+    fs::write(bin.join("systemd-inhibit"), "#!/bin/sh\necho \"$$ $*\" > \"$HOME/inhibit\"\nexec sleep 30\n").unwrap();
+    fs::set_permissions(bin.join("systemd-inhibit"), fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", format!("{}:/usr/bin:/bin", bin.display()))
+        .env("HOME", temp_dir.path())
+        .args(["--lang", "en", "--batch", "--inhibit-sleep", "--format", "mp3", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let inhibit = fs::read_to_string(temp_dir.path().join("inhibit")).unwrap();
+    let (pid, args) = inhibit.trim().split_once(' ').unwrap();
+    assert!(args.contains("--what=sleep:idle"), "{args}");
+    assert!(args.contains("--why=Converting videos"), "{args}");
+    assert!(temp_dir.path().join("inhibited").exists());
+    // 批处理结束后释放抑制锁
+    let alive = std::process::Command::new("kill")
+        .args(["-0", pid])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(!alive.success());
+
+    // 拿不到抑制锁（工具立即退出）时只提示，照常转换
+    fs::write(bin.join("systemd-inhibit"), "#!/bin/sh
+exit 1
+").unwrap();
+    let output = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("PATH", format!("{}:/usr/bin:/bin", bin.display()))
+        .env("HOME", temp_dir.path())
+        .args(["--lang", "en", "--batch", "--inhibit-sleep", "--format", "mp3", "--source"])
+        .arg(&videos)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("systemd-inhibit"));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn test_clean_moves_files_to_trash() {
+    let temp_dir = TempDir::new().unwrap();
+    let source = temp_dir.path().join("videos");
+    let output = temp_dir.path().join("audio");
+    fs::create_dir_all(&source).unwrap();
+    fs::create_dir_all(&output).unwrap();
+    fs::write(output.join("removed.mp3"), "audio").unwrap();
+
+    // 回收站位于 XDG 数据目录中，与输出目录在同一文件系统上
+    let data_dir = temp_dir.path().join("data");
+    let result = assert_cmd::Command::cargo_bin("video2audio-rs")
+        .unwrap()
+        .env("HOME", temp_dir.path())
+        .env("XDG_DATA_HOME", &data_dir)
+        .args(["--lang", "en", "--trash", "clean", "--format", "mp3", "--source"])
+        .arg(&source)
+        .arg("--output")
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(result.status.success(), "{}", String::from_utf8_lossy(&result.stderr));
+
+    assert!(!output.join("removed.mp3").exists());
+    assert_eq!(fs::read_to_string(data_dir.join("Trash/files/removed.mp3")).unwrap(), "audio");
+    assert!(data_dir.join("Trash/info/removed.mp3.trashinfo").exists());
+}